@@ -0,0 +1,26 @@
+-- ============================================================================
+-- iMi Project Registry - Background Fetch State
+-- Version: 2.1.0
+-- Purpose: Track the daemon's periodic `git fetch` results per project
+-- ============================================================================
+
+-- One row per project, upserted after every fetch attempt
+CREATE TABLE IF NOT EXISTS project_fetch_state (
+    project_id UUID PRIMARY KEY REFERENCES projects(id) ON DELETE CASCADE,
+
+    -- Last attempt (successful or not)
+    last_fetch_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_duration_ms BIGINT NOT NULL DEFAULT 0,
+    last_error TEXT,
+
+    -- Last attempt that completed without error
+    last_success_at TIMESTAMPTZ,
+
+    -- Consecutive failures since the last success
+    failure_count INTEGER NOT NULL DEFAULT 0,
+
+    CONSTRAINT project_fetch_state_failure_count_check CHECK (failure_count >= 0)
+);
+
+CREATE INDEX IF NOT EXISTS idx_project_fetch_state_last_fetch
+    ON project_fetch_state (last_fetch_at DESC);
@@ -0,0 +1,17 @@
+-- ============================================================================
+-- iMi Project Registry - Per-Type Defaults
+-- Version: 2.1.0
+-- Purpose: Let each worktree type carry its own creation defaults
+-- ============================================================================
+
+ALTER TABLE worktree_types
+    -- Days a worktree of this type may live before it is considered stale
+    ADD COLUMN IF NOT EXISTS default_ttl_days INTEGER,
+    -- Branch new worktrees are based on (falls back to git_settings.default_branch)
+    ADD COLUMN IF NOT EXISTS default_base_branch TEXT;
+
+ALTER TABLE worktree_types
+    DROP CONSTRAINT IF EXISTS worktree_types_default_ttl_days_check;
+ALTER TABLE worktree_types
+    ADD CONSTRAINT worktree_types_default_ttl_days_check
+    CHECK (default_ttl_days IS NULL OR default_ttl_days > 0);
@@ -0,0 +1,21 @@
+-- ============================================================================
+-- iMi Project Registry - Navigation History
+-- Version: 2.2.0
+-- Purpose: Record `imi go` selections so fuzzy search can rank by frecency
+-- ============================================================================
+
+-- One row per navigated worktree or project, upserted on every selection.
+-- target_id is either worktrees.id or projects.id, so there is no foreign key;
+-- rows for deleted targets are simply never matched again.
+CREATE TABLE IF NOT EXISTS navigation_history (
+    target_id UUID PRIMARY KEY,
+    target_kind TEXT NOT NULL,
+    visit_count INTEGER NOT NULL DEFAULT 1,
+    last_visited_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT navigation_history_kind_check CHECK (target_kind IN ('worktree', 'project')),
+    CONSTRAINT navigation_history_visit_count_check CHECK (visit_count > 0)
+);
+
+CREATE INDEX IF NOT EXISTS idx_navigation_history_last_visited
+    ON navigation_history (last_visited_at DESC);
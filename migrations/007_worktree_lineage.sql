@@ -0,0 +1,13 @@
+-- ============================================================================
+-- iMi Project Registry - Worktree Lineage
+-- Version: 2.3.0
+-- Purpose: Remember which worktree a fork (`imi copy`) was branched from
+-- ============================================================================
+
+ALTER TABLE worktrees
+    ADD COLUMN IF NOT EXISTS parent_worktree_id UUID REFERENCES worktrees(id) ON DELETE SET NULL,
+    ADD COLUMN IF NOT EXISTS forked_from_commit TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_worktrees_parent
+    ON worktrees (parent_worktree_id)
+    WHERE parent_worktree_id IS NOT NULL;
@@ -0,0 +1,16 @@
+-- ============================================================================
+-- iMi Project Registry - Worktree Pull Requests
+-- Version: 2.4.0
+-- Purpose: Remember the GitHub PR opened from a worktree's branch so list and
+--          status can show it and `imi clean` can drop worktrees once merged
+-- ============================================================================
+
+ALTER TABLE worktrees
+    ADD COLUMN IF NOT EXISTS pr_number INTEGER,
+    ADD COLUMN IF NOT EXISTS pr_url TEXT,
+    ADD COLUMN IF NOT EXISTS pr_state TEXT
+        CHECK (pr_state IS NULL OR pr_state IN ('open', 'closed', 'merged'));
+
+CREATE INDEX IF NOT EXISTS idx_worktrees_pr_merged
+    ON worktrees (project_id)
+    WHERE pr_state = 'merged' AND active = TRUE;
@@ -0,0 +1,14 @@
+-- ============================================================================
+-- iMi Project Registry - Worktree Host
+-- Version: 2.5.0
+-- Purpose: Record which machine a worktree lives on. When several machines
+--          share one registry, worktree paths only mean something on the
+--          machine that created them, so path checks skip other hosts' rows.
+-- ============================================================================
+
+ALTER TABLE worktrees
+    ADD COLUMN IF NOT EXISTS host TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_worktrees_host
+    ON worktrees (host)
+    WHERE active = TRUE;
@@ -0,0 +1,10 @@
+-- ============================================================================
+-- iMi Project Registry - Commit Message Templates
+-- Version: 2.6.0
+-- Purpose: Per-type commit message template, installed as the worktree-local
+--          commit.template of every new worktree of that type
+-- ============================================================================
+
+ALTER TABLE worktree_types
+    -- Template text; {{ticket}}, {{branch_name}} etc. are filled in per worktree
+    ADD COLUMN IF NOT EXISTS commit_template TEXT;
@@ -0,0 +1,11 @@
+-- ============================================================================
+-- iMi Project Registry - Worktree Base
+-- Version: 2.7.0
+-- Purpose: Remember the ref a worktree's branch was created from (a branch,
+--          tag or commit given with `imi add --base`) and the commit it
+--          resolved to, so ahead/behind can be measured against it
+-- ============================================================================
+
+ALTER TABLE worktrees
+    ADD COLUMN IF NOT EXISTS base_ref TEXT,
+    ADD COLUMN IF NOT EXISTS base_commit TEXT;
@@ -0,0 +1,10 @@
+-- ============================================================================
+-- iMi Project Registry - Clone Mode
+-- Version: 2.8.0
+-- Purpose: Remember how iMi cloned a project (e.g. `depth=1,single-branch`
+--          or `filter=blob:none`) so `imi unshallow` knows what to undo;
+--          NULL for full clones and repositories registered in place
+-- ============================================================================
+
+ALTER TABLE projects
+    ADD COLUMN IF NOT EXISTS clone_mode TEXT;
@@ -0,0 +1,17 @@
+-- ============================================================================
+-- iMi Project Registry - Repository Tags
+-- Version: 2.9.0
+-- Purpose: Free-form labels on registered repositories (`imi repo tag`) so
+--          list and exec can work on a group of them (`--tag rust`)
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS repo_tags (
+    project_id UUID NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    tag TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (project_id, tag),
+    CONSTRAINT repo_tags_tag_check CHECK (tag <> '')
+);
+
+CREATE INDEX IF NOT EXISTS idx_repo_tags_tag ON repo_tags (tag);
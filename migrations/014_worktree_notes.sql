@@ -0,0 +1,20 @@
+-- ============================================================================
+-- iMi Project Registry - Worktree Notes
+-- Version: 2.10.0
+-- Purpose: Free-form, timestamped notes on a worktree (`imi note`) so agents
+--          and humans can leave handoff context for each other
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS worktree_notes (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    worktree_id UUID NOT NULL REFERENCES worktrees(id) ON DELETE CASCADE,
+    body TEXT NOT NULL,
+    author TEXT,
+    agent_id TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT worktree_notes_body_check CHECK (body <> '')
+);
+
+CREATE INDEX IF NOT EXISTS idx_worktree_notes_worktree
+    ON worktree_notes (worktree_id, created_at DESC);
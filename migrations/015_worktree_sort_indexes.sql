@@ -0,0 +1,12 @@
+-- ============================================================================
+-- iMi Project Registry - Worktree Sort Indexes
+-- Version: 2.11.0
+-- Purpose: Let `imi list --sort age|name` order large fleets of active
+--          worktrees from an index instead of sorting every row
+-- ============================================================================
+
+CREATE INDEX IF NOT EXISTS idx_worktrees_active_created
+    ON worktrees (project_id, created_at) WHERE active = TRUE;
+
+CREATE INDEX IF NOT EXISTS idx_worktrees_active_name
+    ON worktrees (project_id, name) WHERE active = TRUE;
@@ -0,0 +1,28 @@
+-- ============================================================================
+-- iMi Project Registry - Worktree Artifacts
+-- Version: 2.12.0
+-- Purpose: Keep the command, exit code and output location of `imi exec` and
+--          `imi check` runs per worktree (`imi artifacts`) so the last test
+--          run can be read back without rerunning it
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS worktree_artifacts (
+    id UUID PRIMARY KEY,
+    worktree_id UUID NOT NULL REFERENCES worktrees(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL,
+    command TEXT NOT NULL,
+    exit_code INTEGER,
+    success BOOLEAN NOT NULL,
+    duration_ms BIGINT NOT NULL,
+    commit_sha TEXT,
+    output_dir TEXT NOT NULL,
+    stdout_bytes BIGINT NOT NULL DEFAULT 0,
+    stderr_bytes BIGINT NOT NULL DEFAULT 0,
+    agent_id TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT worktree_artifacts_kind_check CHECK (kind IN ('exec', 'check'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_worktree_artifacts_worktree
+    ON worktree_artifacts (worktree_id, created_at DESC);
@@ -0,0 +1,10 @@
+-- ============================================================================
+-- iMi Project Registry - Archived Repositories
+-- Version: 2.13.0
+-- Purpose: Mark repositories archived (`imi repo archive`) so list, status
+--          and go leave them out unless asked with --all; unlike `active`
+--          an archived repository stays registered and reachable by name
+-- ============================================================================
+
+ALTER TABLE projects
+    ADD COLUMN IF NOT EXISTS archived_at TIMESTAMPTZ;
@@ -0,0 +1,16 @@
+-- ============================================================================
+-- iMi Project Registry - Worktree Status Cache
+-- Version: 2.14.0
+-- Purpose: Keep each worktree's last computed git status together with the
+--          HEAD, index mtime and upstream it was computed from, so
+--          `imi status` and `imi list` skip walking unchanged worktrees
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS worktree_status_cache (
+    worktree_id UUID PRIMARY KEY REFERENCES worktrees(id) ON DELETE CASCADE,
+    head_sha TEXT,
+    index_mtime_ns BIGINT,
+    upstream_sha TEXT,
+    status JSONB NOT NULL,
+    computed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
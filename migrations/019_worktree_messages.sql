@@ -0,0 +1,26 @@
+-- ============================================================================
+-- iMi Project Registry - Worktree Messages
+-- Version: 2.15.0
+-- Purpose: A message queue per worktree (`imi msg`) so agents and humans can
+--          ask each other for things ("please rebase") without external
+--          infrastructure; unread messages are counted in `imi status`
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS worktree_messages (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    worktree_id UUID NOT NULL REFERENCES worktrees(id) ON DELETE CASCADE,
+    body TEXT NOT NULL,
+    sender TEXT,
+    agent_id TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    read_at TIMESTAMPTZ,
+
+    CONSTRAINT worktree_messages_body_check CHECK (body <> '')
+);
+
+CREATE INDEX IF NOT EXISTS idx_worktree_messages_worktree
+    ON worktree_messages (worktree_id, created_at DESC);
+
+CREATE INDEX IF NOT EXISTS idx_worktree_messages_unread
+    ON worktree_messages (worktree_id)
+    WHERE read_at IS NULL;
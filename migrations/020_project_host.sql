@@ -0,0 +1,74 @@
+-- ============================================================================
+-- iMi Project Registry - Project Host
+-- Version: 2.16.0
+-- Purpose: Record which machine a repository registration belongs to. When
+--          a synced home directory shares one registry between machines with
+--          different paths, each machine keeps its own row per repository
+--          and only sees its own (plus legacy rows without a host), so one
+--          machine's path repairs can't clobber another's.
+-- ============================================================================
+
+ALTER TABLE projects
+    ADD COLUMN IF NOT EXISTS host TEXT;
+
+-- One registration per remote per host instead of one per remote
+ALTER TABLE projects DROP CONSTRAINT IF EXISTS projects_unique_remote_origin;
+ALTER TABLE projects DROP CONSTRAINT IF EXISTS projects_unique_active_remote;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_projects_unique_remote_host
+    ON projects (remote_origin, (COALESCE(host, '')));
+
+-- Hosts register remotes on any forge, not just github.com
+ALTER TABLE projects DROP CONSTRAINT IF EXISTS projects_remote_origin_check;
+ALTER TABLE projects
+    ADD CONSTRAINT projects_remote_origin_check
+    CHECK (remote_origin ~ '^git@[^:/]+:[^/].*\.git$');
+
+CREATE INDEX IF NOT EXISTS idx_projects_host
+    ON projects (host)
+    WHERE active = TRUE;
+
+-- register_project gains the registering host
+DROP FUNCTION IF EXISTS register_project(TEXT, TEXT, TEXT, TEXT, JSONB);
+
+CREATE OR REPLACE FUNCTION register_project(
+    p_name TEXT,
+    p_remote_origin TEXT,
+    p_default_branch TEXT DEFAULT 'main',
+    p_trunk_path TEXT DEFAULT NULL,
+    p_metadata JSONB DEFAULT '{}'::jsonb,
+    p_host TEXT DEFAULT NULL
+) RETURNS UUID AS $$
+DECLARE
+    v_project_id UUID;
+    v_trunk_path TEXT;
+BEGIN
+    -- Validate remote origin format: iMi registers remotes on any host as
+    -- git@host:namespace/name.git
+    IF p_remote_origin !~ '^git@[^:/]+:[^/].*\.git$' THEN
+        RAISE EXCEPTION 'Invalid remote origin format: %', p_remote_origin;
+    END IF;
+
+    -- Generate trunk path if not provided
+    v_trunk_path := COALESCE(
+        p_trunk_path,
+        format('%s/code/%s/trunk-%s',
+            (SELECT COALESCE(NULLIF(current_setting('imi.home_dir', TRUE), ''), '/home/' || current_user)),
+            p_name,
+            p_default_branch
+        )
+    );
+
+    -- Insert or return this host's existing project
+    INSERT INTO projects (name, remote_origin, default_branch, trunk_path, metadata, host)
+    VALUES (p_name, p_remote_origin, p_default_branch, v_trunk_path, p_metadata, p_host)
+    ON CONFLICT (remote_origin, (COALESCE(host, ''))) DO UPDATE
+        SET updated_at = NOW(),
+            active = TRUE
+    RETURNING id INTO v_project_id;
+
+    RETURN v_project_id;
+END;
+$$ LANGUAGE plpgsql;
+
+COMMENT ON FUNCTION register_project IS 'Register a project for a host with automatic path generation and duplicate prevention';
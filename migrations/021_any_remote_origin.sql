@@ -0,0 +1,59 @@
+-- ============================================================================
+-- iMi Project Registry - Any Remote Origin
+-- Version: 2.17.0
+-- Purpose: Accept remotes on any host. iMi registers every remote in its
+--          normalized `git@host:namespace/name.git` form (GitLab subgroups
+--          included), so the GitHub-only check on projects.remote_origin
+--          rejected https and non-GitHub remotes after they were cloned.
+--          020 now relaxes the check too; this brings databases that ran
+--          the earlier 020 up to date.
+-- ============================================================================
+
+ALTER TABLE projects DROP CONSTRAINT IF EXISTS projects_remote_origin_check;
+ALTER TABLE projects
+    ADD CONSTRAINT projects_remote_origin_check
+    CHECK (remote_origin ~ '^git@[^:/]+:[^/].*\.git$');
+
+COMMENT ON COLUMN projects.remote_origin IS 'Normalized remote origin (git@host:namespace/name.git) - unique per host';
+
+-- register_project validates against the same pattern
+CREATE OR REPLACE FUNCTION register_project(
+    p_name TEXT,
+    p_remote_origin TEXT,
+    p_default_branch TEXT DEFAULT 'main',
+    p_trunk_path TEXT DEFAULT NULL,
+    p_metadata JSONB DEFAULT '{}'::jsonb,
+    p_host TEXT DEFAULT NULL
+) RETURNS UUID AS $$
+DECLARE
+    v_project_id UUID;
+    v_trunk_path TEXT;
+BEGIN
+    -- Validate remote origin format
+    IF p_remote_origin !~ '^git@[^:/]+:[^/].*\.git$' THEN
+        RAISE EXCEPTION 'Invalid remote origin format: %', p_remote_origin;
+    END IF;
+
+    -- Generate trunk path if not provided
+    v_trunk_path := COALESCE(
+        p_trunk_path,
+        format('%s/code/%s/trunk-%s',
+            (SELECT COALESCE(NULLIF(current_setting('imi.home_dir', TRUE), ''), '/home/' || current_user)),
+            p_name,
+            p_default_branch
+        )
+    );
+
+    -- Insert or return this host's existing project
+    INSERT INTO projects (name, remote_origin, default_branch, trunk_path, metadata, host)
+    VALUES (p_name, p_remote_origin, p_default_branch, v_trunk_path, p_metadata, p_host)
+    ON CONFLICT (remote_origin, (COALESCE(host, ''))) DO UPDATE
+        SET updated_at = NOW(),
+            active = TRUE
+    RETURNING id INTO v_project_id;
+
+    RETURN v_project_id;
+END;
+$$ LANGUAGE plpgsql;
+
+COMMENT ON FUNCTION register_project IS 'Register a project for a host with automatic path generation and duplicate prevention';
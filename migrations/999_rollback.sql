@@ -26,6 +26,7 @@ DROP FUNCTION IF EXISTS update_worktree_git_state(UUID, BOOLEAN, INTEGER, INTEGE
 DROP FUNCTION IF EXISTS register_worktree(UUID, TEXT, TEXT, TEXT, TEXT, TEXT, JSONB);
 DROP FUNCTION IF EXISTS deactivate_project(UUID);
 DROP FUNCTION IF EXISTS get_project_by_origin(TEXT);
+DROP FUNCTION IF EXISTS register_project(TEXT, TEXT, TEXT, TEXT, JSONB, TEXT);
 DROP FUNCTION IF EXISTS register_project(TEXT, TEXT, TEXT, TEXT, JSONB);
 
 -- Drop triggers
@@ -38,6 +39,13 @@ DROP FUNCTION IF EXISTS sync_uncommitted_changes();
 DROP FUNCTION IF EXISTS update_updated_at_column();
 
 -- Drop tables (in reverse dependency order)
+DROP TABLE IF EXISTS worktree_messages;
+DROP TABLE IF EXISTS worktree_status_cache;
+DROP TABLE IF EXISTS worktree_artifacts;
+DROP TABLE IF EXISTS worktree_notes;
+DROP TABLE IF EXISTS repo_tags;
+DROP TABLE IF EXISTS navigation_history;
+DROP TABLE IF EXISTS project_fetch_state;
 DROP TABLE IF EXISTS agent_activities;
 DROP TABLE IF EXISTS worktrees;
 DROP TABLE IF EXISTS worktree_types;
@@ -0,0 +1,225 @@
+//! Agent instruction files (`AGENTS.md`, `CLAUDE.md`, ...) for a repository,
+//! rendered from templates into its `sync/user` directory and linked into
+//! every worktree, so each agent starts with the repository's conventions,
+//! its worktree types and how to drive iMi.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::database::WorktreeType;
+use crate::worktree::render_copy_template;
+
+/// Used for files the template directory has no template for
+pub const DEFAULT_TEMPLATE: &str = "\
+# {{repo_name}}
+
+Instructions for coding agents working in this repository, generated by
+`imi agent scaffold` on {{date}}. Edit them freely: they are only regenerated
+with `--force`.
+
+## Conventions
+
+- Language: {{language}}
+- Default branch: `{{default_branch}}`; never commit to it directly
+- Finished work is merged with `imi merge` ({{merge_strategy}})
+
+## Worktrees
+
+Every task gets its own worktree, created with iMi. Its type decides the
+directory and branch names:
+
+{{worktree_types}}
+
+## Using iMi
+
+{{imi_usage}}
+";
+
+/// How agents drive iMi, for `{{imi_usage}}`
+pub const IMI_USAGE: &str = "\
+- `imi add <type> <name>` creates a worktree and prints its path
+- `imi which <name>` prints a worktree's path; `imi list` lists them all
+- `imi claim <name>` before starting, `imi release <name>` when done
+- `imi status` shows every worktree's branch, changes and pull request
+- `imi note <name> \"...\"` leaves a note for whoever picks the work up next
+- `imi check <name> -- <command>` runs tests and keeps their output
+- `imi merge <name>` merges into the default branch and closes the worktree
+- Add `--json` to any command for machine-readable output";
+
+/// What happened to one generated file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileOutcome {
+    Created,
+    Updated,
+    /// Already there and `--force` wasn't given
+    Kept,
+}
+
+/// The repository facts templates can use
+pub struct RepoFacts<'a> {
+    pub repo_name: &'a str,
+    pub language: &'a str,
+    pub default_branch: &'a str,
+    pub merge_strategy: &'a str,
+    pub types: &'a [WorktreeType],
+}
+
+/// Markdown table of the worktree types, trunk left out
+pub fn type_table(types: &[WorktreeType]) -> String {
+    let mut table = String::from(
+        "| Type | Command | Directory | Branch | Description |\n|---|---|---|---|---|\n",
+    );
+    for t in types.iter().filter(|t| t.name != "trunk") {
+        table.push_str(&format!(
+            "| {} | `imi add {} <name>` | `{}<name>` | `{}<name>` | {} |\n",
+            t.name,
+            t.name,
+            t.worktree_prefix,
+            t.branch_prefix,
+            t.description.as_deref().unwrap_or("")
+        ));
+    }
+    table.trim_end().to_string()
+}
+
+/// The template for `file`: `<dir>/<repo>/<file>`, then `<dir>/<file>`,
+/// then the built-in one
+pub fn template_for(template_dir: &Path, repo_name: &str, file: &str) -> Result<String> {
+    for path in [
+        template_dir.join(repo_name).join(file),
+        template_dir.join(file),
+    ] {
+        if path.is_file() {
+            return std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()));
+        }
+    }
+    Ok(DEFAULT_TEMPLATE.to_string())
+}
+
+/// Fill in `{{repo_name}}`, `{{language}}`, `{{default_branch}}`,
+/// `{{merge_strategy}}`, `{{worktree_types}}`, `{{imi_usage}}`,
+/// `{{file_name}}` and `{{date}}`
+pub fn render(template: &str, file: &str, facts: &RepoFacts) -> String {
+    let types = type_table(facts.types);
+    let date = chrono::Local::now().format("%Y-%m-%d").to_string();
+    let vars = [
+        ("repo_name", facts.repo_name),
+        ("language", facts.language),
+        ("default_branch", facts.default_branch),
+        ("merge_strategy", facts.merge_strategy),
+        ("worktree_types", types.as_str()),
+        ("imi_usage", IMI_USAGE),
+        ("file_name", file),
+        ("date", date.as_str()),
+    ];
+    render_copy_template(template, &vars)
+}
+
+/// Write `contents` to `dir/file` unless it exists and `force` isn't set
+pub fn write(
+    dir: &Path,
+    file: &str,
+    contents: &str,
+    force: bool,
+) -> Result<(PathBuf, FileOutcome)> {
+    let path = dir.join(file);
+    let outcome = match (path.exists(), force) {
+        (true, false) => return Ok((path, FileOutcome::Kept)),
+        (true, true) => FileOutcome::Updated,
+        (false, _) => FileOutcome::Created,
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, contents)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok((path, outcome))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn worktree_type(name: &str, worktree_prefix: &str, branch_prefix: &str) -> WorktreeType {
+        WorktreeType {
+            id: 1,
+            name: name.to_string(),
+            branch_prefix: branch_prefix.to_string(),
+            worktree_prefix: worktree_prefix.to_string(),
+            description: Some("New features".to_string()),
+            is_builtin: true,
+            color: None,
+            icon: None,
+            metadata: serde_json::json!({}),
+            created_at: chrono::Utc::now(),
+            default_ttl_days: None,
+            default_base_branch: None,
+            commit_template: None,
+        }
+    }
+
+    #[test]
+    fn renders_templates_with_repo_overrides() {
+        let dir = tempfile::tempdir().unwrap();
+        let types = [
+            worktree_type("feat", "feat-", "feat/"),
+            worktree_type("trunk", "trunk-", ""),
+        ];
+        let facts = RepoFacts {
+            repo_name: "api",
+            language: "rust",
+            default_branch: "main",
+            merge_strategy: "pr",
+            types: &types,
+        };
+
+        let builtin = render(
+            &template_for(dir.path(), "api", "AGENTS.md").unwrap(),
+            "AGENTS.md",
+            &facts,
+        );
+        assert!(builtin.starts_with("# api\n"));
+        assert!(
+            builtin.contains("| feat | `imi add feat <name>` | `feat-<name>` | `feat/<name>` |")
+        );
+        assert!(!builtin.contains("| trunk |"));
+        assert!(builtin.contains("`imi merge <name>`"));
+        assert!(!builtin.contains("{{"));
+
+        std::fs::write(
+            dir.path().join("CLAUDE.md"),
+            "See @AGENTS.md ({{file_name}})",
+        )
+        .unwrap();
+        std::fs::create_dir_all(dir.path().join("api")).unwrap();
+        std::fs::write(
+            dir.path().join("api/CLAUDE.md"),
+            "{{repo_name}} on {{default_branch}}",
+        )
+        .unwrap();
+        let template = template_for(dir.path(), "web", "CLAUDE.md").unwrap();
+        assert_eq!(
+            render(&template, "CLAUDE.md", &facts),
+            "See @AGENTS.md (CLAUDE.md)"
+        );
+        let template = template_for(dir.path(), "api", "CLAUDE.md").unwrap();
+        assert_eq!(render(&template, "CLAUDE.md", &facts), "api on main");
+
+        let sync = dir.path().join("sync/user");
+        let (path, outcome) = write(&sync, "AGENTS.md", "one", false).unwrap();
+        assert_eq!(outcome, FileOutcome::Created);
+        assert_eq!(
+            write(&sync, "AGENTS.md", "two", false).unwrap().1,
+            FileOutcome::Kept
+        );
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "one");
+        assert_eq!(
+            write(&sync, "AGENTS.md", "two", true).unwrap().1,
+            FileOutcome::Updated
+        );
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "two");
+    }
+}
@@ -0,0 +1,284 @@
+use chrono::Utc;
+use colored::*;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::Path;
+use std::process::Command;
+
+use crate::config::AlertSettings;
+use crate::database::Worktree;
+use crate::disk_usage::directory_size;
+use crate::git::GitManager;
+
+/// Anomalous worktree states that raise an alert
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    DirtyTooLong,
+    BehindTrunk,
+    DetachedHead,
+    MergeConflicts,
+    DiskUsage,
+}
+
+impl AlertKind {
+    /// Stable identifier, matching the JSON representation
+    pub fn code(&self) -> &'static str {
+        match self {
+            AlertKind::DirtyTooLong => "dirty_too_long",
+            AlertKind::BehindTrunk => "behind_trunk",
+            AlertKind::DetachedHead => "detached_head",
+            AlertKind::MergeConflicts => "merge_conflicts",
+            AlertKind::DiskUsage => "disk_usage",
+        }
+    }
+}
+
+impl std::fmt::Display for AlertKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let label = match self {
+            AlertKind::DirtyTooLong => "dirty",
+            AlertKind::BehindTrunk => "behind trunk",
+            AlertKind::DetachedHead => "detached HEAD",
+            AlertKind::MergeConflicts => "merge conflicts",
+            AlertKind::DiskUsage => "disk usage",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Alert {
+    pub repo_name: String,
+    pub worktree_name: String,
+    pub kind: AlertKind,
+    pub message: String,
+}
+
+impl Alert {
+    pub fn print(&self) {
+        println!(
+            "   {} {} {}",
+            "🚨".bright_red(),
+            format!("[{}]", self.kind).bright_red(),
+            self.message
+        );
+    }
+
+    /// Identity of the condition, so a persisting alert is only notified once
+    fn key(&self) -> String {
+        format!(
+            "{}/{}/{}",
+            self.repo_name,
+            self.worktree_name,
+            self.kind.code()
+        )
+    }
+}
+
+/// Applies the configured alert rules to worktrees
+pub struct AlertEvaluator<'a> {
+    git: &'a GitManager,
+    settings: &'a AlertSettings,
+    trunk_branch: &'a str,
+}
+
+impl<'a> AlertEvaluator<'a> {
+    pub fn new(git: &'a GitManager, settings: &'a AlertSettings, trunk_branch: &'a str) -> Self {
+        Self {
+            git,
+            settings,
+            trunk_branch,
+        }
+    }
+
+    /// Alerts for one worktree; rules whose git data can't be read are skipped
+    pub fn evaluate(&self, worktree: &Worktree) -> Vec<Alert> {
+        let path = Path::new(&worktree.path);
+        if !self.settings.enabled || !path.exists() {
+            return Vec::new();
+        }
+
+        let mut alerts = Vec::new();
+        let mut raise = |kind, message: String| {
+            alerts.push(Alert {
+                repo_name: worktree.repo_name.clone(),
+                worktree_name: worktree.worktree_name.clone(),
+                kind,
+                message,
+            })
+        };
+
+        if let Some(hours) = self.settings.dirty_after_hours {
+            if let Ok(Some(since)) = self.git.get_oldest_change_time(path) {
+                let dirty_hours = Utc::now().signed_duration_since(since).num_hours();
+                if dirty_hours >= hours as i64 {
+                    raise(
+                        AlertKind::DirtyTooLong,
+                        format!("Uncommitted changes for {}h", dirty_hours),
+                    );
+                }
+            }
+        }
+
+        if let Some(limit) = self.settings.behind_trunk_commits {
+            if let Ok(behind) = self.git.get_behind_trunk(path, self.trunk_branch) {
+                if behind > limit {
+                    raise(
+                        AlertKind::BehindTrunk,
+                        format!("{} commits behind {}", behind, self.trunk_branch),
+                    );
+                }
+            }
+        }
+
+        if self.settings.detached_head && self.git.is_head_detached(path).unwrap_or(false) {
+            raise(
+                AlertKind::DetachedHead,
+                "HEAD is detached from any branch".to_string(),
+            );
+        }
+
+        if self.settings.merge_conflicts && self.git.has_merge_conflicts(path).unwrap_or(false) {
+            raise(
+                AlertKind::MergeConflicts,
+                "Unresolved merge conflicts".to_string(),
+            );
+        }
+
+        if let Some(limit_mb) = self.settings.disk_usage_mb {
+            let used_mb = directory_size(path) / (1024 * 1024);
+            if used_mb > limit_mb {
+                raise(
+                    AlertKind::DiskUsage,
+                    format!("Using {} MB (limit {} MB)", used_mb, limit_mb),
+                );
+            }
+        }
+
+        alerts
+    }
+}
+
+/// Remembers which alerts were already delivered so long-running loops
+/// (monitor, daemon) notify once per condition and again after it clears
+#[derive(Debug, Default)]
+pub struct AlertTracker {
+    active: HashSet<String>,
+}
+
+impl AlertTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Alerts not present in the previous round
+    pub fn update<'a>(&mut self, alerts: &'a [Alert]) -> Vec<&'a Alert> {
+        let current: HashSet<String> = alerts.iter().map(Alert::key).collect();
+        let fresh = alerts
+            .iter()
+            .filter(|alert| !self.active.contains(&alert.key()))
+            .collect();
+        self.active = current;
+        fresh
+    }
+}
+
+/// Deliver an alert through the configured notification channels
+pub fn notify(settings: &AlertSettings, alert: &Alert) {
+    deliver(
+        settings,
+        &format!("iMi: {} ({})", alert.kind, alert.worktree_name),
+        alert.kind.code(),
+        &alert.repo_name,
+        &alert.worktree_name,
+        &alert.message,
+    );
+}
+
+/// Deliver a worktree message (`imi msg send --notify`) through the alert
+/// channels; `notify_command` sees IMI_ALERT_KIND=message
+pub fn notify_message(settings: &AlertSettings, worktree: &Worktree, message: &str) {
+    deliver(
+        settings,
+        &format!("iMi: message for {}", worktree.name),
+        "message",
+        &worktree.repo_name,
+        &worktree.name,
+        message,
+    );
+}
+
+fn deliver(
+    settings: &AlertSettings,
+    title: &str,
+    kind: &str,
+    repo_name: &str,
+    worktree_name: &str,
+    message: &str,
+) {
+    if settings.desktop_notifications {
+        let _ = if cfg!(target_os = "macos") {
+            Command::new("osascript")
+                .args([
+                    "-e",
+                    &format!("display notification {:?} with title {:?}", message, title),
+                ])
+                .output()
+        } else {
+            Command::new("notify-send").args([title, message]).output()
+        };
+    }
+
+    if let Some(command) = &settings.notify_command {
+        let result = Command::new("sh")
+            .args(["-c", command])
+            .env("IMI_ALERT_KIND", kind)
+            .env("IMI_ALERT_REPO", repo_name)
+            .env("IMI_ALERT_WORKTREE", worktree_name)
+            .env("IMI_ALERT_MESSAGE", message)
+            .output();
+        if let Err(e) = result {
+            eprintln!(
+                "{} Alert notify_command failed: {}",
+                "⚠️".bright_yellow(),
+                e
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alert(worktree: &str, kind: AlertKind) -> Alert {
+        Alert {
+            repo_name: "iMi".to_string(),
+            worktree_name: worktree.to_string(),
+            kind,
+            message: String::new(),
+        }
+    }
+
+    #[test]
+    fn tracker_reports_each_condition_once_until_it_clears() {
+        let mut tracker = AlertTracker::new();
+        let round = vec![alert("feat-a", AlertKind::DetachedHead)];
+
+        assert_eq!(tracker.update(&round).len(), 1);
+        assert!(tracker.update(&round).is_empty());
+
+        // Condition clears, then comes back: notify again
+        assert!(tracker.update(&[]).is_empty());
+        assert_eq!(tracker.update(&round).len(), 1);
+
+        let more = vec![
+            alert("feat-a", AlertKind::DetachedHead),
+            alert("feat-a", AlertKind::MergeConflicts),
+        ];
+        let fresh = tracker.update(&more);
+        assert_eq!(fresh.len(), 1);
+        assert_eq!(fresh[0].kind, AlertKind::MergeConflicts);
+    }
+}
@@ -0,0 +1,260 @@
+//! Output kept from `imi exec` and `imi check` runs: stdout and stderr are
+//! written under `.iMi/artifacts/<worktree>/<id>/` of the project, while the
+//! command, exit code and timing go to `worktree_artifacts`, so the last test
+//! run of a worktree can be read back with `imi artifacts` instead of rerun.
+
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Instant;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use uuid::Uuid;
+
+use crate::local::LocalContext;
+
+const STDOUT_FILE: &str = "stdout";
+const STDERR_FILE: &str = "stderr";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArtifactKind {
+    Exec,
+    Check,
+}
+
+impl ArtifactKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ArtifactKind::Exec => "exec",
+            ArtifactKind::Check => "check",
+        }
+    }
+}
+
+/// Which captured stream to read back
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+impl Stream {
+    fn file_name(&self) -> &'static str {
+        match self {
+            Stream::Stdout => STDOUT_FILE,
+            Stream::Stderr => STDERR_FILE,
+        }
+    }
+}
+
+/// A finished command with everything it wrote
+#[derive(Debug, Clone)]
+pub struct CapturedRun {
+    /// None when killed by a signal or when the command couldn't start
+    pub exit_code: Option<i32>,
+    pub success: bool,
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+    pub duration_ms: i64,
+}
+
+/// Run `command` in `dir` and capture its output; with `echo` the output is
+/// also passed through to the terminal as it arrives. A command that can't
+/// be started is a failed run with the error as its stderr, so it is kept
+/// like any other.
+pub async fn run_captured(command: &[String], dir: &Path, echo: bool) -> Result<CapturedRun> {
+    let (program, args) = command
+        .split_first()
+        .context("No command given; pass it after --")?;
+    let started = Instant::now();
+    let spawned = tokio::process::Command::new(program)
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn();
+    let mut child = match spawned {
+        Ok(child) => child,
+        Err(e) => {
+            let message = format!("Failed to run {}: {}\n", program, e);
+            if echo {
+                eprint!("{}", message);
+            }
+            return Ok(CapturedRun {
+                exit_code: None,
+                success: false,
+                stdout: Vec::new(),
+                stderr: message.into_bytes(),
+                duration_ms: started.elapsed().as_millis() as i64,
+            });
+        }
+    };
+
+    let stdout = child.stdout.take().context("Child stdout wasn't piped")?;
+    let stderr = child.stderr.take().context("Child stderr wasn't piped")?;
+    let (stdout, stderr, status) = tokio::join!(
+        pump(stdout, echo.then(tokio::io::stdout)),
+        pump(stderr, echo.then(tokio::io::stderr)),
+        child.wait()
+    );
+    let status = status.context(format!("Failed to wait for {}", program))?;
+
+    Ok(CapturedRun {
+        exit_code: status.code(),
+        success: status.success(),
+        stdout: stdout?,
+        stderr: stderr?,
+        duration_ms: started.elapsed().as_millis() as i64,
+    })
+}
+
+/// Run `command` in `dir` attached to the terminal, for interactive
+/// commands whose output isn't kept; the run has no output
+pub async fn run_inherited(command: &[String], dir: &Path) -> Result<CapturedRun> {
+    let (program, args) = command
+        .split_first()
+        .context("No command given; pass it after --")?;
+    let started = Instant::now();
+    let status = tokio::process::Command::new(program)
+        .args(args)
+        .current_dir(dir)
+        .status()
+        .await;
+    let (exit_code, success, stderr) = match status {
+        Ok(status) => (status.code(), status.success(), Vec::new()),
+        Err(e) => {
+            let message = format!("Failed to run {}: {}\n", program, e);
+            eprint!("{}", message);
+            (None, false, message.into_bytes())
+        }
+    };
+    Ok(CapturedRun {
+        exit_code,
+        success,
+        stdout: Vec::new(),
+        stderr,
+        duration_ms: started.elapsed().as_millis() as i64,
+    })
+}
+
+async fn pump(
+    mut reader: impl AsyncRead + Unpin,
+    mut echo: Option<impl AsyncWrite + Unpin>,
+) -> std::io::Result<Vec<u8>> {
+    let mut captured = Vec::new();
+    let mut buf = [0u8; 8192];
+    loop {
+        let n = reader.read(&mut buf).await?;
+        if n == 0 {
+            return Ok(captured);
+        }
+        captured.extend_from_slice(&buf[..n]);
+        if let Some(out) = echo.as_mut() {
+            out.write_all(&buf[..n]).await?;
+            out.flush().await?;
+        }
+    }
+}
+
+/// Artifact files of one project, under `.iMi/artifacts/`
+pub struct ArtifactStore {
+    root: PathBuf,
+}
+
+impl ArtifactStore {
+    pub fn new(project_root: &Path) -> Self {
+        Self {
+            root: LocalContext::new(project_root)
+                .artifacts_path()
+                .to_path_buf(),
+        }
+    }
+
+    /// Write a run's output and return the directory holding it
+    pub fn write(&self, worktree_name: &str, id: &Uuid, run: &CapturedRun) -> Result<PathBuf> {
+        let dir = self.root.join(worktree_name).join(id.to_string());
+        std::fs::create_dir_all(&dir).context(format!("Failed to create {}", dir.display()))?;
+        std::fs::write(dir.join(STDOUT_FILE), &run.stdout)
+            .context(format!("Failed to write output to {}", dir.display()))?;
+        std::fs::write(dir.join(STDERR_FILE), &run.stderr)
+            .context(format!("Failed to write output to {}", dir.display()))?;
+        Ok(dir)
+    }
+}
+
+/// One captured stream of an artifact; empty when its files were removed
+pub fn read_output(output_dir: &Path, stream: Stream) -> Result<Vec<u8>> {
+    let path = output_dir.join(stream.file_name());
+    match std::fs::read(&path) {
+        Ok(bytes) => Ok(bytes),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e).context(format!("Failed to read {}", path.display())),
+    }
+}
+
+/// Delete an artifact's output directory
+pub fn remove_output(output_dir: &Path) -> Result<()> {
+    match std::fs::remove_dir_all(output_dir) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(e).context(format!("Failed to remove {}", output_dir.display()))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn captures_output_and_exit_codes() {
+        let dir = tempfile::tempdir().unwrap();
+        let run = run_captured(
+            &command(&["sh", "-c", "echo out; echo err >&2; exit 3"]),
+            dir.path(),
+            false,
+        )
+        .await
+        .unwrap();
+        assert_eq!(run.exit_code, Some(3));
+        assert!(!run.success);
+        assert_eq!(run.stdout, b"out\n");
+        assert_eq!(run.stderr, b"err\n");
+
+        let missing = run_captured(&command(&["imi-no-such-tool"]), dir.path(), false)
+            .await
+            .unwrap();
+        assert_eq!(missing.exit_code, None);
+        assert!(String::from_utf8_lossy(&missing.stderr).contains("imi-no-such-tool"));
+    }
+
+    #[tokio::test]
+    async fn stores_output_next_to_the_worktrees() {
+        let project = tempfile::tempdir().unwrap();
+        let run = run_captured(&command(&["sh", "-c", "echo ok"]), project.path(), false)
+            .await
+            .unwrap();
+
+        let id = Uuid::new_v4();
+        let dir = ArtifactStore::new(project.path())
+            .write("feat-auth", &id, &run)
+            .unwrap();
+        assert_eq!(
+            dir,
+            project
+                .path()
+                .join(".iMi/artifacts/feat-auth")
+                .join(id.to_string())
+        );
+        assert_eq!(read_output(&dir, Stream::Stdout).unwrap(), b"ok\n");
+        assert!(read_output(&dir, Stream::Stderr).unwrap().is_empty());
+
+        remove_output(&dir).unwrap();
+        assert!(read_output(&dir, Stream::Stdout).unwrap().is_empty());
+        remove_output(&dir).unwrap();
+    }
+}
@@ -0,0 +1,272 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::config::AuditSettings;
+
+const REDACTED: &str = "***";
+
+/// One line of the append-only audit log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub command: String,
+    pub args: Vec<String>,
+    pub user: Option<String>,
+    pub agent_id: Option<String>,
+    pub hostname: Option<String>,
+    pub cwd: Option<String>,
+    pub duration_ms: u64,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl AuditEntry {
+    pub fn new(
+        command: &str,
+        args: &[String],
+        settings: &AuditSettings,
+        duration_ms: u64,
+        error: Option<String>,
+    ) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            command: command.to_string(),
+            args: redact_args(args, &settings.redact_args),
+            user: std::env::var("USER")
+                .or_else(|_| std::env::var("USERNAME"))
+                .ok(),
+            agent_id: std::env::var("IMI_AGENT_ID").ok(),
+            hostname: hostname::get()
+                .ok()
+                .map(|h| h.to_string_lossy().to_string()),
+            cwd: std::env::current_dir()
+                .ok()
+                .map(|p| p.display().to_string()),
+            duration_ms,
+            success: error.is_none(),
+            error,
+        }
+    }
+
+    /// Case-insensitive match against command, args, cwd, agent and error text
+    pub fn matches(&self, pattern: &str) -> bool {
+        let pattern = pattern.to_lowercase();
+        [
+            Some(self.command.as_str()),
+            self.cwd.as_deref(),
+            self.agent_id.as_deref(),
+            self.user.as_deref(),
+            self.error.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        .chain(self.args.iter().map(String::as_str))
+        .any(|field| field.to_lowercase().contains(&pattern))
+    }
+}
+
+/// Append-only JSONL audit log
+pub struct AuditLog {
+    path: PathBuf,
+}
+
+impl AuditLog {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn from_settings(settings: &AuditSettings) -> Result<Self> {
+        Ok(Self::new(settings.resolve_log_path()?))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn append(&self, entry: &AuditEntry) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).context("Failed to create audit log directory")?;
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .context(format!("Failed to open audit log at {:?}", self.path))?;
+
+        let line = serde_json::to_string(entry).context("Failed to serialize audit entry")?;
+        writeln!(file, "{}", line).context("Failed to write audit entry")?;
+
+        Ok(())
+    }
+
+    /// Read all entries, skipping lines that fail to parse
+    pub fn read_all(&self) -> Result<Vec<AuditEntry>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let file = std::fs::File::open(&self.path)
+            .context(format!("Failed to open audit log at {:?}", self.path))?;
+
+        Ok(BufReader::new(file)
+            .lines()
+            .map_while(|line| line.ok())
+            .filter_map(|line| serde_json::from_str(&line).ok())
+            .collect())
+    }
+
+    /// Entries after byte `offset`, and the offset just past the last
+    /// complete line, so a follower reads each entry once. A log shorter than
+    /// `offset` (truncated or rotated) is read from the start.
+    pub fn read_from(&self, offset: u64) -> Result<(Vec<AuditEntry>, u64)> {
+        if !self.path.exists() {
+            return Ok((Vec::new(), 0));
+        }
+
+        let mut file = std::fs::File::open(&self.path)
+            .context(format!("Failed to open audit log at {:?}", self.path))?;
+        let mut offset = match file.metadata()?.len() < offset {
+            true => 0,
+            false => offset,
+        };
+        file.seek(SeekFrom::Start(offset))?;
+
+        let mut reader = BufReader::new(file);
+        let mut entries = Vec::new();
+        let mut line = String::new();
+        // A line without its newline is still being written
+        while reader.read_line(&mut line)? > 0 && line.ends_with('\n') {
+            offset += line.len() as u64;
+            if let Ok(entry) = serde_json::from_str(&line) {
+                entries.push(entry);
+            }
+            line.clear();
+        }
+        Ok((entries, offset))
+    }
+
+    /// The last `limit` entries, oldest first
+    pub fn tail(&self, limit: usize) -> Result<Vec<AuditEntry>> {
+        let entries = self.read_all()?;
+        let skip = entries.len().saturating_sub(limit);
+        Ok(entries.into_iter().skip(skip).collect())
+    }
+
+    /// Entries matching a free-text pattern and optional filters, oldest first
+    pub fn search(
+        &self,
+        pattern: Option<&str>,
+        command: Option<&str>,
+        failed_only: bool,
+        limit: usize,
+    ) -> Result<Vec<AuditEntry>> {
+        let matches: Vec<AuditEntry> = self
+            .read_all()?
+            .into_iter()
+            .filter(|e| pattern.is_none_or(|p| e.matches(p)))
+            .filter(|e| command.is_none_or(|c| e.command.eq_ignore_ascii_case(c)))
+            .filter(|e| !failed_only || !e.success)
+            .collect();
+
+        let skip = matches.len().saturating_sub(limit);
+        Ok(matches.into_iter().skip(skip).collect())
+    }
+}
+
+/// Mask the values of sensitive flags (`--flag value` and `--flag=value`)
+pub fn redact_args(args: &[String], sensitive: &[String]) -> Vec<String> {
+    let is_sensitive = |flag: &str| sensitive.iter().any(|s| s == flag);
+    let mut redacted = Vec::with_capacity(args.len());
+    let mut mask_next = false;
+
+    for arg in args {
+        if mask_next {
+            redacted.push(REDACTED.to_string());
+            mask_next = false;
+            continue;
+        }
+
+        if let Some((flag, _)) = arg.split_once('=') {
+            if is_sensitive(flag) {
+                redacted.push(format!("{}={}", flag, REDACTED));
+                continue;
+            }
+        }
+
+        mask_next = is_sensitive(arg);
+        redacted.push(arg.clone());
+    }
+
+    redacted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn redacts_separate_and_inline_values() {
+        let sensitive = strings(&["--value", "--token"]);
+        let args = strings(&[
+            "metadata",
+            "set",
+            "--value",
+            "secret",
+            "--token=abc",
+            "--key",
+            "k",
+        ]);
+
+        assert_eq!(
+            redact_args(&args, &sensitive),
+            strings(&[
+                "metadata",
+                "set",
+                "--value",
+                "***",
+                "--token=***",
+                "--key",
+                "k"
+            ])
+        );
+    }
+
+    #[test]
+    fn appends_and_searches_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = AuditLog::new(dir.path().join("audit.jsonl"));
+        let settings = AuditSettings::default();
+
+        log.append(&AuditEntry::new(
+            "add",
+            &strings(&["add", "feat", "auth"]),
+            &settings,
+            12,
+            None,
+        ))
+        .unwrap();
+        log.append(&AuditEntry::new(
+            "merge",
+            &strings(&["merge", "feat-auth"]),
+            &settings,
+            40,
+            Some("conflict".to_string()),
+        ))
+        .unwrap();
+
+        assert_eq!(log.tail(1).unwrap()[0].command, "merge");
+        assert_eq!(log.search(Some("auth"), None, false, 10).unwrap().len(), 2);
+        assert_eq!(log.search(None, None, true, 10).unwrap().len(), 1);
+        assert_eq!(log.search(None, Some("add"), false, 10).unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,237 @@
+//! Seams between iMi and the outside world: the `git` executable and the
+//! GitHub API. [`GitManager`](crate::git::GitManager) runs git and opens
+//! repositories through a [`GitBackend`], and
+//! [`WorktreeManager`](crate::worktree::WorktreeManager) talks to GitHub
+//! through a [`GitHubBackend`]; both default to the real thing. With the `test-fakes` feature, [`fakes`] has scripted stand-ins so
+//! worktree logic can be exercised without repositories or network.
+
+use anyhow::{Context, Result};
+use git2::Repository;
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::Path;
+use std::pin::Pin;
+use std::process::Command;
+use std::sync::Mutex;
+
+use crate::config::RetrySettings;
+use crate::github::pulls::PullRequest;
+use crate::github::{
+    CiStatus, PullRequestLookup, PullRequestMerger, QueueState, WorkflowRunLookup,
+};
+
+#[cfg(any(test, feature = "test-fakes"))]
+#[allow(dead_code)] // Library accessors
+pub mod fakes;
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// What a git command printed and whether it succeeded
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GitOutput {
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Runs git commands and opens repositories for
+/// [`GitManager`](crate::git::GitManager); an error means git couldn't be
+/// run at all, a failing command is `success: false`
+pub trait GitBackend: Send + Sync + std::fmt::Debug {
+    fn run(&self, dir: &Path, args: &[&str]) -> Result<GitOutput>;
+
+    /// Run git on the terminal, its output going to stderr, for interactive
+    /// or long-running commands; returns the exit code (-1 when killed)
+    fn run_attached(&self, dir: &Path, args: &[&str]) -> Result<i32>;
+
+    /// Open the repository at `path` for libgit2
+    fn open(&self, path: &Path) -> Result<Repository, git2::Error>;
+
+    /// Open the repository `path` is in
+    fn discover(&self, path: &Path) -> Result<Repository, git2::Error>;
+}
+
+/// The `git` on PATH
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemGit;
+
+impl GitBackend for SystemGit {
+    fn run(&self, dir: &Path, args: &[&str]) -> Result<GitOutput> {
+        let output = Command::new("git")
+            .current_dir(dir)
+            .args(args)
+            .output()
+            .context("Failed to execute git command")?;
+        Ok(GitOutput {
+            success: output.status.success(),
+            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        })
+    }
+
+    fn run_attached(&self, dir: &Path, args: &[&str]) -> Result<i32> {
+        let status = Command::new("git")
+            .current_dir(dir)
+            .args(args)
+            .stdout(std::io::stderr())
+            .status()
+            .context("Failed to execute git command")?;
+        Ok(status.code().unwrap_or(-1))
+    }
+
+    fn open(&self, path: &Path) -> Result<Repository, git2::Error> {
+        Repository::open(path)
+    }
+
+    fn discover(&self, path: &Path) -> Result<Repository, git2::Error> {
+        Repository::discover(path)
+    }
+}
+
+/// The GitHub calls worktree commands make, keyed by a repository's remote
+/// URL. Remotes that aren't on GitHub have no pull requests or runs.
+pub trait GitHubBackend: Send + Sync + std::fmt::Debug {
+    /// Most recent pull request whose head is `branch`
+    fn find_pull_request<'a>(
+        &'a self,
+        remote_url: &'a str,
+        branch: &'a str,
+    ) -> BoxFuture<'a, Result<Option<PullRequest>>>;
+
+    /// Paths a pull request changes
+    fn pull_request_files<'a>(
+        &'a self,
+        remote_url: &'a str,
+        number: i32,
+    ) -> BoxFuture<'a, Result<Vec<String>>>;
+
+    /// CI status of the newest push to `branch`
+    fn latest_workflow_run<'a>(
+        &'a self,
+        remote_url: &'a str,
+        branch: &'a str,
+    ) -> BoxFuture<'a, Result<Option<CiStatus>>>;
+
+    /// Merge a pull request right away
+    fn merge_pull_request<'a>(
+        &'a self,
+        remote_url: &'a str,
+        number: i32,
+    ) -> BoxFuture<'a, Result<()>>;
+
+    /// Add a pull request to the merge queue
+    fn enqueue_pull_request<'a>(
+        &'a self,
+        remote_url: &'a str,
+        number: i32,
+    ) -> BoxFuture<'a, Result<()>>;
+
+    fn merge_queue_state<'a>(
+        &'a self,
+        remote_url: &'a str,
+        number: i32,
+    ) -> BoxFuture<'a, Result<QueueState>>;
+}
+
+/// The GitHub REST and GraphQL APIs, with the credentials the lookups find
+pub struct GitHubApi {
+    retry: RetrySettings,
+    pulls: PullRequestLookup,
+    runs: WorkflowRunLookup,
+    /// GraphQL ids of pull requests, so polling the merge queue asks once
+    node_ids: Mutex<HashMap<(String, i32), String>>,
+}
+
+impl std::fmt::Debug for GitHubApi {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GitHubApi").finish_non_exhaustive()
+    }
+}
+
+impl GitHubApi {
+    pub fn new(retry: RetrySettings) -> Result<Self> {
+        Ok(Self {
+            pulls: PullRequestLookup::new(retry.clone())?,
+            runs: WorkflowRunLookup::new(retry.clone())?,
+            retry,
+            node_ids: Mutex::new(HashMap::new()),
+        })
+    }
+
+    fn merger(&self, remote_url: &str) -> Result<PullRequestMerger> {
+        PullRequestMerger::new(self.retry.clone(), remote_url)
+    }
+
+    async fn node_id(
+        &self,
+        merger: &PullRequestMerger,
+        remote_url: &str,
+        number: i32,
+    ) -> Result<String> {
+        let key = (remote_url.to_string(), number);
+        if let Some(id) = self.node_ids.lock().unwrap().get(&key) {
+            return Ok(id.clone());
+        }
+        let id = merger.node_id(number).await?;
+        self.node_ids.lock().unwrap().insert(key, id.clone());
+        Ok(id)
+    }
+}
+
+impl GitHubBackend for GitHubApi {
+    fn find_pull_request<'a>(
+        &'a self,
+        remote_url: &'a str,
+        branch: &'a str,
+    ) -> BoxFuture<'a, Result<Option<PullRequest>>> {
+        Box::pin(self.pulls.find(remote_url, branch))
+    }
+
+    fn pull_request_files<'a>(
+        &'a self,
+        remote_url: &'a str,
+        number: i32,
+    ) -> BoxFuture<'a, Result<Vec<String>>> {
+        Box::pin(self.pulls.changed_files(remote_url, number))
+    }
+
+    fn latest_workflow_run<'a>(
+        &'a self,
+        remote_url: &'a str,
+        branch: &'a str,
+    ) -> BoxFuture<'a, Result<Option<CiStatus>>> {
+        Box::pin(self.runs.latest(remote_url, branch))
+    }
+
+    fn merge_pull_request<'a>(
+        &'a self,
+        remote_url: &'a str,
+        number: i32,
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move { self.merger(remote_url)?.merge(number).await })
+    }
+
+    fn enqueue_pull_request<'a>(
+        &'a self,
+        remote_url: &'a str,
+        number: i32,
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let merger = self.merger(remote_url)?;
+            let id = self.node_id(&merger, remote_url, number).await?;
+            merger.enqueue(&id).await
+        })
+    }
+
+    fn merge_queue_state<'a>(
+        &'a self,
+        remote_url: &'a str,
+        number: i32,
+    ) -> BoxFuture<'a, Result<QueueState>> {
+        Box::pin(async move {
+            let merger = self.merger(remote_url)?;
+            let id = self.node_id(&merger, remote_url, number).await?;
+            merger.queue_state(&id).await
+        })
+    }
+}
@@ -0,0 +1,452 @@
+//! Scripted git and GitHub for tests (`test-fakes` feature). Both answer
+//! only what they were told to and record every call, so a test can drive
+//! worktree logic deterministically and then check what was run:
+//!
+//! ```ignore
+//! let git = Arc::new(FakeGit::new().on(&["rev-parse", "--is-shallow-repository"], "true\n"));
+//! let manager = GitManager::new().with_backend(git.clone());
+//! ```
+
+use anyhow::{anyhow, Result};
+use git2::Repository;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::{BoxFuture, GitBackend, GitHubBackend, GitOutput};
+use crate::github::pulls::PullRequest;
+use crate::github::{CiStatus, QueueState};
+
+/// One git command a [`FakeGit`] was asked to run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitCall {
+    pub dir: PathBuf,
+    pub args: Vec<String>,
+}
+
+/// Git that answers from a script: the first rule whose arguments start the
+/// command wins, and a command no rule matches is an error. Every path opens
+/// the fixture repository, if one was given.
+#[derive(Debug, Default)]
+pub struct FakeGit {
+    rules: Vec<(Vec<String>, GitOutput)>,
+    calls: Mutex<Vec<GitCall>>,
+    repository: Option<PathBuf>,
+}
+
+impl FakeGit {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Succeed with `stdout` for commands starting with `args`
+    pub fn on(self, args: &[&str], stdout: &str) -> Self {
+        self.respond(
+            args,
+            GitOutput {
+                success: true,
+                stdout: stdout.to_string(),
+                stderr: String::new(),
+            },
+        )
+    }
+
+    /// Fail with `stderr` for commands starting with `args`
+    pub fn fail(self, args: &[&str], stderr: &str) -> Self {
+        self.respond(
+            args,
+            GitOutput {
+                success: false,
+                stdout: String::new(),
+                stderr: stderr.to_string(),
+            },
+        )
+    }
+
+    /// Open `path` whenever libgit2 asks for a repository
+    pub fn with_repository(mut self, path: &Path) -> Self {
+        self.repository = Some(path.to_path_buf());
+        self
+    }
+
+    pub fn respond(mut self, args: &[&str], output: GitOutput) -> Self {
+        let args = args.iter().map(|arg| arg.to_string()).collect();
+        self.rules.push((args, output));
+        self
+    }
+
+    /// Every command run so far, in order
+    pub fn calls(&self) -> Vec<GitCall> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    /// Arguments of every command run so far, joined with spaces
+    pub fn commands(&self) -> Vec<String> {
+        self.calls()
+            .into_iter()
+            .map(|call| call.args.join(" "))
+            .collect()
+    }
+}
+
+impl GitBackend for FakeGit {
+    fn run(&self, dir: &Path, args: &[&str]) -> Result<GitOutput> {
+        self.calls.lock().unwrap().push(GitCall {
+            dir: dir.to_path_buf(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+        });
+        self.rules
+            .iter()
+            .find(|(prefix, _)| {
+                prefix.len() <= args.len() && prefix.iter().eq(&args[..prefix.len()])
+            })
+            .map(|(_, output)| output.clone())
+            .ok_or_else(|| anyhow!("FakeGit has no answer for: git {}", args.join(" ")))
+    }
+
+    fn run_attached(&self, dir: &Path, args: &[&str]) -> Result<i32> {
+        let output = self.run(dir, args)?;
+        Ok(match output.success {
+            true => 0,
+            false => 1,
+        })
+    }
+
+    fn open(&self, path: &Path) -> Result<Repository, git2::Error> {
+        match &self.repository {
+            Some(fixture) => Repository::open(fixture),
+            None => Err(git2::Error::from_str(&format!(
+                "FakeGit has no repository for {}",
+                path.display()
+            ))),
+        }
+    }
+
+    fn discover(&self, path: &Path) -> Result<Repository, git2::Error> {
+        self.open(path)
+    }
+}
+
+/// What a [`FakeGitHub`] was asked to do
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GitHubCall {
+    FindPullRequest { remote_url: String, branch: String },
+    PullRequestFiles { remote_url: String, number: i32 },
+    LatestWorkflowRun { remote_url: String, branch: String },
+    Merge { remote_url: String, number: i32 },
+    Enqueue { remote_url: String, number: i32 },
+    QueueState { remote_url: String, number: i32 },
+}
+
+/// GitHub with fixed pull requests and runs per branch. Merge queue states
+/// are handed out in order, the last one repeating; merging succeeds unless
+/// the pull request was set to fail.
+#[derive(Debug, Default)]
+pub struct FakeGitHub {
+    pulls: HashMap<(String, String), PullRequest>,
+    files: HashMap<(String, i32), Vec<String>>,
+    runs: HashMap<(String, String), CiStatus>,
+    queue: Mutex<HashMap<(String, i32), VecDeque<QueueState>>>,
+    failing_merges: HashMap<(String, i32), String>,
+    calls: Mutex<Vec<GitHubCall>>,
+}
+
+impl FakeGitHub {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_pull_request(mut self, remote_url: &str, branch: &str, pr: PullRequest) -> Self {
+        self.pulls
+            .insert((remote_url.to_string(), branch.to_string()), pr);
+        self
+    }
+
+    pub fn with_pull_request_files(
+        mut self,
+        remote_url: &str,
+        number: i32,
+        files: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.files.insert(
+            (remote_url.to_string(), number),
+            files.into_iter().map(Into::into).collect(),
+        );
+        self
+    }
+
+    pub fn with_workflow_run(mut self, remote_url: &str, branch: &str, status: CiStatus) -> Self {
+        self.runs
+            .insert((remote_url.to_string(), branch.to_string()), status);
+        self
+    }
+
+    /// States the merge queue reports for a pull request, one per poll
+    pub fn with_queue_states(
+        self,
+        remote_url: &str,
+        number: i32,
+        states: impl IntoIterator<Item = QueueState>,
+    ) -> Self {
+        self.queue.lock().unwrap().insert(
+            (remote_url.to_string(), number),
+            states.into_iter().collect(),
+        );
+        self
+    }
+
+    /// Refuse to merge a pull request with `message`
+    pub fn failing_merge(mut self, remote_url: &str, number: i32, message: &str) -> Self {
+        self.failing_merges
+            .insert((remote_url.to_string(), number), message.to_string());
+        self
+    }
+
+    /// Every call made so far, in order
+    pub fn calls(&self) -> Vec<GitHubCall> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    /// Pull requests merged directly (not through the queue)
+    pub fn merged(&self) -> Vec<(String, i32)> {
+        self.calls()
+            .into_iter()
+            .filter_map(|call| match call {
+                GitHubCall::Merge { remote_url, number } => Some((remote_url, number)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn record(&self, call: GitHubCall) {
+        self.calls.lock().unwrap().push(call);
+    }
+}
+
+impl GitHubBackend for FakeGitHub {
+    fn find_pull_request<'a>(
+        &'a self,
+        remote_url: &'a str,
+        branch: &'a str,
+    ) -> BoxFuture<'a, Result<Option<PullRequest>>> {
+        self.record(GitHubCall::FindPullRequest {
+            remote_url: remote_url.to_string(),
+            branch: branch.to_string(),
+        });
+        let pr = self
+            .pulls
+            .get(&(remote_url.to_string(), branch.to_string()))
+            .cloned();
+        Box::pin(async move { Ok(pr) })
+    }
+
+    fn pull_request_files<'a>(
+        &'a self,
+        remote_url: &'a str,
+        number: i32,
+    ) -> BoxFuture<'a, Result<Vec<String>>> {
+        self.record(GitHubCall::PullRequestFiles {
+            remote_url: remote_url.to_string(),
+            number,
+        });
+        let files = self.files.get(&(remote_url.to_string(), number)).cloned();
+        Box::pin(async move {
+            files.ok_or_else(|| anyhow!("FakeGitHub has no files for PR #{}", number))
+        })
+    }
+
+    fn latest_workflow_run<'a>(
+        &'a self,
+        remote_url: &'a str,
+        branch: &'a str,
+    ) -> BoxFuture<'a, Result<Option<CiStatus>>> {
+        self.record(GitHubCall::LatestWorkflowRun {
+            remote_url: remote_url.to_string(),
+            branch: branch.to_string(),
+        });
+        let status = self
+            .runs
+            .get(&(remote_url.to_string(), branch.to_string()))
+            .cloned();
+        Box::pin(async move { Ok(status) })
+    }
+
+    fn merge_pull_request<'a>(
+        &'a self,
+        remote_url: &'a str,
+        number: i32,
+    ) -> BoxFuture<'a, Result<()>> {
+        self.record(GitHubCall::Merge {
+            remote_url: remote_url.to_string(),
+            number,
+        });
+        let failure = self
+            .failing_merges
+            .get(&(remote_url.to_string(), number))
+            .cloned();
+        Box::pin(async move {
+            match failure {
+                Some(message) => Err(anyhow!(message)),
+                None => Ok(()),
+            }
+        })
+    }
+
+    fn enqueue_pull_request<'a>(
+        &'a self,
+        remote_url: &'a str,
+        number: i32,
+    ) -> BoxFuture<'a, Result<()>> {
+        self.record(GitHubCall::Enqueue {
+            remote_url: remote_url.to_string(),
+            number,
+        });
+        Box::pin(async move { Ok(()) })
+    }
+
+    fn merge_queue_state<'a>(
+        &'a self,
+        remote_url: &'a str,
+        number: i32,
+    ) -> BoxFuture<'a, Result<QueueState>> {
+        self.record(GitHubCall::QueueState {
+            remote_url: remote_url.to_string(),
+            number,
+        });
+        let mut queue = self.queue.lock().unwrap();
+        let state = match queue.get_mut(&(remote_url.to_string(), number)) {
+            Some(states) if states.len() > 1 => states.pop_front(),
+            Some(states) => states.front().cloned(),
+            None => None,
+        };
+        Box::pin(async move {
+            state.ok_or_else(|| anyhow!("FakeGitHub has no queue state for PR #{}", number))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::GitManager;
+    use crate::github::pulls::PullRequestState;
+    use std::sync::Arc;
+
+    #[test]
+    fn git_manager_runs_scripted_commands() {
+        let git = Arc::new(
+            FakeGit::new()
+                .on(
+                    &["config", "--get-all", "remote.origin.fetch"],
+                    "+refs/heads/main:refs/remotes/origin/main\n",
+                )
+                .fail(&["config", "--get-all"], "")
+                .on(&["rev-parse", "--is-shallow-repository"], "true\n")
+                .on(&["config", "--replace-all"], "")
+                .on(&["fetch"], ""),
+        );
+        let manager = GitManager::new().with_backend(git.clone());
+
+        let converted = manager.unshallow(Path::new("/repo")).unwrap();
+        assert_eq!(converted, vec!["single branch", "shallow history"]);
+        assert_eq!(
+            git.commands().last().map(String::as_str),
+            Some("fetch --unshallow origin")
+        );
+        assert!(git
+            .calls()
+            .iter()
+            .all(|call| call.dir == Path::new("/repo")));
+
+        let err = manager
+            .execute_git_command(Path::new("/repo"), &["status"])
+            .unwrap_err();
+        assert!(err.to_string().contains("no answer for: git status"));
+    }
+
+    #[test]
+    fn git_manager_opens_repositories_through_the_backend() {
+        let dir = tempfile::tempdir().unwrap();
+        let fixture = git2::Repository::init(dir.path()).unwrap();
+        fixture.set_head("refs/heads/trunk").unwrap();
+        let mut index = fixture.index().unwrap();
+        let tree = fixture.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("Dev", "dev@example.com").unwrap();
+        fixture
+            .commit(Some("HEAD"), &signature, &signature, "root", &tree, &[])
+            .unwrap();
+
+        let git = Arc::new(
+            FakeGit::new()
+                .with_repository(dir.path())
+                .on(&["mergetool"], ""),
+        );
+        let manager = GitManager::new().with_backend(git.clone());
+        assert_eq!(
+            manager.get_current_branch(Path::new("/nowhere")).unwrap(),
+            "trunk"
+        );
+        assert_eq!(
+            manager
+                .run_git_attached(Path::new("/nowhere"), &["mergetool", "--no-prompt"])
+                .unwrap(),
+            0
+        );
+        assert_eq!(git.commands(), vec!["mergetool --no-prompt"]);
+
+        let manager = GitManager::new().with_backend(Arc::new(FakeGit::new()));
+        assert!(manager.get_current_branch(Path::new("/nowhere")).is_err());
+    }
+
+    #[tokio::test]
+    async fn github_answers_per_branch_and_plays_queue_states() {
+        let remote = "git@github.com:acme/api.git";
+        let pr = PullRequest {
+            number: 7,
+            url: "https://github.com/acme/api/pull/7".to_string(),
+            state: PullRequestState::Open,
+        };
+        let github = FakeGitHub::new()
+            .with_pull_request(remote, "feat/auth", pr.clone())
+            .with_queue_states(
+                remote,
+                7,
+                [
+                    QueueState::Queued {
+                        position: Some(1),
+                        state: "AWAITING_CHECKS".to_string(),
+                    },
+                    QueueState::Merged,
+                ],
+            )
+            .failing_merge(remote, 8, "Pull request is not mergeable");
+
+        assert_eq!(
+            github.find_pull_request(remote, "feat/auth").await.unwrap(),
+            Some(pr)
+        );
+        assert_eq!(
+            github.find_pull_request(remote, "fix/x").await.unwrap(),
+            None
+        );
+        assert!(matches!(
+            github.merge_queue_state(remote, 7).await.unwrap(),
+            QueueState::Queued { .. }
+        ));
+        assert_eq!(
+            github.merge_queue_state(remote, 7).await.unwrap(),
+            QueueState::Merged
+        );
+        assert_eq!(
+            github.merge_queue_state(remote, 7).await.unwrap(),
+            QueueState::Merged
+        );
+
+        github.merge_pull_request(remote, 7).await.unwrap();
+        assert!(github.merge_pull_request(remote, 8).await.is_err());
+        assert_eq!(
+            github.merged(),
+            vec![(remote.to_string(), 7), (remote.to_string(), 8)]
+        );
+    }
+}
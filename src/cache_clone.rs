@@ -0,0 +1,200 @@
+//! Build caches (`target/`, `node_modules`, ...) cloned from the source
+//! worktree when `imi copy` forks it, so the fork builds without starting
+//! cold. Copy-on-write clones cost no disk space and are tried first; a
+//! plain copy costs the cache's full size and is skipped over the limit.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Command;
+use walkdir::WalkDir;
+
+use crate::disk_usage::directory_size;
+
+/// What to do with a cache when the filesystem can't clone it copy-on-write
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum CacheFallback {
+    /// Hard-link every file; free, but both worktrees share file contents
+    Hardlink,
+    /// Copy the files, up to `max_copy_mb`
+    Copy,
+    /// Leave the cache out
+    Skip,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CloneMethod {
+    Reflink,
+    Hardlink,
+    Copy,
+}
+
+/// One cache directory and what happened to it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClonedCache {
+    pub dir: String,
+    pub bytes: u64,
+    /// How it was cloned; None when it was skipped
+    pub method: Option<CloneMethod>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skipped: Option<String>,
+}
+
+/// Clone each of `dirs` that exists in `source` into `dest`. Failures are
+/// reported per cache rather than failing the fork.
+pub fn clone_caches(
+    source: &Path,
+    dest: &Path,
+    dirs: &[String],
+    fallback: CacheFallback,
+    max_copy_bytes: u64,
+) -> Vec<ClonedCache> {
+    let mut cloned = Vec::new();
+    for dir in dirs {
+        let (from, to) = (source.join(dir), dest.join(dir));
+        if !from.is_dir() {
+            continue;
+        }
+        let bytes = directory_size(&from);
+        let mut cache = ClonedCache {
+            dir: dir.clone(),
+            bytes,
+            method: None,
+            skipped: None,
+        };
+
+        // Nested caches such as packages/web/node_modules need their parent
+        let parent_ready = to
+            .parent()
+            .is_none_or(|parent| std::fs::create_dir_all(parent).is_ok());
+        if to.exists() {
+            cache.skipped = Some("already present in the new worktree".to_string());
+        } else if !parent_ready {
+            cache.skipped = Some("could not create its parent directory".to_string());
+        } else if reflink_tree(&from, &to) {
+            cache.method = Some(CloneMethod::Reflink);
+        } else {
+            let result = match fallback {
+                CacheFallback::Skip => {
+                    Err("the filesystem can't clone it copy-on-write".to_string())
+                }
+                CacheFallback::Copy if bytes > max_copy_bytes => Err(format!(
+                    "{} MB is over max_copy_mb ({} MB)",
+                    bytes / (1024 * 1024),
+                    max_copy_bytes / (1024 * 1024)
+                )),
+                CacheFallback::Copy => copy_tree(&from, &to, false).map(|_| CloneMethod::Copy),
+                CacheFallback::Hardlink => {
+                    copy_tree(&from, &to, true).map(|_| CloneMethod::Hardlink)
+                }
+            };
+            match result {
+                Ok(method) => cache.method = Some(method),
+                Err(reason) => {
+                    let _ = std::fs::remove_dir_all(&to);
+                    cache.skipped = Some(reason);
+                }
+            }
+        }
+        cloned.push(cache);
+    }
+    cloned
+}
+
+/// A copy-on-write clone of the whole tree (btrfs, XFS, APFS, ...) via
+/// `cp`, which knows each platform's clone call
+fn reflink_tree(from: &Path, to: &Path) -> bool {
+    let clone_flag = match std::env::consts::OS {
+        "linux" => "--reflink=always",
+        "macos" => "-c",
+        _ => return false,
+    };
+    let cloned = Command::new("cp")
+        .args([clone_flag, "-R"])
+        .arg(from)
+        .arg(to)
+        .stderr(std::process::Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success());
+    if !cloned {
+        let _ = std::fs::remove_dir_all(to);
+    }
+    cloned
+}
+
+/// Recreate the tree under `to`, hard-linking or copying files and keeping
+/// symlinks as symlinks
+fn copy_tree(from: &Path, to: &Path, hardlink: bool) -> Result<(), String> {
+    for entry in WalkDir::new(from) {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let relative = entry.path().strip_prefix(from).map_err(|e| e.to_string())?;
+        let target = to.join(relative);
+        let file_type = entry.file_type();
+        let result = if file_type.is_dir() {
+            std::fs::create_dir_all(&target)
+        } else if file_type.is_symlink() {
+            copy_symlink(entry.path(), &target)
+        } else if hardlink {
+            std::fs::hard_link(entry.path(), &target)
+        } else {
+            std::fs::copy(entry.path(), &target).map(|_| ())
+        };
+        result.map_err(|e| format!("{}: {}", entry.path().display(), e))?;
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn copy_symlink(link: &Path, target: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(std::fs::read_link(link)?, target)
+}
+
+#[cfg(not(unix))]
+fn copy_symlink(link: &Path, target: &Path) -> std::io::Result<()> {
+    std::fs::copy(link, target).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clones_present_caches_within_the_copy_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let (source, dest) = (dir.path().join("feat-a"), dir.path().join("feat-b"));
+        std::fs::create_dir_all(source.join("target/debug")).unwrap();
+        std::fs::write(source.join("target/debug/app"), vec![0u8; 4096]).unwrap();
+        std::fs::create_dir_all(source.join("node_modules/left-pad")).unwrap();
+        std::fs::write(source.join("node_modules/left-pad/index.js"), "x").unwrap();
+        std::fs::create_dir_all(&dest).unwrap();
+        let dirs = vec![
+            "target".to_string(),
+            "node_modules".to_string(),
+            ".venv".to_string(),
+        ];
+
+        // node_modules fits under the limit, target doesn't; .venv isn't there
+        let cloned = clone_caches(&source, &dest, &dirs, CacheFallback::Copy, 1024);
+        assert_eq!(cloned.len(), 2);
+        let node_modules = &cloned[1];
+        assert!(node_modules.method.is_some());
+        assert!(dest.join("node_modules/left-pad/index.js").is_file());
+        let target = &cloned[0];
+        if target.method.is_none() {
+            assert!(target.skipped.as_deref().unwrap().contains("max_copy_mb"));
+            assert!(!dest.join("target").exists());
+        } else {
+            // Copy-on-write filesystems clone it regardless of size
+            assert_eq!(target.method, Some(CloneMethod::Reflink));
+        }
+
+        // Already there: left alone
+        let again = clone_caches(&source, &dest, &dirs[1..2], CacheFallback::Hardlink, 0);
+        assert!(again[0]
+            .skipped
+            .as_deref()
+            .unwrap()
+            .contains("already present"));
+    }
+}
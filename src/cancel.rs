@@ -0,0 +1,46 @@
+//! Ctrl-C during long operations (clone, add, merge, prune). The first press
+//! asks the running command to stop at its next safe point, where it rolls
+//! back or journals what it did so far; the second quits at once.
+
+use colored::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::signal;
+
+use crate::error::ImiError;
+
+static REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Take over Ctrl-C for the rest of the process. Needs a tokio runtime.
+pub fn install() {
+    tokio::spawn(async {
+        if signal::ctrl_c().await.is_err() {
+            return;
+        }
+        REQUESTED.store(true, Ordering::SeqCst);
+        eprintln!(
+            "\n{} Stopping at the next safe point; press Ctrl-C again to quit now",
+            "⏹️".bright_yellow()
+        );
+
+        if signal::ctrl_c().await.is_ok() {
+            eprintln!(
+                "{} Quit mid-operation; 'imi resume' or 'imi rollback' recovers it",
+                "⚠️".bright_yellow()
+            );
+            std::process::exit(ImiError::CANCELLED_EXIT_CODE);
+        }
+    });
+}
+
+/// Whether Ctrl-C was pressed
+pub fn requested() -> bool {
+    REQUESTED.load(Ordering::SeqCst)
+}
+
+/// Fails with [`ImiError::Cancelled`] once Ctrl-C was pressed
+pub fn check() -> anyhow::Result<()> {
+    if requested() {
+        return Err(ImiError::Cancelled.into());
+    }
+    Ok(())
+}
@@ -0,0 +1,273 @@
+//! What this machine supports that iMi relies on (`imi verify`): symlinks,
+//! long paths and case sensitivity where worktrees live, git features and
+//! the gh CLI. The last report is saved so other commands can fall back
+//! instead of failing halfway, e.g. copying files they would symlink.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::config::Config;
+
+/// `git worktree repair` was added in this release
+const WORKTREE_REPAIR_VERSION: (u32, u32) = (2, 30);
+
+/// `git sparse-checkout set --cone` was added in this release
+const SPARSE_CONE_VERSION: (u32, u32) = (2, 25);
+
+/// Total length of the path written by the long-path probe
+const LONG_PATH_LEN: usize = 300;
+
+/// One probed capability
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Capability {
+    pub supported: bool,
+    pub detail: String,
+}
+
+impl Capability {
+    fn new(supported: bool, detail: impl Into<String>) -> Self {
+        Self {
+            supported,
+            detail: detail.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapabilityReport {
+    pub checked_at: DateTime<Utc>,
+    pub imi_version: String,
+    pub os: String,
+    /// Directory the filesystem probes ran in
+    pub probe_dir: PathBuf,
+    pub symlinks: Capability,
+    pub long_paths: Capability,
+    pub case_sensitive: Capability,
+    pub git: Capability,
+    pub worktree_repair: Capability,
+    pub sparse_checkout_cone: Capability,
+    pub gh: Capability,
+}
+
+impl CapabilityReport {
+    /// Probe this machine, with the filesystem checks run in `probe_dir`
+    pub fn collect(probe_dir: &Path) -> Result<Self> {
+        let scratch = probe_dir.join(format!(".imi-verify-{}", std::process::id()));
+        std::fs::create_dir_all(&scratch).context(format!(
+            "Failed to create probe directory in {}",
+            probe_dir.display()
+        ))?;
+        let symlinks = probe_symlinks(&scratch);
+        let long_paths = probe_long_paths(&scratch);
+        let case_sensitive = probe_case_sensitivity(&scratch);
+        let _ = std::fs::remove_dir_all(&scratch);
+
+        let git_version = tool_output("git", &["--version"]);
+        let version = git_version.as_deref().and_then(parse_git_version);
+        let git = match &git_version {
+            Some(line) => Capability::new(true, line.clone()),
+            None => Capability::new(false, "git not found on PATH"),
+        };
+        let needs = |(major, minor): (u32, u32)| match version {
+            Some(found) if found >= (major, minor) => Capability::new(true, "available"),
+            Some(_) => Capability::new(false, format!("needs git {}.{}+", major, minor)),
+            None => Capability::new(false, "git version unknown"),
+        };
+        let gh = match tool_output("gh", &["--version"]) {
+            Some(line) => Capability::new(true, line),
+            None => Capability::new(false, "gh not found on PATH; PR commands are unavailable"),
+        };
+
+        Ok(Self {
+            checked_at: Utc::now(),
+            imi_version: env!("CARGO_PKG_VERSION").to_string(),
+            os: format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH),
+            probe_dir: probe_dir.to_path_buf(),
+            symlinks,
+            long_paths,
+            case_sensitive,
+            git,
+            worktree_repair: needs(WORKTREE_REPAIR_VERSION),
+            sparse_checkout_cone: needs(SPARSE_CONE_VERSION),
+            gh,
+        })
+    }
+
+    /// Capabilities by name, in report order
+    pub fn entries(&self) -> [(&'static str, &Capability); 7] {
+        [
+            ("symlinks", &self.symlinks),
+            ("long_paths", &self.long_paths),
+            ("case_sensitive", &self.case_sensitive),
+            ("git", &self.git),
+            ("worktree_repair", &self.worktree_repair),
+            ("sparse_checkout_cone", &self.sparse_checkout_cone),
+            ("gh", &self.gh),
+        ]
+    }
+
+    /// iMi can't work at all without git; everything else degrades
+    pub fn usable(&self) -> bool {
+        self.git.supported
+    }
+
+    pub fn missing(&self) -> Vec<&'static str> {
+        self.entries()
+            .into_iter()
+            .filter(|(_, capability)| !capability.supported)
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    /// `capabilities.json` next to the global config
+    pub fn cache_path() -> Result<PathBuf> {
+        Ok(Config::get_global_config_path()?.with_file_name("capabilities.json"))
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::cache_path()?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)
+            .context(format!("Failed to write {}", path.display()))
+    }
+
+    /// The report saved by the last `imi verify`, if any
+    pub fn load_cached() -> Option<Self> {
+        let contents = std::fs::read_to_string(Self::cache_path().ok()?).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    pub fn print(&self) {
+        println!(
+            "{} iMi {} on {} (probed in {})",
+            "🔎".bright_cyan(),
+            self.imi_version,
+            self.os,
+            self.probe_dir.display()
+        );
+        for (name, capability) in self.entries() {
+            let icon = match capability.supported {
+                true => "✅".bright_green(),
+                false => "❌".bright_red(),
+            };
+            println!(
+                "   {} {:<22} {}",
+                icon,
+                name,
+                capability.detail.bright_black()
+            );
+        }
+    }
+}
+
+/// Whether the last `imi verify` found `pick` unsupported; machines never
+/// verified are assumed capable
+pub fn known_unsupported(pick: impl Fn(&CapabilityReport) -> &Capability) -> bool {
+    CapabilityReport::load_cached().is_some_and(|report| !pick(&report).supported)
+}
+
+fn probe_symlinks(dir: &Path) -> Capability {
+    let target = dir.join("target");
+    let link = dir.join("link");
+    let result = std::fs::write(&target, "imi").and_then(|_| symlink(&target, &link));
+    match result.and_then(|_| std::fs::read_to_string(&link)) {
+        Ok(contents) if contents == "imi" => Capability::new(true, "symlinks can be created"),
+        Ok(_) => Capability::new(false, "symlink doesn't resolve to its target"),
+        Err(e) => Capability::new(false, format!("{}; symlink_files will be copied", e)),
+    }
+}
+
+#[cfg(unix)]
+fn symlink(target: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(windows)]
+fn symlink(target: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::windows::fs::symlink_file(target, link)
+}
+
+fn probe_long_paths(dir: &Path) -> Capability {
+    let mut path = dir.to_path_buf();
+    while path.as_os_str().len() < LONG_PATH_LEN {
+        path.push("x".repeat(50));
+    }
+    let result = std::fs::create_dir_all(&path).and_then(|_| std::fs::write(path.join("f"), ""));
+    match result {
+        Ok(()) => Capability::new(
+            true,
+            format!("{}-character paths work", path.as_os_str().len()),
+        ),
+        Err(e) => Capability::new(false, format!("deep worktree paths fail: {}", e)),
+    }
+}
+
+fn probe_case_sensitivity(dir: &Path) -> Capability {
+    if let Err(e) = std::fs::write(dir.join("CaseProbe"), "") {
+        return Capability::new(false, format!("probe failed: {}", e));
+    }
+    match dir.join("caseprobe").exists() {
+        true => Capability::new(
+            false,
+            "case-insensitive; branches differing only in case collide",
+        ),
+        false => Capability::new(true, "case-sensitive"),
+    }
+}
+
+/// First line of a tool's output, or None when it isn't installed
+fn tool_output(tool: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(tool).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .map(|line| line.trim().to_string())
+}
+
+/// `(major, minor)` from e.g. `git version 2.39.3 (Apple Git-145)`
+fn parse_git_version(line: &str) -> Option<(u32, u32)> {
+    let version = line
+        .split_whitespace()
+        .find(|word| word.starts_with(|c: char| c.is_ascii_digit()))?;
+    let mut parts = version.split('.').map(|part| part.parse::<u32>().ok());
+    Some((parts.next()??, parts.next()??))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_git_versions() {
+        assert_eq!(parse_git_version("git version 2.43.0"), Some((2, 43)));
+        assert_eq!(
+            parse_git_version("git version 2.39.3 (Apple Git-145)"),
+            Some((2, 39))
+        );
+        assert_eq!(
+            parse_git_version("git version 2.41.0.windows.1"),
+            Some((2, 41))
+        );
+        assert_eq!(parse_git_version("not git"), None);
+    }
+
+    #[test]
+    fn probes_the_filesystem_and_cleans_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let report = CapabilityReport::collect(dir.path()).unwrap();
+        assert!(report.symlinks.supported, "{}", report.symlinks.detail);
+        assert!(report.long_paths.supported, "{}", report.long_paths.detail);
+        assert!(report.git.supported);
+        assert!(report.usable());
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+}
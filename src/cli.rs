@@ -1,5 +1,12 @@
-use clap::{Parser, Subcommand};
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
 use clap_complete::Shell;
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+use crate::cache_clone::CacheFallback;
+use crate::config::{MergeStrategy, WorktreeSort};
+use crate::format::OutputFormat;
+use crate::path_remap::PathRemap;
 
 #[derive(Parser)]
 #[command(
@@ -20,18 +27,57 @@ pub struct Cli {
     /// Output results in JSON format (available for all commands)
     #[arg(long, global = true)]
     pub json: bool,
+
+    /// JSON output for long operations (clone, merge) that also reports
+    /// progress as it goes: one NDJSON progress event per line, then the
+    /// result object
+    #[arg(long, global = true, conflicts_with = "format")]
+    pub json_stream: bool,
+
+    /// Output format for list, status, types and registry stats: table, json,
+    /// yaml, tsv, or a template such as '{{name}}\t{{branch_name}}'
+    #[arg(long, global = true, value_name = "FORMAT", conflicts_with = "json")]
+    pub format: Option<OutputFormat>,
+
+    /// Use an alternate config file (same as IMI_CONFIG)
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Use an alternate database URL (same as IMI_DB)
+    #[arg(long, global = true, value_name = "URL")]
+    pub db: Option<String>,
+
+    /// Refuse commands that create, remove, merge or push anything (same as
+    /// IMI_READ_ONLY=1 or [permissions] read_only)
+    #[arg(long, global = true)]
+    pub read_only: bool,
+
+    /// Print only results such as the created path, without banners or
+    /// progress; on by default when stdout isn't a terminal (same as
+    /// IMI_QUIET=1, IMI_QUIET=0 turns it off)
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+
+    /// Print how long DB queries, git operations, network requests and
+    /// filesystem scans took to stderr when the command finishes
+    #[arg(long, global = true)]
+    pub timings: bool,
 }
 
 #[derive(Subcommand)]
 pub enum Commands {
     /// Add a new worktree of specified type
     Add {
-        /// Worktree type (feat, fix, aiops, devops, review, or custom)
-        #[arg(value_name = "TYPE")]
-        worktree_type: String,
+        /// Worktree type (feat, fix, aiops, devops, review, or custom) and a
+        /// descriptive name (or PR number for review). Given only a name, the
+        /// type is inferred from its ticket prefix, the current worktree or
+        /// the configured default
+        #[arg(value_name = "[TYPE] NAME", num_args = 1..=2, required = true)]
+        args: Vec<String>,
 
-        /// Descriptive name for the worktree (or PR number for review)
-        name: String,
+        /// Worktree type, overriding inference when only a name is given
+        #[arg(short = 't', long = "type", value_name = "TYPE")]
+        worktree_type: Option<String>,
 
         /// Repository name (optional, uses current if not specified)
         #[arg(short, long)]
@@ -40,6 +86,63 @@ pub enum Commands {
         /// PR number (optional for 'review' type if name is a PR number)
         #[arg(long)]
         pr: Option<u32>,
+
+        /// Branch, tag or commit to create the branch from (defaults to the
+        /// type's base branch)
+        #[arg(long, value_name = "REF")]
+        base: Option<String>,
+
+        /// Don't install git hooks (lefthook, husky, pre-commit) or set
+        /// core.hooksPath in the new worktree
+        #[arg(long)]
+        no_hooks: bool,
+
+        /// Push the new branch and set it as upstream right away
+        #[arg(long)]
+        publish: bool,
+
+        /// Open the new worktree in the configured editor
+        #[arg(long)]
+        open: bool,
+
+        /// Start $SHELL in the new worktree (prints `cd <path>` when not
+        /// attached to a terminal, and `cd_command` in JSON)
+        #[arg(long)]
+        switch: bool,
+
+        /// Claim the new worktree for this agent, as `imi claim` does;
+        /// otherwise the [agent_assignment] rules may pick one
+        #[arg(long, visible_alias = "agent", value_name = "AGENT")]
+        claim: Option<String>,
+
+        /// Check out the branch if it already exists, locally or on origin,
+        /// instead of refusing
+        #[arg(long)]
+        adopt_existing: bool,
+
+        /// For review worktrees, check out only the directories the PR
+        /// changes (a sparse checkout), for large monorepos
+        #[arg(long)]
+        sparse_changed: bool,
+
+        /// Apply a patch or diff (`-` reads stdin) to the new worktree;
+        /// `git format-patch` output is applied as commits with `git am`
+        #[arg(long, value_name = "FILE")]
+        apply_patch: Option<String>,
+
+        /// Commit the applied diff, with an optional message
+        #[arg(
+            long,
+            value_name = "MESSAGE",
+            num_args = 0..=1,
+            default_missing_value = "",
+            requires = "apply_patch"
+        )]
+        commit: Option<String>,
+
+        /// Create the worktree even when the repository is over its size budget
+        #[arg(long)]
+        ignore_budget: bool,
     },
 
     /// Manage worktree types
@@ -57,13 +160,22 @@ pub enum Commands {
         repo: Option<String>,
     },
 
-    /// Create a worktree for reviewing a pull request
-    #[command(alias = "pr")]
+    /// Create a worktree for reviewing a pull request, or `sync` review
+    /// worktrees with new commits pushed to their pull requests
+    #[command(
+        alias = "pr",
+        args_conflicts_with_subcommands = true,
+        subcommand_negates_reqs = true
+    )]
     Review {
+        #[command(subcommand)]
+        action: Option<ReviewCommands>,
+
         /// Pull request number
-        pr_number: u32,
+        #[arg(required = true)]
+        pr_number: Option<u32>,
 
-        /// Repository: local name, {org}/{repo}, or {owner}/{repo} (defaults to delorenj/{repo} if org omitted)
+        /// Repository: local name, {org}/{repo}, or {owner}/{repo} (org defaults to [org_settings])
         /// When invoked outside a git project, queries iMi database for registered repos
         repo: Option<String>,
     },
@@ -105,6 +217,39 @@ pub enum Commands {
     Status {
         /// Repository name (optional, shows all repos if not specified)
         repo: Option<String>,
+
+        /// Show the latest GitHub Actions result for each worktree's branch
+        #[arg(long)]
+        ci: bool,
+
+        /// Order worktrees: age (oldest first), size (largest first), activity (idlest first) or name
+        #[arg(long, value_enum)]
+        sort: Option<WorktreeSort>,
+
+        /// Include archived repositories (see `imi repo archive`)
+        #[arg(long)]
+        all: bool,
+
+        /// Recompute every worktree's git status instead of reusing cached results
+        #[arg(long)]
+        no_cache: bool,
+
+        /// Look up each worktree's pull request on GitHub first; otherwise the
+        /// ones recorded by `imi sync` are shown
+        #[arg(long)]
+        refresh_prs: bool,
+
+        /// Keep the table on screen, redrawing it on file changes and at least
+        /// every SECS seconds (`--watch=10`; default 5)
+        #[arg(
+            long,
+            value_name = "SECS",
+            num_args = 0..=1,
+            require_equals = true,
+            default_missing_value = "5",
+            value_parser = clap::value_parser!(u64).range(1..)
+        )]
+        watch: Option<u64>,
     },
 
     /// List all active worktrees
@@ -120,6 +265,31 @@ pub enum Commands {
         /// List only projects/repositories (conflicts with --worktrees)
         #[arg(short = 'p', long, conflicts_with = "worktrees")]
         projects: bool,
+
+        /// List repositories with health indicators (trunk, behind origin, dirty/stale worktrees, last fetch)
+        #[arg(long, conflicts_with = "worktrees")]
+        health: bool,
+
+        /// Only repositories with this tag (see `imi repo tag`)
+        #[arg(long, conflicts_with_all = ["repo", "health"])]
+        tag: Option<String>,
+
+        /// Order worktrees: age (oldest first), size (largest first), activity (idlest first) or name
+        #[arg(long, value_enum, conflicts_with_all = ["projects", "health"])]
+        sort: Option<WorktreeSort>,
+
+        /// Include archived repositories (see `imi repo archive`)
+        #[arg(long)]
+        all: bool,
+
+        /// Recompute every worktree's git status instead of reusing cached results
+        #[arg(long)]
+        no_cache: bool,
+
+        /// Include repositories and worktrees registered on other machines
+        /// sharing this registry
+        #[arg(long)]
+        all_hosts: bool,
     },
 
     /// Remove a worktree
@@ -138,23 +308,50 @@ pub enum Commands {
         /// Keep remote branch after removing worktree (requires --keep-branch)
         #[arg(long)]
         keep_remote: bool,
+
+        /// Match the worktree name exactly instead of trying type prefixes
+        #[arg(long)]
+        exact: bool,
     },
 
     /// Close a worktree without merging (cancel the branch)
     #[command(alias = "cancel")]
     Close {
-        /// Name of the worktree to close
-        name: String,
+        /// Name of the worktree to close. With --merged, --all-of-type or
+        /// --matching, the repository to limit the bulk close to
+        #[arg(required_unless_present_any = ["merged", "all_of_type", "matching"])]
+        name: Option<String>,
 
         /// Repository name (optional, uses current repo if not specified)
         repo: Option<String>,
+
+        /// Close every worktree whose branch is merged into trunk
+        #[arg(long)]
+        merged: bool,
+
+        /// Close every worktree of this type (e.g. feat, fix)
+        #[arg(long, value_name = "TYPE")]
+        all_of_type: Option<String>,
+
+        /// Close every worktree whose name matches this glob (e.g. "feat-*-spike")
+        #[arg(long, value_name = "GLOB")]
+        matching: Option<String>,
+
+        /// Skip the confirmation prompt for bulk closes
+        #[arg(short, long)]
+        yes: bool,
+
+        /// Match the worktree name exactly instead of trying type prefixes
+        #[arg(long)]
+        exact: bool,
     },
 
     /// Navigate to a worktree or repository using fuzzy search
     Go {
-        /// Fuzzy search query (worktree name, branch name, or repo name)
+        /// Fuzzy search query (worktree name, branch name, or repo name).
+        /// Filter with `type:<name>` and `repo:<name>`, e.g. `type:feat repo:imi auth`.
         /// If not provided, shows an interactive picker
-        query: Option<String>,
+        query: Vec<String>,
 
         /// Exact repository name (skip fuzzy search within this repo)
         #[arg(short = 'r', long)]
@@ -167,22 +364,217 @@ pub enum Commands {
         /// Include inactive/closed worktrees in search
         #[arg(short = 'a', long)]
         include_inactive: bool,
+
+        /// Start $SHELL in the target with IMI_WORKTREE, IMI_REPO and IMI_TYPE
+        /// set instead of printing its path; exiting returns you here
+        #[arg(short = 's', long)]
+        shell: bool,
+
+        /// Include archived repositories (see `imi repo archive`)
+        #[arg(long)]
+        all: bool,
+    },
+
+    /// Print the absolute path of a worktree or repository (non-interactive)
+    ///
+    /// Exits with status 1 when nothing matches and 2 when the name is ambiguous
+    Which {
+        /// Worktree or repository name (same matching rules as `go`)
+        name: String,
+
+        /// Exact repository name to search within
+        #[arg(short = 'r', long)]
+        repo: Option<String>,
+
+        /// Limit search to worktrees only (exclude trunk/repos)
+        #[arg(short = 'w', long)]
+        worktrees_only: bool,
+
+        /// Include inactive/closed worktrees in search
+        #[arg(short = 'a', long)]
+        include_inactive: bool,
+
+        /// Include archived repositories (see `imi repo archive`)
+        #[arg(long)]
+        all: bool,
+    },
+
+    /// List the most recently used worktrees across repositories; give a
+    /// number from the list to print that worktree's path (for `cd`)
+    Recent {
+        /// Entry to print the path of (1 = most recent)
+        index: Option<usize>,
+
+        /// How many worktrees to list
+        #[arg(short = 'n', long, default_value_t = 10)]
+        limit: usize,
+
+        /// Only worktrees of this repository
+        #[arg(short = 'r', long)]
+        repo: Option<String>,
+
+        /// Pick one interactively and print its path
+        #[arg(short = 'i', long, conflicts_with = "index")]
+        pick: bool,
+    },
+
+    /// Fork a worktree: new branch from the source worktree's current HEAD
+    Copy {
+        /// Source worktree name (e.g., feat-auth or auth)
+        source: String,
+
+        /// Name for the new worktree; it gets the source's type prefix
+        name: String,
+
+        /// Repository name
+        #[arg(short = 'r', long)]
+        repo: Option<String>,
+
+        /// Carry over the source's uncommitted changes to tracked files
+        #[arg(short = 'u', long)]
+        include_uncommitted: bool,
+
+        /// Clone the source's untracked build caches ([fork_caches] dirs such
+        /// as target/ and node_modules) copy-on-write where the filesystem
+        /// supports it, so the fork builds warm
+        #[arg(long, conflicts_with = "no_caches")]
+        caches: bool,
+
+        /// Don't clone build caches even when [fork_caches] enabled is set
+        #[arg(long)]
+        no_caches: bool,
+
+        /// When a cache can't be cloned copy-on-write: hard-link its files,
+        /// copy them (up to [fork_caches] max_copy_mb) or skip it; implies
+        /// --caches
+        #[arg(long, value_name = "MODE", conflicts_with = "no_caches")]
+        cache_fallback: Option<CacheFallback>,
+
+        /// Create the worktree even when the repository is over its size budget
+        #[arg(long)]
+        ignore_budget: bool,
+    },
+
+    /// Open a worktree in the editor configured under [editor]
+    Open {
+        /// Worktree name (defaults to the current worktree)
+        name: Option<String>,
+
+        /// Repository name
+        #[arg(short = 'r', long)]
+        repo: Option<String>,
+    },
+
+    /// Show how worktree branches relate to trunk and to each other
+    Graph {
+        /// Repository name (optional, uses current repo if not specified)
+        repo: Option<String>,
+
+        /// Emit Graphviz DOT instead of a tree
+        #[arg(long)]
+        dot: bool,
+    },
+
+    /// Show the effective configuration, resolved paths, detected context and
+    /// tool versions, with secrets masked
+    Env,
+
+    /// Check what this machine supports (symlinks, long paths, case
+    /// sensitivity, git features, gh) and save the report other commands
+    /// use to fall back; exits 1 when iMi can't run here
+    Verify {
+        /// Directory to probe the filesystem in (default: the primary system root)
+        #[arg(long)]
+        dir: Option<PathBuf>,
+
+        /// Also exit 1 when any capability is missing, e.g. in CI
+        #[arg(long)]
+        strict: bool,
     },
 
+    /// Inspect iMi's own configuration files
+    #[command(subcommand)]
+    Config(ConfigCommands),
+
+    /// User-defined commands from `[aliases]` in the config
+    #[command(subcommand)]
+    Alias(AliasCommands),
+
     /// Start real-time monitoring of worktree activities
     Monitor {
         /// Repository name (optional, monitors all repos if not specified)
         repo: Option<String>,
+
+        /// Seconds between git status scans, for every repository
+        #[arg(long, value_name = "SECS")]
+        interval: Option<u64>,
+
+        /// Only report file events; skip git status scans
+        #[arg(long)]
+        no_git_status: bool,
+
+        /// Skip a repository (repeatable)
+        #[arg(long, value_name = "REPO")]
+        exclude: Vec<String>,
+
+        /// Skip status scans while the load average per CPU is above this
+        #[arg(long, value_name = "LOAD")]
+        max_load: Option<f64>,
     },
 
     /// Sync database with actual Git worktrees
     Sync {
         /// Repository name (optional, syncs all repos if not specified)
         repo: Option<String>,
+
+        /// Also rebase every worktree onto the updated trunk; conflicted
+        /// ones are left to resolve and finished with --continue
+        #[arg(long, conflicts_with_all = ["resume", "abort"])]
+        rebase_worktrees: bool,
+
+        /// Resume the unfinished --rebase-worktrees batch after resolving
+        /// conflicts
+        #[arg(long = "continue", conflicts_with = "abort")]
+        resume: bool,
+
+        /// Abort the stopped rebases of the unfinished batch
+        #[arg(long)]
+        abort: bool,
+    },
+
+    /// Recover after repositories moved (e.g. a backup restored under another
+    /// home): fix database paths, git's worktree links and broken symlinks
+    Repair {
+        /// Repository name (all registered repositories if not specified)
+        #[arg(long)]
+        repo: Option<String>,
+
+        /// Rewrite registered paths under OLD to NEW before repairing, e.g.
+        /// --remap /home/jarad=/Users/jarad
+        #[arg(long, value_name = "OLD=NEW")]
+        remap: Option<PathRemap>,
+
+        /// First copy this machine's registrations from HOST, another
+        /// machine sharing the registry, with --remap applied to its paths
+        #[arg(long, value_name = "HOST", requires = "remap")]
+        from_host: Option<String>,
     },
 
-    /// Repair repository paths in database (auto-detects moved repositories)
-    Repair,
+    /// Adopt worktrees made with plain `git worktree add`: infer each one's
+    /// type and name, confirm or edit them, and register them without
+    /// touching the directories
+    ImportWorktrees {
+        /// Repository name (optional, uses current repo if not specified)
+        repo: Option<String>,
+
+        /// Register every worktree whose type could be inferred without asking
+        #[arg(short, long)]
+        yes: bool,
+
+        /// Only show the inferred types and names
+        #[arg(long)]
+        dry_run: bool,
+    },
 
     /// Run system health checks
     Doctor {
@@ -195,119 +587,550 @@ pub enum Commands {
         verbose: bool,
     },
 
-    /// Manage the project registry
+    /// Diagnostics for bug reports
     #[command(subcommand)]
-    Registry(RegistryCommands),
+    Debug(DebugCommands),
 
-    /// Initialize iMi in the current directory or clone from GitHub (format: owner/repo)
-    Init {
-        /// GitHub repository to clone (format: owner/repo), or path to existing repository
-        repo: Option<String>,
+    /// Background daemon tasks (periodic fetch of registered repositories)
+    #[command(subcommand)]
+    Daemon(DaemonCommands),
 
-        /// Force initialization even if configuration already exists
+    /// Serve repositories, worktrees and live activity to dashboards
+    Serve {
+        /// Read-only JSON API: /repos, /worktrees, /status/<repo> and
+        /// /events (server-sent events); needs the `http` build feature
         #[arg(long)]
-        force: bool,
-    },
+        http: bool,
 
-    /// Generate shell completions for iMi
-    Completion {
-        /// Shell to generate completions for
-        #[arg(value_enum)]
-        shell: Shell,
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1:7878")]
+        addr: std::net::SocketAddr,
     },
 
-    /// Clean up stale worktree references from Git
-    #[command(alias = "cleanup")]
-    Prune {
-        /// Repository name (optional, uses current repo if not specified)
+    /// Inspect the audit log of iMi command invocations
+    #[command(subcommand)]
+    Audit(AuditCommands),
+
+    /// Print monitor, daemon and command events as NDJSON (worktree_created,
+    /// status_changed, new_commit, merge_completed, ...)
+    Events {
+        /// Only events of this repository
+        #[arg(short, long)]
         repo: Option<String>,
 
-        /// Show what would be removed without actually removing
-        #[arg(long)]
-        dry_run: bool,
+        /// Only events with this name (repeatable)
+        #[arg(long = "event", value_name = "NAME")]
+        events: Vec<String>,
 
-        /// Remove orphaned directories without confirmation
-        #[arg(long)]
-        force: bool,
+        /// Keep printing new events as they happen
+        #[arg(short, long)]
+        follow: bool,
+
+        /// Recorded events to print first [default: 20, or 0 with --follow]
+        #[arg(short = 'n', long)]
+        lines: Option<usize>,
     },
 
-    /// Merge a worktree into trunk-main and close it
-    Merge {
-        /// Name of the worktree to merge (optional, defaults to current branch)
-        name: Option<String>,
+    /// Manage the project registry
+    #[command(subcommand)]
+    Registry(RegistryCommands),
 
-        /// Repository name (optional, uses current repo if not specified)
-        repo: Option<String>,
+    /// Tag registered repositories to group them
+    #[command(subcommand)]
+    Repo(RepoCommands),
+
+    /// Clean up remote branches
+    #[command(subcommand)]
+    Branches(BranchCommands),
+
+    /// Git hooks that log commits and branch switches to the activity log,
+    /// for agents that use plain git
+    #[command(subcommand)]
+    Hooks(HookCommands),
+
+    /// Log a commit or checkout; run by the hooks `imi hooks install` adds
+    #[command(name = "_record-activity", hide = true)]
+    RecordActivity {
+        /// The git hook running (post-commit or post-checkout)
+        hook: String,
+
+        /// Arguments git passed to the hook
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
     },
 
-    /// Create a new project with boilerplate scaffolding
-    Project {
-        #[command(subcommand)]
-        command: ProjectCommands,
+    /// Run a command in the trunk of every registered repository, or of
+    /// those with --tag
+    Exec {
+        /// Only repositories with this tag
+        #[arg(long)]
+        tag: Option<String>,
+
+        /// Command and arguments, after `--`
+        #[arg(last = true, required = true)]
+        command: Vec<String>,
     },
 
-    /// Claim exclusive access to a worktree for agent work
-    Claim {
-        /// Name of the worktree to claim
+    /// Run a command in a worktree and keep its output and exit code as an
+    /// artifact, e.g. `imi check feat-auth -- cargo test`
+    Check {
+        /// Worktree name
         name: String,
 
-        /// Yi agent identifier
-        #[arg(long = "yi-id")]
-        yi_id: String,
-
         /// Repository name (optional, uses current repo if not specified)
         #[arg(short, long)]
         repo: Option<String>,
 
-        /// Force claim even if already claimed by another agent
-        #[arg(long)]
-        force: bool,
+        /// Only record the output instead of also printing it
+        #[arg(short, long)]
+        silent: bool,
+
+        /// Command and arguments, after `--`
+        #[arg(last = true, required = true)]
+        command: Vec<String>,
     },
 
-    /// Verify lock ownership for a worktree
-    #[command(alias = "check-lock")]
-    VerifyLock {
-        /// Name of the worktree to verify
-        name: String,
+    /// Show the output kept from `imi check` and `imi exec` runs
+    #[command(subcommand)]
+    Artifacts(ArtifactCommands),
 
-        /// Yi agent identifier to check ownership
-        #[arg(long = "yi-id")]
-        yi_id: String,
+    /// Find the commit that broke a command with `git bisect run`, in a
+    /// temporary worktree that is removed afterwards
+    Bisect {
+        /// A commit where the command fails
+        #[arg(long, default_value = "HEAD")]
+        bad: String,
+
+        /// A commit where the command passes (repeatable)
+        #[arg(long, required = true)]
+        good: Vec<String>,
 
         /// Repository name (optional, uses current repo if not specified)
         #[arg(short, long)]
         repo: Option<String>,
-    },
 
-    /// Release a worktree after completing work
-    Release {
-        /// Name of the worktree to release
-        name: String,
+        /// Keep the temporary worktree for inspection
+        #[arg(long)]
+        keep: bool,
 
-        /// Yi agent identifier (must match the agent that claimed it)
-        #[arg(long = "yi-id")]
-        yi_id: String,
+        /// Test command, after `--`; exit 0 is good, 125 skips the commit,
+        /// anything else is bad
+        #[arg(last = true, required = true)]
+        command: Vec<String>,
+    },
+
+    /// Run a command in a throwaway worktree that is removed afterwards,
+    /// exiting with the command's status
+    Temp {
+        /// Commit, branch or tag to check out [default: the default branch]
+        #[arg(long)]
+        base: Option<String>,
 
         /// Repository name (optional, uses current repo if not specified)
         #[arg(short, long)]
         repo: Option<String>,
+
+        /// Command and arguments, after `--`
+        #[arg(last = true, required = true)]
+        command: Vec<String>,
     },
 
-    /// Manage worktree metadata for task/source linkage
-    #[command(subcommand)]
-    Metadata(MetadataCommands),
+    /// Initialize iMi in the current directory or clone from GitHub (format: owner/repo)
+    Init {
+        /// Repository to clone (GitHub owner/repo or a git remote URL), or
+        /// path to existing repository
+        repo: Option<String>,
 
-    /// Migrate registered repositories into office layout
-    MigrateOffice {
-        /// Repository name (optional, migrates all registered repositories if omitted)
+        /// Force initialization even if configuration already exists
+        #[arg(long)]
+        force: bool,
+
+        /// Don't contact the remote to verify it is reachable and to detect
+        /// its default branch
+        #[arg(long)]
+        offline: bool,
+
+        /// On first run, write the default configuration instead of asking
         #[arg(short, long)]
-        repo: Option<String>,
+        yes: bool,
 
-        /// Show planned migration actions without moving files
+        #[command(flatten)]
+        clone: CloneArgs,
+    },
+
+    /// Clone several GitHub repositories, or an organization's, in parallel
+    /// into the office layout and register each one; rerun to resume
+    Clone {
+        /// Repositories to clone: GitHub owner/repo, or any git remote URL
+        /// (https, ssh, git@host:group/sub/repo)
+        repos: Vec<String>,
+
+        /// Clone every repository of this GitHub organization or user
         #[arg(long)]
-        dry_run: bool,
+        org: Option<String>,
 
-        /// Continue migration when safe fallbacks are available
+        /// With --org, only repositories tagged with this topic
+        #[arg(long, requires = "org")]
+        topic: Option<String>,
+
+        /// Number of clones to run at once
+        #[arg(short, long, default_value_t = 4)]
+        jobs: usize,
+
+        /// Register again even if the repository is already registered
+        #[arg(long)]
+        force: bool,
+
+        #[command(flatten)]
+        clone: CloneArgs,
+    },
+
+    /// Pick repositories of a GitHub organization or user from a list, then
+    /// clone and register them in parallel
+    Onboard {
+        /// Organization or user, e.g. gh:acme (aliases from [org_settings] apply)
+        target: String,
+
+        /// Only list repositories tagged with this topic
+        #[arg(long)]
+        topic: Option<String>,
+
+        /// Only list repositories whose main language is this one
+        #[arg(long)]
+        language: Option<String>,
+
+        /// Take every listed repository without asking
+        #[arg(long)]
+        all: bool,
+
+        /// Number of clones to run at once
+        #[arg(short, long, default_value_t = 4)]
+        jobs: usize,
+
+        #[command(flatten)]
+        clone: CloneArgs,
+    },
+
+    /// Fetch the full history of a repository cloned with --depth, --filter
+    /// or --single-branch
+    Unshallow {
+        /// Repository name (optional, uses current repo if not specified)
+        repo: Option<String>,
+    },
+
+    /// Move worktrees where `[worktree_layout] worktree_dir` puts new ones
+    /// (`--relayout`), with git's worktree records
+    Mv {
+        /// Move existing worktrees into the configured layout
+        #[arg(long, required = true)]
+        relayout: bool,
+
+        /// Repository name (optional, relayouts every repository if omitted)
+        #[arg(short, long)]
+        repo: Option<String>,
+
+        /// Show what would move without moving anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Generate shell completions for iMi
+    Completion {
+        /// Shell to generate completions for
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+
+    /// Clean up stale worktree references from Git
+    #[command(alias = "cleanup")]
+    Prune {
+        /// Repository name (optional, uses current repo if not specified)
+        repo: Option<String>,
+
+        /// Show what would be removed without actually removing
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Remove orphaned directories without confirmation
+        #[arg(long)]
+        force: bool,
+
+        /// Also remove orphaned directories holding unpushed commits, stashes
+        /// or important untracked files
+        #[arg(long)]
+        include_risky: bool,
+    },
+
+    /// List, restore or empty removed worktrees kept in the trash
+    #[command(subcommand)]
+    Trash(TrashCommands),
+
+    /// Close worktrees whose GitHub pull request was merged (branches are kept)
+    Clean {
+        /// Repository name (optional, cleans all repos if not specified)
+        repo: Option<String>,
+
+        /// Skip the confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+
+        /// Also close worktrees holding unpushed commits, stashes or important
+        /// untracked files
+        #[arg(long)]
+        include_risky: bool,
+    },
+
+    /// Show worktree disk usage against the repository's size budget
+    SizeBudget {
+        /// Repository name (optional, uses current repo if not specified)
+        repo: Option<String>,
+
+        /// Measure every worktree again instead of using cached sizes
+        #[arg(long)]
+        refresh: bool,
+    },
+
+    /// Merge a worktree into trunk-main and close it
+    Merge {
+        /// Name of the worktree to merge (optional, defaults to current branch)
+        name: Option<String>,
+
+        /// Repository name (optional, uses current repo if not specified)
+        repo: Option<String>,
+
+        /// Add the branch's pull request to the GitHub merge queue and wait
+        /// for it to land (same as --strategy queue)
+        #[arg(long, conflicts_with = "strategy")]
+        queue: bool,
+
+        /// How to merge, overriding the repository's [merge_policy]
+        #[arg(long, value_enum)]
+        strategy: Option<MergeStrategy>,
+
+        /// Roll back a merge that stopped on conflicts in the trunk worktree
+        #[arg(long, conflicts_with_all = ["queue", "strategy"])]
+        abort: bool,
+
+        /// Merge these worktrees in order, rebasing each onto the trunk the
+        /// previous ones updated; stops at the first failure
+        #[arg(long, num_args = 1.., value_name = "WORKTREE", conflicts_with_all = ["name", "queue", "strategy", "abort"])]
+        train: Vec<String>,
+    },
+
+    /// Create a new project with boilerplate scaffolding
+    Project {
+        #[command(subcommand)]
+        command: ProjectCommands,
+    },
+
+    /// Agent assignment rules and agent instruction files
+    #[command(subcommand, visible_alias = "agents")]
+    Agent(AgentCommands),
+
+    /// Claim exclusive access to a worktree for agent work
+    Claim {
+        /// Name of the worktree to claim
+        name: String,
+
+        /// Yi agent identifier
+        #[arg(long = "yi-id")]
+        yi_id: String,
+
+        /// Repository name (optional, uses current repo if not specified)
+        #[arg(short, long)]
+        repo: Option<String>,
+
+        /// Force claim even if already claimed by another agent
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Verify lock ownership for a worktree
+    #[command(alias = "check-lock")]
+    VerifyLock {
+        /// Name of the worktree to verify
+        name: String,
+
+        /// Yi agent identifier to check ownership
+        #[arg(long = "yi-id")]
+        yi_id: String,
+
+        /// Repository name (optional, uses current repo if not specified)
+        #[arg(short, long)]
+        repo: Option<String>,
+    },
+
+    /// Release a worktree after completing work
+    Release {
+        /// Name of the worktree to release
+        name: String,
+
+        /// Yi agent identifier (must match the agent that claimed it)
+        #[arg(long = "yi-id")]
+        yi_id: String,
+
+        /// Repository name (optional, uses current repo if not specified)
+        #[arg(short, long)]
+        repo: Option<String>,
+    },
+
+    /// Finish an add or merge that was interrupted midway
+    Resume {
+        /// Worktree name or operation id prefix (defaults to the only interrupted operation)
+        operation: Option<String>,
+    },
+
+    /// Undo the completed steps of an interrupted add or merge
+    Rollback {
+        /// Worktree name or operation id prefix (defaults to the only interrupted operation)
+        operation: Option<String>,
+
+        /// Skip the confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Manage worktree metadata for task/source linkage
+    #[command(subcommand)]
+    Metadata(MetadataCommands),
+
+    /// Show everything about one worktree: record, git state, divergence,
+    /// pull request, disk usage, notes, recent commits, hooks and symlinks
+    Show {
+        /// Worktree name
+        name: String,
+
+        /// Repository name (optional, uses current repo if not specified)
+        #[arg(short, long)]
+        repo: Option<String>,
+    },
+
+    /// Leave a note on a worktree for whoever picks it up next, or list its
+    /// notes when no text is given
+    Note {
+        /// Worktree name
+        name: String,
+
+        /// Note text, e.g. "waiting on API design"
+        text: Option<String>,
+
+        /// Repository name (optional, uses current repo if not specified)
+        #[arg(short, long)]
+        repo: Option<String>,
+    },
+
+    /// Send messages to the agents and people working in a worktree
+    #[command(subcommand)]
+    Msg(MsgCommands),
+
+    /// Migrate registered repositories into office layout
+    MigrateOffice {
+        /// Repository name (optional, migrates all registered repositories if omitted)
+        #[arg(short, long)]
+        repo: Option<String>,
+
+        /// Show planned migration actions without moving files
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Continue migration when safe fallbacks are available
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+impl Commands {
+    /// Whether the command can change worktrees, branches, remotes or the
+    /// registry; these are refused in read-only mode. Anything not listed
+    /// as inspecting only counts as mutating.
+    pub fn mutates(&self) -> bool {
+        !matches!(
+            self,
+            Commands::Status { .. }
+                | Commands::List { .. }
+                | Commands::Go { .. }
+                | Commands::Which { .. }
+                | Commands::Recent { .. }
+                | Commands::Open { .. }
+                | Commands::Graph { .. }
+                | Commands::Trunk { .. }
+                | Commands::Env
+                | Commands::Verify { .. }
+                | Commands::Config(_)
+                | Commands::Alias(_)
+                | Commands::Agent(AgentCommands::Rules(_))
+                | Commands::Monitor { .. }
+                | Commands::Doctor { .. }
+                | Commands::Debug(_)
+                | Commands::Serve { .. }
+                | Commands::Audit(_)
+                | Commands::Events { .. }
+                | Commands::Completion { .. }
+                | Commands::SizeBudget { .. }
+                | Commands::VerifyLock { .. }
+                | Commands::Show { .. }
+                | Commands::Note { text: None, .. }
+                | Commands::Msg(MsgCommands::Read {
+                    keep_unread: true,
+                    ..
+                })
+                | Commands::Artifacts(_)
+                | Commands::Prune { dry_run: true, .. }
+                | Commands::MigrateOffice { dry_run: true, .. }
+                | Commands::ImportWorktrees { dry_run: true, .. }
+                | Commands::Mv { dry_run: true, .. }
+                | Commands::Daemon(DaemonCommands::Status | DaemonCommands::Tasks)
+                | Commands::Trash(TrashCommands::List { .. })
+                | Commands::Registry(RegistryCommands::Stats)
+                | Commands::Repo(
+                    RepoCommands::Tags { .. }
+                        | RepoCommands::Show { .. }
+                        | RepoCommands::Stats { .. }
+                )
+                | Commands::Branches(BranchCommands::PruneRemote { dry_run: true, .. })
+                | Commands::Metadata(MetadataCommands::Get { .. })
+                | Commands::Types(
+                    TypeCommands::List { .. }
+                        | TypeCommands::Export { .. }
+                        | TypeCommands::Import { dry_run: true, .. }
+                )
+        )
+    }
+
+    /// Long operations that stop at a safe point on the first Ctrl-C (see
+    /// `cancel`); everything else keeps the default Ctrl-C
+    #[allow(deprecated)]
+    pub fn cancellable(&self) -> bool {
+        matches!(
+            self,
+            Commands::Add { .. }
+                | Commands::Feat { .. }
+                | Commands::Review { .. }
+                | Commands::Fix { .. }
+                | Commands::Aiops { .. }
+                | Commands::Devops { .. }
+                | Commands::Init { .. }
+                | Commands::Clone { .. }
+                | Commands::Merge { .. }
+                | Commands::Prune { .. }
+        )
+    }
+}
+
+#[derive(Subcommand)]
+pub enum ReviewCommands {
+    /// Move pr-* worktrees to their pull request's latest head and show the
+    /// commits and files changed since the last sync; force-pushes are
+    /// flagged
+    Sync {
+        /// Pull request number (all review worktrees when omitted)
+        pr_number: Option<u32>,
+
+        /// Repository name (optional, uses current repo if not specified)
+        #[arg(short, long)]
+        repo: Option<String>,
+
+        /// Update even over uncommitted changes or local commits, discarding them
         #[arg(long)]
         force: bool,
     },
@@ -373,57 +1196,625 @@ pub enum MetadataCommands {
 }
 
 #[derive(Subcommand)]
-pub enum RegistryCommands {
-    /// Sync filesystem with database - discover and register all projects
-    Sync {
-        /// Root directory to scan (defaults to ~/code)
-        scan_root: Option<String>,
-    },
+pub enum AgentCommands {
+    /// The [agent_assignment] rules `imi add` claims new worktrees by
+    #[command(subcommand)]
+    Rules(AgentRuleCommands),
 
-    /// Show registry statistics
-    Stats,
+    /// Generate agent instruction files (AGENTS.md, CLAUDE.md) into the
+    /// repository's sync/user from templates, with its conventions, worktree
+    /// types and iMi usage, and link them into every worktree
+    Scaffold {
+        /// Repository name (optional, uses current repo if not specified)
+        #[arg(short, long)]
+        repo: Option<String>,
+
+        /// Regenerate files that already exist, overwriting edits
+        #[arg(long)]
+        force: bool,
+
+        /// Only write the files to sync/user, without linking them
+        #[arg(long)]
+        no_link: bool,
+    },
 }
 
 #[derive(Subcommand)]
-pub enum TypeCommands {
-    /// List all available worktree types
-    #[command(alias = "ls")]
+pub enum AgentRuleCommands {
+    /// Show the rules in the order they are tried
     List,
 
-    /// Add a new worktree type
-    Add {
-        /// Type name (lowercase, alphanumeric, hyphens)
+    /// Show which rule would assign a worktree, without creating it
+    Test {
+        /// Worktree type, e.g. fix
+        worktree_type: String,
+
+        /// Name as given to `imi add`, e.g. ml-ranking
         name: String,
 
-        /// Branch prefix (defaults to <type>/)
+        /// Repository name (optional, uses current repo if not specified)
+        #[arg(short, long)]
+        repo: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum DaemonCommands {
+    /// Run the background fetch and maintenance schedulers in the foreground
+    Run {
+        /// Fetch every repository once and exit
+        #[arg(long, conflicts_with = "metrics")]
+        once: bool,
+
+        /// Serve Prometheus metrics on ADDR (`:9187` is localhost only; use
+        /// `0.0.0.0:9187` to listen on every interface)
+        #[arg(long, value_name = "ADDR", value_parser = crate::metrics::parse_listen_addr)]
+        metrics: Option<std::net::SocketAddr>,
+    },
+
+    /// Show the last background fetch for each repository
+    Status,
+
+    /// Show scheduled maintenance tasks with their last and next run
+    Tasks,
+}
+
+#[derive(Subcommand)]
+pub enum AuditCommands {
+    /// Show the most recent invocations
+    Tail {
+        /// Number of entries to show
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+    },
+
+    /// Search invocations by text (command, args, cwd, agent, error)
+    Search {
+        /// Case-insensitive text to look for
+        pattern: Option<String>,
+
+        /// Only invocations of this subcommand (e.g. merge)
         #[arg(long)]
-        branch_prefix: Option<String>,
+        command: Option<String>,
 
-        /// Worktree prefix (defaults to <type>-)
+        /// Only failed invocations
         #[arg(long)]
-        worktree_prefix: Option<String>,
+        failed: bool,
 
-        /// Description
-        #[arg(short, long)]
-        description: Option<String>,
+        /// Maximum number of entries to show
+        #[arg(short = 'n', long, default_value_t = 50)]
+        limit: usize,
     },
+}
 
-    /// Remove a worktree type
-    #[command(alias = "rm")]
-    Remove {
-        /// Type name to remove
-        name: String,
+#[derive(Subcommand)]
+pub enum ConfigCommands {
+    /// Check the global and project config files for syntax and type errors,
+    /// unknown keys, conflicting options and bad schedules; exits 1 on errors
+    Validate {
+        /// Check only this file
+        file: Option<PathBuf>,
+
+        /// Also check that configured paths exist, and exit 1 on warnings
+        #[arg(long)]
+        strict: bool,
     },
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use clap::Parser;
+#[derive(Subcommand)]
+pub enum AliasCommands {
+    /// List the configured aliases and the commands they expand to
+    List,
+}
 
-    #[test]
-    fn parses_metadata_set_command() {
-        let cli = Cli::try_parse_from([
+#[derive(Subcommand)]
+pub enum DebugCommands {
+    /// Collect anonymized diagnostics into a .tar.gz to attach to a bug
+    /// report: version, masked config, database schema and row counts, the
+    /// last log lines, git/gh versions and recent operations
+    Bundle {
+        /// Where to write the tarball (default: imi-debug-<timestamp>.tar.gz)
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+
+        /// Also include a full pg_dump of the database; it is not anonymized
+        #[arg(long)]
+        include_db: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum MsgCommands {
+    /// Leave a message on a worktree, e.g. "please rebase"
+    Send {
+        /// Worktree name
+        worktree: String,
+
+        /// Message text
+        text: String,
+
+        /// Repository name (optional, uses current repo if not specified)
+        #[arg(short, long)]
+        repo: Option<String>,
+
+        /// Also deliver it through the [alert_settings] notification
+        /// channels (desktop notification, notify_command)
+        #[arg(long)]
+        notify: bool,
+    },
+
+    /// Show a worktree's messages, oldest first, and mark them read
+    Read {
+        /// Worktree name (defaults to the current worktree)
+        worktree: Option<String>,
+
+        /// Repository name (optional, uses current repo if not specified)
+        #[arg(short, long)]
+        repo: Option<String>,
+
+        /// Only messages not read yet
+        #[arg(long)]
+        unread: bool,
+
+        /// Leave the messages unread
+        #[arg(long)]
+        keep_unread: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ArtifactCommands {
+    /// List a worktree's runs, newest first, or the last run of every
+    /// worktree when no name is given
+    List {
+        /// Worktree name
+        name: Option<String>,
+
+        /// Repository name (optional, uses current repo if not specified)
+        #[arg(short, long)]
+        repo: Option<String>,
+    },
+
+    /// Print the output of a worktree's last run, or of the run with --id
+    Show {
+        /// Worktree name
+        name: String,
+
+        /// Artifact id, or a prefix of it
+        #[arg(long)]
+        id: Option<String>,
+
+        /// Repository name (optional, uses current repo if not specified)
+        #[arg(short, long)]
+        repo: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum TrashCommands {
+    /// Show trashed worktrees, newest first
+    List {
+        /// Repository name (optional, lists every repository if not specified)
+        repo: Option<String>,
+    },
+
+    /// Put a trashed worktree back, uncommitted files included
+    Restore {
+        /// Trash id, or a worktree name for its most recent removal
+        name: String,
+
+        /// Repository name (optional, searches every repository if not specified)
+        #[arg(short, long)]
+        repo: Option<String>,
+    },
+
+    /// Delete trashed worktrees permanently
+    Empty {
+        /// Repository name (optional, empties every repository if not specified)
+        repo: Option<String>,
+
+        /// Only delete entries past the retention period
+        #[arg(long)]
+        expired: bool,
+
+        /// Skip the confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum RegistryCommands {
+    /// Sync filesystem with database - discover and register all projects
+    Sync {
+        /// Root directory to scan (defaults to ~/code)
+        scan_root: Option<String>,
+    },
+
+    /// Show registry statistics
+    Stats,
+}
+
+#[derive(Subcommand)]
+pub enum RepoCommands {
+    /// Add tags to a repository
+    Tag {
+        /// Repository name
+        repo: String,
+
+        /// Tags to add
+        #[arg(required = true)]
+        tags: Vec<String>,
+    },
+
+    /// Remove tags from a repository
+    Untag {
+        /// Repository name
+        repo: String,
+
+        /// Tags to remove
+        #[arg(required = true)]
+        tags: Vec<String>,
+    },
+
+    /// Show repository tags
+    Tags {
+        /// Repository name (optional, shows every tagged repository)
+        repo: Option<String>,
+    },
+
+    /// Show what is recorded about a repository and where it no longer
+    /// matches the trunk on disk
+    Show {
+        /// Repository name
+        repo: String,
+    },
+
+    /// Hide a repository from list, status, go and exec without
+    /// unregistering it; --all brings it back into those
+    Archive {
+        /// Repository name
+        repo: String,
+    },
+
+    /// Show an archived repository in listings again
+    Unarchive {
+        /// Repository name
+        repo: String,
+    },
+
+    /// Fix a repository's recorded remote URL, default branch or trunk path
+    Set {
+        /// Repository name
+        repo: String,
+
+        /// Remote URL (also set on the trunk's remote)
+        #[arg(long, value_name = "URL")]
+        remote_url: Option<String>,
+
+        /// Default branch; must exist locally or on origin
+        #[arg(long, value_name = "BRANCH")]
+        default_branch: Option<String>,
+
+        /// Trunk path; must be a git repository. Worktree paths are repaired
+        #[arg(long)]
+        path: Option<PathBuf>,
+    },
+
+    /// Show worktree counts and, with --git, the trunk's code activity
+    Stats {
+        /// Repository name
+        repo: String,
+
+        /// Add commits per week, authors, hot directories and PR merge times
+        /// (computed from trunk; cached until it moves)
+        #[arg(long)]
+        git: bool,
+
+        /// Weeks of history, the current one included
+        #[arg(long, default_value_t = 12, value_parser = clap::value_parser!(u32).range(1..))]
+        weeks: u32,
+
+        /// Authors and directories to list
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+
+        /// Recompute instead of using cached activity
+        #[arg(long)]
+        refresh: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum BranchCommands {
+    /// Delete branches with a worktree type's prefix (feat/, fix/, ...) that
+    /// no registered worktree uses and that are merged into the default
+    /// branch from the push remote (origin unless `[remotes]` says
+    /// otherwise), after confirming; the default branch and `[branch_cleanup]
+    /// protected` patterns are never touched
+    PruneRemote {
+        /// Repository name (optional, uses current repo if not specified)
+        repo: Option<String>,
+
+        /// Also delete branches not merged into the default branch, which
+        /// may be someone else's work in progress
+        #[arg(long)]
+        include_unmerged: bool,
+
+        /// Only list the branches that would be deleted
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Delete without asking
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum HookCommands {
+    /// Add post-commit and post-checkout hooks to the repository's
+    /// worktrees, alongside any hooks already there
+    Install {
+        /// Repository name (all registered repositories if not specified)
+        #[arg(long)]
+        repo: Option<String>,
+    },
+
+    /// Remove the hooks `imi hooks install` added, leaving the rest
+    Uninstall {
+        /// Repository name (all registered repositories if not specified)
+        #[arg(long)]
+        repo: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum TypeCommands {
+    /// List all available worktree types
+    #[command(alias = "ls")]
+    List {
+        /// Show per-type defaults (TTL, base branch, template, checks, auto-assign)
+        #[arg(short = 'l', long)]
+        detailed: bool,
+    },
+
+    /// Add a new worktree type
+    Add {
+        /// Type name (lowercase, alphanumeric, hyphens)
+        name: String,
+
+        /// Branch prefix (defaults to <type>/)
+        #[arg(long)]
+        branch_prefix: Option<String>,
+
+        /// Worktree prefix (defaults to <type>-)
+        #[arg(long)]
+        worktree_prefix: Option<String>,
+
+        /// Description
+        #[arg(short, long)]
+        description: Option<String>,
+    },
+
+    /// Edit a worktree type's prefixes, description and defaults
+    Edit {
+        /// Type name to edit
+        name: String,
+
+        /// New branch prefix (custom types only)
+        #[arg(long)]
+        branch_prefix: Option<String>,
+
+        /// New worktree prefix (custom types only)
+        #[arg(long)]
+        worktree_prefix: Option<String>,
+
+        /// New description
+        #[arg(short, long)]
+        description: Option<String>,
+
+        /// Days before worktrees of this type are considered stale
+        #[arg(long)]
+        ttl_days: Option<i32>,
+
+        /// Branch new worktrees of this type are based on
+        #[arg(long)]
+        base_branch: Option<String>,
+
+        /// Reset an optional field (repeatable)
+        #[arg(long, value_enum)]
+        clear: Vec<TypeField>,
+    },
+
+    /// Set the commit message template installed in new worktrees of a type
+    /// (as their own commit.template); without a file, a Conventional Commits
+    /// template with the type's prefix and a ticket trailer
+    SetCommitTemplate {
+        /// Type name
+        name: String,
+
+        /// Template file; {{ticket}}, {{worktree_name}} and {{branch_name}}
+        /// are filled in per worktree
+        file: Option<PathBuf>,
+
+        /// Remove the type's template
+        #[arg(long, conflicts_with = "file")]
+        clear: bool,
+    },
+
+    /// Remove a worktree type
+    #[command(alias = "rm")]
+    Remove {
+        /// Type name to remove
+        name: String,
+    },
+
+    /// Write every type, with its defaults and policies, to a TOML or JSON file
+    Export {
+        /// Output file (.toml or .json); prints TOML when omitted
+        file: Option<PathBuf>,
+    },
+
+    /// Import types from a file, or from the repository's committed .imi/types.toml
+    Import {
+        /// Types file (.toml or .json)
+        file: Option<PathBuf>,
+
+        /// Repository whose committed types file to import when no file is given
+        #[arg(short, long)]
+        repo: Option<String>,
+
+        /// Replace the settings of existing types with the imported ones
+        #[arg(long, conflicts_with = "merge")]
+        overwrite: bool,
+
+        /// Only fill in settings existing types leave unset
+        #[arg(long)]
+        merge: bool,
+
+        /// Show what would change without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+/// Shallow and partial clone options shared by `imi init owner/repo` and
+/// `imi clone`; `imi unshallow` fetches the rest later
+#[derive(Args, Debug, Clone, Default)]
+pub struct CloneArgs {
+    /// Only fetch the last N commits
+    #[arg(long, value_name = "N")]
+    pub depth: Option<u32>,
+
+    /// Partial clone filter, e.g. blob:none to fetch file contents on demand
+    #[arg(long, value_name = "SPEC")]
+    pub filter: Option<String>,
+
+    /// Only fetch the default branch
+    #[arg(long)]
+    pub single_branch: bool,
+}
+
+/// Optional worktree type fields that `imi types edit --clear` can reset
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum TypeField {
+    Description,
+    TtlDays,
+    BaseBranch,
+    CommitTemplate,
+}
+
+impl TypeField {
+    /// Database column backing this field
+    pub fn column(self) -> &'static str {
+        match self {
+            TypeField::Description => "description",
+            TypeField::TtlDays => "default_ttl_days",
+            TypeField::BaseBranch => "default_base_branch",
+            TypeField::CommitTemplate => "commit_template",
+        }
+    }
+}
+
+/// Where a user alias sits in the command line: the first word that is
+/// neither a global flag (or its value) nor a built-in command, so built-in
+/// commands always win over aliases
+pub fn alias_position(args: &[OsString]) -> Option<usize> {
+    let command = Cli::command();
+    let mut i = 1;
+    while let Some(arg) = args.get(i) {
+        let arg = arg.to_str()?;
+        if arg == "--" {
+            return None;
+        }
+        if let Some(flag) = arg.strip_prefix("--") {
+            // `--flag VALUE` takes the next word too, `--flag=VALUE` doesn't
+            let takes_value = !flag.contains('=')
+                && command
+                    .get_arguments()
+                    .any(|a| a.get_long() == Some(flag) && a.get_action().takes_values());
+            i += if takes_value { 2 } else { 1 };
+            continue;
+        }
+        if arg.starts_with('-') {
+            i += 1;
+            continue;
+        }
+        let builtin = arg == "help" || command.find_subcommand(arg).is_some();
+        return (!builtin).then_some(i);
+    }
+    None
+}
+
+/// The command line with the alias at `position` replaced by its template.
+/// `{N}` is the Nth argument after the alias; arguments no placeholder
+/// takes are appended.
+pub fn expand_alias(
+    args: &[OsString],
+    position: usize,
+    template: &str,
+) -> std::result::Result<Vec<OsString>, String> {
+    let name = args[position].to_string_lossy();
+    let alias_args = &args[position + 1..];
+    let mut used = vec![false; alias_args.len()];
+    let placeholder = regex::Regex::new(r"\{(\d+)\}").expect("valid regex");
+
+    let mut missing = None;
+    let mut argument = |index: &str| -> Option<&OsString> {
+        let n: usize = index.parse().ok()?;
+        let arg = n.checked_sub(1).and_then(|i| alias_args.get(i));
+        match arg {
+            Some(_) => used[n - 1] = true,
+            None => missing = missing.max(Some(n)),
+        }
+        arg
+    };
+
+    let mut expanded = args[..position].to_vec();
+    for word in template.split_whitespace() {
+        // A whole-word placeholder passes the argument through untouched
+        let whole = placeholder
+            .captures(word)
+            .filter(|c| c[0].len() == word.len());
+        if let Some(captures) = whole {
+            if let Some(arg) = argument(&captures[1]) {
+                expanded.push(arg.clone());
+            }
+            continue;
+        }
+        let word = placeholder.replace_all(word, |c: &regex::Captures| {
+            argument(&c[1]).map_or_else(String::new, |arg| arg.to_string_lossy().into_owned())
+        });
+        expanded.push(word.into_owned().into());
+    }
+    if let Some(n) = missing {
+        return Err(format!(
+            "alias '{}' ({}) needs {} argument{}",
+            name,
+            template,
+            n,
+            if n == 1 { "" } else { "s" }
+        ));
+    }
+    expanded.extend(
+        alias_args
+            .iter()
+            .zip(&used)
+            .filter(|(_, used)| !**used)
+            .map(|(arg, _)| arg.clone()),
+    );
+    Ok(expanded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn parses_metadata_set_command() {
+        let cli = Cli::try_parse_from([
             "imi",
             "metadata",
             "set",
@@ -455,28 +1846,1501 @@ mod tests {
     }
 
     #[test]
-    fn parses_migrate_office_command() {
-        let cli = Cli::try_parse_from([
-            "imi",
-            "migrate-office",
-            "--repo",
-            "iMi",
-            "--dry-run",
-            "--force",
-        ])
-        .expect("migrate-office should parse");
+    fn parses_which_command() {
+        let cli = Cli::try_parse_from(["imi", "which", "feat-auth", "-r", "iMi", "-w"])
+            .expect("which should parse");
 
         match cli.command {
-            Some(Commands::MigrateOffice {
+            Some(Commands::Which {
+                name,
                 repo,
-                dry_run,
-                force,
+                worktrees_only,
+                include_inactive,
+                all,
             }) => {
+                assert_eq!(name, "feat-auth");
                 assert_eq!(repo.as_deref(), Some("iMi"));
-                assert!(dry_run);
-                assert!(force);
+                assert!(worktrees_only);
+                assert!(!include_inactive);
+                assert!(!all);
             }
-            _ => panic!("expected migrate-office command"),
+            _ => panic!("expected which command"),
+        }
+    }
+
+    #[test]
+    fn parses_go_query_with_filter_tokens() {
+        let cli = Cli::try_parse_from(["imi", "go", "type:feat", "repo:imi", "auth"])
+            .expect("go should parse");
+
+        match cli.command {
+            Some(Commands::Go { query, .. }) => {
+                assert_eq!(query, vec!["type:feat", "repo:imi", "auth"]);
+            }
+            _ => panic!("expected go command"),
         }
     }
+
+    #[test]
+    fn parses_go_shell() {
+        let cli = Cli::try_parse_from(["imi", "go", "auth", "--shell"]).expect("go should parse");
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Go { shell: true, .. })
+        ));
+    }
+
+    #[test]
+    fn only_long_operations_take_over_ctrl_c() {
+        let cancellable = |args: &[&str]| {
+            let cli = Cli::try_parse_from(args).expect("command should parse");
+            cli.command.expect("a command").cancellable()
+        };
+        assert!(cancellable(&["imi", "add", "feat", "auth"]));
+        assert!(cancellable(&["imi", "clone", "acme/api"]));
+        assert!(cancellable(&["imi", "merge", "feat-auth"]));
+        assert!(cancellable(&["imi", "prune", "--dry-run"]));
+        // These stop on Ctrl-C themselves, or have nothing to clean up
+        assert!(!cancellable(&["imi", "monitor"]));
+        assert!(!cancellable(&["imi", "status"]));
+        assert!(!cancellable(&["imi", "serve"]));
+    }
+
+    #[test]
+    fn read_only_mode_allows_only_inspecting_commands() {
+        let mutates = |args: &[&str]| {
+            let cli = Cli::try_parse_from(args).expect("command should parse");
+            cli.command.expect("a command").mutates()
+        };
+        assert!(mutates(&["imi", "add", "feat", "auth"]));
+        assert!(mutates(&["imi", "merge", "feat-auth"]));
+        assert!(mutates(&["imi", "prune"]));
+        assert!(mutates(&["imi", "note", "feat-auth", "waiting on API"]));
+        assert!(!mutates(&["imi", "prune", "--dry-run"]));
+        assert!(!mutates(&["imi", "note", "feat-auth"]));
+        assert!(mutates(&[
+            "imi",
+            "msg",
+            "send",
+            "feat-auth",
+            "please rebase"
+        ]));
+        assert!(mutates(&["imi", "msg", "read"]));
+        assert!(!mutates(&["imi", "msg", "read", "--keep-unread"]));
+        assert!(!mutates(&["imi", "config", "validate"]));
+        assert!(!mutates(&["imi", "agent", "rules", "test", "fix", "x"]));
+        assert!(!mutates(&["imi", "status"]));
+        assert!(!mutates(&["imi", "types", "list"]));
+
+        let cli = Cli::try_parse_from(["imi", "list", "--read-only"]).expect("flag is global");
+        assert!(cli.read_only);
+    }
+
+    #[test]
+    fn timings_flag_is_global() {
+        let cli = Cli::try_parse_from(["imi", "list", "--timings"]).expect("flag is global");
+        assert!(cli.timings);
+        assert!(!Cli::try_parse_from(["imi", "list"]).unwrap().timings);
+    }
+
+    #[test]
+    fn parses_add_with_patch() {
+        let cli =
+            Cli::try_parse_from(["imi", "add", "feat", "x", "--apply-patch", "-", "--commit"])
+                .expect("add should parse");
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Add {
+                apply_patch: Some(ref patch),
+                commit: Some(ref message),
+                ..
+            }) if patch == "-" && message.is_empty()
+        ));
+
+        let cli = Cli::try_parse_from(["imi", "add", "feat", "x", "--apply-patch", "fix.diff"])
+            .expect("add should parse");
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Add { commit: None, .. })
+        ));
+        assert!(Cli::try_parse_from(["imi", "add", "feat", "x", "--commit", "msg"]).is_err());
+    }
+
+    #[test]
+    fn add_takes_composite_flow_flags() {
+        let cli = Cli::try_parse_from([
+            "imi",
+            "add",
+            "feat",
+            "x",
+            "--open",
+            "--switch",
+            "--publish",
+            "--claim",
+            "agent-7",
+        ])
+        .expect("add should parse");
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Add {
+                open: true,
+                switch: true,
+                publish: true,
+                claim: Some(ref agent),
+                ..
+            }) if agent == "agent-7"
+        ));
+        assert!(Cli::try_parse_from(["imi", "add", "feat", "x", "--claim"]).is_err());
+    }
+
+    #[test]
+    fn parses_pr_sync_next_to_pr_numbers() {
+        let cli = Cli::try_parse_from(["imi", "pr", "12", "api"]).expect("pr should parse");
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Review {
+                action: None,
+                pr_number: Some(12),
+                repo: Some(ref repo),
+            }) if repo == "api"
+        ));
+
+        let cli = Cli::try_parse_from(["imi", "pr", "sync", "12", "--force"])
+            .expect("pr sync should parse");
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Review {
+                action: Some(ReviewCommands::Sync {
+                    pr_number: Some(12),
+                    force: true,
+                    ..
+                }),
+                ..
+            })
+        ));
+
+        assert!(Cli::try_parse_from(["imi", "pr"]).is_err());
+    }
+
+    #[test]
+    fn parses_copy_command() {
+        let cli = Cli::try_parse_from(["imi", "copy", "feat-auth", "auth-v2", "-u"])
+            .expect("copy should parse");
+
+        match cli.command {
+            Some(Commands::Copy {
+                source,
+                name,
+                repo,
+                include_uncommitted,
+                caches,
+                cache_fallback,
+                ..
+            }) => {
+                assert_eq!(source, "feat-auth");
+                assert_eq!(name, "auth-v2");
+                assert!(repo.is_none());
+                assert!(include_uncommitted);
+                assert!(!caches);
+                assert!(cache_fallback.is_none());
+            }
+            _ => panic!("expected copy command"),
+        }
+
+        let cli = Cli::try_parse_from([
+            "imi",
+            "copy",
+            "feat-auth",
+            "auth-v2",
+            "--cache-fallback",
+            "hardlink",
+        ])
+        .expect("--cache-fallback parses");
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Copy {
+                cache_fallback: Some(CacheFallback::Hardlink),
+                ..
+            })
+        ));
+        assert!(Cli::try_parse_from([
+            "imi",
+            "copy",
+            "feat-auth",
+            "auth-v2",
+            "--caches",
+            "--no-caches"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn parses_bulk_close() {
+        let cli = Cli::try_parse_from(["imi", "close", "--merged", "--all-of-type", "feat", "-y"])
+            .expect("bulk close should parse");
+        match cli.command {
+            Some(Commands::Close {
+                name,
+                merged,
+                all_of_type,
+                matching,
+                yes,
+                ..
+            }) => {
+                assert!(name.is_none());
+                assert!(merged);
+                assert_eq!(all_of_type.as_deref(), Some("feat"));
+                assert!(matching.is_none());
+                assert!(yes);
+            }
+            _ => panic!("expected close command"),
+        }
+
+        assert!(Cli::try_parse_from(["imi", "close"]).is_err());
+    }
+
+    #[test]
+    fn parses_daemon_commands() {
+        let cli = Cli::try_parse_from(["imi", "daemon", "run", "--once"])
+            .expect("daemon run should parse");
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Daemon(DaemonCommands::Run {
+                once: true,
+                metrics: None
+            }))
+        ));
+
+        let cli = Cli::try_parse_from(["imi", "daemon", "run", "--metrics", ":9187"])
+            .expect("daemon run --metrics should parse");
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Daemon(DaemonCommands::Run {
+                once: false,
+                metrics: Some(addr)
+            })) if addr.port() == 9187
+        ));
+        assert!(
+            Cli::try_parse_from(["imi", "daemon", "run", "--once", "--metrics", ":9187"]).is_err()
+        );
+
+        let cli =
+            Cli::try_parse_from(["imi", "daemon", "status"]).expect("daemon status should parse");
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Daemon(DaemonCommands::Status))
+        ));
+
+        let cli =
+            Cli::try_parse_from(["imi", "daemon", "tasks"]).expect("daemon tasks should parse");
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Daemon(DaemonCommands::Tasks))
+        ));
+    }
+
+    #[test]
+    fn parses_audit_search_command() {
+        let cli = Cli::try_parse_from(["imi", "audit", "search", "feat-auth", "--failed"])
+            .expect("audit search should parse");
+
+        match cli.command {
+            Some(Commands::Audit(AuditCommands::Search {
+                pattern,
+                command,
+                failed,
+                limit,
+            })) => {
+                assert_eq!(pattern.as_deref(), Some("feat-auth"));
+                assert!(command.is_none());
+                assert!(failed);
+                assert_eq!(limit, 50);
+            }
+            _ => panic!("expected audit search command"),
+        }
+    }
+
+    #[test]
+    fn parses_rollback_command() {
+        let cli = Cli::try_parse_from(["imi", "rollback", "feat-auth", "--yes"])
+            .expect("rollback should parse");
+
+        match cli.command {
+            Some(Commands::Rollback { operation, yes }) => {
+                assert_eq!(operation.as_deref(), Some("feat-auth"));
+                assert!(yes);
+            }
+            _ => panic!("expected rollback command"),
+        }
+    }
+
+    #[test]
+    fn parses_types_edit_command() {
+        let cli = Cli::try_parse_from([
+            "imi",
+            "types",
+            "edit",
+            "spike",
+            "--ttl-days",
+            "7",
+            "--base-branch",
+            "develop",
+            "--clear",
+            "commit-template",
+        ])
+        .expect("types edit should parse");
+
+        match cli.command {
+            Some(Commands::Types(TypeCommands::Edit {
+                name,
+                ttl_days,
+                base_branch,
+                clear,
+                ..
+            })) => {
+                assert_eq!(name, "spike");
+                assert_eq!(ttl_days, Some(7));
+                assert_eq!(base_branch.as_deref(), Some("develop"));
+                assert_eq!(clear, vec![TypeField::CommitTemplate]);
+            }
+            _ => panic!("expected types edit command"),
+        }
+    }
+
+    #[test]
+    fn parses_migrate_office_command() {
+        let cli = Cli::try_parse_from([
+            "imi",
+            "migrate-office",
+            "--repo",
+            "iMi",
+            "--dry-run",
+            "--force",
+        ])
+        .expect("migrate-office should parse");
+
+        match cli.command {
+            Some(Commands::MigrateOffice {
+                repo,
+                dry_run,
+                force,
+            }) => {
+                assert_eq!(repo.as_deref(), Some("iMi"));
+                assert!(dry_run);
+                assert!(force);
+            }
+            _ => panic!("expected migrate-office command"),
+        }
+    }
+
+    #[test]
+    fn sync_continue_and_abort_exclude_each_other() {
+        let cli = Cli::try_parse_from(["imi", "sync", "api", "--continue"])
+            .expect("sync --continue should parse");
+        match cli.command {
+            Some(Commands::Sync {
+                repo,
+                rebase_worktrees,
+                resume,
+                abort,
+            }) => {
+                assert_eq!(repo.as_deref(), Some("api"));
+                assert!(resume && !rebase_worktrees && !abort);
+            }
+            _ => panic!("expected sync command"),
+        }
+
+        assert!(Cli::try_parse_from(["imi", "sync", "--continue", "--abort"]).is_err());
+        assert!(Cli::try_parse_from(["imi", "sync", "--rebase-worktrees", "--continue"]).is_err());
+    }
+
+    #[test]
+    fn mv_requires_relayout() {
+        assert!(Cli::try_parse_from(["imi", "mv"]).is_err());
+
+        let cli = Cli::try_parse_from(["imi", "mv", "--relayout", "--repo", "api", "--dry-run"])
+            .expect("mv --relayout should parse");
+        let command = cli.command.expect("command");
+        assert!(!command.mutates());
+        match command {
+            Commands::Mv {
+                relayout,
+                repo,
+                dry_run,
+            } => {
+                assert!(relayout);
+                assert_eq!(repo.as_deref(), Some("api"));
+                assert!(dry_run);
+            }
+            _ => panic!("expected mv command"),
+        }
+    }
+
+    #[test]
+    fn parses_global_config_and_db_overrides() {
+        let cli = Cli::try_parse_from([
+            "imi",
+            "list",
+            "--config",
+            "/tmp/work/config.toml",
+            "--db",
+            "postgresql://imi@localhost/imi_work",
+        ])
+        .expect("global overrides should parse after the subcommand");
+
+        assert_eq!(cli.config, Some(PathBuf::from("/tmp/work/config.toml")));
+        assert_eq!(
+            cli.db.as_deref(),
+            Some("postgresql://imi@localhost/imi_work")
+        );
+    }
+
+    #[test]
+    fn parses_clean_command() {
+        let cli = Cli::try_parse_from(["imi", "clean", "iMi", "-y"]).expect("clean should parse");
+
+        match cli.command {
+            Some(Commands::Clean { repo, yes, .. }) => {
+                assert_eq!(repo.as_deref(), Some("iMi"));
+                assert!(yes);
+            }
+            _ => panic!("expected clean command"),
+        }
+    }
+
+    #[test]
+    fn parses_size_budget_and_ignore_budget() {
+        let cli = Cli::try_parse_from(["imi", "size-budget", "iMi", "--refresh"])
+            .expect("size-budget should parse");
+        match cli.command {
+            Some(Commands::SizeBudget { repo, refresh }) => {
+                assert_eq!(repo.as_deref(), Some("iMi"));
+                assert!(refresh);
+            }
+            _ => panic!("expected size-budget command"),
+        }
+
+        let cli = Cli::try_parse_from(["imi", "add", "feat", "auth", "--ignore-budget"])
+            .expect("--ignore-budget should be accepted by add");
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Add {
+                ignore_budget: true,
+                ..
+            })
+        ));
+        let cli = Cli::try_parse_from(["imi", "copy", "feat-auth", "auth2", "--ignore-budget"])
+            .expect("--ignore-budget should be accepted by copy");
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Copy {
+                ignore_budget: true,
+                ..
+            })
+        ));
+        assert!(Cli::try_parse_from(["imi", "list", "--ignore-budget"]).is_err());
+    }
+
+    #[test]
+    fn test_clone_accepts_repos_or_org_with_topic() {
+        let cli = Cli::try_parse_from(["imi", "clone", "acme/api", "acme/web", "-j", "2"])
+            .expect("clone should accept several repositories");
+        match cli.command {
+            Some(Commands::Clone {
+                repos, org, jobs, ..
+            }) => {
+                assert_eq!(repos, vec!["acme/api", "acme/web"]);
+                assert!(org.is_none());
+                assert_eq!(jobs, 2);
+            }
+            _ => panic!("expected clone command"),
+        }
+
+        let cli = Cli::try_parse_from(["imi", "clone", "--org", "acme", "--topic", "imi"])
+            .expect("clone should accept --org with --topic");
+        match cli.command {
+            Some(Commands::Clone { org, topic, .. }) => {
+                assert_eq!(org.as_deref(), Some("acme"));
+                assert_eq!(topic.as_deref(), Some("imi"));
+            }
+            _ => panic!("expected clone command"),
+        }
+
+        assert!(Cli::try_parse_from(["imi", "clone", "--topic", "imi"]).is_err());
+    }
+
+    #[test]
+    fn test_add_accepts_a_name_alone() {
+        let cli = Cli::try_parse_from(["imi", "add", "payment-retry"])
+            .expect("add should accept just a name");
+        match cli.command {
+            Some(Commands::Add {
+                args,
+                worktree_type,
+                ..
+            }) => {
+                assert_eq!(args, vec!["payment-retry"]);
+                assert!(worktree_type.is_none());
+            }
+            _ => panic!("expected add command"),
+        }
+
+        let cli = Cli::try_parse_from(["imi", "add", "feat", "auth", "--repo", "iMi"])
+            .expect("add should still accept a type and a name");
+        match cli.command {
+            Some(Commands::Add { args, repo, .. }) => {
+                assert_eq!(args, vec!["feat", "auth"]);
+                assert_eq!(repo.as_deref(), Some("iMi"));
+            }
+            _ => panic!("expected add command"),
+        }
+
+        let cli = Cli::try_parse_from(["imi", "add", "payment-retry", "--type", "fix"])
+            .expect("add should accept --type");
+        match cli.command {
+            Some(Commands::Add { worktree_type, .. }) => {
+                assert_eq!(worktree_type.as_deref(), Some("fix"));
+            }
+            _ => panic!("expected add command"),
+        }
+    }
+
+    #[test]
+    fn test_trash_subcommands() {
+        let cli = Cli::try_parse_from(["imi", "trash", "restore", "feat-auth", "--repo", "iMi"])
+            .expect("trash restore should parse");
+        match cli.command {
+            Some(Commands::Trash(TrashCommands::Restore { name, repo })) => {
+                assert_eq!(name, "feat-auth");
+                assert_eq!(repo.as_deref(), Some("iMi"));
+            }
+            _ => panic!("expected trash restore"),
+        }
+
+        let cli = Cli::try_parse_from(["imi", "trash", "empty", "--expired", "-y"])
+            .expect("trash empty should parse");
+        match cli.command {
+            Some(Commands::Trash(TrashCommands::Empty { repo, expired, yes })) => {
+                assert!(repo.is_none());
+                assert!(expired && yes);
+            }
+            _ => panic!("expected trash empty"),
+        }
+    }
+
+    #[test]
+    fn test_types_import_modes_conflict() {
+        let cli = Cli::try_parse_from(["imi", "types", "import", "team.toml", "--merge"])
+            .expect("types import should parse");
+        match cli.command {
+            Some(Commands::Types(TypeCommands::Import {
+                file,
+                merge,
+                overwrite,
+                ..
+            })) => {
+                assert_eq!(file, Some(PathBuf::from("team.toml")));
+                assert!(merge && !overwrite);
+            }
+            _ => panic!("expected types import"),
+        }
+
+        assert!(Cli::try_parse_from(["imi", "types", "import", "--merge", "--overwrite"]).is_err());
+    }
+
+    #[test]
+    fn test_init_offline_flag() {
+        let cli = Cli::try_parse_from(["imi", "init", "--offline"]).expect("init should parse");
+        match cli.command {
+            Some(Commands::Init { repo, offline, .. }) => {
+                assert!(repo.is_none());
+                assert!(offline);
+            }
+            _ => panic!("expected init command"),
+        }
+    }
+
+    #[test]
+    fn test_init_yes_flag() {
+        let cli = Cli::try_parse_from(["imi", "init", "-y"]).expect("init -y should parse");
+        match cli.command {
+            Some(Commands::Init { yes, offline, .. }) => {
+                assert!(yes);
+                assert!(!offline);
+            }
+            _ => panic!("expected init command"),
+        }
+    }
+
+    #[test]
+    fn test_recent_index_and_pick_conflict() {
+        let cli =
+            Cli::try_parse_from(["imi", "recent", "2", "-n", "5"]).expect("recent should parse");
+        match cli.command {
+            Some(Commands::Recent {
+                index, limit, pick, ..
+            }) => {
+                assert_eq!(index, Some(2));
+                assert_eq!(limit, 5);
+                assert!(!pick);
+            }
+            _ => panic!("expected recent command"),
+        }
+
+        assert!(Cli::try_parse_from(["imi", "recent", "2", "--pick"]).is_err());
+    }
+
+    #[test]
+    fn test_types_set_commit_template() {
+        let cli = Cli::try_parse_from(["imi", "types", "set-commit-template", "fix", "fix.txt"])
+            .expect("set-commit-template should parse");
+        match cli.command {
+            Some(Commands::Types(TypeCommands::SetCommitTemplate { name, file, clear })) => {
+                assert_eq!(name, "fix");
+                assert_eq!(file, Some(PathBuf::from("fix.txt")));
+                assert!(!clear);
+            }
+            _ => panic!("expected types set-commit-template"),
+        }
+
+        assert!(Cli::try_parse_from([
+            "imi",
+            "types",
+            "set-commit-template",
+            "fix",
+            "fix.txt",
+            "--clear"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_add_base_ref() {
+        let cli = Cli::try_parse_from(["imi", "add", "fix", "hotfix-1", "--base", "v2.3.1"])
+            .expect("add --base should parse");
+        match cli.command {
+            Some(Commands::Add { args, base, .. }) => {
+                assert_eq!(args, vec!["fix", "hotfix-1"]);
+                assert_eq!(base.as_deref(), Some("v2.3.1"));
+            }
+            _ => panic!("expected add command"),
+        }
+    }
+
+    #[test]
+    fn test_onboard() {
+        let cli = Cli::try_parse_from([
+            "imi",
+            "onboard",
+            "gh:acme",
+            "--language",
+            "rust",
+            "--all",
+            "-j",
+            "8",
+        ])
+        .expect("onboard parses");
+        match cli.command {
+            Some(Commands::Onboard {
+                target,
+                topic,
+                language,
+                all,
+                jobs,
+                ..
+            }) => {
+                assert_eq!(target, "gh:acme");
+                assert!(topic.is_none());
+                assert_eq!(language.as_deref(), Some("rust"));
+                assert!(all);
+                assert_eq!(jobs, 8);
+            }
+            _ => panic!("expected onboard command"),
+        }
+    }
+
+    #[test]
+    fn test_add_no_hooks() {
+        let cli = Cli::try_parse_from(["imi", "add", "feat", "auth", "--no-hooks"])
+            .expect("add --no-hooks should parse");
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Add {
+                no_hooks: true,
+                publish: false,
+                ..
+            })
+        ));
+        let cli = Cli::try_parse_from(["imi", "add", "feat", "auth", "--publish"])
+            .expect("add --publish should parse");
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Add { publish: true, .. })
+        ));
+    }
+
+    #[test]
+    fn test_include_risky_flags() {
+        let cli = Cli::try_parse_from(["imi", "prune", "--include-risky"])
+            .expect("prune --include-risky should parse");
+        match cli.command {
+            Some(Commands::Prune { include_risky, .. }) => assert!(include_risky),
+            _ => panic!("expected prune command"),
+        }
+
+        let cli = Cli::try_parse_from(["imi", "clean", "-y"]).expect("clean should parse");
+        match cli.command {
+            Some(Commands::Clean { include_risky, .. }) => assert!(!include_risky),
+            _ => panic!("expected clean command"),
+        }
+    }
+
+    #[test]
+    fn test_serve_http() {
+        let cli = Cli::try_parse_from(["imi", "serve", "--http", "--addr", "0.0.0.0:9000"])
+            .expect("serve --http should parse");
+        match cli.command {
+            Some(Commands::Serve { http, addr }) => {
+                assert!(http);
+                assert_eq!(addr.port(), 9000);
+            }
+            _ => panic!("expected serve command"),
+        }
+    }
+
+    #[test]
+    fn test_remove_and_close_exact() {
+        let cli = Cli::try_parse_from(["imi", "remove", "auth", "--exact"])
+            .expect("remove --exact should parse");
+        match cli.command {
+            Some(Commands::Remove { name, exact, .. }) => {
+                assert_eq!(name, "auth");
+                assert!(exact);
+            }
+            _ => panic!("expected remove command"),
+        }
+
+        let cli = Cli::try_parse_from(["imi", "close", "auth"]).expect("close should parse");
+        match cli.command {
+            Some(Commands::Close { exact, .. }) => assert!(!exact),
+            _ => panic!("expected close command"),
+        }
+    }
+
+    #[test]
+    fn test_status_ci() {
+        let cli = Cli::try_parse_from(["imi", "status", "my-repo", "--ci"]).expect("status parses");
+        match cli.command {
+            Some(Commands::Status {
+                repo,
+                ci,
+                sort,
+                all,
+                no_cache,
+                refresh_prs,
+                watch,
+            }) => {
+                assert_eq!(repo.as_deref(), Some("my-repo"));
+                assert!(ci);
+                assert_eq!(sort, None);
+                assert!(!all);
+                assert!(!no_cache);
+                assert!(!refresh_prs);
+                assert_eq!(watch, None);
+            }
+            _ => panic!("expected status command"),
+        }
+    }
+
+    #[test]
+    fn test_verify() {
+        let cli = Cli::try_parse_from(["imi", "verify", "--strict", "--dir", "/tmp"])
+            .expect("verify parses");
+        match cli.command {
+            Some(Commands::Verify { dir, strict }) => {
+                assert_eq!(dir, Some(PathBuf::from("/tmp")));
+                assert!(strict);
+            }
+            _ => panic!("expected verify command"),
+        }
+    }
+
+    #[test]
+    fn test_config_validate() {
+        let cli = Cli::try_parse_from(["imi", "config", "validate", "--strict", "my.toml"])
+            .expect("config validate parses");
+        match cli.command {
+            Some(Commands::Config(ConfigCommands::Validate { file, strict })) => {
+                assert_eq!(file, Some(PathBuf::from("my.toml")));
+                assert!(strict);
+            }
+            _ => panic!("expected config validate command"),
+        }
+    }
+
+    #[test]
+    fn test_agent_rules() {
+        let cli =
+            Cli::try_parse_from(["imi", "agent", "rules", "test", "fix", "ml-x", "-r", "api"])
+                .expect("agent rules test parses");
+        match cli.command {
+            Some(Commands::Agent(AgentCommands::Rules(AgentRuleCommands::Test {
+                worktree_type,
+                name,
+                repo,
+            }))) => {
+                assert_eq!(worktree_type, "fix");
+                assert_eq!(name, "ml-x");
+                assert_eq!(repo.as_deref(), Some("api"));
+            }
+            _ => panic!("expected agent rules test command"),
+        }
+
+        let cli = Cli::try_parse_from(["imi", "add", "fix", "x", "--agent", "bugfixer"])
+            .expect("--agent is an alias of --claim");
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Add { claim: Some(ref agent), .. }) if agent == "bugfixer"
+        ));
+    }
+
+    #[test]
+    fn test_list_sort() {
+        let cli = Cli::try_parse_from(["imi", "list", "--sort", "activity"]).expect("list parses");
+        assert!(matches!(
+            cli.command,
+            Some(Commands::List {
+                sort: Some(WorktreeSort::Activity),
+                ..
+            })
+        ));
+
+        assert!(Cli::try_parse_from(["imi", "list", "--sort", "stars"]).is_err());
+        assert!(Cli::try_parse_from(["imi", "list", "--projects", "--sort", "age"]).is_err());
+    }
+
+    #[test]
+    fn test_no_cache() {
+        for args in [["imi", "status", "--no-cache"], ["imi", "ls", "--no-cache"]] {
+            let cli = Cli::try_parse_from(args).expect("--no-cache parses");
+            assert!(matches!(
+                cli.command,
+                Some(
+                    Commands::Status { no_cache: true, .. } | Commands::List { no_cache: true, .. }
+                )
+            ));
+        }
+    }
+
+    #[test]
+    fn status_watch_takes_an_optional_interval() {
+        let cli = Cli::try_parse_from(["imi", "status", "--watch", "api"]).expect("--watch parses");
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Status { watch: Some(5), repo: Some(ref repo), .. }) if repo == "api"
+        ));
+
+        let cli = Cli::try_parse_from(["imi", "status", "--watch=10"]).expect("--watch=N parses");
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Status {
+                watch: Some(10),
+                ..
+            })
+        ));
+
+        assert!(Cli::try_parse_from(["imi", "status", "--watch=0"]).is_err());
+    }
+
+    #[test]
+    fn test_repo_archive_and_all() {
+        let cli = Cli::try_parse_from(["imi", "repo", "archive", "old-api"])
+            .expect("repo archive parses");
+        assert!(cli.command.as_ref().unwrap().mutates());
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Repo(RepoCommands::Archive { ref repo })) if repo == "old-api"
+        ));
+
+        for args in [
+            ["imi", "list", "--all"],
+            ["imi", "status", "--all"],
+            ["imi", "go", "--all"],
+        ] {
+            let cli = Cli::try_parse_from(args).expect("--all parses");
+            assert!(matches!(
+                cli.command,
+                Some(
+                    Commands::List { all: true, .. }
+                        | Commands::Status { all: true, .. }
+                        | Commands::Go { all: true, .. }
+                )
+            ));
+        }
+    }
+
+    #[test]
+    fn test_merge_queue() {
+        let cli = Cli::try_parse_from(["imi", "merge", "feat-x", "--queue"]).expect("merge parses");
+        match cli.command {
+            Some(Commands::Merge {
+                name,
+                queue,
+                strategy,
+                ..
+            }) => {
+                assert_eq!(name.as_deref(), Some("feat-x"));
+                assert!(queue);
+                assert_eq!(strategy, None);
+            }
+            _ => panic!("expected merge command"),
+        }
+
+        let cli =
+            Cli::try_parse_from(["imi", "merge", "--strategy", "pr"]).expect("strategy parses");
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Merge {
+                strategy: Some(MergeStrategy::Pr),
+                ..
+            })
+        ));
+        assert!(Cli::try_parse_from(["imi", "merge", "--queue", "--strategy", "pr"]).is_err());
+
+        let cli = Cli::try_parse_from(["imi", "merge", "--abort"]).expect("abort parses");
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Merge { abort: true, .. })
+        ));
+        assert!(Cli::try_parse_from(["imi", "merge", "--abort", "--queue"]).is_err());
+
+        let cli = Cli::try_parse_from(["imi", "merge", "--train", "feat-a", "feat-b", "fix-c"])
+            .expect("train parses");
+        match cli.command {
+            Some(Commands::Merge { name, train, .. }) => {
+                assert_eq!(name, None);
+                assert_eq!(train, ["feat-a", "feat-b", "fix-c"]);
+            }
+            _ => panic!("expected merge command"),
+        }
+        assert!(Cli::try_parse_from(["imi", "merge", "--train", "feat-a", "--queue"]).is_err());
+    }
+
+    #[test]
+    fn test_clone_shallow_options() {
+        let cli = Cli::try_parse_from([
+            "imi",
+            "clone",
+            "acme/api",
+            "--depth",
+            "1",
+            "--filter",
+            "blob:none",
+            "--single-branch",
+        ])
+        .expect("clone parses");
+        match cli.command {
+            Some(Commands::Clone { clone, .. }) => {
+                assert_eq!(clone.depth, Some(1));
+                assert_eq!(clone.filter.as_deref(), Some("blob:none"));
+                assert!(clone.single_branch);
+            }
+            _ => panic!("expected clone command"),
+        }
+
+        let cli =
+            Cli::try_parse_from(["imi", "init", "acme/api", "--depth", "5"]).expect("init parses");
+        match cli.command {
+            Some(Commands::Init { clone, .. }) => assert_eq!(clone.depth, Some(5)),
+            _ => panic!("expected init command"),
+        }
+
+        let cli = Cli::try_parse_from(["imi", "unshallow", "api"]).expect("unshallow parses");
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Unshallow { repo: Some(ref r) }) if r == "api"
+        ));
+    }
+
+    #[test]
+    fn test_format_flag() {
+        let cli = Cli::try_parse_from(["imi", "list", "--format", "{{name}}\t{{branch_name}}"])
+            .expect("template format parses");
+        assert_eq!(
+            cli.format,
+            Some(OutputFormat::Template(
+                "{{name}}\t{{branch_name}}".to_string()
+            ))
+        );
+
+        let cli = Cli::try_parse_from(["imi", "--format", "tsv", "status"]).expect("tsv parses");
+        assert_eq!(cli.format, Some(OutputFormat::Tsv));
+        assert!(Cli::try_parse_from(["imi", "list", "--format", "xml"]).is_err());
+        assert!(Cli::try_parse_from(["imi", "list", "--json", "--format", "yaml"]).is_err());
+
+        let cli = Cli::try_parse_from(["imi", "clone", "acme/api", "--json-stream"])
+            .expect("--json-stream is global");
+        assert!(cli.json_stream);
+        assert!(
+            Cli::try_parse_from(["imi", "merge", "--json-stream", "--format", "yaml"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_import_worktrees() {
+        let cli = Cli::try_parse_from(["imi", "import-worktrees", "api", "--yes"])
+            .expect("import-worktrees parses");
+        assert!(cli.command.as_ref().unwrap().mutates());
+        assert!(matches!(
+            cli.command,
+            Some(Commands::ImportWorktrees {
+                ref repo,
+                yes: true,
+                dry_run: false,
+            }) if repo.as_deref() == Some("api")
+        ));
+
+        let cli = Cli::try_parse_from(["imi", "import-worktrees", "--dry-run"])
+            .expect("--dry-run parses");
+        assert!(!cli.command.as_ref().unwrap().mutates());
+    }
+
+    #[test]
+    fn test_branches_prune_remote() {
+        let cli = Cli::try_parse_from(["imi", "branches", "prune-remote", "--include-unmerged"])
+            .expect("branches prune-remote parses");
+        assert!(cli.command.as_ref().unwrap().mutates());
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Branches(BranchCommands::PruneRemote {
+                repo: None,
+                include_unmerged: true,
+                dry_run: false,
+                yes: false,
+            }))
+        ));
+
+        let cli = Cli::try_parse_from(["imi", "branches", "prune-remote", "api", "--dry-run"])
+            .expect("--dry-run parses");
+        assert!(!cli.command.as_ref().unwrap().mutates());
+    }
+
+    #[test]
+    fn expands_aliases_before_parsing() {
+        let args = |words: &[&str]| -> Vec<OsString> { words.iter().map(OsString::from).collect() };
+        let template = "add fix {1} --base release/{2} --publish";
+
+        let line = args(&["imi", "--format", "json", "hotfix", "login", "v2", "--open"]);
+        assert_eq!(alias_position(&line), Some(3));
+        assert_eq!(
+            expand_alias(&line, 3, template).unwrap(),
+            args(&[
+                "imi",
+                "--format",
+                "json",
+                "add",
+                "fix",
+                "login",
+                "--base",
+                "release/v2",
+                "--publish",
+                "--open"
+            ])
+        );
+        let cli = Cli::try_parse_from(expand_alias(&line, 3, template).unwrap())
+            .expect("expanded alias parses");
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Add { publish: true, .. })
+        ));
+
+        assert_eq!(
+            expand_alias(&args(&["imi", "hotfix"]), 1, template).unwrap_err(),
+            "alias 'hotfix' (add fix {1} --base release/{2} --publish) needs 2 arguments"
+        );
+
+        // Built-in commands are never aliases
+        assert_eq!(alias_position(&args(&["imi", "add", "fix", "x"])), None);
+        assert_eq!(alias_position(&args(&["imi", "--json", "help"])), None);
+        assert_eq!(alias_position(&args(&["imi", "--json"])), None);
+    }
+
+    #[test]
+    fn test_agents_scaffold() {
+        let cli = Cli::try_parse_from(["imi", "agents", "scaffold", "--repo", "api", "--force"])
+            .expect("agents scaffold parses");
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Agent(AgentCommands::Scaffold {
+                ref repo,
+                force: true,
+                no_link: false
+            })) if repo.as_deref() == Some("api")
+        ));
+        assert!(cli.command.unwrap().mutates());
+
+        let cli = Cli::try_parse_from(["imi", "agent", "scaffold", "--no-link"])
+            .expect("agent scaffold parses");
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Agent(AgentCommands::Scaffold {
+                no_link: true,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_debug_bundle() {
+        let cli = Cli::try_parse_from(["imi", "debug", "bundle"]).expect("debug bundle parses");
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Debug(DebugCommands::Bundle {
+                output: None,
+                include_db: false
+            }))
+        ));
+
+        let cli =
+            Cli::try_parse_from(["imi", "debug", "bundle", "-o", "/tmp/b.tgz", "--include-db"])
+                .expect("debug bundle flags parse");
+        match cli.command {
+            Some(Commands::Debug(DebugCommands::Bundle { output, include_db })) => {
+                assert_eq!(output, Some(PathBuf::from("/tmp/b.tgz")));
+                assert!(include_db);
+            }
+            _ => panic!("expected debug bundle"),
+        }
+    }
+
+    #[test]
+    fn test_hooks_and_record_activity() {
+        let cli = Cli::try_parse_from(["imi", "hooks", "install", "--repo", "api"])
+            .expect("hooks install parses");
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Hooks(HookCommands::Install { ref repo })) if repo.as_deref() == Some("api")
+        ));
+
+        // git passes post-checkout its refs and flag; none are imi options
+        let cli = Cli::try_parse_from([
+            "imi",
+            "_record-activity",
+            "post-checkout",
+            "0a1b",
+            "2c3d",
+            "1",
+        ])
+        .expect("_record-activity parses");
+        match cli.command {
+            Some(Commands::RecordActivity { hook, args }) => {
+                assert_eq!(hook, "post-checkout");
+                assert_eq!(args, ["0a1b", "2c3d", "1"]);
+            }
+            _ => panic!("expected _record-activity"),
+        }
+        assert!(!Cli::command()
+            .get_subcommands()
+            .any(|c| c.get_name() == "_record-activity" && !c.is_hide_set()));
+    }
+
+    #[test]
+    fn test_temp() {
+        let cli = Cli::try_parse_from(["imi", "temp", "--base", "v1.2", "--", "cargo", "test"])
+            .expect("temp parses");
+        assert!(cli.command.as_ref().unwrap().mutates());
+        match cli.command {
+            Some(Commands::Temp {
+                base,
+                repo,
+                command,
+            }) => {
+                assert_eq!(base.as_deref(), Some("v1.2"));
+                assert!(repo.is_none());
+                assert_eq!(command, ["cargo", "test"]);
+            }
+            _ => panic!("expected temp"),
+        }
+
+        assert!(Cli::try_parse_from(["imi", "temp"]).is_err());
+    }
+
+    #[test]
+    fn test_repair() {
+        let cli = Cli::try_parse_from([
+            "imi",
+            "repair",
+            "--repo",
+            "api",
+            "--remap",
+            "/home/old=/home/new",
+        ])
+        .expect("repair parses");
+        assert!(cli.command.as_ref().unwrap().mutates());
+        match cli.command {
+            Some(Commands::Repair {
+                repo,
+                remap,
+                from_host,
+            }) => {
+                assert_eq!(repo.as_deref(), Some("api"));
+                assert_eq!(remap.unwrap().to, PathBuf::from("/home/new"));
+                assert_eq!(from_host, None);
+            }
+            _ => panic!("expected repair"),
+        }
+
+        assert!(Cli::try_parse_from(["imi", "repair", "--remap", "/home/old"]).is_err());
+        assert!(Cli::try_parse_from(["imi", "repair", "--from-host", "laptop"]).is_err());
+        assert!(Cli::try_parse_from([
+            "imi",
+            "repair",
+            "--from-host",
+            "laptop",
+            "--remap",
+            "/home/me=/Users/me"
+        ])
+        .is_ok());
+    }
+
+    #[test]
+    fn test_repo_stats() {
+        let cli = Cli::try_parse_from(["imi", "repo", "stats", "api"]).expect("repo stats parses");
+        assert!(!cli.command.as_ref().unwrap().mutates());
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Repo(RepoCommands::Stats {
+                git: false,
+                weeks: 12,
+                top: 10,
+                refresh: false,
+                ..
+            }))
+        ));
+
+        let cli = Cli::try_parse_from(["imi", "repo", "stats", "api", "--git", "--weeks", "4"])
+            .expect("--git parses");
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Repo(RepoCommands::Stats {
+                git: true,
+                weeks: 4,
+                ..
+            }))
+        ));
+        assert!(Cli::try_parse_from(["imi", "repo", "stats", "api", "--weeks", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_repo_tags_and_exec() {
+        let cli =
+            Cli::try_parse_from(["imi", "repo", "tag", "imi", "cli", "rust"]).expect("tag parses");
+        match cli.command {
+            Some(Commands::Repo(RepoCommands::Tag { repo, tags })) => {
+                assert_eq!(repo, "imi");
+                assert_eq!(tags, vec!["cli", "rust"]);
+            }
+            _ => panic!("expected repo tag command"),
+        }
+        assert!(Cli::try_parse_from(["imi", "repo", "tag", "imi"]).is_err());
+
+        let cli = Cli::try_parse_from(["imi", "list", "--tag", "client-x"]).expect("list parses");
+        assert!(matches!(
+            cli.command,
+            Some(Commands::List { tag: Some(ref t), .. }) if t == "client-x"
+        ));
+
+        let cli = Cli::try_parse_from(["imi", "exec", "--tag", "rust", "--", "cargo", "update"])
+            .expect("exec parses");
+        match cli.command {
+            Some(Commands::Exec { tag, command }) => {
+                assert_eq!(tag.as_deref(), Some("rust"));
+                assert_eq!(command, vec!["cargo", "update"]);
+            }
+            _ => panic!("expected exec command"),
+        }
+    }
+
+    #[test]
+    fn test_repo_set() {
+        let cli = Cli::try_parse_from([
+            "imi",
+            "repo",
+            "set",
+            "api",
+            "--default-branch",
+            "develop",
+            "--path",
+            "/code/api/trunk-develop",
+        ])
+        .expect("repo set parses");
+        match cli.command {
+            Some(Commands::Repo(RepoCommands::Set {
+                repo,
+                remote_url,
+                default_branch,
+                path,
+            })) => {
+                assert_eq!(repo, "api");
+                assert_eq!(remote_url, None);
+                assert_eq!(default_branch.as_deref(), Some("develop"));
+                assert_eq!(path, Some(PathBuf::from("/code/api/trunk-develop")));
+            }
+            _ => panic!("expected repo set command"),
+        }
+        assert!(Cli::try_parse_from(["imi", "repo", "show"]).is_err());
+    }
+
+    #[test]
+    fn test_bisect() {
+        let cli = Cli::try_parse_from([
+            "imi", "bisect", "--good", "v1.2", "--", "cargo", "test", "-p", "foo",
+        ])
+        .expect("bisect parses");
+        match cli.command {
+            Some(Commands::Bisect {
+                bad,
+                good,
+                keep,
+                command,
+                ..
+            }) => {
+                assert_eq!(bad, "HEAD");
+                assert_eq!(good, vec!["v1.2"]);
+                assert!(!keep);
+                assert_eq!(command, vec!["cargo", "test", "-p", "foo"]);
+            }
+            _ => panic!("expected bisect command"),
+        }
+        assert!(Cli::try_parse_from(["imi", "bisect", "--", "true"]).is_err());
+    }
+
+    #[test]
+    fn test_check_and_artifacts() {
+        let cli = Cli::try_parse_from(["imi", "check", "feat-auth", "--", "cargo", "test"])
+            .expect("check parses");
+        match cli.command {
+            Some(Commands::Check {
+                name,
+                silent,
+                command,
+                ..
+            }) => {
+                assert_eq!(name, "feat-auth");
+                assert!(!silent);
+                assert_eq!(command, vec!["cargo", "test"]);
+            }
+            _ => panic!("expected check command"),
+        }
+        assert!(Cli::try_parse_from(["imi", "check", "feat-auth"]).is_err());
+
+        let cli = Cli::try_parse_from(["imi", "artifacts", "show", "feat-auth", "--id", "3f2a"])
+            .expect("artifacts show parses");
+        assert!(!cli.command.as_ref().unwrap().mutates());
+        match cli.command {
+            Some(Commands::Artifacts(ArtifactCommands::Show { name, id, .. })) => {
+                assert_eq!(name, "feat-auth");
+                assert_eq!(id.as_deref(), Some("3f2a"));
+            }
+            _ => panic!("expected artifacts show command"),
+        }
+        let cli = Cli::try_parse_from(["imi", "artifacts", "list"]).expect("artifacts list parses");
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Artifacts(ArtifactCommands::List {
+                name: None,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_events() {
+        let cli = Cli::try_parse_from([
+            "imi",
+            "events",
+            "--follow",
+            "--repo",
+            "iMi",
+            "--event",
+            "new_commit",
+            "--event",
+            "merge_completed",
+        ])
+        .expect("events parses");
+        match cli.command {
+            Some(Commands::Events {
+                repo,
+                events,
+                follow,
+                lines,
+            }) => {
+                assert_eq!(repo.as_deref(), Some("iMi"));
+                assert_eq!(events, vec!["new_commit", "merge_completed"]);
+                assert!(follow);
+                assert_eq!(lines, None);
+            }
+            _ => panic!("expected events command"),
+        }
+    }
+
+    #[test]
+    fn test_msg() {
+        let cli = Cli::try_parse_from([
+            "imi",
+            "msg",
+            "send",
+            "feat-auth",
+            "please rebase",
+            "--notify",
+        ])
+        .expect("msg send parses");
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Msg(MsgCommands::Send { ref worktree, ref text, notify: true, .. }))
+                if worktree == "feat-auth" && text == "please rebase"
+        ));
+
+        let cli = Cli::try_parse_from(["imi", "msg", "read", "--unread"]).expect("msg read parses");
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Msg(MsgCommands::Read {
+                worktree: None,
+                unread: true,
+                keep_unread: false,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_note() {
+        let cli = Cli::try_parse_from(["imi", "note", "auth", "waiting on API design"])
+            .expect("note parses");
+        match cli.command {
+            Some(Commands::Note { name, text, repo }) => {
+                assert_eq!(name, "auth");
+                assert_eq!(text.as_deref(), Some("waiting on API design"));
+                assert_eq!(repo, None);
+            }
+            _ => panic!("expected note command"),
+        }
+
+        let cli = Cli::try_parse_from(["imi", "note", "auth", "--repo", "api"])
+            .expect("listing notes parses");
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Note { text: None, repo: Some(ref r), .. }) if r == "api"
+        ));
+    }
+
+    #[test]
+    fn test_show() {
+        let cli = Cli::try_parse_from(["imi", "show", "auth", "-r", "api"]).expect("show parses");
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Show { ref name, repo: Some(ref r) }) if name == "auth" && r == "api"
+        ));
+        assert!(Cli::try_parse_from(["imi", "show"]).is_err());
+    }
 }
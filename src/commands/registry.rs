@@ -4,6 +4,8 @@ use sqlx::PgPool;
 use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
+use crate::remote_url::normalize_remote_url;
+
 /// Sync filesystem with database - discover and register all iMi cluster hubs
 pub async fn sync_filesystem(pool: &PgPool, scan_root: Option<&Path>) -> Result<SyncStats> {
     let default_scan = PathBuf::from(std::env::var("HOME").unwrap()).join("code");
@@ -122,6 +124,8 @@ struct WorktreeInfo {
 fn discover_cluster_hubs(scan_root: &Path, max_depth: usize) -> Result<Vec<ClusterHub>> {
     use walkdir::WalkDir;
 
+    let _span =
+        tracing::info_span!("fs", detail = format!("scan {}", scan_root.display())).entered();
     let mut hubs = Vec::new();
 
     for entry in WalkDir::new(scan_root)
@@ -201,26 +205,6 @@ fn get_git_remote(path: &Path) -> Result<String> {
     }
 }
 
-/// Normalize remote URL to SSH format with .git suffix
-fn normalize_remote_url(url: &str) -> String {
-    let mut normalized = url.to_string();
-
-    // Convert HTTPS to SSH
-    if let Some(caps) = regex::Regex::new(r"^https://github\.com/(.+)/(.+?)(?:\.git)?$")
-        .unwrap()
-        .captures(url)
-    {
-        normalized = format!("git@github.com:{}/{}.git", &caps[1], &caps[2]);
-    }
-
-    // Ensure .git suffix
-    if !normalized.ends_with(".git") {
-        normalized.push_str(".git");
-    }
-
-    normalized
-}
-
 /// Register a project in PostgreSQL (idempotent)
 async fn register_project(pool: &PgPool, hub: &ClusterHub) -> Result<Uuid> {
     let project_id = sqlx::query_scalar::<_, Uuid>(
@@ -0,0 +1,134 @@
+//! Commit message templates per worktree type, installed as the worktree-local
+//! `commit.template` so `git commit` opens prefilled for the kind of work
+
+use std::path::Path;
+
+use crate::worktree::render_copy_template;
+
+/// Directory under the user sync path holding `<type>.txt` templates, used
+/// for types without a template of their own in the registry
+pub const SYNC_TEMPLATE_DIR: &str = "commit-templates";
+
+/// Conventional Commits type for a worktree type
+pub fn conventional_type(worktree_type: &str) -> &str {
+    match worktree_type {
+        "devops" => "ci",
+        "aiops" => "chore",
+        other => other,
+    }
+}
+
+/// Template stored by `imi types set-commit-template` when no file is given:
+/// the Conventional Commits prefix prefilled and the ticket as a trailer
+pub fn default_template(worktree_type: &str) -> String {
+    format!(
+        "{}: \n\nRefs: {{{{ticket}}}}\n\
+         # <type>(<scope>): <summary>, see https://www.conventionalcommits.org\n",
+        conventional_type(worktree_type)
+    )
+}
+
+/// The registry's template for the type, else `<type>.txt` from the user
+/// sync directory
+pub fn resolve(stored: Option<&str>, user_sync_dir: &Path, worktree_type: &str) -> Option<String> {
+    stored.map(str::to_string).or_else(|| {
+        std::fs::read_to_string(
+            user_sync_dir
+                .join(SYNC_TEMPLATE_DIR)
+                .join(format!("{}.txt", worktree_type)),
+        )
+        .ok()
+    })
+}
+
+/// Ticket id in a worktree name, e.g. `BUG-42` in `fix-bug-42-login`
+pub fn ticket_id(name: &str) -> Option<String> {
+    let re = regex::Regex::new(r"(?i)(?:^|-)([a-z][a-z0-9]*-\d+)").ok()?;
+    let ticket = re
+        .captures_iter(name)
+        .map(|captures| captures[1].to_uppercase())
+        // `feat-2` is a type prefix and a number, not a ticket
+        .find(|ticket| {
+            !ticket
+                .split('-')
+                .next()
+                .is_some_and(|key| matches!(key, "FEAT" | "FIX" | "AIOPS" | "DEVOPS" | "PR"))
+        });
+    ticket
+}
+
+/// Fill in `{{ticket}}`, `{{worktree_name}}`, `{{worktree_type}}`,
+/// `{{repo_name}}` and `{{branch_name}}`. Without a ticket in the name, lines
+/// that only exist to carry one are dropped.
+pub fn render(
+    template: &str,
+    repo_name: &str,
+    worktree_name: &str,
+    worktree_type: &str,
+    branch_name: &str,
+) -> String {
+    let ticket = ticket_id(worktree_name);
+    let template = match &ticket {
+        Some(_) => template.to_string(),
+        None => template
+            .lines()
+            .filter(|line| !line.contains("{{ticket}}") && !line.contains("{{ ticket }}"))
+            .map(|line| format!("{}\n", line))
+            .collect(),
+    };
+
+    render_copy_template(
+        &template,
+        &[
+            ("ticket", ticket.as_deref().unwrap_or_default()),
+            ("worktree_name", worktree_name),
+            ("worktree_type", worktree_type),
+            ("repo_name", repo_name),
+            ("branch_name", branch_name),
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_ticket_ids_but_not_type_prefixes() {
+        assert_eq!(ticket_id("fix-bug-42-login"), Some("BUG-42".to_string()));
+        assert_eq!(ticket_id("PROJ-7"), Some("PROJ-7".to_string()));
+        assert_eq!(ticket_id("feat-2"), None);
+        assert_eq!(ticket_id("feat-login"), None);
+    }
+
+    #[test]
+    fn renders_ticket_or_drops_its_line() {
+        let template = default_template("fix");
+        assert!(template.starts_with("fix: \n"));
+
+        let with_ticket = render(&template, "iMi", "fix-bug-42", "fix", "fix/bug-42");
+        assert!(with_ticket.contains("Refs: BUG-42"));
+
+        let without = render(&template, "iMi", "fix-login", "fix", "fix/login");
+        assert!(!without.contains("Refs:"));
+        assert!(without.starts_with("fix: \n"));
+    }
+
+    #[test]
+    fn falls_back_to_the_sync_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join(SYNC_TEMPLATE_DIR)).unwrap();
+        std::fs::write(
+            dir.path().join(SYNC_TEMPLATE_DIR).join("feat.txt"),
+            "feat: ",
+        )
+        .unwrap();
+
+        assert_eq!(
+            resolve(Some("stored"), dir.path(), "feat").as_deref(),
+            Some("stored")
+        );
+        assert_eq!(resolve(None, dir.path(), "feat").as_deref(), Some("feat: "));
+        assert_eq!(resolve(None, dir.path(), "fix"), None);
+    }
+}
@@ -1,10 +1,15 @@
 use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
 use dirs;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::env;
 use std::path::{Path, PathBuf};
 use tokio::fs;
 
+use crate::cache_clone::CacheFallback;
+use crate::ops::glob_to_regex;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     #[serde(rename = "IMI_DATABASE_PATH")]
@@ -17,12 +22,76 @@ pub struct Config {
     #[serde(rename = "IMI_SYSTEM_PATH", skip_serializing_if = "Option::is_none")]
     legacy_root_path: Option<String>,
 
+    /// Language for messages, e.g. "de" or "pt_BR"; IMI_LOCALE overrides it
+    /// and LANG is used when neither is set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+
     pub sync_settings: SyncSettings,
     pub git_settings: GitSettings,
     pub monitoring_settings: MonitoringSettings,
     pub symlink_files: Vec<String>,
     #[serde(default)]
     pub workspace_settings: WorkspaceSettings,
+    #[serde(default)]
+    pub daemon_settings: DaemonSettings,
+    #[serde(default)]
+    pub maintenance: MaintenanceSettings,
+    #[serde(default)]
+    pub artifacts: ArtifactSettings,
+    #[serde(default)]
+    pub audit_settings: AuditSettings,
+    #[serde(default)]
+    pub copy_from_trunk: CopyFromTrunkSettings,
+    #[serde(default)]
+    pub alert_settings: AlertSettings,
+    #[serde(default)]
+    pub editor: EditorSettings,
+    #[serde(default)]
+    pub retry_settings: RetrySettings,
+    #[serde(default)]
+    pub event_hooks: EventHookSettings,
+    #[serde(default)]
+    pub agent_assignment: AgentAssignmentSettings,
+    #[serde(default)]
+    pub review_settings: ReviewSettings,
+    #[serde(default)]
+    pub size_budget: SizeBudgetSettings,
+    #[serde(default)]
+    pub type_inference: TypeInferenceSettings,
+    #[serde(default)]
+    pub trash: TrashSettings,
+    #[serde(default)]
+    pub type_sharing: TypeSharingSettings,
+    #[serde(default)]
+    pub merge_policy: MergePolicySettings,
+    #[serde(default)]
+    pub org_settings: OrgSettings,
+    #[serde(default)]
+    pub git_hooks: GitHookSettings,
+    #[serde(default)]
+    pub upstream: UpstreamSettings,
+    #[serde(default)]
+    pub worktree_layout: WorktreeLayoutSettings,
+    #[serde(default)]
+    pub list_settings: ListSettings,
+    #[serde(default)]
+    pub permissions: PermissionSettings,
+    #[serde(default)]
+    pub trunk_protection: TrunkProtectionSettings,
+    #[serde(default)]
+    pub branch_cleanup: BranchCleanupSettings,
+    #[serde(default)]
+    pub remotes: RemoteSettings,
+    #[serde(default)]
+    pub fork_caches: ForkCacheSettings,
+    #[serde(default)]
+    pub agent_scaffold: AgentScaffoldSettings,
+    /// `[aliases]`: user commands expanding to an iMi command line, with
+    /// `{1}`, `{2}`... taking the alias's arguments, e.g.
+    /// `hotfix = "add fix {1} --base release/current --publish --open"`
+    #[serde(default)]
+    pub aliases: BTreeMap<String, String>,
     #[serde(skip)]
     pub repo_path: Option<PathBuf>,
 }
@@ -32,6 +101,27 @@ pub struct SyncSettings {
     pub enabled: bool,
     pub user_sync_path: PathBuf,
     pub local_sync_path: PathBuf,
+    /// `user/` and `local/` trees copied into new sync directories with
+    /// `{{repo_name}}`, `{{language}}` and `{{date}}` substituted; defaults
+    /// to `sync-scaffold` next to the global config
+    #[serde(default)]
+    pub scaffold_dir: Option<PathBuf>,
+    /// Per-repository scaffolds whose files replace same-named ones
+    #[serde(default)]
+    pub repos: HashMap<String, PathBuf>,
+}
+
+impl SyncSettings {
+    /// Scaffold directories for a repository, the per-user one first
+    pub fn scaffold_dirs_for(&self, repo_name: &str) -> Result<Vec<PathBuf>> {
+        let user = match &self.scaffold_dir {
+            Some(dir) => dir.clone(),
+            None => Config::get_global_config_path()?.with_file_name("sync-scaffold"),
+        };
+        Ok(std::iter::once(user)
+            .chain(self.repos.get(repo_name).cloned())
+            .collect())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -40,14 +130,122 @@ pub struct GitSettings {
     pub remote_name: String,
     pub auto_fetch: bool,
     pub prune_on_fetch: bool,
+    /// SSH private keys offered (after ssh-agent) before the default ~/.ssh keys
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ssh_key_paths: Vec<PathBuf>,
+    #[serde(default)]
+    pub github_auth: GitHubAuth,
+}
+
+/// Where HTTPS remotes on GitHub get a token from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GitHubAuth {
+    /// GITHUB_TOKEN (or GH_TOKEN, GITHUB_PERSONAL_ACCESS_TOKEN), then `gh auth token`
+    #[default]
+    Auto,
+    /// Only the token environment variables
+    Token,
+    /// Only the gh CLI's credential store
+    GhCli,
+    /// No token; SSH keys and the git credential helper only
+    Ssh,
+}
+
+impl GitHubAuth {
+    pub const ALL: [GitHubAuth; 4] = [Self::Auto, Self::Token, Self::GhCli, Self::Ssh];
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            Self::Auto => "auto (GITHUB_TOKEN, then gh CLI)",
+            Self::Token => "token (GITHUB_TOKEN environment variable)",
+            Self::GhCli => "gh-cli (gh auth login)",
+            Self::Ssh => "ssh (keys and credential helper only)",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct MonitoringSettings {
     pub enabled: bool,
+    /// File events for the same path closer together than this are dropped
     pub refresh_interval_ms: u64,
     pub watch_file_changes: bool,
+    /// Log file events as agent activity in the database
     pub track_agent_activity: bool,
+    /// How often `imi monitor` scans git status, checks alerts and reports
+    /// commits
+    pub status_interval_secs: u64,
+    /// Scan git status at all; off leaves only file events
+    pub git_status: bool,
+    /// Only these repositories are monitored (all when empty)
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    /// Skip status scans while the 1-minute load average per CPU (which
+    /// counts processes waiting on IO) is above this
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_load: Option<f64>,
+    /// Per-repository `status_interval_secs` and `git_status`
+    pub repos: HashMap<String, MonitorRepoSettings>,
+    /// Move clean `pr-*` review worktrees to their pull request's latest
+    /// head on each status scan, firing `pr_updated`
+    pub sync_pr_worktrees: bool,
+}
+
+impl Default for MonitoringSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            refresh_interval_ms: 1000,
+            watch_file_changes: true,
+            track_agent_activity: true,
+            status_interval_secs: 30,
+            git_status: true,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            max_load: None,
+            repos: HashMap::new(),
+            sync_pr_worktrees: false,
+        }
+    }
+}
+
+/// `[monitoring_settings.repos.<name>]`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MonitorRepoSettings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_interval_secs: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub git_status: Option<bool>,
+}
+
+impl MonitoringSettings {
+    pub fn includes(&self, repo_name: &str) -> bool {
+        let listed = |names: &[String]| names.iter().any(|n| n.eq_ignore_ascii_case(repo_name));
+        (self.include.is_empty() || listed(&self.include)) && !listed(&self.exclude)
+    }
+
+    fn repo(&self, repo_name: &str) -> Option<&MonitorRepoSettings> {
+        self.repos
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(repo_name))
+            .map(|(_, settings)| settings)
+    }
+
+    pub fn status_interval_for(&self, repo_name: &str) -> u64 {
+        self.repo(repo_name)
+            .and_then(|repo| repo.status_interval_secs)
+            .unwrap_or(self.status_interval_secs)
+            .max(1)
+    }
+
+    pub fn git_status_for(&self, repo_name: &str) -> bool {
+        self.repo(repo_name)
+            .and_then(|repo| repo.git_status)
+            .unwrap_or(self.git_status)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -79,32 +277,1115 @@ impl Default for WorkspaceSettings {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DaemonSettings {
+    /// Periodically `git fetch` every registered repository
+    pub fetch_enabled: bool,
+    pub fetch_interval_secs: u64,
+    /// Random delay (0..=jitter) added to each cycle so many hosts don't fetch in lockstep
+    pub fetch_jitter_secs: u64,
+    pub max_concurrent_fetches: usize,
+    /// Repository names the scheduler never fetches
+    pub fetch_exclude: Vec<String>,
+}
+
+impl Default for DaemonSettings {
+    fn default() -> Self {
+        Self {
+            fetch_enabled: true,
+            fetch_interval_secs: 900,
+            fetch_jitter_secs: 60,
+            max_concurrent_fetches: 4,
+            fetch_exclude: Vec::new(),
+        }
+    }
+}
+
+/// Background maintenance the daemon runs on a schedule. Each task takes a
+/// cron expression (`0 4 * * *`), `@hourly`/`@daily`/`@weekly`/`@monthly`,
+/// an interval (`every 6h`), or `off`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MaintenanceSettings {
+    /// `git fetch` every repository, on top of the regular fetch cycle
+    pub fetch: String,
+    /// `git maintenance run` (or `git gc`) in each trunk
+    pub git_maintenance: String,
+    /// Delete old agent activity and navigation rows of removed worktrees
+    pub db_gc: String,
+    /// Measure worktree sizes again for the disk usage cache
+    pub disk_usage: String,
+    /// Close worktrees older than their type's `default_ttl_days`; worktrees
+    /// with uncommitted or unpushed work are left alone
+    pub ttl_cleanup: String,
+    /// Agent activity older than this is deleted by `db_gc`
+    pub activity_retention_days: u32,
+}
+
+impl Default for MaintenanceSettings {
+    fn default() -> Self {
+        Self {
+            fetch: "off".to_string(),
+            git_maintenance: "@daily".to_string(),
+            db_gc: "@weekly".to_string(),
+            disk_usage: "every 1h".to_string(),
+            ttl_cleanup: "off".to_string(),
+            activity_retention_days: 90,
+        }
+    }
+}
+
+/// Output kept from `imi exec` and `imi check` runs (`imi artifacts`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ArtifactSettings {
+    /// Capture output of `imi exec`; `imi check` always keeps it
+    pub capture_exec: bool,
+    /// Runs kept per worktree; older ones are deleted with their output
+    pub keep_per_worktree: usize,
+}
+
+impl Default for ArtifactSettings {
+    fn default() -> Self {
+        Self {
+            capture_exec: true,
+            keep_per_worktree: 20,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AuditSettings {
+    /// Record every command invocation (also enabled by IMI_AUDIT=1)
+    pub enabled: bool,
+    /// Defaults to audit.jsonl next to the global config file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_path: Option<PathBuf>,
+    /// Flags whose values are masked before being written to the log
+    pub redact_args: Vec<String>,
+}
+
+impl Default for AuditSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            log_path: None,
+            redact_args: vec![
+                "--value".to_string(),
+                "--payload".to_string(),
+                "--token".to_string(),
+                "--password".to_string(),
+            ],
+        }
+    }
+}
+
+impl AuditSettings {
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+            || env::var("IMI_AUDIT")
+                .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+                .unwrap_or(false)
+    }
+
+    pub fn resolve_log_path(&self) -> Result<PathBuf> {
+        match &self.log_path {
+            Some(path) => Ok(path.clone()),
+            None => Ok(Config::get_global_config_path()?.with_file_name("audit.jsonl")),
+        }
+    }
+}
+
+/// Retry policy for git fetch/push, `gh` and GitHub API calls. Only transient
+/// failures (network, timeouts, 5xx, rate limits) are retried; set
+/// IMI_VERBOSE=1 to log each retry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetrySettings {
+    /// Total attempts including the first; 1 disables retries
+    pub max_attempts: u32,
+    pub initial_delay_ms: u64,
+    pub max_delay_ms: u64,
+    pub backoff_multiplier: f64,
+}
+
+impl Default for RetrySettings {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_delay_ms: 500,
+            max_delay_ms: 8000,
+            backoff_multiplier: 2.0,
+        }
+    }
+}
+
+impl RetrySettings {
+    /// Delay before retry number `retry` (1-based), capped at `max_delay_ms`
+    pub fn delay(&self, retry: u32) -> std::time::Duration {
+        let factor = self
+            .backoff_multiplier
+            .max(1.0)
+            .powi(retry.saturating_sub(1) as i32);
+        let millis = (self.initial_delay_ms as f64 * factor).min(self.max_delay_ms as f64);
+        std::time::Duration::from_millis(millis as u64)
+    }
+}
+
+/// Rules for flagging worktrees in an unhealthy state; a `None` threshold disables its rule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertSettings {
+    pub enabled: bool,
+    /// Uncommitted changes older than this many hours
+    pub dirty_after_hours: Option<u64>,
+    /// Branch behind trunk by more than this many commits
+    pub behind_trunk_commits: Option<usize>,
+    pub detached_head: bool,
+    pub merge_conflicts: bool,
+    /// Worktree directory larger than this many megabytes (walks the tree, so off by default)
+    pub disk_usage_mb: Option<u64>,
+    /// Send alerts to the desktop via notify-send / osascript
+    pub desktop_notifications: bool,
+    /// Shell command run per new alert with IMI_ALERT_* environment variables
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notify_command: Option<String>,
+}
+
+impl Default for AlertSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            dirty_after_hours: Some(24),
+            behind_trunk_commits: Some(50),
+            detached_head: true,
+            merge_conflicts: true,
+            disk_usage_mb: None,
+            desktop_notifications: false,
+            notify_command: None,
+        }
+    }
+}
+
+/// Disk budget for a repository's worktrees (trunk included), checked before
+/// each new worktree is created
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SizeBudgetSettings {
+    /// Budget in megabytes; unset disables the check
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_total_mb: Option<u64>,
+    /// Per-repository budgets, overriding `max_total_mb`
+    pub repo_max_total_mb: HashMap<String, u64>,
+    pub action: BudgetAction,
+    /// Measured worktree sizes are reused for this long
+    pub cache_ttl_secs: u64,
+    /// Cleanup candidates suggested when over budget
+    pub suggestions: usize,
+}
+
+impl Default for SizeBudgetSettings {
+    fn default() -> Self {
+        Self {
+            max_total_mb: None,
+            repo_max_total_mb: HashMap::new(),
+            action: BudgetAction::Warn,
+            cache_ttl_secs: 3600,
+            suggestions: 5,
+        }
+    }
+}
+
+impl SizeBudgetSettings {
+    /// Budget for a repository in bytes, if one is configured
+    pub fn budget_bytes(&self, repo_name: &str) -> Option<u64> {
+        self.repo_max_total_mb
+            .get(repo_name)
+            .copied()
+            .or(self.max_total_mb)
+            .map(|mb| mb * 1024 * 1024)
+    }
+}
+
+/// What creating a worktree over budget does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetAction {
+    Warn,
+    /// Refuse unless `--ignore-budget` is given
+    Refuse,
+}
+
+/// Removed and closed worktrees go to `<repo>/.iMi/trash` first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrashSettings {
+    /// When false, worktree directories are deleted right away
+    pub enabled: bool,
+    /// Trashed worktrees older than this are purged by `imi prune`, by later
+    /// removals and by `imi trash empty --expired`
+    pub retention_days: u32,
+    /// Directories that are deleted (trash disabled, orphans found by
+    /// `imi prune`) are renamed aside and deleted in the background
+    pub background_delete: bool,
+}
+
+impl Default for TrashSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            retention_days: 7,
+            background_delete: true,
+        }
+    }
+}
+
+/// Worktree type definitions committed to a repository (`imi types import`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TypeSharingSettings {
+    /// Path of the shared types file, relative to the trunk
+    pub file: PathBuf,
+    /// Add types missing locally from the shared file before `imi add`;
+    /// existing types are never changed this way
+    pub auto_import: bool,
+}
+
+impl Default for TypeSharingSettings {
+    fn default() -> Self {
+        Self {
+            file: PathBuf::from(".imi").join("types.toml"),
+            auto_import: false,
+        }
+    }
+}
+
+/// How `imi merge` lands a worktree's branch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum MergeStrategy {
+    /// Merge into trunk locally and push
+    Direct,
+    /// Merge the branch's pull request through the GitHub API
+    Pr,
+    /// Add the branch's pull request to the GitHub merge queue and wait
+    Queue,
+}
+
+/// Order of worktrees in `imi list` and `imi status`; stale work first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum WorktreeSort {
+    /// Oldest first
+    Age,
+    /// Largest on disk first
+    Size,
+    /// Least recently changed or visited first
+    Activity,
+    Name,
+}
+
+/// How `imi list` and `imi status` order and highlight worktrees
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ListSettings {
+    /// Order used without `--sort`; newest first when unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort: Option<WorktreeSort>,
+    /// Worktrees untouched for longer are dimmed and flagged; 0 disables
+    pub stale_after_days: u64,
+    /// Reuse a worktree's git status for up to this long while its HEAD,
+    /// index and upstream are unchanged; `imi daemon run` drops it as soon as
+    /// a file changes. Unstaged edits go unnoticed for this long without the
+    /// daemon, so the default 0 disables the cache
+    pub status_cache_secs: u64,
+}
+
+impl Default for ListSettings {
+    fn default() -> Self {
+        Self {
+            sort: None,
+            stale_after_days: 14,
+            status_cache_secs: 0,
+        }
+    }
+}
+
+impl ListSettings {
+    /// Whether a worktree last active at `last_active` counts as stale
+    pub fn is_stale(&self, last_active: DateTime<Utc>) -> bool {
+        self.stale_after_days > 0
+            && Utc::now() - last_active > chrono::Duration::days(self.stale_after_days as i64)
+    }
+}
+
+/// What iMi may change on this machine
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PermissionSettings {
+    /// Refuse commands that create, remove, merge or push anything; for
+    /// demo machines, on-call laptops and agents that should only inspect
+    pub read_only: bool,
+}
+
+impl PermissionSettings {
+    /// IMI_READ_ONLY is set to anything but empty, 0 or false
+    pub fn read_only_requested() -> bool {
+        std::env::var("IMI_READ_ONLY")
+            .is_ok_and(|value| !matches!(value.trim(), "" | "0" | "false"))
+    }
+}
+
+/// Guard against working directly in trunk checkouts
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrunkProtectionSettings {
+    /// Warn in `imi status` and `imi monitor` when a trunk has uncommitted
+    /// changes, unpushed commits or another branch checked out
+    pub enabled: bool,
+    /// Also refuse `imi merge` while the trunk is dirty, and have `imi merge`
+    /// and `imi sync` make clean trunks' tracked files read-only; implies
+    /// `enabled`
+    pub enforce: bool,
+}
+
+impl TrunkProtectionSettings {
+    pub fn active(&self) -> bool {
+        self.enabled || self.enforce
+    }
+}
+
+/// Untracked build caches `imi copy --caches` clones into the fork
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ForkCacheSettings {
+    /// Clone them on every `imi copy`, not only with --caches
+    pub enabled: bool,
+    /// Cache directories relative to the worktree
+    pub dirs: Vec<String>,
+    /// When the filesystem can't clone copy-on-write: hardlink, copy or skip
+    pub fallback: CacheFallback,
+    /// Caches bigger than this are skipped rather than copied
+    pub max_copy_mb: u64,
+}
+
+impl Default for ForkCacheSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            dirs: vec![
+                "target".to_string(),
+                "node_modules".to_string(),
+                ".next/cache".to_string(),
+                ".turbo".to_string(),
+            ],
+            fallback: CacheFallback::Copy,
+            max_copy_mb: 2048,
+        }
+    }
+}
+
+/// Agent instruction files `imi agent scaffold` writes to `sync/user` and
+/// links into every worktree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AgentScaffoldSettings {
+    /// Files to generate, each from the template of the same name
+    pub files: Vec<String>,
+    /// Templates named like the files, with per-repository ones in
+    /// `<repo>/` subdirectories; defaults to agent-templates next to the
+    /// global config. Files without a template use the built-in one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template_dir: Option<PathBuf>,
+}
+
+impl Default for AgentScaffoldSettings {
+    fn default() -> Self {
+        Self {
+            files: vec!["AGENTS.md".to_string(), "CLAUDE.md".to_string()],
+            template_dir: None,
+        }
+    }
+}
+
+impl AgentScaffoldSettings {
+    pub fn resolve_template_dir(&self) -> Result<PathBuf> {
+        match &self.template_dir {
+            Some(dir) => Ok(dir.clone()),
+            None => Ok(Config::get_global_config_path()?.with_file_name("agent-templates")),
+        }
+    }
+}
+
+/// Remote branches `imi branches prune-remote` never deletes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BranchCleanupSettings {
+    /// Branch name patterns (`*` and `?` wildcards), e.g. `release/*`
+    pub protected: Vec<String>,
+}
+
+impl Default for BranchCleanupSettings {
+    fn default() -> Self {
+        Self {
+            protected: vec![
+                "main".to_string(),
+                "master".to_string(),
+                "develop".to_string(),
+                "release/*".to_string(),
+            ],
+        }
+    }
+}
+
+impl BranchCleanupSettings {
+    pub fn is_protected(&self, branch: &str) -> bool {
+        self.protected
+            .iter()
+            .any(|pattern| glob_to_regex(pattern).is_ok_and(|re| re.is_match(branch)))
+    }
+}
+
+/// Remote roles per repository for fork workflows (`[remotes.repos.<name>]`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RemoteSettings {
+    pub repos: HashMap<String, RemoteRoles>,
+}
+
+/// Which remote plays which part; unset roles use `git_settings.remote_name`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RemoteRoles {
+    /// Remote new worktrees branch from and pull requests target
+    pub upstream: Option<String>,
+    /// Remote branches are pushed to, e.g. your fork
+    pub push: Option<String>,
+}
+
+/// A repository's remote roles with defaults applied
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Remotes {
+    pub upstream: String,
+    pub push: String,
+}
+
+impl Remotes {
+    /// Pushes go somewhere other than where branches come from
+    pub fn is_fork(&self) -> bool {
+        self.upstream != self.push
+    }
+}
+
+/// Merge strategy per repository, and how long to wait on merge queues
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MergePolicySettings {
+    pub strategy: MergeStrategy,
+    /// Per-repository strategies, overriding `strategy`
+    pub repos: HashMap<String, MergeStrategy>,
+    /// How often a queued pull request is checked
+    pub queue_poll_secs: u64,
+    /// Give up waiting (the PR stays queued) after this long
+    pub queue_timeout_secs: u64,
+}
+
+impl Default for MergePolicySettings {
+    fn default() -> Self {
+        Self {
+            strategy: MergeStrategy::Direct,
+            repos: HashMap::new(),
+            queue_poll_secs: 30,
+            queue_timeout_secs: 3600,
+        }
+    }
+}
+
+impl MergePolicySettings {
+    pub fn strategy_for(&self, repo_name: &str) -> MergeStrategy {
+        self.repos.get(repo_name).copied().unwrap_or(self.strategy)
+    }
+}
+
+/// How the branch of a new worktree gets an upstream
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum UpstreamMode {
+    /// Leave the branch without an upstream
+    None,
+    /// Set the worktree's `push.autoSetupRemote`, so the first `git push`
+    /// creates the remote branch and tracks it
+    #[default]
+    AutoSetupRemote,
+    /// Push the branch with `-u` right after creating the worktree
+    Push,
+}
+
+/// Upstream setup for new worktree branches, per worktree type
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UpstreamSettings {
+    pub mode: UpstreamMode,
+    /// Per-type modes, overriding `mode`
+    pub types: HashMap<String, UpstreamMode>,
+}
+
+impl UpstreamSettings {
+    pub fn mode_for(&self, worktree_type: &str) -> UpstreamMode {
+        self.types.get(worktree_type).copied().unwrap_or(self.mode)
+    }
+}
+
+/// Where worktrees go relative to the directory holding trunk (IMI_PATH):
+/// beside trunk by default, or under a subdirectory such as `worktrees`.
+/// Existing worktrees move with `imi mv --relayout`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorktreeLayoutSettings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub worktree_dir: Option<PathBuf>,
+    /// Per-repository `worktree_dir`, overriding the global one; `"."` keeps
+    /// a repository's worktrees beside trunk
+    pub repos: HashMap<String, PathBuf>,
+}
+
+impl WorktreeLayoutSettings {
+    /// Subdirectory of IMI_PATH holding the repository's worktrees, `None`
+    /// when they sit beside trunk
+    pub fn worktree_dir_for(&self, repo_name: &str) -> Option<&Path> {
+        self.repos
+            .get(repo_name)
+            .or(self.worktree_dir.as_ref())
+            .map(PathBuf::as_path)
+            .filter(|dir| !dir.as_os_str().is_empty() && *dir != Path::new("."))
+    }
+
+    /// Every configured worktree directory, for telling a worktree's
+    /// IMI_PATH from its path alone
+    pub fn worktree_dirs(&self) -> impl Iterator<Item = &Path> {
+        self.worktree_dir
+            .iter()
+            .chain(self.repos.values())
+            .map(PathBuf::as_path)
+            .filter(|dir| !dir.as_os_str().is_empty() && *dir != Path::new("."))
+    }
+}
+
+/// Git hook setup in each new worktree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GitHookSettings {
+    /// Run the install step of the hook managers a worktree uses (lefthook,
+    /// husky, pre-commit); `imi add --no-hooks` skips it once
+    pub auto_install: bool,
+    /// `core.hooksPath` for new worktrees, relative to the worktree; set
+    /// instead of running install steps
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hooks_path: Option<String>,
+    /// Per-repository `hooks_path`, overriding the global one
+    pub repos: HashMap<String, String>,
+}
+
+impl Default for GitHookSettings {
+    fn default() -> Self {
+        Self {
+            auto_install: true,
+            hooks_path: None,
+            repos: HashMap::new(),
+        }
+    }
+}
+
+impl GitHookSettings {
+    pub fn hooks_path_for(&self, repo_name: &str) -> Option<&str> {
+        self.repos
+            .get(repo_name)
+            .or(self.hooks_path.as_ref())
+            .map(String::as_str)
+    }
+}
+
+/// Which GitHub owner `org/repo` and bare repository arguments refer to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OrgSettings {
+    /// Owner assumed for a bare repository name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_org: Option<String>,
+    /// Default owner per system root, used when running inside that root
+    pub roots: BTreeMap<PathBuf, String>,
+    /// Short names for owners, e.g. `work = "acme-corp"` turns `work/api`
+    /// into `acme-corp/api`
+    pub aliases: BTreeMap<String, String>,
+    /// Without a configured default, use the owner most registered
+    /// repositories' remotes share
+    pub detect_from_remotes: bool,
+}
+
+impl Default for OrgSettings {
+    fn default() -> Self {
+        Self {
+            default_org: None,
+            roots: BTreeMap::new(),
+            aliases: BTreeMap::new(),
+            detect_from_remotes: true,
+        }
+    }
+}
+
+impl OrgSettings {
+    /// The owner an alias stands for, or `org` itself
+    pub fn expand_alias<'a>(&'a self, org: &'a str) -> &'a str {
+        self.aliases
+            .iter()
+            .find(|(alias, _)| alias.eq_ignore_ascii_case(org))
+            .map_or(org, |(_, owner)| owner.as_str())
+    }
+
+    /// The configured default owner for `dir`: the deepest root containing
+    /// it, else `default_org`
+    pub fn default_org_for(&self, dir: &Path) -> Option<&str> {
+        self.roots
+            .iter()
+            .filter(|(root, _)| dir.starts_with(root))
+            .max_by_key(|(root, _)| root.components().count())
+            .map(|(_, org)| org.as_str())
+            .or(self.default_org.as_deref())
+            .map(|org| self.expand_alias(org))
+    }
+
+    /// The owner most of `owners` share; None when empty or tied
+    pub fn detect_org(owners: impl IntoIterator<Item = String>) -> Option<String> {
+        let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+        for owner in owners {
+            *counts.entry(owner).or_default() += 1;
+        }
+        let mut ranked: Vec<(String, usize)> = counts.into_iter().collect();
+        ranked.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        match ranked.as_slice() {
+            [(_, first), (_, second), ..] if first == second => None,
+            [(owner, _), ..] => Some(owner.clone()),
+            [] => None,
+        }
+    }
+
+    /// Layer a project config on top: maps are extended, the default overridden
+    fn merge(&mut self, other: OrgSettings) {
+        if other.default_org.is_some() {
+            self.default_org = other.default_org;
+        }
+        self.roots.extend(other.roots);
+        self.aliases.extend(other.aliases);
+        self.detect_from_remotes = other.detect_from_remotes;
+    }
+}
+
+/// How `imi add <name>` picks a worktree type when none is given: a ticket
+/// prefix on the name, then the type of the worktree the command runs from,
+/// then `default_type`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TypeInferenceSettings {
+    /// Name prefixes (case-insensitive) mapped to types, e.g. "BUG-" = "fix"
+    pub prefixes: BTreeMap<String, String>,
+    pub from_context: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_type: Option<String>,
+    /// Type `imi sync` gives worktrees whose directory and branch match no
+    /// type's prefixes; without it they are left unregistered
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unmatched_type: Option<String>,
+    /// Per-repository rules: prefixes are added to the global ones, the
+    /// other fields replace them
+    pub repos: HashMap<String, RepoTypeInference>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RepoTypeInference {
+    pub prefixes: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_context: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unmatched_type: Option<String>,
+}
+
+impl Default for TypeInferenceSettings {
+    fn default() -> Self {
+        let prefixes = [
+            ("BUG-", "fix"),
+            ("FIX-", "fix"),
+            ("HOTFIX-", "fix"),
+            ("FEAT-", "feat"),
+            ("OPS-", "devops"),
+        ];
+        Self {
+            prefixes: prefixes
+                .into_iter()
+                .map(|(prefix, wt_type)| (prefix.to_string(), wt_type.to_string()))
+                .collect(),
+            from_context: true,
+            default_type: Some("feat".to_string()),
+            unmatched_type: None,
+            repos: HashMap::new(),
+        }
+    }
+}
+
+impl TypeInferenceSettings {
+    /// The rules that apply in a repository
+    pub fn for_repo(&self, repo_name: Option<&str>) -> Self {
+        let mut rules = Self {
+            repos: HashMap::new(),
+            ..self.clone()
+        };
+        if let Some(repo) = repo_name.and_then(|name| self.repos.get(name)) {
+            rules.prefixes.extend(repo.prefixes.clone());
+            rules.from_context = repo.from_context.unwrap_or(rules.from_context);
+            if repo.default_type.is_some() {
+                rules.default_type = repo.default_type.clone();
+            }
+            if repo.unmatched_type.is_some() {
+                rules.unmatched_type = repo.unmatched_type.clone();
+            }
+        }
+        rules
+    }
+
+    /// The longest configured prefix `name` starts with, and its type
+    pub fn prefix_type(&self, name: &str) -> Option<(&str, &str)> {
+        let name = name.to_lowercase();
+        self.prefixes
+            .iter()
+            .filter(|(prefix, _)| !prefix.is_empty() && name.starts_with(&prefix.to_lowercase()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(prefix, wt_type)| (prefix.as_str(), wt_type.as_str()))
+    }
+}
+
+/// Commands the monitor and daemon run when they detect an event. Events:
+/// `new_commit`, `status_changed`, `branch_ahead`, `file_created`,
+/// `file_modified`, `file_deleted`, `fetch_failed`, `pr_updated`, and each
+/// alert kind (`dirty_too_long`, `behind_trunk`, ...; thresholds come from
+/// `[alert_settings]`). All of them, plus `worktree_created`,
+/// `worktree_removed`, `merge_completed` and `pr_updated` from commands,
+/// also go to the event log `imi events` reads
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventHookSettings {
+    /// Hooks running at once; further events wait for a free slot
+    pub max_concurrent: usize,
+    /// A hook still running after this long is killed and logged as failed
+    pub timeout_secs: u64,
+    /// Failed hooks are appended here; defaults to hooks.jsonl next to the
+    /// global config file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_path: Option<PathBuf>,
+    /// Every event is appended here for `imi events`; defaults to
+    /// events.jsonl next to the global config file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_log_path: Option<PathBuf>,
+    /// The event log is rotated to `<name>.1` past this size; 0 turns it off
+    pub event_log_max_bytes: u64,
+    pub hooks: Vec<EventHook>,
+}
+
+impl Default for EventHookSettings {
+    fn default() -> Self {
+        Self {
+            max_concurrent: 2,
+            timeout_secs: 300,
+            log_path: None,
+            event_log_path: None,
+            event_log_max_bytes: 10 * 1024 * 1024,
+            hooks: Vec::new(),
+        }
+    }
+}
+
+impl EventHookSettings {
+    pub fn resolve_log_path(&self) -> Result<PathBuf> {
+        match &self.log_path {
+            Some(path) => Ok(path.clone()),
+            None => Ok(Config::get_global_config_path()?.with_file_name("hooks.jsonl")),
+        }
+    }
+
+    pub fn resolve_event_log_path(&self) -> Result<PathBuf> {
+        match &self.event_log_path {
+            Some(path) => Ok(path.clone()),
+            None => Ok(Config::get_global_config_path()?.with_file_name("events.jsonl")),
+        }
+    }
+}
+
+/// `[[event_hooks.hooks]]`: a shell command run with IMI_EVENT, IMI_REPO,
+/// IMI_WORKTREE, ... set, from the worktree directory when there is one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventHook {
+    /// Event name, or `*` for every event
+    pub event: String,
+    pub command: String,
+    /// Only fire for this repository
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repo: Option<String>,
+}
+
+/// `[[agent_assignment.rules]]`: which agent `imi add` claims a new
+/// worktree for when `--claim` isn't given. The first rule whose conditions
+/// all hold wins; a rule without conditions matches every worktree.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AgentAssignmentSettings {
+    pub rules: Vec<AssignmentRule>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssignmentRule {
+    pub agent: String,
+    /// Worktree type, e.g. "fix"
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub worktree_type: Option<String>,
+    /// Glob (`*`, `?`) over the name as given to `imi add`, without the
+    /// type prefix, e.g. "ml-*"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repo: Option<String>,
+}
+
+impl AssignmentRule {
+    pub fn matches(&self, repo: Option<&str>, worktree_type: &str, name: &str) -> bool {
+        self.worktree_type
+            .as_deref()
+            .is_none_or(|t| t.eq_ignore_ascii_case(worktree_type))
+            && self
+                .name
+                .as_deref()
+                .is_none_or(|glob| glob_to_regex(glob).is_ok_and(|re| re.is_match(name)))
+            && self.repo.as_deref().is_none_or(|r| repo == Some(r))
+    }
+
+    /// The conditions, e.g. `type=fix name=ml-*`
+    pub fn describe(&self) -> String {
+        let conditions: Vec<String> = [
+            ("type", &self.worktree_type),
+            ("name", &self.name),
+            ("repo", &self.repo),
+        ]
+        .into_iter()
+        .filter_map(|(field, value)| value.as_ref().map(|v| format!("{}={}", field, v)))
+        .collect();
+        if conditions.is_empty() {
+            "any worktree".to_string()
+        } else {
+            conditions.join(" ")
+        }
+    }
+}
+
+impl AgentAssignmentSettings {
+    /// The first rule for a worktree, with its 1-based number
+    pub fn find(
+        &self,
+        repo: Option<&str>,
+        worktree_type: &str,
+        name: &str,
+    ) -> Option<(usize, &AssignmentRule)> {
+        self.rules
+            .iter()
+            .enumerate()
+            .find(|(_, rule)| rule.matches(repo, worktree_type, name))
+            .map(|(index, rule)| (index + 1, rule))
+    }
+
+    /// Layer a project config on top: its rules are tried first
+    fn merge(&mut self, other: AgentAssignmentSettings) {
+        let global = std::mem::replace(&mut self.rules, other.rules);
+        self.rules.extend(global);
+    }
+}
+
+/// Review (`pr-*`) worktrees
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReviewSettings {
+    /// Write REVIEW_CONTEXT.json (PR metadata, base/head SHAs and per-file
+    /// diffstats) into new review worktrees, refreshed whenever they move to
+    /// a new PR head
+    pub context_file: bool,
+}
+
+impl Default for ReviewSettings {
+    fn default() -> Self {
+        Self { context_file: true }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EditorKind {
+    /// `<worktree>.code-workspace` with the sync directories as extra folders
+    Vscode,
+    /// `.zed/settings.json`; sync directories are opened alongside the worktree
+    Zed,
+}
+
+/// Editor workspace written into each worktree and opened by `imi open`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EditorSettings {
+    /// Write the workspace file whenever a worktree is created
+    pub generate_on_create: bool,
+    pub kind: EditorKind,
+    /// Command `imi open` runs; defaults to `code` or `zed`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    pub include_sync_dirs: bool,
+    /// Editor settings written into the workspace
+    pub settings: serde_json::Map<String, serde_json::Value>,
+    /// File rendered instead of the generated workspace, with the same
+    /// `{{worktree_name}}`, `{{port}}`, ... variables as `copy_from_trunk`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template: Option<PathBuf>,
+}
+
+impl Default for EditorSettings {
+    fn default() -> Self {
+        let settings = serde_json::json!({
+            "files.exclude": { "**/.iMi": true },
+            "search.exclude": { "**/node_modules": true, "**/target": true },
+        });
+
+        Self {
+            generate_on_create: false,
+            kind: EditorKind::Vscode,
+            command: None,
+            include_sync_dirs: true,
+            settings: settings.as_object().cloned().unwrap_or_default(),
+            template: None,
+        }
+    }
+}
+
+impl EditorSettings {
+    pub fn command(&self) -> &str {
+        match (&self.command, self.kind) {
+            (Some(command), _) => command,
+            (None, EditorKind::Vscode) => "code",
+            (None, EditorKind::Zed) => "zed",
+        }
+    }
+}
+
+/// Untracked files copied (not symlinked) from trunk into each new worktree,
+/// so every worktree can diverge, e.g. with its own port in `.env`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CopyFromTrunkSettings {
+    /// Paths relative to trunk copied for every repository
+    pub files: Vec<String>,
+    /// Extra paths keyed by repository name
+    pub repos: HashMap<String, Vec<String>>,
+    /// Extra paths keyed by worktree type (feat, fix, ...)
+    pub types: HashMap<String, Vec<String>>,
+    /// Substitute `{{worktree_name}}`, `{{port}}`, ... in copied text files
+    pub template: bool,
+    /// `{{port}}` is `port_base` plus a stable offset (< `port_span`) per worktree
+    pub port_base: u16,
+    pub port_span: u16,
+}
+
+impl Default for CopyFromTrunkSettings {
+    fn default() -> Self {
+        Self {
+            files: Vec::new(),
+            repos: HashMap::new(),
+            types: HashMap::new(),
+            template: true,
+            port_base: 3000,
+            port_span: 1000,
+        }
+    }
+}
+
+impl CopyFromTrunkSettings {
+    /// Files to copy for a worktree, global entries first, without duplicates
+    pub fn files_for(&self, repo_name: &str, worktree_type: &str) -> Vec<String> {
+        let mut files: Vec<String> = Vec::new();
+        let scoped = self
+            .repos
+            .get(repo_name)
+            .into_iter()
+            .chain(self.types.get(worktree_type))
+            .flatten();
+
+        for file in self.files.iter().chain(scoped) {
+            if !files.contains(file) {
+                files.push(file.clone());
+            }
+        }
+        files
+    }
+
+    /// Deterministic port for a worktree so re-creating it yields the same value
+    pub fn port_for(&self, repo_name: &str, worktree_name: &str) -> u16 {
+        // FNV-1a: stable across Rust releases, unlike DefaultHasher
+        let hash = format!("{}/{}", repo_name, worktree_name)
+            .bytes()
+            .fold(0xcbf29ce484222325u64, |hash, byte| {
+                (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+            });
+        let span = self.port_span.max(1) as u64;
+        self.port_base.saturating_add((hash % span) as u16)
+    }
+
+    /// Layer a project config on top: lists are extended, scalars overridden
+    fn merge(&mut self, other: CopyFromTrunkSettings) {
+        self.files.extend(other.files);
+        for (repo, files) in other.repos {
+            self.repos.entry(repo).or_default().extend(files);
+        }
+        for (wt_type, files) in other.types {
+            self.types.entry(wt_type).or_default().extend(files);
+        }
+        self.template = other.template;
+        self.port_base = other.port_base;
+        self.port_span = other.port_span;
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         let home_dir = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
-        let config_dir = home_dir.join(".config").join("iMi");
+        let config_dir =
+            Self::global_config_dir().unwrap_or_else(|_| home_dir.join(".config").join("iMi"));
 
         Self {
             database_path: config_dir.join("iMi.db"),
             system_roots: vec![home_dir.join("code")],
             legacy_root_path: None,
+            locale: None,
             sync_settings: SyncSettings {
                 enabled: true,
                 user_sync_path: PathBuf::from("sync/user"),
                 local_sync_path: PathBuf::from("sync/local"),
+                scaffold_dir: None,
+                repos: HashMap::new(),
             },
             git_settings: GitSettings {
                 default_branch: "main".to_string(),
                 remote_name: "origin".to_string(),
                 auto_fetch: true,
                 prune_on_fetch: true,
+                ssh_key_paths: Vec::new(),
+                github_auth: GitHubAuth::default(),
             },
-            monitoring_settings: MonitoringSettings {
-                enabled: true,
-                refresh_interval_ms: 1000,
-                watch_file_changes: true,
-                track_agent_activity: true,
-            },
+            monitoring_settings: MonitoringSettings::default(),
             symlink_files: vec![
                 ".env".to_string(),
                 ".jarad-config".to_string(),
@@ -112,6 +1393,34 @@ impl Default for Config {
                 ".gitignore.local".to_string(),
             ],
             workspace_settings: WorkspaceSettings::default(),
+            daemon_settings: DaemonSettings::default(),
+            maintenance: MaintenanceSettings::default(),
+            artifacts: ArtifactSettings::default(),
+            audit_settings: AuditSettings::default(),
+            copy_from_trunk: CopyFromTrunkSettings::default(),
+            alert_settings: AlertSettings::default(),
+            editor: EditorSettings::default(),
+            retry_settings: RetrySettings::default(),
+            event_hooks: EventHookSettings::default(),
+            agent_assignment: AgentAssignmentSettings::default(),
+            review_settings: ReviewSettings::default(),
+            size_budget: SizeBudgetSettings::default(),
+            type_inference: TypeInferenceSettings::default(),
+            trash: TrashSettings::default(),
+            type_sharing: TypeSharingSettings::default(),
+            merge_policy: MergePolicySettings::default(),
+            org_settings: OrgSettings::default(),
+            git_hooks: GitHookSettings::default(),
+            upstream: UpstreamSettings::default(),
+            worktree_layout: WorktreeLayoutSettings::default(),
+            list_settings: ListSettings::default(),
+            permissions: PermissionSettings::default(),
+            trunk_protection: TrunkProtectionSettings::default(),
+            branch_cleanup: BranchCleanupSettings::default(),
+            remotes: RemoteSettings::default(),
+            fork_caches: ForkCacheSettings::default(),
+            agent_scaffold: AgentScaffoldSettings::default(),
+            aliases: BTreeMap::new(),
             repo_path: None,
         }
     }
@@ -156,10 +1465,23 @@ impl Config {
                 config.git_settings = project_config.git_settings;
                 config.monitoring_settings = project_config.monitoring_settings;
                 config.symlink_files = project_config.symlink_files;
+                config.copy_from_trunk.merge(project_config.copy_from_trunk);
+                config.org_settings.merge(project_config.org_settings);
+                config
+                    .agent_assignment
+                    .merge(project_config.agent_assignment);
+                config.aliases.extend(project_config.aliases);
+                // A project can make iMi read-only but not lift it
+                config.permissions.read_only |= project_config.permissions.read_only;
             }
             config.repo_path = Some(project_root);
         }
 
+        // Set by `--read-only`, or by whoever launched an inspecting agent
+        if PermissionSettings::read_only_requested() {
+            config.permissions.read_only = true;
+        }
+
         Ok(config)
     }
 
@@ -185,10 +1507,7 @@ impl Config {
                 .await
                 .context(format!("Failed to read config file at {:?}", path))?;
 
-            let config: Config =
-                toml::from_str(&contents).context("Failed to parse config file")?;
-
-            Ok(config)
+            crate::config_check::check_on_load(&contents, path)
         } else {
             let config = Self::default();
             if path == Self::get_global_config_path()? {
@@ -220,12 +1539,24 @@ impl Config {
         Ok(())
     }
 
+    /// IMI_CONFIG (also set by `--config`), else `$XDG_CONFIG_HOME/iMi`,
+    /// else the platform config directory
     pub fn get_global_config_path() -> Result<PathBuf> {
-        let config_dir = dirs::config_dir()
-            .context("Could not find config directory")?
-            .join("iMi");
+        if let Some(path) = std::env::var_os("IMI_CONFIG").filter(|p| !p.is_empty()) {
+            return Ok(PathBuf::from(path));
+        }
+
+        Ok(Self::global_config_dir()?.join("config.toml"))
+    }
+
+    fn global_config_dir() -> Result<PathBuf> {
+        let base = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .filter(|p| p.is_absolute())
+            .or_else(dirs::config_dir)
+            .context("Could not find config directory")?;
 
-        Ok(config_dir.join("config.toml"))
+        Ok(base.join("iMi"))
     }
 
     /// Get the primary system root (first in the list, used for new repos)
@@ -260,7 +1591,25 @@ impl Config {
     }
 
     pub fn get_worktree_path(&self, repo_name: &str, worktree_name: &str) -> PathBuf {
-        self.get_repo_path(repo_name).join(worktree_name)
+        let repo_path = self.get_repo_path(repo_name);
+        match self.worktree_layout.worktree_dir_for(repo_name) {
+            Some(dir) => repo_path.join(dir).join(worktree_name),
+            None => repo_path.join(worktree_name),
+        }
+    }
+
+    /// Upstream and push remotes for `repo_name`
+    pub fn remotes_for(&self, repo_name: &str) -> Remotes {
+        let roles = self.remotes.repos.get(repo_name);
+        let role = |pick: fn(&RemoteRoles) -> &Option<String>| {
+            roles
+                .and_then(|roles| pick(roles).clone())
+                .unwrap_or_else(|| self.git_settings.remote_name.clone())
+        };
+        Remotes {
+            upstream: role(|roles| &roles.upstream),
+            push: role(|roles| &roles.push),
+        }
     }
 
     pub fn get_sync_path(&self, repo_name: &str, is_user: bool) -> PathBuf {
@@ -290,6 +1639,33 @@ mod tests {
     use std::fs::File;
     use tempfile::tempdir;
 
+    #[test]
+    fn test_type_inference_prefers_longest_prefix_and_repo_overrides() {
+        let mut settings = TypeInferenceSettings::default();
+        settings.repos.insert(
+            "payments".to_string(),
+            RepoTypeInference {
+                prefixes: [("PAY-FIX-".to_string(), "fix".to_string())].into(),
+                default_type: Some("aiops".to_string()),
+                unmatched_type: Some("fix".to_string()),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(settings.prefix_type("bug-123-crash"), Some(("BUG-", "fix")));
+        assert_eq!(settings.prefix_type("payment-retry"), None);
+
+        let rules = settings.for_repo(Some("payments"));
+        assert_eq!(rules.prefix_type("PAY-FIX-9"), Some(("PAY-FIX-", "fix")));
+        assert_eq!(rules.default_type.as_deref(), Some("aiops"));
+        assert_eq!(rules.unmatched_type.as_deref(), Some("fix"));
+        assert_eq!(
+            settings.for_repo(Some("other")).default_type.as_deref(),
+            Some("feat")
+        );
+        assert_eq!(settings.for_repo(Some("other")).unmatched_type, None);
+    }
+
     #[tokio::test]
     async fn test_config_default() {
         let config = Config::default();
@@ -298,6 +1674,178 @@ mod tests {
         assert!(config.sync_settings.enabled);
     }
 
+    #[test]
+    fn test_config_without_daemon_settings_uses_defaults() {
+        let mut value = toml::Value::try_from(Config::default()).unwrap();
+        value.as_table_mut().unwrap().remove("daemon_settings");
+        value.as_table_mut().unwrap().remove("maintenance");
+        value.as_table_mut().unwrap().remove("artifacts");
+
+        let config: Config = toml::from_str(&toml::to_string(&value).unwrap()).unwrap();
+        assert!(config.daemon_settings.fetch_enabled);
+        assert_eq!(config.daemon_settings.fetch_interval_secs, 900);
+        assert!(config.daemon_settings.fetch_exclude.is_empty());
+        assert_eq!(config.maintenance.git_maintenance, "@daily");
+        assert_eq!(config.maintenance.ttl_cleanup, "off");
+        assert!(config.artifacts.capture_exec);
+        assert_eq!(config.artifacts.keep_per_worktree, 20);
+    }
+
+    #[test]
+    fn test_copy_from_trunk_files_and_ports() {
+        let mut settings = CopyFromTrunkSettings {
+            files: vec![".env".to_string()],
+            ..Default::default()
+        };
+        settings.repos.insert(
+            "iMi".to_string(),
+            vec![".env".to_string(), "local.toml".to_string()],
+        );
+        settings
+            .types
+            .insert("feat".to_string(), vec![".feat-only".to_string()]);
+
+        assert_eq!(
+            settings.files_for("iMi", "feat"),
+            vec![".env", "local.toml", ".feat-only"]
+        );
+        assert_eq!(settings.files_for("other", "fix"), vec![".env"]);
+
+        let port = settings.port_for("iMi", "feat-auth");
+        assert_eq!(port, settings.port_for("iMi", "feat-auth"));
+        assert!((3000..4000).contains(&port));
+    }
+
+    #[test]
+    fn test_org_settings_roots_aliases_and_detection() {
+        let settings = OrgSettings {
+            default_org: Some("me".to_string()),
+            roots: [
+                (PathBuf::from("/code"), "personal".to_string()),
+                (PathBuf::from("/code/work"), "acme-corp".to_string()),
+            ]
+            .into(),
+            aliases: [("me".to_string(), "jdoe".to_string())].into(),
+            ..Default::default()
+        };
+
+        assert_eq!(settings.expand_alias("ME"), "jdoe");
+        assert_eq!(settings.expand_alias("other"), "other");
+        assert_eq!(
+            settings.default_org_for(Path::new("/code/work/api")),
+            Some("acme-corp")
+        );
+        assert_eq!(
+            settings.default_org_for(Path::new("/code/blog")),
+            Some("personal")
+        );
+        assert_eq!(settings.default_org_for(Path::new("/tmp")), Some("jdoe"));
+        assert_eq!(
+            OrgSettings::default().default_org_for(Path::new("/tmp")),
+            None
+        );
+
+        let owners = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            OrgSettings::detect_org(owners(&["acme", "jdoe", "acme"])),
+            Some("acme".to_string())
+        );
+        assert_eq!(OrgSettings::detect_org(owners(&["acme", "jdoe"])), None);
+        assert_eq!(OrgSettings::detect_org(owners(&[])), None);
+    }
+
+    #[test]
+    fn test_agent_assignment_rules() {
+        let toml_str = r#"
+            [[rules]]
+            type = "fix"
+            repo = "api"
+            agent = "api-bugfixer"
+
+            [[rules]]
+            type = "fix"
+            agent = "bugfixer"
+
+            [[rules]]
+            name = "ml-*"
+            agent = "ml-agent"
+        "#;
+        let mut assignment: AgentAssignmentSettings = toml::from_str(toml_str).unwrap();
+
+        let agent = |a: &AgentAssignmentSettings, repo, wt_type, name| {
+            a.find(repo, wt_type, name)
+                .map(|(number, rule)| (number, rule.agent.clone()))
+        };
+        assert_eq!(
+            agent(&assignment, Some("api"), "fix", "login"),
+            Some((1, "api-bugfixer".to_string()))
+        );
+        assert_eq!(
+            agent(&assignment, Some("web"), "FIX", "login"),
+            Some((2, "bugfixer".to_string()))
+        );
+        assert_eq!(
+            agent(&assignment, None, "feat", "ml-ranking"),
+            Some((3, "ml-agent".to_string()))
+        );
+        assert_eq!(agent(&assignment, None, "feat", "html-ml"), None);
+
+        // Project rules are tried before the global ones
+        assignment.merge(AgentAssignmentSettings {
+            rules: vec![AssignmentRule {
+                agent: "anyone".to_string(),
+                worktree_type: None,
+                name: None,
+                repo: None,
+            }],
+        });
+        assert_eq!(
+            agent(&assignment, Some("api"), "fix", "login"),
+            Some((1, "anyone".to_string()))
+        );
+        assert_eq!(assignment.rules[0].describe(), "any worktree");
+        assert_eq!(assignment.rules[1].describe(), "type=fix repo=api");
+    }
+
+    #[test]
+    fn test_monitoring_settings_per_repo() {
+        let settings: MonitoringSettings = toml::from_str(
+            r#"
+            refresh_interval_ms = 500
+            exclude = ["Vendor"]
+
+            [repos.monorepo]
+            status_interval_secs = 300
+            git_status = false
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(settings.status_interval_secs, 30);
+        assert_eq!(settings.status_interval_for("MonoRepo"), 300);
+        assert_eq!(settings.status_interval_for("api"), 30);
+        assert!(!settings.git_status_for("monorepo"));
+        assert!(settings.git_status_for("api"));
+        assert!(settings.includes("api"));
+        assert!(!settings.includes("vendor"));
+
+        let only_api = MonitoringSettings {
+            include: vec!["api".to_string()],
+            ..settings
+        };
+        assert!(only_api.includes("api"));
+        assert!(!only_api.includes("monorepo"));
+    }
+
+    #[test]
+    fn test_retry_delay_backs_off_exponentially_up_to_the_cap() {
+        let settings = RetrySettings::default();
+        let delays: Vec<u64> = (1..=6)
+            .map(|retry| settings.delay(retry).as_millis() as u64)
+            .collect();
+        assert_eq!(delays, vec![500, 1000, 2000, 4000, 8000, 8000]);
+    }
+
     #[tokio::test]
     async fn test_config_paths() {
         let config = Config::default();
@@ -313,6 +1861,31 @@ mod tests {
         assert!(worktree_path.to_string_lossy().contains("feat-test"));
     }
 
+    #[test]
+    fn test_worktree_dir_per_repo() {
+        let mut config = Config::default();
+        config.worktree_layout = toml::from_str(
+            r#"
+            worktree_dir = "worktrees"
+            repos = { legacy = "." }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.get_worktree_path("api", "feat-x"),
+            config.get_repo_path("api").join("worktrees").join("feat-x")
+        );
+        assert_eq!(
+            config.get_worktree_path("legacy", "feat-x"),
+            config.get_repo_path("legacy").join("feat-x")
+        );
+        assert_eq!(
+            config.worktree_layout.worktree_dirs().collect::<Vec<_>>(),
+            vec![Path::new("worktrees")]
+        );
+    }
+
     #[tokio::test]
     async fn test_find_project_root() {
         let dir = tempdir().unwrap();
@@ -331,4 +1904,77 @@ mod tests {
         let not_found_root = Config::find_project_root().unwrap();
         assert_eq!(not_found_root, None);
     }
+
+    #[test]
+    fn test_sync_scaffold_dirs_per_repo() {
+        let toml_str = r#"
+enabled = true
+user_sync_path = "sync/user"
+local_sync_path = "sync/local"
+scaffold_dir = "/scaffolds/default"
+
+[repos]
+api = "/scaffolds/api"
+"#;
+        let settings: SyncSettings = toml::from_str(toml_str).unwrap();
+        assert_eq!(
+            settings.scaffold_dirs_for("api").unwrap(),
+            vec![
+                PathBuf::from("/scaffolds/default"),
+                PathBuf::from("/scaffolds/api")
+            ]
+        );
+        assert_eq!(
+            settings.scaffold_dirs_for("web").unwrap(),
+            vec![PathBuf::from("/scaffolds/default")]
+        );
+    }
+
+    #[test]
+    fn test_list_settings_staleness() {
+        let settings: ListSettings = toml::from_str("sort = \"activity\"").unwrap();
+        assert_eq!(settings.sort, Some(WorktreeSort::Activity));
+        assert_eq!(settings.stale_after_days, 14);
+        assert!(settings.is_stale(Utc::now() - chrono::Duration::days(15)));
+        assert!(!settings.is_stale(Utc::now() - chrono::Duration::days(13)));
+
+        let disabled = ListSettings {
+            stale_after_days: 0,
+            ..ListSettings::default()
+        };
+        assert!(!disabled.is_stale(Utc::now() - chrono::Duration::days(365)));
+    }
+
+    #[test]
+    fn test_remote_roles_per_repo() {
+        let config = Config {
+            remotes: toml::from_str(
+                r#"
+[repos.api]
+upstream = "upstream"
+"#,
+            )
+            .unwrap(),
+            ..Config::default()
+        };
+
+        let api = config.remotes_for("api");
+        assert_eq!(api.upstream, "upstream");
+        assert_eq!(api.push, config.git_settings.remote_name);
+        assert!(api.is_fork());
+        assert!(!config.remotes_for("web").is_fork());
+    }
+
+    #[test]
+    fn test_branch_cleanup_protected_patterns() {
+        let defaults = BranchCleanupSettings::default();
+        assert!(defaults.is_protected("main"));
+        assert!(defaults.is_protected("release/2.0"));
+        assert!(!defaults.is_protected("feat/login"));
+
+        let settings: BranchCleanupSettings =
+            toml::from_str("protected = [\"feat/keep-*\"]").unwrap();
+        assert!(settings.is_protected("feat/keep-me"));
+        assert!(!settings.is_protected("main"));
+    }
 }
@@ -0,0 +1,544 @@
+//! Checks a config file before iMi uses it (`imi config validate`, and every
+//! load): TOML syntax, value types, unknown keys with a "did you mean",
+//! options that conflict or get clamped, schedules, and on request whether
+//! configured paths exist. Each finding points at the line it's about.
+
+use colored::*;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::config::Config;
+use crate::maintenance::Schedule;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The file can't be used as is
+    Error,
+    /// Loaded, but probably not what was meant
+    Warning,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// Dotted key, e.g. `git_settings.default_branch`; empty for syntax errors
+    pub key: String,
+    /// 1-based line in the file, when it could be located
+    pub line: Option<usize>,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "line {}: ", line)?;
+        }
+        if !self.key.is_empty() {
+            write!(f, "{}: ", self.key)?;
+        }
+        write!(f, "{}", self.message)?;
+        if let Some(hint) = &self.hint {
+            write!(f, " ({})", hint)?;
+        }
+        Ok(())
+    }
+}
+
+impl Diagnostic {
+    pub fn print(&self) {
+        let label = match self.severity {
+            Severity::Error => "error".bright_red().bold(),
+            Severity::Warning => "warning".bright_yellow().bold(),
+        };
+        println!("   {} {}", label, self);
+    }
+}
+
+/// Parse `contents` as a config file and check it; the config is None when
+/// it doesn't parse. Paths are only looked up on disk with `check_paths`.
+pub fn check(contents: &str, check_paths: bool) -> (Option<Config>, Vec<Diagnostic>) {
+    let lines = LineIndex::new(contents);
+    let document = match toml_edit::ImDocument::parse(contents) {
+        Ok(document) => document,
+        Err(e) => {
+            let diagnostic = Diagnostic {
+                severity: Severity::Error,
+                key: String::new(),
+                line: e.span().map(|span| lines.line(span.start)),
+                message: format!("invalid TOML: {}", e.message().trim_end()),
+                hint: None,
+            };
+            return (None, vec![diagnostic]);
+        }
+    };
+    let locate = |key: &[String]| key_span(document.as_table(), key).map(|s| lines.line(s.start));
+
+    let mut ignored = Vec::new();
+    let parsed: Result<Config, _> =
+        serde_ignored::deserialize(toml::Deserializer::new(contents), |path| {
+            let mut key = Vec::new();
+            path_segments(&path, &mut key);
+            ignored.push(key);
+        });
+    let config = match parsed {
+        Ok(config) => config,
+        Err(e) => {
+            let diagnostic = Diagnostic {
+                severity: Severity::Error,
+                key: String::new(),
+                line: e.span().map(|span| lines.line(span.start)),
+                message: e.message().trim_end().to_string(),
+                hint: None,
+            };
+            return (None, vec![diagnostic]);
+        }
+    };
+
+    let known = toml::Value::try_from(Config::default()).ok();
+    let mut diagnostics: Vec<Diagnostic> = ignored
+        .iter()
+        .map(|key| Diagnostic {
+            severity: Severity::Warning,
+            key: key.join("."),
+            line: locate(key),
+            message: "unknown key, ignored".to_string(),
+            hint: known
+                .as_ref()
+                .and_then(|known| suggest(known, key))
+                .map(|name| format!("did you mean `{}`?", name)),
+        })
+        .collect();
+
+    for finding in value_findings(&config, contents, check_paths) {
+        let key: Vec<String> = finding.key.split('.').map(String::from).collect();
+        diagnostics.push(Diagnostic {
+            line: locate(&key),
+            ..finding
+        });
+    }
+    diagnostics.sort_by_key(|d| d.line.unwrap_or(usize::MAX));
+
+    (Some(config), diagnostics)
+}
+
+/// Check a config file on load: errors come back as one message listing
+/// them, warnings are printed (once per file and process) to stderr. A bad
+/// `maintenance.*` schedule only warns here; `imi config validate` and the
+/// maintenance runner reject it
+pub fn check_on_load(contents: &str, path: &Path) -> anyhow::Result<Config> {
+    let (config, mut diagnostics) = check(contents, false);
+    for diagnostic in &mut diagnostics {
+        if diagnostic.key.starts_with("maintenance.") {
+            diagnostic.severity = Severity::Warning;
+        }
+    }
+    let (errors, warnings): (Vec<_>, Vec<_>) = diagnostics
+        .iter()
+        .partition(|d| d.severity == Severity::Error);
+    if !errors.is_empty() {
+        let list: Vec<String> = errors.iter().map(|d| format!("  {}", d)).collect();
+        anyhow::bail!(
+            "Failed to parse config file {}:\n{}\nRun `imi config validate` for every finding",
+            path.display(),
+            list.join("\n")
+        );
+    }
+
+    if !warnings.is_empty() && first_report(path) {
+        for warning in warnings {
+            eprintln!("{} {}: {}", "⚠️".bright_yellow(), path.display(), warning);
+        }
+    }
+    config.ok_or_else(|| anyhow::anyhow!("Failed to parse config file {}", path.display()))
+}
+
+/// Whether this process hasn't shown `path`'s warnings yet; Config::load
+/// runs more than once per command
+pub fn first_report(path: &Path) -> bool {
+    static REPORTED: Mutex<Option<HashSet<PathBuf>>> = Mutex::new(None);
+    let mut reported = REPORTED.lock().unwrap_or_else(|e| e.into_inner());
+    reported
+        .get_or_insert_with(HashSet::new)
+        .insert(path.into())
+}
+
+/// Checks of parsed values; keys are located by the caller
+fn value_findings(config: &Config, contents: &str, check_paths: bool) -> Vec<Diagnostic> {
+    let mut findings = Vec::new();
+    let mut warn = |key: &str, message: String, hint: Option<&str>| {
+        findings.push(Diagnostic {
+            severity: Severity::Warning,
+            key: key.to_string(),
+            line: None,
+            message,
+            hint: hint.map(String::from),
+        });
+    };
+
+    // The legacy spelling only counts while IMI_SYSTEM_PATHS is empty
+    let raw: Option<toml::Table> = contents.parse().ok();
+    let legacy_roots = raw
+        .as_ref()
+        .is_some_and(|raw| raw.contains_key("IMI_SYSTEM_PATH"));
+    if legacy_roots && !config.system_roots.is_empty() {
+        warn(
+            "IMI_SYSTEM_PATH",
+            "ignored because IMI_SYSTEM_PATHS is also set".to_string(),
+            Some("remove IMI_SYSTEM_PATH"),
+        );
+    }
+
+    let monitoring = &config.monitoring_settings;
+    for repo in &monitoring.include {
+        if monitoring
+            .exclude
+            .iter()
+            .any(|excluded| excluded.eq_ignore_ascii_case(repo))
+        {
+            warn(
+                "monitoring_settings.exclude",
+                format!("`{}` is both included and excluded; exclude wins", repo),
+                None,
+            );
+        }
+    }
+
+    let hooks = &config.git_hooks;
+    if hooks.hooks_path.is_some() && hooks.auto_install {
+        warn(
+            "git_hooks.hooks_path",
+            "set together with auto_install; hooks_path wins and no install step runs".to_string(),
+            Some("set auto_install = false to make that explicit"),
+        );
+    }
+
+    let layout = &config.worktree_layout;
+    if layout
+        .worktree_dir
+        .as_ref()
+        .is_some_and(|dir| dir.is_absolute())
+    {
+        warn(
+            "worktree_layout.worktree_dir",
+            "should be relative to the directory holding trunk".to_string(),
+            None,
+        );
+    }
+
+    let retry = &config.retry_settings;
+    if retry.max_attempts == 0 {
+        warn(
+            "retry_settings.max_attempts",
+            "0 behaves like 1 (no retries)".to_string(),
+            Some("use 1 to disable retries"),
+        );
+    }
+    if retry.backoff_multiplier < 1.0 {
+        warn(
+            "retry_settings.backoff_multiplier",
+            format!("{} is below 1 and used as 1", retry.backoff_multiplier),
+            None,
+        );
+    }
+    if retry.initial_delay_ms > retry.max_delay_ms {
+        warn(
+            "retry_settings.initial_delay_ms",
+            format!(
+                "{}ms is over max_delay_ms ({}ms), so every retry waits max_delay_ms",
+                retry.initial_delay_ms, retry.max_delay_ms
+            ),
+            None,
+        );
+    }
+
+    let policy = &config.merge_policy;
+    if policy.queue_timeout_secs < policy.queue_poll_secs {
+        warn(
+            "merge_policy.queue_timeout_secs",
+            "shorter than queue_poll_secs, so the queue is polled at most once".to_string(),
+            None,
+        );
+    }
+
+    if check_paths {
+        let mut paths: Vec<(String, &Path)> = config
+            .system_roots
+            .iter()
+            .map(|root| ("IMI_SYSTEM_PATHS".to_string(), root.as_path()))
+            .collect();
+        let sync = &config.sync_settings;
+        paths.extend(
+            sync.scaffold_dir
+                .iter()
+                .map(|dir| ("sync_settings.scaffold_dir".to_string(), dir.as_path())),
+        );
+        paths.extend(
+            sync.repos
+                .iter()
+                .map(|(repo, dir)| (format!("sync_settings.repos.{}", repo), dir.as_path())),
+        );
+        paths.extend(
+            config
+                .editor
+                .template
+                .iter()
+                .map(|file| ("editor.template".to_string(), file.as_path())),
+        );
+        paths.extend(
+            config
+                .git_settings
+                .ssh_key_paths
+                .iter()
+                .map(|key| ("git_settings.ssh_key_paths".to_string(), key.as_path())),
+        );
+        for (key, path) in paths {
+            if !expand_home(path).exists() {
+                warn(&key, format!("{} does not exist", path.display()), None);
+            }
+        }
+    }
+
+    let maintenance = &config.maintenance;
+    let schedules = [
+        ("fetch", &maintenance.fetch),
+        ("git_maintenance", &maintenance.git_maintenance),
+        ("db_gc", &maintenance.db_gc),
+        ("disk_usage", &maintenance.disk_usage),
+        ("ttl_cleanup", &maintenance.ttl_cleanup),
+    ];
+    for (task, spec) in schedules {
+        if let Err(e) = Schedule::parse(spec) {
+            findings.push(Diagnostic {
+                severity: Severity::Error,
+                key: format!("maintenance.{}", task),
+                line: None,
+                message: format!("invalid schedule `{}`: {}", spec, e),
+                hint: Some("use a cron expression, @daily, `every 6h` or off".to_string()),
+            });
+        }
+    }
+
+    findings
+}
+
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+fn path_segments(path: &serde_ignored::Path, out: &mut Vec<String>) {
+    use serde_ignored::Path;
+    match path {
+        Path::Root => {}
+        Path::Seq { parent, index } => {
+            path_segments(parent, out);
+            out.push(index.to_string());
+        }
+        Path::Map { parent, key } => {
+            path_segments(parent, out);
+            out.push(key.clone());
+        }
+        Path::Some { parent }
+        | Path::NewtypeStruct { parent }
+        | Path::NewtypeVariant { parent } => path_segments(parent, out),
+    }
+}
+
+/// The closest known key to the unknown last segment of `key`, among its
+/// siblings in the default config
+fn suggest(known: &toml::Value, key: &[String]) -> Option<String> {
+    let (name, parents) = key.split_last()?;
+    let mut table = known;
+    for parent in parents {
+        table = match (table, parent.parse::<usize>()) {
+            (toml::Value::Array(items), Ok(index)) => items.get(index)?,
+            (value, _) => value.get(parent)?,
+        };
+    }
+    let limit = (name.len() / 3).max(1);
+    table
+        .as_table()?
+        .keys()
+        .map(|candidate| (strsim::damerau_levenshtein(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .min()
+        .map(|(_, candidate)| candidate.clone())
+}
+
+/// Span of the deepest part of `key` present in the document
+fn key_span(table: &toml_edit::Table, key: &[String]) -> Option<Range<usize>> {
+    let (first, rest) = key.split_first()?;
+    let (found, item) = table.get_key_value(first)?;
+    item_span(item, rest).or_else(|| found.span())
+}
+
+fn item_span(item: &toml_edit::Item, key: &[String]) -> Option<Range<usize>> {
+    let (first, rest) = key.split_first()?;
+    match item {
+        toml_edit::Item::Table(table) => key_span(table, key),
+        toml_edit::Item::ArrayOfTables(tables) => {
+            let table = tables.get(first.parse().ok()?)?;
+            key_span(table, rest).or_else(|| table.span())
+        }
+        toml_edit::Item::Value(value) => value_span(value, first, rest),
+        toml_edit::Item::None => None,
+    }
+}
+
+fn value_span(value: &toml_edit::Value, first: &str, rest: &[String]) -> Option<Range<usize>> {
+    match value {
+        toml_edit::Value::InlineTable(table) => {
+            let (found, item) = table.get_key_value(first)?;
+            match (rest.split_first(), item.as_value()) {
+                (Some((next, rest)), Some(value)) => {
+                    value_span(value, next, rest).or_else(|| found.span())
+                }
+                _ => found.span(),
+            }
+        }
+        toml_edit::Value::Array(items) => {
+            let value = items.get(first.parse().ok()?)?;
+            match rest.split_first() {
+                Some((next, rest)) => value_span(value, next, rest).or_else(|| value.span()),
+                None => value.span(),
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Byte offsets to 1-based line numbers
+struct LineIndex {
+    starts: Vec<usize>,
+}
+
+impl LineIndex {
+    fn new(contents: &str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(contents.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { starts }
+    }
+
+    fn line(&self, offset: usize) -> usize {
+        self.starts.partition_point(|&start| start <= offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base() -> String {
+        toml::to_string(&Config::default()).unwrap()
+    }
+
+    fn line_of(contents: &str, prefix: &str) -> Option<usize> {
+        contents
+            .lines()
+            .position(|line| line.starts_with(prefix))
+            .map(|index| index + 1)
+    }
+
+    #[test]
+    fn default_config_is_clean() {
+        let (config, diagnostics) = check(&base(), false);
+        assert!(config.is_some());
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+    }
+
+    #[test]
+    fn reports_syntax_and_type_errors_with_lines() {
+        let (config, diagnostics) = check("IMI_DATABASE_PATH = \"x\"\n[git_settings\n", false);
+        assert!(config.is_none());
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert_eq!(diagnostics[0].line, Some(2));
+
+        let contents = base().replace("refresh_interval_ms = 1000", "refresh_interval_ms = \"1s\"");
+        let (config, diagnostics) = check(&contents, false);
+        assert!(config.is_none());
+        assert_eq!(
+            diagnostics[0].line,
+            line_of(&contents, "refresh_interval_ms")
+        );
+        assert!(diagnostics[0].message.contains("invalid type"));
+    }
+
+    #[test]
+    fn unknown_keys_get_a_line_and_a_suggestion() {
+        let contents = base()
+            .replace(
+                "[alert_settings]\n",
+                "[alert_settings]\ndesktop_notificatoins = true\n",
+            )
+            .replace(
+                "hooks = []",
+                "hooks = [{ event = \"*\", command = \"true\", repos = \"api\" }]",
+            );
+        let (config, diagnostics) = check(&contents, false);
+        assert!(config.is_some());
+
+        let typo = diagnostics
+            .iter()
+            .find(|d| d.key == "alert_settings.desktop_notificatoins")
+            .expect("typo reported");
+        assert_eq!(typo.severity, Severity::Warning);
+        assert_eq!(typo.line, line_of(&contents, "desktop_notificatoins"));
+        assert_eq!(
+            typo.hint.as_deref(),
+            Some("did you mean `desktop_notifications`?")
+        );
+
+        let hook = diagnostics
+            .iter()
+            .find(|d| d.key == "event_hooks.hooks.0.repos")
+            .expect("unknown hook key reported");
+        assert_eq!(hook.line, line_of(&contents, "hooks = ["));
+    }
+
+    #[test]
+    fn flags_conflicts_schedules_and_missing_paths() {
+        let contents = base()
+            .replace("max_attempts = 3", "max_attempts = 0")
+            .replace("db_gc = \"@weekly\"", "db_gc = \"sometimes\"");
+        let (config, diagnostics) = check(&contents, false);
+        assert!(config.is_some());
+        let retries = diagnostics
+            .iter()
+            .find(|d| d.key == "retry_settings.max_attempts")
+            .expect("max_attempts = 0 reported");
+        assert_eq!(retries.severity, Severity::Warning);
+        assert_eq!(retries.line, line_of(&contents, "max_attempts"));
+        let schedule = diagnostics
+            .iter()
+            .find(|d| d.key == "maintenance.db_gc")
+            .expect("bad schedule reported");
+        assert_eq!(schedule.severity, Severity::Error);
+        assert_eq!(schedule.line, line_of(&contents, "db_gc"));
+        let loaded = check_on_load(&contents, Path::new("/tmp/imi-schedule-check.toml"))
+            .expect("a bad schedule only warns on load");
+        assert_eq!(loaded.maintenance.db_gc, "sometimes");
+
+        let contents = base().replace(
+            "IMI_SYSTEM_PATHS = [\"",
+            "IMI_SYSTEM_PATH = \"/old\"\nIMI_SYSTEM_PATHS = [\"/nonexistent/imi-root\", \"",
+        );
+        let (_, diagnostics) = check(&contents, false);
+        let keys: Vec<&str> = diagnostics.iter().map(|d| d.key.as_str()).collect();
+        assert_eq!(keys, ["IMI_SYSTEM_PATH"]);
+        let (_, diagnostics) = check(&contents, true);
+        let missing = diagnostics
+            .iter()
+            .find(|d| d.message.contains("/nonexistent/imi-root"))
+            .expect("missing root reported");
+        assert_eq!(missing.line, line_of(&contents, "IMI_SYSTEM_PATHS"));
+    }
+}
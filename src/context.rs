@@ -4,7 +4,13 @@
 //! within the Git worktree hierarchy and making intelligent decisions about
 //! which repositories and worktrees to display.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// Directories never searched for nested repositories
+const NESTED_SCAN_SKIPPED_DIRS: &[&str] = &[".git", "node_modules", "target", ".venv"];
+
+/// How deep below a worktree to look for nested repositories
+const NESTED_SCAN_MAX_DEPTH: usize = 4;
 
 /// Represents the Git context of the current location
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -180,3 +186,120 @@ impl WorktreeLocationType {
         }
     }
 }
+
+/// A git repository inside another repository's working tree that isn't a
+/// submodule, e.g. a vendored checkout
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NestedRepository {
+    pub path: PathBuf,
+    /// The repository or worktree it sits in
+    pub outer: PathBuf,
+}
+
+/// Whether `dir` is the top of a repository or worktree
+pub fn is_repository_root(dir: &Path) -> bool {
+    dir.join(".git").exists()
+}
+
+/// Submodules keep their git directory under the outer repository's
+/// `.git/modules`, which a vendored checkout never does
+fn is_submodule(dir: &Path) -> bool {
+    std::fs::read_to_string(dir.join(".git")).is_ok_and(|contents| {
+        contents
+            .strip_prefix("gitdir:")
+            .is_some_and(|gitdir| gitdir.contains("/modules/"))
+    })
+}
+
+/// The nested repository `path` is in, if the repository it belongs to sits
+/// inside another one's working tree
+pub fn nested_repository(path: &Path) -> Option<NestedRepository> {
+    let mut ancestors = path.ancestors().filter(|dir| is_repository_root(dir));
+    let inner = ancestors.next()?;
+    if is_submodule(inner) {
+        return None;
+    }
+    let outer = ancestors.next()?;
+    Some(NestedRepository {
+        path: inner.to_path_buf(),
+        outer: outer.to_path_buf(),
+    })
+}
+
+/// Nested repositories below `root`, relative to it. Submodules and the
+/// contents of a nested repository are not included.
+pub fn find_nested_repositories(root: &Path) -> Vec<PathBuf> {
+    let _span = tracing::info_span!(
+        "fs",
+        detail = format!("nested repositories in {}", root.display())
+    )
+    .entered();
+    let mut found = Vec::new();
+    let mut walker = walkdir::WalkDir::new(root)
+        .min_depth(1)
+        .max_depth(NESTED_SCAN_MAX_DEPTH)
+        .into_iter();
+    while let Some(entry) = walker.next() {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        if !entry.file_type().is_dir() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy();
+        if NESTED_SCAN_SKIPPED_DIRS.contains(&name.as_ref()) {
+            walker.skip_current_dir();
+            continue;
+        }
+        if is_repository_root(entry.path()) {
+            if !is_submodule(entry.path()) {
+                if let Ok(relative) = entry.path().strip_prefix(root) {
+                    found.push(relative.to_path_buf());
+                }
+            }
+            walker.skip_current_dir();
+        }
+    }
+    found.sort();
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// outer/ with a vendored checkout and a submodule inside
+    fn outer_repository() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let outer = dir.path().join("outer");
+        std::fs::create_dir_all(outer.join(".git/modules/sub")).unwrap();
+        std::fs::create_dir_all(outer.join("vendor/lib/.git")).unwrap();
+        std::fs::create_dir_all(outer.join("vendor/lib/deep/.git")).unwrap();
+        std::fs::create_dir_all(outer.join("sub/src")).unwrap();
+        std::fs::write(outer.join("sub/.git"), "gitdir: ../.git/modules/sub\n").unwrap();
+        std::fs::create_dir_all(outer.join("node_modules/pkg/.git")).unwrap();
+        std::fs::create_dir_all(outer.join("src")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn detects_being_inside_a_nested_repository() {
+        let dir = outer_repository();
+        let outer = dir.path().join("outer");
+
+        let nested = nested_repository(&outer.join("vendor/lib")).unwrap();
+        assert_eq!(nested.path, outer.join("vendor/lib"));
+        assert_eq!(nested.outer, outer);
+
+        assert_eq!(nested_repository(&outer.join("src")), None);
+        assert_eq!(nested_repository(&outer.join("sub/src")), None);
+        assert_eq!(nested_repository(dir.path()), None);
+    }
+
+    #[test]
+    fn finds_nested_repositories_but_not_submodules() {
+        let dir = outer_repository();
+        let found = find_nested_repositories(&dir.path().join("outer"));
+        assert_eq!(found, vec![PathBuf::from("vendor/lib")]);
+    }
+}
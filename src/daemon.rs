@@ -0,0 +1,467 @@
+use anyhow::Result;
+use colored::*;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, Semaphore};
+use tokio::task::JoinSet;
+use tokio::{signal, time};
+
+use crate::alerts::{self, AlertEvaluator, AlertTracker};
+use crate::config::{AlertSettings, Config, DaemonSettings};
+use crate::database::{Database, Repository};
+use crate::event_hooks::{CommitTracker, HookEvent, HookRunner, StatusTracker};
+use crate::git::GitManager;
+use crate::pending_delete::PendingDeletes;
+
+/// How often the status cache watcher picks up added and removed worktrees
+const WATCH_RESCAN_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Outcome of fetching a single repository
+#[derive(Debug, Clone)]
+pub struct FetchOutcome {
+    pub repo_name: String,
+    pub duration_ms: i64,
+    pub error: Option<String>,
+}
+
+/// Background scheduler that keeps remote-tracking refs fresh so status and
+/// monitor can compute ahead/behind without paying the network cost.
+#[derive(Debug, Clone)]
+pub struct FetchScheduler {
+    db: Database,
+    git: GitManager,
+    remote_name: String,
+    trunk_branch: String,
+    settings: DaemonSettings,
+    alert_settings: AlertSettings,
+    hooks: HookRunner,
+}
+
+impl FetchScheduler {
+    pub fn new(db: Database, config: &Config) -> Self {
+        Self {
+            db,
+            git: GitManager::from_config(config),
+            remote_name: config.git_settings.remote_name.clone(),
+            trunk_branch: config.git_settings.default_branch.clone(),
+            settings: config.daemon_settings.clone(),
+            alert_settings: config.alert_settings.clone(),
+            hooks: HookRunner::new(&config.event_hooks),
+        }
+    }
+
+    /// Run fetch cycles until interrupted with Ctrl+C
+    pub async fn run(&self) -> Result<()> {
+        if !self.settings.fetch_enabled {
+            println!(
+                "{} Background fetch is disabled (daemon_settings.fetch_enabled = false)",
+                "ℹ️".bright_blue()
+            );
+            return Ok(());
+        }
+
+        println!(
+            "{} Fetch scheduler running every {}s (+ up to {}s jitter, {} concurrent)",
+            "🛰️".bright_cyan(),
+            self.settings.fetch_interval_secs,
+            self.settings.fetch_jitter_secs,
+            self.settings.max_concurrent_fetches.max(1)
+        );
+        println!("{}", "Press Ctrl+C to stop".bright_black());
+
+        let mut alert_tracker = AlertTracker::new();
+        let mut commit_tracker = CommitTracker::new();
+        let mut status_tracker = StatusTracker::new();
+
+        loop {
+            let outcomes = self.run_once().await?;
+            self.print_outcomes(&outcomes);
+            for outcome in &outcomes {
+                if let Some(error) = &outcome.error {
+                    self.hooks.fire(HookEvent::for_repo(
+                        "fetch_failed",
+                        &outcome.repo_name,
+                        error.clone(),
+                    ));
+                }
+            }
+            self.check_worktrees(&mut alert_tracker, &mut commit_tracker, &mut status_tracker)
+                .await?;
+            self.finish_pending_deletes().await;
+
+            let delay = Duration::from_secs(self.settings.fetch_interval_secs)
+                + jitter(self.settings.fetch_jitter_secs);
+
+            tokio::select! {
+                _ = time::sleep(delay) => {}
+                _ = signal::ctrl_c() => {
+                    println!("\n{} Fetch scheduler stopped", "🛑".bright_red());
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Fetch every registered, non-excluded repository once and record the results
+    pub async fn run_once(&self) -> Result<Vec<FetchOutcome>> {
+        let repositories: Vec<Repository> = self
+            .db
+            .list_repositories()
+            .await?
+            .into_iter()
+            .filter(|repo| !self.is_excluded(&repo.name))
+            .collect();
+
+        let semaphore = Arc::new(Semaphore::new(self.settings.max_concurrent_fetches.max(1)));
+        let mut tasks = JoinSet::new();
+
+        for repo in repositories {
+            let semaphore = semaphore.clone();
+            let git = self.git.clone();
+            let remote = self.remote_name.clone();
+
+            tasks.spawn(async move {
+                let _permit = semaphore.acquire_owned().await?;
+                let path = PathBuf::from(&repo.path);
+                let started = Instant::now();
+
+                let result = tokio::task::spawn_blocking(move || {
+                    git.execute_git_command(&path, &["fetch", "--prune", &remote])
+                })
+                .await?;
+
+                Ok::<_, anyhow::Error>((
+                    repo,
+                    started.elapsed().as_millis() as i64,
+                    result.err().map(|e| e.to_string().trim().to_string()),
+                ))
+            });
+        }
+
+        let mut outcomes = Vec::new();
+        while let Some(joined) = tasks.join_next().await {
+            let (repo, duration_ms, error) = joined??;
+
+            self.db
+                .record_fetch_result(&repo.id, duration_ms, error.as_deref())
+                .await?;
+
+            outcomes.push(FetchOutcome {
+                repo_name: repo.name,
+                duration_ms,
+                error,
+            });
+        }
+
+        outcomes.sort_by(|a, b| a.repo_name.cmp(&b.repo_name));
+        Ok(outcomes)
+    }
+
+    /// Evaluate alert rules right after a fetch, when behind-trunk counts are
+    /// fresh, and report new commits and status changes to hooks
+    async fn check_worktrees(
+        &self,
+        tracker: &mut AlertTracker,
+        commits: &mut CommitTracker,
+        statuses: &mut StatusTracker,
+    ) -> Result<()> {
+        let mut worktrees = self.db.list_worktrees(None).await?;
+        self.db.populate_worktree_names(&mut worktrees).await?;
+
+        for event in commits.check(&self.git, &worktrees) {
+            // New commits count as use for `imi recent`; best-effort
+            if let Some(wt) = worktrees.iter().find(|wt| {
+                event.worktree_name.as_ref() == Some(&wt.name) && event.repo_name == wt.repo_name
+            }) {
+                let _ = self.db.record_activity(&wt.id).await;
+            }
+            self.hooks.fire(event);
+        }
+        for event in statuses.check(&self.git, &worktrees) {
+            self.hooks.fire(event);
+        }
+        if !self.alert_settings.enabled {
+            return Ok(());
+        }
+
+        let evaluator = AlertEvaluator::new(&self.git, &self.alert_settings, &self.trunk_branch);
+        let current: Vec<_> = worktrees
+            .iter()
+            .flat_map(|wt| evaluator.evaluate(wt))
+            .collect();
+
+        for alert in tracker.update(&current) {
+            println!(
+                "   {} {}/{}: {}",
+                "🚨".bright_red(),
+                alert.repo_name.bright_blue(),
+                alert.worktree_name,
+                alert.message
+            );
+            alerts::notify(&self.alert_settings, alert);
+
+            if let Some(worktree) = worktrees
+                .iter()
+                .find(|wt| wt.name == alert.worktree_name && wt.repo_name == alert.repo_name)
+            {
+                self.hooks.fire(HookEvent::for_alert(alert, worktree));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Delete what background deletions left behind, e.g. after a reboot
+    async fn finish_pending_deletes(&self) {
+        let Ok(repositories) = self.db.list_repositories().await else {
+            return;
+        };
+        let containers: Vec<PathBuf> = repositories
+            .iter()
+            .filter_map(|repo| Path::new(&repo.path).parent().map(Path::to_path_buf))
+            .collect();
+
+        let drained = tokio::task::spawn_blocking(move || {
+            containers
+                .iter()
+                .map(|container| PendingDeletes::new(container).drain())
+                .collect::<Vec<_>>()
+        })
+        .await
+        .unwrap_or_default();
+        for result in drained {
+            match result {
+                Ok(deleted) if !deleted.is_empty() => println!(
+                    "   {} Finished deleting {} removed worktree(s)",
+                    "🧹".bright_cyan(),
+                    deleted.len()
+                ),
+                Ok(_) => {}
+                Err(e) => println!("   {} {}", "⚠️".bright_yellow(), e),
+            }
+        }
+    }
+
+    fn is_excluded(&self, repo_name: &str) -> bool {
+        self.settings
+            .fetch_exclude
+            .iter()
+            .any(|excluded| excluded.eq_ignore_ascii_case(repo_name))
+    }
+
+    fn print_outcomes(&self, outcomes: &[FetchOutcome]) {
+        let failed = outcomes.iter().filter(|o| o.error.is_some()).count();
+        println!(
+            "{} [{}] Fetched {} repositories ({} failed)",
+            "🔄".bright_cyan(),
+            chrono::Local::now().format("%H:%M:%S"),
+            outcomes.len(),
+            failed
+        );
+
+        for outcome in outcomes.iter().filter(|o| o.error.is_some()) {
+            println!(
+                "   {} {}: {}",
+                "⚠️".bright_yellow(),
+                outcome.repo_name.bright_blue(),
+                outcome.error.as_deref().unwrap_or_default()
+            );
+        }
+    }
+}
+
+/// Watches every worktree and drops its cached git status as soon as a file
+/// in it changes, so `imi status` and `imi list` only trust the cache for
+/// worktrees nothing touched. Only directories git doesn't ignore are
+/// watched, each on its own, so `target/` and `node_modules` don't use up
+/// the inotify watch limit
+#[derive(Debug, Clone)]
+pub struct StatusCacheWatcher {
+    db: Database,
+    enabled: bool,
+}
+
+impl StatusCacheWatcher {
+    pub fn new(db: Database, config: &Config) -> Self {
+        Self {
+            db,
+            enabled: config.list_settings.status_cache_secs > 0,
+        }
+    }
+
+    /// Invalidate on file changes until interrupted with Ctrl+C
+    pub async fn run(&self) -> Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut watchers: HashMap<uuid::Uuid, WorktreeWatch> = HashMap::new();
+        let mut rescan = time::interval(WATCH_RESCAN_INTERVAL);
+
+        loop {
+            tokio::select! {
+                _ = rescan.tick() => self.rewatch(&mut watchers, &tx).await,
+                Some(id) = rx.recv() => {
+                    // Coalesce an event burst (a checkout, a build) into one delete
+                    let mut changed = HashSet::from([id]);
+                    while let Ok(id) = rx.try_recv() {
+                        changed.insert(id);
+                    }
+                    let changed: Vec<uuid::Uuid> = changed.into_iter().collect();
+                    if let Err(e) = self.db.invalidate_worktree_statuses(&changed).await {
+                        println!("   {} {}", "⚠️".bright_yellow(), e);
+                    }
+                }
+                _ = signal::ctrl_c() => return Ok(()),
+            }
+        }
+    }
+
+    /// Watch worktrees added since the last scan, and directories added to
+    /// watched ones, and stop watching removed worktrees
+    async fn rewatch(
+        &self,
+        watchers: &mut HashMap<uuid::Uuid, WorktreeWatch>,
+        tx: &mpsc::UnboundedSender<uuid::Uuid>,
+    ) {
+        let Ok(worktrees) = self.db.list_worktrees(None).await else {
+            return;
+        };
+        let current: HashMap<uuid::Uuid, PathBuf> = worktrees
+            .into_iter()
+            .map(|wt| (wt.id, PathBuf::from(wt.path)))
+            .filter(|(_, path)| path.exists())
+            .collect();
+        watchers.retain(|id, watch| current.get(id) == Some(&watch.path));
+
+        for (id, path) in current {
+            let watch = match watchers.entry(id) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(slot) => {
+                    let tx = tx.clone();
+                    let watcher = RecommendedWatcher::new(
+                        move |res: notify::Result<notify::Event>| {
+                            if res.is_ok_and(|event| !matches!(event.kind, EventKind::Access(_))) {
+                                let _ = tx.send(id);
+                            }
+                        },
+                        notify::Config::default(),
+                    );
+                    match watcher {
+                        Ok(watcher) => slot.insert(WorktreeWatch {
+                            path: path.clone(),
+                            watcher,
+                            dirs: HashSet::new(),
+                        }),
+                        // Unwatched worktrees still expire by age
+                        Err(e) => {
+                            println!(
+                                "   {} Not watching {}: {}",
+                                "⚠️".bright_yellow(),
+                                path.display(),
+                                e
+                            );
+                            continue;
+                        }
+                    }
+                }
+            };
+            if let Err(e) = watch.watch_new_dirs() {
+                println!(
+                    "   {} Not watching all of {}: {}",
+                    "⚠️".bright_yellow(),
+                    path.display(),
+                    e
+                );
+            }
+        }
+    }
+}
+
+/// One worktree's watcher and the directories it watches
+struct WorktreeWatch {
+    path: PathBuf,
+    watcher: RecommendedWatcher,
+    dirs: HashSet<PathBuf>,
+}
+
+impl WorktreeWatch {
+    /// Watch the directories not watched yet, non-recursively; ones git
+    /// ignores are skipped along with everything under them
+    fn watch_new_dirs(&mut self) -> Result<()> {
+        let dirs = watched_dirs(&self.path);
+        self.dirs.retain(|dir| dirs.contains(dir));
+        for dir in dirs {
+            if !self.dirs.contains(&dir) {
+                self.watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+                self.dirs.insert(dir);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The directories of a worktree that aren't `.git` or ignored by git
+fn watched_dirs(worktree: &Path) -> HashSet<PathBuf> {
+    let repo = git2::Repository::open(worktree).ok();
+    walkdir::WalkDir::new(worktree)
+        .into_iter()
+        .filter_entry(|entry| {
+            entry.file_type().is_dir()
+                && entry.file_name() != ".git"
+                && !entry.path().strip_prefix(worktree).is_ok_and(|relative| {
+                    !relative.as_os_str().is_empty()
+                        && repo
+                            .as_ref()
+                            .is_some_and(|repo| repo.is_path_ignored(relative).unwrap_or(false))
+                })
+        })
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.into_path())
+        .collect()
+}
+
+/// Pseudo-random delay in `0..=max_secs`; uniqueness matters here, not quality
+fn jitter(max_secs: u64) -> Duration {
+    if max_secs == 0 {
+        return Duration::ZERO;
+    }
+    let millis = (uuid::Uuid::new_v4().as_u128() % (max_secs as u128 * 1000 + 1)) as u64;
+    Duration::from_millis(millis)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn watches_directories_git_does_not_ignore() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        git2::Repository::init(root).unwrap();
+        std::fs::write(root.join(".gitignore"), "target/\n").unwrap();
+        for sub in ["src/bin", "target/debug/deps"] {
+            std::fs::create_dir_all(root.join(sub)).unwrap();
+        }
+
+        let dirs = watched_dirs(root);
+        assert!(dirs.contains(root));
+        assert!(dirs.contains(&root.join("src/bin")));
+        assert!(!dirs.contains(&root.join("target")));
+        assert!(!dirs.contains(&root.join("target/debug")));
+        assert!(!dirs.iter().any(|dir| dir.starts_with(root.join(".git"))));
+    }
+
+    #[test]
+    fn jitter_stays_within_bounds() {
+        assert_eq!(jitter(0), Duration::ZERO);
+        for _ in 0..100 {
+            assert!(jitter(2) <= Duration::from_secs(2));
+        }
+    }
+}
@@ -0,0 +1,323 @@
+//! Deep inspection of a directory before `imi prune` or `imi clean` deletes
+//! it: commits no remote has, stashes, and untracked files worth keeping
+
+use git2::{BranchType, Repository, StatusOptions};
+use serde::Serialize;
+use std::path::Path;
+
+/// Files that are usually ignored but can't be recreated from the repository
+const IMPORTANT_FILE_PATTERNS: &[&str] = &[".env", ".env.*", ".envrc", "*.pem", "*.key", "*.p12"];
+
+/// Directories too large to walk that never hold anything worth keeping
+const SKIPPED_DIRS: &[&str] = &[".git", "node_modules", "target", ".venv", "dist", "build"];
+
+/// How deep to look for nested repositories and important files
+const MAX_DEPTH: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Safety {
+    Safe,
+    Risky,
+}
+
+/// What would be lost by deleting a directory
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DeletionCheck {
+    /// Commits on no remote, across every repository found
+    pub unpushed_commits: usize,
+    pub stashes: usize,
+    /// Untracked files, and ignored ones like `.env` that can't be recreated
+    pub important_files: Vec<String>,
+    /// The directory couldn't be fully inspected
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl DeletionCheck {
+    pub fn safety(&self) -> Safety {
+        if self.unpushed_commits == 0
+            && self.stashes == 0
+            && self.important_files.is_empty()
+            && self.error.is_none()
+        {
+            Safety::Safe
+        } else {
+            Safety::Risky
+        }
+    }
+
+    pub fn is_safe(&self) -> bool {
+        self.safety() == Safety::Safe
+    }
+
+    /// e.g. `2 unpushed commits, 1 stash, .env`
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if self.unpushed_commits > 0 {
+            parts.push(plural(self.unpushed_commits, "unpushed commit"));
+        }
+        if self.stashes > 0 {
+            parts.push(plural(self.stashes, "stash"));
+        }
+        match self.important_files.len() {
+            0 => {}
+            1..=3 => parts.push(self.important_files.join(", ")),
+            count => parts.push(format!(
+                "{} and {} more files",
+                self.important_files[..2].join(", "),
+                count - 2
+            )),
+        }
+        if let Some(error) = &self.error {
+            parts.push(format!("not inspected: {}", error));
+        }
+        parts.join(", ")
+    }
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    match (count, noun.ends_with('h')) {
+        (1, _) => format!("1 {}", noun),
+        (_, true) => format!("{} {}es", count, noun),
+        (_, false) => format!("{} {}s", count, noun),
+    }
+}
+
+/// Inspect `path`: the repository or worktree it is, else any repositories
+/// nested inside it plus important files lying around
+pub fn inspect(path: &Path) -> DeletionCheck {
+    let mut check = DeletionCheck::default();
+    if !path.exists() {
+        return check;
+    }
+
+    if let Ok(repo) = Repository::open(path) {
+        if let Err(e) = inspect_repository(repo, &mut check) {
+            check.error = Some(e.message().to_string());
+        }
+        return check;
+    }
+
+    scan(path, path, 0, &mut check);
+    check
+}
+
+fn scan(root: &Path, dir: &Path, depth: usize, check: &mut DeletionCheck) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+
+        if file_type.is_dir() {
+            if depth + 1 >= MAX_DEPTH || SKIPPED_DIRS.contains(&name.as_str()) {
+                continue;
+            }
+            match path.join(".git").exists() {
+                true => match Repository::open(&path) {
+                    Ok(repo) => {
+                        if let Err(e) = inspect_repository(repo, check) {
+                            check.error = Some(e.message().to_string());
+                        }
+                    }
+                    // A worktree whose admin directory is gone: its commits
+                    // live on in the main repository, its files don't
+                    Err(_) => scan(root, &path, depth + 1, check),
+                },
+                false => scan(root, &path, depth + 1, check),
+            }
+        } else if is_important(&name) {
+            check.important_files.push(relative(root, &path));
+        }
+    }
+}
+
+fn inspect_repository(
+    mut repo: Repository,
+    check: &mut DeletionCheck,
+) -> std::result::Result<(), git2::Error> {
+    let root = repo.workdir().map(Path::to_path_buf);
+    let head_branch = repo
+        .head()
+        .ok()
+        .filter(|head| head.is_branch())
+        .and_then(|head| head.shorthand().map(str::to_string));
+
+    // A worktree's other branches belong to the main checkout; a standalone
+    // repository takes all of its branches with it
+    let branch_names: Vec<String> = if repo.is_worktree() {
+        head_branch.iter().cloned().collect()
+    } else {
+        repo.branches(Some(BranchType::Local))?
+            .filter_map(|branch| branch.ok()?.0.name().ok()?.map(str::to_string))
+            .collect()
+    };
+    for name in &branch_names {
+        if let Ok(branch) = repo.find_branch(name, BranchType::Local) {
+            check.unpushed_commits += unpushed_commits(&repo, &branch)?;
+        }
+    }
+    if head_branch.is_none() {
+        // Detached HEAD: commits made there are on no branch at all
+        if let Ok(head) = repo.head().and_then(|head| head.peel_to_commit()) {
+            check.unpushed_commits += commits_on_no_remote(&repo, head.id())?;
+        }
+    }
+
+    if let Some(root) = &root {
+        let mut options = StatusOptions::new();
+        options
+            .include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .include_ignored(true)
+            .recurse_ignored_dirs(false);
+        for entry in repo.statuses(Some(&mut options))?.iter() {
+            let status = entry.status();
+            let Some(file) = entry.path() else {
+                continue;
+            };
+            let name = file.rsplit('/').next().unwrap_or(file);
+            let keep = status.is_wt_new() || (status.is_ignored() && is_important(name));
+            if keep {
+                check
+                    .important_files
+                    .push(relative(root, &root.join(file.trim_end_matches('/'))));
+            }
+        }
+    }
+
+    // The stash is shared by every worktree; count the entries made on this
+    // worktree's branch ("WIP on <branch>:" / "On <branch>:")
+    let is_worktree = repo.is_worktree();
+    let marker = head_branch.map(|branch| format!("n {}:", branch));
+    repo.stash_foreach(|_, message, _| {
+        let ours = !is_worktree || marker.as_ref().is_some_and(|m| message.contains(m));
+        if ours {
+            check.stashes += 1;
+        }
+        true
+    })?;
+
+    Ok(())
+}
+
+/// Commits ahead of the branch's upstream, or on no remote when it has none
+fn unpushed_commits(
+    repo: &Repository,
+    branch: &git2::Branch,
+) -> std::result::Result<usize, git2::Error> {
+    let Some(tip) = branch.get().target() else {
+        return Ok(0);
+    };
+    match branch.upstream().ok().and_then(|up| up.get().target()) {
+        Some(upstream) => Ok(repo.graph_ahead_behind(tip, upstream)?.0),
+        None => commits_on_no_remote(repo, tip),
+    }
+}
+
+fn commits_on_no_remote(
+    repo: &Repository,
+    tip: git2::Oid,
+) -> std::result::Result<usize, git2::Error> {
+    let mut walk = repo.revwalk()?;
+    walk.push(tip)?;
+    for reference in repo.references_glob("refs/remotes/*")?.flatten() {
+        if let Some(target) = reference.target() {
+            walk.hide(target)?;
+        }
+    }
+    Ok(walk.count())
+}
+
+fn is_important(file_name: &str) -> bool {
+    IMPORTANT_FILE_PATTERNS.iter().any(|pattern| {
+        match (pattern.strip_prefix('*'), pattern.strip_suffix('*')) {
+            (Some(suffix), _) => file_name.ends_with(suffix),
+            (_, Some(prefix)) => file_name.starts_with(prefix),
+            _ => file_name == *pattern,
+        }
+    })
+}
+
+fn relative(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(repo: &Repository, message: &str) -> git2::Oid {
+        let signature = git2::Signature::now("iMi", "imi@example.com").unwrap();
+        let tree = repo
+            .find_tree(repo.treebuilder(None).unwrap().write().unwrap())
+            .unwrap();
+        let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parents,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn nested_repositories_with_unpushed_work_are_risky() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(inspect(dir.path()).is_safe());
+
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/main.rs"), "").unwrap();
+        assert!(inspect(dir.path()).is_safe());
+
+        std::fs::write(dir.path().join(".env"), "TOKEN=1").unwrap();
+        let check = inspect(dir.path());
+        assert_eq!(check.safety(), Safety::Risky);
+        assert_eq!(check.important_files, vec![".env"]);
+        std::fs::remove_file(dir.path().join(".env")).unwrap();
+
+        let nested = Repository::init(dir.path().join("vendor")).unwrap();
+        let pushed = commit(&nested, "pushed");
+        nested
+            .reference("refs/remotes/origin/main", pushed, true, "test")
+            .unwrap();
+        assert!(inspect(dir.path()).is_safe());
+
+        commit(&nested, "local only");
+        let check = inspect(dir.path());
+        assert_eq!(check.unpushed_commits, 1);
+        assert_eq!(check.summary(), "1 unpushed commit");
+    }
+
+    #[test]
+    fn untracked_files_and_stashes_make_a_repository_risky() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut repo = Repository::init(dir.path()).unwrap();
+        let head = commit(&repo, "root");
+        repo.reference("refs/remotes/origin/main", head, true, "test")
+            .unwrap();
+        assert!(inspect(dir.path()).is_safe());
+
+        std::fs::write(dir.path().join("notes.md"), "draft").unwrap();
+        assert_eq!(inspect(dir.path()).important_files, vec!["notes.md"]);
+
+        let signature = git2::Signature::now("iMi", "imi@example.com").unwrap();
+        repo.stash_save(&signature, "wip", Some(git2::StashFlags::INCLUDE_UNTRACKED))
+            .unwrap();
+        let check = inspect(dir.path());
+        assert_eq!((check.stashes, check.important_files.len()), (1, 0));
+        assert_eq!(check.summary(), "1 stash");
+    }
+}
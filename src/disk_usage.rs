@@ -0,0 +1,195 @@
+//! Worktree disk usage with an on-disk cache (walking `node_modules` or
+//! `target` on every command is too slow), and size budget reports
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::json_cache;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedSize {
+    bytes: u64,
+    measured_at: DateTime<Utc>,
+}
+
+/// Directory sizes keyed by path, persisted between commands
+#[derive(Debug)]
+pub struct DiskUsageCache {
+    path: Option<PathBuf>,
+    ttl: chrono::Duration,
+    entries: HashMap<PathBuf, CachedSize>,
+}
+
+impl DiskUsageCache {
+    pub fn load(ttl_secs: u64) -> Self {
+        Self::load_from(json_cache::cache_file("du-cache.json"), ttl_secs)
+    }
+
+    pub fn load_from(path: Option<PathBuf>, ttl_secs: u64) -> Self {
+        let entries = json_cache::read(path.as_deref());
+
+        Self {
+            path,
+            ttl: chrono::Duration::seconds(ttl_secs as i64),
+            entries,
+        }
+    }
+
+    /// Size of `dir`, measured again only when the cached value is stale
+    pub fn size_of(&mut self, dir: &Path) -> u64 {
+        match self.entries.get(dir) {
+            Some(cached) if Utc::now() - cached.measured_at < self.ttl => cached.bytes,
+            _ => self.refresh(dir),
+        }
+    }
+
+    pub fn refresh(&mut self, dir: &Path) -> u64 {
+        let bytes = directory_size(dir);
+        self.entries.insert(
+            dir.to_path_buf(),
+            CachedSize {
+                bytes,
+                measured_at: Utc::now(),
+            },
+        );
+        bytes
+    }
+
+    /// Write the cache back, dropping directories that no longer exist
+    pub fn save(&mut self) -> Result<()> {
+        self.entries.retain(|dir, _| dir.exists());
+        json_cache::write(self.path.as_deref(), &self.entries)
+    }
+}
+
+/// Total size of the files under `path`, in bytes
+pub fn directory_size(path: &Path) -> u64 {
+    let _span = tracing::info_span!("fs", detail = format!("size of {}", path.display())).entered();
+    walkdir::WalkDir::new(path)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter_map(|e| e.metadata().ok())
+        .filter(|meta| meta.is_file())
+        .map(|meta| meta.len())
+        .sum()
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WorktreeUsage {
+    pub worktree_name: String,
+    pub path: PathBuf,
+    pub bytes: u64,
+    /// Branch merged into trunk, or its pull request was merged
+    pub merged: bool,
+    pub is_trunk: bool,
+    /// Last commit, or creation when the worktree has no readable HEAD
+    pub last_activity: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BudgetReport {
+    pub repo_name: String,
+    pub budget_bytes: Option<u64>,
+    pub total_bytes: u64,
+    pub worktrees: Vec<WorktreeUsage>,
+    /// Worktree names worth closing first
+    pub candidates: Vec<String>,
+}
+
+impl BudgetReport {
+    pub fn new(
+        repo_name: &str,
+        budget_bytes: Option<u64>,
+        mut worktrees: Vec<WorktreeUsage>,
+        suggestions: usize,
+    ) -> Self {
+        worktrees.sort_by_key(|wt| std::cmp::Reverse(wt.bytes));
+        let candidates = cleanup_candidates(&worktrees)
+            .into_iter()
+            .take(suggestions)
+            .map(|wt| wt.worktree_name.clone())
+            .collect();
+
+        Self {
+            repo_name: repo_name.to_string(),
+            budget_bytes,
+            total_bytes: worktrees.iter().map(|wt| wt.bytes).sum(),
+            worktrees,
+            candidates,
+        }
+    }
+
+    pub fn over_budget(&self) -> bool {
+        self.budget_bytes
+            .is_some_and(|budget| self.total_bytes > budget)
+    }
+}
+
+/// Non-trunk worktrees ordered for cleanup: merged first, then least
+/// recently active, then largest
+pub fn cleanup_candidates(worktrees: &[WorktreeUsage]) -> Vec<&WorktreeUsage> {
+    let mut candidates: Vec<&WorktreeUsage> = worktrees.iter().filter(|wt| !wt.is_trunk).collect();
+    candidates.sort_by(|a, b| {
+        b.merged
+            .cmp(&a.merged)
+            .then(a.last_activity.cmp(&b.last_activity))
+            .then(b.bytes.cmp(&a.bytes))
+    });
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(name: &str, bytes: u64, merged: bool, days_ago: i64) -> WorktreeUsage {
+        WorktreeUsage {
+            worktree_name: name.to_string(),
+            path: PathBuf::from(name),
+            bytes,
+            merged,
+            is_trunk: name.starts_with("trunk-"),
+            last_activity: Utc::now() - chrono::Duration::days(days_ago),
+        }
+    }
+
+    #[test]
+    fn ranks_merged_then_oldest_and_never_trunk() {
+        let report = BudgetReport::new(
+            "iMi",
+            Some(1000),
+            vec![
+                usage("trunk-main", 900, false, 90),
+                usage("feat-new", 50, false, 1),
+                usage("feat-old", 20, false, 30),
+                usage("fix-done", 10, true, 2),
+            ],
+            2,
+        );
+
+        assert_eq!(report.total_bytes, 980);
+        assert!(!report.over_budget());
+        assert_eq!(report.candidates, vec!["fix-done", "feat-old"]);
+    }
+
+    #[test]
+    fn cache_reuses_fresh_sizes() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache_file = dir.path().join("du-cache.json");
+        let tree = dir.path().join("tree");
+        std::fs::create_dir(&tree).unwrap();
+        std::fs::write(tree.join("a"), vec![0u8; 100]).unwrap();
+
+        let mut cache = DiskUsageCache::load_from(Some(cache_file.clone()), 3600);
+        assert_eq!(cache.size_of(&tree), 100);
+        cache.save().unwrap();
+
+        std::fs::write(tree.join("b"), vec![0u8; 50]).unwrap();
+        let mut cache = DiskUsageCache::load_from(Some(cache_file), 3600);
+        assert_eq!(cache.size_of(&tree), 100);
+        assert_eq!(cache.refresh(&tree), 150);
+    }
+}
@@ -0,0 +1,195 @@
+//! Per-worktree editor workspaces (`[editor]` config, `imi open`)
+
+use anyhow::{Context, Result};
+use serde_json::{json, Map, Value};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::config::{EditorKind, EditorSettings};
+use crate::worktree::render_copy_template;
+
+/// What a workspace is generated for
+pub struct WorkspaceContext<'a> {
+    pub repo_name: &'a str,
+    pub worktree_name: &'a str,
+    pub worktree_type: &'a str,
+    pub branch_name: &'a str,
+    pub worktree_path: &'a Path,
+    pub port: u16,
+    /// The repo's user sync directory, when it exists
+    pub user_sync: Option<PathBuf>,
+    /// The repo's local sync directory, when it exists
+    pub local_sync: Option<PathBuf>,
+}
+
+impl WorkspaceContext<'_> {
+    /// Environment for the editor's integrated terminal
+    fn env(&self) -> Value {
+        json!({
+            "IMI_REPO": self.repo_name,
+            "IMI_WORKTREE": self.worktree_name,
+            "IMI_WORKTREE_TYPE": self.worktree_type,
+            "IMI_BRANCH": self.branch_name,
+            "PORT": self.port.to_string(),
+        })
+    }
+
+    /// Sync directories shown next to the worktree
+    fn sync_dirs(&self) -> impl Iterator<Item = &PathBuf> {
+        self.user_sync.iter().chain(self.local_sync.iter())
+    }
+
+    fn template_vars(&self) -> Vec<(&'static str, String)> {
+        let sync_dir = |dir: &Option<PathBuf>| {
+            dir.as_ref()
+                .map(|dir| dir.display().to_string())
+                .unwrap_or_default()
+        };
+
+        vec![
+            ("worktree_name", self.worktree_name.to_string()),
+            ("worktree_type", self.worktree_type.to_string()),
+            ("repo_name", self.repo_name.to_string()),
+            ("branch", self.branch_name.to_string()),
+            ("worktree_path", self.worktree_path.display().to_string()),
+            ("port", self.port.to_string()),
+            ("user_sync", sync_dir(&self.user_sync)),
+            ("local_sync", sync_dir(&self.local_sync)),
+        ]
+    }
+}
+
+/// Where the workspace file lives, relative to the worktree
+pub fn workspace_file(settings: &EditorSettings, worktree_name: &str) -> PathBuf {
+    match settings.kind {
+        EditorKind::Vscode => PathBuf::from(format!("{}.code-workspace", worktree_name)),
+        EditorKind::Zed => PathBuf::from(".zed").join("settings.json"),
+    }
+}
+
+/// Workspace file contents: the configured template, or generated JSON
+pub fn render_workspace(settings: &EditorSettings, ctx: &WorkspaceContext) -> Result<String> {
+    if let Some(template) = &settings.template {
+        let contents = std::fs::read_to_string(template).context(format!(
+            "Failed to read editor template {}",
+            template.display()
+        ))?;
+        let vars = ctx.template_vars();
+        let vars: Vec<(&str, &str)> = vars.iter().map(|(k, v)| (*k, v.as_str())).collect();
+        return Ok(render_copy_template(&contents, &vars));
+    }
+
+    let mut editor_settings = settings.settings.clone();
+    let workspace = match settings.kind {
+        EditorKind::Vscode => {
+            editor_settings.insert("terminal.integrated.env.linux".to_string(), ctx.env());
+            editor_settings.insert("terminal.integrated.env.osx".to_string(), ctx.env());
+
+            let mut folders = vec![json!({ "name": ctx.worktree_name, "path": "." })];
+            if settings.include_sync_dirs {
+                folders.extend(ctx.sync_dirs().map(|dir| {
+                    json!({
+                        "name": dir.file_name().map(|n| n.to_string_lossy()).unwrap_or_default(),
+                        "path": dir,
+                    })
+                }));
+            }
+            json!({ "folders": folders, "settings": editor_settings })
+        }
+        EditorKind::Zed => {
+            let mut terminal = Map::new();
+            terminal.insert("env".to_string(), ctx.env());
+            editor_settings.insert("terminal".to_string(), Value::Object(terminal));
+            Value::Object(editor_settings)
+        }
+    };
+
+    Ok(serde_json::to_string_pretty(&workspace)? + "\n")
+}
+
+/// Command that opens the workspace in the configured editor
+pub fn open_command(
+    settings: &EditorSettings,
+    ctx: &WorkspaceContext,
+    workspace: &Path,
+) -> Result<Command> {
+    let mut parts = settings.command().split_whitespace();
+    let program = parts.next().context("Editor command is empty")?;
+    let mut command = Command::new(program);
+    command.args(parts);
+
+    match settings.kind {
+        EditorKind::Vscode => {
+            command.arg(workspace);
+        }
+        // Zed has no multi-root workspace file; open the folders together
+        EditorKind::Zed => {
+            command.arg(ctx.worktree_path);
+            if settings.include_sync_dirs {
+                command.args(ctx.sync_dirs());
+            }
+        }
+    }
+
+    Ok(command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(worktree_path: &Path) -> WorkspaceContext<'_> {
+        WorkspaceContext {
+            repo_name: "iMi",
+            worktree_name: "feat-auth",
+            worktree_type: "feat",
+            branch_name: "feat/auth",
+            worktree_path,
+            port: 3412,
+            user_sync: None,
+            local_sync: Some(PathBuf::from("/code/iMi/sync/local")),
+        }
+    }
+
+    #[test]
+    fn vscode_workspace_lists_sync_dirs_and_terminal_env() {
+        let settings = EditorSettings::default();
+        let ctx = context(Path::new("/code/iMi/feat-auth"));
+
+        let workspace: Value =
+            serde_json::from_str(&render_workspace(&settings, &ctx).unwrap()).unwrap();
+        assert_eq!(workspace["folders"][0]["path"], ".");
+        assert_eq!(workspace["folders"][1]["path"], "/code/iMi/sync/local");
+        assert_eq!(
+            workspace["settings"]["terminal.integrated.env.linux"]["PORT"],
+            "3412"
+        );
+        assert_eq!(
+            workspace_file(&settings, "feat-auth"),
+            PathBuf::from("feat-auth.code-workspace")
+        );
+    }
+
+    #[test]
+    fn template_replaces_generated_workspace() {
+        let dir = tempfile::tempdir().unwrap();
+        let template = dir.path().join("workspace.json");
+        std::fs::write(
+            &template,
+            r#"{"name": "{{worktree_name}}", "port": {{port}}, "user": "{{user_sync}}", "local": "{{local_sync}}"}"#,
+        )
+        .unwrap();
+
+        let settings = EditorSettings {
+            kind: EditorKind::Zed,
+            template: Some(template),
+            ..Default::default()
+        };
+        let ctx = context(Path::new("/code/iMi/feat-auth"));
+
+        assert_eq!(
+            render_workspace(&settings, &ctx).unwrap(),
+            r#"{"name": "feat-auth", "port": 3412, "user": "", "local": "/code/iMi/sync/local"}"#
+        );
+    }
+}
@@ -28,15 +28,43 @@ pub enum ImiError {
     #[error("Invalid worktree name: {name}")]
     InvalidWorktreeName { name: String },
 
+    #[error("'{name}' matches several worktrees: {}. Use the full name (or --exact)", matches.join(", "))]
+    AmbiguousWorktreeName { name: String, matches: Vec<String> },
+
     #[error("Git repository not found at path: {path}")]
     GitRepositoryNotFound { path: String },
 
     #[error("Branch not found: {branch}")]
     BranchNotFound { branch: String },
 
+    #[error("Branch '{branch}' already exists {location}. Pass --adopt-existing to check it out in the new worktree, or pick another name ('{suggestion}' is free)")]
+    BranchExists {
+        branch: String,
+        location: String,
+        suggestion: String,
+    },
+
     #[error("Remote not found: {remote}")]
     RemoteNotFound { remote: String },
 
+    #[error("Authentication to remote '{remote}' failed ({message}). Tried: {tried}. Check your ssh-agent, git_settings.ssh_key_paths, git credential helper, or GITHUB_TOKEN")]
+    RemoteAuthFailed {
+        remote: String,
+        tried: String,
+        message: String,
+    },
+
+    #[error(
+        "Could not reach remote '{remote}': {message}. Check your network connection and retry"
+    )]
+    RemoteUnreachable { remote: String, message: String },
+
+    #[error("Signing the merge commit failed: {message}. Check user.signingkey and gpg.format in your git config and that gpg-agent or ssh-agent can use the key (try: git commit --allow-empty -S -m test)")]
+    CommitSigningFailed { message: String },
+
+    #[error("Merge conflicts in {}. Resolve them in the trunk worktree and commit, or run 'imi merge --abort'", files.join(", "))]
+    MergeConflicts { files: Vec<String> },
+
     #[error("Symlink creation failed: {source} -> {target}: {io_error}")]
     SymlinkCreationFailed {
         source: String,
@@ -50,6 +78,26 @@ pub enum ImiError {
 
     #[error("Agent communication error: {0}")]
     AgentCommunicationError(String),
+
+    #[error("'imi {command}' changes state, and iMi is in read-only mode (--read-only, IMI_READ_ONLY or [permissions] read_only)")]
+    ReadOnly { command: String },
+
+    #[error("Cancelled")]
+    Cancelled,
+
+    /// A command that has already reported its outcome and only needs
+    /// `main` to exit with `code`, once the audit entry and `--timings` are
+    /// written
+    #[error("Exited with status {code}")]
+    Exit { code: i32 },
+}
+
+impl ImiError {
+    /// Exit status of a command refused in read-only mode (EX_NOPERM)
+    pub const READ_ONLY_EXIT_CODE: i32 = 77;
+
+    /// Exit status of a command stopped by Ctrl-C (128 + SIGINT)
+    pub const CANCELLED_EXIT_CODE: i32 = 130;
 }
 
 #[allow(dead_code)]
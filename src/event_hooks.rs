@@ -0,0 +1,393 @@
+//! User commands run on monitor and daemon events (`[event_hooks]` config)
+
+use colored::*;
+use serde_json::json;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use uuid::Uuid;
+
+use crate::alerts::Alert;
+use crate::config::{EventHook, EventHookSettings};
+use crate::database::Worktree;
+use crate::events::EventLog;
+use crate::git::{GitManager, PrSync};
+
+/// Something the monitor or daemon noticed
+#[derive(Debug, Clone)]
+pub struct HookEvent {
+    pub name: String,
+    pub repo_name: String,
+    pub worktree_name: Option<String>,
+    pub worktree_path: Option<PathBuf>,
+    pub branch: Option<String>,
+    pub message: String,
+    /// Event-specific variables, e.g. IMI_FILE or IMI_COMMIT
+    pub vars: Vec<(&'static str, String)>,
+}
+
+impl HookEvent {
+    pub fn for_repo(name: &str, repo_name: &str, message: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            repo_name: repo_name.to_string(),
+            worktree_name: None,
+            worktree_path: None,
+            branch: None,
+            message: message.into(),
+            vars: Vec::new(),
+        }
+    }
+
+    pub fn for_worktree(name: &str, worktree: &Worktree, message: impl Into<String>) -> Self {
+        Self {
+            worktree_name: Some(worktree.name.clone()),
+            worktree_path: Some(PathBuf::from(&worktree.path)),
+            branch: Some(worktree.branch_name.clone()),
+            ..Self::for_repo(name, &worktree.repo_name, message)
+        }
+    }
+
+    pub fn for_alert(alert: &Alert, worktree: &Worktree) -> Self {
+        Self::for_worktree(alert.kind.code(), worktree, alert.message.clone())
+    }
+
+    /// `pr_updated`: a review worktree moved to its pull request's new head
+    pub fn for_pr_sync(worktree: &Worktree, sync: &PrSync) -> Self {
+        Self::for_worktree("pr_updated", worktree, sync.describe())
+            .var("IMI_PR_NUMBER", sync.pr_number.to_string())
+            .var("IMI_COMMIT", sync.head.clone())
+            .var("IMI_NEW_COMMITS", sync.new_commits.len().to_string())
+            .var("IMI_CHANGED_FILES", sync.changed_files.len().to_string())
+            .var("IMI_FORCE_PUSHED", sync.force_pushed.to_string())
+    }
+
+    pub fn var(mut self, key: &'static str, value: impl Into<String>) -> Self {
+        self.vars.push((key, value.into()));
+        self
+    }
+
+    fn env(&self) -> Vec<(String, String)> {
+        let mut env = vec![
+            ("IMI_EVENT".to_string(), self.name.clone()),
+            ("IMI_REPO".to_string(), self.repo_name.clone()),
+            ("IMI_EVENT_MESSAGE".to_string(), self.message.clone()),
+        ];
+        if let Some(worktree) = &self.worktree_name {
+            env.push(("IMI_WORKTREE".to_string(), worktree.clone()));
+        }
+        if let Some(path) = &self.worktree_path {
+            env.push(("IMI_WORKTREE_PATH".to_string(), path.display().to_string()));
+        }
+        if let Some(branch) = &self.branch {
+            env.push(("IMI_BRANCH".to_string(), branch.clone()));
+        }
+        env.extend(self.vars.iter().map(|(k, v)| (k.to_string(), v.clone())));
+        env
+    }
+}
+
+impl EventHook {
+    pub fn matches(&self, event: &HookEvent) -> bool {
+        (self.event == "*" || self.event == event.name)
+            && self
+                .repo
+                .as_ref()
+                .is_none_or(|repo| repo.eq_ignore_ascii_case(&event.repo_name))
+    }
+}
+
+/// Runs the configured hooks for events, at most `max_concurrent` at a time,
+/// and records every event to the event log
+#[derive(Debug, Clone)]
+pub struct HookRunner {
+    hooks: Arc<Vec<EventHook>>,
+    events: Option<EventLog>,
+    permits: Arc<Semaphore>,
+    timeout: Duration,
+    log_path: Option<PathBuf>,
+}
+
+impl HookRunner {
+    pub fn new(settings: &EventHookSettings) -> Self {
+        Self {
+            hooks: Arc::new(settings.hooks.clone()),
+            events: EventLog::from_settings(settings).ok().flatten(),
+            permits: Arc::new(Semaphore::new(settings.max_concurrent.max(1))),
+            timeout: Duration::from_secs(settings.timeout_secs.max(1)),
+            log_path: settings.resolve_log_path().ok(),
+        }
+    }
+
+    /// Whether the event log or any hook takes `event`, so callers can skip
+    /// building it
+    pub fn wants(&self, event: &str) -> bool {
+        self.events.is_some()
+            || self
+                .hooks
+                .iter()
+                .any(|h| h.event == "*" || h.event == event)
+    }
+
+    /// Record the event and run matching hooks in the background
+    pub fn fire(&self, event: HookEvent) {
+        if let Some(events) = &self.events {
+            events.record(&event);
+        }
+        if !self.hooks.iter().any(|hook| hook.matches(&event)) {
+            return;
+        }
+        let runner = self.clone();
+        tokio::spawn(async move {
+            runner.run(&event).await;
+        });
+    }
+
+    /// Run matching hooks and wait for them; returns how many failed
+    pub async fn run(&self, event: &HookEvent) -> usize {
+        let mut tasks = tokio::task::JoinSet::new();
+        for hook in self.hooks.iter().filter(|hook| hook.matches(event)) {
+            let (runner, hook, event) = (self.clone(), hook.clone(), event.clone());
+            tasks.spawn(async move {
+                let Ok(_permit) = runner.permits.acquire().await else {
+                    return false;
+                };
+                match run_hook(&hook, &event, runner.timeout).await {
+                    Ok(()) => true,
+                    Err(error) => {
+                        runner.log_failure(&hook, &event, &error);
+                        false
+                    }
+                }
+            });
+        }
+
+        let mut failed = 0;
+        while let Some(joined) = tasks.join_next().await {
+            if !matches!(joined, Ok(true)) {
+                failed += 1;
+            }
+        }
+        failed
+    }
+
+    fn log_failure(&self, hook: &EventHook, event: &HookEvent, error: &str) {
+        eprintln!(
+            "{} Hook for {} failed ({}): {}",
+            "⚠️".bright_yellow(),
+            event.name,
+            hook.command,
+            error
+        );
+
+        let Some(path) = &self.log_path else {
+            return;
+        };
+        let entry = json!({
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+            "event": event.name,
+            "repo": event.repo_name,
+            "worktree": event.worktree_name,
+            "command": hook.command,
+            "error": error,
+        });
+        let written = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| {
+                std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+            })
+            .and_then(|mut file| writeln!(file, "{}", entry));
+        if let Err(e) = written {
+            eprintln!(
+                "{} Failed to write hook log {}: {}",
+                "⚠️".bright_yellow(),
+                path.display(),
+                e
+            );
+        }
+    }
+}
+
+async fn run_hook(hook: &EventHook, event: &HookEvent, timeout: Duration) -> Result<(), String> {
+    let mut command = tokio::process::Command::new("sh");
+    command
+        .args(["-c", &hook.command])
+        .envs(event.env())
+        .stdin(std::process::Stdio::null())
+        .kill_on_drop(true);
+    if let Some(path) = event.worktree_path.as_deref().filter(|p| p.is_dir()) {
+        command.current_dir(path);
+    }
+
+    let output = match tokio::time::timeout(timeout, command.output()).await {
+        Ok(result) => result.map_err(|e| format!("failed to start: {}", e))?,
+        Err(_) => return Err(format!("timed out after {}s", timeout.as_secs())),
+    };
+    if output.status.success() {
+        return Ok(());
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let last_line = stderr.lines().last().unwrap_or_default().trim();
+    Err(match output.status.code() {
+        Some(code) if last_line.is_empty() => format!("exit code {}", code),
+        Some(code) => format!("exit code {}: {}", code, last_line),
+        None => "terminated by signal".to_string(),
+    })
+}
+
+/// Remembers each worktree's HEAD so new commits can be reported; the first
+/// sighting of a worktree only records it
+#[derive(Debug, Default)]
+pub struct CommitTracker {
+    heads: HashMap<Uuid, String>,
+}
+
+impl CommitTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `new_commit` events for worktrees whose HEAD moved since the last check
+    pub fn check(&mut self, git: &GitManager, worktrees: &[Worktree]) -> Vec<HookEvent> {
+        let mut events = Vec::new();
+        for wt in worktrees {
+            let Ok((commit, summary)) = git.get_head_commit(Path::new(&wt.path)) else {
+                continue;
+            };
+            let previous = self.heads.insert(wt.id, commit.clone());
+            if previous.is_some_and(|previous| previous != commit) {
+                events.push(
+                    HookEvent::for_worktree("new_commit", wt, summary.clone())
+                        .var("IMI_COMMIT", commit)
+                        .var("IMI_COMMIT_MESSAGE", summary),
+                );
+            }
+        }
+        events
+    }
+}
+
+/// Remembers each worktree's git status so changes can be reported; the
+/// first sighting of a worktree only records it
+#[derive(Debug, Default)]
+pub struct StatusTracker {
+    statuses: HashMap<Uuid, (bool, usize, usize)>,
+}
+
+impl StatusTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `status_changed` events for worktrees that turned dirty or clean or
+    /// whose ahead/behind counts moved, plus `branch_ahead` when a branch
+    /// gets its first commits to push
+    pub fn check(&mut self, git: &GitManager, worktrees: &[Worktree]) -> Vec<HookEvent> {
+        let mut events = Vec::new();
+        for wt in worktrees {
+            let Ok(status) = git.get_worktree_status(Path::new(&wt.path)) else {
+                continue;
+            };
+            let current = (status.clean, status.commits_ahead, status.commits_behind);
+            let Some(previous) = self.statuses.insert(wt.id, current) else {
+                continue;
+            };
+            if previous == current {
+                continue;
+            }
+
+            let (clean, ahead, behind) = current;
+            let changed =
+                status.modified_files.len() + status.new_files.len() + status.deleted_files.len();
+            let message = match clean {
+                true => format!("clean, {} ahead, {} behind", ahead, behind),
+                false => format!("{} changed, {} ahead, {} behind", changed, ahead, behind),
+            };
+            events.push(
+                HookEvent::for_worktree("status_changed", wt, message)
+                    .var("IMI_CLEAN", clean.to_string())
+                    .var("IMI_CHANGED_FILES", changed.to_string())
+                    .var("IMI_AHEAD", ahead.to_string())
+                    .var("IMI_BEHIND", behind.to_string()),
+            );
+            if previous.1 == 0 && ahead > 0 {
+                events.push(
+                    HookEvent::for_worktree(
+                        "branch_ahead",
+                        wt,
+                        format!("{} commit(s) to push", ahead),
+                    )
+                    .var("IMI_AHEAD", ahead.to_string()),
+                );
+            }
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hook(event: &str, command: &str) -> EventHook {
+        EventHook {
+            event: event.to_string(),
+            command: command.to_string(),
+            repo: None,
+        }
+    }
+
+    #[test]
+    fn hooks_match_by_event_and_repo() {
+        let event = HookEvent::for_repo("new_commit", "iMi", "");
+
+        assert!(hook("new_commit", "true").matches(&event));
+        assert!(hook("*", "true").matches(&event));
+        assert!(!hook("file_modified", "true").matches(&event));
+
+        let scoped = EventHook {
+            repo: Some("other".to_string()),
+            ..hook("new_commit", "true")
+        };
+        assert!(!scoped.matches(&event));
+    }
+
+    #[tokio::test]
+    async fn runs_hooks_with_event_env_and_logs_failures() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("out");
+        let log = dir.path().join("hooks.jsonl");
+        let settings = EventHookSettings {
+            log_path: Some(log.clone()),
+            hooks: vec![
+                hook(
+                    "fetch_failed",
+                    &format!("echo \"$IMI_EVENT $IMI_REPO\" > {}", output.display()),
+                ),
+                hook("*", "echo boom >&2; exit 3"),
+            ],
+            ..Default::default()
+        };
+        let runner = HookRunner::new(&settings);
+
+        let failed = runner
+            .run(&HookEvent::for_repo("fetch_failed", "iMi", "offline"))
+            .await;
+
+        assert_eq!(failed, 1);
+        assert_eq!(
+            std::fs::read_to_string(&output).unwrap().trim(),
+            "fetch_failed iMi"
+        );
+        let logged = std::fs::read_to_string(&log).unwrap();
+        assert!(logged.contains("exit code 3: boom"));
+    }
+}
@@ -0,0 +1,277 @@
+//! The NDJSON event stream behind `imi events`. Every event the monitor,
+//! daemon and worktree commands emit is appended to one log file, which
+//! orchestrators follow instead of polling status.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::config::EventHookSettings;
+use crate::event_hooks::HookEvent;
+
+/// How often `follow` checks the log for new lines
+const FOLLOW_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// One line of the event stream
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StreamEvent {
+    pub timestamp: DateTime<Utc>,
+    pub event: String,
+    pub repo: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub worktree: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    pub message: String,
+    /// Event-specific values, e.g. `commit` for `new_commit`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub data: BTreeMap<String, String>,
+}
+
+impl From<&HookEvent> for StreamEvent {
+    fn from(event: &HookEvent) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            event: event.name.clone(),
+            repo: event.repo_name.clone(),
+            worktree: event.worktree_name.clone(),
+            path: event.worktree_path.clone(),
+            branch: event.branch.clone(),
+            message: event.message.clone(),
+            data: event
+                .vars
+                .iter()
+                .map(|(key, value)| {
+                    let key = key.strip_prefix("IMI_").unwrap_or(key).to_lowercase();
+                    (key, value.clone())
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Which events a reader wants
+#[derive(Debug, Clone, Default)]
+pub struct EventFilter {
+    pub repo: Option<String>,
+    /// Event names; empty means all
+    pub events: Vec<String>,
+}
+
+impl EventFilter {
+    pub fn matches(&self, event: &StreamEvent) -> bool {
+        self.repo
+            .as_ref()
+            .is_none_or(|repo| repo.eq_ignore_ascii_case(&event.repo))
+            && (self.events.is_empty() || self.events.contains(&event.event))
+    }
+}
+
+/// Append-only NDJSON event log, rotated to `<name>.1` past `max_bytes`
+#[derive(Debug, Clone)]
+pub struct EventLog {
+    path: PathBuf,
+    max_bytes: u64,
+}
+
+impl EventLog {
+    pub fn new(path: impl Into<PathBuf>, max_bytes: u64) -> Self {
+        Self {
+            path: path.into(),
+            max_bytes,
+        }
+    }
+
+    /// The configured log, or None when `event_log_max_bytes` is 0
+    pub fn from_settings(settings: &EventHookSettings) -> Result<Option<Self>> {
+        if settings.event_log_max_bytes == 0 {
+            return Ok(None);
+        }
+        Ok(Some(Self::new(
+            settings.resolve_event_log_path()?,
+            settings.event_log_max_bytes,
+        )))
+    }
+
+    pub fn append(&self, event: &StreamEvent) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).context("Failed to create event log directory")?;
+        }
+        if std::fs::metadata(&self.path).is_ok_and(|m| m.len() >= self.max_bytes) {
+            let mut rotated = self.path.clone().into_os_string();
+            rotated.push(".1");
+            std::fs::rename(&self.path, rotated).context("Failed to rotate event log")?;
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .context(format!("Failed to open event log at {:?}", self.path))?;
+        // One write per line so concurrent writers don't interleave
+        let mut line = serde_json::to_string(event).context("Failed to serialize event")?;
+        line.push('\n');
+        file.write_all(line.as_bytes())
+            .context("Failed to write event")?;
+        Ok(())
+    }
+
+    /// Record an event, warning instead of failing the caller
+    pub fn record(&self, event: &HookEvent) {
+        if let Err(e) = self.append(&StreamEvent::from(event)) {
+            eprintln!(
+                "{} Failed to record {} event: {:#}",
+                "⚠️".bright_yellow(),
+                event.name,
+                e
+            );
+        }
+    }
+
+    /// The last `limit` matching events, oldest first, and the log offset
+    /// to follow from
+    pub fn tail(&self, filter: &EventFilter, limit: usize) -> Result<(Vec<StreamEvent>, u64)> {
+        let Ok(file) = std::fs::File::open(&self.path) else {
+            return Ok((Vec::new(), 0));
+        };
+        let offset = file.metadata()?.len();
+        let matching: Vec<StreamEvent> = BufReader::new(file.take(offset))
+            .lines()
+            .map_while(|line| line.ok())
+            .filter_map(|line| serde_json::from_str(&line).ok())
+            .filter(|event| filter.matches(event))
+            .collect();
+        let skip = matching.len().saturating_sub(limit);
+        Ok((matching.into_iter().skip(skip).collect(), offset))
+    }
+
+    /// Hand matching events appended after `offset` to `emit` until it
+    /// returns false. A rotated log is read from its start.
+    pub async fn follow(
+        &self,
+        filter: &EventFilter,
+        mut offset: u64,
+        mut emit: impl FnMut(&StreamEvent) -> bool,
+    ) -> Result<()> {
+        let mut partial = Vec::new();
+        loop {
+            let len = std::fs::metadata(&self.path).map_or(0, |m| m.len());
+            if len < offset {
+                offset = 0;
+                partial.clear();
+            }
+            if len > offset {
+                let mut file = std::fs::File::open(&self.path)?;
+                file.seek(SeekFrom::Start(offset))?;
+                offset += file.take(len - offset).read_to_end(&mut partial)? as u64;
+
+                // Keep a line still being written for the next round
+                let complete = partial
+                    .iter()
+                    .rposition(|byte| *byte == b'\n')
+                    .map_or(0, |end| end + 1);
+                let lines: Vec<u8> = partial.drain(..complete).collect();
+                for event in String::from_utf8_lossy(&lines)
+                    .lines()
+                    .filter_map(|line| serde_json::from_str::<StreamEvent>(line).ok())
+                    .filter(|event| filter.matches(event))
+                {
+                    if !emit(&event) {
+                        return Ok(());
+                    }
+                }
+            }
+            tokio::time::sleep(FOLLOW_POLL_INTERVAL).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(name: &str, repo: &str) -> HookEvent {
+        HookEvent::for_repo(name, repo, "message").var("IMI_COMMIT", "abc123")
+    }
+
+    #[test]
+    fn appends_and_filters_events() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events.jsonl");
+        let log = EventLog::new(&path, 1024 * 1024);
+        log.record(&event("new_commit", "iMi"));
+        log.record(&event("fetch_failed", "iMi"));
+        log.record(&event("new_commit", "other"));
+
+        let filter = EventFilter {
+            repo: Some("imi".to_string()),
+            events: vec![],
+        };
+        let (events, offset) = log.tail(&filter, 10).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].data["commit"], "abc123");
+        assert_eq!(offset, std::fs::metadata(&path).unwrap().len());
+
+        let filter = EventFilter {
+            repo: None,
+            events: vec!["new_commit".to_string()],
+        };
+        let (events, _) = log.tail(&filter, 1).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].repo, "other");
+    }
+
+    #[test]
+    fn rotates_past_max_bytes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events.jsonl");
+        let log = EventLog::new(&path, 10);
+        log.record(&event("new_commit", "iMi"));
+        log.record(&event("fetch_failed", "iMi"));
+
+        assert!(dir.path().join("events.jsonl.1").exists());
+        let (events, _) = log.tail(&EventFilter::default(), 10).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event, "fetch_failed");
+    }
+
+    #[tokio::test]
+    async fn follows_events_appended_later() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = EventLog::new(dir.path().join("events.jsonl"), 1024 * 1024);
+        log.record(&event("new_commit", "iMi"));
+        let (_, offset) = log.tail(&EventFilter::default(), 0).unwrap();
+
+        let writer = log.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            writer.record(&event("fetch_failed", "other"));
+            writer.record(&event("worktree_created", "iMi"));
+        });
+
+        let filter = EventFilter {
+            repo: Some("iMi".to_string()),
+            events: vec![],
+        };
+        let mut seen = Vec::new();
+        tokio::time::timeout(
+            Duration::from_secs(5),
+            log.follow(&filter, offset, |event| {
+                seen.push(event.event.clone());
+                false
+            }),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(seen, vec!["worktree_created"]);
+    }
+}
@@ -0,0 +1,239 @@
+//! First-run setup for `imi init`: a short wizard (or its defaults, with
+//! `--yes` or without a terminal) that writes a commented config.toml
+
+use anyhow::{Context, Result};
+use colored::*;
+use dialoguer::{theme::ColorfulTheme, Input, Select};
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+
+use crate::config::{Config, EditorKind, GitHubAuth};
+
+/// Editors offered by the wizard: label, workspace kind and command
+const EDITORS: [(&str, EditorKind, Option<&str>); 3] = [
+    ("VS Code", EditorKind::Vscode, None),
+    ("Cursor", EditorKind::Vscode, Some("cursor")),
+    ("Zed", EditorKind::Zed, None),
+];
+
+/// Comments written above keys of the generated config, by (section, key)
+const COMMENTS: &[(&str, &str, &str)] = &[
+    ("", "IMI_DATABASE_PATH", "Registry database"),
+    (
+        "",
+        "IMI_SYSTEM_PATHS",
+        "Directories holding your repositories; iMi looks for moved repos here",
+    ),
+    (
+        "",
+        "symlink_files",
+        "Files symlinked from trunk into every new worktree",
+    ),
+    (
+        "git_settings",
+        "default_branch",
+        "Trunk branch when a repository doesn't say otherwise",
+    ),
+    (
+        "git_settings",
+        "github_auth",
+        "Where GitHub tokens come from: auto, token, gh-cli or ssh",
+    ),
+    (
+        "editor",
+        "kind",
+        "Workspace generated for `imi open`: vscode or zed",
+    ),
+    ("editor", "command", "Command `imi open` runs"),
+];
+
+/// What the wizard asks for
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetupAnswers {
+    pub root: PathBuf,
+    pub default_branch: String,
+    pub github_auth: GitHubAuth,
+    pub editor_kind: EditorKind,
+    pub editor_command: Option<String>,
+    pub symlink_files: Vec<String>,
+}
+
+impl SetupAnswers {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            root: config.system_roots.first().cloned().unwrap_or_default(),
+            default_branch: config.git_settings.default_branch.clone(),
+            github_auth: config.git_settings.github_auth,
+            editor_kind: config.editor.kind,
+            editor_command: config.editor.command.clone(),
+            symlink_files: config.symlink_files.clone(),
+        }
+    }
+
+    pub fn apply(&self, config: &mut Config) {
+        config.system_roots.retain(|root| *root != self.root);
+        config.system_roots.insert(0, self.root.clone());
+        config.git_settings.default_branch = self.default_branch.clone();
+        config.git_settings.github_auth = self.github_auth;
+        config.editor.kind = self.editor_kind;
+        config.editor.command = self.editor_command.clone();
+        config.symlink_files = self.symlink_files.clone();
+    }
+
+    fn prompt(defaults: &Self) -> Result<Self> {
+        let theme = ColorfulTheme::default();
+
+        let root: String = Input::with_theme(&theme)
+            .with_prompt("Root directory for your repositories")
+            .default(defaults.root.display().to_string())
+            .interact_text()?;
+
+        let default_branch: String = Input::with_theme(&theme)
+            .with_prompt("Default branch")
+            .default(defaults.default_branch.clone())
+            .interact_text()?;
+
+        let auth_labels: Vec<&str> = GitHubAuth::ALL.iter().map(|a| a.describe()).collect();
+        let auth = Select::with_theme(&theme)
+            .with_prompt("GitHub authentication")
+            .items(&auth_labels)
+            .default(
+                GitHubAuth::ALL
+                    .iter()
+                    .position(|a| *a == defaults.github_auth)
+                    .unwrap_or(0),
+            )
+            .interact()?;
+
+        let editor_labels: Vec<&str> = EDITORS.iter().map(|(label, _, _)| *label).collect();
+        let default_editor = EDITORS
+            .iter()
+            .position(|(_, kind, command)| {
+                *kind == defaults.editor_kind
+                    && command.as_deref() == defaults.editor_command.as_deref()
+            })
+            .unwrap_or(0);
+        let editor = Select::with_theme(&theme)
+            .with_prompt("Editor")
+            .items(&editor_labels)
+            .default(default_editor)
+            .interact()?;
+
+        let symlink_files: String = Input::with_theme(&theme)
+            .with_prompt("Files to symlink from trunk into worktrees (comma-separated)")
+            .default(defaults.symlink_files.join(", "))
+            .allow_empty(true)
+            .interact_text()?;
+
+        let (_, editor_kind, editor_command) = EDITORS[editor];
+        Ok(Self {
+            root: expand_home(root.trim()),
+            default_branch: default_branch.trim().to_string(),
+            github_auth: GitHubAuth::ALL[auth],
+            editor_kind,
+            editor_command: editor_command.map(str::to_string),
+            symlink_files: symlink_files
+                .split(',')
+                .map(str::trim)
+                .filter(|file| !file.is_empty())
+                .map(str::to_string)
+                .collect(),
+        })
+    }
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// No global config yet, so `imi init` should set one up first
+pub fn needed() -> Result<bool> {
+    Ok(!Config::get_global_config_path()?.exists())
+}
+
+/// Ask the setup questions (or take the defaults when `assume_defaults` is
+/// set or there is no terminal) and write the global config; returns its path
+pub async fn run(assume_defaults: bool) -> Result<PathBuf> {
+    let path = Config::get_global_config_path()?;
+    let mut config = Config::default();
+    let defaults = SetupAnswers::from_config(&config);
+
+    let interactive =
+        !assume_defaults && std::io::stdin().is_terminal() && std::io::stdout().is_terminal();
+    let answers = if interactive {
+        println!(
+            "{} No iMi configuration yet; a few questions first (Enter keeps the default)\n",
+            "👋".bright_cyan()
+        );
+        SetupAnswers::prompt(&defaults)?
+    } else {
+        defaults
+    };
+    answers.apply(&mut config);
+
+    write(&config, &path).await?;
+    Ok(path)
+}
+
+async fn write(config: &Config, path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .context("Failed to create config directory")?;
+    }
+    tokio::fs::write(path, commented_toml(config)?)
+        .await
+        .context(format!("Failed to write {}", path.display()))
+}
+
+/// The config as TOML with a comment above the settings the wizard covers
+pub fn commented_toml(config: &Config) -> Result<String> {
+    let contents = toml::to_string_pretty(config).context("Failed to serialize config")?;
+    let mut out = String::from("# iMi configuration, written by `imi init`\n\n");
+
+    let mut section = String::new();
+    for line in contents.lines() {
+        if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = header.to_string();
+        } else if let Some((key, _)) = line.split_once(" = ") {
+            if let Some((_, _, comment)) = COMMENTS
+                .iter()
+                .find(|(s, k, _)| *s == section && *k == key.trim())
+            {
+                out.push_str(&format!("# {}\n", comment));
+            }
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commented_config_round_trips_with_the_answers() {
+        let mut config = Config::default();
+        let answers = SetupAnswers {
+            root: PathBuf::from("/src"),
+            default_branch: "develop".to_string(),
+            github_auth: GitHubAuth::GhCli,
+            editor_kind: EditorKind::Zed,
+            editor_command: None,
+            symlink_files: vec![".env.local".to_string()],
+        };
+        answers.apply(&mut config);
+
+        let contents = commented_toml(&config).unwrap();
+        assert!(contents.contains("# Trunk branch when a repository doesn't say otherwise\n"));
+        assert!(contents.contains("github_auth = \"gh-cli\""));
+
+        let parsed: Config = toml::from_str(&contents).unwrap();
+        assert_eq!(SetupAnswers::from_config(&parsed), answers);
+    }
+}
@@ -0,0 +1,188 @@
+//! Output formats for `--format`: the usual table, JSON, YAML, TSV for awk
+//! pipelines, or a template rendered once per row
+//! (`--format '{{name}}\t{{branch_name}}'`)
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::io::IsTerminal;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OutputFormat {
+    Table,
+    Json,
+    Yaml,
+    Tsv,
+    /// Tera template applied to each row
+    Template(String),
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(Self::Table),
+            "json" => Ok(Self::Json),
+            "yaml" => Ok(Self::Yaml),
+            "tsv" => Ok(Self::Tsv),
+            _ if s.contains("{{") || s.contains("{%") => Ok(Self::Template(s.to_string())),
+            _ => Err(format!(
+                "unknown format '{}': use table, json, yaml, tsv or a template like '{{{{name}}}}'",
+                s
+            )),
+        }
+    }
+}
+
+impl OutputFormat {
+    /// Format from `--format`, with `--json` as shorthand for `--format json`
+    pub fn resolve(format: Option<OutputFormat>, json: bool) -> Self {
+        match (format, json) {
+            (Some(format), _) => format,
+            (None, true) => Self::Json,
+            (None, false) => Self::Table,
+        }
+    }
+
+    /// `data` as YAML, or `rows` as TSV or through the template. Table and
+    /// JSON output stay with the caller.
+    pub fn render(&self, data: &Value, rows: &[Value]) -> Result<String> {
+        match self {
+            Self::Yaml => serde_yaml::to_string(data).context("Failed to render YAML"),
+            Self::Tsv => Ok(tsv(rows)),
+            Self::Template(template) => render_template(template, rows),
+            Self::Table | Self::Json => Ok(serde_json::to_string_pretty(data)?),
+        }
+    }
+}
+
+/// Quiet mode (`--quiet`/IMI_QUIET): stdout carries only results such as a
+/// created path, so `$(imi add feat x)` works. Unless IMI_QUIET says
+/// otherwise it follows whether stdout is a terminal.
+pub fn quiet() -> bool {
+    quiet_from(
+        std::env::var("IMI_QUIET").ok().as_deref(),
+        std::io::stdout().is_terminal(),
+    )
+}
+
+fn quiet_from(setting: Option<&str>, terminal: bool) -> bool {
+    match setting.map(str::trim) {
+        None | Some("") => !terminal,
+        Some(value) => !matches!(value, "0" | "false"),
+    }
+}
+
+/// `println!` for banners, progress and other decoration, left out in
+/// quiet mode
+#[allow(unused_macros)] // Only the binary prints banners
+macro_rules! say {
+    ($($arg:tt)*) => {
+        if !$crate::format::quiet() {
+            println!($($arg)*);
+        }
+    };
+}
+#[allow(unused_imports)]
+pub(crate) use say;
+
+/// Header line naming every key found in the rows, then one line per row
+fn tsv(rows: &[Value]) -> String {
+    let mut columns: Vec<&str> = Vec::new();
+    for row in rows {
+        if let Some(object) = row.as_object() {
+            for key in object.keys() {
+                if !columns.contains(&key.as_str()) {
+                    columns.push(key);
+                }
+            }
+        }
+    }
+
+    let mut out = columns.join("\t");
+    out.push('\n');
+    for row in rows {
+        let cells: Vec<String> = columns.iter().map(|column| cell(&row[*column])).collect();
+        out.push_str(&cells.join("\t"));
+        out.push('\n');
+    }
+    out
+}
+
+fn cell(value: &Value) -> String {
+    let text = match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    text.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+}
+
+fn render_template(template: &str, rows: &[Value]) -> Result<String> {
+    // Shells pass `\t` and `\n` through literally inside single quotes
+    let template = template.replace("\\t", "\t").replace("\\n", "\n");
+    let mut tera = tera::Tera::default();
+    tera.add_raw_template("row", &template)
+        .context("Invalid --format template")?;
+
+    let mut out = String::new();
+    for row in rows {
+        let context = tera::Context::from_value(row.clone())
+            .context("Rows must be objects to render a template")?;
+        out.push_str(
+            &tera
+                .render("row", &context)
+                .context("Failed to render --format template")?,
+        );
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_formats_and_templates() {
+        assert_eq!("yaml".parse(), Ok(OutputFormat::Yaml));
+        assert_eq!(
+            "{{name}}".parse(),
+            Ok(OutputFormat::Template("{{name}}".to_string()))
+        );
+        assert!("xml".parse::<OutputFormat>().is_err());
+        assert_eq!(OutputFormat::resolve(None, true), OutputFormat::Json);
+    }
+
+    #[test]
+    fn quiet_follows_the_terminal_unless_set() {
+        assert!(!quiet_from(None, true));
+        assert!(quiet_from(None, false));
+        assert!(quiet_from(Some("1"), true));
+        assert!(!quiet_from(Some("0"), false));
+        assert!(!quiet_from(Some("false"), false));
+        assert!(quiet_from(Some(" "), false));
+    }
+
+    #[test]
+    fn renders_rows_as_tsv_and_templates() {
+        let rows = vec![
+            json!({ "name": "feat-a", "branch_name": "feat/a", "agent_id": null }),
+            json!({ "name": "fix-b", "branch_name": "fix/b", "agent_id": "yi\t1" }),
+        ];
+        assert_eq!(
+            OutputFormat::Tsv.render(&Value::Null, &rows).unwrap(),
+            "agent_id\tbranch_name\tname\n\tfeat/a\tfeat-a\nyi\\t1\tfix/b\tfix-b\n"
+        );
+
+        let template = OutputFormat::Template("{{name}}\\t{{branch_name}}".to_string());
+        assert_eq!(
+            template.render(&Value::Null, &rows).unwrap(),
+            "feat-a\tfeat/a\nfix-b\tfix/b\n"
+        );
+    }
+}
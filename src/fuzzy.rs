@@ -1,7 +1,64 @@
 use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+use std::io::IsTerminal;
 use std::path::PathBuf;
+use uuid::Uuid;
 
-use crate::database::{Database, Repository, Worktree};
+use crate::database::{Database, NavigationStat, Repository, Worktree};
+
+/// Maximum score a heavily and recently used target gains over its fuzzy score
+const FRECENCY_WEIGHT: f64 = 0.15;
+
+/// A search string split into free text and `type:` / `repo:` filter tokens,
+/// e.g. `type:feat repo:imi auth`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchQuery {
+    pub text: String,
+    pub worktree_type: Option<String>,
+    pub repo: Option<String>,
+}
+
+impl SearchQuery {
+    pub fn parse(input: &str) -> Self {
+        let mut query = SearchQuery::default();
+        let mut words = Vec::new();
+
+        for token in input.split_whitespace() {
+            match token.split_once(':') {
+                Some(("type", value)) if !value.is_empty() => {
+                    query.worktree_type = Some(value.to_lowercase())
+                }
+                Some(("repo", value)) if !value.is_empty() => {
+                    query.repo = Some(value.to_lowercase())
+                }
+                _ => words.push(token),
+            }
+        }
+
+        query.text = words.join(" ");
+        query
+    }
+
+    fn accepts_worktree(&self, worktree: &Worktree) -> bool {
+        self.worktree_type
+            .as_deref()
+            .is_none_or(|t| worktree.worktree_type.eq_ignore_ascii_case(t))
+            && self
+                .repo
+                .as_deref()
+                .is_none_or(|r| worktree.repo_name.to_lowercase().contains(r))
+    }
+
+    fn accepts_repository(&self, repository: &Repository) -> bool {
+        // A type filter only makes sense for worktrees
+        self.worktree_type.is_none()
+            && self
+                .repo
+                .as_deref()
+                .is_none_or(|r| repository.name.to_lowercase().contains(r))
+    }
+}
 
 /// Represents a searchable target (worktree or repository)
 #[derive(Debug, Clone)]
@@ -29,6 +86,18 @@ impl SearchTarget {
         }
     }
 
+    /// Worktree name, or the trunk directory name for a repository
+    pub fn name(&self) -> String {
+        match self {
+            SearchTarget::Worktree { worktree, .. } => worktree.worktree_name.clone(),
+            SearchTarget::Repository { .. } => self
+                .path()
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default(),
+        }
+    }
+
     pub fn score(&self) -> f64 {
         match self {
             SearchTarget::Worktree { score, .. } => *score,
@@ -49,18 +118,104 @@ impl SearchTarget {
             SearchTarget::Repository { .. } => None,
         }
     }
+
+    /// Id and kind as stored in navigation history
+    fn navigation_key(&self) -> (Uuid, &'static str) {
+        match self {
+            SearchTarget::Worktree { worktree, .. } => (worktree.id, "worktree"),
+            SearchTarget::Repository { repository, .. } => (repository.id, "project"),
+        }
+    }
+
+    fn score_mut(&mut self) -> &mut f64 {
+        match self {
+            SearchTarget::Worktree { score, .. } => score,
+            SearchTarget::Repository { score, .. } => score,
+        }
+    }
 }
 
 pub struct FuzzyMatcher {
     db: Database,
+    use_frecency: bool,
+    include_archived: bool,
 }
 
 impl FuzzyMatcher {
     pub fn new(db: Database) -> Self {
-        Self { db }
+        Self {
+            db,
+            use_frecency: true,
+            include_archived: false,
+        }
+    }
+
+    /// Offer archived repositories and their worktrees too (`--all`)
+    pub fn include_archived(mut self, include_archived: bool) -> Self {
+        self.include_archived = include_archived;
+        self
+    }
+
+    /// Archived repositories to leave out; none when asked to include them,
+    /// when a repository was named or when the registry predates archiving
+    async fn hidden_repositories(&self, repo_filter: Option<&str>) -> HashSet<Uuid> {
+        if self.include_archived || repo_filter.is_some() {
+            return HashSet::new();
+        }
+        self.db
+            .list_archived_repository_ids()
+            .await
+            .unwrap_or_default()
+    }
+
+    /// Rank purely by match quality, e.g. for scripts that need stable results
+    pub fn without_frecency(mut self) -> Self {
+        self.use_frecency = false;
+        self
+    }
+
+    /// Remember a selection so it ranks higher next time
+    pub async fn record_selection(&self, target: &SearchTarget) -> Result<()> {
+        let (id, kind) = target.navigation_key();
+        self.db.record_navigation(&id, kind).await
     }
 
-    /// Fuzzy search across worktrees and repositories
+    /// Frecency per target id; empty when disabled or history is unavailable
+    async fn frecency_scores(&self) -> HashMap<Uuid, f64> {
+        if !self.use_frecency {
+            return HashMap::new();
+        }
+
+        // Navigation history is a ranking hint only, so a missing table is not an error
+        let now = Utc::now();
+        self.db
+            .list_navigation_stats()
+            .await
+            .unwrap_or_default()
+            .iter()
+            .map(|stat| (stat.target_id, frecency(stat, now)))
+            .collect()
+    }
+
+    /// Add each target's frecency boost and sort best first
+    fn rank(targets: &mut [SearchTarget], frecency: &HashMap<Uuid, f64>) {
+        for target in targets.iter_mut() {
+            if let Some(f) = frecency.get(&target.navigation_key().0) {
+                // Saturating curve keeps the boost below FRECENCY_WEIGHT
+                *target.score_mut() += FRECENCY_WEIGHT * f / (f + 10.0);
+            }
+        }
+
+        targets.sort_by(|a, b| {
+            b.score()
+                .partial_cmp(&a.score())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+    }
+
+    /// Fuzzy search across worktrees and repositories.
+    ///
+    /// `query` may contain `type:<name>` and `repo:<name>` filter tokens.
     pub async fn search(
         &self,
         query: &str,
@@ -68,17 +223,23 @@ impl FuzzyMatcher {
         worktrees_only: bool,
         include_inactive: bool,
     ) -> Result<Vec<SearchTarget>> {
+        let query = SearchQuery::parse(query);
         let mut targets = Vec::new();
+        let hidden = self.hidden_repositories(repo_filter).await;
 
         // Search worktrees
-        let worktrees = if include_inactive {
+        let mut worktrees = if include_inactive {
             self.db.list_all_worktrees(repo_filter).await?
         } else {
             self.db.list_worktrees(repo_filter).await?
         };
+        worktrees.retain(|wt| !hidden.contains(&wt.project_id));
 
         for worktree in worktrees {
-            let score = self.calculate_score(query, &worktree);
+            if !query.accepts_worktree(&worktree) {
+                continue;
+            }
+            let score = self.calculate_score(&query.text, &worktree);
             if score > 0.0 {
                 targets.push(SearchTarget::Worktree { worktree, score });
             }
@@ -88,19 +249,18 @@ impl FuzzyMatcher {
         if !worktrees_only && repo_filter.is_none() {
             let repositories = self.db.list_repositories().await?;
             for repository in repositories {
-                let score = self.calculate_repo_score(query, &repository);
+                if hidden.contains(&repository.id) || !query.accepts_repository(&repository) {
+                    continue;
+                }
+                let score = self.calculate_repo_score(&query.text, &repository);
                 if score > 0.0 {
                     targets.push(SearchTarget::Repository { repository, score });
                 }
             }
         }
 
-        // Sort by score (highest first)
-        targets.sort_by(|a, b| {
-            b.score()
-                .partial_cmp(&a.score())
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
+        // Sort by score (highest first), nudged by how often each target is used
+        Self::rank(&mut targets, &self.frecency_scores().await);
 
         Ok(targets)
     }
@@ -114,10 +274,14 @@ impl FuzzyMatcher {
         use dialoguer::{theme::ColorfulTheme, Select};
 
         let mut targets = Vec::new();
+        let hidden = self.hidden_repositories(repo_filter).await;
 
         // Get all worktrees
         let worktrees = self.db.list_worktrees(repo_filter).await?;
         for worktree in worktrees {
+            if hidden.contains(&worktree.project_id) {
+                continue;
+            }
             targets.push(SearchTarget::Worktree {
                 worktree,
                 score: 1.0, // All targets have equal score in interactive mode
@@ -128,6 +292,9 @@ impl FuzzyMatcher {
         if !worktrees_only && repo_filter.is_none() {
             let repositories = self.db.list_repositories().await?;
             for repository in repositories {
+                if hidden.contains(&repository.id) {
+                    continue;
+                }
                 targets.push(SearchTarget::Repository {
                     repository,
                     score: 1.0,
@@ -139,6 +306,9 @@ impl FuzzyMatcher {
             return Ok(None);
         }
 
+        // Most frequently and recently used first
+        Self::rank(&mut targets, &self.frecency_scores().await);
+
         // Format display names with icons
         let display_items: Vec<String> = targets
             .iter()
@@ -177,6 +347,11 @@ impl FuzzyMatcher {
     fn calculate_score(&self, query: &str, worktree: &Worktree) -> f64 {
         let query_lower = query.to_lowercase();
 
+        // Only filter tokens were given: everything that passed them matches
+        if query_lower.is_empty() {
+            return 0.5;
+        }
+
         // Exact match gets highest score
         if worktree.worktree_name.to_lowercase() == query_lower {
             return 1.0;
@@ -202,6 +377,11 @@ impl FuzzyMatcher {
             return 0.4;
         }
 
+        // Scattered characters in order (e.g., "fauth" finds "feat-auth")
+        if match_positions(&worktree.worktree_name, query).is_some() {
+            return 0.3;
+        }
+
         // No match
         0.0
     }
@@ -209,6 +389,11 @@ impl FuzzyMatcher {
     fn calculate_repo_score(&self, query: &str, repository: &Repository) -> f64 {
         let query_lower = query.to_lowercase();
 
+        // Only filter tokens were given: everything that passed them matches
+        if query_lower.is_empty() {
+            return 0.5;
+        }
+
         if repository.name.to_lowercase() == query_lower {
             return 1.0;
         }
@@ -220,3 +405,121 @@ impl FuzzyMatcher {
         0.0
     }
 }
+
+/// zoxide-style frecency: visits weighted by how recently the last one happened
+fn frecency(stat: &NavigationStat, now: DateTime<Utc>) -> f64 {
+    let age = now.signed_duration_since(stat.last_visited_at);
+    let recency = if age.num_hours() < 1 {
+        4.0
+    } else if age.num_days() < 1 {
+        2.0
+    } else if age.num_weeks() < 1 {
+        0.5
+    } else {
+        0.25
+    };
+    stat.visit_count.max(0) as f64 * recency
+}
+
+/// Char indices of `query` within `text`, preferring a contiguous
+/// case-insensitive substring and falling back to an in-order subsequence
+pub fn match_positions(text: &str, query: &str) -> Option<Vec<usize>> {
+    let text_chars: Vec<char> = text.chars().flat_map(char::to_lowercase).collect();
+    let query_chars: Vec<char> = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+
+    // Lowercasing can change the length of exotic characters; don't guess positions
+    if query_chars.is_empty() || text_chars.len() != text.chars().count() {
+        return None;
+    }
+
+    if let Some(start) = text_chars
+        .windows(query_chars.len())
+        .position(|window| window == query_chars.as_slice())
+    {
+        return Some((start..start + query_chars.len()).collect());
+    }
+
+    let mut positions = Vec::with_capacity(query_chars.len());
+    let mut wanted = query_chars.iter().peekable();
+    for (idx, c) in text_chars.iter().enumerate() {
+        if wanted.peek() == Some(&c) {
+            positions.push(idx);
+            wanted.next();
+        }
+    }
+
+    wanted.peek().is_none().then_some(positions)
+}
+
+/// Emphasize the characters of `text` matched by the free text of `query`.
+///
+/// Pickers render on stderr while `imi go` prints the path on stdout for the
+/// shell wrapper, so color is decided by stderr rather than by `colored`.
+pub fn highlight_matches(text: &str, query: &str) -> String {
+    let query = SearchQuery::parse(query);
+    let positions = match match_positions(text, &query.text) {
+        Some(positions)
+            if std::io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none() =>
+        {
+            positions
+        }
+        _ => return text.to_string(),
+    };
+
+    let mut highlighted = String::with_capacity(text.len() + positions.len() * 10);
+    for (idx, c) in text.chars().enumerate() {
+        if positions.contains(&idx) {
+            highlighted.push_str("\x1b[1;33m");
+            highlighted.push(c);
+            highlighted.push_str("\x1b[0m");
+        } else {
+            highlighted.push(c);
+        }
+    }
+    highlighted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_filter_tokens() {
+        let query = SearchQuery::parse("type:feat repo:iMi auth flow");
+        assert_eq!(query.text, "auth flow");
+        assert_eq!(query.worktree_type.as_deref(), Some("feat"));
+        assert_eq!(query.repo.as_deref(), Some("imi"));
+
+        // Unknown or empty tokens stay part of the text
+        assert_eq!(SearchQuery::parse("note:x type:").text, "note:x type:");
+    }
+
+    #[test]
+    fn finds_substring_then_subsequence_positions() {
+        assert_eq!(match_positions("feat-Auth", "auth"), Some(vec![5, 6, 7, 8]));
+        assert_eq!(
+            match_positions("feat-auth", "fauth"),
+            Some(vec![0, 2, 6, 7, 8])
+        );
+        assert_eq!(match_positions("feat-auth", "xyz"), None);
+        assert_eq!(match_positions("feat-auth", ""), None);
+    }
+
+    #[test]
+    fn recent_visits_outweigh_old_ones() {
+        let now = Utc::now();
+        let stat = |visits, hours_ago| NavigationStat {
+            target_id: Uuid::new_v4(),
+            target_kind: "worktree".to_string(),
+            visit_count: visits,
+            last_visited_at: now - chrono::Duration::hours(hours_ago),
+        };
+
+        assert!(frecency(&stat(2, 0), now) > frecency(&stat(10, 24 * 30), now));
+        assert!(frecency(&stat(3, 2), now) > frecency(&stat(3, 48), now));
+    }
+}
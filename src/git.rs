@@ -21,53 +21,203 @@ impl GitCredentials {
 
 use anyhow::{Context, Result};
 use git2::build::CheckoutBuilder;
-use git2::{BranchType, Cred, RemoteCallbacks, Repository, WorktreeAddOptions};
+use git2::{BranchType, Cred, CredentialType, RemoteCallbacks, Repository, WorktreeAddOptions};
+use serde::{Deserialize, Serialize};
 use std::env;
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::Arc;
 
-use crate::context::{GitContext, WorktreeLocationType};
+use crate::backend::{GitBackend, GitOutput, SystemGit};
+use crate::config::{Config, GitHubAuth, RetrySettings};
+use crate::context::{self, GitContext, WorktreeLocationType};
 use crate::error::ImiError;
+use crate::remote_url::RemoteUrl;
+use crate::retry::{is_transient_message, retry};
 
-#[derive(Debug, Clone)]
+/// One entry of `git worktree list --porcelain`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct GitWorktree {
     pub path: String,
-    pub branch: String,
+    /// Checked-out branch without `refs/heads/`; None when detached or bare
+    pub branch: Option<String>,
     pub commit: String,
+    pub bare: bool,
+    pub detached: bool,
+    /// Lock reason, empty when locked without one
+    pub locked: Option<String>,
+    /// Why git considers the worktree stale, e.g. its directory is gone
+    pub prunable: Option<String>,
 }
 
-#[derive(Debug, Clone)]
-pub struct GitManager;
+impl GitWorktree {
+    pub fn is_locked(&self) -> bool {
+        self.locked.is_some()
+    }
 
-impl GitManager {
-    pub fn new() -> Self {
-        Self
+    pub fn is_prunable(&self) -> bool {
+        self.prunable.is_some()
     }
 
-    /// Get GitHub authentication credentials
-    #[allow(dead_code)]
-    fn get_github_credentials(&self) -> Option<Cred> {
-        // Check for GitHub Personal Access Token
-        if let Ok(token) = env::var("GITHUB_PERSONAL_ACCESS_TOKEN") {
-            if !token.is_empty() {
-                return Some(Cred::userpass_plaintext("", &token).ok()?);
+    /// Whether this is the worktree at `path`. git reports real paths, so
+    /// symlinks are resolved on both sides; a directory that's gone is
+    /// resolved through its parent
+    pub fn is_at(&self, path: &Path) -> bool {
+        let own = Path::new(&self.path);
+        own == path || matches!((real_path(own), real_path(path)), (Some(a), Some(b)) if a == b)
+    }
+}
+
+fn real_path(path: &Path) -> Option<PathBuf> {
+    path.canonicalize()
+        .ok()
+        .or_else(|| Some(path.parent()?.canonicalize().ok()?.join(path.file_name()?)))
+}
+
+/// Parse `git worktree list --porcelain` output whose fields end with
+/// `separator`: NUL with `-z`, otherwise newline. Records are separated by
+/// an empty field; attributes git adds later are ignored
+pub fn parse_worktree_porcelain(output: &str, separator: char) -> Vec<GitWorktree> {
+    let mut worktrees = Vec::new();
+    let mut current: Option<GitWorktree> = None;
+
+    for field in output.split(separator) {
+        if field.is_empty() {
+            worktrees.extend(current.take());
+            continue;
+        }
+        let (key, value) = match field.split_once(' ') {
+            Some((key, value)) => (key, Some(value)),
+            None => (field, None),
+        };
+        if key == "worktree" {
+            worktrees.extend(current.take());
+            current = Some(GitWorktree {
+                path: value.unwrap_or("").to_string(),
+                ..Default::default()
+            });
+            continue;
+        }
+        let Some(wt) = current.as_mut() else {
+            continue;
+        };
+        match key {
+            "HEAD" => wt.commit = value.unwrap_or("").to_string(),
+            "branch" => {
+                let branch = value.unwrap_or("");
+                wt.branch = Some(
+                    branch
+                        .strip_prefix("refs/heads/")
+                        .unwrap_or(branch)
+                        .to_string(),
+                );
             }
+            "bare" => wt.bare = true,
+            "detached" => wt.detached = true,
+            "locked" => wt.locked = Some(value.unwrap_or("").to_string()),
+            "prunable" => wt.prunable = Some(value.unwrap_or("").to_string()),
+            _ => {}
         }
+    }
+    worktrees.extend(current);
+    worktrees
+}
 
-        // Also check common alternative env var names
-        if let Ok(token) = env::var("GITHUB_TOKEN") {
-            if !token.is_empty() {
-                return Some(Cred::userpass_plaintext("", &token).ok()?);
-            }
+/// Where a new worktree's branch starts: `branch` as found on `remote`
+/// (see [`GitManager::resolve_base`]), or the repository's HEAD without one
+#[derive(Debug, Clone, Copy)]
+pub struct BranchBase<'a> {
+    pub branch: Option<&'a str>,
+    /// Fetched first; normally origin, or the upstream of a fork
+    pub remote: &'a str,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct GitManager {
+    /// Extra SSH private keys to offer before the default ~/.ssh keys
+    ssh_key_paths: Vec<PathBuf>,
+    github_auth: GitHubAuth,
+    retry: RetrySettings,
+    /// Where system git commands run; `None` is the `git` on PATH
+    backend: Option<Arc<dyn GitBackend>>,
+}
+
+/// Tracks which credential sources have been offered during one remote operation.
+/// libgit2 calls the credentials callback again after each rejected credential,
+/// so every source is tried at most once.
+#[derive(Debug, Default)]
+struct CredentialState {
+    username_sent: bool,
+    ssh_agent_tried: bool,
+    key_index: usize,
+    helper_tried: bool,
+    token_tried: bool,
+    default_tried: bool,
+    tried: Vec<String>,
+}
+
+impl GitManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build a manager that honors credential and retry settings from the config
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            ssh_key_paths: config.git_settings.ssh_key_paths.clone(),
+            github_auth: config.git_settings.github_auth,
+            retry: config.retry_settings.clone(),
+            backend: None,
         }
+    }
 
-        if let Ok(token) = env::var("GH_TOKEN") {
-            if !token.is_empty() {
-                return Some(Cred::userpass_plaintext("", &token).ok()?);
-            }
+    /// Run system git commands through `backend` instead of the `git` on PATH
+    #[allow(dead_code)] // Library accessor
+    pub fn with_backend(mut self, backend: Arc<dyn GitBackend>) -> Self {
+        self.backend = Some(backend);
+        self
+    }
+
+    /// GitHub token from the environment, falling back to the gh CLI's secure
+    /// store, limited to the sources `git_settings.github_auth` allows
+    fn github_token(&self) -> Option<String> {
+        let from_env = || {
+            ["GITHUB_PERSONAL_ACCESS_TOKEN", "GITHUB_TOKEN", "GH_TOKEN"]
+                .iter()
+                .filter_map(|var| env::var(var).ok())
+                .find(|token| !token.trim().is_empty())
+        };
+        let from_gh = || {
+            let output = Command::new("gh").args(["auth", "token"]).output().ok()?;
+            let token = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            (output.status.success() && !token.is_empty()).then_some(token)
+        };
+
+        match self.github_auth {
+            GitHubAuth::Auto => from_env().or_else(from_gh),
+            GitHubAuth::Token => from_env(),
+            GitHubAuth::GhCli => from_gh(),
+            GitHubAuth::Ssh => None,
         }
+    }
+
+    /// SSH private keys to try: configured paths first, then the usual defaults
+    fn candidate_ssh_keys(&self) -> Vec<PathBuf> {
+        let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+        let expand = |path: &PathBuf| match path.strip_prefix("~") {
+            Ok(rest) => home.join(rest),
+            Err(_) => path.clone(),
+        };
 
-        None
+        self.ssh_key_paths
+            .iter()
+            .map(expand)
+            .chain(
+                ["id_ed25519", "id_rsa", "id_ecdsa"]
+                    .iter()
+                    .map(|name| home.join(".ssh").join(name)),
+            )
+            .collect()
     }
 
     /// Prompt user for GitHub Personal Access Token
@@ -93,47 +243,135 @@ impl GitManager {
         }
     }
 
-    /// Create authentication callbacks for git operations
-    fn create_auth_callbacks(&self) -> RemoteCallbacks<'_> {
-        let mut callbacks = RemoteCallbacks::new();
-
-        callbacks.credentials(|_url, username_from_url, _allowed_types| {
-            if let Some(username) = username_from_url {
-                // Try SSH keys directly from filesystem
-                let home = env::var("HOME").unwrap_or_else(|_| "/home/delorenj".to_string());
-                let ssh_dir = format!("{}/.ssh", home);
-
-                // Try common key files in order of preference
-                let key_files = ["id_ed25519", "id_rsa", "id_ecdsa"];
-                for key_file in &key_files {
-                    let private_key_path = format!("{}/{}", ssh_dir, key_file);
-                    let public_key_path = format!("{}/{}.pub", ssh_dir, key_file);
-
-                    if std::path::Path::new(&private_key_path).exists() {
-                        let public_key_opt = if std::path::Path::new(&public_key_path).exists() {
-                            Some(std::path::Path::new(&public_key_path))
-                        } else {
-                            None
-                        };
-
-                        if let Ok(cred) = Cred::ssh_key(
-                            username,
-                            public_key_opt,
-                            std::path::Path::new(&private_key_path),
-                            None,
-                        ) {
-                            return Ok(cred);
-                        }
+    /// Offer the next untried credential for the types libgit2 will accept
+    fn next_credential(
+        &self,
+        state: &mut CredentialState,
+        url: &str,
+        username_from_url: Option<&str>,
+        allowed: CredentialType,
+    ) -> std::result::Result<Cred, git2::Error> {
+        let username = username_from_url.unwrap_or("git");
+
+        if allowed.contains(CredentialType::USERNAME) && !state.username_sent {
+            state.username_sent = true;
+            return Cred::username(username);
+        }
+
+        if allowed.contains(CredentialType::SSH_KEY) {
+            if !state.ssh_agent_tried {
+                state.ssh_agent_tried = true;
+                if env::var_os("SSH_AUTH_SOCK").is_some() {
+                    state.tried.push("ssh-agent".to_string());
+                    if let Ok(cred) = Cred::ssh_key_from_agent(username) {
+                        return Ok(cred);
                     }
                 }
             }
 
-            Err(git2::Error::from_str("SSH authentication failed"))
-        });
+            let keys = self.candidate_ssh_keys();
+            while state.key_index < keys.len() {
+                let private_key = &keys[state.key_index];
+                state.key_index += 1;
+                if !private_key.exists() {
+                    continue;
+                }
+
+                state
+                    .tried
+                    .push(format!("ssh key {}", private_key.display()));
+                let public_key = public_key_path(private_key);
+                let passphrase = env::var("IMI_SSH_KEY_PASSPHRASE").ok();
+                if let Ok(cred) = Cred::ssh_key(
+                    username,
+                    public_key.exists().then_some(public_key.as_path()),
+                    private_key,
+                    passphrase.as_deref(),
+                ) {
+                    return Ok(cred);
+                }
+            }
+        }
+
+        if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) {
+            if !state.helper_tried {
+                state.helper_tried = true;
+                if let Ok(git_config) = git2::Config::open_default() {
+                    if let Ok(cred) = Cred::credential_helper(&git_config, url, username_from_url) {
+                        state.tried.push("git credential helper".to_string());
+                        return Ok(cred);
+                    }
+                }
+            }
+
+            if !state.token_tried {
+                state.token_tried = true;
+                let enterprise_host = env::var("GH_HOST").ok();
+                if let Some(token) = sends_github_token(url, enterprise_host.as_deref())
+                    .then(|| self.github_token())
+                    .flatten()
+                {
+                    state.tried.push("GitHub token".to_string());
+                    return Cred::userpass_plaintext("x-access-token", &token);
+                }
+            }
+        }
+
+        if allowed.contains(CredentialType::DEFAULT) && !state.default_tried {
+            state.default_tried = true;
+            return Cred::default();
+        }
+
+        Err(git2::Error::new(
+            git2::ErrorCode::Auth,
+            git2::ErrorClass::Callback,
+            "no more credentials to try",
+        ))
+    }
 
-        callbacks
+    /// Run a remote operation with credential callbacks, turning failures into
+    /// errors that distinguish authentication problems from network problems.
+    /// Network failures are retried per the retry settings.
+    fn with_remote_auth<T>(
+        &self,
+        remote_name: &str,
+        action: &str,
+        mut op: impl FnMut(RemoteCallbacks<'_>) -> std::result::Result<T, git2::Error>,
+    ) -> Result<T> {
+        retry(
+            &self.retry,
+            &format!("git {} {}", action, remote_name),
+            || {
+                let state = std::cell::RefCell::new(CredentialState::default());
+
+                let result = {
+                    let mut callbacks = RemoteCallbacks::new();
+                    callbacks.credentials(|url, username, allowed| {
+                        self.next_credential(&mut state.borrow_mut(), url, username, allowed)
+                    });
+                    op(callbacks)
+                };
+
+                result.map_err(|err| classify_remote_error(err, remote_name, &state.borrow().tried))
+            },
+        )
     }
 
+    /// Run `gh` in `repo_path`, retrying when it fails with a transient error.
+    /// Other failures are returned as output for the caller to interpret.
+    fn run_gh(&self, repo_path: &Path, args: &[&str]) -> Result<std::process::Output> {
+        retry(&self.retry, &format!("gh {}", args.join(" ")), || {
+            let output = Command::new("gh")
+                .current_dir(repo_path)
+                .args(args)
+                .output()?;
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if !output.status.success() && is_transient_message(&stderr) {
+                return Err(anyhow::anyhow!("{}", stderr.trim()));
+            }
+            Ok(output)
+        })
+    }
     /// Check if GitHub authentication is available
     pub fn check_github_auth(&self) -> bool {
         // Check environment variables first
@@ -147,25 +385,25 @@ impl GitManager {
         use colored::*;
 
         if self.check_github_auth() {
-            println!("✅ GitHub authentication available via environment variable");
+            eprintln!("✅ GitHub authentication available via environment variable");
         } else {
-            println!("⚠️  GitHub authentication not configured");
-            println!("   To authenticate with GitHub, set one of these environment variables:");
-            println!(
+            eprintln!("⚠️  GitHub authentication not configured");
+            eprintln!("   To authenticate with GitHub, set one of these environment variables:");
+            eprintln!(
                 "   • {}",
                 "export GITHUB_PERSONAL_ACCESS_TOKEN=your_token_here".bright_cyan()
             );
-            println!(
+            eprintln!(
                 "   • {}",
                 "export GITHUB_TOKEN=your_token_here".bright_cyan()
             );
-            println!("   • {}", "export GH_TOKEN=your_token_here".bright_cyan());
-            println!();
-            println!(
+            eprintln!("   • {}", "export GH_TOKEN=your_token_here".bright_cyan());
+            eprintln!();
+            eprintln!(
                 "   Create a Personal Access Token at: {}",
                 "https://github.com/settings/tokens".bright_blue()
             );
-            println!(
+            eprintln!(
                 "   Required scopes: repo (for private repos) or public_repo (for public repos)"
             );
         }
@@ -181,7 +419,7 @@ impl GitManager {
         let search_path = path.unwrap_or_else(|| Path::new("."));
 
         // Try to discover a repository
-        let repo = match Repository::discover(search_path) {
+        let repo = match self.discover_repository(search_path) {
             Ok(repo) => repo,
             Err(_) => return GitContext::Outside,
         };
@@ -302,11 +540,47 @@ impl GitManager {
         ))
     }
 
+    /// Ask `origin` itself which branch its HEAD points at, rather than
+    /// trusting local refs. Fails when the remote can't be reached; `None`
+    /// means it answered but has no HEAD yet (an empty repository).
+    pub fn remote_default_branch(&self, path: &Path) -> Result<Option<String>> {
+        let repo = self.find_repository(Some(path))?;
+        let mut remote = repo.find_remote("origin")?;
+
+        self.with_remote_auth("origin", "ls-remote", |callbacks| {
+            let connection = remote.connect_auth(git2::Direction::Fetch, Some(callbacks), None)?;
+            let branch = connection.default_branch().ok().and_then(|head| {
+                head.as_str()
+                    .map(|name| name.trim_start_matches("refs/heads/").to_string())
+            });
+            Ok(branch)
+        })
+    }
+
+    /// Commit a new branch starts from: `<upstream>/<base>` when that exists,
+    /// else `base` as a local branch, remote branch, tag or commit
+    pub fn resolve_base<'r>(
+        &self,
+        repo: &'r Repository,
+        base: &str,
+        upstream: &str,
+    ) -> Result<git2::Commit<'r>> {
+        repo.revparse_single(&format!("{}/{}", upstream, base))
+            .or_else(|_| repo.revparse_single(base))
+            .and_then(|object| object.peel_to_commit())
+            .map_err(|_| {
+                anyhow::anyhow!(
+                    "Base '{}' is not a branch, tag or commit in this repository",
+                    base
+                )
+            })
+    }
+
     /// Find the Git repository from the current directory or a specified path
     pub fn find_repository(&self, path: Option<&Path>) -> Result<Repository> {
         let search_path = path.unwrap_or_else(|| Path::new("."));
 
-        Repository::discover(search_path).map_err(|_e| {
+        self.discover_repository(search_path).map_err(|_e| {
             ImiError::GitRepositoryNotFound {
                 path: search_path.display().to_string(),
             }
@@ -327,15 +601,9 @@ impl GitManager {
             .context("No suitable remote found")?;
 
         let url = remote.url().context("Remote URL not found")?;
+        let remote = RemoteUrl::parse(url).context("Could not extract repository name from URL")?;
 
-        // Extract repo name from URL (handles both SSH and HTTPS)
-        let name = url
-            .split('/')
-            .last()
-            .context("Could not extract repository name from URL")?
-            .trim_end_matches(".git");
-
-        Ok(name.to_string())
+        Ok(remote.name)
     }
 
     /// Get repository name from current directory
@@ -344,57 +612,44 @@ impl GitManager {
         self.get_repository_name(&repo)
     }
 
-    /// Create a new worktree
+    /// Create a new worktree on `branch`; returns where the branch came from
     pub fn create_worktree(
         &self,
         repo: &Repository,
         name: &str,
         path: &Path,
         branch: &str,
-        base_branch: Option<&str>,
-    ) -> Result<()> {
+        base: BranchBase,
+        adopt_existing: bool,
+    ) -> Result<BranchOrigin> {
         // Ensure we have the latest changes from remote
-        self.fetch_all(repo)?;
+        self.fetch_remote(repo, base.remote)?;
 
-        // Create the branch if it doesn't exist
-        let base = if let Some(base_ref) = base_branch {
-            format!("origin/{}", base_ref)
-        } else {
-            "HEAD".to_string()
+        // The base is normally a branch on the remote, but a local-only
+        // branch, a tag or a commit works too.
+        let base_commit = match base.branch {
+            Some(branch) => self.resolve_base(repo, branch, base.remote)?,
+            None => repo.head()?.peel_to_commit()?,
         };
 
-        // Clean up any existing branches that might conflict
-        // This handles both the intended branch name and any alternative naming patterns
-        let potential_branch_names = vec![
-            branch.to_string(),
-            // Also check for dash-separated version (in case of naming confusion)
-            branch.replace('/', "-"),
-            // Also check for worktree name as branch (common mistake)
-            name.to_string(),
-        ];
-
-        for branch_to_check in &potential_branch_names {
-            if let Ok(mut existing_branch) = repo.find_branch(branch_to_check, BranchType::Local) {
-                // Check if branch is in use by any worktree
-                let is_in_use = self.is_branch_in_use_by_worktree(repo, branch_to_check)?;
-
-                if !is_in_use && !existing_branch.is_head() {
-                    println!("🗑️ Removing existing branch: {}", branch_to_check);
+        // Branches left behind under the dash-separated or worktree name
+        // (e.g. by git2 auto-creating one) are removed when unused
+        for leftover in [branch.replace('/', "-"), name.to_string()] {
+            if leftover == branch {
+                continue;
+            }
+            if let Ok(mut existing_branch) = repo.find_branch(&leftover, BranchType::Local) {
+                if !existing_branch.is_head()
+                    && !self.is_branch_in_use_by_worktree(repo, &leftover)?
+                {
+                    println!("🗑️ Removing existing branch: {}", leftover);
                     existing_branch.delete()?;
                     println!("✅ Existing branch removed");
-                } else if branch_to_check == branch {
-                    // If the intended branch is in use, we can't recreate it
-                    return Err(anyhow::anyhow!(
-                        "Cannot recreate branch '{}' as it is currently in use by another worktree",
-                        branch
-                    ));
                 }
             }
         }
 
-        // Now create the new branch
-        let base_commit = repo.revparse_single(&base)?.peel_to_commit()?;
-        repo.branch(branch, &base_commit, false)?;
+        let origin = self.prepare_branch(repo, branch, &base_commit, adopt_existing)?;
 
         // Only clean up if there are actual conflicts (worktree exists or directory exists)
         let needs_cleanup = self.worktree_exists(repo, name) || path.exists();
@@ -402,6 +657,12 @@ impl GitManager {
             self.cleanup_worktree_artifacts(repo, name, path)?;
         }
 
+        // A configured worktree_dir may not exist yet
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+
         // Add the worktree with the worktree name, then we'll checkout the correct branch
         let mut options = WorktreeAddOptions::new();
         let worktree = repo.worktree(name, path, Some(&mut options))?;
@@ -442,7 +703,83 @@ impl GitManager {
                 .remove_untracked(true),
         ))?;
 
-        Ok(())
+        Ok(origin)
+    }
+
+    /// Make `branch` exist locally for a new worktree. A branch that already
+    /// exists here or on origin is only checked out with `adopt_existing`;
+    /// a local one with nothing beyond `base` is simply created again.
+    fn prepare_branch(
+        &self,
+        repo: &Repository,
+        branch: &str,
+        base: &git2::Commit,
+        adopt_existing: bool,
+    ) -> Result<BranchOrigin> {
+        let local = repo.find_branch(branch, BranchType::Local).ok();
+        let remote_name = format!("origin/{}", branch);
+        let remote = repo.find_branch(&remote_name, BranchType::Remote).ok();
+        let checked_out = match &local {
+            Some(local) => local.is_head() || self.is_branch_in_use_by_worktree(repo, branch)?,
+            None => false,
+        };
+        if checked_out {
+            return Err(anyhow::anyhow!(
+                "Branch '{}' is already checked out in another worktree",
+                branch
+            ));
+        }
+
+        match (local, remote) {
+            (None, None) => {
+                repo.branch(branch, base, false)?;
+                Ok(BranchOrigin::Created)
+            }
+            (Some(_), _) if adopt_existing => Ok(BranchOrigin::AdoptedLocal),
+            (None, Some(remote)) if adopt_existing => {
+                let commit = remote.get().peel_to_commit()?;
+                let mut local = repo.branch(branch, &commit, false)?;
+                local.set_upstream(Some(&remote_name))?;
+                Ok(BranchOrigin::AdoptedRemote)
+            }
+            (Some(mut local), None) if self.contains(repo, base, &local)? => {
+                local.delete()?;
+                repo.branch(branch, base, false)?;
+                Ok(BranchOrigin::Recreated)
+            }
+            (local, remote) => {
+                let location = match (local.is_some(), remote.is_some()) {
+                    (true, true) => "locally and on origin",
+                    (true, false) => "locally",
+                    _ => "on origin",
+                };
+                Err(ImiError::BranchExists {
+                    branch: branch.to_string(),
+                    location: location.to_string(),
+                    suggestion: self.free_branch_name(repo, branch),
+                }
+                .into())
+            }
+        }
+    }
+
+    /// Whether every commit of `branch` is already in `base`
+    fn contains(
+        &self,
+        repo: &Repository,
+        base: &git2::Commit,
+        branch: &git2::Branch,
+    ) -> Result<bool> {
+        let tip = branch.get().peel_to_commit()?.id();
+        Ok(tip == base.id() || repo.graph_descendant_of(base.id(), tip)?)
+    }
+
+    /// `branch` with the first numeric suffix taken neither here nor on origin
+    fn free_branch_name(&self, repo: &Repository, branch: &str) -> String {
+        (2..)
+            .map(|n| format!("{}-{}", branch, n))
+            .find(|candidate| !self.branch_exists(repo, candidate))
+            .unwrap_or_else(|| branch.to_string())
     }
 
     /// Remove a worktree
@@ -461,48 +798,67 @@ impl GitManager {
         Ok(())
     }
 
-    /// Prune all stale worktree references
+    /// Prune all stale worktree references: those git reports as prunable
+    /// or whose directory is gone. Locked worktrees are kept, as git does,
+    /// since their directory may only be on a drive that isn't mounted
     pub fn prune_worktrees(&self, repo: &Repository) -> Result<()> {
         use colored::Colorize;
 
-        // Get list of worktrees and prune any that are prunable
-        let worktrees = repo.worktrees()?;
+        let repo_path = repo.workdir().unwrap_or_else(|| repo.path());
+        let stale: Vec<GitWorktree> = self
+            .list_git_worktrees(repo_path)?
+            .into_iter()
+            .filter(|wt| !wt.bare && (wt.is_prunable() || !Path::new(&wt.path).exists()))
+            .collect();
+        if stale.is_empty() {
+            return self.forget_removed_worktree_config(repo_path);
+        }
+
+        // The admin directories under .git/worktrees, by worktree path
+        let admin_names: Vec<(PathBuf, String)> = repo
+            .worktrees()?
+            .iter()
+            .flatten()
+            .filter_map(|name| {
+                let worktree = repo.find_worktree(name).ok()?;
+                Some((worktree.path().to_path_buf(), name.to_string()))
+            })
+            .collect();
         let mut pruned_count = 0;
 
-        for worktree_name in worktrees.iter().flatten() {
-            if let Ok(worktree) = repo.find_worktree(worktree_name) {
-                // Check if the worktree directory actually exists
-                let worktree_path = worktree.path().parent().unwrap_or(worktree.path());
-                let directory_exists = worktree_path.exists();
-
-                // If directory doesn't exist, force-prune by removing admin directory
-                if !directory_exists {
-                    println!(
-                        "🔍 Found stale worktree reference: {} (directory missing)",
-                        worktree_name
-                    );
+        for wt in stale {
+            if let Some(reason) = &wt.locked {
+                let reason = if reason.is_empty() {
+                    String::new()
+                } else {
+                    format!(": {}", reason)
+                };
+                println!(
+                    "🔒 Keeping locked worktree reference: {}{}",
+                    wt.path, reason
+                );
+                continue;
+            }
+            let Some((_, name)) = admin_names.iter().find(|(path, _)| wt.is_at(path)) else {
+                continue;
+            };
+            println!(
+                "🔍 Found stale worktree reference: {} ({})",
+                name,
+                wt.prunable.as_deref().unwrap_or("directory missing")
+            );
 
-                    // Force-remove the administrative directory
-                    let git_dir = repo.path();
-                    let worktree_admin_dir = git_dir.join("worktrees").join(worktree_name);
-
-                    if worktree_admin_dir.exists() {
-                        std::fs::remove_dir_all(&worktree_admin_dir).context(format!(
-                            "Failed to remove worktree admin directory for {}",
-                            worktree_name
-                        ))?;
-                        println!(
-                            "🗑️  Removed Git admin directory for: {}",
-                            worktree_name.bright_yellow()
-                        );
-                        pruned_count += 1;
-                    }
-                } else if worktree.is_prunable(None)? {
-                    // Standard pruning for normally-prunable worktrees
-                    worktree.prune(None)?;
-                    println!("🧹 Pruned worktree reference: {}", worktree_name);
-                    pruned_count += 1;
-                }
+            let worktree_admin_dir = repo.path().join("worktrees").join(name);
+            if worktree_admin_dir.exists() {
+                std::fs::remove_dir_all(&worktree_admin_dir).context(format!(
+                    "Failed to remove worktree admin directory for {}",
+                    name
+                ))?;
+                println!(
+                    "🗑️  Removed Git admin directory for: {}",
+                    name.bright_yellow()
+                );
+                pruned_count += 1;
             }
         }
 
@@ -510,7 +866,7 @@ impl GitManager {
             println!("✅ Pruned {} stale worktree reference(s)", pruned_count);
         }
 
-        Ok(())
+        self.forget_removed_worktree_config(repo_path)
     }
 
     /// Delete a local branch
@@ -548,19 +904,71 @@ impl GitManager {
             .find_remote(remote_name)
             .context("Failed to find remote 'origin'")?;
 
-        // Set up callbacks for authentication
-        let callbacks = self.create_auth_callbacks();
-        let mut push_options = git2::PushOptions::new();
-        push_options.remote_callbacks(callbacks);
-
         // Push empty reference to delete the branch
         let refspec = format!(":refs/heads/{}", branch_name);
-        remote.push(&[&refspec], Some(&mut push_options))?;
+        self.with_remote_auth(remote_name, "push --delete", |callbacks| {
+            let mut push_options = git2::PushOptions::new();
+            push_options.remote_callbacks(callbacks);
+            remote.push(&[&refspec], Some(&mut push_options))
+        })?;
 
         println!("✅ Remote branch '{}/{}' deleted", remote_name, branch_name);
         Ok(())
     }
 
+    /// Branches on `remote` as last fetched, without the remote's prefix;
+    /// only those whose tip is reachable from `merged_into` when given
+    pub fn remote_branches(
+        &self,
+        repo_path: &Path,
+        remote: &str,
+        merged_into: Option<&str>,
+    ) -> Result<Vec<String>> {
+        let prefix = format!("refs/remotes/{}/", remote);
+        let merged = merged_into.map(|target| format!("--merged={}", target));
+        let mut args = vec!["for-each-ref", "--format=%(refname)"];
+        args.extend(merged.as_deref());
+        args.push(&prefix);
+        let output = self.execute_git_command(repo_path, &args)?;
+        Ok(output
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix(prefix.as_str()))
+            .filter(|branch| *branch != "HEAD")
+            .map(String::from)
+            .collect())
+    }
+
+    /// Delete `branches` on `remote` with a single `git push --delete`
+    pub fn delete_remote_branches(
+        &self,
+        repo_path: &Path,
+        remote: &str,
+        branches: &[String],
+    ) -> Result<()> {
+        // One push per branch, so a retry after a partial failure doesn't
+        // trip over the branches that were already deleted
+        let mut failed = Vec::new();
+        for branch in branches {
+            let deleted = retry(&self.retry, "git push --delete", || {
+                let output = self.run_git(repo_path, &["push", remote, "--delete", branch])?;
+                match output.success {
+                    true => Ok(()),
+                    false => Err(anyhow::anyhow!("{}", output.stderr.trim())),
+                }
+            });
+            if let Err(e) = deleted {
+                failed.push(format!("{}: {:#}", branch, e));
+            }
+        }
+        match failed.is_empty() {
+            true => Ok(()),
+            false => Err(anyhow::anyhow!(
+                "Failed to delete remote branches:\n{}",
+                failed.join("\n")
+            )),
+        }
+    }
+
     /// Check if a branch is in use by any worktree
     fn is_branch_in_use_by_worktree(&self, repo: &Repository, branch_name: &str) -> Result<bool> {
         let worktrees = repo.worktrees()?;
@@ -644,37 +1052,42 @@ impl GitManager {
 
     /// List Git worktrees using git worktree list command
     pub fn list_git_worktrees(&self, repo_path: &Path) -> Result<Vec<GitWorktree>> {
+        // -z keeps paths and lock reasons with newlines intact; git before
+        // 2.36 doesn't have it
+        let output = self.run_git(repo_path, &["worktree", "list", "--porcelain", "-z"])?;
+        if output.success {
+            return Ok(parse_worktree_porcelain(&output.stdout, '\0'));
+        }
         let output = self.execute_git_command(repo_path, &["worktree", "list", "--porcelain"])?;
-        let mut worktrees = Vec::new();
-        let mut current_worktree: Option<GitWorktree> = None;
+        Ok(parse_worktree_porcelain(&output, '\n'))
+    }
 
-        for line in output.lines() {
-            if line.starts_with("worktree ") {
-                if let Some(wt) = current_worktree.take() {
-                    worktrees.push(wt);
-                }
-                let path = line.strip_prefix("worktree ").unwrap_or("").to_string();
-                current_worktree = Some(GitWorktree {
-                    path,
-                    branch: String::new(),
-                    commit: String::new(),
-                });
-            } else if line.starts_with("HEAD ") {
-                if let Some(ref mut wt) = current_worktree {
-                    wt.commit = line.strip_prefix("HEAD ").unwrap_or("").to_string();
-                }
-            } else if line.starts_with("branch ") {
-                if let Some(ref mut wt) = current_worktree {
-                    wt.branch = line.strip_prefix("branch ").unwrap_or("").to_string();
-                }
-            }
-        }
+    /// Add a pattern to the shared `info/exclude` so generated files in a
+    /// worktree stay out of `git status`
+    pub fn exclude_untracked(&self, worktree_path: &Path, pattern: &str) -> Result<()> {
+        let repo = self.open_repository(worktree_path)?;
+        // Linked worktrees keep a `commondir` file pointing at the main git dir
+        let git_dir = repo.path().to_path_buf();
+        let common_dir = match std::fs::read_to_string(git_dir.join("commondir")) {
+            Ok(relative) => git_dir.join(relative.trim()),
+            Err(_) => git_dir,
+        };
 
-        if let Some(wt) = current_worktree {
-            worktrees.push(wt);
+        let exclude = common_dir.join("info").join("exclude");
+        let existing = std::fs::read_to_string(&exclude).unwrap_or_default();
+        if existing.lines().any(|line| line.trim() == pattern) {
+            return Ok(());
         }
 
-        Ok(worktrees)
+        std::fs::create_dir_all(common_dir.join("info"))?;
+        let separator = if existing.is_empty() || existing.ends_with('\n') {
+            ""
+        } else {
+            "\n"
+        };
+        std::fs::write(&exclude, format!("{}{}{}\n", existing, separator, pattern))
+            .context("Failed to update info/exclude")?;
+        Ok(())
     }
 
     /// Check if a worktree exists
@@ -684,19 +1097,21 @@ impl GitManager {
 
     /// Fetch all remotes
     pub fn fetch_all(&self, repo: &Repository) -> Result<()> {
-        let mut remote = repo.find_remote("origin")?;
+        self.fetch_remote(repo, "origin")
+    }
+
+    /// Fetch one remote's branches
+    pub fn fetch_remote(&self, repo: &Repository, remote_name: &str) -> Result<()> {
+        let mut remote = repo.find_remote(remote_name)?;
         let refspecs = remote.fetch_refspecs()?;
         let refspecs: Vec<&str> = refspecs.iter().filter_map(|s| s).collect();
 
-        // Create authentication callbacks
-        let callbacks = self.create_auth_callbacks();
-
-        // Create fetch options with authentication
-        let mut fetch_options = git2::FetchOptions::new();
-        fetch_options.remote_callbacks(callbacks);
-
-        remote.fetch(&refspecs, Some(&mut fetch_options), None)?;
-        Ok(())
+        self.with_remote_auth(remote_name, "fetch", |callbacks| {
+            // Create fetch options with authentication
+            let mut fetch_options = git2::FetchOptions::new();
+            fetch_options.remote_callbacks(callbacks);
+            remote.fetch(&refspecs, Some(&mut fetch_options), None)
+        })
     }
 
     /// Check if a branch exists (local or remote)
@@ -709,7 +1124,7 @@ impl GitManager {
 
     /// Get the current branch name for a worktree
     pub fn get_current_branch(&self, repo_path: &Path) -> Result<String> {
-        let repo = Repository::open(repo_path)?;
+        let repo = self.open_repository(repo_path)?;
         let head = repo.head()?;
 
         if let Some(branch_name) = head.shorthand() {
@@ -721,7 +1136,10 @@ impl GitManager {
 
     /// Get worktree status (modified files, commits ahead/behind, etc.)
     pub fn get_worktree_status(&self, repo_path: &Path) -> Result<WorktreeStatus> {
-        let repo = Repository::open(repo_path)?;
+        let _span =
+            tracing::info_span!("git", detail = format!("status ({})", repo_path.display()))
+                .entered();
+        let repo = self.open_repository(repo_path)?;
 
         // Configure status options to match `git status` behavior:
         // - Include untracked files
@@ -747,8 +1165,15 @@ impl GitManager {
             if status_flags.is_wt_modified() || status_flags.is_index_modified() {
                 modified_files.push(file_path);
             } else if status_flags.is_wt_new() || status_flags.is_index_new() {
-                // Check for new/untracked files
-                new_files.push(file_path);
+                // A nested repository shows up as one untracked directory;
+                // its changes are its own, not this worktree's
+                let nested = file_path.ends_with('/')
+                    && repo
+                        .workdir()
+                        .is_some_and(|dir| context::is_repository_root(&dir.join(&file_path)));
+                if !nested {
+                    new_files.push(file_path);
+                }
             } else if status_flags.is_wt_deleted() || status_flags.is_index_deleted() {
                 // Check for deleted files
                 deleted_files.push(file_path);
@@ -774,101 +1199,913 @@ impl GitManager {
         })
     }
 
-    /// Get commits ahead/behind compared to upstream
-    fn get_ahead_behind(&self, repo: &Repository) -> Result<(usize, usize)> {
-        let head = repo.head()?;
-        let head_oid = head.target().context("HEAD has no target")?;
+    /// Read what `get_worktree_status` depends on without walking the
+    /// working tree; take it before computing the status so a change made
+    /// meanwhile makes the cached result miss instead of hiding
+    pub fn status_cache_key(&self, repo_path: &Path) -> Result<StatusCacheKey> {
+        let repo = self.open_repository(repo_path)?;
+        let head = repo.head().ok();
+        let upstream_sha = head
+            .as_ref()
+            .and_then(|head| head.shorthand())
+            .and_then(|name| repo.find_branch(name, BranchType::Local).ok())
+            .and_then(|branch| branch.upstream().ok())
+            .and_then(|upstream| upstream.get().target())
+            .map(|oid| oid.to_string());
+        let index_mtime_ns = std::fs::metadata(repo.path().join("index"))
+            .and_then(|meta| meta.modified())
+            .ok()
+            .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|since| since.as_nanos() as i64);
+
+        Ok(StatusCacheKey {
+            head_sha: head
+                .and_then(|head| head.target())
+                .map(|oid| oid.to_string()),
+            index_mtime_ns,
+            upstream_sha,
+        })
+    }
 
-        // Try to find upstream branch
-        if let Ok(branch) = repo.find_branch(&head.shorthand().unwrap_or("HEAD"), BranchType::Local)
-        {
-            if let Ok(upstream) = branch.upstream() {
-                let upstream_oid = upstream.get().target().context("Upstream has no target")?;
-                let (ahead, behind) = repo.graph_ahead_behind(head_oid, upstream_oid)?;
-                return Ok((ahead, behind));
-            }
-        }
+    /// Count commits the local branch is behind its `origin/<branch>` counterpart
+    pub fn get_behind_origin(&self, repo_path: &Path, branch: &str) -> Result<usize> {
+        let repo = self.open_repository(repo_path)?;
+        let local = repo
+            .find_branch(branch, BranchType::Local)?
+            .get()
+            .target()
+            .context("Local branch has no target")?;
+        let remote = repo
+            .find_branch(&format!("origin/{}", branch), BranchType::Remote)?
+            .get()
+            .target()
+            .context("Remote branch has no target")?;
 
-        Ok((0, 0))
+        let (_ahead, behind) = repo.graph_ahead_behind(local, remote)?;
+        Ok(behind)
     }
 
-    /// Execute git command using system git (for operations not available in git2)
-    pub fn execute_git_command(&self, repo_path: &Path, args: &[&str]) -> Result<String> {
-        let output = Command::new("git")
-            .current_dir(repo_path)
-            .args(args)
-            .output()
-            .context("Failed to execute git command")?;
+    /// Count commits HEAD is behind trunk, preferring `origin/<trunk>` over the local branch
+    pub fn get_behind_trunk(&self, repo_path: &Path, trunk_branch: &str) -> Result<usize> {
+        let repo = self.open_repository(repo_path)?;
+        let head = repo.head()?.target().context("HEAD has no target")?;
+        let trunk = repo
+            .find_branch(&format!("origin/{}", trunk_branch), BranchType::Remote)
+            .or_else(|_| repo.find_branch(trunk_branch, BranchType::Local))?
+            .get()
+            .target()
+            .context("Trunk branch has no target")?;
+
+        let (_ahead, behind) = repo.graph_ahead_behind(head, trunk)?;
+        Ok(behind)
+    }
 
-        if output.status.success() {
-            Ok(String::from_utf8_lossy(&output.stdout).to_string())
-        } else {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            Err(anyhow::anyhow!("Git command failed: {}", stderr))
-        }
+    /// True when the branch tip is reachable from trunk, like `git branch --merged`;
+    /// prefers `origin/<trunk>` over the local branch
+    pub fn is_branch_merged(
+        &self,
+        repo_path: &Path,
+        branch: &str,
+        trunk_branch: &str,
+    ) -> Result<bool> {
+        let repo = self.open_repository(repo_path)?;
+        let tip = repo
+            .find_branch(branch, BranchType::Local)?
+            .get()
+            .target()
+            .context("Branch has no target")?;
+        let trunk = repo
+            .find_branch(&format!("origin/{}", trunk_branch), BranchType::Remote)
+            .or_else(|_| repo.find_branch(trunk_branch, BranchType::Local))?
+            .get()
+            .target()
+            .context("Trunk branch has no target")?;
+
+        Ok(tip == trunk || repo.graph_descendant_of(trunk, tip)?)
     }
 
-    /// Validate PR exists using gh CLI
-    pub fn validate_pr_exists(&self, repo_path: &Path, pr_number: u32) -> Result<bool> {
-        let output = Command::new("gh")
-            .current_dir(repo_path)
-            .args(&["pr", "view", &pr_number.to_string(), "--json", "number"])
-            .output();
+    pub fn is_head_detached(&self, repo_path: &Path) -> Result<bool> {
+        Ok(self.open_repository(repo_path)?.head_detached()?)
+    }
 
-        match output {
-            Ok(output) if output.status.success() => Ok(true),
-            Ok(_) => Ok(false),
-            Err(e) => Err(anyhow::anyhow!(
-                "Failed to validate PR existence (is gh CLI installed?): {}",
-                e
-            )),
+    /// True while a merge, rebase or cherry-pick has left unresolved conflicts
+    pub fn has_merge_conflicts(&self, repo_path: &Path) -> Result<bool> {
+        Ok(self.open_repository(repo_path)?.index()?.has_conflicts())
+    }
+
+    /// True while a rebase has stopped in the worktree at `repo_path`
+    pub fn rebase_in_progress(&self, repo_path: &Path) -> Result<bool> {
+        Ok(matches!(
+            self.open_repository(repo_path)?.state(),
+            git2::RepositoryState::Rebase
+                | git2::RepositoryState::RebaseInteractive
+                | git2::RepositoryState::RebaseMerge
+                | git2::RepositoryState::ApplyMailboxOrRebase
+        ))
+    }
+
+    /// Files with unresolved conflicts
+    pub fn conflicted_files(&self, repo_path: &Path) -> Result<Vec<String>> {
+        let index = self.open_repository(repo_path)?.index()?;
+        let mut files = Vec::new();
+        for conflict in index.conflicts()? {
+            let conflict = conflict?;
+            if let Some(entry) = conflict.our.or(conflict.their).or(conflict.ancestor) {
+                files.push(String::from_utf8_lossy(&entry.path).to_string());
+            }
         }
+        Ok(files)
     }
 
-    /// Checkout a PR using gh cli and create worktree
-    pub fn checkout_pr(
-        &self,
-        repo_path: &Path,
-        pr_number: u32,
-        worktree_path: &Path,
-    ) -> Result<()> {
-        // Validate PR exists first
-        if !self.validate_pr_exists(repo_path, pr_number)? {
+    /// True while a merge stopped on conflicts is waiting to be concluded
+    pub fn merge_in_progress(&self, repo_path: &Path) -> Result<bool> {
+        Ok(self.open_repository(repo_path)?.state() == git2::RepositoryState::Merge)
+    }
+
+    /// Commit a merge whose conflicts are resolved, with the prepared
+    /// message; system git signs it like any other merge commit
+    pub fn conclude_merge(&self, repo_path: &Path) -> Result<()> {
+        let files = self.conflicted_files(repo_path)?;
+        if !files.is_empty() {
+            return Err(ImiError::MergeConflicts { files }.into());
+        }
+        let output = self.run_git(repo_path, &["commit", "--no-edit"])?;
+        if !output.success {
             return Err(anyhow::anyhow!(
-                "PR #{} does not exist in this repository. Check the PR number and try again.",
-                pr_number
+                "Failed to commit the merge: {}",
+                first_line(&output.stderr)
             ));
         }
+        Ok(())
+    }
 
-        if let Some(parent) = worktree_path.parent() {
-            std::fs::create_dir_all(parent)
-                .context("Failed to create parent directory for PR worktree")?;
+    /// Roll back a merge in progress to the commit it started from
+    pub fn abort_merge(&self, repo_path: &Path) -> Result<()> {
+        self.execute_git_command(repo_path, &["merge", "--abort"])
+            .map(|_| ())
+            .context("Failed to abort the merge")
+    }
+
+    /// Stage conflicted files an editor left without conflict markers
+    pub fn stage_resolved(&self, repo_path: &Path, files: &[String]) -> Result<Vec<String>> {
+        let resolved: Vec<String> = files
+            .iter()
+            .filter(|file| {
+                std::fs::read_to_string(repo_path.join(file))
+                    .is_ok_and(|contents| !contents.lines().any(|l| l.starts_with("<<<<<<< ")))
+            })
+            .cloned()
+            .collect();
+        if !resolved.is_empty() {
+            let mut args = vec!["add", "--"];
+            args.extend(resolved.iter().map(String::as_str));
+            self.execute_git_command(repo_path, &args)?;
         }
+        Ok(resolved)
+    }
 
-        // Use gh pr checkout to create a worktree on the underlying PR branch and set tracking.
-        let output = Command::new("gh")
-            .current_dir(repo_path)
-            .args(&[
-                "pr",
-                "checkout",
-                &pr_number.to_string(),
-                "--worktree",
-                worktree_path.to_str().unwrap(),
-            ])
-            .output()
-            .context("Failed to checkout PR with gh CLI")?;
+    /// The `merge.tool` configured for the repository, if any
+    pub fn merge_tool(&self, repo_path: &Path) -> Option<String> {
+        self.open_repository(repo_path)
+            .ok()?
+            .config()
+            .ok()?
+            .get_string("merge.tool")
+            .ok()
+            .filter(|tool| !tool.trim().is_empty())
+    }
 
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(anyhow::anyhow!("Failed to checkout PR: {}", stderr));
+    /// The hooks directory every worktree of the repository runs, honouring
+    /// `core.hooksPath`
+    pub fn hooks_dir(&self, repo_path: &Path) -> Result<PathBuf> {
+        let dir = self.execute_git_command(repo_path, &["rev-parse", "--git-path", "hooks"])?;
+        Ok(repo_path.join(dir.trim()))
+    }
+
+    /// Rebase the branch checked out at `repo_path` onto `onto`. A rebase
+    /// that stops on conflicts is left for the user; any other failure is
+    /// rolled back.
+    pub fn rebase_onto(&self, repo_path: &Path, onto: &str) -> Result<RebaseOutcome> {
+        let before = self.get_head_commit(repo_path)?.0;
+        let output = self.run_git(repo_path, &["rebase", onto])?;
+        if output.success {
+            let after = self.get_head_commit(repo_path)?.0;
+            return Ok(match before == after {
+                true => RebaseOutcome::UpToDate,
+                false => RebaseOutcome::Rebased,
+            });
         }
+        if self.rebase_in_progress(repo_path)? {
+            let files = self.conflicted_files(repo_path)?;
+            if !files.is_empty() {
+                return Ok(RebaseOutcome::Conflicted(files));
+            }
+            let _ = self.run_git(repo_path, &["rebase", "--abort"]);
+        }
+        Ok(RebaseOutcome::Failed(first_line(&output.stderr)))
+    }
 
-        // Best-effort: ensure upstream is set when the PR branch is on origin.
-        if let Ok(branch_name) = self.get_current_branch(worktree_path) {
-            let has_upstream = self
-                .execute_git_command(
-                    worktree_path,
-                    &[
+    /// Carry on with a stopped rebase once its conflicts are resolved
+    pub fn continue_rebase(&self, repo_path: &Path) -> Result<RebaseOutcome> {
+        let files = self.conflicted_files(repo_path)?;
+        if !files.is_empty() {
+            return Ok(RebaseOutcome::Conflicted(files));
+        }
+        let output = self.run_git(
+            repo_path,
+            &["-c", "core.editor=true", "rebase", "--continue"],
+        )?;
+        if output.success {
+            return Ok(RebaseOutcome::Rebased);
+        }
+        let files = self.conflicted_files(repo_path)?;
+        if !files.is_empty() {
+            return Ok(RebaseOutcome::Conflicted(files));
+        }
+        // e.g. a commit that became empty; the user decides what to do
+        Ok(RebaseOutcome::Stopped(first_line(
+            if output.stderr.trim().is_empty() {
+                &output.stdout
+            } else {
+                &output.stderr
+            },
+        )))
+    }
+
+    /// Oldest modification time among uncommitted files, approximating how
+    /// long the worktree has been dirty; None when clean
+    pub fn get_oldest_change_time(
+        &self,
+        repo_path: &Path,
+    ) -> Result<Option<chrono::DateTime<chrono::Utc>>> {
+        let status = self.get_worktree_status(repo_path)?;
+        Ok(status
+            .modified_files
+            .iter()
+            .chain(&status.new_files)
+            .filter_map(|file| std::fs::metadata(repo_path.join(file)).ok())
+            .filter_map(|meta| meta.modified().ok())
+            .min()
+            .map(Into::into))
+    }
+
+    /// When the repository was last fetched, based on FETCH_HEAD's mtime
+    pub fn get_last_fetch_time(&self, repo_path: &Path) -> Option<chrono::DateTime<chrono::Utc>> {
+        let repo = self.open_repository(repo_path).ok()?;
+        let modified = std::fs::metadata(repo.path().join("FETCH_HEAD"))
+            .ok()?
+            .modified()
+            .ok()?;
+        Some(modified.into())
+    }
+
+    /// Commit time of HEAD in the given worktree
+    pub fn get_head_commit_time(&self, repo_path: &Path) -> Result<chrono::DateTime<chrono::Utc>> {
+        let repo = self.open_repository(repo_path)?;
+        let commit = repo.head()?.peel_to_commit()?;
+        chrono::DateTime::from_timestamp(commit.time().seconds(), 0)
+            .context("HEAD commit has an invalid timestamp")
+    }
+
+    /// HEAD commit id and summary line in the given worktree
+    pub fn get_head_commit(&self, repo_path: &Path) -> Result<(String, String)> {
+        let repo = self.open_repository(repo_path)?;
+        let commit = repo.head()?.peel_to_commit()?;
+        Ok((
+            commit.id().to_string(),
+            commit.summary().unwrap_or_default().to_string(),
+        ))
+    }
+
+    /// HEAD's divergence from the branch's upstream; None without one
+    pub fn get_upstream_divergence(&self, repo_path: &Path) -> Result<Option<Divergence>> {
+        let repo = self.open_repository(repo_path)?;
+        let head = repo.head()?;
+        let head_oid = head.target().context("HEAD has no target")?;
+        let Ok(branch) = repo.find_branch(head.shorthand().unwrap_or("HEAD"), BranchType::Local)
+        else {
+            return Ok(None);
+        };
+        let Ok(upstream) = branch.upstream() else {
+            return Ok(None);
+        };
+
+        let upstream_oid = upstream.get().target().context("Upstream has no target")?;
+        let (ahead, behind) = repo.graph_ahead_behind(head_oid, upstream_oid)?;
+        Ok(Some(Divergence {
+            reference: upstream.name()?.unwrap_or_default().to_string(),
+            ahead,
+            behind,
+        }))
+    }
+
+    /// HEAD's divergence from `reference`, preferring `origin/<reference>`
+    /// so a stale local trunk doesn't hide new commits
+    pub fn get_divergence(&self, repo_path: &Path, reference: &str) -> Result<Divergence> {
+        let repo = self.open_repository(repo_path)?;
+        let head = repo.head()?.target().context("HEAD has no target")?;
+        let (reference, object) = [format!("origin/{}", reference), reference.to_string()]
+            .into_iter()
+            .find_map(|name| {
+                repo.revparse_single(&name)
+                    .ok()
+                    .map(|object| (name, object))
+            })
+            .with_context(|| format!("Cannot resolve '{}'", reference))?;
+
+        let target = object.peel_to_commit()?.id();
+        let (ahead, behind) = repo.graph_ahead_behind(head, target)?;
+        Ok(Divergence {
+            reference,
+            ahead,
+            behind,
+        })
+    }
+
+    /// The newest `limit` commits reachable from HEAD
+    pub fn recent_commits(&self, repo_path: &Path, limit: usize) -> Result<Vec<CommitSummary>> {
+        let repo = self.open_repository(repo_path)?;
+        let mut walk = repo.revwalk()?;
+        walk.push_head()?;
+        walk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
+
+        walk.take(limit)
+            .map(|oid| Ok(CommitSummary::from(&repo.find_commit(oid?)?)))
+            .collect()
+    }
+
+    /// The commit `rev` names, e.g. `refs/bisect/bad`
+    pub fn commit_summary(&self, repo_path: &Path, rev: &str) -> Result<CommitSummary> {
+        let repo = self.open_repository(repo_path)?;
+        let commit = repo
+            .revparse_single(rev)
+            .and_then(|object| object.peel_to_commit())
+            .with_context(|| format!("Cannot resolve '{}' to a commit", rev))?;
+        Ok(CommitSummary::from(&commit))
+    }
+
+    /// Move a review worktree to its pull request's latest head, fetched
+    /// into `refs/imi/pr/<n>`. That ref's previous value is the head last
+    /// synced (the checked-out HEAD on the first sync), which the report is
+    /// relative to. Local changes or commits block the update unless `force`.
+    pub fn sync_pr_head(
+        &self,
+        worktree_path: &Path,
+        pr_number: u32,
+        force: bool,
+    ) -> Result<PrSync> {
+        let rev = |name: &str| -> Result<String> {
+            Ok(self
+                .execute_git_command(worktree_path, &["rev-parse", "--verify", "-q", name])?
+                .trim()
+                .to_string())
+        };
+        let is_ancestor = |older: &str, newer: &str| {
+            self.execute_git_command(
+                worktree_path,
+                &["merge-base", "--is-ancestor", older, newer],
+            )
+            .is_ok()
+        };
+        let lines = |args: &[&str]| -> Result<Vec<String>> {
+            Ok(self
+                .execute_git_command(worktree_path, args)?
+                .lines()
+                .map(String::from)
+                .collect())
+        };
+
+        let pr_ref = format!("refs/imi/pr/{}", pr_number);
+        let head = rev("HEAD")?;
+        let reviewed = rev(&pr_ref).unwrap_or_else(|_| head.clone());
+
+        if !force {
+            let changes = self.execute_git_command(
+                worktree_path,
+                &["status", "--porcelain", "--untracked-files=no"],
+            )?;
+            if !changes.trim().is_empty() {
+                anyhow::bail!(
+                    "{} has uncommitted changes; commit or stash them, or pass --force to discard them",
+                    worktree_path.display()
+                );
+            }
+            let local = lines(&["rev-list", &format!("{}..HEAD", reviewed)])?.len();
+            if local > 0 {
+                anyhow::bail!(
+                    "{} has {} local commit(s) not in PR #{}; pass --force to drop them",
+                    worktree_path.display(),
+                    local,
+                    pr_number
+                );
+            }
+        }
+
+        self.execute_git_command(
+            worktree_path,
+            &[
+                "fetch",
+                "--quiet",
+                "origin",
+                &format!("+refs/pull/{}/head:{}", pr_number, pr_ref),
+            ],
+        )
+        .with_context(|| format!("Failed to fetch the head of PR #{}", pr_number))?;
+        let latest = rev(&pr_ref)?;
+
+        let force_pushed = reviewed != latest && !is_ancestor(&reviewed, &latest);
+        let range = format!("{}..{}", reviewed, latest);
+        let sync = PrSync {
+            pr_number,
+            new_commits: lines(&["log", "--format=%h %s", &range])?,
+            dropped_commits: match force_pushed {
+                true => lines(&["rev-list", &format!("{}..{}", latest, reviewed)])?.len(),
+                false => 0,
+            },
+            changed_files: lines(&["diff", "--name-status", &reviewed, &latest])?,
+            force_pushed,
+            reviewed_head: reviewed,
+            head: latest,
+        };
+
+        if head != sync.head {
+            let args: &[&str] = match is_ancestor(&head, &sync.head) {
+                true => &["merge", "--ff-only", "--quiet", &sync.head],
+                false => &["reset", "--hard", "--quiet", &sync.head],
+            };
+            self.execute_git_command(worktree_path, args)
+                .context("Failed to move the review worktree to the PR head")?;
+        }
+        Ok(sync)
+    }
+
+    /// Apply a patch to a worktree: mailbox patches from `git format-patch`
+    /// become commits through `git am --3way`, plain diffs are staged with
+    /// `git apply`, retried as a 3-way merge when they don't apply cleanly,
+    /// and committed when `commit_message` is given
+    pub fn apply_patch(
+        &self,
+        worktree_path: &Path,
+        patch: &[u8],
+        commit_message: Option<&str>,
+    ) -> Result<PatchApply> {
+        let git_path = self.execute_git_command(
+            worktree_path,
+            &["rev-parse", "--git-path", "imi-apply.patch"],
+        )?;
+        let patch_file = worktree_path.join(git_path.trim());
+        std::fs::write(&patch_file, patch)
+            .context(format!("Failed to write {}", patch_file.display()))?;
+        let result = self.apply_patch_file(worktree_path, &patch_file, patch, commit_message);
+        let _ = std::fs::remove_file(&patch_file);
+        result
+    }
+
+    fn apply_patch_file(
+        &self,
+        worktree_path: &Path,
+        patch_file: &Path,
+        patch: &[u8],
+        commit_message: Option<&str>,
+    ) -> Result<PatchApply> {
+        let file = patch_file.to_string_lossy();
+        let lines = |args: &[&str]| -> Result<Vec<String>> {
+            Ok(self
+                .execute_git_command(worktree_path, args)?
+                .lines()
+                .map(String::from)
+                .collect())
+        };
+        let start = self
+            .execute_git_command(worktree_path, &["rev-parse", "HEAD"])?
+            .trim()
+            .to_string();
+
+        let method = if is_mailbox(patch) {
+            self.execute_git_command(worktree_path, &["am", "--3way", "--quiet", &file])
+                .context(
+                    "Patch doesn't apply; resolve the conflicts and run 'git am --continue', or 'git am --abort'",
+                )?;
+            "am"
+        } else if self
+            .execute_git_command(worktree_path, &["apply", "--index", &file])
+            .is_ok()
+        {
+            "apply"
+        } else {
+            self.execute_git_command(worktree_path, &["apply", "--3way", &file])
+                .context(
+                    "Patch doesn't apply, even as a 3-way merge; resolve the conflicts left in the worktree",
+                )?;
+            "apply --3way"
+        };
+
+        let files = match method {
+            "am" => lines(&["diff", "--name-only", &start, "HEAD"])?,
+            _ => lines(&["diff", "--cached", "--name-only"])?,
+        };
+        if files.is_empty() && method != "am" {
+            anyhow::bail!("Patch made no changes");
+        }
+        if let (Some(message), false) = (commit_message, method == "am") {
+            self.execute_git_command(worktree_path, &["commit", "--quiet", "-m", message])
+                .context("Failed to commit the applied patch")?;
+        }
+
+        Ok(PatchApply {
+            method: method.to_string(),
+            commits: lines(&[
+                "log",
+                "--reverse",
+                "--format=%h %s",
+                &format!("{}..HEAD", start),
+            ])?,
+            files,
+        })
+    }
+
+    /// Get commits ahead/behind compared to upstream
+    fn get_ahead_behind(&self, repo: &Repository) -> Result<(usize, usize)> {
+        let head = repo.head()?;
+        let head_oid = head.target().context("HEAD has no target")?;
+
+        // Try to find upstream branch
+        if let Ok(branch) = repo.find_branch(&head.shorthand().unwrap_or("HEAD"), BranchType::Local)
+        {
+            if let Ok(upstream) = branch.upstream() {
+                let upstream_oid = upstream.get().target().context("Upstream has no target")?;
+                let (ahead, behind) = repo.graph_ahead_behind(head_oid, upstream_oid)?;
+                return Ok((ahead, behind));
+            }
+        }
+
+        Ok((0, 0))
+    }
+
+    /// Execute git command using system git (for operations not available in git2)
+    pub fn execute_git_command(&self, repo_path: &Path, args: &[&str]) -> Result<String> {
+        let output = self.run_git(repo_path, args)?;
+        if output.success {
+            Ok(output.stdout)
+        } else {
+            Err(anyhow::anyhow!("Git command failed: {}", output.stderr))
+        }
+    }
+
+    /// Run a system git command, leaving a failing exit to the caller
+    pub fn run_git(&self, repo_path: &Path, args: &[&str]) -> Result<GitOutput> {
+        let detail = || format!("git {} ({})", args.join(" "), repo_path.display());
+        let _span = match args.first() {
+            Some(&("fetch" | "pull" | "push" | "clone" | "ls-remote")) => {
+                tracing::info_span!("network", detail = detail())
+            }
+            _ => tracing::info_span!("git", detail = detail()),
+        }
+        .entered();
+        self.backend().run(repo_path, args)
+    }
+
+    /// Run a system git command on the terminal; returns its exit code
+    pub fn run_git_attached(&self, repo_path: &Path, args: &[&str]) -> Result<i32> {
+        self.backend().run_attached(repo_path, args)
+    }
+
+    /// Open the repository at `path` through the backend
+    pub fn open_repository(&self, path: &Path) -> Result<Repository, git2::Error> {
+        self.backend().open(path)
+    }
+
+    /// Open the repository `path` is in through the backend
+    pub fn discover_repository(&self, path: &Path) -> Result<Repository, git2::Error> {
+        self.backend().discover(path)
+    }
+
+    fn backend(&self) -> &dyn GitBackend {
+        match &self.backend {
+            Some(backend) => backend.as_ref(),
+            None => &SystemGit,
+        }
+    }
+
+    /// Turn a shallow, partial or single-branch clone into a full one and
+    /// fetch what it was missing; returns what was converted (empty when the
+    /// clone was already complete)
+    pub fn unshallow(&self, repo_path: &Path) -> Result<Vec<&'static str>> {
+        let config = |key: &str| {
+            self.execute_git_command(repo_path, &["config", "--get-all", key])
+                .map(|value| value.trim().to_string())
+                .unwrap_or_default()
+        };
+        let mut converted = Vec::new();
+        let mut fetch = vec!["fetch"];
+
+        let all_branches = "+refs/heads/*:refs/remotes/origin/*";
+        if config("remote.origin.fetch") != all_branches {
+            self.execute_git_command(
+                repo_path,
+                &[
+                    "config",
+                    "--replace-all",
+                    "remote.origin.fetch",
+                    all_branches,
+                ],
+            )?;
+            converted.push("single branch");
+        }
+        if !config("remote.origin.partialclonefilter").is_empty() {
+            self.execute_git_command(
+                repo_path,
+                &["config", "--unset", "remote.origin.partialclonefilter"],
+            )?;
+            fetch.push("--refetch");
+            converted.push("partial clone");
+        }
+        let shallow =
+            self.execute_git_command(repo_path, &["rev-parse", "--is-shallow-repository"])?;
+        if shallow.trim() == "true" {
+            fetch.push("--unshallow");
+            converted.push("shallow history");
+        }
+
+        if !converted.is_empty() {
+            fetch.push("origin");
+            retry(&self.retry, "git fetch origin", || {
+                self.execute_git_command(repo_path, &fetch)
+            })?;
+            // Objects are all local now; stop treating origin as a promisor
+            if fetch.contains(&"--refetch") {
+                self.execute_git_command(
+                    repo_path,
+                    &["config", "remote.origin.promisor", "false"],
+                )?;
+            }
+        }
+        Ok(converted)
+    }
+
+    /// Set `key` for one worktree only. `extensions.worktreeConfig` would make
+    /// the repository unreadable to libgit2, so the setting goes into a file
+    /// in the worktree's git dir, included on the condition of that git dir.
+    /// The include is dropped with the git dir, by [`Self::prune_worktrees`].
+    pub fn set_worktree_config(&self, worktree_path: &Path, key: &str, value: &str) -> Result<()> {
+        let git_dir = self
+            .execute_git_command(worktree_path, &["rev-parse", "--absolute-git-dir"])?
+            .trim()
+            .to_string();
+        let include = Path::new(&git_dir).join("imi.gitconfig");
+        let include = include.to_string_lossy();
+
+        self.execute_git_command(worktree_path, &["config", "--file", &include, key, value])?;
+        self.execute_git_command(
+            worktree_path,
+            &[
+                "config",
+                &format!("includeIf.gitdir:{}.path", git_dir),
+                &include,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Remove the includes [`Self::set_worktree_config`] added for worktrees
+    /// whose git dir is gone, so they don't pile up in the shared config or
+    /// apply to a later worktree with the same name
+    pub fn forget_removed_worktree_config(&self, repo_path: &Path) -> Result<()> {
+        // Exits 1 when there are none
+        let output = self.run_git(
+            repo_path,
+            &[
+                "config",
+                "--local",
+                "-z",
+                "--get-regexp",
+                r"^includeif\.gitdir:",
+            ],
+        )?;
+        if !output.success {
+            return Ok(());
+        }
+        for entry in output.stdout.split('\0').filter(|entry| !entry.is_empty()) {
+            let Some((key, include)) = entry.split_once('\n') else {
+                continue;
+            };
+            let include = Path::new(include);
+            let stale = include.file_name() == Some(std::ffi::OsStr::new("imi.gitconfig"))
+                && include.parent().is_some_and(|git_dir| !git_dir.exists());
+            if stale {
+                self.execute_git_command(repo_path, &["config", "--local", "--unset-all", key])?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Validate PR exists using gh CLI
+    pub fn validate_pr_exists(&self, repo_path: &Path, pr_number: u32) -> Result<bool> {
+        let pr = pr_number.to_string();
+        let output = self.run_gh(repo_path, &["pr", "view", &pr, "--json", "number"]);
+
+        match output {
+            Ok(output) if output.status.success() => Ok(true),
+            Ok(_) => Ok(false),
+            Err(e) => Err(anyhow::anyhow!(
+                "Failed to validate PR existence (is gh CLI installed?): {}",
+                e
+            )),
+        }
+    }
+
+    /// Checkout a PR using gh cli and create worktree
+    pub fn checkout_pr(
+        &self,
+        repo_path: &Path,
+        pr_number: u32,
+        worktree_path: &Path,
+    ) -> Result<()> {
+        // Validate PR exists first
+        if !self.validate_pr_exists(repo_path, pr_number)? {
+            return Err(anyhow::anyhow!(
+                "PR #{} does not exist in this repository. Check the PR number and try again.",
+                pr_number
+            ));
+        }
+
+        if let Some(parent) = worktree_path.parent() {
+            std::fs::create_dir_all(parent)
+                .context("Failed to create parent directory for PR worktree")?;
+        }
+
+        // Use gh pr checkout to create a worktree on the underlying PR branch and set tracking.
+        let pr = pr_number.to_string();
+        let output = self
+            .run_gh(
+                repo_path,
+                &[
+                    "pr",
+                    "checkout",
+                    &pr,
+                    "--worktree",
+                    worktree_path.to_str().unwrap(),
+                ],
+            )
+            .context("Failed to checkout PR with gh CLI")?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(anyhow::anyhow!("Failed to checkout PR: {}", stderr));
+        }
+
+        self.track_origin_branch(repo_path, worktree_path);
+        Ok(())
+    }
+
+    /// Check out PR `pr_number` like [`checkout_pr`](Self::checkout_pr), but
+    /// with a cone-mode sparse checkout of only `dirs` (plus the files at
+    /// the root), so files outside them are never written
+    pub fn checkout_pr_sparse(
+        &self,
+        repo_path: &Path,
+        pr_number: u32,
+        worktree_path: &Path,
+        dirs: &[String],
+    ) -> Result<()> {
+        if !self.validate_pr_exists(repo_path, pr_number)? {
+            return Err(anyhow::anyhow!(
+                "PR #{} does not exist in this repository. Check the PR number and try again.",
+                pr_number
+            ));
+        }
+
+        if let Some(parent) = worktree_path.parent() {
+            std::fs::create_dir_all(parent)
+                .context("Failed to create parent directory for PR worktree")?;
+        }
+
+        let view = self
+            .pr_view(repo_path, pr_number, &["headRefName"])
+            .context("Failed to look up the PR's branch")?;
+        let branch_name = view["headRefName"]
+            .as_str()
+            .filter(|name| !name.is_empty())
+            .context("gh reported no head branch for the PR")?
+            .to_string();
+
+        self.add_sparse_pr_worktree(repo_path, pr_number, &branch_name, worktree_path, dirs)?;
+        self.track_origin_branch(repo_path, worktree_path);
+        Ok(())
+    }
+
+    /// `gh pr view --json <fields>` for PR `pr_number` of the repository at
+    /// `repo_path`
+    pub fn pr_view(
+        &self,
+        repo_path: &Path,
+        pr_number: u32,
+        fields: &[&str],
+    ) -> Result<serde_json::Value> {
+        let pr = pr_number.to_string();
+        let fields = fields.join(",");
+        let output = self
+            .run_gh(repo_path, &["pr", "view", &pr, "--json", &fields])
+            .context("Failed to run gh pr view")?;
+        if !output.status.success() {
+            return Err(anyhow::anyhow!(
+                "gh pr view {} failed: {}",
+                pr,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        serde_json::from_slice(&output.stdout).context("Failed to parse gh pr view output")
+    }
+
+    /// The `limit` most recently merged PRs into `base`, as
+    /// `gh pr list --json <fields>` reports them
+    pub fn merged_prs(
+        &self,
+        repo_path: &Path,
+        base: &str,
+        limit: usize,
+        fields: &[&str],
+    ) -> Result<serde_json::Value> {
+        let limit = limit.to_string();
+        let fields = fields.join(",");
+        let args = [
+            "pr", "list", "--state", "merged", "--base", base, "--limit", &limit, "--json", &fields,
+        ];
+        let output = self
+            .run_gh(repo_path, &args)
+            .context("Failed to run gh pr list")?;
+        if !output.status.success() {
+            return Err(anyhow::anyhow!(
+                "gh pr list failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        serde_json::from_slice(&output.stdout).context("Failed to parse gh pr list output")
+    }
+
+    /// Add a worktree at PR `pr_number`'s head on `branch_name` (`pr/<n>`
+    /// when a local branch already has that name), checking out only `dirs`
+    fn add_sparse_pr_worktree(
+        &self,
+        repo_path: &Path,
+        pr_number: u32,
+        branch_name: &str,
+        worktree_path: &Path,
+        dirs: &[String],
+    ) -> Result<()> {
+        // pull/<n>/head also has the commits of PRs from forks
+        self.execute_git_command(
+            repo_path,
+            &["fetch", "origin", &format!("pull/{}/head", pr_number)],
+        )
+        .context("Failed to fetch the PR head")?;
+        let taken = self
+            .execute_git_command(
+                repo_path,
+                &[
+                    "show-ref",
+                    "--verify",
+                    "-q",
+                    &format!("refs/heads/{}", branch_name),
+                ],
+            )
+            .is_ok();
+        let branch_name = match taken {
+            true => format!("pr/{}", pr_number),
+            false => branch_name.to_string(),
+        };
+        self.execute_git_command(
+            repo_path,
+            &[
+                "worktree",
+                "add",
+                "--no-checkout",
+                "-b",
+                &branch_name,
+                &worktree_path.to_string_lossy(),
+                "FETCH_HEAD",
+            ],
+        )?;
+
+        // Per-worktree, so trunk and the other worktrees stay complete
+        self.set_worktree_config(worktree_path, "core.sparseCheckout", "true")?;
+        self.set_worktree_config(worktree_path, "core.sparseCheckoutCone", "true")?;
+        let mut args = vec!["sparse-checkout", "set", "--cone"];
+        args.extend(dirs.iter().map(String::as_str));
+        self.execute_git_command(worktree_path, &args)?;
+        self.execute_git_command(worktree_path, &["read-tree", "-mu", "HEAD"])
+            .context("Failed to check out the sparse worktree")?;
+        Ok(())
+    }
+
+    /// Best-effort: track `origin/<branch>` when the worktree's branch is on
+    /// origin and has no upstream yet
+    fn track_origin_branch(&self, repo_path: &Path, worktree_path: &Path) {
+        if let Ok(branch_name) = self.get_current_branch(worktree_path) {
+            let has_upstream = self
+                .execute_git_command(
+                    worktree_path,
+                    &[
                         "rev-parse",
                         "--abbrev-ref",
                         "--symbolic-full-name",
@@ -894,8 +2131,6 @@ impl GitManager {
                 }
             }
         }
-
-        Ok(())
     }
 
     /// Merge a branch into the default branch (typically trunk-main)
@@ -926,9 +2161,9 @@ impl GitManager {
             .find_branch(source_branch, BranchType::Local)
             .context(format!("Source branch '{}' not found", source_branch))?;
 
-        // Get the annotated commit for the source branch
         let source_commit = source_branch_ref.get().peel_to_commit()?;
-        let annotated_commit = repo.find_annotated_commit(source_commit.id())?;
+        // Annotated by branch so a conflicted merge gets the usual "Merge branch" message
+        let annotated_commit = repo.reference_to_annotated_commit(source_branch_ref.get())?;
 
         // Perform merge analysis
         let (merge_analysis, _merge_preference) = repo.merge_analysis(&[&annotated_commit])?;
@@ -954,18 +2189,30 @@ impl GitManager {
             repo.checkout_head(Some(CheckoutBuilder::new().force()))?;
 
             println!("{} Fast-forward merge completed", "✅".bright_green());
+        } else if merge_analysis.is_normal() && commit_signing_enabled(repo) {
+            println!("{} Performing signed merge", "🔀".bright_cyan());
+
+            let workdir = repo
+                .workdir()
+                .context("Repository has no working directory")?;
+            let commit_message = format!("Merge branch '{}'", source_branch);
+            self.merge_with_git_cli(workdir, source_branch, &commit_message)?;
+
+            println!("{} Signed merge commit created", "✅".bright_green());
         } else if merge_analysis.is_normal() {
             println!("{} Performing normal merge", "🔀".bright_cyan());
 
             // Perform normal merge
             repo.merge(&[&annotated_commit], None, None)?;
 
-            // Check if there are conflicts
+            // Conflicts leave the merge in progress for the caller to resolve
             let mut index = repo.index()?;
             if index.has_conflicts() {
-                return Err(anyhow::anyhow!(
-                    "Merge conflicts detected. Please resolve conflicts manually in the trunk worktree."
-                ));
+                let workdir = repo
+                    .workdir()
+                    .context("Repository has no working directory")?;
+                let files = self.conflicted_files(workdir)?;
+                return Err(ImiError::MergeConflicts { files }.into());
             }
 
             // Create merge commit
@@ -997,6 +2244,68 @@ impl GitManager {
         Ok(())
     }
 
+    /// Merge with system git, which honors the user's signing setup
+    /// (`user.signingkey`, `gpg.format` openpgp/ssh/x509, `gpg.program`).
+    /// A merge whose commit can't be signed is aborted, leaving trunk as it was.
+    fn merge_with_git_cli(&self, workdir: &Path, source_branch: &str, message: &str) -> Result<()> {
+        let output = self
+            .run_git(
+                workdir,
+                &[
+                    "merge",
+                    "--no-ff",
+                    "--no-edit",
+                    "-m",
+                    message,
+                    source_branch,
+                ],
+            )
+            .context("Failed to execute git merge")?;
+        if output.success {
+            return Ok(());
+        }
+
+        let stderr = output.stderr;
+        if output.stdout.contains("CONFLICT") {
+            let files = self.conflicted_files(workdir)?;
+            return Err(ImiError::MergeConflicts { files }.into());
+        }
+
+        let _ = self.run_git(workdir, &["merge", "--abort"]);
+
+        let lower = stderr.to_lowercase();
+        if lower.contains("sign") || lower.contains("failed to write commit object") {
+            let message = stderr
+                .lines()
+                .map(|line| line.trim())
+                .find(|line| !line.is_empty())
+                .unwrap_or("git could not sign the commit")
+                .to_string();
+            return Err(ImiError::CommitSigningFailed { message }.into());
+        }
+        Err(anyhow::anyhow!("git merge failed: {}", stderr.trim()))
+    }
+
+    /// Push a worktree's branch to `remote` and make it the upstream, like
+    /// `git push -u`
+    pub fn publish_branch(&self, worktree_path: &Path, remote: &str, branch: &str) -> Result<()> {
+        let repo = self.open_repository(worktree_path)?;
+        let mut origin = repo
+            .find_remote(remote)
+            .context(format!("Failed to find remote '{}'", remote))?;
+        let refspec = format!("refs/heads/{}:refs/heads/{}", branch, branch);
+        self.with_remote_auth(remote, "push", |callbacks| {
+            let mut push_options = git2::PushOptions::new();
+            push_options.remote_callbacks(callbacks);
+            origin.push(&[&refspec], Some(&mut push_options))
+        })?;
+
+        repo.find_branch(branch, BranchType::Local)?
+            .set_upstream(Some(&format!("{}/{}", remote, branch)))
+            .context("Pushed, but failed to set the upstream branch")?;
+        Ok(())
+    }
+
     /// Push changes to remote
     pub fn push_to_remote(&self, repo: &Repository, branch_name: &str) -> Result<()> {
         use colored::*;
@@ -1011,14 +2320,13 @@ impl GitManager {
             .find_remote("origin")
             .context("Failed to find remote 'origin'")?;
 
-        // Set up callbacks for authentication
-        let callbacks = self.create_auth_callbacks();
-        let mut push_options = git2::PushOptions::new();
-        push_options.remote_callbacks(callbacks);
-
         // Push the branch
         let refspec = format!("refs/heads/{}:refs/heads/{}", branch_name, branch_name);
-        remote.push(&[&refspec], Some(&mut push_options))?;
+        self.with_remote_auth("origin", "push", |callbacks| {
+            let mut push_options = git2::PushOptions::new();
+            push_options.remote_callbacks(callbacks);
+            remote.push(&[&refspec], Some(&mut push_options))
+        })?;
 
         println!("{} Successfully pushed to remote", "✅".bright_green());
 
@@ -1026,7 +2334,46 @@ impl GitManager {
     }
 }
 
-#[derive(Debug, Clone)]
+/// Map a libgit2 remote failure onto an auth or network error with a useful hint
+fn classify_remote_error(err: git2::Error, remote: &str, tried: &[String]) -> anyhow::Error {
+    use git2::{ErrorClass, ErrorCode};
+
+    let message = err.message().to_lowercase();
+    let is_auth = err.code() == ErrorCode::Auth
+        || err.code() == ErrorCode::Certificate
+        || message.contains("authentication")
+        || message.contains("credentials")
+        || message.contains("permission denied");
+    let is_network = matches!(err.class(), ErrorClass::Net | ErrorClass::Os)
+        || message.contains("failed to resolve")
+        || message.contains("could not resolve")
+        || message.contains("timed out")
+        || message.contains("connection");
+
+    if is_auth {
+        let tried = if tried.is_empty() {
+            "none available".to_string()
+        } else {
+            tried.join(", ")
+        };
+        ImiError::RemoteAuthFailed {
+            remote: remote.to_string(),
+            tried,
+            message: err.message().to_string(),
+        }
+        .into()
+    } else if is_network {
+        ImiError::RemoteUnreachable {
+            remote: remote.to_string(),
+            message: err.message().to_string(),
+        }
+        .into()
+    } else {
+        ImiError::GitError(err).into()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorktreeStatus {
     pub modified_files: Vec<String>,
     pub new_files: Vec<String>,
@@ -1036,8 +2383,1019 @@ pub struct WorktreeStatus {
     pub clean: bool,
 }
 
-impl Default for GitManager {
-    fn default() -> Self {
-        Self::new()
+/// What a worktree's status depends on besides the working files: a
+/// commit, checkout, `git add` or fetch changes at least one of these
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StatusCacheKey {
+    pub head_sha: Option<String>,
+    /// Modification time of the worktree's index in nanoseconds
+    pub index_mtime_ns: Option<i64>,
+    pub upstream_sha: Option<String>,
+}
+
+/// Commits ahead of and behind another ref
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Divergence {
+    pub reference: String,
+    pub ahead: usize,
+    pub behind: usize,
+}
+
+/// What `sync_pr_head` found since the last sync of a review worktree
+#[derive(Debug, Clone, Serialize)]
+pub struct PrSync {
+    pub pr_number: u32,
+    /// PR head at the previous sync
+    pub reviewed_head: String,
+    pub head: String,
+    /// `<short id> <subject>`, newest first
+    pub new_commits: Vec<String>,
+    /// Commits of the reviewed head a force-push removed
+    pub dropped_commits: usize,
+    /// `git diff --name-status` lines between the two heads
+    pub changed_files: Vec<String>,
+    pub force_pushed: bool,
+}
+
+impl PrSync {
+    pub fn updated(&self) -> bool {
+        self.reviewed_head != self.head
+    }
+
+    pub fn describe(&self) -> String {
+        if !self.updated() {
+            return format!("PR #{} is up to date", self.pr_number);
+        }
+        let changes = format!(
+            "{} new commit(s), {} file(s) changed",
+            self.new_commits.len(),
+            self.changed_files.len()
+        );
+        match self.force_pushed {
+            true => format!(
+                "PR #{} was force-pushed ({} commit(s) dropped): {}",
+                self.pr_number, self.dropped_commits, changes
+            ),
+            false => format!("PR #{}: {}", self.pr_number, changes),
+        }
+    }
+}
+
+/// How one attempt to rebase a worktree ended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RebaseOutcome {
+    Rebased,
+    /// Already based on the target
+    UpToDate,
+    /// Stopped on conflicts in these files
+    Conflicted(Vec<String>),
+    /// Stopped mid-rebase for another reason
+    Stopped(String),
+    /// Couldn't rebase; nothing was changed
+    Failed(String),
+}
+
+fn first_line(text: &str) -> String {
+    text.lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or("git rebase failed")
+        .to_string()
+}
+
+/// Where the branch of a new worktree came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BranchOrigin {
+    /// Created from the base
+    Created,
+    /// A local branch with no commits beyond the base, created again
+    Recreated,
+    /// An existing local branch, checked out (`--adopt-existing`)
+    AdoptedLocal,
+    /// A branch only on origin, checked out as a tracking branch
+    /// (`--adopt-existing`)
+    AdoptedRemote,
+}
+
+impl BranchOrigin {
+    pub fn is_adopted(self) -> bool {
+        matches!(
+            self,
+            BranchOrigin::AdoptedLocal | BranchOrigin::AdoptedRemote
+        )
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BranchOrigin::Created => "created",
+            BranchOrigin::Recreated => "recreated",
+            BranchOrigin::AdoptedLocal => "adopted-local",
+            BranchOrigin::AdoptedRemote => "adopted-remote",
+        }
+    }
+}
+
+/// What `apply_patch` did to a worktree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatchApply {
+    /// `am`, `apply` or `apply --3way`
+    pub method: String,
+    /// `<short id> <subject>` of the commits created, oldest first
+    pub commits: Vec<String>,
+    /// Files the patch changed
+    pub files: Vec<String>,
+}
+
+/// Whether a patch is a mail message (`git format-patch`) rather than a
+/// bare diff, by its headers
+fn is_mailbox(patch: &[u8]) -> bool {
+    let text = String::from_utf8_lossy(patch);
+    let headers = text.split("\n\n").next().unwrap_or_default();
+    let from = headers.starts_with("From ") || headers.lines().any(|l| l.starts_with("From:"));
+    from && headers.lines().any(|l| l.starts_with("Subject:"))
+}
+
+/// The directories to check out for `files`: each file's parent, minus
+/// those inside another (cone mode includes subdirectories). Files at the
+/// root need none, cone mode always has them.
+pub fn sparse_dirs(files: &[String]) -> Vec<String> {
+    let mut dirs: Vec<&str> = files
+        .iter()
+        .filter_map(|file| file.rsplit_once('/').map(|(dir, _)| dir))
+        .collect();
+    // Parents sort before their subdirectories
+    dirs.sort_unstable();
+    dirs.dedup();
+
+    let mut kept: Vec<String> = Vec::new();
+    for dir in dirs {
+        let inside = kept.iter().any(|parent| {
+            dir.strip_prefix(parent.as_str())
+                .is_some_and(|rest| rest.starts_with('/'))
+        });
+        if !inside {
+            kept.push(dir.to_string());
+        }
+    }
+    kept
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CommitSummary {
+    pub id: String,
+    pub summary: String,
+    pub author: String,
+    pub time: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<&git2::Commit<'_>> for CommitSummary {
+    fn from(commit: &git2::Commit<'_>) -> Self {
+        Self {
+            id: commit.id().to_string(),
+            summary: commit.summary().unwrap_or_default().to_string(),
+            author: commit.author().name().unwrap_or_default().to_string(),
+            time: chrono::DateTime::from_timestamp(commit.time().seconds(), 0).unwrap_or_default(),
+        }
+    }
+}
+
+/// `<key>.pub` next to a private key; `with_extension` would drop a dotted
+/// key name's suffix (`id_ed25519.work` -> `id_ed25519.pub`)
+fn public_key_path(private_key: &Path) -> PathBuf {
+    let mut path = private_key.as_os_str().to_owned();
+    path.push(".pub");
+    PathBuf::from(path)
+}
+
+/// Whether the GitHub token may be offered to `url`: only github.com or the
+/// GitHub Enterprise host `gh` is pointed at (`GH_HOST`), never another forge
+fn sends_github_token(url: &str, enterprise_host: Option<&str>) -> bool {
+    RemoteUrl::parse(url).is_some_and(|remote| {
+        remote.is_github()
+            || enterprise_host.is_some_and(|host| {
+                remote.host.as_deref() == Some(host.trim().to_ascii_lowercase().as_str())
+            })
+    })
+}
+
+/// Whether the user's git config asks for signed commits
+fn commit_signing_enabled(repo: &Repository) -> bool {
+    repo.config()
+        .and_then(|config| config.get_bool("commit.gpgsign"))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_git(dir: &Path, args: &[&str]) {
+        let output = Command::new("git")
+            .current_dir(dir)
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?} failed", args);
+    }
+
+    #[test]
+    fn github_token_only_goes_to_github_hosts() {
+        assert!(sends_github_token("https://github.com/acme/api.git", None));
+        assert!(sends_github_token(
+            "https://x-access-token@GitHub.com/acme/api",
+            None
+        ));
+        assert!(sends_github_token(
+            "https://ghe.acme.dev/acme/api.git",
+            Some("GHE.acme.dev")
+        ));
+
+        for url in [
+            "https://gitlab.com/acme/api.git",
+            "https://bitbucket.org/acme/api.git",
+            "https://git.acme.dev/acme/api.git",
+            "https://github.com.evil.dev/acme/api.git",
+            "https://evil.dev/github.com/api.git",
+            "/srv/git/api.git",
+        ] {
+            assert!(!sends_github_token(url, None), "{}", url);
+            assert!(!sends_github_token(url, Some("ghe.acme.dev")), "{}", url);
+        }
+    }
+
+    #[test]
+    fn public_keys_sit_next_to_the_full_private_key_name() {
+        assert_eq!(
+            public_key_path(Path::new("/home/dev/.ssh/id_ed25519")),
+            Path::new("/home/dev/.ssh/id_ed25519.pub")
+        );
+        assert_eq!(
+            public_key_path(Path::new("/home/dev/.ssh/id_ed25519.work")),
+            Path::new("/home/dev/.ssh/id_ed25519.work.pub")
+        );
+    }
+
+    #[test]
+    fn conflicted_merge_is_left_in_progress_until_concluded() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path();
+        run_git(path, &["init", "-q", "-b", "main"]);
+        run_git(path, &["config", "user.email", "dev@example.com"]);
+        run_git(path, &["config", "user.name", "Dev"]);
+        std::fs::write(path.join("f"), "base\n").unwrap();
+        run_git(path, &["add", "f"]);
+        run_git(path, &["commit", "-q", "-m", "base"]);
+        run_git(path, &["checkout", "-q", "-b", "feat/x"]);
+        std::fs::write(path.join("f"), "feature\n").unwrap();
+        run_git(path, &["commit", "-q", "-am", "feature"]);
+        run_git(path, &["checkout", "-q", "main"]);
+        std::fs::write(path.join("f"), "trunk\n").unwrap();
+        run_git(path, &["commit", "-q", "-am", "trunk"]);
+
+        let git = GitManager::new();
+        let repo = Repository::open(path).unwrap();
+        let err = git.merge_branch(&repo, "feat/x", "main").unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ImiError>(),
+            Some(ImiError::MergeConflicts { files }) if files == &["f".to_string()]
+        ));
+        assert!(git.merge_in_progress(path).unwrap());
+        assert!(git.conclude_merge(path).is_err());
+
+        // Still marked up, so nothing is staged
+        assert!(git
+            .stage_resolved(path, &["f".to_string()])
+            .unwrap()
+            .is_empty());
+        std::fs::write(path.join("f"), "both\n").unwrap();
+        assert_eq!(git.stage_resolved(path, &["f".to_string()]).unwrap(), ["f"]);
+        git.conclude_merge(path).unwrap();
+        assert!(!git.merge_in_progress(path).unwrap());
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.parent_count(), 2);
+        assert_eq!(head.summary(), Some("Merge branch 'feat/x'"));
+    }
+
+    #[test]
+    fn unsignable_merge_is_aborted_with_a_signing_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path();
+        run_git(path, &["init", "-q", "-b", "main"]);
+        run_git(path, &["config", "user.email", "dev@example.com"]);
+        run_git(path, &["config", "user.name", "Dev"]);
+        run_git(path, &["commit", "-q", "--allow-empty", "-m", "root"]);
+        run_git(path, &["checkout", "-q", "-b", "feat/x"]);
+        std::fs::write(path.join("x"), "x").unwrap();
+        run_git(path, &["add", "x"]);
+        run_git(path, &["commit", "-q", "-m", "x"]);
+        run_git(path, &["checkout", "-q", "main"]);
+        std::fs::write(path.join("y"), "y").unwrap();
+        run_git(path, &["add", "y"]);
+        run_git(path, &["commit", "-q", "-m", "y"]);
+
+        // Signing required, but the signing program always fails
+        run_git(path, &["config", "commit.gpgsign", "true"]);
+        run_git(path, &["config", "gpg.program", "false"]);
+
+        let repo = Repository::open(path).unwrap();
+        assert!(commit_signing_enabled(&repo));
+        let head_before = repo.head().unwrap().target().unwrap();
+
+        let err = GitManager::new()
+            .merge_branch(&repo, "feat/x", "main")
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ImiError>(),
+            Some(ImiError::CommitSigningFailed { .. })
+        ));
+        assert_eq!(repo.head().unwrap().target().unwrap(), head_before);
+        assert_eq!(repo.state(), git2::RepositoryState::Clean);
+        assert!(!path.join("x").exists());
+    }
+
+    #[test]
+    fn rebase_stops_on_conflicts_and_continues_once_resolved() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path();
+        run_git(path, &["init", "-q", "-b", "main"]);
+        run_git(path, &["config", "user.email", "dev@example.com"]);
+        run_git(path, &["config", "user.name", "Dev"]);
+        std::fs::write(path.join("f"), "base\n").unwrap();
+        run_git(path, &["add", "f"]);
+        run_git(path, &["commit", "-q", "-m", "base"]);
+        run_git(path, &["checkout", "-q", "-b", "feat/x"]);
+        std::fs::write(path.join("f"), "feature\n").unwrap();
+        run_git(path, &["commit", "-q", "-am", "feature"]);
+        run_git(path, &["checkout", "-q", "main"]);
+        std::fs::write(path.join("f"), "trunk\n").unwrap();
+        run_git(path, &["commit", "-q", "-am", "trunk"]);
+        run_git(path, &["checkout", "-q", "feat/x"]);
+
+        let git = GitManager::new();
+        assert_eq!(
+            git.rebase_onto(path, "main").unwrap(),
+            RebaseOutcome::Conflicted(vec!["f".to_string()])
+        );
+        assert!(git.rebase_in_progress(path).unwrap());
+        assert_eq!(
+            git.continue_rebase(path).unwrap(),
+            RebaseOutcome::Conflicted(vec!["f".to_string()])
+        );
+
+        std::fs::write(path.join("f"), "trunk\nfeature\n").unwrap();
+        run_git(path, &["add", "f"]);
+        assert_eq!(git.continue_rebase(path).unwrap(), RebaseOutcome::Rebased);
+        assert!(!git.rebase_in_progress(path).unwrap());
+        assert_eq!(
+            git.rebase_onto(path, "main").unwrap(),
+            RebaseOutcome::UpToDate
+        );
+    }
+
+    #[test]
+    fn classifies_auth_and_network_failures() {
+        let auth = git2::Error::new(
+            git2::ErrorCode::Auth,
+            git2::ErrorClass::Ssh,
+            "authentication required but no callback set",
+        );
+        let err = classify_remote_error(auth, "origin", &["ssh-agent".to_string()]);
+        assert!(matches!(
+            err.downcast_ref::<ImiError>(),
+            Some(ImiError::RemoteAuthFailed { tried, .. }) if tried == "ssh-agent"
+        ));
+
+        let network = git2::Error::new(
+            git2::ErrorCode::GenericError,
+            git2::ErrorClass::Net,
+            "failed to resolve address for github.com",
+        );
+        let err = classify_remote_error(network, "origin", &[]);
+        assert!(matches!(
+            err.downcast_ref::<ImiError>(),
+            Some(ImiError::RemoteUnreachable { .. })
+        ));
+    }
+
+    #[test]
+    fn asks_the_remote_for_its_default_branch() {
+        let dir = tempfile::tempdir().unwrap();
+        let upstream = dir.path().join("upstream");
+        std::fs::create_dir(&upstream).unwrap();
+        run_git(&upstream, &["init", "-q", "-b", "develop"]);
+        run_git(&upstream, &["config", "user.email", "dev@example.com"]);
+        run_git(&upstream, &["config", "user.name", "Dev"]);
+        run_git(&upstream, &["commit", "-q", "--allow-empty", "-m", "root"]);
+
+        let local = dir.path().join("local");
+        std::fs::create_dir(&local).unwrap();
+        run_git(&local, &["init", "-q", "-b", "main"]);
+        run_git(
+            &local,
+            &["remote", "add", "origin", upstream.to_str().unwrap()],
+        );
+
+        let git = GitManager::new();
+        assert_eq!(
+            git.remote_default_branch(&local).unwrap().as_deref(),
+            Some("develop")
+        );
+
+        run_git(
+            &local,
+            &["remote", "set-url", "origin", "/nonexistent/repo"],
+        );
+        assert!(git.remote_default_branch(&local).is_err());
+    }
+
+    #[test]
+    fn reports_divergence_and_recent_commits() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path();
+        run_git(path, &["init", "-q", "-b", "main"]);
+        run_git(path, &["config", "user.email", "dev@example.com"]);
+        run_git(path, &["config", "user.name", "Dev"]);
+        run_git(path, &["commit", "-q", "--allow-empty", "-m", "root"]);
+        run_git(path, &["checkout", "-q", "-b", "feat/x"]);
+        run_git(path, &["commit", "-q", "--allow-empty", "-m", "x1"]);
+        run_git(path, &["commit", "-q", "--allow-empty", "-m", "x2"]);
+        run_git(path, &["checkout", "-q", "main"]);
+        run_git(path, &["commit", "-q", "--allow-empty", "-m", "m1"]);
+        run_git(path, &["checkout", "-q", "feat/x"]);
+
+        let git = GitManager::new();
+        assert_eq!(
+            git.get_divergence(path, "main").unwrap(),
+            Divergence {
+                reference: "main".to_string(),
+                ahead: 2,
+                behind: 1
+            }
+        );
+        assert!(git.get_divergence(path, "nope").is_err());
+        assert_eq!(git.get_upstream_divergence(path).unwrap(), None);
+
+        run_git(path, &["branch", "-q", "--set-upstream-to", "main"]);
+        assert_eq!(
+            git.get_upstream_divergence(path).unwrap().map(|d| d.behind),
+            Some(1)
+        );
+
+        let commits = git.recent_commits(path, 2).unwrap();
+        let summaries: Vec<&str> = commits.iter().map(|c| c.summary.as_str()).collect();
+        assert_eq!(summaries.len(), 2);
+        assert!(summaries.iter().all(|s| s.starts_with('x')));
+        assert_eq!(commits[0].author, "Dev");
+        assert_eq!(git.commit_summary(path, "main").unwrap().summary, "m1");
+        assert!(git.commit_summary(path, "nope").is_err());
+    }
+
+    #[test]
+    fn publish_branch_pushes_and_sets_upstream() {
+        let dir = tempfile::tempdir().unwrap();
+        let remote = dir.path().join("remote.git");
+        let local = dir.path().join("local");
+        std::fs::create_dir(&local).unwrap();
+        run_git(
+            dir.path(),
+            &["init", "-q", "--bare", remote.to_str().unwrap()],
+        );
+        run_git(&local, &["init", "-q", "-b", "feat/x"]);
+        run_git(&local, &["config", "user.email", "dev@example.com"]);
+        run_git(&local, &["config", "user.name", "Dev"]);
+        run_git(&local, &["commit", "-q", "--allow-empty", "-m", "root"]);
+        run_git(
+            &local,
+            &["remote", "add", "origin", remote.to_str().unwrap()],
+        );
+
+        let git = GitManager::new();
+        assert_eq!(git.get_upstream_divergence(&local).unwrap(), None);
+        git.publish_branch(&local, "origin", "feat/x").unwrap();
+
+        let upstream = git.get_upstream_divergence(&local).unwrap().unwrap();
+        assert_eq!(upstream.reference, "origin/feat/x");
+        assert_eq!((upstream.ahead, upstream.behind), (0, 0));
+        assert!(git.publish_branch(&local, "nope", "feat/x").is_err());
+    }
+
+    #[test]
+    fn worktree_config_only_applies_to_that_worktree() {
+        let dir = tempfile::tempdir().unwrap();
+        let trunk = dir.path().join("trunk-main");
+        std::fs::create_dir(&trunk).unwrap();
+        run_git(&trunk, &["init", "-q", "-b", "main"]);
+        run_git(&trunk, &["config", "user.email", "dev@example.com"]);
+        run_git(&trunk, &["config", "user.name", "Dev"]);
+        run_git(&trunk, &["commit", "-q", "--allow-empty", "-m", "root"]);
+        let feature = dir.path().join("feat-x");
+        run_git(
+            &trunk,
+            &[
+                "worktree",
+                "add",
+                "-q",
+                "-b",
+                "feat/x",
+                feature.to_str().unwrap(),
+            ],
+        );
+
+        let git = GitManager::new();
+        git.set_worktree_config(&feature, "commit.template", "/tmp/template")
+            .unwrap();
+
+        let read = |path: &Path| {
+            git.execute_git_command(path, &["config", "--get", "commit.template"])
+                .map(|value| value.trim().to_string())
+                .ok()
+        };
+        assert_eq!(read(&feature).as_deref(), Some("/tmp/template"));
+        assert_eq!(read(&trunk), None);
+        // libgit2 can still open the repository
+        assert!(Repository::open(&feature).is_ok());
+        assert!(Repository::open(&trunk).is_ok());
+
+        // Removing the worktree drops its include from the shared config
+        let includes = || {
+            git.run_git(&trunk, &["config", "--local", "--get-regexp", "^includeif"])
+                .unwrap()
+                .stdout
+        };
+        assert!(includes().contains("worktrees/feat-x"));
+        std::fs::remove_dir_all(&feature).unwrap();
+        git.prune_worktrees(&Repository::open(&trunk).unwrap())
+            .unwrap();
+        assert_eq!(includes(), "");
+    }
+
+    #[test]
+    fn fork_push_default_leaves_with_its_worktree() {
+        let dir = tempfile::tempdir().unwrap();
+        let trunk = dir.path().join("trunk-main");
+        std::fs::create_dir(&trunk).unwrap();
+        run_git(&trunk, &["init", "-q", "-b", "main"]);
+        run_git(&trunk, &["config", "user.email", "dev@example.com"]);
+        run_git(&trunk, &["config", "user.name", "Dev"]);
+        run_git(&trunk, &["commit", "-q", "--allow-empty", "-m", "root"]);
+        let feature = dir.path().join("feat-fork");
+        let feature_arg = feature.to_str().unwrap();
+        run_git(
+            &trunk,
+            &["worktree", "add", "-q", "-b", "feat/fork", feature_arg],
+        );
+
+        // What setup_fork_remotes writes for a fork worktree
+        let git = GitManager::new();
+        git.set_worktree_config(&feature, "remote.pushDefault", "fork")
+            .unwrap();
+        let push_default = |path: &Path| {
+            git.execute_git_command(path, &["config", "--get", "remote.pushDefault"])
+                .map(|value| value.trim().to_string())
+                .ok()
+        };
+        assert_eq!(push_default(&feature).as_deref(), Some("fork"));
+        assert_eq!(push_default(&trunk), None);
+
+        // Closing removes the directory, then the git worktree
+        std::fs::remove_dir_all(&feature).unwrap();
+        git.remove_worktree(&Repository::open(&trunk).unwrap(), "feat-fork")
+            .unwrap();
+        run_git(
+            &trunk,
+            &["worktree", "add", "-q", "-b", "feat/again", feature_arg],
+        );
+        assert_eq!(push_default(&feature), None);
+    }
+
+    #[test]
+    fn resolves_tags_commits_and_remote_branches_as_bases() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path();
+        run_git(path, &["init", "-q", "-b", "main"]);
+        run_git(path, &["config", "user.email", "dev@example.com"]);
+        run_git(path, &["config", "user.name", "Dev"]);
+        run_git(path, &["commit", "-q", "--allow-empty", "-m", "v1"]);
+        run_git(path, &["tag", "-a", "v2.3.1", "-m", "release"]);
+        run_git(path, &["commit", "-q", "--allow-empty", "-m", "next"]);
+
+        let repo = Repository::open(path).unwrap();
+        let git = GitManager::new();
+        let tagged = git.resolve_base(&repo, "v2.3.1", "origin").unwrap();
+        assert_eq!(tagged.summary(), Some("v1"));
+
+        let sha = tagged.id().to_string();
+        assert_eq!(
+            git.resolve_base(&repo, &sha[..8], "origin").unwrap().id(),
+            tagged.id()
+        );
+        assert_eq!(
+            git.resolve_base(&repo, "main", "origin").unwrap().summary(),
+            Some("next")
+        );
+
+        let err = git.resolve_base(&repo, "v9.9.9", "origin").unwrap_err();
+        assert!(err.to_string().contains("not a branch, tag or commit"));
+    }
+
+    #[test]
+    fn status_cache_key_changes_with_commits_and_staging() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path();
+        run_git(path, &["init", "-q", "-b", "main"]);
+        run_git(path, &["config", "user.email", "dev@example.com"]);
+        run_git(path, &["config", "user.name", "Dev"]);
+        run_git(path, &["commit", "-q", "--allow-empty", "-m", "root"]);
+
+        let git = GitManager::new();
+        let before = git.status_cache_key(path).unwrap();
+        assert!(before.head_sha.is_some());
+        assert_eq!(before.upstream_sha, None);
+        assert_eq!(git.status_cache_key(path).unwrap(), before);
+
+        std::fs::write(path.join("a.txt"), "a").unwrap();
+        run_git(path, &["add", "a.txt"]);
+        let staged = git.status_cache_key(path).unwrap();
+        assert_eq!(staged.head_sha, before.head_sha);
+        assert_ne!(staged.index_mtime_ns, before.index_mtime_ns);
+
+        run_git(path, &["commit", "-q", "-m", "a"]);
+        assert_ne!(
+            git.status_cache_key(path).unwrap().head_sha,
+            before.head_sha
+        );
+    }
+
+    #[test]
+    fn nested_repositories_do_not_dirty_the_status() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path();
+        run_git(path, &["init", "-q", "-b", "main"]);
+        run_git(path, &["config", "user.email", "dev@example.com"]);
+        run_git(path, &["config", "user.name", "Dev"]);
+        run_git(path, &["commit", "-q", "--allow-empty", "-m", "root"]);
+
+        let vendored = path.join("vendor/lib");
+        std::fs::create_dir_all(&vendored).unwrap();
+        run_git(&vendored, &["init", "-q"]);
+        std::fs::write(vendored.join("lib.rs"), "").unwrap();
+
+        let status = GitManager::new().get_worktree_status(path).unwrap();
+        assert!(status.clean, "{:?}", status.new_files);
+
+        std::fs::write(path.join("notes.txt"), "").unwrap();
+        let status = GitManager::new().get_worktree_status(path).unwrap();
+        assert_eq!(status.new_files, vec!["notes.txt"]);
+    }
+
+    #[test]
+    fn applies_diffs_and_mailbox_patches_to_worktrees() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path();
+        run_git(repo, &["init", "-q", "-b", "main"]);
+        run_git(repo, &["config", "user.email", "dev@example.com"]);
+        run_git(repo, &["config", "user.name", "Dev"]);
+        std::fs::write(repo.join("a"), "1\n2\n3\n4\n5\n6\n7\n8\n").unwrap();
+        run_git(repo, &["add", "a"]);
+        run_git(repo, &["commit", "-q", "-m", "root"]);
+        let root = Command::new("git")
+            .current_dir(repo)
+            .args(["rev-parse", "HEAD"])
+            .output()
+            .unwrap()
+            .stdout;
+        let root = String::from_utf8_lossy(&root).trim().to_string();
+
+        std::fs::write(repo.join("a"), "1\n2\n3\n4\n5\n6\n7\n8\n9\n").unwrap();
+        run_git(repo, &["commit", "-q", "-am", "Add nine"]);
+        let git = GitManager::new();
+        let diff = git
+            .execute_git_command(repo, &["diff", &root, "HEAD"])
+            .unwrap();
+        let mailbox = git
+            .execute_git_command(repo, &["format-patch", "--stdout", "-1", "HEAD"])
+            .unwrap();
+        assert!(is_mailbox(mailbox.as_bytes()));
+        assert!(!is_mailbox(diff.as_bytes()));
+
+        // A bare diff is staged, and committed when asked
+        run_git(repo, &["checkout", "-q", "-b", "plain", &root]);
+        let applied = git
+            .apply_patch(repo, diff.as_bytes(), Some("Apply nine"))
+            .unwrap();
+        assert_eq!(applied.method, "apply");
+        assert_eq!(applied.files, vec!["a".to_string()]);
+        assert_eq!(applied.commits.len(), 1);
+        assert!(applied.commits[0].ends_with("Apply nine"));
+
+        // A mailbox patch keeps its own commit
+        run_git(repo, &["checkout", "-q", "-b", "mail", &root]);
+        let applied = git.apply_patch(repo, mailbox.as_bytes(), None).unwrap();
+        assert_eq!(applied.method, "am");
+        assert!(applied.commits[0].ends_with("Add nine"));
+
+        // Changed context falls back to a 3-way merge
+        run_git(repo, &["checkout", "-q", "-b", "moved", &root]);
+        std::fs::write(repo.join("a"), "1\n2\n3\n4\n5\nsix\n7\n8\n").unwrap();
+        run_git(repo, &["commit", "-q", "-am", "Spell six"]);
+        let applied = git.apply_patch(repo, diff.as_bytes(), None).unwrap();
+        assert_eq!(applied.method, "apply --3way");
+        assert!(applied.commits.is_empty());
+        assert_eq!(
+            std::fs::read_to_string(repo.join("a")).unwrap(),
+            "1\n2\n3\n4\n5\nsix\n7\n8\n9\n"
+        );
+
+        // Overlapping changes are left as conflicts
+        run_git(repo, &["reset", "-q", "--hard"]);
+        std::fs::write(repo.join("a"), "1\n2\n3\n4\n5\n6\n7\neight\n").unwrap();
+        run_git(repo, &["commit", "-q", "-am", "Spell eight"]);
+        assert!(git.apply_patch(repo, diff.as_bytes(), None).is_err());
+    }
+
+    #[test]
+    fn existing_branches_are_adopted_or_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let origin = dir.path().join("origin");
+        let trunk = dir.path().join("trunk");
+        std::fs::create_dir(&origin).unwrap();
+        run_git(&origin, &["init", "-q", "-b", "main"]);
+        run_git(&origin, &["config", "user.email", "dev@example.com"]);
+        run_git(&origin, &["config", "user.name", "Dev"]);
+        run_git(&origin, &["commit", "-q", "--allow-empty", "-m", "root"]);
+        run_git(&origin, &["branch", "feat/remote"]);
+        run_git(&origin, &["checkout", "-q", "feat/remote"]);
+        run_git(
+            &origin,
+            &["commit", "-q", "--allow-empty", "-m", "remote work"],
+        );
+        run_git(&origin, &["checkout", "-q", "main"]);
+        run_git(dir.path(), &["clone", "-q", "origin", "trunk"]);
+        run_git(&trunk, &["config", "user.email", "dev@example.com"]);
+        run_git(&trunk, &["config", "user.name", "Dev"]);
+        run_git(&trunk, &["branch", "feat/stale"]);
+        run_git(&trunk, &["branch", "feat/local"]);
+        run_git(&trunk, &["checkout", "-q", "feat/local"]);
+        run_git(
+            &trunk,
+            &["commit", "-q", "--allow-empty", "-m", "local work"],
+        );
+        run_git(&trunk, &["checkout", "-q", "main"]);
+
+        let git = GitManager::new();
+        let repo = Repository::open(&trunk).unwrap();
+        let create = |name: &str, branch: &str, adopt: bool| {
+            git.create_worktree(
+                &repo,
+                name,
+                &dir.path().join(name),
+                branch,
+                BranchBase {
+                    branch: Some("main"),
+                    remote: "origin",
+                },
+                adopt,
+            )
+        };
+        let subject = |name: &str| {
+            git.execute_git_command(&dir.path().join(name), &["log", "-1", "--format=%s"])
+                .unwrap()
+                .trim()
+                .to_string()
+        };
+
+        assert_eq!(
+            create("feat-new", "feat/new", false).unwrap(),
+            BranchOrigin::Created
+        );
+        assert_eq!(
+            create("feat-stale", "feat/stale", false).unwrap(),
+            BranchOrigin::Recreated
+        );
+
+        let refused = create("feat-local", "feat/local", false).unwrap_err();
+        assert!(matches!(
+            refused.downcast_ref(),
+            Some(ImiError::BranchExists { location, suggestion, .. })
+                if location == "locally" && suggestion == "feat/local-2"
+        ));
+        assert_eq!(
+            create("feat-local", "feat/local", true).unwrap(),
+            BranchOrigin::AdoptedLocal
+        );
+        assert_eq!(subject("feat-local"), "local work");
+
+        assert!(create("feat-remote", "feat/remote", false).is_err());
+        assert_eq!(
+            create("feat-remote", "feat/remote", true).unwrap(),
+            BranchOrigin::AdoptedRemote
+        );
+        assert_eq!(subject("feat-remote"), "remote work");
+        let upstream = git
+            .execute_git_command(
+                &dir.path().join("feat-remote"),
+                &["rev-parse", "--abbrev-ref", "@{upstream}"],
+            )
+            .unwrap();
+        assert_eq!(upstream.trim(), "origin/feat/remote");
+
+        // A branch checked out elsewhere can't be adopted twice
+        assert!(create("feat-local-again", "feat/local", true).is_err());
+    }
+
+    #[test]
+    fn syncs_review_worktrees_and_flags_force_pushes() {
+        let dir = tempfile::tempdir().unwrap();
+        let origin = dir.path().join("origin");
+        let review = dir.path().join("review");
+        std::fs::create_dir(&origin).unwrap();
+        run_git(&origin, &["init", "-q", "-b", "main"]);
+        run_git(&origin, &["config", "user.email", "dev@example.com"]);
+        run_git(&origin, &["config", "user.name", "Dev"]);
+        run_git(&origin, &["commit", "-q", "--allow-empty", "-m", "root"]);
+        run_git(&origin, &["checkout", "-q", "-b", "feature"]);
+        std::fs::write(origin.join("a"), "a").unwrap();
+        run_git(&origin, &["add", "a"]);
+        run_git(&origin, &["commit", "-q", "-m", "a"]);
+        run_git(&origin, &["update-ref", "refs/pull/7/head", "HEAD"]);
+
+        run_git(dir.path(), &["clone", "-q", "origin", "review"]);
+        run_git(&review, &["fetch", "-q", "origin", "refs/pull/7/head"]);
+        run_git(&review, &["checkout", "-q", "-b", "pr-7", "FETCH_HEAD"]);
+
+        let git = GitManager::new();
+        let sync = git.sync_pr_head(&review, 7, false).unwrap();
+        assert!(!sync.updated());
+
+        // New commit pushed to the PR: fast-forward
+        std::fs::write(origin.join("b"), "b").unwrap();
+        run_git(&origin, &["add", "b"]);
+        run_git(&origin, &["commit", "-q", "-m", "b"]);
+        run_git(&origin, &["update-ref", "refs/pull/7/head", "HEAD"]);
+        let sync = git.sync_pr_head(&review, 7, false).unwrap();
+        assert!(sync.updated() && !sync.force_pushed);
+        assert_eq!(sync.new_commits.len(), 1);
+        assert_eq!(sync.changed_files, vec!["A\tb"]);
+        assert_eq!(git.get_head_commit(&review).unwrap().0, sync.head);
+
+        // Rewritten history: reported against the last synced head
+        run_git(&origin, &["commit", "-q", "--amend", "-m", "b, reworded"]);
+        run_git(&origin, &["update-ref", "refs/pull/7/head", "HEAD"]);
+        std::fs::write(review.join("a"), "edited while reviewing").unwrap();
+        assert!(git.sync_pr_head(&review, 7, false).is_err());
+        let sync = git.sync_pr_head(&review, 7, true).unwrap();
+        assert!(sync.force_pushed);
+        assert_eq!(sync.dropped_commits, 1);
+        assert!(sync.changed_files.is_empty());
+        assert!(sync.describe().contains("force-pushed"));
+        assert_eq!(git.get_head_commit(&review).unwrap().0, sync.head);
+        assert_eq!(std::fs::read_to_string(review.join("a")).unwrap(), "a");
+    }
+
+    #[test]
+    fn sparse_dirs_keep_outermost_parents() {
+        let files: Vec<String> = [
+            "README.md",
+            "services/api/src/main.rs",
+            "services/api/Cargo.toml",
+            "services/api-gateway/lib.rs",
+            "services/api/src/deep/mod.rs",
+            "web/app.ts",
+        ]
+        .iter()
+        .map(|f| f.to_string())
+        .collect();
+        assert_eq!(
+            sparse_dirs(&files),
+            vec!["services/api", "services/api-gateway", "web"]
+        );
+        assert!(sparse_dirs(&["README.md".to_string()]).is_empty());
+    }
+
+    #[test]
+    fn sparse_pr_worktrees_only_check_out_changed_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let origin = dir.path().join("origin");
+        let trunk = dir.path().join("trunk");
+        let review = dir.path().join("pr-7");
+        std::fs::create_dir(&origin).unwrap();
+        run_git(&origin, &["init", "-q", "-b", "main"]);
+        run_git(&origin, &["config", "user.email", "dev@example.com"]);
+        run_git(&origin, &["config", "user.name", "Dev"]);
+        for file in ["README.md", "api/lib.rs", "web/app.ts"] {
+            let path = origin.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, file).unwrap();
+        }
+        run_git(&origin, &["add", "."]);
+        run_git(&origin, &["commit", "-q", "-m", "root"]);
+        run_git(&origin, &["checkout", "-q", "-b", "feature"]);
+        std::fs::write(origin.join("api/lib.rs"), "changed").unwrap();
+        run_git(&origin, &["commit", "-q", "-am", "api"]);
+        run_git(&origin, &["update-ref", "refs/pull/7/head", "HEAD"]);
+        run_git(&origin, &["checkout", "-q", "main"]);
+        run_git(dir.path(), &["clone", "-q", "origin", "trunk"]);
+
+        let git = GitManager::new();
+        // `main` exists locally already, as for a fork's PR from its main
+        git.add_sparse_pr_worktree(&trunk, 7, "main", &review, &["api".to_string()])
+            .unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(review.join("api/lib.rs")).unwrap(),
+            "changed"
+        );
+        assert!(review.join("README.md").exists());
+        assert!(!review.join("web").exists());
+        assert_eq!(git.get_current_branch(&review).unwrap(), "pr/7");
+        assert!(trunk.join("web/app.ts").exists());
+        assert!(git
+            .execute_git_command(&review, &["status", "--porcelain"])
+            .unwrap()
+            .trim()
+            .is_empty());
+    }
+
+    #[test]
+    fn lists_locked_detached_and_prunable_worktrees() {
+        let output = "worktree /src/api\nHEAD 1111\nbranch refs/heads/main\n\n\
+                      worktree /src/feat-x\nHEAD 2222\ndetached\nlocked on usb\n\n\
+                      worktree /src/gone\nHEAD 3333\nbranch refs/heads/fix/y\n\
+                      prunable gitdir file points to non-existent location\n";
+        let worktrees = parse_worktree_porcelain(output, '\n');
+        assert_eq!(worktrees.len(), 3);
+        assert_eq!(worktrees[0].branch.as_deref(), Some("main"));
+        assert!(!worktrees[0].is_locked() && !worktrees[0].detached);
+        assert_eq!(worktrees[1].branch, None);
+        assert!(worktrees[1].detached);
+        assert_eq!(worktrees[1].locked.as_deref(), Some("on usb"));
+        assert_eq!(worktrees[2].branch.as_deref(), Some("fix/y"));
+        assert!(worktrees[2].is_prunable());
+
+        // Against git itself, with -z
+        let dir = tempfile::tempdir().unwrap();
+        let trunk = dir.path().join("trunk");
+        std::fs::create_dir(&trunk).unwrap();
+        run_git(&trunk, &["init", "-q", "-b", "main"]);
+        run_git(&trunk, &["config", "user.email", "dev@example.com"]);
+        run_git(&trunk, &["config", "user.name", "Dev"]);
+        run_git(&trunk, &["commit", "-q", "--allow-empty", "-m", "base"]);
+        let locked = dir.path().join("locked");
+        let detached = dir.path().join("detached");
+        let gone = dir.path().join("gone");
+        for (path, args) in [
+            (&locked, vec!["-b", "feat/locked"]),
+            (&detached, vec!["--detach"]),
+            (&gone, vec!["-b", "feat/gone"]),
+        ] {
+            let path = path.to_str().unwrap();
+            let mut add = vec!["worktree", "add", "-q"];
+            add.extend(args);
+            add.push(path);
+            run_git(&trunk, &add);
+        }
+        run_git(
+            &trunk,
+            &[
+                "worktree",
+                "lock",
+                "--reason",
+                "on usb",
+                locked.to_str().unwrap(),
+            ],
+        );
+        std::fs::remove_dir_all(&locked).unwrap();
+        std::fs::remove_dir_all(&gone).unwrap();
+
+        let git = GitManager::new();
+        let find = |worktrees: &[GitWorktree], name: &str| {
+            worktrees
+                .iter()
+                .find(|wt| wt.path.ends_with(name))
+                .cloned()
+                .unwrap()
+        };
+        let worktrees = git.list_git_worktrees(&trunk).unwrap();
+        assert_eq!(worktrees.len(), 4);
+        assert_eq!(find(&worktrees, "trunk").branch.as_deref(), Some("main"));
+        assert!(find(&worktrees, "detached").detached);
+        assert_eq!(find(&worktrees, "locked").locked.as_deref(), Some("on usb"));
+        assert!(find(&worktrees, "gone").is_prunable());
+
+        // Paths through a symlink name the same worktrees
+        let linked = dir.path().join("linked");
+        std::os::unix::fs::symlink(dir.path(), &linked).unwrap();
+        assert!(find(&worktrees, "detached").is_at(&linked.join("detached")));
+        assert!(find(&worktrees, "gone").is_at(&linked.join("gone")));
+        assert!(!find(&worktrees, "gone").is_at(&linked.join("detached")));
+
+        // Pruning keeps the locked one
+        git.prune_worktrees(&Repository::open(&trunk).unwrap())
+            .unwrap();
+        let worktrees = git.list_git_worktrees(&trunk).unwrap();
+        assert_eq!(worktrees.len(), 3);
+        assert!(worktrees.iter().all(|wt| !wt.path.ends_with("gone")));
+        assert!(find(&worktrees, "locked").is_locked());
     }
 }
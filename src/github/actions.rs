@@ -0,0 +1,312 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use super::pulls::{auth_token, parse_github_remote};
+use crate::config::RetrySettings;
+use crate::json_cache;
+use crate::retry::retry_async;
+
+/// Runs still going are looked up again after this long; finished runs for
+/// the branch's current head are reused until the branch moves
+const PENDING_TTL_SECS: i64 = 60;
+
+/// Runs fetched per branch; enough to cover every workflow of the newest push
+const RUNS_PER_PAGE: &str = "20";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CiState {
+    Success,
+    Failure,
+    InProgress,
+    Cancelled,
+}
+
+impl CiState {
+    /// State of one run from its `status` and `conclusion`
+    fn from_run(status: &str, conclusion: Option<&str>) -> Self {
+        match (status, conclusion) {
+            ("completed", Some("success" | "skipped" | "neutral")) => Self::Success,
+            ("completed", Some("cancelled")) => Self::Cancelled,
+            ("completed", _) => Self::Failure,
+            _ => Self::InProgress,
+        }
+    }
+
+    /// Failures first, then running, then cancelled: the state a branch shows
+    /// when its workflows disagree
+    fn severity(&self) -> u8 {
+        match self {
+            Self::Failure => 3,
+            Self::InProgress => 2,
+            Self::Cancelled => 1,
+            Self::Success => 0,
+        }
+    }
+}
+
+/// GitHub Actions result for the newest push to a branch
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CiStatus {
+    pub state: CiState,
+    /// Workflow that decided the state (the failing one, if any)
+    pub workflow: String,
+    pub url: String,
+    pub head_sha: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failing_job: Option<String>,
+    #[serde(skip)]
+    run_id: u64,
+}
+
+impl CiStatus {
+    /// Combine the runs of the newest head commit in a `actions/runs`
+    /// response (newest first) into one status
+    fn from_runs(runs: &[Value]) -> Option<Self> {
+        let head_sha = runs.first()?["head_sha"].as_str()?;
+        runs.iter()
+            .filter(|run| run["head_sha"] == head_sha)
+            .filter_map(|run| {
+                Some(Self {
+                    state: CiState::from_run(run["status"].as_str()?, run["conclusion"].as_str()),
+                    workflow: run["name"].as_str().unwrap_or_default().to_string(),
+                    url: run["html_url"].as_str()?.to_string(),
+                    head_sha: head_sha.to_string(),
+                    failing_job: None,
+                    run_id: run["id"].as_u64()?,
+                })
+            })
+            .max_by_key(|status| status.state.severity())
+    }
+}
+
+/// Looks up the GitHub Actions runs for a branch, with the same credentials
+/// as [`super::PullRequestLookup`]
+pub struct WorkflowRunLookup {
+    client: reqwest::Client,
+    token: Option<String>,
+    retry: RetrySettings,
+}
+
+impl WorkflowRunLookup {
+    pub fn new(retry: RetrySettings) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .user_agent("iMi-Project-Creator/0.1.0")
+            .timeout(std::time::Duration::from_secs(15))
+            .build()
+            .context("Failed to create HTTP client")?;
+
+        Ok(Self {
+            client,
+            token: auth_token(),
+            retry,
+        })
+    }
+
+    /// CI status of the newest push to `branch`, with the failing job's name
+    /// when it failed; None when the remote isn't on GitHub or nothing ran
+    pub async fn latest(&self, remote_url: &str, branch: &str) -> Result<Option<CiStatus>> {
+        let Some((owner, repo)) = parse_github_remote(remote_url) else {
+            return Ok(None);
+        };
+        let base = format!("https://api.github.com/repos/{}/{}/actions", owner, repo);
+
+        let runs = self
+            .get(
+                &format!("{}/runs", base),
+                &[("branch", branch), ("per_page", RUNS_PER_PAGE)],
+                "GET /actions/runs",
+            )
+            .await?;
+        let Some(mut status) = runs["workflow_runs"]
+            .as_array()
+            .and_then(|runs| CiStatus::from_runs(runs))
+        else {
+            return Ok(None);
+        };
+
+        if status.state == CiState::Failure {
+            let jobs = self
+                .get(
+                    &format!("{}/runs/{}/jobs", base, status.run_id),
+                    &[("filter", "latest")],
+                    "GET /actions/jobs",
+                )
+                .await?;
+            status.failing_job = failing_job(&jobs);
+        }
+
+        Ok(Some(status))
+    }
+
+    async fn get(&self, url: &str, query: &[(&str, &str)], operation: &str) -> Result<Value> {
+        retry_async(&self.retry, operation, || async {
+            let mut request = self
+                .client
+                .get(url)
+                .query(query)
+                .header("Accept", "application/vnd.github+json");
+            if let Some(token) = &self.token {
+                request = request.header("Authorization", format!("Bearer {}", token));
+            }
+
+            request
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .context(format!("Failed to query {}", url))?
+                .json()
+                .await
+                .context("Failed to parse GitHub Actions response")
+        })
+        .await
+    }
+}
+
+fn failing_job(jobs: &Value) -> Option<String> {
+    jobs["jobs"]
+        .as_array()?
+        .iter()
+        .find(|job| {
+            matches!(
+                job["conclusion"].as_str(),
+                Some("failure" | "timed_out" | "startup_failure")
+            )
+        })
+        .and_then(|job| job["name"].as_str())
+        .map(str::to_string)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedStatus {
+    status: Option<CiStatus>,
+    fetched_at: DateTime<Utc>,
+}
+
+/// CI statuses keyed by remote and branch, persisted between commands so
+/// `imi status --ci` doesn't hit the API for branches that haven't moved
+#[derive(Debug)]
+pub struct CiCache {
+    path: Option<PathBuf>,
+    entries: HashMap<String, CachedStatus>,
+}
+
+impl CiCache {
+    pub fn load() -> Self {
+        Self::load_from(json_cache::cache_file("ci-cache.json"))
+    }
+
+    pub fn load_from(path: Option<PathBuf>) -> Self {
+        let entries = json_cache::read(path.as_deref());
+        Self { path, entries }
+    }
+
+    /// The cached status, if still current for a branch whose local head is
+    /// `head_sha`: finished runs of that commit never change, anything else
+    /// is reused for a minute
+    pub fn get(
+        &self,
+        remote_url: &str,
+        branch: &str,
+        head_sha: Option<&str>,
+    ) -> Option<Option<CiStatus>> {
+        let cached = self.entries.get(&key(remote_url, branch))?;
+        let settled = cached.status.as_ref().is_some_and(|status| {
+            status.state != CiState::InProgress && Some(status.head_sha.as_str()) == head_sha
+        });
+        let fresh = Utc::now() - cached.fetched_at < chrono::Duration::seconds(PENDING_TTL_SECS);
+        (settled || fresh).then(|| cached.status.clone())
+    }
+
+    pub fn insert(&mut self, remote_url: &str, branch: &str, status: Option<CiStatus>) {
+        self.entries.insert(
+            key(remote_url, branch),
+            CachedStatus {
+                status,
+                fetched_at: Utc::now(),
+            },
+        );
+    }
+
+    pub fn save(&self) -> Result<()> {
+        json_cache::write(self.path.as_deref(), &self.entries)
+    }
+}
+
+fn key(remote_url: &str, branch: &str) -> String {
+    format!("{}#{}", remote_url, branch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn run(id: u64, sha: &str, name: &str, status: &str, conclusion: Option<&str>) -> Value {
+        json!({
+            "id": id,
+            "name": name,
+            "head_sha": sha,
+            "status": status,
+            "conclusion": conclusion,
+            "html_url": format!("https://github.com/delorenj/iMi/actions/runs/{}", id),
+        })
+    }
+
+    #[test]
+    fn newest_push_reports_its_worst_workflow() {
+        let runs = vec![
+            run(3, "bbb", "Lint", "completed", Some("success")),
+            run(2, "bbb", "CI", "completed", Some("failure")),
+            run(1, "aaa", "CI", "in_progress", None),
+        ];
+        let status = CiStatus::from_runs(&runs).unwrap();
+        assert_eq!(status.state, CiState::Failure);
+        assert_eq!((status.workflow.as_str(), status.run_id), ("CI", 2));
+
+        let runs = vec![
+            run(5, "ccc", "Lint", "queued", None),
+            run(4, "ccc", "CI", "completed", Some("skipped")),
+        ];
+        assert_eq!(
+            CiStatus::from_runs(&runs).unwrap().state,
+            CiState::InProgress
+        );
+        assert_eq!(CiStatus::from_runs(&[]), None);
+
+        let jobs = json!({ "jobs": [
+            { "name": "fmt", "conclusion": "success" },
+            { "name": "test (ubuntu)", "conclusion": "failure" },
+        ]});
+        assert_eq!(failing_job(&jobs).as_deref(), Some("test (ubuntu)"));
+    }
+
+    #[test]
+    fn finished_runs_are_cached_until_the_branch_moves() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache_file = dir.path().join("ci-cache.json");
+        let remote = "git@github.com:delorenj/iMi.git";
+
+        let mut cache = CiCache::load_from(Some(cache_file.clone()));
+        let status = CiStatus::from_runs(&[run(1, "aaa", "CI", "completed", Some("success"))]);
+        cache.insert(remote, "feat/x", status.clone());
+        cache
+            .entries
+            .get_mut(&key(remote, "feat/x"))
+            .unwrap()
+            .fetched_at = Utc::now() - chrono::Duration::hours(1);
+        cache.save().unwrap();
+
+        let cache = CiCache::load_from(Some(cache_file));
+        assert_eq!(
+            cache.get(remote, "feat/x", Some("aaa")).flatten(),
+            status.map(|s| CiStatus { run_id: 0, ..s })
+        );
+        assert_eq!(cache.get(remote, "feat/x", Some("bbb")), None);
+        assert_eq!(cache.get(remote, "feat/y", Some("aaa")), None);
+    }
+}
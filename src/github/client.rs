@@ -1,11 +1,14 @@
 use anyhow::{anyhow, Context, Result};
 
 use crate::commands::project::config::{ProjectConfig, RepoVisibility};
+use crate::config::{Config, RetrySettings};
+use crate::retry::retry_async;
 
 pub struct GitHubClient {
     client: reqwest::Client,
     token: String,
     username: String,
+    retry: RetrySettings,
 }
 
 impl GitHubClient {
@@ -21,20 +24,26 @@ impl GitHubClient {
             .user_agent("iMi-Project-Creator/0.1.0")
             .build()
             .context("Failed to create HTTP client")?;
-
-        // Get authenticated user
-        let user_response = client
-            .get("https://api.github.com/user")
-            .header("Authorization", format!("Bearer {}", token))
-            .header("Accept", "application/vnd.github+json")
-            .send()
+        let retry = Config::load()
             .await
-            .context("Failed to fetch authenticated user")?;
+            .map(|config| config.retry_settings)
+            .unwrap_or_default();
 
-        let user: serde_json::Value = user_response
-            .json()
-            .await
-            .context("Failed to parse user response")?;
+        // Get authenticated user
+        let user: serde_json::Value = retry_async(&retry, "GET /user", || async {
+            client
+                .get("https://api.github.com/user")
+                .header("Authorization", format!("Bearer {}", token))
+                .header("Accept", "application/vnd.github+json")
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .context("Failed to fetch authenticated user")?
+                .json()
+                .await
+                .context("Failed to parse user response")
+        })
+        .await?;
 
         let username = user["login"]
             .as_str()
@@ -45,6 +54,7 @@ impl GitHubClient {
             client,
             token,
             username,
+            retry,
         })
     }
 
@@ -71,7 +81,8 @@ impl GitHubClient {
             "auto_init": false,
         });
 
-        // Create repository using GitHub REST API
+        // Create repository using GitHub REST API. Not retried: a request
+        // that timed out or got a 5xx may have created the repository anyway
         let response = self
             .client
             .post("https://api.github.com/user/repos")
@@ -111,16 +122,25 @@ impl GitHubClient {
 
     /// Check if repository exists
     async fn repository_exists(&self, repo_name: &str) -> Result<bool> {
-        let response = self
-            .client
-            .get(&format!(
-                "https://api.github.com/repos/{}/{}",
-                self.username, repo_name
-            ))
-            .header("Authorization", format!("Bearer {}", self.token))
-            .header("Accept", "application/vnd.github+json")
-            .send()
-            .await?;
+        let url = format!(
+            "https://api.github.com/repos/{}/{}",
+            self.username, repo_name
+        );
+        let response = retry_async(&self.retry, "GET /repos", || async {
+            let response = self
+                .client
+                .get(url.as_str())
+                .header("Authorization", format!("Bearer {}", self.token))
+                .header("Accept", "application/vnd.github+json")
+                .send()
+                .await?;
+            // A 404 is an answer; server errors are worth another attempt
+            if response.status().is_server_error() {
+                response.error_for_status_ref()?;
+            }
+            Ok(response)
+        })
+        .await?;
 
         Ok(response.status().is_success())
     }
@@ -0,0 +1,200 @@
+use anyhow::{anyhow, Context, Result};
+use serde_json::{json, Value};
+
+use super::pulls::{auth_token, parse_github_remote};
+use crate::config::RetrySettings;
+use crate::retry::retry_async;
+
+/// Where a pull request stands in its repository's merge queue
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueueState {
+    /// Waiting in the queue; `state` is GitHub's entry state, e.g.
+    /// `AWAITING_CHECKS`
+    Queued {
+        position: Option<u64>,
+        state: String,
+    },
+    Merged,
+    /// Dropped from the queue without merging (failed checks, conflicts or
+    /// someone dequeued it)
+    Removed,
+}
+
+impl QueueState {
+    /// Parse the `node` of a pull request query
+    fn from_node(node: &Value) -> Result<Self> {
+        if node["merged"].as_bool().unwrap_or(false) {
+            return Ok(Self::Merged);
+        }
+        let entry = &node["mergeQueueEntry"];
+        if entry.is_null() {
+            return Ok(Self::Removed);
+        }
+        Ok(Self::Queued {
+            position: entry["position"].as_u64(),
+            state: entry["state"]
+                .as_str()
+                .context("Merge queue entry has no state")?
+                .to_string(),
+        })
+    }
+}
+
+/// Merges pull requests on GitHub, directly or through the merge queue.
+/// Needs a token from GITHUB_TOKEN, GH_TOKEN or the `gh` CLI login.
+pub struct PullRequestMerger {
+    client: reqwest::Client,
+    token: String,
+    retry: RetrySettings,
+    owner: String,
+    repo: String,
+}
+
+impl PullRequestMerger {
+    pub fn new(retry: RetrySettings, remote_url: &str) -> Result<Self> {
+        let (owner, repo) = parse_github_remote(remote_url)
+            .ok_or_else(|| anyhow!("'{}' is not a GitHub remote", remote_url))?;
+        let token = auth_token().ok_or_else(|| {
+            anyhow!("Merging on GitHub needs a token: set GITHUB_TOKEN or run `gh auth login`")
+        })?;
+        let client = reqwest::Client::builder()
+            .user_agent("iMi-Project-Creator/0.1.0")
+            .timeout(std::time::Duration::from_secs(15))
+            .build()
+            .context("Failed to create HTTP client")?;
+
+        Ok(Self {
+            client,
+            token,
+            retry,
+            owner,
+            repo,
+        })
+    }
+
+    /// Merge the pull request right away, with the repository's default
+    /// merge method
+    pub async fn merge(&self, number: i32) -> Result<()> {
+        let url = format!(
+            "https://api.github.com/repos/{}/{}/pulls/{}/merge",
+            self.owner, self.repo, number
+        );
+        retry_async(&self.retry, "PUT /pulls/merge", || async {
+            self.client
+                .put(url.as_str())
+                .bearer_auth(&self.token)
+                .header("Accept", "application/vnd.github+json")
+                .json(&json!({}))
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .context(format!("Failed to merge pull request #{}", number))
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// GraphQL node id of a pull request, for the merge queue calls
+    pub async fn node_id(&self, number: i32) -> Result<String> {
+        let data = self
+            .graphql(
+                "query($owner: String!, $repo: String!, $number: Int!) {
+                    repository(owner: $owner, name: $repo) {
+                        pullRequest(number: $number) { id }
+                    }
+                }",
+                json!({ "owner": self.owner, "repo": self.repo, "number": number }),
+            )
+            .await?;
+        data["repository"]["pullRequest"]["id"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| anyhow!("Pull request #{} not found", number))
+    }
+
+    /// Add the pull request with node id `id` to the merge queue
+    pub async fn enqueue(&self, id: &str) -> Result<()> {
+        self.graphql(
+            "mutation($id: ID!) {
+                enqueuePullRequest(input: { pullRequestId: $id }) {
+                    mergeQueueEntry { position }
+                }
+            }",
+            json!({ "id": id }),
+        )
+        .await
+        .context("Failed to add the pull request to the merge queue")?;
+        Ok(())
+    }
+
+    pub async fn queue_state(&self, id: &str) -> Result<QueueState> {
+        let data = self
+            .graphql(
+                "query($id: ID!) {
+                    node(id: $id) {
+                        ... on PullRequest { merged mergeQueueEntry { position state } }
+                    }
+                }",
+                json!({ "id": id }),
+            )
+            .await?;
+        QueueState::from_node(&data["node"])
+    }
+
+    /// Run a GraphQL request and return its `data`, failing on any `errors`
+    async fn graphql(&self, query: &str, variables: Value) -> Result<Value> {
+        let body = json!({ "query": query, "variables": variables });
+        let response: Value = retry_async(&self.retry, "POST /graphql", || async {
+            self.client
+                .post("https://api.github.com/graphql")
+                .bearer_auth(&self.token)
+                .json(&body)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .context("GitHub GraphQL request failed")?
+                .json()
+                .await
+                .context("Failed to parse GraphQL response")
+        })
+        .await?;
+
+        if let Some(errors) = response["errors"].as_array().filter(|e| !e.is_empty()) {
+            let messages: Vec<&str> = errors
+                .iter()
+                .filter_map(|error| error["message"].as_str())
+                .collect();
+            return Err(anyhow!("{}", messages.join("; ")));
+        }
+        Ok(response["data"].clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_queue_state_from_pull_request_node() {
+        let queued = json!({
+            "merged": false,
+            "mergeQueueEntry": { "position": 2, "state": "AWAITING_CHECKS" },
+        });
+        assert_eq!(
+            QueueState::from_node(&queued).unwrap(),
+            QueueState::Queued {
+                position: Some(2),
+                state: "AWAITING_CHECKS".to_string()
+            }
+        );
+
+        let merged = json!({ "merged": true, "mergeQueueEntry": null });
+        assert_eq!(QueueState::from_node(&merged).unwrap(), QueueState::Merged);
+
+        let dropped = json!({ "merged": false, "mergeQueueEntry": null });
+        assert_eq!(
+            QueueState::from_node(&dropped).unwrap(),
+            QueueState::Removed
+        );
+    }
+}
@@ -1,3 +1,11 @@
+pub mod actions;
 pub mod client;
+pub mod merge;
+pub mod pulls;
+pub mod repos;
 
+pub use actions::{CiCache, CiState, CiStatus, WorkflowRunLookup};
 pub use client::{check_auth, show_auth_help, GitHubClient};
+pub use merge::{PullRequestMerger, QueueState};
+pub use pulls::PullRequestLookup;
+pub use repos::{list_owner_repositories, owner_repositories, parse_owner};
@@ -0,0 +1,234 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::config::RetrySettings;
+use crate::remote_url::RemoteUrl;
+use crate::retry::retry_async;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PullRequestState {
+    Open,
+    Closed,
+    Merged,
+}
+
+impl PullRequestState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Open => "open",
+            Self::Closed => "closed",
+            Self::Merged => "merged",
+        }
+    }
+}
+
+/// A pull request opened from a worktree's branch
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PullRequest {
+    pub number: i32,
+    pub url: String,
+    pub state: PullRequestState,
+}
+
+impl PullRequest {
+    /// Parse one entry of the GitHub `pulls` API response
+    fn from_api(value: &Value) -> Option<Self> {
+        let state = if !value["merged_at"].is_null() {
+            PullRequestState::Merged
+        } else if value["state"] == "open" {
+            PullRequestState::Open
+        } else {
+            PullRequestState::Closed
+        };
+
+        Some(Self {
+            number: i32::try_from(value["number"].as_u64()?).ok()?,
+            url: value["html_url"].as_str()?.to_string(),
+            state,
+        })
+    }
+}
+
+/// Finds the pull request for a branch, whether iMi or someone else opened it.
+/// Works without credentials for public repositories; otherwise uses
+/// GITHUB_TOKEN, GH_TOKEN or the `gh` CLI login.
+pub struct PullRequestLookup {
+    client: reqwest::Client,
+    token: Option<String>,
+    retry: RetrySettings,
+}
+
+impl PullRequestLookup {
+    pub fn new(retry: RetrySettings) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .user_agent("iMi-Project-Creator/0.1.0")
+            .timeout(std::time::Duration::from_secs(15))
+            .build()
+            .context("Failed to create HTTP client")?;
+
+        Ok(Self {
+            client,
+            token: auth_token(),
+            retry,
+        })
+    }
+
+    /// Most recent pull request whose head is `branch`; None when the remote
+    /// isn't on GitHub or no PR was opened yet
+    pub async fn find(&self, remote_url: &str, branch: &str) -> Result<Option<PullRequest>> {
+        let Some((owner, repo)) = parse_github_remote(remote_url) else {
+            return Ok(None);
+        };
+        let url = format!("https://api.github.com/repos/{}/{}/pulls", owner, repo);
+        let head = format!("{}:{}", owner, branch);
+
+        let pulls: Vec<Value> = retry_async(&self.retry, "GET /pulls", || async {
+            let mut request = self
+                .client
+                .get(url.as_str())
+                .query(&[("head", head.as_str()), ("state", "all"), ("per_page", "1")])
+                .header("Accept", "application/vnd.github+json");
+            if let Some(token) = &self.token {
+                request = request.header("Authorization", format!("Bearer {}", token));
+            }
+
+            request
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .context(format!("Failed to look up pull requests for {}", branch))?
+                .json()
+                .await
+                .context("Failed to parse pull request response")
+        })
+        .await?;
+
+        Ok(pulls.first().and_then(PullRequest::from_api))
+    }
+
+    /// Paths a pull request adds, modifies or removes, as of its head. The
+    /// API stops at 3000 files.
+    pub async fn changed_files(&self, remote_url: &str, number: i32) -> Result<Vec<String>> {
+        let (owner, repo) = parse_github_remote(remote_url)
+            .with_context(|| format!("{} is not a GitHub remote", remote_url))?;
+        let url = format!(
+            "https://api.github.com/repos/{}/{}/pulls/{}/files",
+            owner, repo, number
+        );
+
+        let mut files = Vec::new();
+        for page in 1..=CHANGED_FILES_MAX_PAGES {
+            let page = page.to_string();
+            let entries: Vec<Value> = retry_async(&self.retry, "GET /pulls/files", || async {
+                let mut request = self
+                    .client
+                    .get(url.as_str())
+                    .query(&[("per_page", "100"), ("page", page.as_str())])
+                    .header("Accept", "application/vnd.github+json");
+                if let Some(token) = &self.token {
+                    request = request.header("Authorization", format!("Bearer {}", token));
+                }
+
+                request
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status())
+                    .context(format!("Failed to list the files of PR #{}", number))?
+                    .json()
+                    .await
+                    .context("Failed to parse pull request files response")
+            })
+            .await?;
+
+            let last = entries.len() < 100;
+            files.extend(
+                entries
+                    .iter()
+                    .filter_map(|entry| entry["filename"].as_str())
+                    .map(String::from),
+            );
+            if last {
+                break;
+            }
+        }
+        Ok(files)
+    }
+}
+
+/// Pages of 100 the pull request files API serves at most
+const CHANGED_FILES_MAX_PAGES: u32 = 30;
+
+/// `owner/repo` from any form of GitHub remote
+pub fn parse_github_remote(remote_url: &str) -> Option<(String, String)> {
+    let remote = RemoteUrl::parse(remote_url).filter(RemoteUrl::is_github)?;
+    match remote.namespace.split('/').collect::<Vec<_>>()[..] {
+        [owner] if !owner.is_empty() => Some((owner.to_string(), remote.name)),
+        _ => None,
+    }
+}
+
+pub(crate) fn auth_token() -> Option<String> {
+    ["GITHUB_TOKEN", "GH_TOKEN"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|token| !token.is_empty())
+        .or_else(|| {
+            let output = std::process::Command::new("gh")
+                .args(["auth", "token"])
+                .output()
+                .ok()?;
+            let token = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            (output.status.success() && !token.is_empty()).then_some(token)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_github_remotes() {
+        let expected = Some(("delorenj".to_string(), "iMi".to_string()));
+        assert_eq!(
+            parse_github_remote("git@github.com:delorenj/iMi.git"),
+            expected
+        );
+        assert_eq!(
+            parse_github_remote("https://github.com/delorenj/iMi"),
+            expected
+        );
+        assert_eq!(
+            parse_github_remote("ssh://git@github.com/delorenj/iMi.git"),
+            expected
+        );
+        assert_eq!(
+            parse_github_remote("https://token@github.com/delorenj/iMi.git/"),
+            expected
+        );
+        assert_eq!(parse_github_remote("git@gitlab.com:delorenj/iMi.git"), None);
+    }
+
+    #[test]
+    fn merged_at_wins_over_closed_state() {
+        let pr = PullRequest::from_api(&serde_json::json!({
+            "number": 42,
+            "html_url": "https://github.com/delorenj/iMi/pull/42",
+            "state": "closed",
+            "merged_at": "2026-10-01T12:00:00Z",
+        }))
+        .unwrap();
+        assert_eq!(pr.number, 42);
+        assert_eq!(pr.state, PullRequestState::Merged);
+
+        let pr = PullRequest::from_api(&serde_json::json!({
+            "number": 7,
+            "html_url": "https://github.com/delorenj/iMi/pull/7",
+            "state": "open",
+            "merged_at": null,
+        }))
+        .unwrap();
+        assert_eq!(pr.state, PullRequestState::Open);
+    }
+}
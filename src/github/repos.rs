@@ -0,0 +1,181 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::Value;
+
+use super::pulls::auth_token;
+use crate::config::RetrySettings;
+use crate::retry::retry_async;
+
+const PER_PAGE: usize = 100;
+
+/// One repository of an organization or user
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OwnerRepository {
+    /// `owner/repo`
+    pub full_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    pub topics: Vec<String>,
+}
+
+impl OwnerRepository {
+    /// None for archived repositories and malformed entries
+    fn from_api(repo: &Value) -> Option<Self> {
+        if repo["archived"].as_bool().unwrap_or(false) {
+            return None;
+        }
+        Some(Self {
+            full_name: repo["full_name"].as_str()?.to_string(),
+            description: repo["description"].as_str().map(str::to_string),
+            language: repo["language"].as_str().map(str::to_string),
+            topics: repo["topics"]
+                .as_array()
+                .map(|topics| {
+                    topics
+                        .iter()
+                        .filter_map(Value::as_str)
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Tagged with `topic` and written in `language` (case-insensitive),
+    /// where given
+    pub fn matches(&self, topic: Option<&str>, language: Option<&str>) -> bool {
+        topic.is_none_or(|topic| self.topics.iter().any(|t| t.eq_ignore_ascii_case(topic)))
+            && language.is_none_or(|language| {
+                self.language
+                    .as_deref()
+                    .is_some_and(|l| l.eq_ignore_ascii_case(language))
+            })
+    }
+}
+
+/// The owner named by `gh:acme`, `github:acme`, a github.com URL or a bare
+/// `acme`
+pub fn parse_owner(target: &str) -> Option<&str> {
+    let owner = target
+        .strip_prefix("gh:")
+        .or_else(|| target.strip_prefix("github:"))
+        .or_else(|| target.strip_prefix("https://github.com/"))
+        .unwrap_or(target)
+        .trim_matches('/');
+    (!owner.is_empty() && !owner.contains(['/', ':'])).then_some(owner)
+}
+
+/// `owner/repo` names of an organization's (or user's) repositories,
+/// optionally only those tagged with `topic`. Archived repositories are left
+/// out. Works without credentials for public repositories.
+pub async fn list_owner_repositories(
+    owner: &str,
+    topic: Option<&str>,
+    retry: &RetrySettings,
+) -> Result<Vec<String>> {
+    Ok(owner_repositories(owner, retry)
+        .await?
+        .into_iter()
+        .filter(|repo| repo.matches(topic, None))
+        .map(|repo| repo.full_name)
+        .collect())
+}
+
+/// An organization's (or user's) unarchived repositories, sorted by name
+pub async fn owner_repositories(
+    owner: &str,
+    retry: &RetrySettings,
+) -> Result<Vec<OwnerRepository>> {
+    let client = reqwest::Client::builder()
+        .user_agent("iMi-Project-Creator/0.1.0")
+        .timeout(std::time::Duration::from_secs(30))
+        .build()
+        .context("Failed to create HTTP client")?;
+    let token = auth_token();
+
+    // Organizations and users have separate endpoints; try the org one first
+    let mut endpoint = format!("https://api.github.com/orgs/{}/repos", owner);
+    let mut repositories = Vec::new();
+    let mut page = 1;
+    loop {
+        let response = retry_async(retry, "GET /repos", || async {
+            let mut request = client
+                .get(endpoint.as_str())
+                .query(&[
+                    ("per_page", PER_PAGE.to_string()),
+                    ("page", page.to_string()),
+                ])
+                .header("Accept", "application/vnd.github+json");
+            if let Some(token) = &token {
+                request = request.header("Authorization", format!("Bearer {}", token));
+            }
+            request
+                .send()
+                .await
+                .context(format!("Failed to list repositories for {}", owner))
+        })
+        .await?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND && endpoint.contains("/orgs/") {
+            endpoint = format!("https://api.github.com/users/{}/repos", owner);
+            continue;
+        }
+
+        let repos: Vec<Value> = response
+            .error_for_status()
+            .context(format!("Failed to list repositories for {}", owner))?
+            .json()
+            .await
+            .context("Failed to parse repository list")?;
+
+        let count = repos.len();
+        repositories.extend(repos.iter().filter_map(OwnerRepository::from_api));
+        if count < PER_PAGE {
+            break;
+        }
+        page += 1;
+    }
+
+    repositories.sort_by(|a, b| a.full_name.cmp(&b.full_name));
+    Ok(repositories)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_owner_targets() {
+        assert_eq!(parse_owner("gh:acme"), Some("acme"));
+        assert_eq!(parse_owner("github:acme"), Some("acme"));
+        assert_eq!(parse_owner("https://github.com/acme/"), Some("acme"));
+        assert_eq!(parse_owner("acme"), Some("acme"));
+        assert_eq!(parse_owner("gh:"), None);
+        assert_eq!(parse_owner("acme/api"), None);
+        assert_eq!(parse_owner("gl:acme"), None);
+    }
+
+    #[test]
+    fn filters_archived_repositories_by_topic_and_language() {
+        let parse = |value: Value| OwnerRepository::from_api(&value);
+        let tagged = parse(serde_json::json!({
+            "full_name": "acme/api", "language": "Rust", "topics": ["imi", "rust"]
+        }))
+        .unwrap();
+        let untagged =
+            parse(serde_json::json!({ "full_name": "acme/web", "language": null, "topics": [] }))
+                .unwrap();
+        let archived =
+            serde_json::json!({ "full_name": "acme/old", "archived": true, "topics": ["imi"] });
+
+        assert!(tagged.matches(Some("imi"), None));
+        assert!(tagged.matches(Some("IMI"), Some("rust")));
+        assert!(!tagged.matches(None, Some("go")));
+        assert!(untagged.matches(None, None));
+        assert!(!untagged.matches(Some("imi"), None));
+        assert!(!untagged.matches(None, Some("rust")));
+        assert_eq!(parse(archived), None);
+    }
+}
@@ -0,0 +1,352 @@
+//! Branch relationships among a repository's worktrees (`imi graph`)
+
+use anyhow::{Context, Result};
+use git2::{BranchType, Oid, Repository};
+use serde::Serialize;
+use std::fmt::Write;
+
+/// A worktree to place in the graph
+#[derive(Debug, Clone)]
+pub struct GraphInput {
+    pub worktree_name: String,
+    pub branch_name: String,
+    /// Worktree it was forked from, when recorded by `imi copy`
+    pub recorded_parent: Option<String>,
+    /// Commit its branch was created from, when recorded by `imi add`
+    pub recorded_base: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BranchNode {
+    pub worktree_name: String,
+    pub branch_name: String,
+    /// Worktree this branch is stacked on; None when it is based on trunk
+    pub parent: Option<String>,
+    /// Commits ahead of / behind the parent (or trunk)
+    pub ahead: usize,
+    pub behind: usize,
+    /// Every commit is already reachable from trunk
+    pub merged: bool,
+    /// The branch no longer exists locally
+    pub missing: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BranchGraph {
+    pub repo_name: String,
+    pub trunk_branch: String,
+    pub nodes: Vec<BranchNode>,
+}
+
+impl BranchGraph {
+    /// Resolve each worktree's base: its recorded fork parent, else the
+    /// closest unmerged worktree branch it contains, else the commit it was
+    /// created from when trunk never contained it (a release tag), else trunk
+    pub fn build(
+        repo: &Repository,
+        repo_name: &str,
+        trunk_branch: &str,
+        inputs: Vec<GraphInput>,
+    ) -> Result<Self> {
+        let trunk = repo
+            .find_branch(&format!("origin/{}", trunk_branch), BranchType::Remote)
+            .or_else(|_| repo.find_branch(trunk_branch, BranchType::Local))
+            .context(format!("Trunk branch '{}' not found", trunk_branch))?
+            .get()
+            .target()
+            .context("Trunk branch has no target")?;
+
+        let tips: Vec<Option<Oid>> = inputs
+            .iter()
+            .map(|input| {
+                repo.find_branch(&input.branch_name, BranchType::Local)
+                    .ok()
+                    .and_then(|branch| branch.get().target())
+            })
+            .collect();
+        let merged: Vec<bool> = tips
+            .iter()
+            .map(|tip| tip.is_some_and(|tip| is_ancestor(repo, tip, trunk)))
+            .collect();
+        let ahead_of_trunk: Vec<usize> = tips
+            .iter()
+            .map(|tip| {
+                tip.and_then(|tip| repo.graph_ahead_behind(tip, trunk).ok())
+                    .map_or(0, |(ahead, _)| ahead)
+            })
+            .collect();
+
+        let mut nodes = Vec::with_capacity(inputs.len());
+        for (i, input) in inputs.iter().enumerate() {
+            let Some(tip) = tips[i] else {
+                nodes.push(BranchNode {
+                    worktree_name: input.worktree_name.clone(),
+                    branch_name: input.branch_name.clone(),
+                    parent: None,
+                    ahead: 0,
+                    behind: 0,
+                    merged: false,
+                    missing: true,
+                });
+                continue;
+            };
+
+            let recorded = input.recorded_parent.as_ref().and_then(|parent| {
+                inputs
+                    .iter()
+                    .position(|other| &other.worktree_name == parent)
+                    .filter(|&j| j != i && tips[j].is_some())
+            });
+            let parent = recorded.or_else(|| {
+                (0..inputs.len())
+                    .filter(|&j| j != i && !merged[j])
+                    .filter(|&j| {
+                        tips[j].is_some_and(|other| other != tip && is_ancestor(repo, other, tip))
+                    })
+                    .max_by_key(|&j| ahead_of_trunk[j])
+            });
+
+            let recorded_base = input
+                .recorded_base
+                .as_deref()
+                .and_then(|sha| Oid::from_str(sha).ok())
+                .filter(|&base| repo.find_commit(base).is_ok() && !is_ancestor(repo, base, trunk));
+            let base = parent
+                .and_then(|j| tips[j])
+                .or(recorded_base)
+                .unwrap_or(trunk);
+            let (ahead, behind) = repo.graph_ahead_behind(tip, base)?;
+
+            nodes.push(BranchNode {
+                worktree_name: input.worktree_name.clone(),
+                branch_name: input.branch_name.clone(),
+                parent: parent.map(|j| inputs[j].worktree_name.clone()),
+                ahead,
+                behind,
+                merged: merged[i],
+                missing: false,
+            });
+        }
+
+        Ok(Self {
+            repo_name: repo_name.to_string(),
+            trunk_branch: trunk_branch.to_string(),
+            nodes,
+        })
+    }
+
+    /// Nodes stacked directly on `parent` (None for trunk), by name
+    fn children(&self, parent: Option<&str>) -> Vec<&BranchNode> {
+        let mut children: Vec<&BranchNode> = self
+            .nodes
+            .iter()
+            .filter(|node| node.parent.as_deref() == parent)
+            .collect();
+        children.sort_by(|a, b| a.worktree_name.cmp(&b.worktree_name));
+        children
+    }
+
+    /// Tree rendering for the terminal
+    pub fn to_ascii(&self) -> String {
+        let mut out = format!("{} (trunk)\n", self.trunk_branch);
+        self.write_ascii(&mut out, None, "");
+        out
+    }
+
+    fn write_ascii(&self, out: &mut String, parent: Option<&str>, prefix: &str) {
+        let children = self.children(parent);
+        for (index, node) in children.iter().enumerate() {
+            let last = index + 1 == children.len();
+            let _ = writeln!(
+                out,
+                "{}{} {}  {}  {}",
+                prefix,
+                if last { "└──" } else { "├──" },
+                node.worktree_name,
+                node.branch_name,
+                node.status()
+            );
+            let child_prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+            self.write_ascii(out, Some(&node.worktree_name), &child_prefix);
+        }
+    }
+
+    /// Graphviz DOT, e.g. `imi graph --dot | dot -Tsvg > graph.svg`
+    pub fn to_dot(&self) -> String {
+        let mut out = format!("digraph {} {{\n", quote(&self.repo_name));
+        out.push_str("    rankdir=LR;\n    node [shape=box, style=rounded];\n");
+        let _ = writeln!(
+            out,
+            "    trunk [label={}, shape=box, style=\"bold,rounded\"];",
+            quote(&self.trunk_branch)
+        );
+
+        for node in &self.nodes {
+            let color = if node.missing {
+                "gray"
+            } else if node.merged {
+                "darkgreen"
+            } else {
+                "black"
+            };
+            let label = format!(
+                "{}\n{}\n{}",
+                node.worktree_name,
+                node.branch_name,
+                node.status()
+            );
+            let _ = writeln!(
+                out,
+                "    {} [label={}, color={}];",
+                quote(&node.worktree_name),
+                quote(&label),
+                color
+            );
+        }
+
+        for node in &self.nodes {
+            let from = node
+                .parent
+                .as_deref()
+                .map(quote)
+                .unwrap_or_else(|| "trunk".to_string());
+            let _ = writeln!(out, "    {} -> {};", from, quote(&node.worktree_name));
+        }
+
+        out.push_str("}\n");
+        out
+    }
+}
+
+impl BranchNode {
+    fn status(&self) -> String {
+        if self.missing {
+            "branch missing".to_string()
+        } else if self.merged {
+            "merged".to_string()
+        } else {
+            format!("+{} -{}", self.ahead, self.behind)
+        }
+    }
+}
+
+/// True when `ancestor` is reachable from `commit` (or is the same commit)
+fn is_ancestor(repo: &Repository, ancestor: Oid, commit: Oid) -> bool {
+    ancestor == commit || repo.graph_descendant_of(commit, ancestor).unwrap_or(false)
+}
+
+fn quote(text: &str) -> String {
+    format!(
+        "\"{}\"",
+        text.replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(repo: &Repository, branch: &str, parent: Option<Oid>, message: &str) -> Oid {
+        let signature = git2::Signature::now("iMi", "imi@example.com").unwrap();
+        let tree = repo
+            .find_tree(repo.treebuilder(None).unwrap().write().unwrap())
+            .unwrap();
+        let parents: Vec<git2::Commit> = parent
+            .map(|oid| repo.find_commit(oid).unwrap())
+            .into_iter()
+            .collect();
+        let parent_refs: Vec<&git2::Commit> = parents.iter().collect();
+        let oid = repo
+            .commit(None, &signature, &signature, message, &tree, &parent_refs)
+            .unwrap();
+        repo.branch(branch, &repo.find_commit(oid).unwrap(), true)
+            .unwrap();
+        oid
+    }
+
+    fn input(worktree: &str, branch: &str) -> GraphInput {
+        GraphInput {
+            worktree_name: worktree.to_string(),
+            branch_name: branch.to_string(),
+            recorded_parent: None,
+            recorded_base: None,
+        }
+    }
+
+    #[test]
+    fn infers_stacked_branches_and_merge_status() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+
+        let root = commit(&repo, "main", None, "root");
+        let auth = commit(&repo, "feat/auth", Some(root), "auth");
+        commit(&repo, "feat/auth-ui", Some(auth), "auth ui");
+        commit(&repo, "fix/typo", Some(root), "typo");
+        let main = commit(&repo, "main", Some(root), "trunk moves on");
+        repo.branch("fix/done", &repo.find_commit(main).unwrap(), true)
+            .unwrap();
+
+        let graph = BranchGraph::build(
+            &repo,
+            "iMi",
+            "main",
+            vec![
+                input("feat-auth", "feat/auth"),
+                input("feat-auth-ui", "feat/auth-ui"),
+                input("fix-typo", "fix/typo"),
+                input("fix-done", "fix/done"),
+                input("fix-gone", "fix/gone"),
+            ],
+        )
+        .unwrap();
+
+        let node = |name: &str| {
+            graph
+                .nodes
+                .iter()
+                .find(|n| n.worktree_name == name)
+                .unwrap()
+        };
+        assert_eq!(node("feat-auth-ui").parent.as_deref(), Some("feat-auth"));
+        assert_eq!(
+            (node("feat-auth-ui").ahead, node("feat-auth-ui").behind),
+            (1, 0)
+        );
+        assert_eq!(node("feat-auth").parent, None);
+        assert_eq!((node("fix-typo").ahead, node("fix-typo").behind), (1, 1));
+        assert!(node("fix-done").merged);
+        assert!(node("fix-gone").missing);
+
+        assert_eq!(
+            graph.to_ascii(),
+            "main (trunk)\n\
+             ├── feat-auth  feat/auth  +1 -1\n\
+             │   └── feat-auth-ui  feat/auth-ui  +1 -0\n\
+             ├── fix-done  fix/done  merged\n\
+             ├── fix-gone  fix/gone  branch missing\n\
+             └── fix-typo  fix/typo  +1 -1\n"
+        );
+        assert!(graph
+            .to_dot()
+            .contains("\"feat-auth\" -> \"feat-auth-ui\";"));
+    }
+
+    #[test]
+    fn measures_hotfixes_against_their_recorded_base() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+
+        let root = commit(&repo, "main", None, "root");
+        let release = commit(&repo, "release/2.3", Some(root), "v2.3.1");
+        commit(&repo, "fix/hotfix-1", Some(release), "hotfix");
+        commit(&repo, "main", Some(root), "trunk moves on");
+
+        let mut hotfix = input("fix-hotfix-1", "fix/hotfix-1");
+        hotfix.recorded_base = Some(release.to_string());
+        let graph = BranchGraph::build(&repo, "iMi", "main", vec![hotfix]).unwrap();
+
+        assert_eq!((graph.nodes[0].ahead, graph.nodes[0].behind), (1, 0));
+    }
+}
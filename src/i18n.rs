@@ -0,0 +1,188 @@
+//! Translated user-facing messages. A message's English format string is its
+//! key: `tr!("{} Removed {}", icon, name)` looks the string up in the current
+//! locale's catalog and fills in the `{}` placeholders (`{0}`, `{1}`, ... in a
+//! translation put them in another order). Catalogs are TOML files mapping
+//! the English string to its translation, in `locales/<locale>.toml` next to
+//! the global config; anything a catalog leaves out stays in English.
+
+use std::collections::HashMap;
+use std::fmt::{Display, Write};
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::config::Config;
+
+/// Translate and format a message, like `format!` with a literal format
+/// string whose placeholders are all plain `{}`
+macro_rules! tr {
+    ($message:literal $(,)?) => {
+        $crate::i18n::translate($message, &[])
+    };
+    ($message:literal, $($arg:expr),+ $(,)?) => {
+        $crate::i18n::translate($message, &[$(&$arg as &dyn std::fmt::Display),+])
+    };
+}
+#[allow(unused_imports)]
+pub(crate) use tr;
+
+#[derive(Debug, Clone, Default)]
+pub struct Catalog {
+    messages: HashMap<String, String>,
+}
+
+impl Catalog {
+    /// The messages for `locale` from `dir`: `pt.toml`, then `pt_BR.toml`
+    /// over it for pt_BR. Files that are missing or don't parse are skipped
+    pub fn load(dir: &Path, locale: &str) -> Self {
+        let mut messages = HashMap::new();
+        for name in candidates(locale) {
+            let Ok(text) = std::fs::read_to_string(dir.join(format!("{}.toml", name))) else {
+                continue;
+            };
+            match toml::from_str::<HashMap<String, String>>(&text) {
+                Ok(entries) => messages.extend(entries),
+                Err(e) => eprintln!("⚠️ Ignoring the {} message catalog: {}", name, e),
+            }
+        }
+        Self { messages }
+    }
+
+    pub fn lookup<'a>(&'a self, message: &'a str) -> &'a str {
+        self.messages
+            .get(message)
+            .map(String::as_str)
+            .filter(|translation| !translation.is_empty())
+            .unwrap_or(message)
+    }
+}
+
+/// The locale asked for: IMI_LOCALE, `locale` in the global config, then the
+/// usual LC_ALL, LC_MESSAGES and LANG
+pub fn requested_locale(configured: Option<&str>) -> Option<String> {
+    let from_env = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+    from_env("IMI_LOCALE")
+        .or_else(|| configured.map(str::to_string))
+        .or_else(|| {
+            ["LC_ALL", "LC_MESSAGES", "LANG"]
+                .into_iter()
+                .find_map(from_env)
+        })
+}
+
+/// Catalog names for a locale, most general first: "pt_BR.UTF-8" gives
+/// ["pt", "pt_BR"]; the C and POSIX locales have none
+pub fn candidates(locale: &str) -> Vec<String> {
+    let locale = locale.split(['.', '@']).next().unwrap_or("").trim();
+    let locale = locale.replace('-', "_");
+    if locale.is_empty() || locale == "C" || locale == "POSIX" {
+        return Vec::new();
+    }
+    match locale.split_once('_') {
+        Some((language, _)) => vec![language.to_string(), locale.clone()],
+        None => vec![locale],
+    }
+}
+
+fn catalog() -> &'static Catalog {
+    static CATALOG: OnceLock<Catalog> = OnceLock::new();
+    CATALOG.get_or_init(|| {
+        let Ok(config_path) = Config::get_global_config_path() else {
+            return Catalog::default();
+        };
+        // Read directly rather than through Config::load, which is async and
+        // fails on configs that messages may be reporting about
+        let configured = std::fs::read_to_string(&config_path)
+            .ok()
+            .and_then(|text| text.parse::<toml::Table>().ok())
+            .and_then(|table| table.get("locale")?.as_str().map(str::to_string));
+        match requested_locale(configured.as_deref()) {
+            Some(locale) => Catalog::load(&config_path.with_file_name("locales"), &locale),
+            None => Catalog::default(),
+        }
+    })
+}
+
+/// `message` in the current locale with `args` filled in
+pub fn translate(message: &str, args: &[&dyn Display]) -> String {
+    render(catalog().lookup(message), args)
+}
+
+/// Fill `{}` placeholders in order and `{N}` by position; `{{` and `}}` are
+/// literal braces. A placeholder without an argument is kept as written
+pub fn render(template: &str, args: &[&dyn Display]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut next = 0;
+    let mut rest = template;
+    while let Some(i) = rest.find(['{', '}']) {
+        out.push_str(&rest[..i]);
+        rest = &rest[i..];
+        if rest.starts_with("{{") || rest.starts_with("}}") {
+            out.push_str(&rest[..1]);
+            rest = &rest[2..];
+            continue;
+        }
+        let placeholder = rest
+            .strip_prefix('{')
+            .and_then(|inner| inner.find('}').map(|end| &inner[..end]))
+            .filter(|index| index.chars().all(|c| c.is_ascii_digit()));
+        let Some(index) = placeholder else {
+            out.push_str(&rest[..1]);
+            rest = &rest[1..];
+            continue;
+        };
+        let position = match index {
+            "" => {
+                next += 1;
+                next - 1
+            }
+            index => index.parse().unwrap_or(usize::MAX),
+        };
+        match args.get(position) {
+            Some(arg) => {
+                let _ = write!(out, "{}", arg);
+            }
+            None => out.push_str(&rest[..index.len() + 2]),
+        }
+        rest = &rest[index.len() + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_translations_from_catalogs() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("pt.toml"),
+            r#""{} Removed {}" = "{} {} removido"
+"Operation completed" = "Operação concluída""#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("pt_BR.toml"),
+            r#""Operation completed" = "Operação finalizada""#,
+        )
+        .unwrap();
+
+        let catalog = Catalog::load(dir.path(), "pt_BR.UTF-8");
+        assert_eq!(catalog.lookup("Operation completed"), "Operação finalizada");
+        assert_eq!(catalog.lookup("Not translated"), "Not translated");
+        assert_eq!(
+            render(catalog.lookup("{} Removed {}"), &[&"✅", &"feat-login"]),
+            "✅ feat-login removido"
+        );
+
+        // Reordered, escaped and missing placeholders
+        assert_eq!(render("{1} before {0}", &[&"a", &"b"]), "b before a");
+        assert_eq!(render("{{literal}} {}", &[&3]), "{literal} 3");
+        assert_eq!(render("{} and {}", &[&1]), "1 and {}");
+
+        assert_eq!(candidates("de"), ["de"]);
+        assert_eq!(candidates("pt-BR"), ["pt", "pt_BR"]);
+        assert!(candidates("C.UTF-8").is_empty());
+    }
+}
@@ -1,16 +1,25 @@
 use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
 use colored::*;
 use dialoguer::{Confirm, Select};
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::env;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use tokio::fs;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
 use uuid::Uuid;
 
+use crate::cancel;
 use crate::config::Config;
 use crate::database::Database;
+use crate::error::ImiError;
 use crate::git::GitManager;
+use crate::progress;
+use crate::remote_url::{normalize_remote_url, RemoteUrl};
+use crate::type_prefixes::TypePrefixes;
 
 #[derive(Debug, Clone)]
 pub struct InitResult {
@@ -46,6 +55,85 @@ pub struct OfficeMigrationResult {
     pub warnings: Vec<String>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CloneStatus {
+    Cloned,
+    /// Cloned by an earlier, interrupted run; only registered this time
+    Resumed,
+    Skipped,
+    Failed,
+}
+
+/// Per-repository result of [`InitCommand::clone_many`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CloneOutcome {
+    pub repo: String,
+    pub status: CloneStatus,
+    pub path: Option<PathBuf>,
+    pub message: String,
+}
+
+impl CloneOutcome {
+    pub fn print(&self) {
+        let (icon, label) = match self.status {
+            CloneStatus::Cloned => ("✅".bright_green(), "cloned".bright_green()),
+            CloneStatus::Resumed => ("✅".bright_green(), "registered".bright_green()),
+            CloneStatus::Skipped => ("⏭️".bright_black(), "skipped".bright_black()),
+            CloneStatus::Failed => ("❌".bright_red(), "failed".bright_red()),
+        };
+        println!("{} {} {}", icon, self.repo.bright_white(), label);
+        if matches!(self.status, CloneStatus::Failed | CloneStatus::Skipped) {
+            let first_line = self.message.lines().next().unwrap_or_default();
+            println!("   {}", first_line.bright_black());
+        }
+    }
+}
+
+/// Shallow and partial clone options for `imi clone` and `imi init
+/// owner/repo`, passed straight to `git clone`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CloneOptions {
+    /// `--depth N`: only the last N commits
+    pub depth: Option<u32>,
+    /// `--filter`, e.g. `blob:none` to fetch file contents on demand
+    pub filter: Option<String>,
+    /// `--single-branch`: only the default branch
+    pub single_branch: bool,
+}
+
+impl CloneOptions {
+    fn git_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(depth) = self.depth {
+            args.push(format!("--depth={}", depth));
+        }
+        if let Some(filter) = &self.filter {
+            args.push(format!("--filter={}", filter));
+        }
+        if self.single_branch {
+            args.push("--single-branch".to_string());
+        }
+        args
+    }
+
+    /// What's recorded in the registry, e.g. `depth=1,single-branch`; None
+    /// for a full clone
+    pub fn mode(&self) -> Option<String> {
+        let mut parts = Vec::new();
+        if let Some(depth) = self.depth {
+            parts.push(format!("depth={}", depth));
+        }
+        if let Some(filter) = &self.filter {
+            parts.push(format!("filter={}", filter));
+        }
+        if self.single_branch {
+            parts.push("single-branch".to_string());
+        }
+        (!parts.is_empty()).then(|| parts.join(","))
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OfficeMigrationSummary {
     pub dry_run: bool,
@@ -68,16 +156,146 @@ pub struct ProjectMetadata {
     pub description: Option<String>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RemoteStatus {
+    Verified,
+    Unreachable,
+    /// Skipped with `--offline`
+    Offline,
+    NoRemote,
+}
+
+/// What init found out about `origin`; stored in the project metadata under
+/// `remote_verification`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteVerification {
+    pub status: RemoteStatus,
+    pub remote_url: String,
+    /// Branch origin's HEAD points at, when the remote answered
+    pub remote_default_branch: Option<String>,
+    /// What local refs suggested before asking the remote
+    pub local_default_branch: String,
+    pub checked_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl RemoteVerification {
+    fn new(status: RemoteStatus, remote_url: &str, local_default_branch: &str) -> Self {
+        Self {
+            status,
+            remote_url: remote_url.to_string(),
+            remote_default_branch: None,
+            local_default_branch: local_default_branch.to_string(),
+            checked_at: Utc::now(),
+            error: None,
+        }
+    }
+
+    /// The branch to register: the remote's answer wins over the local guess
+    pub fn default_branch(&self) -> &str {
+        self.remote_default_branch
+            .as_deref()
+            .unwrap_or(&self.local_default_branch)
+    }
+
+    /// Warn about anything that needs attention, with the steps to fix it
+    fn warn(&self, repo_path: &Path) {
+        let path = repo_path.display();
+        match self.status {
+            RemoteStatus::Unreachable => {
+                eprintln!(
+                    "{} Could not reach origin ({}): {}",
+                    "⚠️".bright_yellow(),
+                    self.remote_url,
+                    self.error.as_deref().unwrap_or("unknown error")
+                );
+                eprintln!(
+                    "   Registered with '{}' as the default branch, guessed from local refs. To fix:",
+                    self.local_default_branch
+                );
+                eprintln!("   • check the remote URL: git -C {} remote -v", path);
+                eprintln!(
+                    "   • point it at the right repository: git -C {} remote set-url origin <url>",
+                    path
+                );
+                eprintln!("   • check your credentials: ssh-add -l, or gh auth login");
+                eprintln!("   then run imi init --force again");
+            }
+            RemoteStatus::NoRemote => {
+                eprintln!(
+                    "{} No origin remote; '{}' as the default branch is a guess.",
+                    "⚠️".bright_yellow(),
+                    self.local_default_branch
+                );
+                eprintln!(
+                    "   Add one with git -C {} remote add origin <url>, then run imi init --force again",
+                    path
+                );
+            }
+            RemoteStatus::Verified => {
+                if let Some(remote_branch) = self
+                    .remote_default_branch
+                    .as_deref()
+                    .filter(|branch| *branch != self.local_default_branch)
+                {
+                    eprintln!(
+                        "{} origin's default branch is '{}', not '{}' as local refs suggested; registered '{}'.",
+                        "⚠️".bright_yellow(),
+                        remote_branch,
+                        self.local_default_branch,
+                        remote_branch
+                    );
+                    eprintln!(
+                        "   Update the local refs with git -C {} remote set-head origin --auto",
+                        path
+                    );
+                }
+            }
+            RemoteStatus::Offline => {}
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct InitCommand {
     pub force: bool,
     config: Config,
     db: Database,
+    quiet: bool,
+    offline: bool,
+    clone_options: CloneOptions,
 }
 
 impl InitCommand {
     pub fn new(force: bool, config: Config, db: Database) -> Self {
-        Self { force, config, db }
+        Self {
+            force,
+            config,
+            db,
+            quiet: false,
+            offline: false,
+            clone_options: CloneOptions::default(),
+        }
+    }
+
+    /// Suppress per-step progress output, e.g. while cloning in parallel
+    pub fn quiet(mut self, quiet: bool) -> Self {
+        self.quiet = quiet;
+        self
+    }
+
+    /// Register without contacting the remote to verify it
+    pub fn offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /// Clone shallow or partial instead of fetching the full history
+    pub fn clone_options(mut self, clone_options: CloneOptions) -> Self {
+        self.clone_options = clone_options;
+        self
     }
 
     pub async fn execute(&self, path: Option<&Path>) -> Result<InitResult> {
@@ -273,6 +491,7 @@ impl InitCommand {
                 (true, false) => {
                     if Self::paths_match(&source_trunk, &target_container) {
                         self.move_container_contents_to_trunk(
+                            &repo.name,
                             &target_container,
                             &target_trunk,
                             &tracked_worktree_names,
@@ -415,6 +634,7 @@ impl InitCommand {
 
     async fn move_container_contents_to_trunk(
         &self,
+        repo_name: &str,
         container: &Path,
         trunk_path: &Path,
         tracked_worktrees: &[String],
@@ -437,6 +657,8 @@ impl InitCommand {
             reserved_names.insert(name.to_string());
         }
 
+        let type_prefixes =
+            TypePrefixes::load(&self.db, &self.config.type_inference, repo_name).await;
         let mut entries = fs::read_dir(container).await?;
         while let Some(entry) = entries.next_entry().await? {
             let path = entry.path();
@@ -447,14 +669,7 @@ impl InitCommand {
                 continue;
             }
 
-            let looks_like_worktree = file_name_str.starts_with("feat-")
-                || file_name_str.starts_with("fix-")
-                || file_name_str.starts_with("aiops-")
-                || file_name_str.starts_with("devops-")
-                || file_name_str.starts_with("pr-")
-                || file_name_str.starts_with("review-");
-
-            if looks_like_worktree {
+            if type_prefixes.looks_like_worktree(&file_name_str) {
                 continue;
             }
 
@@ -844,40 +1059,59 @@ impl InitCommand {
             .get_remote_url(repo_path)
             .await
             .unwrap_or_default();
-        let default_branch = git_manager
+        let local_branch = git_manager
             .get_default_branch(repo_path)
             .await
             .unwrap_or_else(|_| "main".to_string());
+        let verification = self.verify_remote(repo_path, &remote_url, &local_branch);
+        let default_branch = verification.default_branch().to_string();
 
-        let project = self
+        let mut project = self
             .db
             .create_repository(
                 repo_name,
                 repo_path.to_str().unwrap(),
-                &remote_url,
+                &normalize_remote_url(&remote_url),
                 &default_branch,
             )
             .await?;
-        println!(
-            "{} Registered repository '{}' in the database.",
-            "✅".bright_green(),
-            repo_name
-        );
-        println!(
-            "   {} Project ID: {}",
-            "🔑".bright_black(),
-            project.id.to_string().bright_cyan()
-        );
+        // An already registered project keeps its old default branch above
+        let verified_branch = verification.remote_default_branch.as_deref();
+        self.db
+            .record_remote_verification(
+                &project.id,
+                verified_branch,
+                serde_json::to_value(&verification)?,
+            )
+            .await?;
+        if let Some(branch) = verified_branch {
+            project.default_branch = branch.to_string();
+        }
+        verification.warn(repo_path);
+        if !self.quiet {
+            println!(
+                "{} Registered repository '{}' in the database.",
+                "✅".bright_green(),
+                repo_name
+            );
+            println!(
+                "   {} Project ID: {}",
+                "🔑".bright_black(),
+                project.id.to_string().bright_cyan()
+            );
+        }
 
         let imi_dir = repo_path.parent().unwrap().join(".iMi");
         fs::create_dir_all(&imi_dir)
             .await
             .context("Failed to create .iMi directory")?;
-        println!(
-            "{} Created .iMi directory at {}",
-            "✅".bright_green(),
-            imi_dir.display()
-        );
+        if !self.quiet {
+            println!(
+                "{} Created .iMi directory at {}",
+                "✅".bright_green(),
+                imi_dir.display()
+            );
+        }
 
         // Write project metadata to .iMi/project.json for fast filesystem access
         let project_metadata = ProjectMetadata {
@@ -894,11 +1128,13 @@ impl InitCommand {
         fs::write(&project_json_path, json_content)
             .await
             .context("Failed to write project.json")?;
-        println!(
-            "{} Created project.json with UUID {}",
-            "✅".bright_green(),
-            project.id.to_string().bright_cyan()
-        );
+        if !self.quiet {
+            println!(
+                "{} Created project.json with UUID {}",
+                "✅".bright_green(),
+                project.id.to_string().bright_cyan()
+            );
+        }
 
         Ok(InitResult::success(format!(
             "Successfully initialized iMi for repository '{}'.",
@@ -906,6 +1142,32 @@ impl InitCommand {
         )))
     }
 
+    /// Ask origin for its default branch, which also proves it is reachable
+    fn verify_remote(
+        &self,
+        repo_path: &Path,
+        remote_url: &str,
+        local_branch: &str,
+    ) -> RemoteVerification {
+        if remote_url.is_empty() {
+            return RemoteVerification::new(RemoteStatus::NoRemote, remote_url, local_branch);
+        }
+        if self.offline {
+            return RemoteVerification::new(RemoteStatus::Offline, remote_url, local_branch);
+        }
+
+        match GitManager::from_config(&self.config).remote_default_branch(repo_path) {
+            Ok(remote_branch) => RemoteVerification {
+                remote_default_branch: remote_branch,
+                ..RemoteVerification::new(RemoteStatus::Verified, remote_url, local_branch)
+            },
+            Err(e) => RemoteVerification {
+                error: Some(e.to_string()),
+                ..RemoteVerification::new(RemoteStatus::Unreachable, remote_url, local_branch)
+            },
+        }
+    }
+
     fn validate_office_layout(&self, repo_path: &Path, repo_name: &str) -> Result<()> {
         let expected_container = self.config.get_repo_path(repo_name);
         let expected_trunk_name = format!("trunk-{}", self.config.git_settings.default_branch);
@@ -948,33 +1210,18 @@ impl InitCommand {
         Ok((imi_path, repo_name))
     }
 
-    /// Clone a repository from GitHub and set up iMi structure
+    /// Clone a repository (GitHub `owner/repo` or any remote URL) and set
+    /// up iMi structure
     pub async fn clone_from_github(&self, github_repo: &str) -> Result<InitResult> {
         println!(
-            "{} Cloning {} from GitHub...",
+            "{} Cloning {}...",
             "🔍".bright_cyan(),
             github_repo.bright_white()
         );
 
-        // Extract repo name from owner/repo format
-        let parts: Vec<&str> = github_repo.split('/').collect();
-        if parts.len() != 2 {
-            return Err(anyhow!(
-                "Invalid GitHub repository format. Expected: owner/repo"
-            ));
-        }
-
-        let repo_name = parts[1];
-
-        // Determine clone location in the entity office
-        let entity_workspace = self.config.get_entity_workspace_path();
-        fs::create_dir_all(&entity_workspace)
-            .await
-            .context("Failed to create entity workspace directory")?;
-
-        let trunk_dir = format!("trunk-{}", self.config.git_settings.default_branch);
-        let repo_container = entity_workspace.join(repo_name);
-        let trunk_path = repo_container.join(&trunk_dir);
+        let source = CloneSource::parse(github_repo)?;
+        let repo_name = source.name.as_str();
+        let trunk_path = self.config.get_trunk_path(repo_name);
 
         // Check if already exists
         if trunk_path.exists() {
@@ -985,38 +1232,268 @@ impl InitCommand {
             ));
         }
 
-        // Create container directory
-        fs::create_dir_all(&repo_container)
-            .await
-            .context("Failed to create repository container")?;
-
-        // Clone the repository using git command
-        let git_url = format!("https://github.com/{}.git", github_repo);
-
         println!(
             "{} Cloning into {}...",
             "📁".bright_blue(),
             trunk_path.display().to_string().bright_white()
         );
 
-        let output = tokio::process::Command::new("git")
-            .args(&["clone", &git_url, trunk_path.to_str().unwrap()])
-            .output()
+        self.clone_into_trunk(&source, &trunk_path).await?;
+
+        println!("{} Clone complete!", "✅".bright_green());
+
+        // Now initialize iMi in the cloned repository
+        let result = self.register_repository(&trunk_path, repo_name).await?;
+        if result.success {
+            self.record_clone_mode(repo_name).await?;
+        }
+        Ok(result)
+    }
+
+    async fn record_clone_mode(&self, repo_name: &str) -> Result<()> {
+        if let Some(project) = self.db.get_repository(repo_name).await? {
+            self.db
+                .set_repository_clone_mode(&project.id, self.clone_options.mode().as_deref())
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Clone several GitHub repositories in parallel, at most `jobs` at a
+    /// time, reporting each one as it finishes. Rerunning after an
+    /// interruption resumes: registered clones are skipped and clones that
+    /// finished but were never registered are registered without cloning again.
+    pub async fn clone_many(&self, repos: &[String], jobs: usize) -> Vec<CloneOutcome> {
+        let permits = Arc::new(Semaphore::new(jobs.max(1)));
+        let mut tasks = JoinSet::new();
+        for (index, github_repo) in repos.iter().enumerate() {
+            let init = self.clone().quiet(true);
+            let (permits, github_repo) = (permits.clone(), github_repo.clone());
+            tasks.spawn(async move {
+                let _permit = permits.acquire().await;
+                (index, init.clone_one(&github_repo).await)
+            });
+        }
+
+        let mut outcomes: Vec<CloneOutcome> = repos
+            .iter()
+            .map(|repo| CloneOutcome {
+                repo: repo.clone(),
+                status: CloneStatus::Failed,
+                path: None,
+                message: "Clone task did not finish".to_string(),
+            })
+            .collect();
+        let mut finished = 0;
+        while let Some(joined) = tasks.join_next().await {
+            if let Ok((index, outcome)) = joined {
+                if !self.quiet {
+                    outcome.print();
+                }
+                finished += 1;
+                progress::emit(
+                    "clone",
+                    Some(progress::percent(finished, repos.len())),
+                    &format!(
+                        "{} {}",
+                        outcome.repo,
+                        format!("{:?}", outcome.status).to_lowercase()
+                    ),
+                );
+                outcomes[index] = outcome;
+            }
+        }
+        outcomes
+    }
+
+    async fn clone_one(&self, github_repo: &str) -> CloneOutcome {
+        let mut outcome = CloneOutcome {
+            repo: github_repo.to_string(),
+            status: CloneStatus::Failed,
+            path: None,
+            message: String::new(),
+        };
+        let result = async {
+            cancel::check().context("Not started")?;
+            let source = CloneSource::parse(github_repo)?;
+            let repo_name = source.name.as_str();
+            let trunk_path = self.config.get_trunk_path(repo_name);
+            outcome.path = Some(trunk_path.clone());
+
+            let registered = self.db.get_repository(repo_name).await?.is_some();
+            let status = if trunk_path.join(".git").exists() {
+                if registered && !self.force {
+                    return Ok((
+                        CloneStatus::Skipped,
+                        "Already cloned and registered".to_string(),
+                    ));
+                }
+                CloneStatus::Resumed
+            } else {
+                self.clone_into_trunk(&source, &trunk_path).await?;
+                CloneStatus::Cloned
+            };
+
+            let result = self.register_repository(&trunk_path, repo_name).await?;
+            if !result.success {
+                return Err(anyhow!(result.message));
+            }
+            if status == CloneStatus::Cloned {
+                self.record_clone_mode(repo_name).await?;
+            }
+            Ok((status, result.message))
+        }
+        .await;
+
+        match result {
+            Ok((status, message)) => {
+                outcome.status = status;
+                outcome.message = message;
+            }
+            Err(e) => outcome.message = format!("{:#}", e),
+        }
+        outcome
+    }
+
+    /// Clone into a staging directory next to the trunk and rename it into
+    /// place, so an interrupted clone never leaves a half-populated trunk
+    async fn clone_into_trunk(&self, source: &CloneSource, trunk_path: &Path) -> Result<()> {
+        let repo_container = trunk_path
+            .parent()
+            .context("Trunk path has no parent directory")?;
+        let trunk_dir = trunk_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .context("Trunk path has no directory name")?;
+        fs::create_dir_all(repo_container)
             .await
-            .context("Failed to execute git clone")?;
+            .context("Failed to create repository container")?;
+
+        let staging = repo_container.join(format!(".{}.partial", trunk_dir));
+        if staging.exists() {
+            fs::remove_dir_all(&staging)
+                .await
+                .context("Failed to remove an interrupted clone")?;
+        }
+
+        // Clone the repository using git command
+        let output = if progress::enabled() {
+            clone_reporting_progress(&self.clone_options, source, &staging).await
+        } else {
+            tokio::process::Command::new("git")
+                .arg("clone")
+                .args(self.clone_options.git_args())
+                .arg(&source.url)
+                .arg(&staging)
+                .output()
+                .await
+        }
+        .context("Failed to execute git clone")?;
 
         if !output.status.success() {
+            let _ = fs::remove_dir_all(&staging).await;
+            // Ctrl-C reached git too; the staging directory is all it left
+            if cancel::requested() {
+                return Err(ImiError::Cancelled.into());
+            }
             let stderr = String::from_utf8_lossy(&output.stderr);
             return Err(anyhow!(
-                "Git clone failed: {}\n\nThis might be a private repository. Try:\n  1. Check that {} exists on GitHub\n  2. Ensure you're authenticated (gh auth login or SSH keys)",
+                "Git clone failed: {}\n\nThis might be a private repository. Try:\n  1. Check that {} exists\n  2. Ensure you're authenticated (gh auth login or SSH keys)",
                 stderr,
-                github_repo
+                source.url
             ));
         }
 
-        println!("{} Clone complete!", "✅".bright_green());
+        fs::rename(&staging, trunk_path)
+            .await
+            .context("Failed to move the clone into place")
+    }
+}
 
-        // Now initialize iMi in the cloned repository
-        self.register_repository(&trunk_path, repo_name).await
+/// `git clone --progress`, turning each stage's percentage into a
+/// `--json-stream` event as git reports it
+async fn clone_reporting_progress(
+    options: &CloneOptions,
+    source: &CloneSource,
+    staging: &Path,
+) -> std::io::Result<std::process::Output> {
+    use tokio::io::AsyncReadExt;
+
+    let mut child = tokio::process::Command::new("git")
+        .arg("clone")
+        .arg("--progress")
+        .args(options.git_args())
+        .arg(&source.url)
+        .arg(staging)
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()?;
+
+    let mut stderr = child.stderr.take().expect("stderr is piped");
+    let mut collected = Vec::new();
+    let mut buffer = [0u8; 4096];
+    let mut line = String::new();
+    let mut last: Option<(String, u8)> = None;
+    loop {
+        let read = stderr.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        collected.extend_from_slice(&buffer[..read]);
+        // git redraws its progress with carriage returns
+        for c in String::from_utf8_lossy(&buffer[..read]).chars() {
+            if c != '\r' && c != '\n' {
+                line.push(c);
+                continue;
+            }
+            if let Some((stage, percent)) = progress::parse_git_progress(&line) {
+                if last.as_ref() != Some(&(stage.to_string(), percent)) {
+                    progress::emit(&stage.to_lowercase(), Some(percent), &source.name);
+                    last = Some((stage.to_string(), percent));
+                }
+            }
+            line.clear();
+        }
+    }
+
+    let mut output = child.wait_with_output().await?;
+    output.stderr = collected;
+    Ok(output)
+}
+
+/// What `imi init`/`imi clone` clones: a remote URL in any form git takes
+/// (GitLab subgroups included) is cloned as given, `owner/repo` from GitHub
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CloneSource {
+    /// Repository name the trunk and registration use
+    pub name: String,
+    pub url: String,
+}
+
+impl CloneSource {
+    pub fn parse(repo: &str) -> Result<Self> {
+        if let Some(remote) = RemoteUrl::parse(repo).filter(|remote| remote.host.is_some()) {
+            return Ok(Self {
+                name: remote.name,
+                url: repo.to_string(),
+            });
+        }
+        match repo.split('/').collect::<Vec<_>>()[..] {
+            [owner, name] if !owner.is_empty() && !name.is_empty() => Ok(Self {
+                name: name.strip_suffix(".git").unwrap_or(name).to_string(),
+                url: format!("https://github.com/{}.git", repo.trim_end_matches(".git")),
+            }),
+            _ => Err(anyhow!(
+                "Invalid repository '{}'. Expected owner/repo or a git remote URL",
+                repo
+            )),
+        }
+    }
+
+    /// Whether an `imi init` argument names something to clone rather than
+    /// a local directory
+    pub fn is_remote(arg: &str) -> bool {
+        (arg.contains('/') && !arg.contains(':'))
+            || RemoteUrl::parse(arg).is_some_and(|remote| remote.host.is_some())
     }
 }
@@ -0,0 +1,422 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::config::Config;
+
+/// A `.json.tmp` older than this was torn by a crash mid-write; live
+/// writers rename theirs within milliseconds
+const STALE_TMP_AGE: Duration = Duration::from_secs(60);
+
+/// Whether process `pid` is still running on this machine. Where that can't
+/// be checked the process is assumed alive, so nothing is cleaned up early.
+pub fn process_alive(pid: u32) -> bool {
+    if cfg!(target_os = "linux") {
+        return Path::new(&format!("/proc/{}", pid)).exists();
+    }
+    Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .stderr(Stdio::null())
+        .status()
+        .map_or(true, |status| status.success())
+}
+
+/// Multi-step operations that are journaled so an interruption can be recovered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OperationKind {
+    Add,
+    Merge,
+}
+
+impl std::fmt::Display for OperationKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OperationKind::Add => write!(f, "add"),
+            OperationKind::Merge => write!(f, "merge"),
+        }
+    }
+}
+
+/// Step markers written as an operation progresses
+pub mod steps {
+    pub const GIT_WORKTREE: &str = "git_worktree";
+    pub const DB_RECORD: &str = "db_record";
+    pub const MERGED: &str = "merged";
+    pub const PUSHED: &str = "pushed";
+    pub const CLOSED: &str = "closed";
+}
+
+/// A write-ahead record of an in-flight operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub id: Uuid,
+    pub kind: OperationKind,
+    pub repo_name: String,
+    pub worktree_name: String,
+    pub branch_name: String,
+    pub worktree_type: String,
+    pub worktree_path: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_branch: Option<String>,
+    /// Trunk HEAD before a merge, used to undo an unpushed merge
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trunk_head_before: Option<String>,
+    /// Trunk branch a merge committed to, pushed again on resume
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trunk_branch: Option<String>,
+    pub completed_steps: Vec<String>,
+    pub pid: u32,
+    pub started_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl JournalEntry {
+    pub fn new(
+        kind: OperationKind,
+        repo_name: &str,
+        worktree_name: &str,
+        branch_name: &str,
+        worktree_type: &str,
+        worktree_path: &Path,
+    ) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            kind,
+            repo_name: repo_name.to_string(),
+            worktree_name: worktree_name.to_string(),
+            branch_name: branch_name.to_string(),
+            worktree_type: worktree_type.to_string(),
+            worktree_path: worktree_path.to_path_buf(),
+            base_branch: None,
+            trunk_head_before: None,
+            trunk_branch: None,
+            completed_steps: Vec::new(),
+            pid: std::process::id(),
+            started_at: now,
+            updated_at: now,
+        }
+    }
+
+    pub fn has_step(&self, step: &str) -> bool {
+        self.completed_steps.iter().any(|s| s == step)
+    }
+
+    /// True when the process that started the operation is no longer running
+    pub fn is_abandoned(&self) -> bool {
+        self.pid != std::process::id() && !process_alive(self.pid)
+    }
+
+    /// An add that died before its worktree directory appeared left nothing
+    /// behind to finish or undo
+    pub fn nothing_to_recover(&self) -> bool {
+        self.kind == OperationKind::Add
+            && self.completed_steps.is_empty()
+            && !self.worktree_path.exists()
+    }
+
+    pub fn progress(&self) -> String {
+        if self.completed_steps.is_empty() {
+            "no steps completed".to_string()
+        } else {
+            format!("completed: {}", self.completed_steps.join(", "))
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "{} {}/{} ({})",
+            self.kind,
+            self.repo_name,
+            self.worktree_name,
+            self.progress()
+        )
+    }
+}
+
+/// Directory of journal files, one JSON file per in-flight operation
+#[derive(Debug, Clone)]
+pub struct Journal {
+    dir: PathBuf,
+}
+
+impl Journal {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The journal that lives next to the global config file
+    pub fn open_default() -> Result<Self> {
+        let config_path = Config::get_global_config_path()?;
+        Ok(Self::new(config_path.with_file_name("journal")))
+    }
+
+    fn entry_path(&self, id: &Uuid) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+
+    /// Persist a new operation before any side effects happen
+    pub fn begin(&self, entry: JournalEntry) -> Result<OperationHandle> {
+        let handle = OperationHandle {
+            path: self.entry_path(&entry.id),
+            entry,
+        };
+        fs::create_dir_all(&self.dir).context("Failed to create journal directory")?;
+        handle.persist()?;
+        Ok(handle)
+    }
+
+    /// Resume tracking an existing entry (used by `imi resume`)
+    pub fn reopen(&self, entry: JournalEntry) -> OperationHandle {
+        OperationHandle {
+            path: self.entry_path(&entry.id),
+            entry,
+        }
+    }
+
+    /// All journaled operations, oldest first
+    pub fn list(&self) -> Result<Vec<JournalEntry>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut entries: Vec<JournalEntry> = fs::read_dir(&self.dir)
+            .context("Failed to read journal directory")?
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|e| fs::read_to_string(e.path()).ok())
+            .filter_map(|contents| serde_json::from_str(&contents).ok())
+            .collect();
+
+        entries.sort_by_key(|e| e.started_at);
+        Ok(entries)
+    }
+
+    /// Operations whose process died before finishing
+    pub fn incomplete(&self) -> Result<Vec<JournalEntry>> {
+        Ok(self
+            .list()?
+            .into_iter()
+            .filter(|e| e.is_abandoned())
+            .collect())
+    }
+
+    /// Find an incomplete operation by id prefix or worktree name
+    pub fn find_incomplete(&self, selector: Option<&str>) -> Result<Option<JournalEntry>> {
+        let mut candidates = self.incomplete()?;
+        match selector {
+            None => Ok(candidates.pop()),
+            Some(sel) => Ok(candidates
+                .into_iter()
+                .rev()
+                .find(|e| e.worktree_name == sel || e.id.to_string().starts_with(sel))),
+        }
+    }
+
+    /// Drop what crashed processes left that needs no recovery: adds that
+    /// stopped before creating anything and torn temp files. Returns the
+    /// entries removed.
+    pub fn clean_up_abandoned(&self) -> Result<Vec<JournalEntry>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        for entry in fs::read_dir(&self.dir)
+            .context("Failed to read journal directory")?
+            .filter_map(|e| e.ok())
+        {
+            let torn = entry.file_name().to_string_lossy().ends_with(".json.tmp")
+                && entry
+                    .metadata()
+                    .and_then(|m| m.modified())
+                    .is_ok_and(|modified| modified.elapsed().is_ok_and(|age| age > STALE_TMP_AGE));
+            if torn {
+                let _ = fs::remove_file(entry.path());
+            }
+        }
+
+        let removed: Vec<JournalEntry> = self
+            .incomplete()?
+            .into_iter()
+            .filter(|e| e.nothing_to_recover())
+            .collect();
+        for entry in &removed {
+            self.discard(&entry.id)?;
+        }
+        Ok(removed)
+    }
+
+    pub fn discard(&self, id: &Uuid) -> Result<()> {
+        let path = self.entry_path(id);
+        if path.exists() {
+            fs::remove_file(&path).context("Failed to remove journal entry")?;
+        }
+        Ok(())
+    }
+}
+
+/// Handle to a journaled operation; records steps as they complete
+#[derive(Debug)]
+pub struct OperationHandle {
+    path: PathBuf,
+    entry: JournalEntry,
+}
+
+impl OperationHandle {
+    pub fn step(&mut self, step: &str) -> Result<()> {
+        if !self.entry.has_step(step) {
+            self.entry.completed_steps.push(step.to_string());
+        }
+        self.persist()
+    }
+
+    /// What was recorded so far, e.g. to roll the operation back
+    pub fn into_entry(self) -> JournalEntry {
+        self.entry
+    }
+
+    /// The operation finished (or was fully undone); drop its journal entry
+    pub fn finish(self) -> Result<()> {
+        if self.path.exists() {
+            fs::remove_file(&self.path).context("Failed to remove journal entry")?;
+        }
+        Ok(())
+    }
+
+    fn persist(&self) -> Result<()> {
+        let mut entry = self.entry.clone();
+        entry.pid = std::process::id();
+        entry.updated_at = Utc::now();
+
+        // Write to a temp file and rename so a crash never leaves a torn entry
+        let tmp = self.path.with_extension("json.tmp");
+        let contents =
+            serde_json::to_string_pretty(&entry).context("Failed to serialize journal entry")?;
+        fs::write(&tmp, contents).context("Failed to write journal entry")?;
+        fs::rename(&tmp, &self.path).context("Failed to commit journal entry")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_entry() -> JournalEntry {
+        JournalEntry::new(
+            OperationKind::Add,
+            "iMi",
+            "feat-auth",
+            "feat/auth",
+            "feat",
+            Path::new("/tmp/iMi/feat-auth"),
+        )
+    }
+
+    #[test]
+    fn records_steps_and_finishes() {
+        let dir = tempfile::tempdir().unwrap();
+        let journal = Journal::new(dir.path());
+
+        let mut op = journal.begin(sample_entry()).unwrap();
+        op.step(steps::GIT_WORKTREE).unwrap();
+        op.step(steps::GIT_WORKTREE).unwrap();
+
+        let listed = journal.list().unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].completed_steps, vec![steps::GIT_WORKTREE]);
+
+        // Still owned by this process, so not considered interrupted
+        assert!(journal.incomplete().unwrap().is_empty());
+
+        op.finish().unwrap();
+        assert!(journal.list().unwrap().is_empty());
+    }
+
+    #[test]
+    fn merge_entries_remember_their_trunk_branch() {
+        let dir = tempfile::tempdir().unwrap();
+        let journal = Journal::new(dir.path());
+
+        let mut entry = sample_entry();
+        entry.kind = OperationKind::Merge;
+        entry.trunk_branch = Some("develop".to_string());
+        let _op = journal.begin(entry).unwrap();
+        assert_eq!(
+            journal.list().unwrap()[0].trunk_branch.as_deref(),
+            Some("develop")
+        );
+
+        // Entries written before the field existed still load
+        let mut value = serde_json::to_value(sample_entry()).unwrap();
+        value.as_object_mut().unwrap().remove("trunk_branch");
+        let old: JournalEntry = serde_json::from_value(value).unwrap();
+        assert_eq!(old.trunk_branch, None);
+    }
+
+    #[test]
+    fn entries_from_dead_processes_are_incomplete() {
+        let dir = tempfile::tempdir().unwrap();
+        let journal = Journal::new(dir.path());
+
+        let mut entry = sample_entry();
+        entry.pid = u32::MAX;
+        fs::write(
+            dir.path().join(format!("{}.json", entry.id)),
+            serde_json::to_string(&entry).unwrap(),
+        )
+        .unwrap();
+
+        let found = journal.find_incomplete(Some("feat-auth")).unwrap();
+        assert_eq!(found.map(|e| e.id), Some(entry.id));
+        assert!(journal.find_incomplete(Some("other")).unwrap().is_none());
+    }
+
+    #[test]
+    fn cleans_up_abandoned_entries_with_nothing_to_recover() {
+        let dir = tempfile::tempdir().unwrap();
+        let journal = Journal::new(dir.path());
+        let write = |entry: &JournalEntry| {
+            fs::write(
+                dir.path().join(format!("{}.json", entry.id)),
+                serde_json::to_string(entry).unwrap(),
+            )
+            .unwrap();
+        };
+
+        // Died before `git worktree add` created anything
+        let mut untouched = sample_entry();
+        untouched.pid = u32::MAX;
+        untouched.worktree_path = dir.path().join("never-created");
+        write(&untouched);
+
+        // Died after creating the worktree: needs resume or rollback
+        let mut started = sample_entry();
+        started.pid = u32::MAX;
+        started.worktree_path = dir.path().to_path_buf();
+        started
+            .completed_steps
+            .push(steps::GIT_WORKTREE.to_string());
+        write(&started);
+
+        // Still running in this process
+        let mut live = sample_entry();
+        live.worktree_path = dir.path().join("also-never-created");
+        write(&live);
+
+        let removed = journal.clean_up_abandoned().unwrap();
+        assert_eq!(
+            removed.iter().map(|e| e.id).collect::<Vec<_>>(),
+            vec![untouched.id]
+        );
+        let remaining: Vec<Uuid> = journal.list().unwrap().iter().map(|e| e.id).collect();
+        assert_eq!(remaining.len(), 2);
+        assert!(remaining.contains(&started.id) && remaining.contains(&live.id));
+    }
+}
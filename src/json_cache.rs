@@ -0,0 +1,55 @@
+//! Small JSON files kept between commands (disk usage, git stats, CI
+//! results, ...). A missing or unreadable file reads as empty: these only
+//! save work, so a corrupt one just starts over.
+
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// `name` in iMi's directory under the user cache directory
+pub fn cache_file(name: &str) -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("iMi").join(name))
+}
+
+/// The contents of `path`, or the default when it is missing or unreadable
+pub fn read<T: DeserializeOwned + Default>(path: Option<&Path>) -> T {
+    path.and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+/// Write `value` to `path`, creating its directory; nothing without a path
+pub fn write<T: Serialize>(path: Option<&Path>, value: &T) -> Result<()> {
+    let Some(path) = path else {
+        return Ok(());
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).context("Failed to create cache directory")?;
+    }
+    std::fs::write(path, serde_json::to_string(value)?)
+        .context(format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn missing_and_corrupt_files_read_as_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested/cache.json");
+        let empty: HashMap<String, u64> = read(Some(&path));
+        assert!(empty.is_empty());
+
+        write(Some(&path), &HashMap::from([("a".to_string(), 1u64)])).unwrap();
+        let loaded: HashMap<String, u64> = read(Some(&path));
+        assert_eq!(loaded["a"], 1);
+
+        std::fs::write(&path, "{not json").unwrap();
+        let corrupt: HashMap<String, u64> = read(Some(&path));
+        assert!(corrupt.is_empty());
+        assert!(write(None, &loaded).is_ok());
+    }
+}
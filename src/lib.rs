@@ -3,18 +3,62 @@
 //! A sophisticated worktree management tool designed for asynchronous,
 //! parallel multi-agent workflows with opinionated defaults and real-time visibility.
 
+pub mod agent_scaffold;
+pub mod alerts;
+pub mod artifacts;
+pub mod audit;
+pub mod backend;
+pub mod cache_clone;
+pub mod cancel;
+pub mod capabilities;
 pub mod cli;
 pub mod commands;
+pub mod commit_template;
 pub mod config;
+pub mod config_check;
 pub mod context;
+pub mod daemon;
 pub mod database;
+pub mod debug_bundle;
+pub mod deletion_safety;
+pub mod disk_usage;
+pub mod editor;
+pub mod environment;
 pub mod error;
+pub mod event_hooks;
+pub mod events;
+pub mod first_run;
+pub mod format;
 pub mod fuzzy;
 pub mod git;
+pub mod git_hooks;
 pub mod github;
+pub mod graph;
+pub mod i18n;
 pub mod init;
+pub mod journal;
+pub mod json_cache;
 pub mod local;
+pub mod maintenance;
+pub mod metrics;
 pub mod monitor;
+pub mod ops;
+pub mod path_remap;
+pub mod pending_delete;
+pub mod progress;
+pub mod rebase_batch;
+pub mod remote_url;
+pub mod repo_stats;
+pub mod retry;
+pub mod review_context;
+#[cfg(feature = "http")]
+pub mod server;
+pub mod sync_scaffold;
+pub mod timings;
+pub mod trash;
+pub mod trunk_protection;
+pub mod type_prefixes;
+pub mod type_sharing;
 pub mod worktree;
 
 // Re-export commonly used types
@@ -26,6 +70,7 @@ pub use database::{AgentActivity, Database, Repository, Worktree, WorktreeType};
 pub use error::ImiError;
 pub use git::{GitManager, WorktreeStatus};
 pub use init::{InitCommand, InitResult};
+pub use ops::Imi;
 pub use worktree::WorktreeManager;
 
 /// Version information
@@ -2,10 +2,18 @@ use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
+use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::thread;
 use std::time::Duration;
 
+use crate::journal::process_alive;
+
+/// A registry lock without a PID (written by older versions, or caught
+/// between create and write) is only stale once it's this old; the lock is
+/// normally held for milliseconds
+const STALE_REGISTRY_LOCK_AGE: Duration = Duration::from_secs(10);
+
 /// Manages the "Data Plane" (.iMi directory) for a specific project.
 /// Optimized for speed and shell consumption (Starship).
 pub struct LocalContext {
@@ -15,6 +23,8 @@ pub struct LocalContext {
     presence_dir: PathBuf,
     /// Path to .iMi/links/ (Source of truth for symlinks)
     links_dir: PathBuf,
+    /// Path to .iMi/artifacts/ (Output of `imi exec` and `imi check` runs)
+    artifacts_dir: PathBuf,
     /// Path to .iMi/registry.toml (Fast metadata cache)
     registry_file: PathBuf,
 }
@@ -41,6 +51,7 @@ impl LocalContext {
         Self {
             presence_dir: imi_dir.join("presence"),
             links_dir: imi_dir.join("links"),
+            artifacts_dir: imi_dir.join("artifacts"),
             registry_file: imi_dir.join("registry.toml"),
             imi_dir,
         }
@@ -75,14 +86,18 @@ impl LocalContext {
         while retries < 10 {
             // Try to create the lock file exclusively
             // This is atomic on most filesystems
-            if fs::OpenOptions::new()
+            if let Ok(mut file) = fs::OpenOptions::new()
                 .write(true)
                 .create_new(true)
                 .open(&lock_path)
-                .is_ok()
             {
+                // Record the holder so a crash can be told apart from contention
+                let _ = write!(file, "{}", std::process::id());
                 return Ok(());
             }
+            if self.clear_stale_registry_lock() {
+                continue;
+            }
             thread::sleep(Duration::from_millis(50));
             retries += 1;
         }
@@ -96,6 +111,45 @@ impl LocalContext {
         let _ = fs::remove_file(lock_path);
     }
 
+    /// Remove `registry.lock` if the process holding it is gone; returns
+    /// whether a stale lock was removed
+    pub fn clear_stale_registry_lock(&self) -> bool {
+        let lock_path = self.imi_dir.join("registry.lock");
+        let Ok(contents) = fs::read_to_string(&lock_path) else {
+            return false;
+        };
+        let stale = match contents.trim().parse::<u32>() {
+            Ok(pid) => pid != std::process::id() && !process_alive(pid),
+            Err(_) => fs::metadata(&lock_path)
+                .and_then(|m| m.modified())
+                .is_ok_and(|modified| {
+                    modified
+                        .elapsed()
+                        .is_ok_and(|age| age > STALE_REGISTRY_LOCK_AGE)
+                }),
+        };
+        stale && self.remove_registry_lock_if(&contents)
+    }
+
+    /// Remove `registry.lock` only if it still holds `seen`. The lock is
+    /// renamed away first, so of several processes clearing the same stale
+    /// lock one wins; a fresh lock taken in between is put back.
+    fn remove_registry_lock_if(&self, seen: &str) -> bool {
+        let lock_path = self.imi_dir.join("registry.lock");
+        let claimed = self
+            .imi_dir
+            .join(format!("registry.lock.stale-{}", std::process::id()));
+        if fs::rename(&lock_path, &claimed).is_err() {
+            return false;
+        }
+        let unchanged = fs::read_to_string(&claimed).is_ok_and(|now| now == seen);
+        if !unchanged {
+            let _ = fs::hard_link(&claimed, &lock_path);
+        }
+        let _ = fs::remove_file(&claimed);
+        unchanged
+    }
+
     /// Lock a worktree to signal active agent work
     /// Used by agents or long-running tasks to turn the prompt "Purple"
     pub fn lock_worktree(&self, worktree_name: &str, agent_id: &str) -> Result<()> {
@@ -198,6 +252,11 @@ impl LocalContext {
         &self.links_dir
     }
 
+    /// Get the path to the 'artifacts' directory holding captured run output
+    pub fn artifacts_path(&self) -> &Path {
+        &self.artifacts_dir
+    }
+
     /// Create a lock file with full metadata (for agent claim operations)
     /// Format: JSON with agent_id, claimed_at, hostname, worktree_id
     pub async fn create_lock_file(
@@ -257,3 +316,36 @@ impl LocalContext {
         Ok(data)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stale_registry_locks_are_taken_over() {
+        let dir = tempfile::tempdir().unwrap();
+        let ctx = LocalContext::new(dir.path());
+        ctx.init().unwrap();
+        let lock_path = dir.path().join(".iMi/registry.lock");
+
+        // Held by a live process: left alone
+        fs::write(&lock_path, std::process::id().to_string()).unwrap();
+        assert!(!ctx.clear_stale_registry_lock());
+        assert!(lock_path.exists());
+
+        // Replaced by a live holder after it was judged stale: put back
+        assert!(!ctx.remove_registry_lock_if(&u32::MAX.to_string()));
+        assert_eq!(
+            fs::read_to_string(&lock_path).unwrap(),
+            std::process::id().to_string()
+        );
+
+        // Held by a process that crashed: removed, and registering proceeds
+        fs::write(&lock_path, u32::MAX.to_string()).unwrap();
+        ctx.register_worktree("feat-auth", "feat", None).unwrap();
+        assert!(!lock_path.exists());
+        assert!(fs::read_to_string(dir.path().join(".iMi/registry.toml"))
+            .unwrap()
+            .contains("feat-auth"));
+    }
+}
@@ -1,32 +1,101 @@
 use anyhow::{Context, Result};
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches};
 use colored::*;
 use serde::{Deserialize, Serialize};
 use serde_json;
-use std::path::PathBuf;
-
+use std::io::{IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+mod agent_scaffold;
+mod alerts;
+mod artifacts;
+mod audit;
+mod backend;
+mod cache_clone;
+mod cancel;
+mod capabilities;
 mod cli;
 mod commands;
+mod commit_template;
 mod config;
+mod config_check;
 mod context;
+mod daemon;
 mod database;
+mod debug_bundle;
+mod deletion_safety;
+mod disk_usage;
+mod editor;
+mod environment;
 mod error;
+mod event_hooks;
+mod events;
+mod first_run;
+mod format;
 mod fuzzy;
 mod git;
+mod git_hooks;
 mod github;
+mod graph;
+mod i18n;
 mod init;
+mod journal;
+mod json_cache;
 mod local;
+mod maintenance;
+#[cfg_attr(not(feature = "http"), allow(dead_code))] // Served only by the http feature
+mod metrics;
 mod monitor;
+mod ops;
+mod path_remap;
+mod pending_delete;
+mod progress;
+mod rebase_batch;
+mod remote_url;
+mod repo_stats;
+mod retry;
+mod review_context;
+#[cfg(feature = "http")]
+mod server;
+mod sync_scaffold;
+mod timings;
+mod trash;
+mod trunk_protection;
+mod type_prefixes;
+mod type_sharing;
 mod worktree;
 
-use cli::{Cli, Commands, MetadataCommands, ProjectCommands, RegistryCommands, TypeCommands};
+use agent_scaffold::FileOutcome;
+use artifacts::ArtifactKind;
+use audit::{AuditEntry, AuditLog};
+use capabilities::CapabilityReport;
+use cli::{
+    AgentCommands, AgentRuleCommands, AliasCommands, ArtifactCommands, AuditCommands,
+    BranchCommands, Cli, Commands, ConfigCommands, DaemonCommands, DebugCommands, HookCommands,
+    MetadataCommands, MsgCommands, ProjectCommands, RegistryCommands, RepoCommands, ReviewCommands,
+    TrashCommands, TypeCommands,
+};
 use commands::project::{ProjectConfig, ProjectCreator};
-use config::Config;
-use database::Database;
-use git::GitManager;
-use init::InitCommand;
+use config::{Config, MergeStrategy, MonitoringSettings, PermissionSettings, WorktreeSort};
+use database::{Database, RepositoryEdit, WorktreeTypeEdit};
+use environment::EnvironmentReport;
+use error::ImiError;
+use events::{EventFilter, EventLog, StreamEvent};
+use format::{say, OutputFormat};
+use fuzzy::SearchTarget;
+use git::{GitManager, PrSync};
+use i18n::tr;
+use init::{CloneOptions, CloneStatus, InitCommand};
+use journal::{Journal, JournalEntry};
 use local::LocalContext;
-use worktree::WorktreeManager;
+use ops::{
+    BulkCloseOptions, CloseStatus, CreateOptions, ForkOptions, Imi, ListOptions, RemoveOptions,
+    ResolveOptions, TypeSource,
+};
+use rebase_batch::{RebaseBatch, RebaseState};
+use type_sharing::{ImportAction, ImportMode};
+use worktree::{RepositoryDetail, WorktreeManager};
 
 /// JSON response structure for --json output mode
 #[derive(Serialize, Deserialize)]
@@ -55,21 +124,194 @@ impl JsonResponse {
         }
     }
 
+    /// Pretty-printed, or one line ending the NDJSON stream under --json-stream
     fn print(&self) {
-        println!("{}", serde_json::to_string_pretty(self).unwrap());
+        if progress::enabled() {
+            println!("{}", serde_json::to_string(self).unwrap());
+            RESULT_PRINTED.store(true, std::sync::atomic::Ordering::Relaxed);
+        } else {
+            println!("{}", serde_json::to_string_pretty(self).unwrap());
+        }
     }
 }
 
+/// Whether a result object ended the --json-stream output, so a failure
+/// doesn't add a second one
+static RESULT_PRINTED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
 #[tokio::main]
 async fn main() -> Result<()> {
     // Initialize the CLI
-    let cli = Cli::parse();
-    let json_mode = cli.json;
+    let mut args: Vec<std::ffi::OsString> = std::env::args_os().collect();
+    if let Some(position) = cli::alias_position(&args) {
+        let name = args[position].to_string_lossy().into_owned();
+        let aliases = Config::load().await.map(|c| c.aliases).unwrap_or_default();
+        if let Some(template) = aliases.get(&name) {
+            args = cli::expand_alias(&args, position, template).unwrap_or_else(|e| {
+                Cli::command()
+                    .error(clap::error::ErrorKind::WrongNumberOfValues, e)
+                    .exit()
+            });
+        }
+    }
+    let matches = Cli::command().get_matches_from(args);
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    let command_name = matches.subcommand_name().unwrap_or_default().to_string();
+
+    // Overrides flow through the environment so every Config::load and
+    // Database::new (including in spawned hooks) sees the same profile
+    if let Some(path) = &cli.config {
+        std::env::set_var("IMI_CONFIG", path);
+    }
+    if let Some(url) = &cli.db {
+        std::env::set_var("IMI_DB", url);
+    }
+    if cli.read_only {
+        std::env::set_var("IMI_READ_ONLY", "1");
+    }
+    if cli.quiet {
+        std::env::set_var("IMI_QUIET", "1");
+    }
+    if cli.json_stream {
+        // Progress comes as events; banners and colour would corrupt them
+        cli.json = true;
+        std::env::set_var("IMI_JSON_STREAM", "1");
+        colored::control::set_override(false);
+    }
+    let show_timings = cli.timings;
+    if show_timings {
+        timings::install();
+    }
+
+    let json_mode = OutputFormat::resolve(cli.format.clone(), cli.json) == OutputFormat::Json;
+    if cli.command.as_ref().is_some_and(Commands::cancellable) {
+        cancel::install();
+    }
+    let started = Instant::now();
+    let result = match &cli.command {
+        Some(command) if command.mutates() && read_only_mode().await => Err(ImiError::ReadOnly {
+            command: command_name.clone(),
+        }
+        .into()),
+        _ => run(cli).await,
+    };
+
+    // Inspecting the audit log shouldn't grow it, and neither should every
+    // commit the activity hooks report
+    if command_name != "audit" && command_name != "_record-activity" {
+        record_audit_entry(&command_name, started.elapsed(), &result).await;
+    }
+    if show_timings {
+        timings::report();
+    }
+
+    if let Err(e) = &result {
+        // The command has already reported why
+        if let Some(ImiError::Exit { code }) = e.downcast_ref() {
+            std::process::exit(*code);
+        }
+
+        // Refusals get their own exit status so scripts and agents can tell
+        // them from failures
+        if matches!(e.downcast_ref(), Some(ImiError::ReadOnly { .. })) {
+            if json_mode {
+                JsonResponse::error(e.to_string()).print();
+            } else {
+                eprintln!("{} {}", "🔒".bright_yellow(), e);
+            }
+            std::process::exit(ImiError::READ_ONLY_EXIT_CODE);
+        }
+
+        // A failure after Ctrl-C is most likely the interrupted git child
+        if cancel::requested() || matches!(e.downcast_ref(), Some(ImiError::Cancelled)) {
+            if json_mode {
+                JsonResponse::error(format!("{:#}", e)).print();
+            } else {
+                eprintln!("{} {:#}", "⏹️".bright_yellow(), e);
+            }
+            std::process::exit(ImiError::CANCELLED_EXIT_CODE);
+        }
+
+        if progress::enabled() && !RESULT_PRINTED.load(std::sync::atomic::Ordering::Relaxed) {
+            JsonResponse::error(format!("{:#}", e)).print();
+        }
+    }
+
+    result
+}
+
+/// A banner ending in the path a command produced; quiet mode prints only
+/// the path, for `cd "$(imi add feat x)"`
+fn print_path_result(banner: String, path: &Path) {
+    if format::quiet() {
+        println!("{}", path.display());
+    } else {
+        println!("{} {}", banner, path.display());
+    }
+}
+
+/// `--read-only`/IMI_READ_ONLY, else `[permissions] read_only`
+async fn read_only_mode() -> bool {
+    PermissionSettings::read_only_requested()
+        || Config::load()
+            .await
+            .is_ok_and(|config| config.permissions.read_only)
+}
+
+/// Append this invocation to the audit log when audit mode is enabled
+async fn record_audit_entry(command_name: &str, elapsed: Duration, result: &Result<()>) {
+    let Ok(config) = Config::load().await else {
+        return;
+    };
+    let settings = &config.audit_settings;
+    if !settings.is_enabled() {
+        return;
+    }
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let entry = AuditEntry::new(
+        command_name,
+        &args,
+        settings,
+        elapsed.as_millis() as u64,
+        result.as_ref().err().map(|e| format!("{:#}", e)),
+    );
+
+    if let Err(e) = AuditLog::from_settings(settings).and_then(|log| log.append(&entry)) {
+        eprintln!(
+            "{}",
+            tr!("{} Failed to write audit log: {}", "⚠️".bright_yellow(), e)
+        );
+    }
+}
+
+async fn run(cli: Cli) -> Result<()> {
+    let format = OutputFormat::resolve(cli.format.clone(), cli.json);
+    let json_mode = format == OutputFormat::Json;
+    let ignore_budget = matches!(
+        cli.command,
+        Some(
+            Commands::Add {
+                ignore_budget: true,
+                ..
+            } | Commands::Copy {
+                ignore_budget: true,
+                ..
+            }
+        )
+    );
 
     if let Some(command) = cli.command {
         match command {
-            Commands::Init { repo, force } => {
-                handle_init_command(repo, force, json_mode).await?;
+            Commands::Init {
+                repo,
+                force,
+                offline,
+                yes,
+                clone,
+            } => {
+                handle_init_command(repo, force, offline, yes, clone_options(clone), json_mode)
+                    .await?;
             }
             Commands::MigrateOffice {
                 repo,
@@ -78,19 +320,71 @@ async fn main() -> Result<()> {
             } => {
                 handle_migrate_office_command(repo, dry_run, force, json_mode).await?;
             }
+            Commands::Env => {
+                let config = Config::load().await.context(tr!(
+                    "Failed to load configuration. Have you run 'imi init'?"
+                ))?;
+                let report = EnvironmentReport::collect(&config)?;
+                if json_mode {
+                    JsonResponse::success(serde_json::to_value(&report)?).print();
+                } else {
+                    report.print();
+                }
+            }
+            Commands::Verify { dir, strict } => {
+                let config = Config::load().await.context(tr!(
+                    "Failed to load configuration. Have you run 'imi init'?"
+                ))?;
+                handle_verify_command(&config, dir, strict, json_mode)?;
+            }
+            Commands::Config(ConfigCommands::Validate { file, strict }) => {
+                // Before Config::load, which would refuse a broken file
+                handle_config_validate_command(file, strict, json_mode).await?;
+            }
+            Commands::Alias(AliasCommands::List) => {
+                let config = Config::load().await.context(tr!(
+                    "Failed to load configuration. Have you run 'imi init'?"
+                ))?;
+                handle_alias_list_command(&config, json_mode);
+            }
+            Commands::Audit(cmd) => {
+                let config = Config::load().await.context(tr!(
+                    "Failed to load configuration. Have you run 'imi init'?"
+                ))?;
+                handle_audit_command(&config, cmd, json_mode)?;
+            }
+            Commands::Debug(DebugCommands::Bundle { output, include_db }) => {
+                // Before the database is required, so a broken setup can be reported
+                let config = Config::load().await.context(tr!(
+                    "Failed to load configuration. Have you run 'imi init'?"
+                ))?;
+                handle_debug_bundle_command(&config, output, include_db, json_mode).await?;
+            }
+            Commands::Events {
+                repo,
+                events,
+                follow,
+                lines,
+            } => {
+                let config = Config::load().await.context(tr!(
+                    "Failed to load configuration. Have you run 'imi init'?"
+                ))?;
+                let filter = EventFilter { repo, events };
+                handle_events_command(&config, filter, follow, lines).await?;
+            }
             _ => {
                 // Load configuration
-                let config = Config::load()
-                    .await
-                    .context("Failed to load configuration. Have you run 'imi init'?")?;
+                let config = Config::load().await.context(tr!(
+                    "Failed to load configuration. Have you run 'imi init'?"
+                ))?;
 
                 // Initialize database
                 let db = Database::new(&config.database_path)
                     .await
-                    .context("Failed to initialize database")?;
+                    .context(tr!("Failed to initialize database"))?;
 
                 // Initialize Git manager
-                let git_manager = GitManager::new();
+                let git_manager = GitManager::from_config(&config);
 
                 // Initialize worktree manager
                 let worktree_manager = WorktreeManager::new(
@@ -98,32 +392,69 @@ async fn main() -> Result<()> {
                     db.clone(),
                     config.clone(),
                     config.repo_path.clone(),
-                );
+                )
+                .quiet(format != OutputFormat::Table || format::quiet())
+                .ignore_budget(ignore_budget);
+
+                // Typed, non-printing API the handlers present results from
+                let imi = Imi::new(worktree_manager.clone().quiet(true));
+
+                if !matches!(command, Commands::Resume { .. } | Commands::Rollback { .. }) {
+                    warn_about_interrupted_operations();
+                }
+                warn_if_in_nested_repository();
 
                 match command {
                     Commands::Add {
+                        args,
                         worktree_type,
-                        name,
                         repo,
                         pr,
+                        base,
+                        no_hooks,
+                        publish,
+                        open,
+                        switch,
+                        claim,
+                        adopt_existing,
+                        sparse_changed,
+                        apply_patch,
+                        commit,
+                        ignore_budget: _,
                     } => {
-                        handle_add_command(
-                            &worktree_manager,
-                            &worktree_type,
-                            &name,
-                            repo.as_deref(),
+                        let patch = apply_patch.as_deref().map(read_patch).transpose()?;
+                        let commit_message = commit.map(|message| match message.is_empty() {
+                            true => default_patch_message(apply_patch.as_deref()),
+                            false => message,
+                        });
+                        // Type and name are filled in once the arguments are parsed
+                        let options = CreateOptions {
+                            repo,
                             pr,
-                            json_mode,
-                        )
-                        .await?;
+                            base,
+                            no_hooks,
+                            publish,
+                            adopt_existing,
+                            patch,
+                            commit_message,
+                            open,
+                            claim,
+                            sparse_changed,
+                            ..Default::default()
+                        };
+                        handle_add_command(&imi, args, worktree_type, options, switch, json_mode)
+                            .await?;
                     }
                     Commands::Types(type_cmd) => {
-                        handle_types_command(&worktree_manager, type_cmd, json_mode).await?;
+                        handle_types_command(&imi, type_cmd, &format).await?;
                     }
                     Commands::Feat { name, repo } => {
                         eprintln!(
-                            "⚠️  Warning: 'imi feat' is deprecated. Use 'imi add feat {}' instead.",
-                            name
+                            "{}",
+                            tr!(
+                                "⚠️  Warning: 'imi feat' is deprecated. Use 'imi add feat {}' instead.",
+                                name
+                            )
                         );
                         handle_feature_command(
                             &worktree_manager,
@@ -133,7 +464,30 @@ async fn main() -> Result<()> {
                         )
                         .await?;
                     }
-                    Commands::Review { pr_number, repo } => {
+                    Commands::Review {
+                        action:
+                            Some(ReviewCommands::Sync {
+                                pr_number,
+                                repo,
+                                force,
+                            }),
+                        ..
+                    } => {
+                        handle_review_sync_command(
+                            &worktree_manager,
+                            pr_number,
+                            repo.as_deref(),
+                            force,
+                            json_mode,
+                        )
+                        .await?;
+                    }
+                    Commands::Review {
+                        pr_number, repo, ..
+                    } => {
+                        // clap requires the number unless `sync` was given
+                        let pr_number =
+                            pr_number.context(tr!("A pull request number is required"))?;
                         handle_review_command(
                             &worktree_manager,
                             pr_number,
@@ -157,21 +511,62 @@ async fn main() -> Result<()> {
                     Commands::Trunk { repo } => {
                         handle_trunk_command(&worktree_manager, repo.as_deref(), json_mode).await?;
                     }
-                    Commands::Status { repo } => {
-                        handle_status_command(&worktree_manager, repo.as_deref(), json_mode)
-                            .await?;
+                    Commands::Status {
+                        repo,
+                        ci,
+                        sort,
+                        all,
+                        no_cache,
+                        refresh_prs,
+                        watch,
+                    } => {
+                        let manager = worktree_manager
+                            .clone()
+                            .sort_worktrees(sort)
+                            .include_archived(all)
+                            .no_status_cache(no_cache);
+                        // A network round trip per worktree, so only on request
+                        if refresh_prs {
+                            manager.refresh_pull_requests(repo.as_deref()).await?;
+                        }
+                        handle_status_command(
+                            &manager,
+                            &Imi::new(manager.clone().quiet(true)),
+                            repo.as_deref(),
+                            ci,
+                            sort,
+                            watch,
+                            &format,
+                        )
+                        .await?;
                     }
                     Commands::List {
                         repo,
                         worktrees,
                         projects,
+                        health,
+                        tag,
+                        sort,
+                        all,
+                        no_cache,
+                        all_hosts,
                     } => {
                         handle_list_command(
-                            &worktree_manager,
+                            &Imi::new(
+                                worktree_manager
+                                    .clone()
+                                    .quiet(true)
+                                    .sort_worktrees(sort)
+                                    .include_archived(all)
+                                    .no_status_cache(no_cache)
+                                    .all_hosts(all_hosts),
+                            ),
                             repo.as_deref(),
                             worktrees,
                             projects,
-                            json_mode,
+                            health,
+                            tag.as_deref(),
+                            &format,
                         )
                         .await?;
                     }
@@ -180,36 +575,295 @@ async fn main() -> Result<()> {
                         repo,
                         keep_branch,
                         keep_remote,
+                        exact,
                     } => {
                         handle_remove_command(
+                            &imi,
+                            RemoveOptions {
+                                name,
+                                repo,
+                                keep_branch,
+                                keep_remote,
+                                exact,
+                            },
+                            json_mode,
+                        )
+                        .await?;
+                    }
+                    Commands::Copy {
+                        source,
+                        name,
+                        repo,
+                        include_uncommitted,
+                        caches,
+                        no_caches,
+                        cache_fallback,
+                        ignore_budget: _,
+                    } => {
+                        handle_copy_command(
+                            &imi,
+                            ForkOptions {
+                                source,
+                                name,
+                                repo,
+                                include_uncommitted,
+                                caches: match (caches, no_caches) {
+                                    (true, _) => Some(true),
+                                    (_, true) => Some(false),
+                                    _ => None,
+                                },
+                                cache_fallback,
+                            },
+                            json_mode,
+                        )
+                        .await?;
+                    }
+                    Commands::Monitor {
+                        repo,
+                        interval,
+                        no_git_status,
+                        exclude,
+                        max_load,
+                    } => {
+                        // Flags apply to every repository, over per-repo settings
+                        let mut settings = config.monitoring_settings.clone();
+                        if let Some(interval) = interval {
+                            settings.status_interval_secs = interval;
+                            for repo in settings.repos.values_mut() {
+                                repo.status_interval_secs = None;
+                            }
+                        }
+                        if no_git_status {
+                            settings.git_status = false;
+                            for repo in settings.repos.values_mut() {
+                                repo.git_status = None;
+                            }
+                        }
+                        settings.exclude.extend(exclude);
+                        settings.max_load = max_load.or(settings.max_load);
+                        handle_monitor_command(
                             &worktree_manager,
-                            &name,
                             repo.as_deref(),
-                            keep_branch,
-                            keep_remote,
+                            settings,
                             json_mode,
                         )
                         .await?;
                     }
-                    Commands::Monitor { repo } => {
-                        handle_monitor_command(&worktree_manager, repo.as_deref(), json_mode)
-                            .await?;
+                    Commands::Sync {
+                        repo,
+                        rebase_worktrees,
+                        resume,
+                        abort,
+                    } => {
+                        if resume || abort {
+                            let batch = match resume {
+                                true => {
+                                    ops::continue_rebase_worktrees(&imi, repo.as_deref()).await?
+                                }
+                                false => ops::abort_rebase_worktrees(&imi, repo.as_deref()).await?,
+                            };
+                            print_rebase_batch(&batch, json_mode)?;
+                        } else {
+                            handle_sync_command(&imi, repo.as_deref(), rebase_worktrees, json_mode)
+                                .await?;
+                        }
                     }
-                    Commands::Sync { repo } => {
-                        handle_sync_command(&worktree_manager, repo.as_deref(), json_mode).await?;
+                    Commands::Repair {
+                        repo,
+                        remap,
+                        from_host,
+                    } => {
+                        handle_repair_command(
+                            &worktree_manager,
+                            repo.as_deref(),
+                            remap.as_ref(),
+                            from_host.as_deref(),
+                        )
+                        .await?;
                     }
-                    Commands::Repair => {
-                        handle_repair_command(&worktree_manager).await?;
+                    Commands::ImportWorktrees { repo, yes, dry_run } => {
+                        handle_import_worktrees_command(
+                            &imi,
+                            repo.as_deref(),
+                            yes,
+                            dry_run,
+                            json_mode,
+                        )
+                        .await?;
                     }
                     Commands::Doctor { network, verbose } => {
                         handle_doctor_command(&db, network, verbose).await?;
                     }
+                    Commands::Daemon(cmd) => {
+                        handle_daemon_command(&imi, cmd, json_mode).await?;
+                    }
+                    Commands::Serve { http, addr } => {
+                        handle_serve_command(imi, http, addr).await?;
+                    }
+                    Commands::Alias(_)
+                    | Commands::Audit(_)
+                    | Commands::Config(_)
+                    | Commands::Debug(_)
+                    | Commands::Env
+                    | Commands::Events { .. }
+                    | Commands::Verify { .. } => {
+                        // Already handled; none needs the database
+                    }
                     Commands::Registry(cmd) => {
-                        handle_registry_command(&db, &cmd).await?;
+                        handle_registry_command(&db, &cmd, &format).await?;
+                    }
+                    Commands::Repo(cmd) => {
+                        handle_repo_command(&imi, cmd, json_mode).await?;
+                    }
+                    Commands::Branches(BranchCommands::PruneRemote {
+                        repo,
+                        include_unmerged,
+                        dry_run,
+                        yes,
+                    }) => {
+                        handle_prune_remote_command(
+                            &imi,
+                            repo.as_deref(),
+                            include_unmerged,
+                            dry_run,
+                            yes,
+                            json_mode,
+                        )
+                        .await?;
+                    }
+                    Commands::Hooks(cmd) => {
+                        handle_hooks_command(&worktree_manager, cmd, json_mode).await?;
+                    }
+                    Commands::RecordActivity { hook, args } => {
+                        worktree_manager.record_git_activity(&hook, &args).await?;
+                    }
+                    Commands::Agent(AgentCommands::Rules(cmd)) => {
+                        handle_agent_rules_command(&imi, cmd, json_mode).await?;
+                    }
+                    Commands::Agent(AgentCommands::Scaffold {
+                        repo,
+                        force,
+                        no_link,
+                    }) => {
+                        handle_agent_scaffold_command(
+                            &worktree_manager,
+                            repo.as_deref(),
+                            force,
+                            !no_link,
+                            json_mode,
+                        )
+                        .await?;
+                    }
+                    Commands::Exec { tag, command } => {
+                        handle_exec_command(&imi, tag.as_deref(), &command, json_mode).await?;
+                    }
+                    Commands::Check {
+                        name,
+                        repo,
+                        silent,
+                        command,
+                    } => {
+                        handle_check_command(
+                            &imi,
+                            ops::CheckOptions {
+                                worktree: name,
+                                repo,
+                                command,
+                                echo: !silent && !json_mode,
+                            },
+                            json_mode,
+                        )
+                        .await?;
+                    }
+                    Commands::Artifacts(cmd) => {
+                        handle_artifacts_command(&imi, cmd, json_mode).await?;
+                    }
+                    Commands::Bisect {
+                        bad,
+                        good,
+                        repo,
+                        keep,
+                        command,
+                    } => {
+                        let options = ops::BisectOptions {
+                            repo,
+                            bad,
+                            good,
+                            command,
+                            keep,
+                        };
+                        handle_bisect_command(&imi, options, json_mode).await?;
+                    }
+                    Commands::Temp {
+                        base,
+                        repo,
+                        command,
+                    } => {
+                        let options = ops::TempOptions {
+                            repo,
+                            base,
+                            command,
+                        };
+                        handle_temp_command(&imi, options, json_mode).await?;
                     }
                     Commands::Init { .. } => {
                         // Already handled
                     }
+                    Commands::Clone {
+                        repos,
+                        org,
+                        topic,
+                        jobs,
+                        force,
+                        clone,
+                    } => {
+                        let init_cmd = InitCommand::new(force, config.clone(), db.clone())
+                            .quiet(json_mode || format::quiet())
+                            .clone_options(clone_options(clone));
+                        handle_clone_command(
+                            &init_cmd, &config, repos, org, topic, jobs, json_mode,
+                        )
+                        .await?;
+                    }
+                    Commands::Onboard {
+                        target,
+                        topic,
+                        language,
+                        all,
+                        jobs,
+                        clone,
+                    } => {
+                        let repos = select_onboard_repositories(
+                            &config,
+                            &db,
+                            &target,
+                            topic.as_deref(),
+                            language.as_deref(),
+                            all,
+                            json_mode,
+                        )
+                        .await?;
+                        if !repos.is_empty() {
+                            let init_cmd = InitCommand::new(false, config.clone(), db.clone())
+                                .quiet(json_mode || format::quiet())
+                                .clone_options(clone_options(clone));
+                            handle_clone_command(
+                                &init_cmd, &config, repos, None, None, jobs, json_mode,
+                            )
+                            .await?;
+                        }
+                    }
+                    Commands::Unshallow { repo } => {
+                        handle_unshallow_command(&worktree_manager, repo.as_deref(), json_mode)
+                            .await?;
+                    }
+                    Commands::Mv {
+                        relayout: _,
+                        repo,
+                        dry_run,
+                    } => {
+                        handle_mv_command(&imi, repo.as_deref(), dry_run, json_mode).await?;
+                    }
                     Commands::Completion { shell } => {
                         handle_completion_command(&shell);
                     }
@@ -217,22 +871,43 @@ async fn main() -> Result<()> {
                         repo,
                         dry_run,
                         force,
+                        include_risky,
                     } => {
                         handle_prune_command(
                             &worktree_manager,
                             repo.as_deref(),
                             dry_run,
                             force,
+                            include_risky,
+                            json_mode,
+                        )
+                        .await?;
+                    }
+                    Commands::Trash(cmd) => {
+                        handle_trash_command(&worktree_manager, cmd, json_mode).await?;
+                    }
+                    Commands::Clean {
+                        repo,
+                        yes,
+                        include_risky,
+                    } => {
+                        handle_clean_command(&imi, repo, yes, include_risky, json_mode).await?;
+                    }
+                    Commands::SizeBudget { repo, refresh } => {
+                        handle_size_budget_command(
+                            &worktree_manager,
+                            repo.as_deref(),
+                            refresh,
                             json_mode,
                         )
                         .await?;
                     }
-                    Commands::Close { name, repo } => {
-                        handle_close_command(&worktree_manager, &name, repo.as_deref(), json_mode)
+                    Commands::Graph { repo, dot } => {
+                        handle_graph_command(&worktree_manager, repo.as_deref(), dot, json_mode)
                             .await?;
                     }
-                    Commands::Merge { name, repo } => {
-                        handle_merge_command(
+                    Commands::Open { name, repo } => {
+                        handle_open_command(
                             &worktree_manager,
                             name.as_deref(),
                             repo.as_deref(),
@@ -240,22 +915,121 @@ async fn main() -> Result<()> {
                         )
                         .await?;
                     }
+                    Commands::Close {
+                        name,
+                        repo,
+                        merged,
+                        all_of_type,
+                        matching,
+                        yes,
+                        exact,
+                    } => {
+                        if merged || all_of_type.is_some() || matching.is_some() {
+                            let options = BulkCloseOptions {
+                                repo: repo.or(name),
+                                merged,
+                                worktree_type: all_of_type,
+                                pattern: matching,
+                                pr_merged: false,
+                                expired: false,
+                                include_risky: false,
+                            };
+                            handle_bulk_close_command(&imi, options, yes, json_mode).await?;
+                        } else {
+                            let name = name.context(tr!("Worktree name is required"))?;
+                            handle_close_command(
+                                &worktree_manager.clone().exact_names(exact),
+                                &name,
+                                repo.as_deref(),
+                                json_mode,
+                            )
+                            .await?;
+                        }
+                    }
+                    Commands::Merge {
+                        name,
+                        repo,
+                        queue,
+                        strategy,
+                        abort,
+                        train,
+                    } => {
+                        if !train.is_empty() {
+                            handle_merge_train_command(
+                                &worktree_manager,
+                                &train,
+                                repo.as_deref(),
+                                json_mode,
+                            )
+                            .await?;
+                        } else if abort {
+                            handle_merge_abort_command(
+                                &worktree_manager,
+                                name.as_deref(),
+                                repo.as_deref(),
+                                json_mode,
+                            )
+                            .await?;
+                        } else {
+                            let strategy = queue.then_some(MergeStrategy::Queue).or(strategy);
+                            handle_merge_command(
+                                &worktree_manager,
+                                name.as_deref(),
+                                repo.as_deref(),
+                                strategy,
+                                json_mode,
+                            )
+                            .await?;
+                        }
+                    }
                     Commands::Go {
                         query,
                         repo,
                         worktrees_only,
                         include_inactive,
+                        shell,
+                        all,
                     } => {
+                        let query = query.join(" ");
                         handle_go_command(
-                            &worktree_manager,
-                            query.as_deref(),
+                            &worktree_manager.clone().include_archived(all),
+                            (!query.is_empty()).then_some(query.as_str()),
                             repo.as_deref(),
                             worktrees_only,
                             include_inactive,
+                            shell,
+                            json_mode,
+                        )
+                        .await?;
+                    }
+                    Commands::Which {
+                        name,
+                        repo,
+                        worktrees_only,
+                        include_inactive,
+                        all,
+                    } => {
+                        handle_which_command(
+                            &Imi::new(worktree_manager.clone().quiet(true).include_archived(all)),
+                            ResolveOptions {
+                                query: name,
+                                repo,
+                                worktrees_only,
+                                include_inactive,
+                            },
                             json_mode,
                         )
                         .await?;
                     }
+                    Commands::Recent {
+                        index,
+                        limit,
+                        repo,
+                        pick,
+                    } => {
+                        handle_recent_command(&imi, index, limit, repo.as_deref(), pick, json_mode)
+                            .await?;
+                    }
                     Commands::Project { command } => {
                         handle_project_command(command, json_mode).await?;
                     }
@@ -295,9 +1069,38 @@ async fn main() -> Result<()> {
                         )
                         .await?;
                     }
+                    Commands::Resume { operation } => {
+                        handle_resume_command(&worktree_manager, operation.as_deref(), json_mode)
+                            .await?;
+                    }
+                    Commands::Rollback { operation, yes } => {
+                        handle_rollback_command(
+                            &worktree_manager,
+                            operation.as_deref(),
+                            yes,
+                            json_mode,
+                        )
+                        .await?;
+                    }
                     Commands::Metadata(cmd) => {
                         handle_metadata_command(&worktree_manager, cmd, json_mode).await?;
                     }
+                    Commands::Show { name, repo } => {
+                        handle_show_command(&imi, &name, repo.as_deref(), json_mode).await?;
+                    }
+                    Commands::Note { name, text, repo } => {
+                        handle_note_command(
+                            &imi,
+                            &name,
+                            text.as_deref(),
+                            repo.as_deref(),
+                            json_mode,
+                        )
+                        .await?;
+                    }
+                    Commands::Msg(msg_cmd) => {
+                        handle_msg_command(&imi, msg_cmd, json_mode).await?;
+                    }
                     Commands::MigrateOffice { .. } => {
                         // Already handled before loading repository-scoped managers
                     }
@@ -316,10 +1119,13 @@ async fn handle_feature_command(
     json_mode: bool,
 ) -> Result<()> {
     if !json_mode {
-        println!(
-            "{} Creating feature worktree: {}",
-            "🚀".bright_cyan(),
-            name.bright_green()
+        say!(
+            "{}",
+            tr!(
+                "{} Creating feature worktree: {}",
+                "🚀".bright_cyan(),
+                name.bright_green()
+            )
         );
     }
 
@@ -333,17 +1139,19 @@ async fn handle_feature_command(
                 }))
                 .print();
             } else {
-                println!(
-                    "{} Feature worktree created at: {}",
-                    "✅".bright_green(),
-                    worktree_path.display()
+                print_path_result(
+                    format!("{} Feature worktree created at:", "✅".bright_green()),
+                    &worktree_path,
                 );
 
                 // Print command to change directory (processes can't change parent shell's directory)
-                println!(
-                    "\n{} To navigate to the worktree, run:\n   {}",
-                    "💡".bright_yellow(),
-                    format!("cd {}", worktree_path.display()).bright_cyan()
+                say!(
+                    "{}",
+                    tr!(
+                        "\n{} To navigate to the worktree, run:\n   {}",
+                        "💡".bright_yellow(),
+                        format!("cd {}", worktree_path.display()).bright_cyan()
+                    )
                 );
             }
         }
@@ -360,13 +1168,13 @@ async fn handle_feature_command(
                 || error_msg.contains("credential")
                 || error_msg.contains("ssh")
             {
-                println!("{} Authentication failed", "❌".bright_red());
-                println!();
+                eprintln!("{}", tr!("{} Authentication failed", "❌".bright_red()));
+                eprintln!();
 
                 // Show authentication help
                 let git_manager = GitManager::new();
                 git_manager.show_auth_help();
-                println!();
+                eprintln!();
 
                 return Err(e);
             }
@@ -384,10 +1192,13 @@ async fn handle_review_command(
     json_mode: bool,
 ) -> Result<()> {
     if !json_mode {
-        println!(
-            "{} Creating review worktree for PR: {}",
-            "🔍".bright_yellow(),
-            pr_number.to_string().bright_green()
+        say!(
+            "{}",
+            tr!(
+                "{} Creating review worktree for PR: {}",
+                "🔍".bright_yellow(),
+                pr_number.to_string().bright_green()
+            )
         );
     }
 
@@ -401,34 +1212,121 @@ async fn handle_review_command(
         }))
         .print();
     } else {
-        println!(
-            "{} Review worktree created at: {}",
-            "✅".bright_green(),
-            worktree_path.display()
+        print_path_result(
+            format!("{} Review worktree created at:", "✅".bright_green()),
+            &worktree_path,
         );
 
         // Print command to change directory (processes can't change parent shell's directory)
-        println!(
-            "\n{} To navigate to the worktree, run:\n   {}",
-            "💡".bright_yellow(),
-            format!("cd {}", worktree_path.display()).bright_cyan()
+        say!(
+            "{}",
+            tr!(
+                "\n{} To navigate to the worktree, run:\n   {}",
+                "💡".bright_yellow(),
+                format!("cd {}", worktree_path.display()).bright_cyan()
+            )
         );
     }
 
     Ok(())
 }
 
-async fn handle_fix_command(
+/// `imi pr sync`: move review worktrees to their pull request's latest head
+async fn handle_review_sync_command(
     manager: &WorktreeManager,
-    name: &str,
+    pr_number: Option<u32>,
     repo: Option<&str>,
+    force: bool,
     json_mode: bool,
 ) -> Result<()> {
-    if !json_mode {
+    let worktrees = manager.review_worktrees(repo, pr_number).await?;
+    if worktrees.is_empty() && !json_mode {
+        say!(
+            "{}",
+            tr!("{} No review worktrees to sync", "ℹ️".bright_blue())
+        );
+        return Ok(());
+    }
+
+    let mut synced = Vec::new();
+    let mut failed = 0;
+    for worktree in &worktrees {
+        match manager.sync_review_worktree(worktree, force).await {
+            Ok(sync) => {
+                if !json_mode {
+                    print_pr_sync(&worktree.name, &sync);
+                }
+                synced.push(sync);
+            }
+            Err(e) if pr_number.is_some() => return Err(e),
+            Err(e) => {
+                failed += 1;
+                eprintln!("{} {}: {:#}", "⚠️".bright_yellow(), worktree.name, e);
+            }
+        }
+    }
+
+    if json_mode {
+        JsonResponse::success(serde_json::to_value(&synced)?).print();
+    }
+    if failed > 0 {
+        anyhow::bail!(tr!("{} review worktree(s) could not be synced", failed));
+    }
+    Ok(())
+}
+
+fn print_pr_sync(worktree_name: &str, sync: &PrSync) {
+    if !sync.updated() {
         println!(
-            "{} Creating fix worktree: {}",
-            "🔧".bright_red(),
-            name.bright_green()
+            "{} {}: {}",
+            "✅".bright_green(),
+            worktree_name.bright_green(),
+            sync.describe()
+        );
+        return;
+    }
+
+    let icon = match sync.force_pushed {
+        true => "⚠️".bright_yellow(),
+        false => "🔄".bright_cyan(),
+    };
+    println!(
+        "{} {}: {}",
+        icon,
+        worktree_name.bright_green(),
+        sync.describe()
+    );
+    if sync.force_pushed {
+        println!(
+            "{}",
+            tr!(
+                "   {} History was rewritten; the changes below are against the head you last synced",
+                "💡".bright_yellow()
+            )
+        );
+    }
+    for commit in &sync.new_commits {
+        println!("   {} {}", "+".bright_green(), commit);
+    }
+    for file in &sync.changed_files {
+        println!("     {}", file.replace('\t', "  ").bright_black());
+    }
+}
+
+async fn handle_fix_command(
+    manager: &WorktreeManager,
+    name: &str,
+    repo: Option<&str>,
+    json_mode: bool,
+) -> Result<()> {
+    if !json_mode {
+        say!(
+            "{}",
+            tr!(
+                "{} Creating fix worktree: {}",
+                "🔧".bright_red(),
+                name.bright_green()
+            )
         );
     }
 
@@ -442,17 +1340,19 @@ async fn handle_fix_command(
         }))
         .print();
     } else {
-        println!(
-            "{} Fix worktree created at: {}",
-            "✅".bright_green(),
-            worktree_path.display()
+        print_path_result(
+            format!("{} Fix worktree created at:", "✅".bright_green()),
+            &worktree_path,
         );
 
         // Print command to change directory (processes can't change parent shell's directory)
-        println!(
-            "\n{} To navigate to the worktree, run:\n   {}",
-            "💡".bright_yellow(),
-            format!("cd {}", worktree_path.display()).bright_cyan()
+        say!(
+            "{}",
+            tr!(
+                "\n{} To navigate to the worktree, run:\n   {}",
+                "💡".bright_yellow(),
+                format!("cd {}", worktree_path.display()).bright_cyan()
+            )
         );
     }
 
@@ -466,10 +1366,13 @@ async fn handle_aiops_command(
     json_mode: bool,
 ) -> Result<()> {
     if !json_mode {
-        println!(
-            "{} Creating aiops worktree: {}",
-            "🤖".bright_magenta(),
-            name.bright_green()
+        say!(
+            "{}",
+            tr!(
+                "{} Creating aiops worktree: {}",
+                "🤖".bright_magenta(),
+                name.bright_green()
+            )
         );
     }
 
@@ -483,17 +1386,19 @@ async fn handle_aiops_command(
         }))
         .print();
     } else {
-        println!(
-            "{} Aiops worktree created at: {}",
-            "✅".bright_green(),
-            worktree_path.display()
+        print_path_result(
+            format!("{} Aiops worktree created at:", "✅".bright_green()),
+            &worktree_path,
         );
 
         // Print command to change directory (processes can't change parent shell's directory)
-        println!(
-            "\n{} To navigate to the worktree, run:\n   {}",
-            "💡".bright_yellow(),
-            format!("cd {}", worktree_path.display()).bright_cyan()
+        say!(
+            "{}",
+            tr!(
+                "\n{} To navigate to the worktree, run:\n   {}",
+                "💡".bright_yellow(),
+                format!("cd {}", worktree_path.display()).bright_cyan()
+            )
         );
     }
 
@@ -507,10 +1412,13 @@ async fn handle_devops_command(
     json_mode: bool,
 ) -> Result<()> {
     if !json_mode {
-        println!(
-            "{} Creating devops worktree: {}",
-            "⚙️".bright_blue(),
-            name.bright_green()
+        say!(
+            "{}",
+            tr!(
+                "{} Creating devops worktree: {}",
+                "⚙️".bright_blue(),
+                name.bright_green()
+            )
         );
     }
 
@@ -524,17 +1432,19 @@ async fn handle_devops_command(
         }))
         .print();
     } else {
-        println!(
-            "{} Devops worktree created at: {}",
-            "✅".bright_green(),
-            worktree_path.display()
+        print_path_result(
+            format!("{} Devops worktree created at:", "✅".bright_green()),
+            &worktree_path,
         );
 
         // Print command to change directory (processes can't change parent shell's directory)
-        println!(
-            "\n{} To navigate to the worktree, run:\n   {}",
-            "💡".bright_yellow(),
-            format!("cd {}", worktree_path.display()).bright_cyan()
+        say!(
+            "{}",
+            tr!(
+                "\n{} To navigate to the worktree, run:\n   {}",
+                "💡".bright_yellow(),
+                format!("cd {}", worktree_path.display()).bright_cyan()
+            )
         );
     }
 
@@ -547,7 +1457,10 @@ async fn handle_trunk_command(
     json_mode: bool,
 ) -> Result<()> {
     if !json_mode {
-        println!("{} Switching to trunk worktree", "🌳".bright_green());
+        say!(
+            "{}",
+            tr!("{} Switching to trunk worktree", "🌳".bright_green())
+        );
     }
 
     let worktree_path = manager.get_trunk_worktree(repo).await?;
@@ -560,242 +1473,2193 @@ async fn handle_trunk_command(
         .print();
     } else {
         // Print command to change directory (processes can't change parent shell's directory)
-        println!(
-            "{} To navigate to trunk, run:\n   {}",
-            "💡".bright_yellow(),
-            format!("cd {}", worktree_path.display()).bright_cyan()
+        if format::quiet() {
+            println!("{}", worktree_path.display());
+        }
+        say!(
+            "{}",
+            tr!(
+                "{} To navigate to trunk, run:\n   {}",
+                "💡".bright_yellow(),
+                format!("cd {}", worktree_path.display()).bright_cyan()
+            )
         );
     }
 
     Ok(())
 }
 
+#[cfg(feature = "http")]
+async fn handle_serve_command(imi: Imi, http: bool, addr: std::net::SocketAddr) -> Result<()> {
+    if !http {
+        return Err(anyhow::anyhow!(tr!("Nothing to serve; pass --http")));
+    }
+    say!(
+        "{}",
+        tr!(
+            "{} Serving the status API on http://{} (Ctrl-C to stop)",
+            "🌐".bright_cyan(),
+            addr
+        )
+    );
+    server::serve(imi, addr).await
+}
+
+#[cfg(not(feature = "http"))]
+async fn handle_serve_command(_imi: Imi, _http: bool, _addr: std::net::SocketAddr) -> Result<()> {
+    Err(anyhow::anyhow!(tr!(
+        "This iMi was built without the status API; reinstall with `cargo install iMi --features http`"
+    )))
+}
+
+#[cfg(feature = "http")]
+async fn serve_metrics(imi: &Imi, addr: std::net::SocketAddr) -> Result<()> {
+    println!(
+        "{}",
+        tr!(
+            "{} Serving Prometheus metrics on http://{}/metrics",
+            "📈".bright_cyan(),
+            addr
+        )
+    );
+    server::serve_metrics(Imi::new(imi.manager().clone()), addr).await
+}
+
+#[cfg(not(feature = "http"))]
+async fn serve_metrics(_imi: &Imi, _addr: std::net::SocketAddr) -> Result<()> {
+    Err(anyhow::anyhow!(tr!(
+        "This iMi was built without the metrics endpoint; reinstall with `cargo install iMi --features http`"
+    )))
+}
+
+/// Print `data` in a non-table format: the usual JSON envelope, YAML, or
+/// `rows` as TSV or through a template
+fn print_formatted(
+    format: &OutputFormat,
+    data: serde_json::Value,
+    rows: &[serde_json::Value],
+) -> Result<()> {
+    match format {
+        OutputFormat::Json => JsonResponse::success(data).print(),
+        _ => print!("{}", format.render(&data, rows)?),
+    }
+    Ok(())
+}
+
+/// Give each worktree row its newest note as a `latest_note` column
+async fn add_latest_notes(manager: &WorktreeManager, rows: &mut [serde_json::Value]) {
+    let notes = manager.latest_notes().await;
+    for row in rows {
+        let note = notes
+            .values()
+            .find(|note| row["id"] == note.worktree_id.to_string());
+        row["latest_note"] = serde_json::json!(note.map(|note| &note.body));
+    }
+}
+
+async fn add_unread_messages(manager: &WorktreeManager, rows: &mut [serde_json::Value]) {
+    let unread = manager.unread_messages().await;
+    for row in rows {
+        let count = unread
+            .iter()
+            .find(|(id, _)| row["id"] == id.to_string())
+            .map_or(0, |(_, count)| *count);
+        row["unread_messages"] = serde_json::json!(count);
+    }
+}
+
 async fn handle_status_command(
     manager: &WorktreeManager,
+    imi: &Imi,
     repo: Option<&str>,
-    json_mode: bool,
+    ci: bool,
+    sort: Option<WorktreeSort>,
+    watch: Option<u64>,
+    format: &OutputFormat,
 ) -> Result<()> {
-    if json_mode {
-        // For JSON mode, we need to capture the status data instead of printing
-        // This would require modifying WorktreeManager.show_status() to return data
-        // For now, we'll use a simple response
-        JsonResponse::success(serde_json::json!({
-            "message": "Status command in JSON mode not yet fully implemented",
-            "note": "Use non-JSON mode for detailed status"
-        }))
-        .print();
+    if let Some(secs) = watch {
+        if *format != OutputFormat::Table {
+            return Err(anyhow::anyhow!(tr!(
+                "--watch redraws the table and only works with table output"
+            )));
+        }
+        return manager
+            .watch_status(repo, ci, std::time::Duration::from_secs(secs))
+            .await;
+    }
+
+    if *format != OutputFormat::Table {
+        let options = ListOptions {
+            repo: repo.map(String::from),
+            include_inactive: false,
+            tag: None,
+            sort,
+        };
+        let worktrees = ops::list_worktrees(imi, options).await?;
+        let mut rows: Vec<serde_json::Value> = worktrees
+            .iter()
+            .map(serde_json::to_value)
+            .collect::<Result<_, _>>()?;
+        add_latest_notes(manager, &mut rows).await;
+        add_unread_messages(manager, &mut rows).await;
+        let mut data = serde_json::json!({
+            "worktrees": &rows,
+            "alerts": ops::worktree_alerts(imi, repo).await?,
+            "pending_deletes": manager.pending_deletes(repo).await.unwrap_or_default(),
+            "trunk_violations": manager.trunk_violations(repo).await,
+        });
+        if ci {
+            let statuses = ops::ci_statuses(imi, repo).await?;
+            // Rows get flat ci_* columns for TSV and templates
+            for row in &mut rows {
+                let status = statuses.iter().find(|s| row["name"] == s.worktree_name);
+                row["ci_state"] = serde_json::json!(status.map(|s| s.status.state));
+                row["ci_failing_job"] =
+                    serde_json::json!(status.and_then(|s| s.status.failing_job.clone()));
+            }
+            data["ci"] = serde_json::to_value(statuses)?;
+        }
+        print_formatted(format, data, &rows)?;
     } else {
-        println!("{} Worktree Status", "📊".bright_cyan());
-        manager.show_status(repo).await?;
+        let statuses = match ci {
+            true => manager.ci_statuses(repo).await?,
+            false => Default::default(),
+        };
+        say!("{}", tr!("{} Worktree Status", "📊".bright_cyan()));
+        manager.show_status_with_ci(repo, &statuses).await?;
+        manager.print_pending_deletes(&manager.pending_deletes(repo).await.unwrap_or_default());
+        manager.print_trunk_violations(&manager.trunk_violations(repo).await);
     }
     Ok(())
 }
 
 async fn handle_list_command(
-    manager: &WorktreeManager,
+    imi: &Imi,
     repo: Option<&str>,
     worktrees: bool,
     projects: bool,
-    json_mode: bool,
+    health: bool,
+    tag: Option<&str>,
+    format: &OutputFormat,
 ) -> Result<()> {
+    let manager = imi.manager();
+    let tag = tag.map(ops::normalize_tag).transpose()?;
+    if health {
+        if *format != OutputFormat::Table {
+            let repositories: Vec<_> = ops::repository_health(imi)
+                .await?
+                .into_iter()
+                .map(|entry| {
+                    serde_json::json!({
+                        "name": entry.repository.name,
+                        "path": entry.repository.path,
+                        "default_branch": entry.repository.default_branch,
+                        "health": entry.health,
+                    })
+                })
+                .collect();
+            print_formatted(
+                format,
+                serde_json::json!({ "repositories": repositories }),
+                &repositories,
+            )?;
+        } else {
+            manager.list_repositories_with_health().await?;
+        }
+        return Ok(());
+    }
+
+    if *format != OutputFormat::Table {
+        let mut data = serde_json::Map::new();
+        if !worktrees && repo.is_none() {
+            let repositories = match &tag {
+                Some(tag) => ops::list_repositories_with_tag(imi, tag).await?,
+                None => ops::list_repositories(imi).await?,
+            };
+            data.insert(
+                "repositories".to_string(),
+                serde_json::to_value(repositories)?,
+            );
+        }
+        if !projects {
+            let options = ListOptions {
+                repo: repo.map(String::from),
+                include_inactive: false,
+                tag: tag.clone(),
+                sort: None,
+            };
+            let mut rows: Vec<serde_json::Value> = ops::list_worktrees(imi, options)
+                .await?
+                .iter()
+                .map(serde_json::to_value)
+                .collect::<Result<_, _>>()?;
+            add_latest_notes(manager, &mut rows).await;
+            data.insert("worktrees".to_string(), serde_json::Value::Array(rows));
+        }
+        // TSV and templates take one kind of row: worktrees unless only
+        // projects were asked for
+        let rows = data
+            .get(if projects {
+                "repositories"
+            } else {
+                "worktrees"
+            })
+            .and_then(|rows| rows.as_array().cloned())
+            .unwrap_or_default();
+        print_formatted(format, serde_json::Value::Object(data), &rows)?;
+    } else if let Some(tag) = &tag {
+        manager.list_tagged(tag, projects).await?;
+    } else {
+        manager.list_smart(repo, worktrees, projects).await?;
+    }
+    Ok(())
+}
+
+async fn handle_remove_command(imi: &Imi, options: RemoveOptions, json_mode: bool) -> Result<()> {
+    if !json_mode {
+        say!(
+            "{}",
+            tr!(
+                "{} Removing worktree: {}",
+                "🗑️".bright_red(),
+                options.name.bright_yellow()
+            )
+        );
+    }
+
+    let mut name = options.name.clone();
+    if let Err(e) = ops::remove_worktree(imi, options.clone()).await {
+        let Some(chosen) = pick_ambiguous_worktree(&e, json_mode)? else {
+            return Err(e);
+        };
+        name = chosen.clone();
+        let options = RemoveOptions {
+            name: chosen,
+            exact: true,
+            ..options
+        };
+        ops::remove_worktree(imi, options).await?;
+    }
+
     if json_mode {
-        // For JSON mode, would need to capture list data
-        // For now, simple response
         JsonResponse::success(serde_json::json!({
-            "message": "List command in JSON mode not yet fully implemented",
-            "note": "Use non-JSON mode for detailed listing"
+            "worktree_name": name,
+            "message": "Worktree removed successfully"
         }))
         .print();
     } else {
-        manager.list_smart(repo, worktrees, projects).await?;
+        say!(
+            "{}",
+            tr!("{} Worktree removed successfully", "✅".bright_green())
+        );
     }
     Ok(())
 }
 
-async fn handle_remove_command(
-    manager: &WorktreeManager,
-    name: &str,
-    repo: Option<&str>,
-    keep_branch: bool,
-    keep_remote: bool,
-    json_mode: bool,
-) -> Result<()> {
+/// When a bare name matched several worktrees, let the user pick one in a
+/// terminal; None leaves the ambiguity error to be reported
+fn pick_ambiguous_worktree(error: &anyhow::Error, json_mode: bool) -> Result<Option<String>> {
+    let Some(ImiError::AmbiguousWorktreeName { name, matches }) = error.downcast_ref() else {
+        return Ok(None);
+    };
+    if json_mode || !std::io::stdin().is_terminal() {
+        return Ok(None);
+    }
+
+    let choice = dialoguer::Select::with_theme(&dialoguer::theme::ColorfulTheme::default())
+        .with_prompt(tr!("'{}' matches several worktrees", name))
+        .items(matches)
+        .default(0)
+        .interact_opt()?;
+    Ok(choice.map(|index| matches[index].clone()))
+}
+
+async fn handle_copy_command(imi: &Imi, options: ForkOptions, json_mode: bool) -> Result<()> {
     if !json_mode {
-        println!(
-            "{} Removing worktree: {}",
-            "🗑️".bright_red(),
-            name.bright_yellow()
+        say!(
+            "{}",
+            tr!(
+                "{} Forking {} into new worktree: {}",
+                "🍴".bright_cyan(),
+                options.source.bright_yellow(),
+                options.name.bright_green()
+            )
         );
     }
 
-    manager
-        .remove_worktree(name, repo, keep_branch, keep_remote)
-        .await?;
+    let outcome = ops::fork_worktree(imi, options).await?;
 
     if json_mode {
         JsonResponse::success(serde_json::json!({
-            "worktree_name": name,
-            "message": "Worktree removed successfully"
+            "worktree_path": outcome.worktree.path.display().to_string(),
+            "worktree_name": outcome.worktree.worktree_name,
+            "worktree_type": outcome.worktree.worktree_type,
+            "branch_name": outcome.worktree.branch_name,
+            "repo_name": outcome.worktree.repo_name,
+            "parent_worktree": outcome.parent_worktree,
+            "caches": outcome.caches,
+            "message": "Worktree forked successfully"
         }))
         .print();
     } else {
-        println!("{} Worktree removed successfully", "✅".bright_green());
+        print_path_result(
+            format!("{} Forked worktree created at:", "✅".bright_green()),
+            &outcome.worktree.path,
+        );
+        say!(
+            "{}",
+            tr!(
+                "\n{} To navigate to the worktree, run:\n   {}",
+                "💡".bright_yellow(),
+                format!("cd {}", outcome.worktree.path.display()).bright_cyan()
+            )
+        );
     }
+
     Ok(())
 }
 
 async fn handle_monitor_command(
     manager: &WorktreeManager,
     repo: Option<&str>,
+    settings: MonitoringSettings,
     json_mode: bool,
 ) -> Result<()> {
     if json_mode {
-        JsonResponse::error(
-            "Monitor command does not support JSON mode (interactive mode only)".to_string(),
-        )
+        JsonResponse::error(tr!(
+            "Monitor command does not support JSON mode (interactive mode only)"
+        ))
         .print();
-        return Err(anyhow::anyhow!(
+        return Err(anyhow::anyhow!(tr!(
             "Monitor command requires interactive terminal"
-        ));
+        )));
     }
 
-    println!("{} Starting real-time monitoring...", "👁️".bright_purple());
-    manager.start_monitoring(repo).await?;
+    say!(
+        "{}",
+        tr!("{} Starting real-time monitoring...", "👁️".bright_purple())
+    );
+    manager.start_monitoring(repo, settings).await?;
     Ok(())
 }
 
 async fn handle_sync_command(
-    manager: &WorktreeManager,
+    imi: &Imi,
     repo: Option<&str>,
+    rebase_worktrees: bool,
     json_mode: bool,
 ) -> Result<()> {
+    let manager = imi.manager();
     if !json_mode {
-        println!(
-            "{} Syncing database with Git worktrees...",
-            "🔄".bright_cyan()
+        say!(
+            "{}",
+            tr!(
+                "{} Syncing database with Git worktrees...",
+                "🔄".bright_cyan()
+            )
         );
     }
 
     manager.sync_with_git(repo).await?;
+    let repo_name = manager.resolve_repo_name(repo).await?;
+    let linked = manager.refresh_pull_requests(Some(&repo_name)).await?;
+    if linked > 0 && !json_mode {
+        println!(
+            "{}",
+            tr!(
+                "   {} Updated {} pull request link(s)",
+                "🔀".bright_cyan(),
+                linked
+            )
+        );
+    }
+
+    let batch = match rebase_worktrees {
+        true => Some(ops::rebase_worktrees(imi, Some(&repo_name)).await?),
+        false => None,
+    };
 
     if json_mode {
         JsonResponse::success(serde_json::json!({
-            "message": "Database synced successfully"
+            "message": "Database synced successfully",
+            "pull_requests_updated": linked,
+            "rebase": batch,
         }))
         .print();
+    } else if let Some(batch) = &batch {
+        print_rebase_batch(batch, false)?;
     }
     Ok(())
 }
 
-async fn handle_repair_command(manager: &WorktreeManager) -> Result<()> {
-    println!(
-        "{} Repairing repository paths in database...",
-        "🔧".bright_cyan()
-    );
-    println!();
+/// Per-worktree results of a rebase batch, and what to do about conflicts
+fn print_rebase_batch(batch: &RebaseBatch, json_mode: bool) -> Result<()> {
+    if json_mode {
+        JsonResponse::success(serde_json::to_value(batch)?).print();
+        return Ok(());
+    }
 
-    manager.repair_all_repository_paths().await?;
+    say!(
+        "{}",
+        tr!(
+            "{} Rebasing {}'s worktrees onto {}",
+            "🔁".bright_cyan(),
+            batch.repo_name.bright_white(),
+            batch.onto.bright_green()
+        )
+    );
+    for item in &batch.items {
+        let icon = match item.state {
+            RebaseState::Rebased | RebaseState::UpToDate => "✅".bright_green(),
+            RebaseState::Conflicted | RebaseState::InProgress => "⚔️".bright_red(),
+            RebaseState::Failed => "❌".bright_red(),
+            RebaseState::Skipped | RebaseState::Pending => "⏭️".bright_yellow(),
+        };
+        let detail = item
+            .detail
+            .as_ref()
+            .map(|detail| format!(" ({})", detail))
+            .unwrap_or_default();
+        println!(
+            "   {} {}: {}{}",
+            icon,
+            item.worktree_name.bright_white(),
+            item.state.as_str(),
+            detail.bright_black()
+        );
+    }
 
-    println!();
-    println!("{} Repair complete!", "✓".bright_green());
+    let conflicted = batch.count(RebaseState::Conflicted);
+    if conflicted > 0 {
+        say!(
+            "{}",
+            tr!(
+                "\n{} {} worktree(s) stopped mid-rebase. Resolve the conflicts, `git add` the files, then run `imi sync --continue` (or `imi sync --abort`)",
+                "💡".bright_yellow(),
+                conflicted
+            )
+        );
+    } else {
+        say!(
+            "{}",
+            tr!(
+                "{} Rebased {}, {} already up to date",
+                "✅".bright_green(),
+                batch.count(RebaseState::Rebased),
+                batch.count(RebaseState::UpToDate)
+            )
+        );
+    }
     Ok(())
 }
 
-async fn handle_doctor_command(db: &Database, network: bool, verbose: bool) -> Result<()> {
-    use commands::doctor::{print_report, run_doctor, DoctorOpts};
+async fn handle_repair_command(
+    manager: &WorktreeManager,
+    repo: Option<&str>,
+    remap: Option<&path_remap::PathRemap>,
+    from_host: Option<&str>,
+) -> Result<()> {
+    let remap = match (from_host, remap) {
+        (Some(host), Some(remap)) => {
+            let (repos, worktrees) = manager.adopt_host_registrations(host, remap, repo).await?;
+            say!(
+                "{}",
+                tr!(
+                    "{} Adopted {} repositories and {} worktrees from {}",
+                    "🖥️".bright_cyan(),
+                    repos,
+                    worktrees,
+                    host.bright_magenta()
+                )
+            );
+            say!();
+            // Adopted paths are already remapped
+            None
+        }
+        _ => remap,
+    };
 
-    let opts = DoctorOpts { network, verbose };
-    let checks = run_doctor(db.pool(), opts).await?;
-    print_report(&checks);
+    say!(
+        "{}",
+        tr!(
+            "{} Repairing repository paths, worktree links and symlinks...",
+            "🔧".bright_cyan()
+        )
+    );
+    say!();
 
+    manager.repair_repositories(repo, remap).await?;
+
+    say!();
+    say!("{}", tr!("{} Repair complete!", "✓".bright_green()));
     Ok(())
 }
 
-async fn handle_registry_command(db: &Database, cmd: &RegistryCommands) -> Result<()> {
-    use commands::registry;
+async fn handle_hooks_command(
+    manager: &WorktreeManager,
+    cmd: HookCommands,
+    json_mode: bool,
+) -> Result<()> {
+    let (repo, install) = match cmd {
+        HookCommands::Install { repo } => (repo, true),
+        HookCommands::Uninstall { repo } => (repo, false),
+    };
+    let results = manager.activity_hooks(repo.as_deref(), install).await?;
 
-    match cmd {
-        RegistryCommands::Sync { scan_root } => {
-            let path = scan_root.as_ref().map(|s| std::path::Path::new(s));
+    if json_mode {
+        let json: Vec<_> = results
+            .iter()
+            .map(|(repo, dir, changes)| {
+                serde_json::json!({
+                    "repo": repo,
+                    "hooks_dir": dir,
+                    "hooks": changes
+                        .iter()
+                        .map(|(hook, change)| (hook.to_string(), format!("{:?}", change).to_lowercase()))
+                        .collect::<std::collections::BTreeMap<_, _>>(),
+                })
+            })
+            .collect();
+        JsonResponse::success(serde_json::json!({ "repositories": json })).print();
+        return Ok(());
+    }
 
-            registry::sync_filesystem(db.pool(), path).await?;
+    if results.is_empty() {
+        say!("{}", tr!("No repositories registered on this machine."));
+        return Ok(());
+    }
+    for (repo, dir, changes) in &results {
+        say!(
+            "{} {}",
+            repo.bright_yellow(),
+            dir.display().to_string().dimmed()
+        );
+        for (hook, change) in changes {
+            let status = match change {
+                git_hooks::HookChange::Created => "installed".green(),
+                git_hooks::HookChange::Appended => "added to existing hook".green(),
+                git_hooks::HookChange::Removed => "removed".green(),
+                git_hooks::HookChange::Unchanged if install => "already installed".dimmed(),
+                git_hooks::HookChange::Unchanged => "not installed".dimmed(),
+            };
+            say!("   {} {}", hook, status);
         }
-        RegistryCommands::Stats => {
-            // Query registry stats
-            let stats = sqlx::query_as::<
-                _,
-                (
-                    Option<i64>,
-                    Option<i64>,
-                    Option<i64>,
-                    Option<i64>,
-                    Option<i64>,
-                    Option<i64>,
-                    Option<i64>,
-                ),
-            >(
-                r#"
-                SELECT
-                    total_projects,
-                    active_projects,
-                    total_worktrees,
-                    active_worktrees,
-                    in_flight_worktrees,
-                    total_activities,
-                    activities_last_24h
-                FROM get_registry_stats()
-                "#,
+    }
+    if install {
+        say!();
+        say!(
+            "{}",
+            tr!(
+                "{} Commits and branch switches now show up in the activity log",
+                "🪝".bright_cyan()
             )
-            .fetch_one(db.pool())
-            .await?;
+        );
+    }
+    Ok(())
+}
 
-            println!("\n{}", "━".repeat(60).bright_black());
-            println!("{}", "iMi Registry Statistics".bold().bright_white());
-            println!("{}\n", "━".repeat(60).bright_black());
-            println!(
-                "Total projects: {}",
-                stats.0.unwrap_or(0).to_string().green()
-            );
-            println!(
-                "Active projects: {}",
-                stats.1.unwrap_or(0).to_string().green()
-            );
+async fn handle_import_worktrees_command(
+    imi: &Imi,
+    repo: Option<&str>,
+    yes: bool,
+    dry_run: bool,
+    json_mode: bool,
+) -> Result<()> {
+    let mut plan = ops::unmanaged_worktrees(imi, repo).await?;
+    let interactive = !yes && !json_mode && !dry_run && std::io::stdin().is_terminal();
+    if !yes && !dry_run && !interactive {
+        return Err(anyhow::anyhow!(tr!(
+            "Confirming each worktree needs a terminal; pass --yes to import every one with an inferred type, or --dry-run to list them"
+        )));
+    }
+
+    if !json_mode {
+        for path in &plan.detached {
+            say!(
+                "{}",
+                tr!(
+                    "{} Skipping {} (detached HEAD; check out a branch to import it)",
+                    "⚠️".bright_yellow(),
+                    path.display()
+                )
+            );
+        }
+    }
+    if plan.worktrees.is_empty() || dry_run {
+        if json_mode {
+            JsonResponse::success(serde_json::to_value(&plan)?).print();
+        } else if plan.worktrees.is_empty() {
+            say!(
+                "{}",
+                tr!(
+                    "{} No unmanaged worktrees in {}",
+                    "✅".bright_green(),
+                    plan.repo_name.bright_cyan()
+                )
+            );
+        } else {
+            say!(
+                "{}",
+                tr!(
+                    "{} {} unmanaged worktree(s) in {}:",
+                    "📋".bright_cyan(),
+                    plan.worktrees.len(),
+                    plan.repo_name.bright_cyan()
+                )
+            );
+            for worktree in &plan.worktrees {
+                print_unmanaged_worktree(worktree);
+            }
+        }
+        return Ok(());
+    }
+
+    if interactive {
+        let types: Vec<String> = ops::list_worktree_types(imi)
+            .await?
+            .into_iter()
+            .map(|wt_type| wt_type.name)
+            .collect();
+        // Labels are translated, so each menu item carries its action
+        enum Choice {
+            Import,
+            Edit,
+            Skip,
+        }
+        let mut chosen = Vec::new();
+        for mut worktree in std::mem::take(&mut plan.worktrees) {
+            print_unmanaged_worktree(&worktree);
+            let mut choices = vec![
+                (Choice::Edit, tr!("Edit type and name")),
+                (Choice::Skip, tr!("Skip")),
+            ];
+            if let Some(wt_type) = &worktree.worktree_type {
+                choices.insert(
+                    0,
+                    (
+                        Choice::Import,
+                        tr!("Import as {} '{}'", wt_type, worktree.name),
+                    ),
+                );
+            }
+            let items: Vec<&str> = choices.iter().map(|(_, label)| label.as_str()).collect();
+            let Some(choice) =
+                dialoguer::Select::with_theme(&dialoguer::theme::ColorfulTheme::default())
+                    .items(&items)
+                    .default(0)
+                    .interact_opt()?
+            else {
+                say!("{}", tr!("{} Import cancelled", "ℹ️".bright_blue()));
+                return Ok(());
+            };
+            match choices[choice].0 {
+                Choice::Skip => continue,
+                Choice::Edit => {
+                    let current = worktree
+                        .worktree_type
+                        .as_ref()
+                        .and_then(|wt_type| types.iter().position(|t| t == wt_type))
+                        .unwrap_or(0);
+                    let index =
+                        dialoguer::Select::with_theme(&dialoguer::theme::ColorfulTheme::default())
+                            .with_prompt(tr!("Type"))
+                            .items(&types)
+                            .default(current)
+                            .interact()?;
+                    worktree.worktree_type = Some(types[index].clone());
+                    worktree.name =
+                        dialoguer::Input::with_theme(&dialoguer::theme::ColorfulTheme::default())
+                            .with_prompt(tr!("Name"))
+                            .with_initial_text(&worktree.name)
+                            .interact_text()?;
+                }
+                Choice::Import => {}
+            }
+            chosen.push(worktree);
+        }
+        plan.worktrees = chosen;
+    }
+
+    let outcome = ops::import_worktrees(imi, &plan.repo_name, &plan.worktrees).await?;
+    if json_mode {
+        JsonResponse::success(serde_json::to_value(&outcome)?).print();
+        return Ok(());
+    }
+    for worktree in &outcome.imported {
+        say!(
+            "{}",
+            tr!(
+                "{} Imported {} ({})",
+                "➕".bright_green(),
+                worktree.name.bright_green(),
+                worktree.path.bright_black()
+            )
+        );
+    }
+    for skipped in &outcome.skipped {
+        say!(
+            "{}",
+            tr!(
+                "{} Skipped {}: {}",
+                "⚠️".bright_yellow(),
+                skipped.path.display(),
+                skipped.reason
+            )
+        );
+    }
+    say!(
+        "{}",
+        tr!(
+            "{} {} worktree(s) imported into {}",
+            "✅".bright_green(),
+            outcome.imported.len(),
+            outcome.repo_name.bright_cyan()
+        )
+    );
+    Ok(())
+}
+
+fn print_unmanaged_worktree(worktree: &ops::UnmanagedWorktree) {
+    let mapping = match &worktree.worktree_type {
+        Some(wt_type) => format!("{} {}", wt_type.bright_blue(), worktree.name.bright_green()),
+        None => "no type inferred".bright_yellow().to_string(),
+    };
+    println!(
+        "   {} [{}] → {}",
+        worktree.path.display(),
+        worktree.branch.bright_yellow(),
+        mapping
+    );
+}
+
+async fn handle_doctor_command(db: &Database, network: bool, verbose: bool) -> Result<()> {
+    use commands::doctor::{print_report, run_doctor, DoctorOpts};
+
+    let opts = DoctorOpts { network, verbose };
+    let checks = run_doctor(db.pool(), opts).await?;
+    print_report(&checks);
+
+    Ok(())
+}
+
+async fn handle_daemon_command(imi: &Imi, cmd: DaemonCommands, json_mode: bool) -> Result<()> {
+    let db = imi.database();
+    let config = &imi.manager().config;
+    match cmd {
+        DaemonCommands::Run { once, metrics } => {
+            let scheduler = daemon::FetchScheduler::new(db.clone(), config);
+
+            if !once {
+                if json_mode {
+                    anyhow::bail!(tr!(
+                        "JSON output is not supported for the long-running daemon"
+                    ));
+                }
+                let maintenance = maintenance::MaintenanceScheduler::new(imi.manager())?;
+                let watcher = daemon::StatusCacheWatcher::new(db.clone(), config);
+                let exporter = async {
+                    match metrics {
+                        Some(addr) => serve_metrics(imi, addr).await,
+                        None => Ok(()),
+                    }
+                };
+                // try_join so a metrics port that can't be bound stops the daemon
+                tokio::try_join!(scheduler.run(), maintenance.run(), watcher.run(), exporter)?;
+                return Ok(());
+            }
+
+            let outcomes = scheduler.run_once().await?;
+            if json_mode {
+                let results: Vec<_> = outcomes
+                    .iter()
+                    .map(|o| {
+                        serde_json::json!({
+                            "repo_name": o.repo_name,
+                            "duration_ms": o.duration_ms,
+                            "success": o.error.is_none(),
+                            "error": o.error,
+                        })
+                    })
+                    .collect();
+                JsonResponse::success(serde_json::json!({ "fetched": results })).print();
+            } else {
+                for o in &outcomes {
+                    match &o.error {
+                        None => println!(
+                            "{}",
+                            tr!(
+                                "{} {} ({} ms)",
+                                "✅".bright_green(),
+                                o.repo_name.bright_blue(),
+                                o.duration_ms
+                            )
+                        ),
+                        Some(err) => eprintln!(
+                            "{} {}: {}",
+                            "❌".bright_red(),
+                            o.repo_name.bright_blue(),
+                            err
+                        ),
+                    }
+                }
+            }
+        }
+        DaemonCommands::Status => {
+            let states = db.list_fetch_states().await?;
+
+            if json_mode {
+                JsonResponse::success(serde_json::json!({
+                    "fetch_enabled": config.daemon_settings.fetch_enabled,
+                    "fetch_interval_secs": config.daemon_settings.fetch_interval_secs,
+                    "excluded": config.daemon_settings.fetch_exclude,
+                    "repositories": states,
+                }))
+                .print();
+                return Ok(());
+            }
+
+            say!("{}", tr!("{} Background fetch status", "🛰️".bright_cyan()));
+            say!("{}", "─".repeat(60).bright_black());
+
+            if states.is_empty() {
+                say!(
+                    "{}",
+                    tr!(
+                        "{} No fetches recorded yet. Start one with 'imi daemon run'",
+                        "ℹ️".bright_blue()
+                    )
+                );
+                return Ok(());
+            }
+
+            for state in &states {
+                let age = chrono::Utc::now().signed_duration_since(state.last_fetch_at);
+                let icon = if state.last_error.is_none() {
+                    "✅".bright_green()
+                } else {
+                    "❌".bright_red()
+                };
+                println!(
+                    "{}",
+                    tr!(
+                        "{} {} - last fetch {}m ago ({} ms)",
+                        icon,
+                        state.project_name.bright_blue(),
+                        age.num_minutes(),
+                        state.last_duration_ms
+                    )
+                );
+                if let Some(err) = &state.last_error {
+                    println!(
+                        "{}",
+                        tr!(
+                            "   {} {} consecutive failure(s): {}",
+                            "⚠️".bright_yellow(),
+                            state.failure_count,
+                            err
+                        )
+                    );
+                }
+            }
+
+            if !config.daemon_settings.fetch_exclude.is_empty() {
+                say!(
+                    "{}",
+                    tr!(
+                        "\n{} Excluded: {}",
+                        "🚫".bright_black(),
+                        config.daemon_settings.fetch_exclude.join(", ")
+                    )
+                );
+            }
+        }
+        DaemonCommands::Tasks => {
+            let now = chrono::Utc::now();
+            let statuses =
+                maintenance::task_statuses(&config.maintenance, &maintenance::TaskLog::load(), now);
+
+            if json_mode {
+                JsonResponse::success(serde_json::json!({ "tasks": statuses })).print();
+                return Ok(());
+            }
+
+            say!("{}", tr!("{} Maintenance tasks", "🧰".bright_cyan()));
+            say!("{}", "─".repeat(60).bright_black());
+            for status in &statuses {
+                let icon = match (&status.schedule_error, &status.last_run) {
+                    (Some(_), _) => "❌".bright_red(),
+                    (None, Some(run)) if run.error.is_some() => "⚠️".bright_yellow(),
+                    _ if !status.enabled() => "⏸️".bright_black(),
+                    _ => "✅".bright_green(),
+                };
+                let next = match status.next_run {
+                    Some(next) if next <= now => "due now".to_string(),
+                    Some(next) => format!("next {}", time_until(next)),
+                    None => "off".to_string(),
+                };
+                println!(
+                    "{} {:<16} {:<14} {}",
+                    icon,
+                    status.task.name().bright_blue(),
+                    status.schedule,
+                    next.bright_black()
+                );
+                if let Some(error) = &status.schedule_error {
+                    println!("   {} {}", "↳".bright_black(), error.red());
+                }
+                let Some(run) = &status.last_run else {
+                    continue;
+                };
+                match &run.error {
+                    Some(error) => println!(
+                        "{}",
+                        tr!(
+                            "   {} last run {} failed ({} in a row): {}",
+                            "↳".bright_black(),
+                            time_ago(run.started_at),
+                            run.failures,
+                            error
+                        )
+                    ),
+                    None => println!(
+                        "{}",
+                        tr!(
+                            "   {} last run {}: {}",
+                            "↳".bright_black(),
+                            time_ago(run.started_at),
+                            run.summary.as_deref().unwrap_or_default()
+                        )
+                    ),
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Print events as NDJSON, one object per line, whatever the output format;
+/// stops quietly once stdout is closed
+async fn handle_events_command(
+    config: &Config,
+    filter: EventFilter,
+    follow: bool,
+    lines: Option<usize>,
+) -> Result<()> {
+    let Some(log) = EventLog::from_settings(&config.event_hooks)? else {
+        anyhow::bail!(tr!(
+            "The event log is off (event_hooks.event_log_max_bytes = 0)"
+        ));
+    };
+
+    let mut stdout = std::io::stdout().lock();
+    let mut emit = |event: &StreamEvent| {
+        serde_json::to_string(event)
+            .map_err(std::io::Error::from)
+            .and_then(|line| writeln!(stdout, "{}", line))
+            .and_then(|_| stdout.flush())
+            .is_ok()
+    };
+
+    let limit = lines.unwrap_or(if follow { 0 } else { 20 });
+    let (recorded, offset) = log.tail(&filter, limit)?;
+    if !recorded.iter().all(&mut emit) || !follow {
+        return Ok(());
+    }
+    log.follow(&filter, offset, emit).await
+}
+
+fn handle_alias_list_command(config: &Config, json_mode: bool) {
+    if json_mode {
+        JsonResponse::success(serde_json::json!({ "aliases": config.aliases })).print();
+        return;
+    }
+    if config.aliases.is_empty() {
+        say!(
+            "{}",
+            tr!(
+                "No aliases configured. Add them under [aliases] in the config, e.g. hotfix = \"add fix {{1}} --publish\""
+            )
+        );
+        return;
+    }
+    let width = config.aliases.keys().map(String::len).max().unwrap_or(0);
+    for (name, template) in &config.aliases {
+        println!(
+            "{:<width$}  imi {}",
+            name.bright_cyan(),
+            template,
+            width = width
+        );
+    }
+}
+
+async fn handle_debug_bundle_command(
+    config: &Config,
+    output: Option<PathBuf>,
+    include_db: bool,
+    json_mode: bool,
+) -> Result<()> {
+    let output = output.unwrap_or_else(|| {
+        PathBuf::from(format!(
+            "imi-debug-{}.tar.gz",
+            chrono::Local::now().format("%Y%m%d-%H%M%S")
+        ))
+    });
+    if !json_mode {
+        say!(
+            "{}",
+            tr!("{} Collecting diagnostics...", "🔍".bright_cyan())
+        );
+    }
+    let summary = debug_bundle::create(config, &output, include_db).await?;
+
+    if json_mode {
+        JsonResponse::success(serde_json::to_value(&summary)?).print();
+        return Ok(());
+    }
+    for warning in &summary.warnings {
+        eprintln!(
+            "{}",
+            tr!("{} Not collected: {}", "⚠️".bright_yellow(), warning)
+        );
+    }
+    if summary.includes_database {
+        eprintln!(
+            "{}",
+            tr!(
+                "{} The bundle contains a full database dump; it is not anonymized",
+                "⚠️".bright_yellow()
+            )
+        );
+    }
+    println!(
+        "{}",
+        tr!(
+            "{} Wrote {} ({} files)",
+            "✅".bright_green(),
+            summary.path.display().to_string().bright_cyan(),
+            summary.files.len()
+        )
+    );
+    say!(
+        "{}",
+        tr!("   Review it before attaching it to a bug report")
+    );
+    Ok(())
+}
+
+fn handle_audit_command(config: &Config, cmd: AuditCommands, json_mode: bool) -> Result<()> {
+    let log = AuditLog::from_settings(&config.audit_settings)?;
+
+    let entries = match &cmd {
+        AuditCommands::Tail { limit } => log.tail(*limit)?,
+        AuditCommands::Search {
+            pattern,
+            command,
+            failed,
+            limit,
+        } => log.search(pattern.as_deref(), command.as_deref(), *failed, *limit)?,
+    };
+
+    if json_mode {
+        JsonResponse::success(serde_json::json!({
+            "log_path": log.path().display().to_string(),
+            "entries": entries,
+        }))
+        .print();
+        return Ok(());
+    }
+
+    if !config.audit_settings.is_enabled() {
+        say!(
+            "{}",
+            tr!(
+                "{} Audit mode is off. Enable it with audit_settings.enabled = true or IMI_AUDIT=1",
+                "💡".bright_yellow()
+            )
+        );
+    }
+
+    if entries.is_empty() {
+        say!(
+            "{}",
+            tr!(
+                "{} No audit entries found in {}",
+                "ℹ️".bright_blue(),
+                log.path().display()
+            )
+        );
+        return Ok(());
+    }
+
+    for entry in &entries {
+        let status = if entry.success {
+            "✅".bright_green()
+        } else {
+            "❌".bright_red()
+        };
+        let who = entry
+            .agent_id
+            .as_deref()
+            .or(entry.user.as_deref())
+            .unwrap_or("unknown");
+
+        println!(
+            "{}",
+            tr!(
+                "{} {} {} {} ({} ms)",
+                status,
+                entry
+                    .timestamp
+                    .with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string()
+                    .bright_black(),
+                who.bright_cyan(),
+                entry.args.join(" "),
+                entry.duration_ms
+            )
+        );
+        if let Some(cwd) = &entry.cwd {
+            println!("   {} {}", "📂".bright_black(), cwd.bright_black());
+        }
+        if let Some(err) = &entry.error {
+            println!("   {} {}", "⚠️".bright_yellow(), err);
+        }
+    }
+
+    Ok(())
+}
+
+async fn handle_prune_remote_command(
+    imi: &Imi,
+    repo: Option<&str>,
+    include_unmerged: bool,
+    dry_run: bool,
+    yes: bool,
+    json_mode: bool,
+) -> Result<()> {
+    let plan = ops::remote_branches_to_prune(imi, repo, include_unmerged).await?;
+    let confirm = !yes && !dry_run && !plan.branches.is_empty();
+    if confirm && (json_mode || !std::io::stdin().is_terminal()) {
+        return Err(anyhow::anyhow!(tr!(
+            "Deleting remote branches needs confirmation; pass --yes, or --dry-run to list them"
+        )));
+    }
+
+    if !json_mode {
+        if let Some(error) = &plan.fetch_error {
+            eprintln!(
+                "{}",
+                tr!(
+                    "{} Couldn't refresh origin, the list may be stale: {}",
+                    "⚠️".bright_yellow(),
+                    error
+                )
+            );
+        }
+        if !plan.in_use.is_empty() || !plan.protected.is_empty() {
+            say!(
+                "{}",
+                tr!(
+                    "{} Keeping {} branch(es) used by worktrees and {} protected",
+                    "ℹ️".bright_blue(),
+                    plan.in_use.len(),
+                    plan.protected.len()
+                )
+            );
+        }
+        if plan.branches.is_empty() {
+            say!(
+                "{}",
+                tr!(
+                    "{} No remote branches to prune in {}",
+                    "✅".bright_green(),
+                    plan.repo_name.bright_cyan()
+                )
+            );
+        } else {
+            say!(
+                "{}",
+                tr!(
+                    "{} {} remote branch(es) in {}:",
+                    "🌿".bright_cyan(),
+                    plan.branches.len(),
+                    plan.repo_name.bright_cyan()
+                )
+            );
+            for branch in &plan.branches {
+                let state = match branch.merged {
+                    true => format!("merged into {}", plan.default_branch).bright_black(),
+                    false => "not merged".bright_yellow(),
+                };
+                println!(
+                    "   {}/{:<40} {}",
+                    plan.remote,
+                    branch.name.bright_green(),
+                    state
+                );
+            }
+        }
+    }
+    if plan.branches.is_empty() || dry_run {
+        if json_mode {
+            JsonResponse::success(serde_json::to_value(&plan)?).print();
+        }
+        return Ok(());
+    }
+
+    if confirm {
+        let unmerged = plan.branches.iter().filter(|b| !b.merged).count();
+        let prompt = match unmerged {
+            0 => tr!("Delete {} remote branch(es)?", plan.branches.len()),
+            _ => tr!(
+                "Delete {} remote branch(es), {} not merged?",
+                plan.branches.len(),
+                unmerged
+            ),
+        };
+        let confirmed = dialoguer::Confirm::new()
+            .with_prompt(prompt)
+            .default(false)
+            .interact()?;
+        if !confirmed {
+            say!("{}", tr!("{} Prune cancelled", "ℹ️".bright_blue()));
+            return Ok(());
+        }
+    }
+
+    ops::prune_remote_branches(imi, &plan)?;
+    if json_mode {
+        JsonResponse::success(serde_json::to_value(&plan)?).print();
+    } else {
+        say!(
+            "{}",
+            tr!(
+                "{} Deleted {} remote branch(es)",
+                "✓".bright_green(),
+                plan.branches.len()
+            )
+        );
+    }
+    Ok(())
+}
+
+async fn handle_repo_command(imi: &Imi, cmd: RepoCommands, json_mode: bool) -> Result<()> {
+    let (repo, tags) = match cmd {
+        RepoCommands::Show { repo } => {
+            let detail = ops::show_repository(imi, &repo).await?;
+            if json_mode {
+                JsonResponse::success(serde_json::to_value(&detail)?).print();
+            } else {
+                print_repository_detail(&detail);
+            }
+            return Ok(());
+        }
+        RepoCommands::Set {
+            repo,
+            remote_url,
+            default_branch,
+            path,
+        } => {
+            let edit = RepositoryEdit {
+                remote_url,
+                default_branch,
+                path,
+            };
+            let outcome = ops::edit_repository(imi, &repo, edit).await?;
+            if json_mode {
+                JsonResponse::success(serde_json::to_value(&outcome)?).print();
+            } else if outcome.changes.is_empty() {
+                say!(
+                    "{}",
+                    tr!(
+                        "{} {} already has those settings",
+                        "ℹ️".bright_blue(),
+                        repo.bright_green()
+                    )
+                );
+            } else {
+                say!(
+                    "{}",
+                    tr!("{} Updated {}", "✅".bright_green(), repo.bright_green())
+                );
+                for change in &outcome.changes {
+                    println!("   {}", change);
+                }
+                if outcome.worktrees_repaired > 0 {
+                    println!(
+                        "{}",
+                        tr!(
+                            "   {} worktree path(s) repaired",
+                            outcome.worktrees_repaired
+                        )
+                    );
+                }
+            }
+            return Ok(());
+        }
+        RepoCommands::Stats {
+            repo,
+            git,
+            weeks,
+            top,
+            refresh,
+        } => {
+            let options = ops::RepoStatsOptions {
+                repo,
+                git,
+                weeks,
+                top,
+                refresh,
+            };
+            let stats = ops::repository_stats(imi, options).await?;
+            if json_mode {
+                JsonResponse::success(serde_json::to_value(&stats)?).print();
+            } else {
+                print_repository_stats(&stats);
+            }
+            return Ok(());
+        }
+        RepoCommands::Archive { repo } => {
+            let changed = ops::archive_repository(imi, &repo, true).await?;
+            print_archive_outcome(&repo, true, changed, json_mode);
+            return Ok(());
+        }
+        RepoCommands::Unarchive { repo } => {
+            let changed = ops::archive_repository(imi, &repo, false).await?;
+            print_archive_outcome(&repo, false, changed, json_mode);
+            return Ok(());
+        }
+        RepoCommands::Tag { repo, tags } => {
+            let tags = ops::tag_repository(imi, &repo, &tags).await?;
+            (Some(repo), tags)
+        }
+        RepoCommands::Untag { repo, tags } => {
+            let tags = ops::untag_repository(imi, &repo, &tags).await?;
+            (Some(repo), tags)
+        }
+        RepoCommands::Tags { repo } => {
+            let all = ops::repository_tags(imi).await?;
+            match repo {
+                Some(repo) => {
+                    let tags = all.get(&repo).cloned().unwrap_or_default();
+                    (Some(repo), tags)
+                }
+                None => {
+                    if json_mode {
+                        JsonResponse::success(serde_json::json!({ "repositories": all })).print();
+                    } else if all.is_empty() {
+                        say!(
+                            "{}",
+                            tr!(
+                                "{} No tagged repositories; add tags with imi repo tag <repo> <tag>...",
+                                "ℹ️".bright_blue()
+                            )
+                        );
+                    } else {
+                        for (repo, tags) in &all {
+                            println!(
+                                "{} {}: {}",
+                                "🏷️".bright_cyan(),
+                                repo.bright_green(),
+                                tags.join(", ").bright_magenta()
+                            );
+                        }
+                    }
+                    return Ok(());
+                }
+            }
+        }
+    };
+
+    let repo = repo.unwrap_or_default();
+    if json_mode {
+        JsonResponse::success(serde_json::json!({ "repository": repo, "tags": tags })).print();
+    } else if tags.is_empty() {
+        println!(
+            "{}",
+            tr!("{} {} has no tags", "🏷️".bright_cyan(), repo.bright_green())
+        );
+    } else {
+        println!(
+            "{} {}: {}",
+            "🏷️".bright_cyan(),
+            repo.bright_green(),
+            tags.join(", ").bright_magenta()
+        );
+    }
+    Ok(())
+}
+
+fn print_archive_outcome(repo: &str, archived: bool, changed: bool, json_mode: bool) {
+    if json_mode {
+        JsonResponse::success(serde_json::json!({
+            "repository": repo,
+            "archived": archived,
+            "changed": changed,
+        }))
+        .print();
+    } else if !changed {
+        say!(
+            "{}",
+            tr!(
+                "{} {} is already {}",
+                "ℹ️".bright_blue(),
+                repo.bright_green(),
+                if archived { "archived" } else { "listed" }
+            )
+        );
+    } else if archived {
+        say!(
+            "{}",
+            tr!(
+                "{} Archived {}; list, status and go show it again with --all",
+                "🗄️".bright_cyan(),
+                repo.bright_green()
+            )
+        );
+    } else {
+        say!(
+            "{}",
+            tr!("{} Unarchived {}", "✅".bright_green(), repo.bright_green())
+        );
+    }
+}
+
+fn print_repository_stats(stats: &ops::RepositoryStats) {
+    say!(
+        "{} {}",
+        "📊".bright_cyan(),
+        stats.repo.bright_green().bold()
+    );
+    println!("{}", tr!("   Worktrees:      {}", stats.worktrees));
+    println!("{}", tr!("   In flight:      {}", stats.in_flight));
+    println!("{}", tr!("   Open PRs:       {}", stats.open_prs));
+
+    let Some(git) = &stats.git else {
+        return;
+    };
+    let cached = if git.cached {
+        format!(
+            " (cached {})",
+            git.computed_at
+                .with_timezone(&chrono::Local)
+                .format("%H:%M")
+        )
+    } else {
+        String::new()
+    };
+    println!(
+        "{}",
+        tr!(
+            "\n   {} since {} at {}{}",
+            git.branch.bright_cyan(),
+            git.since,
+            &git.head[..git.head.len().min(8)],
+            cached.bright_black()
+        )
+    );
+    println!(
+        "{}",
+        tr!(
+            "   Commits:        {} by {} author(s)",
+            git.commits.to_string().green(),
+            git.unique_authors
+        )
+    );
+    match (&git.pr_merge_time, &git.pr_merge_time_error) {
+        (Some(time), _) => println!(
+            "   PR merge time:  {:.1}h average, {:.1}h median ({} PRs)",
+            time.average_hours, time.median_hours, time.pull_requests
+        ),
+        (None, Some(error)) => println!(
+            "{}",
+            tr!(
+                "   PR merge time:  {}",
+                format!("unavailable ({})", error).bright_black()
+            )
+        ),
+        (None, None) => println!(
+            "{}",
+            tr!("   PR merge time:  {}", tr!("no merged PRs").bright_black())
+        ),
+    }
+
+    let busiest = git
+        .weeks
+        .iter()
+        .map(|w| w.commits)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    println!("\n   {}", tr!("Commits per week").bold());
+    for week in &git.weeks {
+        let bar = "█".repeat((week.commits * 30).div_ceil(busiest));
+        println!(
+            "   {}  {:>4}  {}",
+            week.week_start,
+            week.commits,
+            bar.bright_blue()
+        );
+    }
+
+    if !git.authors.is_empty() {
+        println!("\n   {}", tr!("Top authors").bold());
+        for author in &git.authors {
             println!(
-                "Total worktrees: {}",
-                stats.2.unwrap_or(0).to_string().cyan()
+                "   {:>5}  {} {}",
+                author.commits,
+                author.name,
+                format!("<{}>", author.email).bright_black()
             );
+        }
+    }
+    if !git.hot_directories.is_empty() {
+        println!("\n   {}", tr!("Hottest directories").bold());
+        for directory in &git.hot_directories {
             println!(
-                "Active worktrees: {}",
-                stats.3.unwrap_or(0).to_string().cyan()
+                "   {:>5}  {}",
+                directory.commits,
+                directory.path.bright_cyan()
             );
+        }
+    }
+}
+
+fn print_repository_detail(detail: &RepositoryDetail) {
+    let repository = &detail.repository;
+    say!(
+        "{} {}",
+        "📦".bright_cyan(),
+        repository.name.bright_green().bold()
+    );
+    println!("{}", tr!("   Path:           {}", repository.path));
+    println!("{}", tr!("   Remote:         {}", repository.remote_url));
+    println!(
+        "{}",
+        tr!("   Default branch: {}", repository.default_branch)
+    );
+    if let Some(clone_mode) = &detail.clone_mode {
+        println!("{}", tr!("   Clone:          {}", clone_mode));
+    }
+    if let Some(archived_at) = detail.archived_at {
+        println!(
+            "{}",
+            tr!(
+                "   Archived:       {}",
+                archived_at
+                    .with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+                    .bright_yellow()
+            )
+        );
+    }
+    if !detail.tags.is_empty() {
+        println!(
+            "{}",
+            tr!(
+                "   Tags:           {}",
+                detail.tags.join(", ").bright_magenta()
+            )
+        );
+    }
+    println!("{}", tr!("   Worktrees:      {}", detail.worktrees));
+    println!(
+        "{}",
+        tr!(
+            "   Registered:     {}",
+            repository
+                .created_at
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M")
+        )
+    );
+    for problem in &detail.problems {
+        println!("   {} {}", "⚠️".bright_yellow(), problem);
+    }
+    if !detail.problems.is_empty() {
+        println!(
+            "   {}",
+            format!(
+                "Fix with: imi repo set {} --remote-url/--default-branch/--path",
+                repository.name
+            )
+            .bright_black()
+        );
+    }
+}
+
+/// Run `command` in each matching repository's trunk, one after another;
+/// fails at the end if any run failed
+async fn handle_bisect_command(
+    imi: &Imi,
+    options: ops::BisectOptions,
+    json_mode: bool,
+) -> Result<()> {
+    let outcome = ops::bisect(imi, options).await?;
+    if json_mode {
+        JsonResponse::success(serde_json::to_value(&outcome)?).print();
+        return Ok(());
+    }
+
+    let culprit = &outcome.culprit;
+    println!(
+        "{}",
+        tr!(
+            "{} First bad commit: {} {}",
+            "🎯".bright_red(),
+            culprit.id.bright_yellow(),
+            culprit.summary
+        )
+    );
+    println!(
+        "   {}",
+        format!(
+            "{}, {}",
+            culprit.author,
+            culprit.time.format("%Y-%m-%d %H:%M")
+        )
+        .bright_black()
+    );
+    if let Some(path) = &outcome.kept_path {
+        print_path_result(
+            format!("{} Bisect worktree kept at", "📂".bright_cyan()),
+            &path,
+        );
+    }
+    Ok(())
+}
+
+async fn handle_mv_command(
+    imi: &Imi,
+    repo: Option<&str>,
+    dry_run: bool,
+    json_mode: bool,
+) -> Result<()> {
+    let moves = ops::relayout_worktrees(imi, repo, dry_run).await?;
+    if json_mode {
+        JsonResponse::success(serde_json::to_value(&moves)?).print();
+        return Ok(());
+    }
+    if moves.is_empty() {
+        say!(
+            "{}",
+            tr!(
+                "{} Every worktree is already where the layout puts it",
+                "✅".bright_green()
+            )
+        );
+        return Ok(());
+    }
+
+    for relocation in &moves {
+        println!(
+            "   {} {}: {} → {}",
+            "📦".bright_cyan(),
+            relocation.worktree_name.bright_white(),
+            relocation.from.display().to_string().bright_black(),
+            relocation.to.display()
+        );
+    }
+    if dry_run {
+        say!(
+            "{}",
+            tr!(
+                "{} Would move {} worktree(s); run without --dry-run to move them",
+                "ℹ️".bright_blue(),
+                moves.len()
+            )
+        );
+    } else {
+        say!(
+            "{}",
+            tr!("{} Moved {} worktree(s)", "✅".bright_green(), moves.len())
+        );
+    }
+    Ok(())
+}
+
+async fn handle_exec_command(
+    imi: &Imi,
+    tag: Option<&str>,
+    command: &[String],
+    json_mode: bool,
+) -> Result<()> {
+    let repositories = match tag {
+        Some(tag) => ops::list_repositories_with_tag(imi, tag).await?,
+        None => ops::list_repositories(imi).await?,
+    };
+    if command.is_empty() {
+        anyhow::bail!(tr!("No command given; pass it after --"));
+    }
+    let capture = imi.config().artifacts.capture_exec;
+
+    let mut results = Vec::new();
+    for repository in &repositories {
+        if !json_mode {
+            say!(
+                "\n{} {} {}",
+                "▶️".bright_cyan(),
+                repository.name.bright_green().bold(),
+                repository.path.bright_black()
+            );
+        }
+        let dir = Path::new(&repository.path);
+        // Interactive commands need the terminal; only pipe what's kept
+        let run = if capture || json_mode {
+            artifacts::run_captured(command, dir, !json_mode).await?
+        } else {
+            artifacts::run_inherited(command, dir).await?
+        };
+        if capture {
+            // The command already ran; failing to keep its output doesn't fail it
+            let recorded = match ops::exec_worktree(imi, repository).await {
+                Ok(Some(worktree)) => {
+                    ops::record_artifact(imi, &worktree, ArtifactKind::Exec, command, &run)
+                        .await
+                        .map(|_| ())
+                }
+                Ok(None) => Ok(()),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = recorded {
+                eprintln!(
+                    "{}",
+                    tr!(
+                        "{} Couldn't keep the output of {}: {}",
+                        "⚠️".bright_yellow(),
+                        repository.name,
+                        e
+                    )
+                );
+            }
+        }
+        let (success, exit_code) = (run.success, run.exit_code);
+        let (stdout, stderr) = if json_mode {
+            (
+                String::from_utf8_lossy(&run.stdout).to_string(),
+                String::from_utf8_lossy(&run.stderr).to_string(),
+            )
+        } else {
+            (String::new(), String::new())
+        };
+
+        results.push(serde_json::json!({
+            "repository": repository.name,
+            "path": repository.path,
+            "success": success,
+            "exit_code": exit_code,
+            "stdout": stdout,
+            "stderr": stderr,
+        }));
+    }
+
+    let failed: Vec<&str> = results
+        .iter()
+        .filter(|r| r["success"] == false)
+        .filter_map(|r| r["repository"].as_str())
+        .collect();
+    if json_mode {
+        JsonResponse::success(serde_json::json!({ "results": results })).print();
+    } else {
+        say!(
+            "{}",
+            tr!(
+                "\n{} Ran in {} repositories, {} failed",
+                "📊".bright_cyan(),
+                repositories.len(),
+                failed.len()
+            )
+        );
+    }
+
+    if !failed.is_empty() {
+        return Err(anyhow::anyhow!(tr!(
+            "Command failed in: {}",
+            failed.join(", ")
+        )));
+    }
+    Ok(())
+}
+
+async fn handle_registry_command(
+    db: &Database,
+    cmd: &RegistryCommands,
+    format: &OutputFormat,
+) -> Result<()> {
+    use commands::registry;
+
+    match cmd {
+        RegistryCommands::Sync { scan_root } => {
+            let path = scan_root.as_ref().map(|s| std::path::Path::new(s));
+
+            registry::sync_filesystem(db.pool(), path).await?;
+        }
+        RegistryCommands::Stats => {
+            // Query registry stats
+            let stats = sqlx::query_as::<
+                _,
+                (
+                    Option<i64>,
+                    Option<i64>,
+                    Option<i64>,
+                    Option<i64>,
+                    Option<i64>,
+                    Option<i64>,
+                    Option<i64>,
+                ),
+            >(
+                r#"
+                SELECT
+                    total_projects,
+                    active_projects,
+                    total_worktrees,
+                    active_worktrees,
+                    in_flight_worktrees,
+                    total_activities,
+                    activities_last_24h
+                FROM get_registry_stats()
+                "#,
+            )
+            .fetch_one(db.pool())
+            .await?;
+
+            if *format != OutputFormat::Table {
+                let data = serde_json::json!({
+                    "total_projects": stats.0.unwrap_or(0),
+                    "active_projects": stats.1.unwrap_or(0),
+                    "total_worktrees": stats.2.unwrap_or(0),
+                    "active_worktrees": stats.3.unwrap_or(0),
+                    "in_flight_worktrees": stats.4.unwrap_or(0),
+                    "total_activities": stats.5.unwrap_or(0),
+                    "activities_last_24h": stats.6.unwrap_or(0),
+                });
+                return print_formatted(format, data.clone(), &[data]);
+            }
+
+            println!("\n{}", "━".repeat(60).bright_black());
+            say!("{}", tr!("iMi Registry Statistics").bold().bright_white());
+            println!("{}\n", "━".repeat(60).bright_black());
             println!(
-                "In-flight worktrees: {}",
-                stats.4.unwrap_or(0).to_string().yellow()
+                "{}",
+                tr!(
+                    "Total projects: {}",
+                    stats.0.unwrap_or(0).to_string().green()
+                )
+            );
+            println!(
+                "{}",
+                tr!(
+                    "Active projects: {}",
+                    stats.1.unwrap_or(0).to_string().green()
+                )
+            );
+            println!(
+                "{}",
+                tr!(
+                    "Total worktrees: {}",
+                    stats.2.unwrap_or(0).to_string().cyan()
+                )
+            );
+            println!(
+                "{}",
+                tr!(
+                    "Active worktrees: {}",
+                    stats.3.unwrap_or(0).to_string().cyan()
+                )
             );
             println!(
-                "Total activities: {}",
-                stats.5.unwrap_or(0).to_string().bright_black()
+                "{}",
+                tr!(
+                    "In-flight worktrees: {}",
+                    stats.4.unwrap_or(0).to_string().yellow()
+                )
+            );
+            println!(
+                "{}",
+                tr!(
+                    "Total activities: {}",
+                    stats.5.unwrap_or(0).to_string().bright_black()
+                )
+            );
+            println!(
+                "{}",
+                tr!(
+                    "Activities (24h): {}",
+                    stats.6.unwrap_or(0).to_string().bright_black()
+                )
+            );
+            say!();
+        }
+    }
+
+    Ok(())
+}
+
+async fn handle_clone_command(
+    init_cmd: &InitCommand,
+    config: &Config,
+    mut repos: Vec<String>,
+    org: Option<String>,
+    topic: Option<String>,
+    jobs: usize,
+    json_mode: bool,
+) -> Result<()> {
+    if let Some(org) = &org {
+        let listed =
+            github::list_owner_repositories(org, topic.as_deref(), &config.retry_settings).await?;
+        if listed.is_empty() && !json_mode {
+            say!(
+                "{}",
+                tr!(
+                    "{} No repositories found for {}{}",
+                    "ℹ️".bright_blue(),
+                    org.bright_white(),
+                    topic
+                        .as_deref()
+                        .map(|t| format!(" with topic '{}'", t))
+                        .unwrap_or_default()
+                )
+            );
+        }
+        repos.extend(listed);
+    }
+    let mut seen = std::collections::HashSet::new();
+    repos.retain(|repo| seen.insert(repo.clone()));
+    if repos.is_empty() && org.is_none() {
+        return Err(anyhow::anyhow!(tr!(
+            "Nothing to clone. Pass repositories (owner/repo) or --org"
+        )));
+    }
+
+    if !json_mode {
+        say!(
+            "{}",
+            tr!(
+                "{} Cloning {} repositor{} into {}",
+                "🔍".bright_cyan(),
+                repos.len(),
+                if repos.len() == 1 { "y" } else { "ies" },
+                config.get_entity_workspace_path().display()
+            )
+        );
+    }
+
+    let outcomes = init_cmd.clone_many(&repos, jobs).await;
+    let count = |status: CloneStatus| outcomes.iter().filter(|o| o.status == status).count();
+    let failed = count(CloneStatus::Failed);
+
+    if json_mode {
+        JsonResponse::success(serde_json::json!({
+            "cloned": count(CloneStatus::Cloned),
+            "resumed": count(CloneStatus::Resumed),
+            "skipped": count(CloneStatus::Skipped),
+            "failed": failed,
+            "results": outcomes,
+        }))
+        .print();
+    } else {
+        say!(
+            "{}",
+            tr!(
+                "\n{} {} cloned, {} registered, {} skipped, {} failed",
+                "📊".bright_cyan(),
+                count(CloneStatus::Cloned),
+                count(CloneStatus::Resumed),
+                count(CloneStatus::Skipped),
+                failed
+            )
+        );
+        if failed > 0 {
+            println!(
+                "{}",
+                tr!(
+                    "   {} Rerun the same command to retry; finished repositories are skipped",
+                    "💡".bright_yellow()
+                )
             );
-            println!(
-                "Activities (24h): {}",
-                stats.6.unwrap_or(0).to_string().bright_black()
+        }
+    }
+
+    if failed > 0 {
+        return Err(anyhow::anyhow!(tr!(
+            "{} of {} clones failed",
+            failed,
+            repos.len()
+        )));
+    }
+    Ok(())
+}
+
+/// The `owner/repo` names to onboard: all listed ones with `all`, else those
+/// ticked in a terminal multi-select
+async fn select_onboard_repositories(
+    config: &Config,
+    db: &Database,
+    target: &str,
+    topic: Option<&str>,
+    language: Option<&str>,
+    all: bool,
+    json_mode: bool,
+) -> Result<Vec<String>> {
+    let owner = github::parse_owner(target).with_context(|| {
+        tr!(
+            "'{}' is not an organization or user; use e.g. gh:acme",
+            target
+        )
+    })?;
+    let owner = config.org_settings.expand_alias(owner);
+    let interactive = !json_mode && std::io::stdin().is_terminal();
+    if !all && !interactive {
+        return Err(anyhow::anyhow!(tr!(
+            "Choosing repositories needs a terminal; pass --all to take every listed one"
+        )));
+    }
+
+    let listed: Vec<_> = github::owner_repositories(owner, &config.retry_settings)
+        .await?
+        .into_iter()
+        .filter(|repo| repo.matches(topic, language))
+        .collect();
+    if listed.is_empty() {
+        if !json_mode {
+            say!(
+                "{}",
+                tr!(
+                    "{} No repositories of {} match",
+                    "ℹ️".bright_blue(),
+                    owner.bright_white()
+                )
             );
-            println!();
         }
+        return Ok(Vec::new());
+    }
+    if all {
+        return Ok(listed.into_iter().map(|repo| repo.full_name).collect());
     }
 
+    let registered: std::collections::HashSet<String> = db
+        .list_repositories()
+        .await?
+        .iter()
+        .filter_map(|repo| github::pulls::parse_github_remote(&repo.remote_url))
+        .map(|(owner, name)| format!("{}/{}", owner, name).to_lowercase())
+        .collect();
+    let width = listed.iter().map(|r| r.full_name.len()).max().unwrap_or(0);
+    let items: Vec<String> = listed
+        .iter()
+        .map(|repo| {
+            let mut item = format!(
+                "{:<width$}  {:<12} {}",
+                repo.full_name,
+                repo.language.as_deref().unwrap_or("-"),
+                repo.description.as_deref().unwrap_or_default(),
+                width = width
+            );
+            if registered.contains(&repo.full_name.to_lowercase()) {
+                item.push_str(" (registered)");
+            }
+            item
+        })
+        .collect();
+
+    let chosen = dialoguer::MultiSelect::with_theme(&dialoguer::theme::ColorfulTheme::default())
+        .with_prompt(tr!(
+            "Repositories of {} to clone (space to toggle, enter to confirm)",
+            owner
+        ))
+        .items(&items)
+        .max_length(20)
+        .interact_opt()?
+        .unwrap_or_default();
+    if chosen.is_empty() {
+        say!("{}", tr!("{} Nothing selected", "ℹ️".bright_blue()));
+    }
+    Ok(chosen
+        .into_iter()
+        .map(|index| listed[index].full_name.clone())
+        .collect())
+}
+
+fn clone_options(args: cli::CloneArgs) -> CloneOptions {
+    CloneOptions {
+        depth: args.depth,
+        filter: args.filter,
+        single_branch: args.single_branch,
+    }
+}
+
+async fn handle_unshallow_command(
+    manager: &WorktreeManager,
+    repo: Option<&str>,
+    json_mode: bool,
+) -> Result<()> {
+    let (repo_name, converted) = manager.unshallow_repository(repo).await?;
+
+    if json_mode {
+        JsonResponse::success(serde_json::json!({
+            "repository": repo_name,
+            "converted": converted,
+        }))
+        .print();
+    } else if converted.is_empty() {
+        say!(
+            "{}",
+            tr!(
+                "{} {} already has its full history",
+                "ℹ️".bright_blue(),
+                repo_name.bright_cyan()
+            )
+        );
+    } else {
+        say!(
+            "{}",
+            tr!(
+                "{} {} is now a full clone (was: {})",
+                "✅".bright_green(),
+                repo_name.bright_cyan(),
+                converted.join(", ")
+            )
+        );
+    }
     Ok(())
 }
 
-async fn handle_init_command(repo: Option<String>, force: bool, json_mode: bool) -> Result<()> {
+async fn handle_init_command(
+    repo: Option<String>,
+    force: bool,
+    offline: bool,
+    yes: bool,
+    clone_options: CloneOptions,
+    json_mode: bool,
+) -> Result<()> {
+    // Config::load would quietly write the defaults, so ask first
+    if first_run::needed()? {
+        let path = first_run::run(yes || json_mode).await?;
+        if !json_mode {
+            say!(
+                "{}",
+                tr!(
+                    "{} Wrote configuration to {}",
+                    "✅".bright_green(),
+                    path.display()
+                )
+            );
+        }
+    }
+
     let config = Config::load().await?;
     let db = Database::new(&config.database_path).await?;
-    let init_cmd = InitCommand::new(force, config, db);
+    let init_cmd = InitCommand::new(force, config, db)
+        .offline(offline)
+        .clone_options(clone_options);
 
-    // Check if repo argument looks like a GitHub repo (owner/repo format)
+    // owner/repo or a remote URL is cloned, anything else is a local path
     if let Some(ref repo_arg) = repo {
-        if repo_arg.contains('/') && !repo_arg.contains(':') {
-            // Looks like owner/repo format - clone from GitHub
+        if init::CloneSource::is_remote(repo_arg) {
             let result = init_cmd.clone_from_github(repo_arg).await?;
 
             if json_mode {
@@ -839,103 +3703,526 @@ async fn handle_init_command(repo: Option<String>, force: bool, json_mode: bool)
                     println!("{}", result.message.red());
                 }
             }
-
-            return Ok(());
+
+            return Ok(());
+        }
+    }
+
+    // No repo argument - normal init
+    let result = init_cmd.execute(None).await?;
+
+    if json_mode {
+        if result.success {
+            JsonResponse::success(serde_json::json!({
+                "message": result.message
+            }))
+            .print();
+        } else {
+            JsonResponse::error(result.message).print();
+        }
+    } else {
+        if result.success {
+            println!("{}", result.message.green());
+        } else {
+            println!("{}", result.message.red());
+        }
+    }
+
+    Ok(())
+}
+
+async fn handle_migrate_office_command(
+    repo: Option<String>,
+    dry_run: bool,
+    force: bool,
+    json_mode: bool,
+) -> Result<()> {
+    let config = Config::load().await?;
+    let db = Database::new(&config.database_path).await?;
+    let init_cmd = InitCommand::new(force, config, db);
+
+    let summary = init_cmd
+        .migrate_office_layout(repo.as_deref(), dry_run)
+        .await?;
+
+    if json_mode {
+        JsonResponse::success(serde_json::json!({
+            "processed": summary.processed,
+            "migrated": summary.migrated,
+            "skipped": summary.skipped,
+            "failed": summary.failed,
+            "dry_run": summary.dry_run,
+            "results": summary.results
+        }))
+        .print();
+    } else {
+        say!();
+        say!(
+            "{} {}",
+            "🏢".bright_cyan(),
+            tr!("Office migration summary").bright_cyan().bold()
+        );
+        println!(
+            "{}",
+            tr!(
+                "   processed={} migrated={} skipped={} failed={} dry_run={}",
+                summary.processed,
+                summary.migrated,
+                summary.skipped,
+                summary.failed,
+                summary.dry_run
+            )
+        );
+
+        for result in &summary.results {
+            let status = match result.status.as_str() {
+                "migrated" => "migrated".bright_green(),
+                "skipped" => "skipped".bright_yellow(),
+                "failed" => "failed".bright_red(),
+                _ => result.status.bright_black(),
+            };
+
+            println!(
+                "   [{}] {}: {} -> {}",
+                status, result.repo_name, result.source_trunk, result.target_trunk
+            );
+            println!("      {}", result.message);
+            for warning in &result.warnings {
+                println!("{}", tr!("      warning: {}", warning));
+            }
+        }
+    }
+
+    if summary.failed > 0 {
+        return Err(anyhow::anyhow!(tr!(
+            "Office migration finished with {} failed repository migrations",
+            summary.failed
+        )));
+    }
+
+    Ok(())
+}
+
+fn parse_metadata_value(raw: &str) -> serde_json::Value {
+    serde_json::from_str(raw).unwrap_or_else(|_| serde_json::Value::String(raw.to_string()))
+}
+
+async fn handle_show_command(
+    imi: &Imi,
+    name: &str,
+    repo: Option<&str>,
+    json_mode: bool,
+) -> Result<()> {
+    let detail = ops::show_worktree(imi, name, repo).await?;
+    if json_mode {
+        JsonResponse::success(serde_json::to_value(&detail)?).print();
+    } else {
+        imi.manager().print_worktree_detail(&detail);
+    }
+    Ok(())
+}
+
+async fn handle_note_command(
+    imi: &Imi,
+    name: &str,
+    text: Option<&str>,
+    repo: Option<&str>,
+    json_mode: bool,
+) -> Result<()> {
+    if let Some(text) = text {
+        let (worktree, note) = ops::add_note(imi, name, repo, text).await?;
+        if json_mode {
+            JsonResponse::success(serde_json::json!({
+                "worktree_name": worktree.name,
+                "note": note,
+            }))
+            .print();
+        } else {
+            say!(
+                "{}",
+                tr!(
+                    "{} Note added to {}",
+                    "🗒️".bright_green(),
+                    worktree.name.bright_cyan()
+                )
+            );
+        }
+        return Ok(());
+    }
+
+    let (worktree, notes) = ops::worktree_notes(imi, name, repo).await?;
+    if json_mode {
+        JsonResponse::success(serde_json::json!({
+            "worktree_name": worktree.name,
+            "notes": notes,
+        }))
+        .print();
+    } else if notes.is_empty() {
+        say!(
+            "{}",
+            tr!(
+                "{} No notes on {}",
+                "ℹ️".bright_blue(),
+                worktree.name.bright_cyan()
+            )
+        );
+    } else {
+        say!(
+            "{}",
+            tr!(
+                "{} Notes on {}",
+                "🗒️".bright_cyan(),
+                worktree.name.bright_cyan()
+            )
+        );
+        worktree::print_notes(&notes);
+    }
+    Ok(())
+}
+
+/// `imi agent rules list/test`: the [agent_assignment] rules, and which one
+/// a hypothetical worktree would get
+async fn handle_agent_rules_command(
+    imi: &Imi,
+    cmd: AgentRuleCommands,
+    json_mode: bool,
+) -> Result<()> {
+    let assignment = &imi.manager().config.agent_assignment;
+    match cmd {
+        AgentRuleCommands::List => {
+            if json_mode {
+                let rules: Vec<_> = assignment
+                    .rules
+                    .iter()
+                    .enumerate()
+                    .map(|(index, rule)| {
+                        let mut value = serde_json::to_value(rule).unwrap_or_default();
+                        value["number"] = serde_json::json!(index + 1);
+                        value
+                    })
+                    .collect();
+                JsonResponse::success(serde_json::json!({ "rules": rules })).print();
+            } else if assignment.rules.is_empty() {
+                say!(
+                    "{}",
+                    tr!(
+                        "{} No [agent_assignment] rules; new worktrees are only claimed with --claim",
+                        "ℹ️".bright_blue()
+                    )
+                );
+            } else {
+                say!(
+                    "{}",
+                    tr!(
+                        "{} Agent assignment rules (first match wins):",
+                        "🤖".bright_cyan()
+                    )
+                );
+                for (index, rule) in assignment.rules.iter().enumerate() {
+                    println!(
+                        "   {}. {} → {}",
+                        index + 1,
+                        rule.describe(),
+                        rule.agent.bright_cyan()
+                    );
+                }
+            }
+        }
+        AgentRuleCommands::Test {
+            worktree_type,
+            name,
+            repo,
+        } => {
+            // Rules with a repo condition only fire when the repo is known
+            let repo_name = imi.manager().resolve_repo_name(repo.as_deref()).await.ok();
+            let found = assignment.find(repo_name.as_deref(), &worktree_type, &name);
+            if json_mode {
+                JsonResponse::success(serde_json::json!({
+                    "repo": repo_name,
+                    "type": worktree_type,
+                    "name": name,
+                    "rule": found.map(|(number, _)| number),
+                    "agent": found.map(|(_, rule)| &rule.agent),
+                }))
+                .print();
+            } else {
+                match found {
+                    Some((number, rule)) => println!(
+                        "{}",
+                        tr!(
+                            "{} Rule {} ({}) assigns {}",
+                            "✅".bright_green(),
+                            number,
+                            rule.describe(),
+                            rule.agent.bright_cyan()
+                        )
+                    ),
+                    None => println!(
+                        "{}",
+                        tr!(
+                            "{} No rule matches; the worktree would be left unclaimed",
+                            "ℹ️".bright_blue()
+                        )
+                    ),
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn handle_agent_scaffold_command(
+    manager: &WorktreeManager,
+    repo: Option<&str>,
+    force: bool,
+    link: bool,
+    json_mode: bool,
+) -> Result<()> {
+    let scaffold = manager.scaffold_agent_files(repo, force, link).await?;
+    if json_mode {
+        JsonResponse::success(serde_json::to_value(&scaffold)?).print();
+        return Ok(());
+    }
+
+    for file in &scaffold.files {
+        let (icon, label) = match file.outcome {
+            FileOutcome::Created => ("📝".bright_green(), tr!("Created")),
+            FileOutcome::Updated => ("📝".bright_yellow(), tr!("Regenerated")),
+            FileOutcome::Kept => ("ℹ️".bright_blue(), tr!("Kept (use --force to regenerate)")),
+        };
+        say!("{} {} {}", icon, label, file.path.display());
+    }
+    if link {
+        say!(
+            "{}",
+            tr!(
+                "{} Linked into {} worktree(s) of {}",
+                "🔗".bright_cyan(),
+                scaffold.worktrees.len(),
+                scaffold.repo_name.bright_cyan()
+            )
+        );
+    }
+    Ok(())
+}
+
+async fn handle_msg_command(imi: &Imi, cmd: MsgCommands, json_mode: bool) -> Result<()> {
+    match cmd {
+        MsgCommands::Send {
+            worktree,
+            text,
+            repo,
+            notify,
+        } => {
+            let (worktree, message) =
+                ops::send_message(imi, &worktree, repo.as_deref(), &text, notify).await?;
+            if json_mode {
+                JsonResponse::success(serde_json::json!({
+                    "worktree_name": worktree.name,
+                    "message": message,
+                }))
+                .print();
+            } else {
+                say!(
+                    "{}",
+                    tr!(
+                        "{} Message sent to {}",
+                        "✉️".bright_green(),
+                        worktree.name.bright_cyan()
+                    )
+                );
+            }
+        }
+        MsgCommands::Read {
+            worktree,
+            repo,
+            unread,
+            keep_unread,
+        } => {
+            let (worktree, messages) = ops::read_messages(
+                imi,
+                worktree.as_deref(),
+                repo.as_deref(),
+                unread,
+                keep_unread,
+            )
+            .await?;
+            if json_mode {
+                JsonResponse::success(serde_json::json!({
+                    "worktree_name": worktree.name,
+                    "messages": messages,
+                }))
+                .print();
+            } else if messages.is_empty() {
+                say!(
+                    "{}",
+                    tr!(
+                        "{} No {}messages for {}",
+                        "ℹ️".bright_blue(),
+                        if unread { "unread " } else { "" },
+                        worktree.name.bright_cyan()
+                    )
+                );
+            } else {
+                say!(
+                    "{}",
+                    tr!(
+                        "{} Messages for {}",
+                        "✉️".bright_cyan(),
+                        worktree.name.bright_cyan()
+                    )
+                );
+                worktree::print_messages(&messages);
+            }
         }
     }
+    Ok(())
+}
 
-    // No repo argument - normal init
-    let result = init_cmd.execute(None).await?;
-
+async fn handle_temp_command(imi: &Imi, options: ops::TempOptions, json_mode: bool) -> Result<()> {
+    let run = ops::run_temp(imi, options).await?;
     if json_mode {
-        if result.success {
-            JsonResponse::success(serde_json::json!({
-                "message": result.message
-            }))
-            .print();
-        } else {
-            JsonResponse::error(result.message).print();
-        }
-    } else {
-        if result.success {
-            println!("{}", result.message.green());
-        } else {
-            println!("{}", result.message.red());
-        }
+        JsonResponse::success(serde_json::to_value(&run)?).print();
+    } else if run.removed {
+        eprintln!(
+            "{}",
+            tr!("{} Removed {}", "🧹".bright_cyan(), run.path.display())
+        );
     }
 
+    if run.exit_code != 0 {
+        return Err(ImiError::Exit {
+            code: run.exit_code,
+        }
+        .into());
+    }
     Ok(())
 }
 
-async fn handle_migrate_office_command(
-    repo: Option<String>,
-    dry_run: bool,
-    force: bool,
+async fn handle_check_command(
+    imi: &Imi,
+    options: ops::CheckOptions,
     json_mode: bool,
 ) -> Result<()> {
-    let config = Config::load().await?;
-    let db = Database::new(&config.database_path).await?;
-    let init_cmd = InitCommand::new(force, config, db);
-
-    let summary = init_cmd
-        .migrate_office_layout(repo.as_deref(), dry_run)
-        .await?;
-
+    let (worktree, artifact) = ops::check_worktree(imi, options).await?;
     if json_mode {
         JsonResponse::success(serde_json::json!({
-            "processed": summary.processed,
-            "migrated": summary.migrated,
-            "skipped": summary.skipped,
-            "failed": summary.failed,
-            "dry_run": summary.dry_run,
-            "results": summary.results
+            "worktree_name": worktree.name,
+            "artifact": artifact,
         }))
         .print();
-    } else {
-        println!();
+    } else if artifact.success {
         println!(
-            "{} {}",
-            "🏢".bright_cyan(),
-            "Office migration summary".bright_cyan().bold()
+            "{} Passed in {} ({:.1}s), output kept as {}",
+            "✅".bright_green(),
+            worktree.name.bright_cyan(),
+            artifact.duration_ms as f64 / 1000.0,
+            artifact.id.to_string().bright_black()
         );
+    } else {
         println!(
-            "   processed={} migrated={} skipped={} failed={} dry_run={}",
-            summary.processed, summary.migrated, summary.skipped, summary.failed, summary.dry_run
+            "{}",
+            tr!(
+                "{} Failed in {}, output kept as {}",
+                "❌".bright_red(),
+                worktree.name.bright_cyan(),
+                artifact.id.to_string().bright_black()
+            )
         );
+    }
 
-        for result in &summary.results {
-            let status = match result.status.as_str() {
-                "migrated" => "migrated".bright_green(),
-                "skipped" => "skipped".bright_yellow(),
-                "failed" => "failed".bright_red(),
-                _ => result.status.bright_black(),
-            };
+    if !artifact.success {
+        return Err(anyhow::anyhow!(tr!(
+            "{} failed in {}",
+            artifact.command,
+            worktree.name
+        )));
+    }
+    Ok(())
+}
 
-            println!(
-                "   [{}] {}: {} -> {}",
-                status, result.repo_name, result.source_trunk, result.target_trunk
+async fn handle_artifacts_command(imi: &Imi, cmd: ArtifactCommands, json_mode: bool) -> Result<()> {
+    match cmd {
+        ArtifactCommands::List {
+            name: Some(name),
+            repo,
+        } => {
+            let (worktree, artifacts) =
+                ops::worktree_artifacts(imi, &name, repo.as_deref()).await?;
+            if json_mode {
+                JsonResponse::success(serde_json::json!({
+                    "worktree_name": worktree.name,
+                    "artifacts": artifacts,
+                }))
+                .print();
+            } else if artifacts.is_empty() {
+                say!(
+                    "{}",
+                    tr!(
+                        "{} No artifacts on {}",
+                        "ℹ️".bright_blue(),
+                        worktree.name.bright_cyan()
+                    )
+                );
+            } else {
+                say!(
+                    "{}",
+                    tr!(
+                        "{} Runs in {}",
+                        "📦".bright_cyan(),
+                        worktree.name.bright_cyan()
+                    )
+                );
+                worktree::print_artifacts(&artifacts);
+            }
+        }
+        ArtifactCommands::List { name: None, repo } => {
+            let latest = ops::latest_artifacts(imi, repo.as_deref()).await?;
+            if json_mode {
+                JsonResponse::success(serde_json::json!({ "artifacts": latest })).print();
+            } else if latest.is_empty() {
+                say!("{}", tr!("{} No artifacts recorded", "ℹ️".bright_blue()));
+            } else {
+                say!("{}", tr!("{} Last run per worktree", "📦".bright_cyan()));
+                for entry in latest {
+                    println!(
+                        "\n{} {}",
+                        entry.worktree_name.bright_cyan(),
+                        entry.branch_name.bright_black()
+                    );
+                    worktree::print_artifacts(std::slice::from_ref(&entry.artifact));
+                }
+            }
+        }
+        ArtifactCommands::Show { name, id, repo } => {
+            let output = ops::artifact_output(imi, &name, repo.as_deref(), id.as_deref()).await?;
+            if json_mode {
+                JsonResponse::success(serde_json::to_value(&output)?).print();
+                return Ok(());
+            }
+            say!(
+                "{}",
+                tr!(
+                    "{} {} in {}",
+                    "📦".bright_cyan(),
+                    output.artifact.command.bright_white(),
+                    output.worktree_name.bright_cyan()
+                )
             );
-            println!("      {}", result.message);
-            for warning in &result.warnings {
-                println!("      warning: {}", warning);
+            worktree::print_artifacts(std::slice::from_ref(&output.artifact));
+            if let Some(sha) = &output.artifact.commit_sha {
+                println!("   {} {}", tr!("at").bright_black(), sha.bright_black());
+            }
+            say!();
+            print!("{}", output.stdout);
+            if !output.stderr.is_empty() {
+                eprint!("{}", output.stderr);
             }
         }
     }
-
-    if summary.failed > 0 {
-        return Err(anyhow::anyhow!(
-            "Office migration finished with {} failed repository migrations",
-            summary.failed
-        ));
-    }
-
     Ok(())
 }
 
-fn parse_metadata_value(raw: &str) -> serde_json::Value {
-    serde_json::from_str(raw).unwrap_or_else(|_| serde_json::Value::String(raw.to_string()))
-}
-
 async fn handle_metadata_command(
     manager: &WorktreeManager,
     command: MetadataCommands,
@@ -954,7 +4241,7 @@ async fn handle_metadata_command(
             {
                 Some(wt) => wt,
                 None => {
-                    let error_msg = format!("Worktree '{}' not found", worktree);
+                    let error_msg = tr!("Worktree '{}' not found", worktree);
                     if json_mode {
                         JsonResponse::error(error_msg.clone()).print();
                     } else {
@@ -985,11 +4272,14 @@ async fn handle_metadata_command(
                 }))
                 .print();
             } else {
-                println!(
-                    "{} Metadata updated for '{}': {}",
-                    "✅".bright_green(),
-                    worktree_entry.name.bright_cyan(),
-                    key.bright_yellow()
+                say!(
+                    "{}",
+                    tr!(
+                        "{} Metadata updated for '{}': {}",
+                        "✅".bright_green(),
+                        worktree_entry.name.bright_cyan(),
+                        key.bright_yellow()
+                    )
                 );
                 println!("   {}", serde_json::to_string_pretty(&stored_value)?);
             }
@@ -1005,7 +4295,7 @@ async fn handle_metadata_command(
             {
                 Some(wt) => wt,
                 None => {
-                    let error_msg = format!("Worktree '{}' not found", worktree);
+                    let error_msg = tr!("Worktree '{}' not found", worktree);
                     if json_mode {
                         JsonResponse::error(error_msg.clone()).print();
                     } else {
@@ -1029,10 +4319,13 @@ async fn handle_metadata_command(
                 }))
                 .print();
             } else {
-                println!(
-                    "{} Metadata for '{}'",
-                    "📌".bright_cyan(),
-                    worktree_entry.name.bright_cyan()
+                say!(
+                    "{}",
+                    tr!(
+                        "{} Metadata for '{}'",
+                        "📌".bright_cyan(),
+                        worktree_entry.name.bright_cyan()
+                    )
                 );
                 println!("{}", serde_json::to_string_pretty(&metadata)?);
             }
@@ -1047,25 +4340,362 @@ async fn handle_prune_command(
     repo: Option<&str>,
     dry_run: bool,
     force: bool,
+    include_risky: bool,
     json_mode: bool,
 ) -> Result<()> {
     if !json_mode {
-        println!(
-            "{} Cleaning up stale worktree references",
-            "🧹".bright_cyan()
+        say!(
+            "{}",
+            tr!(
+                "{} Cleaning up stale worktree references",
+                "🧹".bright_cyan()
+            )
+        );
+    }
+
+    manager
+        .prune_stale_worktrees(repo, dry_run, force, include_risky)
+        .await?;
+
+    if json_mode {
+        JsonResponse::success(serde_json::json!({
+            "message": "Cleanup complete",
+            "dry_run": dry_run
+        }))
+        .print();
+    } else {
+        say!("{}", tr!("{} Cleanup complete", "✅".bright_green()));
+    }
+    Ok(())
+}
+
+async fn handle_graph_command(
+    manager: &WorktreeManager,
+    repo: Option<&str>,
+    dot: bool,
+    json_mode: bool,
+) -> Result<()> {
+    let graph = manager.branch_graph(repo).await?;
+
+    if json_mode {
+        JsonResponse::success(serde_json::to_value(&graph)?).print();
+    } else if dot {
+        print!("{}", graph.to_dot());
+    } else {
+        say!(
+            "{}",
+            tr!(
+                "{} Branch graph for {}\n",
+                "🌳".bright_green(),
+                graph.repo_name.bright_cyan()
+            )
+        );
+        print!("{}", graph.to_ascii());
+    }
+
+    Ok(())
+}
+
+async fn handle_open_command(
+    manager: &WorktreeManager,
+    name: Option<&str>,
+    repo: Option<&str>,
+    json_mode: bool,
+) -> Result<()> {
+    let workspace = manager.open_in_editor(name, repo).await?;
+
+    if json_mode {
+        JsonResponse::success(serde_json::json!({
+            "workspace": workspace.display().to_string(),
+            "editor": manager.config.editor.command(),
+        }))
+        .print();
+    } else {
+        say!(
+            "{}",
+            tr!(
+                "{} Opened {} in {}",
+                "🖥️".bright_cyan(),
+                workspace.display(),
+                manager.config.editor.command()
+            )
+        );
+    }
+
+    Ok(())
+}
+
+/// Close every worktree matching the bulk selectors, keeping their branches
+async fn handle_bulk_close_command(
+    imi: &Imi,
+    options: BulkCloseOptions,
+    yes: bool,
+    json_mode: bool,
+) -> Result<()> {
+    let targets = ops::select_close_targets(imi, &options).await?;
+
+    if targets.is_empty() {
+        if json_mode {
+            JsonResponse::success(serde_json::json!({
+                "message": "No worktrees matched",
+                "results": [],
+            }))
+            .print();
+        } else {
+            say!("{}", tr!("{} No worktrees matched", "ℹ️".bright_blue()));
+        }
+        return Ok(());
+    }
+
+    let closable = targets.iter().filter(|t| t.skip_reason.is_none()).count();
+
+    if !json_mode {
+        say!(
+            "{}",
+            tr!(
+                "{} Worktrees to close (branches are kept):\n",
+                "🚫".bright_yellow()
+            )
+        );
+        for target in &targets {
+            let note = match &target.skip_reason {
+                Some(reason) => format!("skip: {}", reason).bright_yellow(),
+                None => target.branch_name.bright_black(),
+            };
+            println!(
+                "   {:<20} {:<36} {}",
+                target.repo_name.bright_cyan(),
+                target.worktree_name.bright_green(),
+                note
+            );
+        }
+        say!();
+    }
+
+    if closable > 0 && !yes && !json_mode {
+        let confirmed = dialoguer::Confirm::new()
+            .with_prompt(tr!("Close {} worktree(s)?", closable))
+            .default(false)
+            .interact()?;
+        if !confirmed {
+            say!("{}", tr!("{} Close cancelled", "ℹ️".bright_blue()));
+            return Ok(());
+        }
+    }
+
+    let results = ops::close_worktrees(imi, targets).await;
+    let count = |status| results.iter().filter(|r| r.status == status).count();
+    let (closed, skipped, failed) = (
+        count(CloseStatus::Closed),
+        count(CloseStatus::Skipped),
+        count(CloseStatus::Failed),
+    );
+
+    if json_mode {
+        JsonResponse::success(serde_json::json!({
+            "closed": closed,
+            "skipped": skipped,
+            "failed": failed,
+            "results": results,
+        }))
+        .print();
+    } else {
+        for result in &results {
+            let (icon, label) = match result.status {
+                CloseStatus::Closed => ("✅", "closed".bright_green()),
+                CloseStatus::Skipped => ("⏭️", "skipped".bright_yellow()),
+                CloseStatus::Failed => ("❌", "failed".bright_red()),
+            };
+            println!(
+                "   {} {:<20} {:<36} {} {}",
+                icon,
+                result.target.repo_name,
+                result.target.worktree_name,
+                label,
+                result.message.as_deref().unwrap_or("")
+            );
+        }
+        say!(
+            "{}",
+            tr!(
+                "\n{} {} closed, {} skipped, {} failed",
+                "📊".bright_cyan(),
+                closed,
+                skipped,
+                failed
+            )
+        );
+    }
+
+    // The JSON report already carries the failures
+    if failed > 0 && !json_mode {
+        return Err(anyhow::anyhow!(tr!(
+            "{} worktree(s) failed to close",
+            failed
+        )));
+    }
+
+    Ok(())
+}
+
+/// Refresh PR links, then close every worktree whose PR was merged
+async fn handle_clean_command(
+    imi: &Imi,
+    repo: Option<String>,
+    yes: bool,
+    include_risky: bool,
+    json_mode: bool,
+) -> Result<()> {
+    let repo = match repo {
+        Some(repo) => Some(imi.manager().resolve_repo_name(Some(&repo)).await?),
+        None => None,
+    };
+
+    if !json_mode {
+        say!(
+            "{}",
+            tr!(
+                "{} Checking GitHub for merged pull requests...",
+                "🔍".bright_cyan()
+            )
         );
     }
+    imi.manager().refresh_pull_requests(repo.as_deref()).await?;
+
+    let options = BulkCloseOptions {
+        repo,
+        pr_merged: true,
+        include_risky,
+        ..Default::default()
+    };
+    handle_bulk_close_command(imi, options, yes, json_mode).await
+}
+
+async fn handle_trash_command(
+    manager: &WorktreeManager,
+    cmd: TrashCommands,
+    json_mode: bool,
+) -> Result<()> {
+    match cmd {
+        TrashCommands::List { repo } => {
+            let entries = manager.trash_entries(repo.as_deref()).await?;
+            if json_mode {
+                JsonResponse::success(serde_json::json!({
+                    "retention_days": manager.config.trash.retention_days,
+                    "entries": entries,
+                }))
+                .print();
+                return Ok(());
+            }
+
+            if entries.is_empty() {
+                say!("{}", tr!("{} The trash is empty", "ℹ️".bright_blue()));
+                return Ok(());
+            }
+            say!(
+                "{}",
+                tr!(
+                    "{} Trashed worktrees (kept {} days)\n",
+                    "🗑️".bright_yellow(),
+                    manager.config.trash.retention_days
+                )
+            );
+            for entry in &entries {
+                println!(
+                    "   {} {} {}",
+                    entry.id.bright_white(),
+                    entry.repo_name.bright_cyan(),
+                    entry.branch_name.as_deref().unwrap_or("-").bright_black()
+                );
+                println!(
+                    "{}",
+                    tr!(
+                        "      {} from {}, {} days ago",
+                        "↳".bright_black(),
+                        entry.original_path.display(),
+                        entry.age_days()
+                    )
+                );
+            }
+        }
+        TrashCommands::Restore { name, repo } => {
+            let entry = manager.restore_from_trash(&name, repo.as_deref()).await?;
+            if json_mode {
+                JsonResponse::success(serde_json::json!({
+                    "message": "Worktree restored",
+                    "worktree_name": entry.worktree_name,
+                    "worktree_path": entry.original_path.display().to_string(),
+                    "branch_name": entry.branch_name,
+                }))
+                .print();
+            } else {
+                say!(
+                    "{}",
+                    tr!(
+                        "{} Restored {} at {}",
+                        "♻️".bright_green(),
+                        entry.worktree_name.bright_white(),
+                        entry.original_path.display()
+                    )
+                );
+                print_path_result("cd".to_string(), &entry.original_path);
+            }
+        }
+        TrashCommands::Empty { repo, expired, yes } => {
+            if !yes && !json_mode {
+                let prompt = if expired {
+                    "Permanently delete trashed worktrees past the retention period?"
+                } else {
+                    "Permanently delete every trashed worktree?"
+                };
+                let confirmed = dialoguer::Confirm::new()
+                    .with_prompt(prompt)
+                    .default(false)
+                    .interact()?;
+                if !confirmed {
+                    say!("{}", tr!("{} Empty cancelled", "ℹ️".bright_blue()));
+                    return Ok(());
+                }
+            }
+
+            let purged = manager.empty_trash(repo.as_deref(), expired).await?;
+            if json_mode {
+                JsonResponse::success(serde_json::json!({
+                    "purged": purged.len(),
+                    "entries": purged,
+                }))
+                .print();
+            } else {
+                say!(
+                    "{}",
+                    tr!(
+                        "{} Deleted {} trashed worktree(s)",
+                        "✅".bright_green(),
+                        purged.len()
+                    )
+                );
+            }
+        }
+    }
+    Ok(())
+}
 
-    manager.prune_stale_worktrees(repo, dry_run, force).await?;
+async fn handle_size_budget_command(
+    manager: &WorktreeManager,
+    repo: Option<&str>,
+    refresh: bool,
+    json_mode: bool,
+) -> Result<()> {
+    let report = manager.size_budget_report(repo, refresh).await?;
 
     if json_mode {
         JsonResponse::success(serde_json::json!({
-            "message": "Cleanup complete",
-            "dry_run": dry_run
+            "over_budget": report.over_budget(),
+            "report": report,
         }))
         .print();
     } else {
-        println!("{} Cleanup complete", "✅".bright_green());
+        manager.print_size_budget(&report);
     }
     Ok(())
 }
@@ -1077,14 +4707,28 @@ async fn handle_close_command(
     json_mode: bool,
 ) -> Result<()> {
     if !json_mode {
-        println!(
-            "{} Closing worktree: {}",
-            "🚫".bright_yellow(),
-            name.bright_yellow()
+        say!(
+            "{}",
+            tr!(
+                "{} Closing worktree: {}",
+                "🚫".bright_yellow(),
+                name.bright_yellow()
+            )
         );
     }
 
-    manager.close_worktree(name, repo).await?;
+    let mut name = name.to_string();
+    if let Err(e) = manager.close_worktree(&name, repo).await {
+        let Some(chosen) = pick_ambiguous_worktree(&e, json_mode)? else {
+            return Err(e);
+        };
+        name = chosen;
+        manager
+            .clone()
+            .exact_names(true)
+            .close_worktree(&name, repo)
+            .await?;
+    }
 
     if json_mode {
         match manager.get_trunk_worktree(repo).await {
@@ -1106,26 +4750,38 @@ async fn handle_close_command(
             }
         }
     } else {
-        println!("{} Worktree closed successfully", "✅".bright_green());
+        say!(
+            "{}",
+            tr!("{} Worktree closed successfully", "✅".bright_green())
+        );
 
         match manager.get_trunk_worktree(repo).await {
             Ok(trunk_path) => {
-                println!(
-                    "\n{} To navigate to trunk, run:\n   {}",
-                    "💡".bright_yellow(),
-                    format!("cd {}", trunk_path.display()).bright_cyan()
+                say!(
+                    "{}",
+                    tr!(
+                        "\n{} To navigate to trunk, run:\n   {}",
+                        "💡".bright_yellow(),
+                        format!("cd {}", trunk_path.display()).bright_cyan()
+                    )
                 );
             }
             Err(err) => {
-                println!(
-                    "{} Unable to locate trunk worktree: {}",
-                    "⚠️".bright_yellow(),
-                    err
+                eprintln!(
+                    "{}",
+                    tr!(
+                        "{} Unable to locate trunk worktree: {}",
+                        "⚠️".bright_yellow(),
+                        err
+                    )
                 );
-                println!(
-                    "{} If needed you can recreate it with: {}",
-                    "💡".bright_yellow(),
-                    "imi trunk".bright_cyan()
+                say!(
+                    "{}",
+                    tr!(
+                        "{} If needed you can recreate it with: {}",
+                        "💡".bright_yellow(),
+                        "imi trunk".bright_cyan()
+                    )
                 );
             }
         }
@@ -1140,12 +4796,17 @@ async fn handle_go_command(
     repo: Option<&str>,
     worktrees_only: bool,
     include_inactive: bool,
+    shell: bool,
     json_mode: bool,
 ) -> Result<()> {
     // Perform fuzzy search and get best match or show interactive picker
-    let target_path = manager
-        .fuzzy_navigate(query, repo, worktrees_only, include_inactive)
+    let target = manager
+        .fuzzy_select(query, repo, worktrees_only, include_inactive)
         .await?;
+    if shell {
+        return spawn_worktree_shell(&target);
+    }
+    let target_path = target.path();
 
     if json_mode {
         JsonResponse::success(serde_json::json!({
@@ -1161,10 +4822,561 @@ async fn handle_go_command(
     Ok(())
 }
 
+/// Run $SHELL in the target until it exits, for shells without the `cd`
+/// wrapper integration
+fn spawn_worktree_shell(target: &SearchTarget) -> Result<()> {
+    spawn_shell_in(
+        &target.path(),
+        &target.name(),
+        target.repo_name(),
+        target.worktree_type().unwrap_or("trunk"),
+    )
+}
+
+/// Run $SHELL in `path` with IMI_WORKTREE, IMI_REPO and IMI_TYPE set
+fn spawn_shell_in(path: &Path, name: &str, repo_name: &str, worktree_type: &str) -> Result<()> {
+    if !path.is_dir() {
+        return Err(anyhow::anyhow!(tr!("{} no longer exists", path.display())));
+    }
+    if let Ok(current) = std::env::var("IMI_WORKTREE") {
+        eprintln!(
+            "{}",
+            tr!(
+                "{} Already in an iMi shell for {}; exit it to avoid stacking shells",
+                "⚠️".bright_yellow(),
+                current
+            )
+        );
+    }
+
+    let shell = std::env::var_os("SHELL")
+        .filter(|shell| !shell.is_empty())
+        .unwrap_or_else(|| "/bin/sh".into());
+    eprintln!(
+        "{}",
+        tr!(
+            "{} Entering {} ({}); exit the shell to come back",
+            "🐚".bright_cyan(),
+            name.bright_green(),
+            path.display()
+        )
+    );
+
+    let status = std::process::Command::new(&shell)
+        .current_dir(path)
+        .env("IMI_WORKTREE", name)
+        .env("IMI_REPO", repo_name)
+        .env("IMI_TYPE", worktree_type)
+        .env("IMI_WORKTREE_PATH", path)
+        .status()
+        .context(tr!("Failed to start {}", shell.to_string_lossy()))?;
+
+    eprintln!("{}", tr!("{} Left {}", "↩️".bright_cyan(), name));
+    if let Some(code) = status.code().filter(|code| *code != 0) {
+        return Err(ImiError::Exit { code }.into());
+    }
+    Ok(())
+}
+
+/// List recently used worktrees, or print the path of one chosen by number
+/// or picked interactively, for the shell wrapper to cd into
+async fn handle_recent_command(
+    imi: &Imi,
+    index: Option<usize>,
+    limit: usize,
+    repo: Option<&str>,
+    pick: bool,
+    json_mode: bool,
+) -> Result<()> {
+    let recent = ops::recent_worktrees(imi, repo, limit.max(index.unwrap_or(0))).await?;
+
+    let chosen = match index {
+        Some(index) => Some(
+            index
+                .checked_sub(1)
+                .and_then(|i| recent.get(i))
+                .ok_or_else(|| {
+                    anyhow::anyhow!(tr!(
+                        "No recent worktree #{} ({} recently used)",
+                        index,
+                        recent.len()
+                    ))
+                })?,
+        ),
+        None if pick => {
+            if recent.is_empty() {
+                return Err(anyhow::anyhow!(tr!("No recently used worktrees")));
+            }
+            let items: Vec<String> = recent
+                .iter()
+                .map(|r| {
+                    format!(
+                        "{}/{} [{}] {}",
+                        r.repo_name,
+                        r.worktree_name,
+                        r.branch_name,
+                        time_ago(r.last_visited_at)
+                    )
+                })
+                .collect();
+            let Some(selection) =
+                dialoguer::Select::with_theme(&dialoguer::theme::ColorfulTheme::default())
+                    .with_prompt(tr!("Jump to"))
+                    .items(&items)
+                    .default(0)
+                    .interact_opt()?
+            else {
+                return Ok(());
+            };
+            Some(&recent[selection])
+        }
+        None => None,
+    };
+
+    if let Some(chosen) = chosen {
+        // Keeps the jump list ordered by use; never fail the jump over it
+        let _ = ops::record_visit(imi, chosen).await;
+        if json_mode {
+            JsonResponse::success(serde_json::to_value(chosen)?).print();
+        } else {
+            // Path only on stdout, like `imi go`, for the shell wrapper
+            print!("{}", chosen.path.display());
+        }
+        return Ok(());
+    }
+
+    if json_mode {
+        JsonResponse::success(serde_json::json!({ "worktrees": recent })).print();
+        return Ok(());
+    }
+    if recent.is_empty() {
+        say!(
+            "{}",
+            tr!(
+                "{} No recently used worktrees yet; visit some with {} or {}",
+                "ℹ️".bright_blue(),
+                "imi go".bright_cyan(),
+                "imi open".bright_cyan()
+            )
+        );
+        return Ok(());
+    }
+
+    say!("{}", tr!("{} Recently used worktrees", "🕒".bright_cyan()));
+    for (i, r) in recent.iter().enumerate() {
+        println!(
+            "  {:>2}. {}/{} {} {}",
+            i + 1,
+            r.repo_name.bright_blue(),
+            r.worktree_name.bright_white(),
+            format!("[{}]", r.branch_name).bright_black(),
+            time_ago(r.last_visited_at).bright_black()
+        );
+    }
+    say!(
+        "{}",
+        tr!(
+            "{} Jump with {}",
+            "💡".bright_yellow(),
+            "cd \"$(imi recent <n>)\"".bright_cyan()
+        )
+    );
+
+    Ok(())
+}
+
+fn time_ago(at: chrono::DateTime<chrono::Utc>) -> String {
+    let elapsed = chrono::Utc::now().signed_duration_since(at);
+    if elapsed.num_days() > 0 {
+        format!("{}d ago", elapsed.num_days())
+    } else if elapsed.num_hours() > 0 {
+        format!("{}h ago", elapsed.num_hours())
+    } else {
+        format!("{}m ago", elapsed.num_minutes().max(0))
+    }
+}
+
+fn time_until(at: chrono::DateTime<chrono::Utc>) -> String {
+    let remaining = at.signed_duration_since(chrono::Utc::now());
+    if remaining.num_days() > 0 {
+        format!("in {}d", remaining.num_days())
+    } else if remaining.num_hours() > 0 {
+        format!("in {}h", remaining.num_hours())
+    } else {
+        format!("in {}m", remaining.num_minutes().max(1))
+    }
+}
+
+async fn handle_which_command(imi: &Imi, options: ResolveOptions, json_mode: bool) -> Result<()> {
+    let query = options.query.clone();
+    let matches = ops::resolve(imi, options).await?;
+
+    match matches.as_slice() {
+        [] => {
+            let error_msg = tr!("No worktree or repository found matching '{}'", query);
+            if json_mode {
+                JsonResponse::error(error_msg).print();
+            } else {
+                eprintln!("{}", error_msg.red());
+            }
+            return Err(ImiError::Exit { code: 1 }.into());
+        }
+        [target] => {
+            if json_mode {
+                JsonResponse::success(serde_json::json!({
+                    "name": target.name,
+                    "repo_name": target.repo_name,
+                    "worktree_type": target.worktree_type,
+                    "path": target.path.display().to_string(),
+                }))
+                .print();
+            } else {
+                // Only the path goes to stdout so scripts can capture it directly
+                println!("{}", target.path.display());
+            }
+            Ok(())
+        }
+        candidates => {
+            let error_msg = tr!(
+                "'{}' is ambiguous ({} equally good matches)",
+                query,
+                candidates.len()
+            );
+            if json_mode {
+                JsonResponse::error(error_msg).print();
+            } else {
+                eprintln!("{}", error_msg.red());
+                for target in candidates {
+                    eprintln!("   {} {}", target.name, target.path.display());
+                }
+            }
+            Err(ImiError::Exit { code: 2 }.into())
+        }
+    }
+}
+
+/// Clean up after crashed iMi processes where nothing needs recovering, and
+/// point at `imi resume`/`imi rollback` for adds and merges that do
+fn warn_about_interrupted_operations() {
+    if let Some(imi_root) = std::env::current_dir().ok().and_then(|cwd| {
+        cwd.ancestors()
+            .find(|dir| dir.join(".iMi").is_dir())
+            .map(PathBuf::from)
+    }) {
+        if LocalContext::new(&imi_root).clear_stale_registry_lock() {
+            eprintln!(
+                "{}",
+                tr!(
+                    "{} Removed a registry lock left in {} by a crashed iMi process",
+                    "🧹".bright_black(),
+                    imi_root.join(".iMi").display()
+                )
+            );
+        }
+    }
+
+    let Ok(journal) = Journal::open_default() else {
+        return;
+    };
+    for entry in journal.clean_up_abandoned().unwrap_or_default() {
+        eprintln!(
+            "{}",
+            tr!(
+                "{} Cleared interrupted {} of {}/{}; it stopped before changing anything",
+                "🧹".bright_black(),
+                entry.kind,
+                entry.repo_name,
+                entry.worktree_name
+            )
+        );
+    }
+
+    let incomplete = journal.incomplete().unwrap_or_default();
+    for entry in &incomplete {
+        eprintln!(
+            "{}",
+            tr!(
+                "{} Previous {} on worktree {}/{} appears interrupted ({}) — run 'imi resume'",
+                "⚠️".bright_yellow(),
+                entry.kind,
+                entry.repo_name,
+                entry.worktree_name,
+                entry.progress()
+            )
+        );
+    }
+    if !incomplete.is_empty() {
+        eprintln!(
+            "{}",
+            tr!(
+                "   {} 'imi resume' finishes it; 'imi rollback' undoes it instead",
+                "💡".bright_yellow()
+            )
+        );
+    }
+}
+
+/// `imi verify`: probe, save the report for other commands, and fail when
+/// iMi can't run here (or anything is missing with `--strict`)
+fn handle_verify_command(
+    config: &Config,
+    dir: Option<PathBuf>,
+    strict: bool,
+    json_mode: bool,
+) -> Result<()> {
+    let probe_dir = dir
+        .or_else(|| Some(config.get_primary_root()).filter(|root| root.is_dir()))
+        .unwrap_or_else(std::env::temp_dir);
+    let report = CapabilityReport::collect(&probe_dir)?;
+    if let Err(e) = report.save() {
+        eprintln!(
+            "{} Failed to save the capability report: {:#}",
+            "⚠️".bright_yellow(),
+            e
+        );
+    }
+
+    let missing = report.missing();
+    if json_mode {
+        JsonResponse::success(serde_json::to_value(&report)?).print();
+    } else {
+        report.print();
+        if !missing.is_empty() {
+            eprintln!(
+                "{}",
+                tr!(
+                    "\n{} Missing: {}; iMi falls back where it can",
+                    "⚠️".bright_yellow(),
+                    missing.join(", ")
+                )
+            );
+        }
+    }
+
+    if !report.usable() || (strict && !missing.is_empty()) {
+        return Err(ImiError::Exit { code: 1 }.into());
+    }
+    Ok(())
+}
+
+/// `imi config validate`: check the global and project config files (or
+/// just `file`) and list what's wrong with each
+async fn handle_config_validate_command(
+    file: Option<PathBuf>,
+    strict: bool,
+    json_mode: bool,
+) -> Result<()> {
+    let paths = match file {
+        Some(file) => vec![file],
+        None => {
+            let mut paths = vec![Config::get_global_config_path()?];
+            if let Some(root) = Config::find_project_root()? {
+                paths.push(root.join(".iMi").join("config.toml"));
+            }
+            paths.retain(|path| path.exists());
+            paths
+        }
+    };
+
+    let mut reports = Vec::new();
+    let (mut errors, mut warnings) = (0, 0);
+    for path in &paths {
+        let contents = tokio::fs::read_to_string(path)
+            .await
+            .with_context(|| tr!("Failed to read config file at {}", path.display()))?;
+        let (_, diagnostics) = config_check::check(&contents, strict);
+        // Shown here; a later Config::load in this process needn't repeat them
+        config_check::first_report(path);
+
+        let file_errors = diagnostics
+            .iter()
+            .filter(|d| d.severity == config_check::Severity::Error)
+            .count();
+        errors += file_errors;
+        warnings += diagnostics.len() - file_errors;
+        reports.push((path, diagnostics));
+    }
+
+    if json_mode {
+        let files: Vec<_> = reports
+            .iter()
+            .map(|(path, diagnostics)| {
+                serde_json::json!({
+                    "path": path,
+                    "valid": diagnostics
+                        .iter()
+                        .all(|d| d.severity != config_check::Severity::Error),
+                    "diagnostics": diagnostics,
+                })
+            })
+            .collect();
+        JsonResponse::success(serde_json::json!({ "files": files })).print();
+    } else if reports.is_empty() {
+        println!(
+            "{}",
+            tr!(
+                "{} No config file yet; iMi uses the defaults",
+                "ℹ️".bright_blue()
+            )
+        );
+    } else {
+        for (path, diagnostics) in &reports {
+            if diagnostics.is_empty() {
+                println!("{} {}", "✅".bright_green(), path.display());
+            } else {
+                println!("{} {}", "📄".bright_cyan(), path.display());
+                for diagnostic in diagnostics {
+                    diagnostic.print();
+                }
+            }
+        }
+        if errors + warnings > 0 {
+            println!("{}", tr!("\n{} error(s), {} warning(s)", errors, warnings));
+        }
+    }
+
+    if errors > 0 || (strict && warnings > 0) {
+        return Err(ImiError::Exit { code: 1 }.into());
+    }
+    Ok(())
+}
+
+/// Commands run from a vendored checkout act on it, not the repository
+/// around it
+fn warn_if_in_nested_repository() {
+    let Some(nested) = std::env::current_dir()
+        .ok()
+        .and_then(|cwd| context::nested_repository(&cwd))
+    else {
+        return;
+    };
+
+    eprintln!(
+        "{}",
+        tr!(
+            "{} {} is a repository nested inside {}",
+            "⚠️".bright_yellow(),
+            nested.path.display(),
+            nested.outer.display()
+        )
+    );
+    eprintln!(
+        "{}",
+        tr!(
+            "   {} iMi commands here act on the nested repository; cd to {} for the outer one",
+            "💡".bright_yellow(),
+            nested.outer.display()
+        )
+    );
+}
+
+/// Pick the interrupted operation a resume/rollback applies to
+fn select_interrupted_operation(selector: Option<&str>) -> Result<Option<JournalEntry>> {
+    let journal = Journal::open_default()?;
+    let incomplete = journal.incomplete()?;
+
+    if selector.is_none() && incomplete.len() > 1 {
+        let listing: Vec<String> = incomplete
+            .iter()
+            .map(|e| format!("  {} {}", &e.id.to_string()[..8], e.summary()))
+            .collect();
+        return Err(anyhow::anyhow!(tr!(
+            "Several interrupted operations found; specify one by worktree name or id:\n{}",
+            listing.join("\n")
+        )));
+    }
+
+    journal.find_incomplete(selector)
+}
+
+async fn handle_resume_command(
+    manager: &WorktreeManager,
+    operation: Option<&str>,
+    json_mode: bool,
+) -> Result<()> {
+    let Some(entry) = select_interrupted_operation(operation)? else {
+        if json_mode {
+            JsonResponse::success(serde_json::json!({ "resumed": null })).print();
+        } else {
+            say!(
+                "{}",
+                tr!(
+                    "{} No interrupted operations to resume",
+                    "✅".bright_green()
+                )
+            );
+        }
+        return Ok(());
+    };
+
+    if !json_mode {
+        say!(
+            "{}",
+            tr!("{} Resuming {}", "🔁".bright_cyan(), entry.summary())
+        );
+    }
+
+    let summary = entry.summary();
+    manager.resume_operation(entry).await?;
+
+    if json_mode {
+        JsonResponse::success(serde_json::json!({ "resumed": summary })).print();
+    } else {
+        say!("{}", tr!("{} Operation completed", "✅".bright_green()));
+    }
+
+    Ok(())
+}
+
+async fn handle_rollback_command(
+    manager: &WorktreeManager,
+    operation: Option<&str>,
+    yes: bool,
+    json_mode: bool,
+) -> Result<()> {
+    let Some(entry) = select_interrupted_operation(operation)? else {
+        if json_mode {
+            JsonResponse::success(serde_json::json!({ "rolled_back": null })).print();
+        } else {
+            say!(
+                "{}",
+                tr!(
+                    "{} No interrupted operations to roll back",
+                    "✅".bright_green()
+                )
+            );
+        }
+        return Ok(());
+    };
+
+    if !yes && !json_mode {
+        let confirmed = dialoguer::Confirm::new()
+            .with_prompt(tr!("Roll back {}?", entry.summary()))
+            .default(false)
+            .interact()?;
+        if !confirmed {
+            say!("{}", tr!("{} Rollback cancelled", "ℹ️".bright_blue()));
+            return Ok(());
+        }
+    }
+
+    let summary = entry.summary();
+    manager.rollback_operation(entry).await?;
+
+    if json_mode {
+        JsonResponse::success(serde_json::json!({ "rolled_back": summary })).print();
+    } else {
+        say!("{}", tr!("{} Rolled back {}", "✅".bright_green(), summary));
+    }
+
+    Ok(())
+}
+
 async fn handle_merge_command(
     manager: &WorktreeManager,
     name: Option<&str>,
     repo: Option<&str>,
+    strategy: Option<MergeStrategy>,
     json_mode: bool,
 ) -> Result<()> {
     let worktree_name = match name {
@@ -1174,36 +5386,92 @@ async fn handle_merge_command(
             let current_dir = std::env::current_dir()?;
             let dir_name = current_dir
                 .file_name()
-                .ok_or_else(|| anyhow::anyhow!("Could not determine directory name"))?
+                .ok_or_else(|| anyhow::anyhow!(tr!("Could not determine directory name")))?
                 .to_str()
-                .ok_or_else(|| anyhow::anyhow!("Invalid directory name"))?;
+                .ok_or_else(|| anyhow::anyhow!(tr!("Invalid directory name")))?;
             dir_name.to_string()
         }
     };
 
     if !json_mode {
-        println!(
-            "{} Merging worktree: {}",
-            "🔀".bright_cyan(),
-            worktree_name.bright_yellow()
+        say!(
+            "{}",
+            tr!(
+                "{} Merging worktree: {}",
+                "🔀".bright_cyan(),
+                worktree_name.bright_yellow()
+            )
         );
     }
 
-    manager.merge_worktree(&worktree_name, repo).await?;
+    let interactive =
+        !json_mode && std::io::stdin().is_terminal() && std::io::stdout().is_terminal();
+    manager
+        .merge_worktree(&worktree_name, repo, strategy, interactive)
+        .await?;
+
+    if json_mode {
+        JsonResponse::success(serde_json::json!({
+            "message": "Worktree merged successfully",
+            "worktree_name": worktree_name
+        }))
+        .print();
+    }
+
+    Ok(())
+}
+
+async fn handle_merge_train_command(
+    manager: &WorktreeManager,
+    names: &[String],
+    repo: Option<&str>,
+    json_mode: bool,
+) -> Result<()> {
+    let interactive =
+        !json_mode && std::io::stdin().is_terminal() && std::io::stdout().is_terminal();
+    let merged = manager.merge_train(names, repo, interactive).await?;
+    if json_mode {
+        JsonResponse::success(serde_json::json!({ "merged": merged })).print();
+    } else {
+        say!(
+            "{}",
+            tr!(
+                "\n{} Merge train complete: {}",
+                "🚂".bright_green(),
+                merged.join(" → ")
+            )
+        );
+    }
+    Ok(())
+}
 
+async fn handle_merge_abort_command(
+    manager: &WorktreeManager,
+    name: Option<&str>,
+    repo: Option<&str>,
+    json_mode: bool,
+) -> Result<()> {
+    let trunk_path = manager.abort_merge(name, repo).await?;
     if json_mode {
         JsonResponse::success(serde_json::json!({
-            "message": "Worktree merged successfully",
-            "worktree_name": worktree_name
+            "aborted": true,
+            "trunk_path": trunk_path,
         }))
         .print();
+    } else {
+        say!(
+            "{}",
+            tr!(
+                "{} Merge aborted; {} is back where it started",
+                "↩️".bright_yellow(),
+                trunk_path.display()
+            )
+        );
     }
-
     Ok(())
 }
 
 fn handle_completion_command(shell: &clap_complete::Shell) {
-    use clap::CommandFactory;
     use clap_complete::{generate, Generator};
     use std::io;
 
@@ -1216,94 +5484,233 @@ fn handle_completion_command(shell: &clap_complete::Shell) {
 }
 
 async fn handle_add_command(
-    manager: &WorktreeManager,
-    worktree_type: &str,
-    name: &str,
-    repo: Option<&str>,
-    pr: Option<u32>,
+    imi: &Imi,
+    args: Vec<String>,
+    worktree_type: Option<String>,
+    options: CreateOptions,
+    switch: bool,
     json_mode: bool,
 ) -> Result<()> {
-    // Get the database from manager
-    let db = &manager.db;
-
-    // Validate worktree type exists
-    let wt_type = db.get_worktree_type(worktree_type).await.context(format!(
-        "Unknown worktree type '{}'. Run 'imi types' to see available types.",
-        worktree_type
-    ))?;
+    let mut inferred = None;
+    let (worktree_type, name) = match (&args[..], worktree_type) {
+        ([wt_type, name], None) => (wt_type.clone(), name.clone()),
+        ([wt_type, name], Some(flag)) => {
+            return Err(anyhow::anyhow!(tr!(
+                "Worktree type given twice ('{}' and --type {}). Use: imi add {} {}",
+                wt_type,
+                flag,
+                flag,
+                name
+            )));
+        }
+        ([name], Some(flag)) => (flag, name.clone()),
+        ([name], None) => {
+            let result = ops::infer_worktree_type(imi, name, options.repo.as_deref()).await?;
+            if !json_mode {
+                let reason = match &result.source {
+                    TypeSource::Prefix { prefix } => format!("name starts with {}", prefix),
+                    TypeSource::Context { worktree } => format!("run from {}", worktree),
+                    TypeSource::Default => "configured default".to_string(),
+                };
+                say!(
+                    "{}",
+                    tr!(
+                        "{} Inferred type {} ({}); pass --type to override",
+                        "🧭".bright_cyan(),
+                        result.worktree_type.bright_yellow(),
+                        reason
+                    )
+                );
+            }
+            let worktree_type = result.worktree_type.clone();
+            inferred = Some(result);
+            (worktree_type, name.clone())
+        }
+        _ => return Err(anyhow::anyhow!(tr!("Usage: imi add [TYPE] NAME"))),
+    };
+    let options = CreateOptions {
+        worktree_type,
+        name,
+        ..options
+    };
 
     if !json_mode {
-        println!(
-            "{} Creating {} worktree: {}",
-            "🚀".bright_cyan(),
-            wt_type.name,
-            name.bright_green()
+        say!(
+            "{}",
+            tr!(
+                "{} Creating {} worktree: {}",
+                "🚀".bright_cyan(),
+                options.worktree_type,
+                options.name.bright_green()
+            )
         );
     }
 
-    // Handle review type specially (needs PR number)
-    if worktree_type == "review" {
-        let pr_number = match pr {
-            Some(pr_number) => pr_number,
-            None => name.parse::<u32>().context(
-                "PR number required for review worktree. Use: imi add review <pr-number> or --pr <number>"
-            )?,
-        };
-        return handle_review_command(manager, pr_number, repo, json_mode).await;
-    }
-
-    // Route to appropriate handler based on type
-    match worktree_type {
-        "feat" => handle_feature_command(manager, name, repo, json_mode).await,
-        "fix" => handle_fix_command(manager, name, repo, json_mode).await,
-        "aiops" => handle_aiops_command(manager, name, repo, json_mode).await,
-        "devops" => handle_devops_command(manager, name, repo, json_mode).await,
-        _ => {
-            // Custom worktree type - use generic creation
-            let worktree_path = manager
-                .create_custom_worktree(name, worktree_type, repo)
-                .await?;
-
+    let outcome = match ops::create_worktree(imi, options).await {
+        Ok(outcome) => outcome,
+        Err(e) => {
             if json_mode {
-                JsonResponse::success(serde_json::json!({
-                    "worktree_path": worktree_path.display().to_string(),
-                    "worktree_name": format!("{}-{}", worktree_type, name),
-                    "worktree_type": worktree_type,
-                    "message": format!("{} worktree created successfully", worktree_type)
-                }))
-                .print();
-            } else {
-                println!(
-                    "{} {} worktree created at: {}",
-                    "✅".bright_green(),
-                    worktree_type,
-                    worktree_path.display()
-                );
+                JsonResponse::error(e.to_string()).print();
+            } else if e.chain().any(|cause| {
+                matches!(
+                    cause.downcast_ref(),
+                    Some(ImiError::RemoteAuthFailed { .. })
+                )
+            }) {
+                eprintln!("{}", tr!("{} Authentication failed", "❌".bright_red()));
+                eprintln!();
+                imi.manager().git.show_auth_help();
+                eprintln!();
+            }
+            return Err(e);
+        }
+    };
 
-                println!(
-                    "\n{} To navigate to the worktree, run:\n   {}",
-                    "💡".bright_yellow(),
-                    format!("cd {}", worktree_path.display()).bright_cyan()
-                );
+    if json_mode {
+        JsonResponse::success(serde_json::json!({
+            "worktree_path": outcome.path.display().to_string(),
+            "worktree_name": outcome.worktree_name,
+            "worktree_type": outcome.worktree_type,
+            "branch_name": outcome.branch_name,
+            "repo_name": outcome.repo_name,
+            "pr_number": outcome.pr_number,
+            "branch_origin": outcome.branch_origin,
+            "patch": outcome.patch,
+            "inferred_type": inferred,
+            "published": outcome.published,
+            "workspace": outcome.workspace,
+            "claimed_by": outcome.claimed_by,
+            "assigned_by_rule": outcome.assigned_by_rule,
+            "warnings": outcome.warnings,
+            "cd_command": switch.then(|| cd_command(&outcome.path)),
+            "message": format!("{} worktree created successfully", outcome.worktree_type)
+        }))
+        .print();
+    } else {
+        print_path_result(
+            tr!(
+                "{} {} worktree created at:",
+                "✅".bright_green(),
+                outcome.worktree_type
+            ),
+            &outcome.path,
+        );
+
+        if let Some(patch) = &outcome.patch {
+            say!(
+                "{}",
+                tr!(
+                    "{} Applied patch with git {}: {} file(s) changed",
+                    "🩹".bright_cyan(),
+                    patch.method,
+                    patch.files.len()
+                )
+            );
+            for commit in &patch.commits {
+                say!("   {}", commit.bright_black());
+            }
+            if patch.commits.is_empty() {
+                say!("{}", tr!("   Changes are staged; commit them when ready"));
             }
+        }
+        if let Some(agent_id) = &outcome.claimed_by {
+            say!(
+                "{}",
+                tr!(
+                    "{} Claimed for agent {}{}",
+                    "🔑".bright_cyan(),
+                    agent_id.bright_cyan(),
+                    outcome
+                        .assigned_by_rule
+                        .map(|number| format!(" (agent_assignment rule {})", number))
+                        .unwrap_or_default()
+                        .bright_black()
+                )
+            );
+        }
+        if let Some(workspace) = &outcome.workspace {
+            say!(
+                "{}",
+                tr!(
+                    "{} Opened {} in {}",
+                    "🖥️".bright_cyan(),
+                    workspace.display(),
+                    imi.manager().config.editor.command()
+                )
+            );
+        }
+        for warning in &outcome.warnings {
+            eprintln!("{} {}", "⚠️".bright_yellow(), warning);
+        }
 
-            Ok(())
+        if switch && std::io::stdin().is_terminal() && std::io::stdout().is_terminal() {
+            return spawn_shell_in(
+                &outcome.path,
+                &outcome.worktree_name,
+                &outcome.repo_name,
+                &outcome.worktree_type,
+            );
+        } else if switch {
+            // Last line for a wrapper: eval "$(imi add ... --switch | tail -n 1)"
+            println!("{}", cd_command(&outcome.path));
+        } else {
+            // Print command to change directory (processes can't change parent shell's directory)
+            say!(
+                "{}",
+                tr!(
+                    "\n{} To navigate to the worktree, run:\n   {}",
+                    "💡".bright_yellow(),
+                    format!("cd {}", outcome.path.display()).bright_cyan()
+                )
+            );
         }
     }
+
+    Ok(())
+}
+
+/// `cd '<path>'`, quoted for POSIX shells
+fn cd_command(path: &Path) -> String {
+    format!("cd '{}'", path.display().to_string().replace('\'', r"'\''"))
+}
+
+/// Patch contents from a file, or stdin for `-`
+fn read_patch(source: &str) -> Result<Vec<u8>> {
+    if source == "-" {
+        let mut patch = Vec::new();
+        std::io::stdin()
+            .read_to_end(&mut patch)
+            .context(tr!("Failed to read the patch from stdin"))?;
+        return Ok(patch);
+    }
+    std::fs::read(source).context(tr!("Failed to read patch {}", source))
+}
+
+/// Commit message for `--commit` given without one
+fn default_patch_message(source: Option<&str>) -> String {
+    let name = source
+        .filter(|source| *source != "-")
+        .and_then(|path| std::path::Path::new(path).file_name())
+        .map(|name| name.to_string_lossy().to_string());
+    match name {
+        Some(name) => format!("Apply {}", name),
+        None => "Apply patch".to_string(),
+    }
 }
 
 async fn handle_types_command(
-    manager: &WorktreeManager,
+    imi: &Imi,
     type_cmd: TypeCommands,
-    json_mode: bool,
+    format: &OutputFormat,
 ) -> Result<()> {
-    let db = &manager.db;
+    let db = imi.database();
+    let json_mode = *format == OutputFormat::Json;
 
     match type_cmd {
-        TypeCommands::List => {
-            let types = db.list_worktree_types().await?;
+        TypeCommands::List { detailed } => {
+            let types = ops::list_worktree_types(imi).await?;
 
-            if json_mode {
+            if *format != OutputFormat::Table {
                 let types_json: Vec<_> = types
                     .iter()
                     .map(|t| {
@@ -1313,17 +5720,22 @@ async fn handle_types_command(
                             "worktree_prefix": t.worktree_prefix,
                             "description": t.description,
                             "is_builtin": t.is_builtin,
+                            "default_ttl_days": t.default_ttl_days,
+                            "default_base_branch": t.default_base_branch,
                         })
                     })
                     .collect();
 
-                JsonResponse::success(serde_json::json!({
+                let data = serde_json::json!({
                     "types": types_json,
                     "count": types.len()
-                }))
-                .print();
+                });
+                print_formatted(format, data, &types_json)?;
             } else {
-                println!("{} Available Worktree Types:\n", "📋".bright_cyan());
+                say!(
+                    "{}",
+                    tr!("{} Available Worktree Types:\n", "📋".bright_cyan())
+                );
 
                 for wt_type in types {
                     let builtin_badge = if wt_type.is_builtin {
@@ -1339,13 +5751,22 @@ async fn handle_types_command(
                         wt_type.description.as_deref().unwrap_or("No description")
                     );
                     println!(
-                        "      Branch: {}  Worktree: {}",
-                        wt_type.branch_prefix.bright_cyan(),
-                        wt_type.worktree_prefix.bright_cyan()
+                        "{}",
+                        tr!(
+                            "      Branch: {}  Worktree: {}",
+                            wt_type.branch_prefix.bright_cyan(),
+                            wt_type.worktree_prefix.bright_cyan()
+                        )
                     );
+                    if detailed {
+                        print_type_defaults(&wt_type);
+                    }
                 }
 
-                println!("\n{} Usage: imi add <type> <name>", "💡".bright_yellow());
+                say!(
+                    "{}",
+                    tr!("\n{} Usage: imi add <type> <name>", "💡".bright_yellow())
+                );
             }
         }
         TypeCommands::Add {
@@ -1355,10 +5776,13 @@ async fn handle_types_command(
             description,
         } => {
             if !json_mode {
-                println!(
-                    "{} Adding new worktree type: {}",
-                    "➕".bright_cyan(),
-                    name.bright_green()
+                say!(
+                    "{}",
+                    tr!(
+                        "{} Adding new worktree type: {}",
+                        "➕".bright_cyan(),
+                        name.bright_green()
+                    )
                 );
             }
 
@@ -1383,27 +5807,135 @@ async fn handle_types_command(
                 }))
                 .print();
             } else {
+                say!(
+                    "{}",
+                    tr!(
+                        "{} Worktree type '{}' added successfully!",
+                        "✅".bright_green(),
+                        name
+                    )
+                );
                 println!(
-                    "{} Worktree type '{}' added successfully!",
-                    "✅".bright_green(),
-                    name
+                    "{}",
+                    tr!("  Branch prefix: {}", wt_type.branch_prefix.bright_cyan())
                 );
-                println!("  Branch prefix: {}", wt_type.branch_prefix.bright_cyan());
                 println!(
-                    "  Worktree prefix: {}",
-                    wt_type.worktree_prefix.bright_cyan()
+                    "{}",
+                    tr!(
+                        "  Worktree prefix: {}",
+                        wt_type.worktree_prefix.bright_cyan()
+                    )
                 );
                 if let Some(desc) = wt_type.description {
-                    println!("  Description: {}", desc);
+                    println!("{}", tr!("  Description: {}", desc));
+                }
+            }
+        }
+        TypeCommands::Edit {
+            name,
+            branch_prefix,
+            worktree_prefix,
+            description,
+            ttl_days,
+            base_branch,
+            clear,
+        } => {
+            let mut wt_type = db.get_worktree_type(&name).await.context(tr!(
+                "Unknown worktree type '{}'. Run 'imi types' to see available types.",
+                name
+            ))?;
+
+            wt_type.apply_edit(WorktreeTypeEdit {
+                branch_prefix,
+                worktree_prefix,
+                description,
+                default_ttl_days: ttl_days,
+                default_base_branch: base_branch,
+                clear: clear.iter().map(|f| f.column().to_string()).collect(),
+            })?;
+
+            let wt_type = db.update_worktree_type(&wt_type).await?;
+
+            if json_mode {
+                JsonResponse::success(serde_json::json!({
+                    "message": "Worktree type updated successfully",
+                    "type": wt_type,
+                }))
+                .print();
+            } else {
+                say!(
+                    "{}",
+                    tr!(
+                        "{} Worktree type '{}' updated",
+                        "✅".bright_green(),
+                        wt_type.name.bright_green()
+                    )
+                );
+                println!(
+                    "{}",
+                    tr!(
+                        "      Branch: {}  Worktree: {}",
+                        wt_type.branch_prefix.bright_cyan(),
+                        wt_type.worktree_prefix.bright_cyan()
+                    )
+                );
+                print_type_defaults(&wt_type);
+            }
+        }
+        TypeCommands::SetCommitTemplate { name, file, clear } => {
+            let mut wt_type = db.get_worktree_type(&name).await.context(tr!(
+                "Unknown worktree type '{}'. Run 'imi types' to see available types.",
+                name
+            ))?;
+
+            wt_type.commit_template = match (&file, clear) {
+                (_, true) => None,
+                (Some(path), false) => Some(
+                    std::fs::read_to_string(path)
+                        .context(tr!("Failed to read {}", path.display()))?,
+                ),
+                (None, false) => Some(commit_template::default_template(&wt_type.name)),
+            };
+            let wt_type = db.update_worktree_type(&wt_type).await?;
+
+            if json_mode {
+                JsonResponse::success(serde_json::json!({
+                    "type_name": wt_type.name,
+                    "commit_template": wt_type.commit_template,
+                }))
+                .print();
+            } else if let Some(template) = &wt_type.commit_template {
+                say!(
+                    "{}",
+                    tr!(
+                        "{} Commit template for '{}' set; new worktrees of this type get it",
+                        "✅".bright_green(),
+                        wt_type.name.bright_green()
+                    )
+                );
+                for line in template.lines() {
+                    println!("      {}", line.bright_black());
                 }
+            } else {
+                say!(
+                    "{}",
+                    tr!(
+                        "{} Commit template for '{}' removed",
+                        "✅".bright_green(),
+                        wt_type.name.bright_green()
+                    )
+                );
             }
         }
         TypeCommands::Remove { name } => {
             if !json_mode {
-                println!(
-                    "{} Removing worktree type: {}",
-                    "🗑️".bright_red(),
-                    name.bright_yellow()
+                say!(
+                    "{}",
+                    tr!(
+                        "{} Removing worktree type: {}",
+                        "🗑️".bright_red(),
+                        name.bright_yellow()
+                    )
                 );
             }
 
@@ -1416,18 +5948,172 @@ async fn handle_types_command(
                 }))
                 .print();
             } else {
-                println!(
-                    "{} Worktree type '{}' removed successfully",
-                    "✅".bright_green(),
-                    name
+                say!(
+                    "{}",
+                    tr!(
+                        "{} Worktree type '{}' removed successfully",
+                        "✅".bright_green(),
+                        name
+                    )
                 );
             }
         }
+        TypeCommands::Export { file } => {
+            let types = ops::export_worktree_types(imi).await?;
+            let rendered = types.render(file.as_deref())?;
+            match &file {
+                Some(path) => {
+                    std::fs::write(path, &rendered)
+                        .context(tr!("Failed to write {}", path.display()))?;
+                    if json_mode {
+                        JsonResponse::success(serde_json::json!({
+                            "file": path.display().to_string(),
+                            "count": types.types.len(),
+                        }))
+                        .print();
+                    } else {
+                        say!(
+                            "{}",
+                            tr!(
+                                "{} Exported {} type(s) to {}",
+                                "📤".bright_cyan(),
+                                types.types.len(),
+                                path.display()
+                            )
+                        );
+                    }
+                }
+                None if json_mode => JsonResponse::success(serde_json::to_value(&types)?).print(),
+                None => print!("{}", rendered),
+            }
+        }
+        TypeCommands::Import {
+            file,
+            repo,
+            overwrite,
+            merge,
+            dry_run,
+        } => {
+            let source = match file {
+                Some(path) => path,
+                None => ops::shared_types_file(imi, repo.as_deref())
+                    .await?
+                    .ok_or_else(|| {
+                        anyhow::anyhow!(tr!(
+                            "No file given and the trunk has no {}",
+                            imi.manager().config.type_sharing.file.display()
+                        ))
+                    })?,
+            };
+            let mode = if overwrite {
+                ImportMode::Overwrite
+            } else if merge {
+                ImportMode::Merge
+            } else {
+                ImportMode::Skip
+            };
+
+            let report = ops::import_worktree_types(imi, &source, mode, dry_run).await?;
+            if json_mode {
+                JsonResponse::success(serde_json::to_value(&report)?).print();
+            } else {
+                print_type_import(&report);
+            }
+        }
     }
 
     Ok(())
 }
 
+fn print_type_import(report: &ops::TypeImportReport) {
+    say!(
+        "{}",
+        tr!(
+            "{} {} types from {}\n",
+            "📥".bright_cyan(),
+            if report.dry_run {
+                "Would import"
+            } else {
+                "Imported"
+            },
+            report.source.display()
+        )
+    );
+
+    let mut conflicts = 0;
+    for action in &report.actions {
+        match action {
+            ImportAction::Add { definition } => {
+                println!(
+                    "  {} {}",
+                    "+".bright_green(),
+                    definition.name.bright_green()
+                )
+            }
+            ImportAction::Update { definition, fields } => println!(
+                "  {} {} ({})",
+                "~".bright_yellow(),
+                definition.name.bright_yellow(),
+                fields.join(", ")
+            ),
+            ImportAction::Unchanged { name } => {
+                println!("  {} {}", "=".bright_black(), name.bright_black())
+            }
+            ImportAction::Conflict { name, fields } => {
+                conflicts += 1;
+                println!(
+                    "{}",
+                    tr!(
+                        "  {} {} kept local {}",
+                        "!".bright_red(),
+                        name.bright_red(),
+                        fields.join(", ")
+                    )
+                );
+            }
+        }
+    }
+
+    if conflicts > 0 {
+        say!(
+            "{}",
+            tr!(
+                "\n{} {} type(s) differ locally; use --merge or --overwrite to take the imported settings",
+                "💡".bright_yellow(),
+                conflicts
+            )
+        );
+    }
+}
+
+fn print_type_defaults(wt_type: &database::WorktreeType) {
+    let unset = || "-".bright_black().to_string();
+
+    println!(
+        "{}",
+        tr!(
+            "      TTL: {}  Base: {}",
+            wt_type
+                .default_ttl_days
+                .map(|d| format!("{}d", d))
+                .unwrap_or_else(unset),
+            wt_type.default_base_branch.clone().unwrap_or_else(unset)
+        )
+    );
+    println!(
+        "{}",
+        tr!(
+            "      Commit template: {}",
+            wt_type
+                .commit_template
+                .as_deref()
+                .and_then(|t| t.lines().next())
+                .map(str::to_string)
+                .unwrap_or_else(unset)
+        )
+    );
+}
+
 async fn handle_project_command(command: ProjectCommands, json_mode: bool) -> Result<()> {
     match command {
         ProjectCommands::Create {
@@ -1439,7 +6125,7 @@ async fn handle_project_command(command: ProjectCommands, json_mode: bool) -> Re
             // Check GitHub authentication first
             if let Err(e) = github::check_auth() {
                 if json_mode {
-                    JsonResponse::error(format!("GitHub authentication failed: {}", e)).print();
+                    JsonResponse::error(tr!("GitHub authentication failed: {}", e)).print();
                 } else {
                     eprintln!("{}", format!("{}", e).red());
                     github::show_auth_help();
@@ -1496,7 +6182,7 @@ async fn handle_claim_command(
     let worktree = match manager.get_worktree_by_name(name, repo).await? {
         Some(wt) => wt,
         None => {
-            let error_msg = format!("Worktree '{}' not found", name);
+            let error_msg = tr!("Worktree '{}' not found", name);
             if json_mode {
                 JsonResponse::error(error_msg.clone()).print();
             } else {
@@ -1509,55 +6195,44 @@ async fn handle_claim_command(
     // Check if already claimed
     if let Some(current_agent) = &worktree.agent_id {
         if !force {
-            let error_msg = format!(
+            let error_msg = tr!(
                 "Worktree '{}' is already claimed by agent '{}'",
-                name, current_agent
+                name,
+                current_agent
             );
             if json_mode {
                 JsonResponse::error(error_msg.clone()).print();
             } else {
                 eprintln!("{}", error_msg.red());
                 eprintln!(
-                    "Use --force to override, or release with: imi release {} --yi-id {}",
-                    name, current_agent
+                    "{}",
+                    tr!(
+                        "Use --force to override, or release with: imi release {} --yi-id {}",
+                        name,
+                        current_agent
+                    )
                 );
             }
             return Err(anyhow::anyhow!(error_msg));
         } else if json_mode {
-            eprintln!("Warning: Force claiming from agent '{}'", current_agent);
+            eprintln!(
+                "{}",
+                tr!("Warning: Force claiming from agent '{}'", current_agent)
+            );
         } else {
-            println!(
-                "{} Force claiming from agent '{}'",
-                "⚠️".yellow(),
-                current_agent
+            eprintln!(
+                "{}",
+                tr!(
+                    "{} Force claiming from agent '{}'",
+                    "⚠️".yellow(),
+                    current_agent
+                )
             );
         }
     }
 
-    // Claim the worktree in database
-    manager.db.claim_worktree(&worktree.id, yi_id).await?;
-
-    // Create lock file in .iMi/presence/
-    let worktree_path = PathBuf::from(&worktree.path);
-    let repo_root = worktree_path
-        .parent()
-        .ok_or_else(|| anyhow::anyhow!("Invalid worktree path"))?;
-
-    let local_ctx = LocalContext::new(repo_root);
-    let imi_dir = repo_root.join(".iMi");
-    local_ctx.create_lock_file(&imi_dir, name, yi_id).await?;
-
-    // Log activity
-    manager
-        .db
-        .log_agent_activity(
-            yi_id,
-            &worktree.id,
-            "claimed",
-            None,
-            "Agent claimed worktree",
-        )
-        .await?;
+    // Claim in the database, the .iMi/presence/ lock file and the activity log
+    manager.claim_worktree(&worktree, name, yi_id).await?;
 
     if json_mode {
         JsonResponse::success(serde_json::json!({
@@ -1569,22 +6244,34 @@ async fn handle_claim_command(
         }))
         .print();
     } else {
+        say!(
+            "{}",
+            tr!(
+                "{} Successfully claimed worktree '{}'",
+                "✅".bright_green(),
+                name
+            )
+        );
         println!(
-            "{} Successfully claimed worktree '{}'",
-            "✅".bright_green(),
-            name
+            "{}",
+            tr!(
+                "   {} Agent ID: {}",
+                "🔑".bright_black(),
+                yi_id.bright_cyan()
+            )
         );
         println!(
-            "   {} Agent ID: {}",
-            "🔑".bright_black(),
-            yi_id.bright_cyan()
+            "{}",
+            tr!(
+                "   {} Worktree ID: {}",
+                "🆔".bright_black(),
+                worktree.id.to_string().bright_black()
+            )
         );
         println!(
-            "   {} Worktree ID: {}",
-            "🆔".bright_black(),
-            worktree.id.to_string().bright_black()
+            "{}",
+            tr!("   {} Path: {}", "📂".bright_black(), worktree.path)
         );
-        println!("   {} Path: {}", "📂".bright_black(), worktree.path);
     }
 
     Ok(())
@@ -1601,21 +6288,19 @@ async fn handle_verify_lock_command(
     let worktree = match manager.get_worktree_by_name(name, repo).await? {
         Some(wt) => wt,
         None => {
-            let error_msg = format!("Worktree '{}' not found", name);
+            let error_msg = tr!("Worktree '{}' not found", name);
             if json_mode {
                 JsonResponse::error(error_msg.clone()).print();
             } else {
                 eprintln!("{}", error_msg.red());
             }
-            std::process::exit(2);
+            return Err(ImiError::Exit { code: 2 }.into());
         }
     };
 
     // Get repository root to find .iMi directory
     let worktree_path = PathBuf::from(&worktree.path);
-    let repo_root = worktree_path
-        .parent()
-        .ok_or_else(|| anyhow::anyhow!("Invalid worktree path"))?;
+    let repo_root = &manager.detect_imi_path(&worktree_path)?;
 
     let lock_file = repo_root
         .join(".iMi/presence")
@@ -1633,35 +6318,38 @@ async fn handle_verify_lock_command(
             }))
             .print();
         } else {
-            println!("{} Worktree '{}' is not locked", "✅".bright_green(), name);
+            say!(
+                "{}",
+                tr!("{} Worktree '{}' is not locked", "✅".bright_green(), name)
+            );
         }
-        std::process::exit(0);
+        return Ok(());
     }
 
     // Read and parse lock file
     let lock_content = match std::fs::read_to_string(&lock_file) {
         Ok(content) => content,
         Err(e) => {
-            let error_msg = format!("Failed to read lock file: {}", e);
+            let error_msg = tr!("Failed to read lock file: {}", e);
             if json_mode {
                 JsonResponse::error(error_msg.clone()).print();
             } else {
                 eprintln!("{} {}", "❌".bright_red(), error_msg);
             }
-            std::process::exit(2);
+            return Err(ImiError::Exit { code: 2 }.into());
         }
     };
 
     let lock_data: serde_json::Value = match serde_json::from_str(&lock_content) {
         Ok(data) => data,
         Err(e) => {
-            let error_msg = format!("Failed to parse lock file: {}", e);
+            let error_msg = tr!("Failed to parse lock file: {}", e);
             if json_mode {
                 JsonResponse::error(error_msg.clone()).print();
             } else {
                 eprintln!("{} {}", "❌".bright_red(), error_msg);
             }
-            std::process::exit(2);
+            return Err(ImiError::Exit { code: 2 }.into());
         }
     };
 
@@ -1681,25 +6369,24 @@ async fn handle_verify_lock_command(
             }))
             .print();
         } else {
-            println!(
-                "{} Worktree '{}' is locked by you ({})",
-                "✅".bright_green(),
-                name,
-                yi_id.bright_cyan()
+            say!(
+                "{}",
+                tr!(
+                    "{} Worktree '{}' is locked by you ({})",
+                    "✅".bright_green(),
+                    name,
+                    yi_id.bright_cyan()
+                )
             );
         }
-        std::process::exit(0);
+        Ok(())
     } else {
         // Locked by different agent - verification fails
         let claimed_at = lock_data["claimed_at"].as_str().unwrap_or("unknown");
         let hostname = lock_data["hostname"].as_str().unwrap_or("unknown");
 
         if json_mode {
-            JsonResponse::error(format!(
-                "Worktree locked by different agent: {}",
-                lock_owner
-            ))
-            .print();
+            JsonResponse::error(tr!("Worktree locked by different agent: {}", lock_owner)).print();
             // Also output lock details in data field
             println!(
                 "{}",
@@ -1714,19 +6401,32 @@ async fn handle_verify_lock_command(
             );
         } else {
             eprintln!(
-                "{} Worktree '{}' is locked by {}",
-                "❌".bright_red(),
-                name,
-                lock_owner.bright_yellow()
+                "{}",
+                tr!(
+                    "{} Worktree '{}' is locked by {}",
+                    "❌".bright_red(),
+                    name,
+                    lock_owner.bright_yellow()
+                )
+            );
+            eprintln!(
+                "{}",
+                tr!("   {} Claimed at: {}", "🕒".bright_black(), claimed_at)
             );
-            eprintln!("   {} Claimed at: {}", "🕒".bright_black(), claimed_at);
-            eprintln!("   {} Hostname: {}", "🖥️".bright_black(), hostname);
             eprintln!(
-                "\n   Use 'imi claim {} --yi-id {} --force' to override",
-                name, lock_owner
+                "{}",
+                tr!("   {} Hostname: {}", "🖥️".bright_black(), hostname)
+            );
+            eprintln!(
+                "{}",
+                tr!(
+                    "\n   Use 'imi claim {} --yi-id {} --force' to override",
+                    name,
+                    lock_owner
+                )
             );
         }
-        std::process::exit(1);
+        Err(ImiError::Exit { code: 1 }.into())
     }
 }
 
@@ -1741,7 +6441,7 @@ async fn handle_release_command(
     let worktree = match manager.get_worktree_by_name(name, repo).await? {
         Some(wt) => wt,
         None => {
-            let error_msg = format!("Worktree '{}' not found", name);
+            let error_msg = tr!("Worktree '{}' not found", name);
             if json_mode {
                 JsonResponse::error(error_msg.clone()).print();
             } else {
@@ -1757,20 +6457,25 @@ async fn handle_release_command(
             // Ownership verified
         }
         Some(owner) => {
-            let error_msg = format!(
+            let error_msg = tr!(
                 "Cannot release: worktree '{}' is owned by agent '{}', not '{}'",
-                name, owner, yi_id
+                name,
+                owner,
+                yi_id
             );
             if json_mode {
                 JsonResponse::error(error_msg.clone()).print();
             } else {
                 eprintln!("{}", error_msg.red());
-                eprintln!("   Only the owning agent can release this worktree");
+                eprintln!(
+                    "{}",
+                    tr!("   Only the owning agent can release this worktree")
+                );
             }
             return Err(anyhow::anyhow!(error_msg));
         }
         None => {
-            let error_msg = format!(
+            let error_msg = tr!(
                 "Cannot release: worktree '{}' is not claimed by any agent",
                 name
             );
@@ -1779,8 +6484,12 @@ async fn handle_release_command(
             } else {
                 eprintln!("{}", error_msg.yellow());
                 eprintln!(
-                    "   Use 'imi claim {} --yi-id {}' to claim it first",
-                    name, yi_id
+                    "{}",
+                    tr!(
+                        "   Use 'imi claim {} --yi-id {}' to claim it first",
+                        name,
+                        yi_id
+                    )
                 );
             }
             return Err(anyhow::anyhow!(error_msg));
@@ -1792,7 +6501,7 @@ async fn handle_release_command(
     let git_status = manager.git.get_worktree_status(&worktree_path)?;
 
     if !git_status.clean {
-        let error_msg = format!(
+        let error_msg = tr!(
             "Cannot release: worktree '{}' has uncommitted changes",
             name
         );
@@ -1809,7 +6518,7 @@ async fn handle_release_command(
             );
         } else {
             eprintln!("{}", error_msg.red());
-            eprintln!("\n   Modified files:");
+            eprintln!("{}", tr!("\n   Modified files:"));
             for file in &git_status.modified_files {
                 eprintln!("      M {}", file.yellow());
             }
@@ -1819,7 +6528,7 @@ async fn handle_release_command(
             for file in &git_status.deleted_files {
                 eprintln!("      D {}", file.red());
             }
-            eprintln!("\n   Commit or discard changes before releasing");
+            eprintln!("{}", tr!("\n   Commit or discard changes before releasing"));
         }
         return Err(anyhow::anyhow!(error_msg));
     }
@@ -1828,9 +6537,7 @@ async fn handle_release_command(
     manager.db.release_worktree(&worktree.id, yi_id).await?;
 
     // Remove lock file
-    let repo_root = worktree_path
-        .parent()
-        .ok_or_else(|| anyhow::anyhow!("Invalid worktree path"))?;
+    let repo_root = &manager.detect_imi_path(&worktree_path)?;
 
     let local_ctx = LocalContext::new(repo_root);
     let imi_dir = repo_root.join(".iMi");
@@ -1859,23 +6566,38 @@ async fn handle_release_command(
         }))
         .print();
     } else {
+        say!(
+            "{}",
+            tr!(
+                "{} Successfully released worktree '{}'",
+                "✅".bright_green(),
+                name
+            )
+        );
         println!(
-            "{} Successfully released worktree '{}'",
-            "✅".bright_green(),
-            name
+            "{}",
+            tr!(
+                "   {} Agent ID: {}",
+                "🔓".bright_black(),
+                yi_id.bright_cyan()
+            )
+        );
+        println!(
+            "{}",
+            tr!(
+                "   {} Worktree ID: {}",
+                "🆔".bright_black(),
+                worktree.id.to_string().bright_black()
+            )
         );
         println!(
-            "   {} Agent ID: {}",
-            "🔓".bright_black(),
-            yi_id.bright_cyan()
+            "{}",
+            tr!("   {} Path: {}", "📂".bright_black(), worktree.path)
         );
         println!(
-            "   {} Worktree ID: {}",
-            "🆔".bright_black(),
-            worktree.id.to_string().bright_black()
+            "{}",
+            tr!("\n   Worktree is now available for other agents to claim")
         );
-        println!("   {} Path: {}", "📂".bright_black(), worktree.path);
-        println!("\n   Worktree is now available for other agents to claim");
     }
 
     Ok(())
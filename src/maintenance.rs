@@ -0,0 +1,666 @@
+//! Maintenance the daemon runs on a schedule (`[maintenance]`): extra
+//! fetches, `git maintenance` in each trunk, database garbage collection,
+//! disk usage refresh and closing worktrees past their type's TTL. When each
+//! task last ran is kept in `maintenance.json` for `imi daemon tasks`.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, Local, NaiveDate, NaiveDateTime, TimeZone, Timelike, Utc};
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tokio::{signal, time};
+
+use crate::config::{Config, MaintenanceSettings};
+use crate::daemon::FetchScheduler;
+use crate::disk_usage::DiskUsageCache;
+use crate::json_cache;
+use crate::ops::{self, BulkCloseOptions, CloseStatus, Imi};
+use crate::worktree::WorktreeManager;
+
+/// How often the scheduler looks for due tasks; cron schedules have minute
+/// resolution
+const TICK: Duration = Duration::from_secs(60);
+
+/// Cron expressions that match nothing within this many days are rejected
+const CRON_HORIZON_DAYS: i64 = 4 * 366;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskKind {
+    Fetch,
+    GitMaintenance,
+    DbGc,
+    DiskUsage,
+    TtlCleanup,
+}
+
+impl TaskKind {
+    pub const ALL: [TaskKind; 5] = [
+        TaskKind::Fetch,
+        TaskKind::GitMaintenance,
+        TaskKind::DbGc,
+        TaskKind::DiskUsage,
+        TaskKind::TtlCleanup,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TaskKind::Fetch => "fetch",
+            TaskKind::GitMaintenance => "git_maintenance",
+            TaskKind::DbGc => "db_gc",
+            TaskKind::DiskUsage => "disk_usage",
+            TaskKind::TtlCleanup => "ttl_cleanup",
+        }
+    }
+
+    fn schedule_spec(self, settings: &MaintenanceSettings) -> &str {
+        match self {
+            TaskKind::Fetch => &settings.fetch,
+            TaskKind::GitMaintenance => &settings.git_maintenance,
+            TaskKind::DbGc => &settings.db_gc,
+            TaskKind::DiskUsage => &settings.disk_usage,
+            TaskKind::TtlCleanup => &settings.ttl_cleanup,
+        }
+    }
+}
+
+/// When a task runs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Schedule {
+    Every(chrono::Duration),
+    Cron(CronSpec),
+}
+
+impl Schedule {
+    /// A cron expression, `@hourly`/`@daily`/`@weekly`/`@monthly`, or
+    /// `every <n><s|m|h|d|w>`; None for `off`
+    pub fn parse(spec: &str) -> Result<Option<Self>> {
+        let spec = spec.trim();
+        let cron = match spec {
+            "off" | "never" | "" => return Ok(None),
+            "@hourly" => "0 * * * *",
+            "@daily" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            spec => match spec.strip_prefix("every ") {
+                Some(interval) => return parse_interval(interval).map(|d| Some(Self::Every(d))),
+                None => spec,
+            },
+        };
+        CronSpec::parse(cron).map(|spec| Some(Self::Cron(spec)))
+    }
+
+    /// When the task is next due: an interval after the last run (right away
+    /// if it never ran), or the first cron match after the last run, or after
+    /// `since` if it never ran
+    pub fn next_run(&self, last_run: Option<DateTime<Utc>>, since: DateTime<Utc>) -> DateTime<Utc> {
+        match self {
+            Schedule::Every(interval) => last_run.map(|last| last + *interval).unwrap_or(since),
+            Schedule::Cron(spec) => {
+                let after = last_run
+                    .unwrap_or(since)
+                    .with_timezone(&Local)
+                    .naive_local();
+                spec.next_after(after)
+                    .and_then(|next| Local.from_local_datetime(&next).earliest())
+                    .map(|next| next.with_timezone(&Utc))
+                    .unwrap_or(DateTime::<Utc>::MAX_UTC)
+            }
+        }
+    }
+}
+
+fn parse_interval(interval: &str) -> Result<chrono::Duration> {
+    let interval = interval.trim();
+    let split = interval
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(interval.len());
+    let (count, unit) = interval.split_at(split);
+    let count: i64 = count
+        .parse()
+        .ok()
+        .filter(|count| *count > 0)
+        .context(format!("Invalid interval 'every {}'", interval))?;
+    match unit.trim() {
+        "s" => Ok(chrono::Duration::seconds(count)),
+        "m" => Ok(chrono::Duration::minutes(count)),
+        "h" => Ok(chrono::Duration::hours(count)),
+        "d" => Ok(chrono::Duration::days(count)),
+        "w" => Ok(chrono::Duration::weeks(count)),
+        unit => anyhow::bail!("Unknown interval unit '{}'; use s, m, h, d or w", unit),
+    }
+}
+
+/// A five-field cron expression (minute hour day-of-month month
+/// day-of-week) in local time, with `*`, lists, ranges and steps
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSpec {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// Day-of-month and day-of-week were both restricted, so either matches
+    either_day: bool,
+}
+
+impl CronSpec {
+    pub fn parse(expr: &str) -> Result<Self> {
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            anyhow::bail!(
+                "Invalid schedule '{}'; expected 5 cron fields, @daily or 'every 6h'",
+                expr
+            );
+        };
+        let field = |text: &str, min: u32, max: u32| {
+            parse_field(text, min, max)
+                .with_context(|| format!("Invalid field '{}' in schedule '{}'", text, expr))
+        };
+        let mut weekdays = field(weekday, 0, 7)?;
+        if weekdays & (1 << 7) != 0 {
+            weekdays |= 1;
+        }
+        let spec = Self {
+            minutes: field(minute, 0, 59)?,
+            hours: field(hour, 0, 23)?,
+            days: field(day, 1, 31)?,
+            months: field(month, 1, 12)?,
+            weekdays,
+            either_day: day != "*" && weekday != "*",
+        };
+
+        let epoch = NaiveDate::from_ymd_opt(2000, 1, 1)
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+            .unwrap_or_default();
+        if spec.next_after(epoch).is_none() {
+            anyhow::bail!("Schedule '{}' never runs", expr);
+        }
+        Ok(spec)
+    }
+
+    fn day_matches(&self, date: NaiveDate) -> bool {
+        let day = self.days & (1 << date.day()) != 0;
+        let weekday = self.weekdays & (1 << date.weekday().num_days_from_sunday()) != 0;
+        let month = self.months & (1 << date.month()) != 0;
+        month
+            && match self.either_day {
+                true => day || weekday,
+                false => day && weekday,
+            }
+    }
+
+    pub fn matches(&self, at: NaiveDateTime) -> bool {
+        self.day_matches(at.date())
+            && self.hours & (1 << at.hour()) != 0
+            && self.minutes & (1 << at.minute()) != 0
+    }
+
+    /// First matching minute after `after`
+    pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let mut at = after.with_second(0)?.with_nanosecond(0)? + chrono::Duration::minutes(1);
+        let limit = after + chrono::Duration::days(CRON_HORIZON_DAYS);
+        while at <= limit {
+            if !self.day_matches(at.date()) {
+                at = at.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+                continue;
+            }
+            if self.matches(at) {
+                return Some(at);
+            }
+            at += chrono::Duration::minutes(1);
+        }
+        None
+    }
+}
+
+/// Bit mask of the values a cron field allows
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64> {
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<usize>()?),
+            None => (part, 1),
+        };
+        anyhow::ensure!(step > 0, "step must be positive");
+        let (start, end) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((start, end)) => (start.parse()?, end.parse()?),
+            None if step > 1 => (range.parse()?, max),
+            None => {
+                let value = range.parse()?;
+                (value, value)
+            }
+        };
+        anyhow::ensure!(
+            min <= start && start <= end && end <= max,
+            "values must be within {}-{}",
+            min,
+            max
+        );
+        for value in (start..=end).step_by(step) {
+            mask |= 1 << value;
+        }
+    }
+    Ok(mask)
+}
+
+/// The outcome of one task run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskRun {
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Failed runs in a row, this one included
+    #[serde(default)]
+    pub failures: u32,
+}
+
+/// The last run of each task, persisted between daemon runs
+#[derive(Debug)]
+pub struct TaskLog {
+    path: Option<PathBuf>,
+    runs: BTreeMap<TaskKind, TaskRun>,
+}
+
+impl TaskLog {
+    /// `maintenance.json` next to the global config; a missing or unreadable
+    /// log starts empty
+    pub fn load() -> Self {
+        let path = Config::get_global_config_path()
+            .ok()
+            .map(|path| path.with_file_name("maintenance.json"));
+        Self::load_from(path)
+    }
+
+    pub fn load_from(path: Option<PathBuf>) -> Self {
+        let runs = json_cache::read(path.as_deref());
+        Self { path, runs }
+    }
+
+    pub fn last(&self, task: TaskKind) -> Option<&TaskRun> {
+        self.runs.get(&task)
+    }
+
+    /// Record a run, counting consecutive failures, and write the log back
+    pub fn record(&mut self, task: TaskKind, mut run: TaskRun) -> Result<()> {
+        run.failures = match (&run.error, self.runs.get(&task)) {
+            (None, _) => 0,
+            (Some(_), Some(previous)) => previous.failures + 1,
+            (Some(_), None) => 1,
+        };
+        self.runs.insert(task, run);
+
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(&self.runs)?)
+            .context(format!("Failed to write {}", path.display()))
+    }
+}
+
+/// A task's schedule with its last and next run, for `imi daemon tasks`
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskStatus {
+    pub task: TaskKind,
+    pub schedule: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_run: Option<TaskRun>,
+    /// None when the task is off or its schedule is invalid
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_run: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schedule_error: Option<String>,
+}
+
+impl TaskStatus {
+    pub fn enabled(&self) -> bool {
+        self.next_run.is_some()
+    }
+}
+
+/// Every task with its schedule, last run and next run as of `now`
+pub fn task_statuses(
+    settings: &MaintenanceSettings,
+    log: &TaskLog,
+    now: DateTime<Utc>,
+) -> Vec<TaskStatus> {
+    TaskKind::ALL
+        .iter()
+        .map(|&task| {
+            let spec = task.schedule_spec(settings);
+            let last_run = log.last(task).cloned();
+            let (next_run, schedule_error) = match Schedule::parse(spec) {
+                Ok(Some(schedule)) => (
+                    Some(schedule.next_run(last_run.as_ref().map(|run| run.started_at), now)),
+                    None,
+                ),
+                Ok(None) => (None, None),
+                Err(e) => (None, Some(format!("{:#}", e))),
+            };
+            TaskStatus {
+                task,
+                schedule: spec.to_string(),
+                last_run,
+                next_run,
+                schedule_error,
+            }
+        })
+        .collect()
+}
+
+/// Runs due maintenance tasks next to the daemon's fetch cycle
+pub struct MaintenanceScheduler {
+    imi: Imi,
+    settings: MaintenanceSettings,
+    schedules: Vec<(TaskKind, Schedule)>,
+}
+
+impl MaintenanceScheduler {
+    /// Fails on an invalid schedule so the daemon doesn't start half-configured
+    pub fn new(manager: &WorktreeManager) -> Result<Self> {
+        let settings = manager.config.maintenance.clone();
+        let mut schedules = Vec::new();
+        for task in TaskKind::ALL {
+            let schedule = Schedule::parse(task.schedule_spec(&settings))
+                .with_context(|| format!("Invalid [maintenance] {} schedule", task.name()))?;
+            if let Some(schedule) = schedule {
+                schedules.push((task, schedule));
+            }
+        }
+        Ok(Self {
+            imi: Imi::new(manager.clone().quiet(true)),
+            settings,
+            schedules,
+        })
+    }
+
+    /// Run due tasks until interrupted with Ctrl+C
+    pub async fn run(&self) -> Result<()> {
+        if self.schedules.is_empty() {
+            return Ok(());
+        }
+        let names: Vec<_> = self.schedules.iter().map(|(task, _)| task.name()).collect();
+        println!(
+            "{} Maintenance scheduler running: {}",
+            "🧰".bright_cyan(),
+            names.join(", ")
+        );
+
+        let started = Utc::now();
+        loop {
+            let mut log = TaskLog::load();
+            for (task, schedule) in &self.schedules {
+                let last_run = log.last(*task).map(|run| run.started_at);
+                if schedule.next_run(last_run, started) > Utc::now() {
+                    continue;
+                }
+                let run = self.run_task(*task).await;
+                print_run(*task, &run);
+                if let Err(e) = log.record(*task, run) {
+                    println!("   {} {}", "⚠️".bright_yellow(), e);
+                }
+            }
+
+            tokio::select! {
+                _ = time::sleep(TICK) => {}
+                _ = signal::ctrl_c() => {
+                    println!("\n{} Maintenance scheduler stopped", "🛑".bright_red());
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    pub async fn run_task(&self, task: TaskKind) -> TaskRun {
+        let started_at = Utc::now();
+        let timer = Instant::now();
+        let result = match task {
+            TaskKind::Fetch => self.fetch().await,
+            TaskKind::GitMaintenance => self.git_maintenance().await,
+            TaskKind::DbGc => self.db_gc().await,
+            TaskKind::DiskUsage => self.disk_usage().await,
+            TaskKind::TtlCleanup => self.ttl_cleanup().await,
+        };
+        let (summary, error) = match result {
+            Ok(summary) => (Some(summary), None),
+            Err(e) => (None, Some(format!("{:#}", e))),
+        };
+        TaskRun {
+            started_at,
+            duration_ms: timer.elapsed().as_millis() as i64,
+            summary,
+            error,
+            failures: 0,
+        }
+    }
+
+    async fn fetch(&self) -> Result<String> {
+        let manager = self.imi.manager();
+        let outcomes = FetchScheduler::new(manager.db.clone(), &manager.config)
+            .run_once()
+            .await?;
+        let failed: Vec<_> = outcomes
+            .iter()
+            .filter(|outcome| outcome.error.is_some())
+            .map(|outcome| outcome.repo_name.as_str())
+            .collect();
+        anyhow::ensure!(failed.is_empty(), "fetch failed for {}", failed.join(", "));
+        Ok(format!("fetched {} repositories", outcomes.len()))
+    }
+
+    async fn git_maintenance(&self) -> Result<String> {
+        let manager = self.imi.manager();
+        let mut done = 0;
+        let mut failed = Vec::new();
+        for repo in manager.db.list_repositories().await? {
+            let path = PathBuf::from(&repo.path);
+            if !path.exists() {
+                continue;
+            }
+            let git = manager.git.clone();
+            let result = tokio::task::spawn_blocking(move || {
+                match git.execute_git_command(&path, &["maintenance", "run", "--quiet"]) {
+                    // git before 2.29 has no `maintenance`
+                    Err(e) if e.to_string().contains("is not a git command") => {
+                        git.execute_git_command(&path, &["gc", "--auto", "--quiet"])
+                    }
+                    result => result,
+                }
+            })
+            .await?;
+            match result {
+                Ok(_) => done += 1,
+                Err(e) => failed.push(format!("{}: {}", repo.name, e.to_string().trim())),
+            }
+        }
+        anyhow::ensure!(failed.is_empty(), "{}", failed.join("; "));
+        Ok(format!("maintained {} repositories", done))
+    }
+
+    async fn db_gc(&self) -> Result<String> {
+        let (activities, navigation) = self
+            .imi
+            .database()
+            .collect_garbage(self.settings.activity_retention_days)
+            .await?;
+        Ok(format!(
+            "deleted {} old activity and {} stale navigation row(s)",
+            activities, navigation
+        ))
+    }
+
+    async fn disk_usage(&self) -> Result<String> {
+        let manager = self.imi.manager();
+        let paths: Vec<PathBuf> = manager
+            .db
+            .list_worktrees(None)
+            .await?
+            .into_iter()
+            .filter(|wt| wt.is_local())
+            .map(|wt| PathBuf::from(wt.path))
+            .filter(|path| path.exists())
+            .collect();
+        let ttl_secs = manager.config.size_budget.cache_ttl_secs;
+        tokio::task::spawn_blocking(move || {
+            let mut cache = DiskUsageCache::load(ttl_secs);
+            let bytes: u64 = paths.iter().map(|path| cache.refresh(path)).sum();
+            cache.save()?;
+            Ok(format!(
+                "measured {} worktree(s), {} MB in total",
+                paths.len(),
+                bytes / (1024 * 1024)
+            ))
+        })
+        .await?
+    }
+
+    async fn ttl_cleanup(&self) -> Result<String> {
+        let options = BulkCloseOptions {
+            expired: true,
+            ..Default::default()
+        };
+        let targets = ops::select_close_targets(&self.imi, &options).await?;
+        let results = ops::close_worktrees(&self.imi, targets).await;
+        let count = |status| results.iter().filter(|r| r.status == status).count();
+        let failed: Vec<_> = results
+            .iter()
+            .filter(|r| r.status == CloseStatus::Failed)
+            .map(|r| {
+                format!(
+                    "{}/{}: {}",
+                    r.target.repo_name,
+                    r.target.worktree_name,
+                    r.message.as_deref().unwrap_or_default()
+                )
+            })
+            .collect();
+        anyhow::ensure!(failed.is_empty(), "{}", failed.join("; "));
+        Ok(format!(
+            "closed {} expired worktree(s), left {} with unsaved work",
+            count(CloseStatus::Closed),
+            count(CloseStatus::Skipped)
+        ))
+    }
+}
+
+fn print_run(task: TaskKind, run: &TaskRun) {
+    let time = chrono::Local::now().format("%H:%M:%S");
+    match (&run.summary, &run.error) {
+        (_, Some(error)) => println!(
+            "{} [{}] {} failed: {}",
+            "⚠️".bright_yellow(),
+            time,
+            task.name().bright_blue(),
+            error
+        ),
+        (summary, None) => println!(
+            "{} [{}] {}: {} ({} ms)",
+            "🧰".bright_cyan(),
+            time,
+            task.name().bright_blue(),
+            summary.as_deref().unwrap_or_default(),
+            run.duration_ms
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(text: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn parses_cron_expressions_and_finds_the_next_match() {
+        let nightly = CronSpec::parse("30 4 * * *").unwrap();
+        assert_eq!(
+            nightly.next_after(at("2026-03-02 12:00")),
+            Some(at("2026-03-03 04:30"))
+        );
+
+        let weekly = CronSpec::parse("*/15 8-9 * * 1-5").unwrap();
+        // 2026-03-07 is a Saturday
+        assert_eq!(
+            weekly.next_after(at("2026-03-06 09:50")),
+            Some(at("2026-03-09 08:00"))
+        );
+        // Both day fields restricted: either one matches
+        let either = CronSpec::parse("0 0 1 * 0").unwrap();
+        assert!(either.matches(at("2026-03-01 00:00")));
+        assert!(either.matches(at("2026-03-08 00:00")));
+        assert!(!either.matches(at("2026-03-09 00:00")));
+        assert!(CronSpec::parse("0 0 * * 7")
+            .unwrap()
+            .matches(at("2026-03-08 00:00")));
+
+        assert!(CronSpec::parse("0 0 31 2 *").is_err());
+        assert!(CronSpec::parse("60 * * * *").is_err());
+        assert!(CronSpec::parse("* * *").is_err());
+    }
+
+    #[test]
+    fn parses_intervals_aliases_and_off() {
+        assert_eq!(Schedule::parse("off").unwrap(), None);
+        assert_eq!(
+            Schedule::parse("every 6h").unwrap(),
+            Some(Schedule::Every(chrono::Duration::hours(6)))
+        );
+        assert!(Schedule::parse("every 0m").is_err());
+        assert!(Schedule::parse("every 3y").is_err());
+        assert!(matches!(
+            Schedule::parse("@weekly").unwrap(),
+            Some(Schedule::Cron(_))
+        ));
+
+        let now = Utc::now();
+        let hourly = Schedule::Every(chrono::Duration::hours(1));
+        assert_eq!(hourly.next_run(None, now), now);
+        assert_eq!(
+            hourly.next_run(Some(now), now),
+            now + chrono::Duration::hours(1)
+        );
+        let cron = Schedule::parse("@hourly").unwrap().unwrap();
+        assert!(cron.next_run(None, now) > now);
+    }
+
+    #[test]
+    fn records_runs_and_counts_consecutive_failures() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("maintenance.json");
+        let run = |error: Option<&str>| TaskRun {
+            started_at: Utc::now(),
+            duration_ms: 5,
+            summary: None,
+            error: error.map(String::from),
+            failures: 0,
+        };
+
+        let mut log = TaskLog::load_from(Some(path.clone()));
+        log.record(TaskKind::DbGc, run(Some("down"))).unwrap();
+        log.record(TaskKind::DbGc, run(Some("still down"))).unwrap();
+        let log = TaskLog::load_from(Some(path.clone()));
+        assert_eq!(log.last(TaskKind::DbGc).unwrap().failures, 2);
+
+        let mut log = log;
+        log.record(TaskKind::DbGc, run(None)).unwrap();
+        assert_eq!(log.last(TaskKind::DbGc).unwrap().failures, 0);
+
+        let settings = MaintenanceSettings::default();
+        let statuses = task_statuses(&settings, &log, Utc::now());
+        let status = |task| statuses.iter().find(|s| s.task == task).unwrap();
+        assert!(!status(TaskKind::Fetch).enabled());
+        assert!(status(TaskKind::DbGc).last_run.is_some());
+        assert!(status(TaskKind::DiskUsage).next_run.unwrap() <= Utc::now());
+    }
+}
@@ -0,0 +1,345 @@
+//! Prometheus metrics for `imi daemon run --metrics`, so worktree hygiene
+//! (dirty trees, drift from trunk, disk use) can be alerted on like any
+//! other system. Collection is always available; serving needs the `http`
+//! feature.
+
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::audit::AuditLog;
+use crate::config::AuditSettings;
+use crate::disk_usage::DiskUsageCache;
+use crate::ops::Imi;
+
+/// What one local worktree looked like at scrape time
+#[derive(Debug, Clone)]
+pub struct WorktreeSample {
+    pub repo_name: String,
+    pub worktree_name: String,
+    pub worktree_type: String,
+    pub dirty: bool,
+    pub behind_trunk: Option<usize>,
+    pub disk_bytes: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Snapshot {
+    pub worktrees: Vec<WorktreeSample>,
+    /// Audit log invocations keyed by command and whether they succeeded
+    pub operations: BTreeMap<(String, bool), u64>,
+}
+
+/// Gather a snapshot of every active worktree on this machine. Disk usage
+/// comes from the size cache the maintenance scheduler keeps warm.
+pub async fn collect(imi: &Imi) -> Result<Snapshot> {
+    let db = imi.database();
+    let mut worktrees = db.list_worktrees(None).await?;
+    db.populate_worktree_names(&mut worktrees).await?;
+    worktrees.retain(|wt| wt.is_local());
+
+    let config = imi.manager().config.clone();
+    let git = imi.manager().git.clone();
+
+    tokio::task::spawn_blocking(move || {
+        let mut cache = DiskUsageCache::load(config.size_budget.cache_ttl_secs);
+        let samples = worktrees
+            .into_iter()
+            .map(|wt| {
+                let path = Path::new(&wt.path);
+                let exists = path.exists();
+                WorktreeSample {
+                    dirty: exists
+                        && git
+                            .get_worktree_status(path)
+                            .is_ok_and(|status| !status.clean),
+                    behind_trunk: exists
+                        .then(|| {
+                            git.get_behind_trunk(path, &config.git_settings.default_branch)
+                                .ok()
+                        })
+                        .flatten(),
+                    disk_bytes: exists.then(|| cache.size_of(path)),
+                    repo_name: wt.repo_name,
+                    worktree_name: wt.name,
+                    worktree_type: wt.worktree_type,
+                }
+            })
+            .collect();
+        let _ = cache.save();
+
+        let operations = match config.audit_settings.is_enabled() {
+            true => count_operations(&config.audit_settings),
+            false => BTreeMap::new(),
+        };
+
+        Ok(Snapshot {
+            worktrees: samples,
+            operations,
+        })
+    })
+    .await?
+}
+
+/// Audit log invocations by command and outcome, kept for the life of the
+/// process so each scrape only reads what was appended since the last one
+fn count_operations(settings: &AuditSettings) -> BTreeMap<(String, bool), u64> {
+    static COUNTER: Mutex<OperationCounter> = Mutex::new(OperationCounter::new());
+    let mut counter = COUNTER.lock().unwrap_or_else(|e| e.into_inner());
+    if let Ok(log) = AuditLog::from_settings(settings) {
+        counter.update(&log);
+    }
+    counter.counts.clone()
+}
+
+/// Running totals of an audit log and how far it has been read
+#[derive(Debug)]
+struct OperationCounter {
+    path: Option<PathBuf>,
+    offset: u64,
+    counts: BTreeMap<(String, bool), u64>,
+}
+
+impl OperationCounter {
+    const fn new() -> Self {
+        Self {
+            path: None,
+            offset: 0,
+            counts: BTreeMap::new(),
+        }
+    }
+
+    /// Count the entries appended to `log` since the last update; a
+    /// different log starts the totals over
+    fn update(&mut self, log: &AuditLog) {
+        if self.path.as_deref() != Some(log.path()) {
+            *self = Self::new();
+            self.path = Some(log.path().to_path_buf());
+        }
+        let Ok((entries, offset)) = log.read_from(self.offset) else {
+            return;
+        };
+        self.offset = offset;
+        for entry in entries {
+            *self
+                .counts
+                .entry((entry.command, entry.success))
+                .or_default() += 1;
+        }
+    }
+}
+
+/// The snapshot in the Prometheus text exposition format
+pub fn render(snapshot: &Snapshot) -> String {
+    let mut per_type: BTreeMap<(&str, &str), (u64, u64)> = BTreeMap::new();
+    for wt in &snapshot.worktrees {
+        let (total, dirty) = per_type
+            .entry((&wt.repo_name, &wt.worktree_type))
+            .or_default();
+        *total += 1;
+        *dirty += u64::from(wt.dirty);
+    }
+
+    let mut out = String::new();
+    header(&mut out, "imi_worktrees", "gauge", "Active worktrees");
+    for ((repo, kind), (total, _)) in &per_type {
+        sample(
+            &mut out,
+            "imi_worktrees",
+            &[("repo", repo), ("type", kind)],
+            *total,
+        );
+    }
+
+    header(
+        &mut out,
+        "imi_worktrees_dirty",
+        "gauge",
+        "Active worktrees with uncommitted changes",
+    );
+    for ((repo, kind), (_, dirty)) in &per_type {
+        sample(
+            &mut out,
+            "imi_worktrees_dirty",
+            &[("repo", repo), ("type", kind)],
+            *dirty,
+        );
+    }
+
+    header(
+        &mut out,
+        "imi_worktree_behind_trunk_commits",
+        "gauge",
+        "Commits on trunk the worktree does not have",
+    );
+    for wt in &snapshot.worktrees {
+        if let Some(behind) = wt.behind_trunk {
+            sample(
+                &mut out,
+                "imi_worktree_behind_trunk_commits",
+                &worktree_labels(wt),
+                behind as u64,
+            );
+        }
+    }
+
+    header(
+        &mut out,
+        "imi_worktree_disk_usage_bytes",
+        "gauge",
+        "Disk used by the worktree directory",
+    );
+    for wt in &snapshot.worktrees {
+        if let Some(bytes) = wt.disk_bytes {
+            sample(
+                &mut out,
+                "imi_worktree_disk_usage_bytes",
+                &worktree_labels(wt),
+                bytes,
+            );
+        }
+    }
+
+    header(
+        &mut out,
+        "imi_operations_total",
+        "counter",
+        "Commands recorded in the audit log",
+    );
+    for ((command, success), count) in &snapshot.operations {
+        let result = if *success { "success" } else { "failure" };
+        sample(
+            &mut out,
+            "imi_operations_total",
+            &[("command", command), ("result", result)],
+            *count,
+        );
+    }
+    out
+}
+
+fn worktree_labels(wt: &WorktreeSample) -> [(&str, &str); 3] {
+    [
+        ("repo", &wt.repo_name),
+        ("worktree", &wt.worktree_name),
+        ("type", &wt.worktree_type),
+    ]
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+fn sample(out: &mut String, name: &str, labels: &[(&str, &str)], value: u64) {
+    let labels: Vec<String> = labels
+        .iter()
+        .map(|(key, value)| format!("{}=\"{}\"", key, escape_label(value)))
+        .collect();
+    let _ = writeln!(out, "{}{{{}}} {}", name, labels.join(","), value);
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// `HOST:PORT`, or `:PORT` for localhost only; worktree names and paths
+/// are exposed to whoever can reach the address, so listening on every
+/// interface takes an explicit `0.0.0.0:PORT`
+pub fn parse_listen_addr(s: &str) -> std::result::Result<SocketAddr, String> {
+    let addr = match s.strip_prefix(':') {
+        Some(port) => format!("127.0.0.1:{}", port),
+        None => s.to_string(),
+    };
+    addr.parse()
+        .map_err(|_| format!("expected HOST:PORT or :PORT, got '{}'", s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::AuditEntry;
+
+    fn worktree(name: &str, kind: &str, dirty: bool, behind: Option<usize>) -> WorktreeSample {
+        WorktreeSample {
+            repo_name: "api".to_string(),
+            worktree_name: name.to_string(),
+            worktree_type: kind.to_string(),
+            dirty,
+            behind_trunk: behind,
+            disk_bytes: Some(2048),
+        }
+    }
+
+    #[test]
+    fn renders_prometheus_text() {
+        let snapshot = Snapshot {
+            worktrees: vec![
+                worktree("feat-a", "feat", true, Some(3)),
+                worktree("feat-\"b\"", "feat", false, None),
+                worktree("fix-c", "fix", false, Some(0)),
+            ],
+            operations: BTreeMap::from([
+                (("add".to_string(), true), 4),
+                (("merge".to_string(), false), 1),
+            ]),
+        };
+
+        let text = render(&snapshot);
+        assert!(text.contains("# TYPE imi_worktrees gauge\n"));
+        assert!(text.contains("imi_worktrees{repo=\"api\",type=\"feat\"} 2\n"));
+        assert!(text.contains("imi_worktrees_dirty{repo=\"api\",type=\"feat\"} 1\n"));
+        assert!(text.contains("imi_worktrees_dirty{repo=\"api\",type=\"fix\"} 0\n"));
+        assert!(text.contains(
+            "imi_worktree_behind_trunk_commits{repo=\"api\",worktree=\"feat-a\",type=\"feat\"} 3\n"
+        ));
+        assert!(!text.contains(
+            "imi_worktree_behind_trunk_commits{repo=\"api\",worktree=\"feat-\\\"b\\\"\""
+        ));
+        assert!(text.contains(
+            "imi_worktree_disk_usage_bytes{repo=\"api\",worktree=\"feat-\\\"b\\\"\",type=\"feat\"} 2048\n"
+        ));
+        assert!(text.contains("# TYPE imi_operations_total counter\n"));
+        assert!(text.contains("imi_operations_total{command=\"merge\",result=\"failure\"} 1\n"));
+    }
+
+    #[test]
+    fn parses_listen_addresses() {
+        assert_eq!(
+            parse_listen_addr(":9187").unwrap(),
+            "127.0.0.1:9187".parse::<SocketAddr>().unwrap()
+        );
+        assert_eq!(
+            parse_listen_addr("0.0.0.0:9187").unwrap(),
+            "0.0.0.0:9187".parse::<SocketAddr>().unwrap()
+        );
+        assert!(parse_listen_addr("9187").is_err());
+    }
+
+    #[test]
+    fn counts_operations_appended_since_the_last_scrape() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = AuditLog::new(dir.path().join("audit.jsonl"));
+        let settings = AuditSettings::default();
+        let entry = |command: &str, error: Option<&str>| {
+            AuditEntry::new(command, &[], &settings, 1, error.map(String::from))
+        };
+        let mut counter = OperationCounter::new();
+
+        log.append(&entry("add", None)).unwrap();
+        log.append(&entry("merge", Some("conflict"))).unwrap();
+        counter.update(&log);
+        log.append(&entry("add", None)).unwrap();
+        counter.update(&log);
+
+        assert_eq!(counter.counts[&("add".to_string(), true)], 2);
+        assert_eq!(counter.counts[&("merge".to_string(), false)], 1);
+        assert_eq!(counter.offset, std::fs::metadata(log.path()).unwrap().len());
+    }
+}
@@ -1,18 +1,26 @@
 use anyhow::{Context, Result};
 use colored::*;
 use notify::{Config, Event, RecommendedWatcher, RecursiveMode, Watcher};
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
-use tokio::{signal, time};
+use tokio::{signal, sync::mpsc, time};
+use uuid::Uuid;
 
+use crate::alerts::{self, AlertEvaluator, AlertTracker};
 use crate::database::Worktree;
+use crate::event_hooks::{CommitTracker, HookEvent, HookRunner, StatusTracker};
+use crate::trunk_protection::TrunkViolation;
 use crate::worktree::WorktreeManager;
 
+/// How long `imi status --watch` lets a burst of file events settle
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(300);
+
 #[derive(Debug, Clone)]
 pub struct MonitorManager {
     pub worktree_manager: WorktreeManager,
     pub config: crate::config::Config,
+    hooks: HookRunner,
 }
 
 #[derive(Debug, Clone)]
@@ -26,6 +34,7 @@ pub struct ActivityEvent {
 impl MonitorManager {
     pub fn new(worktree_manager: WorktreeManager, config: crate::config::Config) -> Self {
         Self {
+            hooks: HookRunner::new(&config.event_hooks),
             worktree_manager,
             config,
         }
@@ -40,7 +49,13 @@ impl MonitorManager {
         println!("{}", "─".repeat(60).bright_black());
 
         // Get active worktrees to monitor
-        let worktrees = self.worktree_manager.db.list_worktrees(repo).await?;
+        let settings = &self.config.monitoring_settings;
+        let mut worktrees = self.worktree_manager.db.list_worktrees(repo).await?;
+        self.worktree_manager
+            .db
+            .populate_worktree_names(&mut worktrees)
+            .await?;
+        worktrees.retain(|wt| repo.is_some() || settings.includes(&wt.repo_name));
 
         if worktrees.is_empty() {
             println!("{} No active worktrees to monitor", "ℹ️".bright_blue());
@@ -67,7 +82,7 @@ impl MonitorManager {
         let mut _watchers = Vec::new();
         let mut path_to_worktree = HashMap::new();
 
-        for worktree in &worktrees {
+        for worktree in worktrees.iter().filter(|_| settings.watch_file_changes) {
             let path = PathBuf::from(&worktree.path);
             if path.exists() {
                 let tx_clone = tx.clone();
@@ -103,6 +118,62 @@ impl MonitorManager {
         Ok(())
     }
 
+    /// Redraw the short status table in place (`imi status --watch`) on
+    /// file changes in any listed worktree, and at least every `interval`.
+    /// CI results are refetched on the interval only, not per file event.
+    pub async fn watch_status(
+        &self,
+        repo: Option<&str>,
+        ci: bool,
+        interval: Duration,
+    ) -> Result<()> {
+        let manager = &self.worktree_manager;
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut watchers: HashMap<Uuid, (PathBuf, RecommendedWatcher)> = HashMap::new();
+        let mut ticker = time::interval(interval);
+        ticker.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+        let mut ci_statuses = HashMap::new();
+
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {
+                    let worktrees = manager.active_worktrees(repo).await?;
+                    watch_worktrees(&mut watchers, &worktrees, &tx);
+                    if ci {
+                        ci_statuses = manager.ci_statuses(repo).await.unwrap_or_default();
+                    }
+                }
+                Some(id) = rx.recv() => {
+                    // Let a burst (a checkout, a build) settle into one redraw
+                    time::sleep(WATCH_DEBOUNCE).await;
+                    let mut changed = HashSet::from([id]);
+                    while let Ok(id) = rx.try_recv() {
+                        changed.insert(id);
+                    }
+                    // Unstaged edits keep the cache key, so drop the entries
+                    let changed: Vec<Uuid> = changed.into_iter().collect();
+                    let _ = manager.db.invalidate_worktree_statuses(&changed).await;
+                }
+                _ = signal::ctrl_c() => return Ok(()),
+            }
+
+            print!("\x1b[2J\x1b[H");
+            println!(
+                "{} Worktree Status {}",
+                "📊".bright_cyan(),
+                format!(
+                    "(every {}s and on changes, Ctrl+C to stop · {})",
+                    interval.as_secs(),
+                    chrono::Local::now().format("%H:%M:%S")
+                )
+                .bright_black()
+            );
+            manager.show_status_with_ci(repo, &ci_statuses).await?;
+            manager.print_pending_deletes(&manager.pending_deletes(repo).await.unwrap_or_default());
+            manager.print_trunk_violations(&manager.trunk_violations(repo).await);
+        }
+    }
+
     /// Main monitoring loop for file system events
     pub async fn monitor_loop(
         &self,
@@ -110,7 +181,8 @@ impl MonitorManager {
         path_to_worktree: HashMap<PathBuf, Worktree>,
     ) -> Result<()> {
         let mut last_events: HashMap<String, Instant> = HashMap::new();
-        let debounce_duration = Duration::from_secs(1);
+        let debounce_duration =
+            Duration::from_millis(self.config.monitoring_settings.refresh_interval_ms);
 
         while let Some(event) = rx.recv().await {
             if let Some(activity) = self.process_file_event(&event, &path_to_worktree).await {
@@ -129,8 +201,12 @@ impl MonitorManager {
 
                 last_events.insert(key, activity.timestamp);
                 self.display_activity(&activity).await;
+                self.fire_file_hooks(&activity, &path_to_worktree);
 
                 // Log to database
+                if !self.config.monitoring_settings.track_agent_activity {
+                    continue;
+                }
                 if let Err(e) = self.log_activity_to_db(&activity).await {
                     eprintln!("Failed to log activity: {}", e);
                 }
@@ -140,22 +216,212 @@ impl MonitorManager {
         Ok(())
     }
 
-    /// Periodic status updates
+    /// Periodic status updates: each repository's worktrees are scanned
+    /// every `status_interval_secs` (per repository), skipped while the
+    /// system is over `max_load`
     pub async fn periodic_status_update(
         &self,
         _repo: Option<&str>,
         worktrees: Vec<Worktree>,
     ) -> Result<()> {
-        let mut interval = time::interval(Duration::from_secs(30));
-        let mut last_status_check = Instant::now();
+        let settings = &self.config.monitoring_settings;
+        let worktrees: Vec<Worktree> = worktrees
+            .into_iter()
+            .filter(|wt| settings.git_status_for(&wt.repo_name))
+            .collect();
+        let Some(tick) = worktrees
+            .iter()
+            .map(|wt| settings.status_interval_for(&wt.repo_name))
+            .min()
+        else {
+            // File events only
+            return std::future::pending().await;
+        };
+
+        let mut interval = time::interval(Duration::from_secs(tick));
+        interval.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+        // The first tick completes right away; scans start one interval in
+        interval.tick().await;
+        let mut last_scans: HashMap<String, Instant> = HashMap::new();
+        let mut alert_tracker = AlertTracker::new();
+        let mut commit_tracker = CommitTracker::new();
+        let mut status_tracker = StatusTracker::new();
+        let mut trunk_warnings: HashMap<String, TrunkViolation> = HashMap::new();
 
         loop {
             interval.tick().await;
 
-            // Every 30 seconds, show a summary
-            if last_status_check.elapsed() >= Duration::from_secs(30) {
-                let _ = self.display_status_summary(&worktrees).await;
-                last_status_check = Instant::now();
+            if let (Some(max_load), Some(load)) = (settings.max_load, load_per_cpu()) {
+                if load > max_load {
+                    println!(
+                        "{} Skipping status scan: load {:.2} per CPU is over {:.2}",
+                        "⏸️".bright_yellow(),
+                        load,
+                        max_load
+                    );
+                    continue;
+                }
+            }
+
+            // A repository is due when its own interval has (nearly) passed
+            let due: Vec<Worktree> = worktrees
+                .iter()
+                .filter(|wt| {
+                    let every = Duration::from_secs(settings.status_interval_for(&wt.repo_name));
+                    last_scans
+                        .get(&wt.repo_name)
+                        .is_none_or(|last| last.elapsed() + Duration::from_millis(500) >= every)
+                })
+                .cloned()
+                .collect();
+            if due.is_empty() {
+                continue;
+            }
+            for wt in &due {
+                last_scans.insert(wt.repo_name.clone(), Instant::now());
+            }
+
+            let _ = self.display_status_summary(&due).await;
+            self.check_alerts(&due, &mut alert_tracker);
+            let git = &self.worktree_manager.git;
+            for event in commit_tracker.check(git, &due) {
+                self.hooks.fire(event);
+            }
+            for event in status_tracker.check(git, &due) {
+                self.hooks.fire(event);
+            }
+            if settings.sync_pr_worktrees && !self.config.permissions.read_only {
+                self.sync_review_worktrees(&due);
+            }
+            self.check_trunks(&due, &mut trunk_warnings).await;
+        }
+    }
+
+    /// Warn about work found in the trunks of `worktrees`' repositories,
+    /// once per change rather than on every scan
+    async fn check_trunks(
+        &self,
+        worktrees: &[Worktree],
+        warned: &mut HashMap<String, TrunkViolation>,
+    ) {
+        if !self.config.trunk_protection.active() {
+            return;
+        }
+        let repos: HashSet<&str> = worktrees.iter().map(|wt| wt.repo_name.as_str()).collect();
+        for repo in repos {
+            let violation = self
+                .worktree_manager
+                .trunk_violations(Some(repo))
+                .await
+                .into_iter()
+                .next();
+            match violation {
+                Some(violation) if warned.get(repo) != Some(&violation) => {
+                    violation.print();
+                    warned.insert(repo.to_string(), violation);
+                }
+                Some(_) => {}
+                None => {
+                    if warned.remove(repo).is_some() {
+                        println!(
+                            "{} {}'s trunk is clean again",
+                            "✅".bright_green(),
+                            repo.bright_blue()
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// Move review worktrees to their pull request's latest head, firing
+    /// `pr_updated` when it changed. Worktrees with local changes or commits
+    /// are left for `imi pr sync --force`.
+    fn sync_review_worktrees(&self, worktrees: &[Worktree]) {
+        let git = &self.worktree_manager.git;
+        for wt in worktrees.iter().filter(|wt| wt.is_local()) {
+            let Some(number) = wt.review_pr_number() else {
+                continue;
+            };
+            match git.sync_pr_head(Path::new(&wt.path), number, false) {
+                Ok(sync) if sync.updated() => {
+                    let icon = match sync.force_pushed {
+                        true => "⚠️".bright_yellow(),
+                        false => "🔄".bright_cyan(),
+                    };
+                    println!(
+                        "{} {}/{}: {}",
+                        icon,
+                        wt.repo_name.bright_blue(),
+                        wt.name.bright_green(),
+                        sync.describe()
+                    );
+                    self.hooks.fire(HookEvent::for_pr_sync(wt, &sync));
+                    self.worktree_manager.write_review_context(
+                        &wt.repo_name,
+                        number,
+                        Path::new(&wt.path),
+                    );
+                }
+                Ok(_) => {}
+                // Blocked by local work, or offline; retried next scan
+                Err(_) => {}
+            }
+        }
+    }
+
+    /// Fire `file_created` / `file_modified` / `file_deleted` hooks
+    fn fire_file_hooks(
+        &self,
+        activity: &ActivityEvent,
+        path_to_worktree: &HashMap<PathBuf, Worktree>,
+    ) {
+        let name = format!("file_{}", activity.event_type);
+        if !self.hooks.wants(&name) {
+            return;
+        }
+        let Some(worktree) = path_to_worktree
+            .values()
+            .find(|wt| wt.id.to_string() == activity.worktree_id)
+        else {
+            return;
+        };
+
+        let file = activity.file_path.clone().unwrap_or_default();
+        let message = format!("{} {}", activity.event_type, file);
+        self.hooks
+            .fire(HookEvent::for_worktree(&name, worktree, message).var("IMI_FILE", file));
+    }
+
+    /// Print alerts that appeared since the last check and send notifications for them
+    pub fn check_alerts(&self, worktrees: &[Worktree], tracker: &mut AlertTracker) {
+        let settings = &self.config.alert_settings;
+        let evaluator = AlertEvaluator::new(
+            &self.worktree_manager.git,
+            settings,
+            &self.config.git_settings.default_branch,
+        );
+
+        let alerts: Vec<_> = worktrees
+            .iter()
+            .flat_map(|wt| evaluator.evaluate(wt))
+            .collect();
+
+        for alert in tracker.update(&alerts) {
+            println!(
+                "{} {}/{}",
+                "🚨 Alert:".bright_red().bold(),
+                alert.repo_name.bright_blue(),
+                alert.worktree_name.bright_green()
+            );
+            alert.print();
+            alerts::notify(settings, alert);
+
+            if let Some(worktree) = worktrees
+                .iter()
+                .find(|wt| wt.name == alert.worktree_name && wt.repo_name == alert.repo_name)
+            {
+                self.hooks.fire(HookEvent::for_alert(alert, worktree));
             }
         }
     }
@@ -411,3 +677,50 @@ impl MonitorManager {
         Ok(())
     }
 }
+
+/// The 1-minute load average divided by the CPU count; None where
+/// /proc/loadavg doesn't exist
+/// Watch worktrees that appeared since the last call and drop the watchers
+/// of removed ones; each event sends its worktree's id
+fn watch_worktrees(
+    watchers: &mut HashMap<Uuid, (PathBuf, RecommendedWatcher)>,
+    worktrees: &[Worktree],
+    tx: &mpsc::UnboundedSender<Uuid>,
+) {
+    let current: HashMap<Uuid, PathBuf> = worktrees
+        .iter()
+        .map(|wt| (wt.id, PathBuf::from(&wt.path)))
+        .filter(|(_, path)| path.exists())
+        .collect();
+    watchers.retain(|id, (path, _)| current.get(id) == Some(path));
+
+    for (id, path) in current {
+        if watchers.contains_key(&id) {
+            continue;
+        }
+        let tx = tx.clone();
+        let watcher = RecommendedWatcher::new(
+            move |res: notify::Result<Event>| {
+                if res.is_ok_and(|event| !matches!(event.kind, notify::EventKind::Access(_))) {
+                    let _ = tx.send(id);
+                }
+            },
+            Config::default(),
+        )
+        .and_then(|mut watcher| {
+            watcher.watch(&path, RecursiveMode::Recursive)?;
+            Ok(watcher)
+        });
+        // Unwatched worktrees still refresh on the interval
+        if let Ok(watcher) = watcher {
+            watchers.insert(id, (path, watcher));
+        }
+    }
+}
+
+fn load_per_cpu() -> Option<f64> {
+    let loadavg = std::fs::read_to_string("/proc/loadavg").ok()?;
+    let load: f64 = loadavg.split_whitespace().next()?.parse().ok()?;
+    let cpus = std::thread::available_parallelism().map_or(1, |n| n.get());
+    Some(load / cpus as f64)
+}
@@ -0,0 +1,1828 @@
+//! Typed library API for embedding iMi (e.g. in an MCP server).
+//!
+//! Every operation takes an [`Imi`] handle plus a typed options struct and
+//! returns a structured outcome. Nothing here prints to stdout; presentation
+//! is left to the caller, which is exactly how the CLI uses these functions.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use crate::alerts::{Alert, AlertEvaluator};
+use crate::artifacts::{self, ArtifactKind, ArtifactStore, CapturedRun, Stream};
+use crate::cache_clone::{CacheFallback, ClonedCache};
+use crate::config::{Config, WorktreeSort};
+use crate::context::GitContext;
+use crate::database::{
+    Database, NavigationStat, Repository, RepositoryEdit, Worktree, WorktreeArtifact,
+    WorktreeMessage, WorktreeNote, WorktreeType,
+};
+use crate::deletion_safety;
+use crate::fuzzy::SearchTarget;
+use crate::git::{BranchOrigin, GitManager, PatchApply};
+use crate::github::CiStatus;
+use crate::i18n::tr;
+use crate::rebase_batch::RebaseBatch;
+use crate::repo_stats::{GitActivity, GitStatsCache};
+use crate::type_prefixes::TypePrefixes;
+use crate::type_sharing::{plan_import, ImportAction, ImportMode, TypesFile};
+use crate::worktree::{
+    BisectOutcome, Relocation, RepoHealth, RepositoryDetail, RepositoryEditOutcome, TempRun,
+    WorktreeDetail, WorktreeManager,
+};
+
+/// Entry point for library consumers: configuration, database and git access
+pub struct Imi {
+    manager: WorktreeManager,
+}
+
+impl Imi {
+    /// Load the user's configuration and connect to the registry database;
+    /// progress output is suppressed
+    #[allow(dead_code)] // Library entry point; the CLI builds its own manager
+    pub async fn open() -> Result<Self> {
+        let config = Config::load()
+            .await
+            .context("Failed to load configuration")?;
+        let db = Database::new(&config.database_path)
+            .await
+            .context("Failed to initialize database")?;
+        let git = GitManager::from_config(&config);
+        let repo_path = config.repo_path.clone();
+
+        Ok(Self::new(
+            WorktreeManager::new(git, db, config, repo_path).quiet(true),
+        ))
+    }
+
+    /// Wrap an existing manager, keeping its quiet setting
+    pub fn new(manager: WorktreeManager) -> Self {
+        Self { manager }
+    }
+
+    pub fn manager(&self) -> &WorktreeManager {
+        &self.manager
+    }
+
+    pub fn database(&self) -> &Database {
+        &self.manager.db
+    }
+
+    #[allow(dead_code)] // Library accessor
+    pub fn config(&self) -> &Config {
+        &self.manager.config
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CreateOptions {
+    /// Worktree type name (feat, fix, aiops, devops, review, or a custom type)
+    pub worktree_type: String,
+    /// Worktree name without the type prefix; the PR number for `review`
+    pub name: String,
+    pub repo: Option<String>,
+    /// PR number for `review` when `name` is not the number itself
+    pub pr: Option<u32>,
+    /// Branch, tag or commit to start the branch from instead of the type's
+    /// default base branch
+    #[serde(default)]
+    pub base: Option<String>,
+    /// Skip running hook managers' install steps and `[git_hooks]`
+    #[serde(default)]
+    pub no_hooks: bool,
+    /// Push the new branch and track it, whatever `[upstream]` says
+    #[serde(default)]
+    pub publish: bool,
+    /// Check out the branch when it already exists instead of refusing
+    #[serde(default)]
+    pub adopt_existing: bool,
+    /// Patch or diff to apply to the new worktree
+    #[serde(default)]
+    pub patch: Option<Vec<u8>>,
+    /// Commit an applied diff with this message
+    #[serde(default)]
+    pub commit_message: Option<String>,
+    /// Open the new worktree in the configured editor
+    #[serde(default)]
+    pub open: bool,
+    /// Agent to claim the new worktree for; without one the
+    /// `[agent_assignment]` rules may pick it
+    #[serde(default)]
+    pub claim: Option<String>,
+    /// Check out only the directories a review worktree's PR changes
+    #[serde(default)]
+    pub sparse_changed: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateOutcome {
+    pub repo_name: String,
+    pub worktree_name: String,
+    pub worktree_type: String,
+    pub branch_name: Option<String>,
+    pub path: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pr_number: Option<u32>,
+    /// Whether the branch was created or an existing one checked out
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch_origin: Option<BranchOrigin>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub patch: Option<PatchApply>,
+    /// With `publish`, whether the branch ended up with an upstream
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published: Option<bool>,
+    /// Editor workspace opened with `open`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<PathBuf>,
+    /// Agent the worktree was claimed for with `claim`, or by a rule
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub claimed_by: Option<String>,
+    /// Number of the `[agent_assignment]` rule that picked `claimed_by`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assigned_by_rule: Option<usize>,
+    /// Follow-up steps that failed; the worktree itself was created
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+/// Add types the repository's shared types file defines but this machine
+/// lacks; types that already exist are left alone
+async fn auto_import_types(imi: &Imi, repo: Option<&str>) -> Result<()> {
+    if let Some(path) = shared_types_file(imi, repo).await? {
+        import_worktree_types(imi, &path, ImportMode::Skip, false).await?;
+    }
+    Ok(())
+}
+
+/// Where an inferred worktree type came from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "source", rename_all = "snake_case")]
+pub enum TypeSource {
+    /// The name starts with a configured ticket prefix
+    Prefix {
+        prefix: String,
+    },
+    /// The command ran from a worktree of this type
+    Context {
+        worktree: String,
+    },
+    Default,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InferredType {
+    pub worktree_type: String,
+    #[serde(flatten)]
+    pub source: TypeSource,
+}
+
+/// Pick a type for `imi add <name>` per `[type_inference]`, or fail when no
+/// rule applies and no default is configured
+pub async fn infer_worktree_type(
+    imi: &Imi,
+    name: &str,
+    repo: Option<&str>,
+) -> Result<InferredType> {
+    let manager = &imi.manager;
+    let repo_name = manager.resolve_repo_name(repo).await.ok();
+    let rules = manager.config.type_inference.for_repo(repo_name.as_deref());
+
+    if let Some((prefix, wt_type)) = rules.prefix_type(name) {
+        return Ok(InferredType {
+            worktree_type: wt_type.to_string(),
+            source: TypeSource::Prefix {
+                prefix: prefix.to_string(),
+            },
+        });
+    }
+
+    if rules.from_context {
+        if let Some(inferred) = context_worktree_type(manager).await? {
+            return Ok(inferred);
+        }
+    }
+
+    match rules.default_type {
+        Some(wt_type) => Ok(InferredType {
+            worktree_type: wt_type,
+            source: TypeSource::Default,
+        }),
+        None => Err(anyhow::anyhow!(
+            "Could not infer a worktree type for '{}'. Pass one: imi add <type> {} (or --type <type>)",
+            name,
+            name
+        )),
+    }
+}
+
+/// Type of the worktree the current directory is in, matched by its
+/// worktree prefix. Review worktrees are skipped since they need a PR number.
+async fn context_worktree_type(manager: &WorktreeManager) -> Result<Option<InferredType>> {
+    let GitContext::InWorktree { worktree_path, .. } = manager.git.detect_context(None) else {
+        return Ok(None);
+    };
+    let Some(worktree) = worktree_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+    else {
+        return Ok(None);
+    };
+
+    let types = manager.db.list_worktree_types().await?;
+    Ok(types
+        .iter()
+        .filter(|t| t.name != "review" && !t.worktree_prefix.is_empty())
+        .filter(|t| worktree.starts_with(&t.worktree_prefix))
+        .max_by_key(|t| t.worktree_prefix.len())
+        .map(|t| InferredType {
+            worktree_type: t.name.clone(),
+            source: TypeSource::Context { worktree },
+        }))
+}
+
+/// Create a worktree of any registered type
+pub async fn create_worktree(imi: &Imi, options: CreateOptions) -> Result<CreateOutcome> {
+    let manager = &imi
+        .manager
+        .clone()
+        .skip_git_hooks(options.no_hooks)
+        .publish(options.publish)
+        .adopt_existing(options.adopt_existing)
+        .sparse_changed(options.sparse_changed);
+    if manager.config.type_sharing.auto_import {
+        if let Err(e) = auto_import_types(imi, options.repo.as_deref()).await {
+            eprintln!(
+                "{}",
+                tr!(
+                    "⚠️ Failed to import shared worktree types: {}",
+                    format!("{:#}", e)
+                )
+            );
+        }
+    }
+    let wt_type = manager
+        .db
+        .get_worktree_type(&options.worktree_type)
+        .await
+        .context(tr!(
+            "Unknown worktree type '{}'. Run 'imi types' to see available types.",
+            options.worktree_type
+        ))?;
+
+    let repo = options.repo.as_deref();
+    let name = options.name.as_str();
+    let mut pr_number = None;
+
+    let path = match (wt_type.name.as_str(), options.base.as_deref()) {
+        ("review", Some(_)) => {
+            return Err(anyhow::anyhow!(
+                "--base does not apply to review worktrees; they check out the PR's branch"
+            ));
+        }
+        (custom, _) if options.sparse_changed && custom != "review" => {
+            return Err(anyhow::anyhow!(
+                "--sparse-changed only applies to review worktrees"
+            ));
+        }
+        (custom, Some(base)) => {
+            manager
+                .create_typed_worktree(name, custom, repo, Some(base))
+                .await?
+        }
+        ("review", None) => {
+            let number = match options.pr {
+                Some(number) => number,
+                None => name.parse::<u32>().context(
+                    "PR number required for review worktree. Use: imi add review <pr-number> or --pr <number>",
+                )?,
+            };
+            pr_number = Some(number);
+            manager.create_review_worktree(number, repo).await?
+        }
+        ("feat", None) => manager.create_feature_worktree(name, repo).await?,
+        ("fix", None) => manager.create_fix_worktree(name, repo).await?,
+        ("aiops", None) => manager.create_aiops_worktree(name, repo).await?,
+        ("devops", None) => manager.create_devops_worktree(name, repo).await?,
+        (custom, None) => manager.create_custom_worktree(name, custom, repo).await?,
+    };
+
+    let patch = match &options.patch {
+        Some(patch) => Some(
+            manager
+                .git
+                .apply_patch(&path, patch, options.commit_message.as_deref())
+                .with_context(|| tr!("Created {} but the patch didn't apply", path.display()))?,
+        ),
+        None => None,
+    };
+
+    let repo_name = manager.resolve_repo_name(repo).await?;
+    let worktree_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| name.to_string());
+    let created = manager.db.get_worktree(&repo_name, &worktree_name).await?;
+    let branch_origin = created
+        .as_ref()
+        .and_then(|wt| wt.metadata.get("branch_origin"))
+        .and_then(|origin| serde_json::from_value(origin.clone()).ok());
+    let published = options.publish.then(|| {
+        manager
+            .git
+            .get_upstream_divergence(&path)
+            .ok()
+            .flatten()
+            .is_some()
+    });
+
+    // An explicit claim wins over [agent_assignment] rules
+    let (claim, assigned_by_rule) = match &options.claim {
+        Some(agent_id) => (Some(agent_id.clone()), None),
+        None => match manager
+            .config
+            .agent_assignment
+            .find(Some(&repo_name), &wt_type.name, name)
+        {
+            Some((number, rule)) => (Some(rule.agent.clone()), Some(number)),
+            None => (None, None),
+        },
+    };
+    let mut warnings = Vec::new();
+    let claimed_by = match (&claim, &created) {
+        (Some(agent_id), Some(worktree)) => {
+            match manager
+                .claim_worktree(worktree, &worktree_name, agent_id)
+                .await
+            {
+                Ok(()) => Some(agent_id.clone()),
+                Err(e) => {
+                    warnings.push(format!("Failed to claim for {}: {:#}", agent_id, e));
+                    None
+                }
+            }
+        }
+        _ => None,
+    };
+    let workspace = match options.open {
+        true => match manager
+            .open_in_editor(Some(&worktree_name), Some(&repo_name))
+            .await
+        {
+            Ok(workspace) => Some(workspace),
+            Err(e) => {
+                warnings.push(format!("Failed to open the editor: {:#}", e));
+                None
+            }
+        },
+        false => None,
+    };
+    let branch_name = created.map(|wt| wt.branch_name);
+
+    Ok(CreateOutcome {
+        repo_name,
+        worktree_name,
+        worktree_type: wt_type.name,
+        branch_name,
+        path,
+        pr_number,
+        branch_origin,
+        patch,
+        published,
+        workspace,
+        assigned_by_rule: assigned_by_rule.filter(|_| claimed_by.is_some()),
+        claimed_by,
+        warnings,
+    })
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ForkOptions {
+    /// Worktree to branch off from
+    pub source: String,
+    /// New worktree name without the type prefix
+    pub name: String,
+    pub repo: Option<String>,
+    /// Carry over the source's uncommitted changes to tracked files
+    pub include_uncommitted: bool,
+    /// Clone the source's build caches (`[fork_caches]`): Some(true) for
+    /// --caches, Some(false) for --no-caches, None to follow the config
+    pub caches: Option<bool>,
+    /// Overrides `[fork_caches] fallback`
+    pub cache_fallback: Option<CacheFallback>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForkOutcome {
+    pub worktree: CreateOutcome,
+    pub parent_worktree: String,
+    /// Build caches cloned from the source, or skipped
+    pub caches: Vec<ClonedCache>,
+}
+
+/// Create a worktree on a new branch from another worktree's current HEAD
+pub async fn fork_worktree(imi: &Imi, options: ForkOptions) -> Result<ForkOutcome> {
+    let manager = &imi.manager;
+    let settings = &manager.config.fork_caches;
+    let cache_fallback = options
+        .caches
+        .unwrap_or(settings.enabled || options.cache_fallback.is_some())
+        .then(|| options.cache_fallback.unwrap_or(settings.fallback));
+    let (path, caches) = manager
+        .fork_worktree(
+            &options.source,
+            &options.name,
+            options.repo.as_deref(),
+            options.include_uncommitted,
+            cache_fallback,
+        )
+        .await?;
+
+    let repo_name = manager.resolve_repo_name(options.repo.as_deref()).await?;
+    let worktree_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| options.name.clone());
+    let created = manager.db.get_worktree(&repo_name, &worktree_name).await?;
+    let worktree_type = match &created {
+        Some(wt) => manager
+            .db
+            .list_worktree_types()
+            .await?
+            .into_iter()
+            .find(|t| t.id == wt.type_id)
+            .map(|t| t.name)
+            .unwrap_or_default(),
+        None => String::new(),
+    };
+
+    Ok(ForkOutcome {
+        worktree: CreateOutcome {
+            repo_name,
+            worktree_name,
+            worktree_type,
+            branch_name: created.map(|wt| wt.branch_name),
+            path,
+            pr_number: None,
+            branch_origin: None,
+            patch: None,
+            published: None,
+            workspace: None,
+            claimed_by: None,
+            assigned_by_rule: None,
+            warnings: Vec::new(),
+        },
+        parent_worktree: options.source,
+        caches,
+    })
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RemoveOptions {
+    pub name: String,
+    pub repo: Option<String>,
+    pub keep_branch: bool,
+    pub keep_remote: bool,
+    /// `name` is the full worktree name; don't try type prefixes
+    #[serde(default)]
+    pub exact: bool,
+}
+
+/// Remove a worktree, its directory and (unless kept) its branches
+pub async fn remove_worktree(imi: &Imi, options: RemoveOptions) -> Result<()> {
+    imi.manager
+        .clone()
+        .exact_names(options.exact)
+        .remove_worktree(
+            &options.name,
+            options.repo.as_deref(),
+            options.keep_branch,
+            options.keep_remote,
+        )
+        .await
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BisectOptions {
+    pub repo: Option<String>,
+    /// Commit where `command` fails
+    pub bad: String,
+    /// Commits where `command` passes
+    pub good: Vec<String>,
+    pub command: Vec<String>,
+    /// Leave the temporary worktree in place
+    #[serde(default)]
+    pub keep: bool,
+}
+
+/// Find the first bad commit with `git bisect run` in a temporary worktree
+pub async fn bisect(imi: &Imi, options: BisectOptions) -> Result<BisectOutcome> {
+    imi.manager
+        .bisect(
+            options.repo.as_deref(),
+            &options.bad,
+            &options.good,
+            &options.command,
+            options.keep,
+        )
+        .await
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TempOptions {
+    pub repo: Option<String>,
+    /// Commit to check out; trunk's default branch when not given
+    pub base: Option<String>,
+    pub command: Vec<String>,
+}
+
+/// Run a command in a throwaway worktree that is removed afterwards
+pub async fn run_temp(imi: &Imi, options: TempOptions) -> Result<TempRun> {
+    imi.manager
+        .run_in_temp_worktree(
+            options.repo.as_deref(),
+            options.base.as_deref(),
+            &options.command,
+        )
+        .await
+}
+
+/// Selects worktrees for a bulk close; every given criterion must match
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BulkCloseOptions {
+    pub repo: Option<String>,
+    /// Branch is merged into trunk
+    pub merged: bool,
+    pub worktree_type: Option<String>,
+    /// Glob over worktree names, e.g. `feat-*-spike`
+    pub pattern: Option<String>,
+    /// The recorded GitHub pull request is merged (`imi clean`)
+    pub pr_merged: bool,
+    /// Older than its type's `default_ttl_days`
+    #[serde(default)]
+    pub expired: bool,
+    /// Also close worktrees holding unpushed commits, stashes or important
+    /// untracked files
+    #[serde(default)]
+    pub include_risky: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CloseTarget {
+    pub repo_name: String,
+    pub worktree_name: String,
+    pub worktree_type: String,
+    pub branch_name: String,
+    pub path: PathBuf,
+    /// Why the worktree is selected but left alone (e.g. uncommitted changes)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skip_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CloseStatus {
+    Closed,
+    Skipped,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CloseResult {
+    #[serde(flatten)]
+    pub target: CloseTarget,
+    pub status: CloseStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// Active worktrees matching the bulk selectors, sorted by repository and name
+pub async fn select_close_targets(
+    imi: &Imi,
+    options: &BulkCloseOptions,
+) -> Result<Vec<CloseTarget>> {
+    let manager = &imi.manager;
+    let repositories = manager.db.list_repositories().await?;
+    let types = manager.db.list_worktree_types().await?;
+    let trunk_branch = &manager.config.git_settings.default_branch;
+    let pattern = options.pattern.as_deref().map(glob_to_regex).transpose()?;
+    let repo_name = match options.repo.as_deref() {
+        Some(repo) => Some(manager.resolve_repo_name(Some(repo)).await?),
+        None => None,
+    };
+
+    let mut targets = Vec::new();
+    for wt in manager.db.list_worktrees(repo_name.as_deref()).await? {
+        let Some(repository) = repositories.iter().find(|r| r.id == wt.project_id) else {
+            continue;
+        };
+        let wt_type = types.iter().find(|t| t.id == wt.type_id);
+        let worktree_type = wt_type.map(|t| t.name.clone()).unwrap_or_default();
+
+        // Trunk is never a close candidate
+        if worktree_type == "trunk" || wt.name.starts_with("trunk-") {
+            continue;
+        }
+        if options
+            .worktree_type
+            .as_ref()
+            .is_some_and(|wanted| *wanted != worktree_type)
+        {
+            continue;
+        }
+        if pattern.as_ref().is_some_and(|re| !re.is_match(&wt.name)) {
+            continue;
+        }
+        if options.pr_merged && wt.pr_state.as_deref() != Some("merged") {
+            continue;
+        }
+        if options.expired {
+            let ttl_days = wt_type.and_then(|t| t.default_ttl_days);
+            let expired = ttl_days.is_some_and(|days| {
+                Utc::now() - wt.created_at > chrono::Duration::days(days.into())
+            });
+            if !expired {
+                continue;
+            }
+        }
+
+        let path = PathBuf::from(&wt.path);
+        if options.merged {
+            let merged = wt.merged_at.is_some()
+                || (path.exists()
+                    && manager
+                        .git
+                        .is_branch_merged(&path, &wt.branch_name, trunk_branch)
+                        .unwrap_or(false));
+            if !merged {
+                continue;
+            }
+        }
+
+        // Closing deletes the directory, so never take uncommitted work with it
+        let skip_reason = match path.exists() {
+            true => match manager.git.get_worktree_status(&path) {
+                Ok(status) if status.clean => {
+                    let check = deletion_safety::inspect(&path);
+                    (!check.is_safe() && !options.include_risky)
+                        .then(|| format!("risky: {}", check.summary()))
+                }
+                Ok(_) => Some("uncommitted changes".to_string()),
+                Err(e) => Some(format!("unable to read status: {}", e)),
+            },
+            false => None,
+        };
+
+        targets.push(CloseTarget {
+            repo_name: repository.name.clone(),
+            worktree_name: wt.name,
+            worktree_type,
+            branch_name: wt.branch_name,
+            path,
+            skip_reason,
+        });
+    }
+
+    targets.sort_by(|a, b| (&a.repo_name, &a.worktree_name).cmp(&(&b.repo_name, &b.worktree_name)));
+    Ok(targets)
+}
+
+/// Close the targets in parallel, keeping their branches; one result per target
+pub async fn close_worktrees(imi: &Imi, targets: Vec<CloseTarget>) -> Vec<CloseResult> {
+    let mut tasks = tokio::task::JoinSet::new();
+    let mut results = Vec::new();
+
+    for (index, target) in targets.into_iter().enumerate() {
+        if let Some(reason) = target.skip_reason.clone() {
+            results.push((
+                index,
+                CloseResult {
+                    target,
+                    status: CloseStatus::Skipped,
+                    message: Some(reason),
+                },
+            ));
+            continue;
+        }
+
+        let manager = imi.manager.clone();
+        tasks.spawn(async move {
+            let outcome = manager
+                .close_worktree(&target.worktree_name, Some(&target.repo_name))
+                .await;
+            let (status, message) = match outcome {
+                Ok(()) => (CloseStatus::Closed, None),
+                Err(e) => (CloseStatus::Failed, Some(format!("{:#}", e))),
+            };
+            (
+                index,
+                CloseResult {
+                    target,
+                    status,
+                    message,
+                },
+            )
+        });
+    }
+
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok(result) => results.push(result),
+            Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+            Err(_) => {}
+        }
+    }
+
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Translate a shell-style glob (`*`, `?`) into an anchored regex
+pub(crate) fn glob_to_regex(pattern: &str) -> Result<regex::Regex> {
+    let mut source = String::from("^");
+    for c in pattern.chars() {
+        match c {
+            '*' => source.push_str(".*"),
+            '?' => source.push('.'),
+            c => source.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    source.push('$');
+    regex::Regex::new(&source).context(tr!("Invalid pattern '{}'", pattern))
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ListOptions {
+    pub repo: Option<String>,
+    pub include_inactive: bool,
+    /// Only worktrees of repositories carrying this tag
+    #[serde(default)]
+    pub tag: Option<String>,
+    /// Order of active worktrees, else the manager's or `[list_settings] sort`
+    #[serde(default)]
+    pub sort: Option<WorktreeSort>,
+}
+
+pub async fn list_worktrees(imi: &Imi, options: ListOptions) -> Result<Vec<Worktree>> {
+    let db = imi.database();
+    let mut worktrees = if options.include_inactive {
+        let worktrees = db.list_all_worktrees(options.repo.as_deref()).await?;
+        imi.manager()
+            .without_archived(worktrees, options.repo.as_deref())
+            .await
+    } else {
+        let manager = match options.sort {
+            Some(sort) => imi.manager().clone().sort_worktrees(Some(sort)),
+            None => imi.manager().clone(),
+        };
+        manager.active_worktrees(options.repo.as_deref()).await?
+    };
+
+    if let Some(tag) = &options.tag {
+        let tagged: Vec<_> = list_repositories_with_tag(imi, tag)
+            .await?
+            .into_iter()
+            .map(|repo| repo.id)
+            .collect();
+        worktrees.retain(|wt| tagged.contains(&wt.project_id));
+    }
+    Ok(worktrees)
+}
+
+/// Registered repositories; archived ones only when the manager was built
+/// with `include_archived`
+pub async fn list_repositories(imi: &Imi) -> Result<Vec<Repository>> {
+    imi.manager().listed_repositories(None).await
+}
+
+pub async fn list_repositories_with_tag(imi: &Imi, tag: &str) -> Result<Vec<Repository>> {
+    imi.manager()
+        .listed_repositories(Some(&normalize_tag(tag)?))
+        .await
+}
+
+/// Tags are case-insensitive single words, e.g. `client-x`
+pub fn normalize_tag(tag: &str) -> Result<String> {
+    let tag = tag.trim().to_lowercase();
+    if tag.is_empty() || tag.contains(|c: char| c.is_whitespace() || c == ',') {
+        anyhow::bail!("Invalid tag '{}': use a single word like 'client-x'", tag);
+    }
+    Ok(tag)
+}
+
+/// Add tags to a registered repository; returns all of its tags
+pub async fn tag_repository(imi: &Imi, repo: &str, tags: &[String]) -> Result<Vec<String>> {
+    let repository = registered_repository(imi, repo).await?;
+    let tags = tags
+        .iter()
+        .map(|tag| normalize_tag(tag))
+        .collect::<Result<Vec<_>>>()?;
+    imi.database()
+        .add_repository_tags(&repository.id, &tags)
+        .await?;
+    tags_of(imi, &repository).await
+}
+
+/// Remove tags from a registered repository; returns the tags it keeps
+pub async fn untag_repository(imi: &Imi, repo: &str, tags: &[String]) -> Result<Vec<String>> {
+    let repository = registered_repository(imi, repo).await?;
+    let tags = tags
+        .iter()
+        .map(|tag| normalize_tag(tag))
+        .collect::<Result<Vec<_>>>()?;
+    imi.database()
+        .remove_repository_tags(&repository.id, &tags)
+        .await?;
+    tags_of(imi, &repository).await
+}
+
+/// Tags of every registered repository that has any, by repository name
+pub async fn repository_tags(imi: &Imi) -> Result<BTreeMap<String, Vec<String>>> {
+    let repositories = imi.database().list_repositories().await?;
+    let mut tags: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (project_id, tag) in imi.database().list_repository_tags().await? {
+        if let Some(repository) = repositories.iter().find(|r| r.id == project_id) {
+            tags.entry(repository.name.clone()).or_default().push(tag);
+        }
+    }
+    Ok(tags)
+}
+
+async fn tags_of(imi: &Imi, repository: &Repository) -> Result<Vec<String>> {
+    Ok(repository_tags(imi)
+        .await?
+        .remove(&repository.name)
+        .unwrap_or_default())
+}
+
+/// Archive a repository (or unarchive it with `archived` false): it stays
+/// registered but is left out of list, status, go and exec without `--all`.
+/// Returns false when it already was in that state.
+pub async fn archive_repository(imi: &Imi, repo: &str, archived: bool) -> Result<bool> {
+    let repository = registered_repository(imi, repo).await?;
+    imi.database()
+        .set_repository_archived(&repository.id, archived)
+        .await
+}
+
+/// Move existing worktrees into the `[worktree_layout]` directory (or back
+/// beside trunk); with `dry_run` only lists what would move
+pub async fn relayout_worktrees(
+    imi: &Imi,
+    repo: Option<&str>,
+    dry_run: bool,
+) -> Result<Vec<Relocation>> {
+    imi.manager.relayout_worktrees(repo, dry_run).await
+}
+
+/// Rebase the repository's worktrees onto its updated trunk; conflicted
+/// ones are left for the user and the batch saved for
+/// [`continue_rebase_worktrees`]
+pub async fn rebase_worktrees(imi: &Imi, repo: Option<&str>) -> Result<RebaseBatch> {
+    imi.manager.rebase_worktrees(repo).await
+}
+
+/// Resume an unfinished rebase batch where it stopped
+pub async fn continue_rebase_worktrees(imi: &Imi, repo: Option<&str>) -> Result<RebaseBatch> {
+    imi.manager.continue_rebase_worktrees(repo).await
+}
+
+/// Abort the stopped rebases of an unfinished batch and forget it
+pub async fn abort_rebase_worktrees(imi: &Imi, repo: Option<&str>) -> Result<RebaseBatch> {
+    imi.manager.abort_rebase_worktrees(repo).await
+}
+
+/// A repository's registration, tags, clone mode and mismatches with the disk
+pub async fn show_repository(imi: &Imi, repo: &str) -> Result<RepositoryDetail> {
+    imi.manager().repository_detail(repo).await
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RepoStatsOptions {
+    pub repo: String,
+    /// Include trunk activity (slow; cached)
+    pub git: bool,
+    /// Weeks of history, the current one included
+    pub weeks: u32,
+    /// Authors and directories listed
+    pub top: usize,
+    /// Recompute instead of using cached activity
+    #[serde(default)]
+    pub refresh: bool,
+}
+
+/// Result of `imi repo stats`
+#[derive(Debug, Clone, Serialize)]
+pub struct RepositoryStats {
+    pub repo: String,
+    /// Active worktrees
+    pub worktrees: usize,
+    /// Worktrees with uncommitted changes or unpushed commits
+    pub in_flight: usize,
+    /// Worktrees with an open pull request
+    pub open_prs: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub git: Option<GitActivity>,
+}
+
+/// Worktree counts for a repository and, with `git`, its trunk's activity
+pub async fn repository_stats(imi: &Imi, options: RepoStatsOptions) -> Result<RepositoryStats> {
+    let repository = registered_repository(imi, &options.repo).await?;
+    let worktrees = imi
+        .database()
+        .list_worktrees(Some(&repository.name))
+        .await?;
+    let in_flight = worktrees
+        .iter()
+        .filter(|wt| wt.has_uncommitted_changes == Some(true) || wt.ahead_of_trunk.unwrap_or(0) > 0)
+        .count();
+    let open_prs = worktrees
+        .iter()
+        .filter(|wt| wt.pr_number.is_some() && wt.merged_at.is_none())
+        .count();
+
+    let git = if options.git {
+        let mut cache = GitStatsCache::load();
+        let mut activity = GitActivity::collect(
+            &imi.manager().git,
+            &mut cache,
+            Path::new(&repository.path),
+            &repository.default_branch,
+            options.weeks,
+            options.refresh,
+        )?;
+        let _ = cache.save();
+        activity.truncate(options.top);
+        Some(activity)
+    } else {
+        None
+    };
+
+    Ok(RepositoryStats {
+        repo: repository.name,
+        worktrees: worktrees.len(),
+        in_flight,
+        open_prs,
+        git,
+    })
+}
+
+/// Fix a repository's recorded remote URL, default branch or path
+pub async fn edit_repository(
+    imi: &Imi,
+    repo: &str,
+    edit: RepositoryEdit,
+) -> Result<RepositoryEditOutcome> {
+    imi.manager().edit_repository(repo, edit).await
+}
+
+/// A worktree git knows about and the registry doesn't, with the type and
+/// name `imi import-worktrees` would register it under
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnmanagedWorktree {
+    pub path: PathBuf,
+    pub branch: String,
+    /// None when no prefix matches and no `unmatched_type` is set
+    pub worktree_type: Option<String>,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorktreeImportPlan {
+    pub repo_name: String,
+    pub worktrees: Vec<UnmanagedWorktree>,
+    /// Worktrees on a detached HEAD, which can't be registered
+    pub detached: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedImport {
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WorktreeImportOutcome {
+    pub repo_name: String,
+    pub imported: Vec<Worktree>,
+    pub skipped: Vec<SkippedImport>,
+}
+
+/// Worktrees of the repository made with plain `git worktree add`, with an
+/// inferred type and name for each
+pub async fn unmanaged_worktrees(imi: &Imi, repo: Option<&str>) -> Result<WorktreeImportPlan> {
+    let manager = imi.manager();
+    let repo_name = manager.resolve_repo_name(repo).await?;
+    let trunk = manager.get_trunk_worktree(Some(&repo_name)).await?;
+    let same_path = |a: &Path, b: &Path| {
+        a == b || matches!((a.canonicalize(), b.canonicalize()), (Ok(a), Ok(b)) if a == b)
+    };
+    let registered: Vec<PathBuf> = imi
+        .database()
+        .list_worktrees(Some(&repo_name))
+        .await?
+        .into_iter()
+        .map(|wt| PathBuf::from(wt.path))
+        .collect();
+    let prefixes =
+        TypePrefixes::load(imi.database(), &manager.config.type_inference, &repo_name).await;
+
+    let mut plan = WorktreeImportPlan {
+        repo_name,
+        worktrees: Vec::new(),
+        detached: Vec::new(),
+    };
+    for git_worktree in manager.git.list_git_worktrees(&trunk)? {
+        let path = PathBuf::from(&git_worktree.path);
+        // Bare entries have no checkout and prunable ones are already gone
+        if git_worktree.bare
+            || git_worktree.is_prunable()
+            || same_path(&path, &trunk)
+            || registered.iter().any(|r| same_path(r, &path))
+        {
+            continue;
+        }
+        let Some(branch) = git_worktree.branch.as_deref() else {
+            plan.detached.push(path);
+            continue;
+        };
+        let dir_name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(branch)
+            .to_string();
+        let worktree_type = prefixes.infer(&dir_name, branch).map(String::from);
+        let name = match &worktree_type {
+            Some(wt_type) => prefixes.name_for(wt_type, &dir_name, branch),
+            None => dir_name,
+        };
+        plan.worktrees.push(UnmanagedWorktree {
+            path,
+            branch: branch.to_string(),
+            worktree_type,
+            name,
+        });
+    }
+    Ok(plan)
+}
+
+/// Register worktrees under their (confirmed or edited) type and name,
+/// leaving the directories alone. Ones without a type, with an unknown type
+/// or with a name already in use are skipped.
+pub async fn import_worktrees(
+    imi: &Imi,
+    repo_name: &str,
+    worktrees: &[UnmanagedWorktree],
+) -> Result<WorktreeImportOutcome> {
+    let db = imi.database();
+    let types: Vec<String> = db
+        .list_worktree_types()
+        .await?
+        .into_iter()
+        .map(|wt_type| wt_type.name)
+        .collect();
+
+    let mut outcome = WorktreeImportOutcome {
+        repo_name: repo_name.to_string(),
+        imported: Vec::new(),
+        skipped: Vec::new(),
+    };
+    for worktree in worktrees {
+        let mut skip = |reason: String| {
+            outcome.skipped.push(SkippedImport {
+                path: worktree.path.clone(),
+                reason,
+            })
+        };
+        let Some(wt_type) = &worktree.worktree_type else {
+            skip("no worktree type".to_string());
+            continue;
+        };
+        if !types.contains(wt_type) {
+            skip(format!("unknown worktree type '{}'", wt_type));
+            continue;
+        }
+        if worktree.name.trim().is_empty() {
+            skip("empty name".to_string());
+            continue;
+        }
+        if db.get_worktree(repo_name, &worktree.name).await?.is_some() {
+            skip(format!(
+                "a worktree named '{}' already exists",
+                worktree.name
+            ));
+            continue;
+        }
+
+        let registered = db
+            .create_worktree(
+                repo_name,
+                &worktree.name,
+                &worktree.branch,
+                wt_type,
+                &worktree.path.to_string_lossy(),
+                None,
+            )
+            .await?;
+        outcome.imported.push(registered);
+    }
+    Ok(outcome)
+}
+
+/// A remote branch `imi branches prune-remote` deletes
+#[derive(Debug, Clone, Serialize)]
+pub struct RemoteBranch {
+    pub name: String,
+    pub worktree_type: String,
+    /// Tip reachable from `origin/<default branch>`
+    pub merged: bool,
+}
+
+/// What `imi branches prune-remote` found on the push remote
+#[derive(Debug, Clone, Serialize)]
+pub struct RemoteBranchPrunePlan {
+    pub repo_name: String,
+    #[serde(skip)]
+    trunk_path: PathBuf,
+    /// Remote the branches are on: the push remote
+    pub remote: String,
+    pub default_branch: String,
+    pub branches: Vec<RemoteBranch>,
+    /// Managed branches a registered worktree still uses
+    pub in_use: Vec<String>,
+    /// Managed branches matching `[branch_cleanup] protected`
+    pub protected: Vec<String>,
+    /// `git fetch --prune` failed, so the list may include branches already gone
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fetch_error: Option<String>,
+}
+
+/// Branches on the push remote with a worktree type's branch prefix that no
+/// registered worktree uses and that are merged into the upstream's default
+/// branch, after refreshing it; `include_unmerged` adds the unmerged ones
+pub async fn remote_branches_to_prune(
+    imi: &Imi,
+    repo: Option<&str>,
+    include_unmerged: bool,
+) -> Result<RemoteBranchPrunePlan> {
+    let manager = imi.manager();
+    let repo_name = manager.resolve_repo_name(repo).await?;
+    let repository = registered_repository(imi, &repo_name).await?;
+    let trunk = Path::new(&repository.path);
+    let git = &manager.git;
+
+    let remotes = manager.config.remotes_for(&repo_name);
+
+    let mut fetch = vec![remotes.push.as_str()];
+    if remotes.is_fork() {
+        fetch.push(&remotes.upstream);
+    }
+    let fetch_error = fetch
+        .into_iter()
+        .filter_map(|remote| {
+            git.execute_git_command(trunk, &["fetch", "--prune", remote])
+                .err()
+        })
+        .map(|e| format!("{:#}", e))
+        .next();
+    let merged = git.remote_branches(
+        trunk,
+        &remotes.push,
+        Some(&format!(
+            "refs/remotes/{}/{}",
+            remotes.upstream, repository.default_branch
+        )),
+    )?;
+    let used: Vec<String> = imi
+        .database()
+        .list_worktrees(Some(&repo_name))
+        .await?
+        .into_iter()
+        .map(|wt| wt.branch_name)
+        .collect();
+    let prefixes =
+        TypePrefixes::load(imi.database(), &manager.config.type_inference, &repo_name).await;
+    let cleanup = &manager.config.branch_cleanup;
+
+    let mut plan = RemoteBranchPrunePlan {
+        repo_name,
+        trunk_path: trunk.to_path_buf(),
+        remote: remotes.push.clone(),
+        default_branch: repository.default_branch.clone(),
+        branches: Vec::new(),
+        in_use: Vec::new(),
+        protected: Vec::new(),
+        fetch_error,
+    };
+    for name in git.remote_branches(trunk, &remotes.push, None)? {
+        let Some(worktree_type) = prefixes.type_for_branch(&name) else {
+            continue;
+        };
+        if name == repository.default_branch || cleanup.is_protected(&name) {
+            plan.protected.push(name);
+        } else if used.contains(&name) {
+            plan.in_use.push(name);
+        } else if include_unmerged || merged.contains(&name) {
+            plan.branches.push(RemoteBranch {
+                worktree_type: worktree_type.to_string(),
+                merged: merged.contains(&name),
+                name,
+            });
+        }
+    }
+    Ok(plan)
+}
+
+/// Delete the planned branches from the push remote
+pub fn prune_remote_branches(imi: &Imi, plan: &RemoteBranchPrunePlan) -> Result<()> {
+    let names: Vec<String> = plan.branches.iter().map(|b| b.name.clone()).collect();
+    imi.manager()
+        .git
+        .delete_remote_branches(&plan.trunk_path, &plan.remote, &names)
+}
+
+async fn registered_repository(imi: &Imi, repo: &str) -> Result<Repository> {
+    imi.database()
+        .get_repository(repo)
+        .await?
+        .with_context(|| tr!("Repository '{}' is not registered", repo))
+}
+
+pub async fn list_worktree_types(imi: &Imi) -> Result<Vec<WorktreeType>> {
+    imi.database().list_worktree_types().await
+}
+
+/// Every worktree type with its defaults and policies, ready to share
+pub async fn export_worktree_types(imi: &Imi) -> Result<TypesFile> {
+    Ok(TypesFile::new(&imi.database().list_worktree_types().await?))
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TypeImportReport {
+    pub source: PathBuf,
+    pub mode: ImportMode,
+    pub dry_run: bool,
+    pub actions: Vec<ImportAction>,
+}
+
+/// Import type definitions from a TOML or JSON file
+pub async fn import_worktree_types(
+    imi: &Imi,
+    source: &Path,
+    mode: ImportMode,
+    dry_run: bool,
+) -> Result<TypeImportReport> {
+    let file = TypesFile::load(source)?;
+    let db = imi.database();
+    let actions = plan_import(&db.list_worktree_types().await?, &file.types, mode);
+
+    if !dry_run {
+        for action in &actions {
+            let definition = match action {
+                ImportAction::Add { definition } => {
+                    db.add_worktree_type(
+                        &definition.name,
+                        Some(&definition.branch_prefix),
+                        Some(&definition.worktree_prefix),
+                        definition.description.as_deref(),
+                    )
+                    .await?;
+                    definition
+                }
+                ImportAction::Update { definition, .. } => definition,
+                ImportAction::Unchanged { .. } | ImportAction::Conflict { .. } => continue,
+            };
+            let mut wt_type = db.get_worktree_type(&definition.name).await?;
+            definition.apply_to(&mut wt_type);
+            db.update_worktree_type(&wt_type).await?;
+        }
+    }
+
+    Ok(TypeImportReport {
+        source: source.to_path_buf(),
+        mode,
+        dry_run,
+        actions,
+    })
+}
+
+/// The types file committed to a repository's trunk, if it has one
+pub async fn shared_types_file(imi: &Imi, repo: Option<&str>) -> Result<Option<PathBuf>> {
+    let trunk_path = imi.manager.get_trunk_worktree(repo).await?;
+    let path = trunk_path.join(&imi.manager.config.type_sharing.file);
+    Ok(path.exists().then_some(path))
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResolveOptions {
+    pub query: String,
+    pub repo: Option<String>,
+    pub worktrees_only: bool,
+    pub include_inactive: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolvedTarget {
+    pub name: String,
+    pub repo_name: String,
+    pub worktree_type: Option<String>,
+    pub path: PathBuf,
+    pub score: f64,
+}
+
+impl From<&SearchTarget> for ResolvedTarget {
+    fn from(target: &SearchTarget) -> Self {
+        Self {
+            name: target.display_name(),
+            repo_name: target.repo_name().to_string(),
+            worktree_type: target.worktree_type().map(String::from),
+            path: target.path(),
+            score: target.score(),
+        }
+    }
+}
+
+/// Every target tied for the best match; more than one means the query is ambiguous
+pub async fn resolve(imi: &Imi, options: ResolveOptions) -> Result<Vec<ResolvedTarget>> {
+    let targets = imi
+        .manager
+        .resolve_targets(
+            &options.query,
+            options.repo.as_deref(),
+            options.worktrees_only,
+            options.include_inactive,
+        )
+        .await?;
+
+    Ok(targets.iter().map(ResolvedTarget::from).collect())
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RepositoryHealth {
+    pub repository: Repository,
+    pub health: RepoHealth,
+}
+
+/// Trunk, fetch and worktree health for every registered repository
+pub async fn repository_health(imi: &Imi) -> Result<Vec<RepositoryHealth>> {
+    Ok(imi
+        .manager
+        .collect_repository_health()
+        .await?
+        .into_iter()
+        .map(|(repository, health)| RepositoryHealth { repository, health })
+        .collect())
+}
+
+/// Alerts raised by the configured rules for active worktrees
+pub async fn worktree_alerts(imi: &Imi, repo: Option<&str>) -> Result<Vec<Alert>> {
+    let config = &imi.manager.config;
+    let evaluator = AlertEvaluator::new(
+        &imi.manager.git,
+        &config.alert_settings,
+        &config.git_settings.default_branch,
+    );
+
+    Ok(imi
+        .database()
+        .list_worktrees(repo)
+        .await?
+        .iter()
+        .flat_map(|wt| evaluator.evaluate(wt))
+        .collect())
+}
+
+/// GitHub Actions status of one worktree's branch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorktreeCi {
+    pub worktree_name: String,
+    pub branch_name: String,
+    #[serde(flatten)]
+    pub status: CiStatus,
+}
+
+/// Latest CI result for each active worktree's branch that has workflow runs
+pub async fn ci_statuses(imi: &Imi, repo: Option<&str>) -> Result<Vec<WorktreeCi>> {
+    let mut statuses = imi.manager.ci_statuses(repo).await?;
+    Ok(imi
+        .database()
+        .list_worktrees(repo)
+        .await?
+        .into_iter()
+        .filter_map(|wt| {
+            Some(WorktreeCi {
+                status: statuses.remove(&wt.id)?,
+                worktree_name: wt.worktree_name,
+                branch_name: wt.branch_name,
+            })
+        })
+        .collect())
+}
+
+/// Leave a note on a worktree, signed with the current user and, when an
+/// agent writes it, IMI_AGENT_ID
+pub async fn add_note(
+    imi: &Imi,
+    worktree: &str,
+    repo: Option<&str>,
+    body: &str,
+) -> Result<(Worktree, WorktreeNote)> {
+    let body = body.trim();
+    if body.is_empty() {
+        anyhow::bail!("A note needs some text");
+    }
+    let worktree = registered_worktree(imi, worktree, repo).await?;
+    let author = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .ok();
+    let agent_id = std::env::var("IMI_AGENT_ID").ok();
+    let note = imi
+        .database()
+        .add_worktree_note(&worktree.id, body, author.as_deref(), agent_id.as_deref())
+        .await?;
+    Ok((worktree, note))
+}
+
+/// A worktree's notes, newest first
+pub async fn worktree_notes(
+    imi: &Imi,
+    worktree: &str,
+    repo: Option<&str>,
+) -> Result<(Worktree, Vec<WorktreeNote>)> {
+    let worktree = registered_worktree(imi, worktree, repo).await?;
+    let notes = imi.database().list_worktree_notes(&worktree.id).await?;
+    Ok((worktree, notes))
+}
+
+/// Send a message to a worktree for whoever works in it, optionally also
+/// through the alert notification channels
+pub async fn send_message(
+    imi: &Imi,
+    worktree: &str,
+    repo: Option<&str>,
+    body: &str,
+    notify: bool,
+) -> Result<(Worktree, WorktreeMessage)> {
+    let body = body.trim();
+    if body.is_empty() {
+        anyhow::bail!("A message needs some text");
+    }
+    let mut worktree = registered_worktree(imi, worktree, repo).await?;
+    let sender = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .ok();
+    let agent_id = std::env::var("IMI_AGENT_ID").ok();
+    let message = imi
+        .database()
+        .send_worktree_message(&worktree.id, body, sender.as_deref(), agent_id.as_deref())
+        .await?;
+
+    if notify {
+        imi.database()
+            .populate_worktree_names(std::slice::from_mut(&mut worktree))
+            .await?;
+        crate::alerts::notify_message(&imi.config().alert_settings, &worktree, body);
+    }
+    Ok((worktree, message))
+}
+
+/// Messages sent to a worktree (the one the current directory is in when
+/// `worktree` is None), oldest first; unless `keep_unread`, they're marked
+/// read
+pub async fn read_messages(
+    imi: &Imi,
+    worktree: Option<&str>,
+    repo: Option<&str>,
+    unread_only: bool,
+    keep_unread: bool,
+) -> Result<(Worktree, Vec<WorktreeMessage>)> {
+    let worktree = match worktree {
+        Some(name) => registered_worktree(imi, name, repo).await?,
+        None => {
+            let GitContext::InWorktree { worktree_path, .. } =
+                imi.manager().git.detect_context(None)
+            else {
+                anyhow::bail!("Not inside a worktree; name the worktree to read messages for");
+            };
+            let name = worktree_path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .context("Not inside a worktree")?;
+            registered_worktree(imi, &name, repo).await?
+        }
+    };
+
+    let db = imi.database();
+    let messages = db.list_worktree_messages(&worktree.id, unread_only).await?;
+    if !keep_unread {
+        let unread: Vec<Uuid> = messages
+            .iter()
+            .filter(|message| message.read_at.is_none())
+            .map(|message| message.id)
+            .collect();
+        if !unread.is_empty() {
+            db.mark_worktree_messages_read(&unread).await?;
+        }
+    }
+    Ok((worktree, messages))
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CheckOptions {
+    pub worktree: String,
+    pub repo: Option<String>,
+    /// Command and arguments
+    pub command: Vec<String>,
+    /// Pass the command's output through to the terminal while it runs
+    pub echo: bool,
+}
+
+/// Run a command in a worktree and keep its output and exit code as an
+/// artifact; a failing command is still recorded, check `success`
+pub async fn check_worktree(
+    imi: &Imi,
+    options: CheckOptions,
+) -> Result<(Worktree, WorktreeArtifact)> {
+    let worktree = registered_worktree(imi, &options.worktree, options.repo.as_deref()).await?;
+    let run =
+        artifacts::run_captured(&options.command, Path::new(&worktree.path), options.echo).await?;
+    let artifact =
+        record_artifact(imi, &worktree, ArtifactKind::Check, &options.command, &run).await?;
+    Ok((worktree, artifact))
+}
+
+/// Keep a finished run's output for a worktree, deleting its oldest
+/// artifacts beyond `[artifacts] keep_per_worktree`
+pub async fn record_artifact(
+    imi: &Imi,
+    worktree: &Worktree,
+    kind: ArtifactKind,
+    command: &[String],
+    run: &CapturedRun,
+) -> Result<WorktreeArtifact> {
+    let path = Path::new(&worktree.path);
+    let id = Uuid::new_v4();
+    let project_root = imi.manager().detect_imi_path(path)?;
+    let output_dir = ArtifactStore::new(&project_root).write(&worktree.name, &id, run)?;
+    let artifact = WorktreeArtifact {
+        id,
+        worktree_id: worktree.id,
+        kind: kind.as_str().to_string(),
+        command: command.join(" "),
+        exit_code: run.exit_code,
+        success: run.success,
+        duration_ms: run.duration_ms,
+        commit_sha: imi
+            .manager()
+            .git
+            .get_head_commit(path)
+            .ok()
+            .map(|(sha, _)| sha),
+        output_dir: output_dir.display().to_string(),
+        stdout_bytes: run.stdout.len() as i64,
+        stderr_bytes: run.stderr.len() as i64,
+        agent_id: std::env::var("IMI_AGENT_ID").ok(),
+        created_at: Utc::now(),
+    };
+    let db = imi.database();
+    db.add_worktree_artifact(&artifact).await?;
+
+    let keep = imi.config().artifacts.keep_per_worktree.max(1);
+    let stale: Vec<WorktreeArtifact> = db
+        .list_worktree_artifacts(&worktree.id)
+        .await?
+        .into_iter()
+        .skip(keep)
+        .collect();
+    if !stale.is_empty() {
+        for old in &stale {
+            artifacts::remove_output(Path::new(&old.output_dir))?;
+        }
+        let ids: Vec<Uuid> = stale.iter().map(|old| old.id).collect();
+        db.delete_worktree_artifacts(&ids).await?;
+    }
+    Ok(artifact)
+}
+
+/// The registered worktree `imi exec` runs in for a repository, i.e. the
+/// one checked out at the repository's path; None when it isn't registered
+pub async fn exec_worktree(imi: &Imi, repository: &Repository) -> Result<Option<Worktree>> {
+    let repo_path = Path::new(&repository.path);
+    Ok(imi
+        .database()
+        .list_worktrees(Some(&repository.name))
+        .await?
+        .into_iter()
+        .find(|wt| Path::new(&wt.path) == repo_path))
+}
+
+/// A worktree's artifacts, newest first
+pub async fn worktree_artifacts(
+    imi: &Imi,
+    worktree: &str,
+    repo: Option<&str>,
+) -> Result<(Worktree, Vec<WorktreeArtifact>)> {
+    let worktree = registered_worktree(imi, worktree, repo).await?;
+    let artifacts = imi.database().list_worktree_artifacts(&worktree.id).await?;
+    Ok((worktree, artifacts))
+}
+
+/// The last run of one worktree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LatestArtifact {
+    pub worktree_name: String,
+    pub branch_name: String,
+    #[serde(flatten)]
+    pub artifact: WorktreeArtifact,
+}
+
+/// Newest artifact of each active worktree that has one
+pub async fn latest_artifacts(imi: &Imi, repo: Option<&str>) -> Result<Vec<LatestArtifact>> {
+    let mut latest: BTreeMap<Uuid, WorktreeArtifact> = imi
+        .database()
+        .latest_worktree_artifacts()
+        .await?
+        .into_iter()
+        .map(|artifact| (artifact.worktree_id, artifact))
+        .collect();
+    Ok(imi
+        .database()
+        .list_worktrees(repo)
+        .await?
+        .into_iter()
+        .filter_map(|wt| {
+            Some(LatestArtifact {
+                artifact: latest.remove(&wt.id)?,
+                worktree_name: wt.name,
+                branch_name: wt.branch_name,
+            })
+        })
+        .collect())
+}
+
+/// An artifact with its captured output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtifactOutput {
+    pub worktree_name: String,
+    pub artifact: WorktreeArtifact,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Output of a worktree's latest artifact, or of the one whose id starts
+/// with `id`
+pub async fn artifact_output(
+    imi: &Imi,
+    worktree: &str,
+    repo: Option<&str>,
+    id: Option<&str>,
+) -> Result<ArtifactOutput> {
+    let (worktree, artifacts) = worktree_artifacts(imi, worktree, repo).await?;
+    let artifact = match id {
+        Some(id) => {
+            let mut matches = artifacts
+                .into_iter()
+                .filter(|artifact| artifact.id.to_string().starts_with(id));
+            let artifact = matches
+                .next()
+                .with_context(|| tr!("No artifact '{}' on worktree '{}'", id, worktree.name))?;
+            if matches.next().is_some() {
+                anyhow::bail!("Artifact id '{}' is ambiguous; give more of it", id);
+            }
+            artifact
+        }
+        None => artifacts.into_iter().next().with_context(|| {
+            tr!(
+                "No artifacts on worktree '{}'; run 'imi check {} -- <command>'",
+                worktree.name,
+                worktree.name
+            )
+        })?,
+    };
+    let output_dir = Path::new(&artifact.output_dir);
+    let stdout = artifacts::read_output(output_dir, Stream::Stdout)?;
+    let stderr = artifacts::read_output(output_dir, Stream::Stderr)?;
+    Ok(ArtifactOutput {
+        worktree_name: worktree.name,
+        stdout: String::from_utf8_lossy(&stdout).into_owned(),
+        stderr: String::from_utf8_lossy(&stderr).into_owned(),
+        artifact,
+    })
+}
+
+/// DB record, git state, divergence, disk usage, notes, recent commits,
+/// hooks and symlink health of one worktree
+pub async fn show_worktree(
+    imi: &Imi,
+    worktree: &str,
+    repo: Option<&str>,
+) -> Result<WorktreeDetail> {
+    imi.manager().worktree_detail(worktree, repo).await
+}
+
+async fn registered_worktree(imi: &Imi, name: &str, repo: Option<&str>) -> Result<Worktree> {
+    imi.manager()
+        .get_worktree_by_name(name, repo)
+        .await?
+        .with_context(|| tr!("Worktree '{}' not found", name))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentWorktree {
+    pub repo_name: String,
+    pub worktree_name: String,
+    pub worktree_type: String,
+    pub branch_name: String,
+    pub path: PathBuf,
+    pub last_visited_at: DateTime<Utc>,
+    pub visit_count: i32,
+}
+
+/// Worktrees on this machine, most recently visited (via `go`, `open` or
+/// `recent`) or active first
+pub async fn recent_worktrees(
+    imi: &Imi,
+    repo: Option<&str>,
+    limit: usize,
+) -> Result<Vec<RecentWorktree>> {
+    let db = imi.database();
+    let stats = db.list_navigation_stats().await?;
+    let mut worktrees = db.list_worktrees(repo).await?;
+    worktrees.retain(Worktree::is_local);
+    db.populate_worktree_names(&mut worktrees).await?;
+
+    Ok(most_recent(&stats, &worktrees, limit))
+}
+
+fn most_recent(
+    stats: &[NavigationStat],
+    worktrees: &[Worktree],
+    limit: usize,
+) -> Vec<RecentWorktree> {
+    let mut visited: Vec<(&NavigationStat, &Worktree)> = stats
+        .iter()
+        .filter(|stat| stat.target_kind == "worktree")
+        .filter_map(|stat| {
+            worktrees
+                .iter()
+                .find(|wt| wt.id == stat.target_id)
+                .map(|wt| (stat, wt))
+        })
+        .collect();
+    visited.sort_by_key(|(stat, _)| std::cmp::Reverse(stat.last_visited_at));
+
+    visited
+        .into_iter()
+        .take(limit)
+        .map(|(stat, wt)| RecentWorktree {
+            repo_name: wt.repo_name.clone(),
+            worktree_name: wt.name.clone(),
+            worktree_type: wt.worktree_type.clone(),
+            branch_name: wt.branch_name.clone(),
+            path: PathBuf::from(&wt.path),
+            last_visited_at: stat.last_visited_at,
+            visit_count: stat.visit_count,
+        })
+        .collect()
+}
+
+/// Record a jump to a recent worktree so it stays at the top of the list
+pub async fn record_visit(imi: &Imi, recent: &RecentWorktree) -> Result<()> {
+    let Some(worktree) = imi
+        .database()
+        .get_worktree(&recent.repo_name, &recent.worktree_name)
+        .await?
+    else {
+        return Ok(());
+    };
+    imi.database()
+        .record_navigation(&worktree.id, "worktree")
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_patterns_match_whole_worktree_names() {
+        let re = glob_to_regex("feat-*-spike").unwrap();
+        assert!(re.is_match("feat-auth-spike"));
+        assert!(!re.is_match("feat-auth-spike-2"));
+        assert!(!re.is_match("fix-auth-spike"));
+
+        let re = glob_to_regex("pr-1?").unwrap();
+        assert!(re.is_match("pr-12"));
+        assert!(!re.is_match("pr-123"));
+
+        // Regex metacharacters are literal
+        assert!(!glob_to_regex("feat.x").unwrap().is_match("featax"));
+
+        assert!(glob_to_regex("ml-*").unwrap().is_match("ml-"));
+        assert!(glob_to_regex("*-api-?").unwrap().is_match("x-api-api-2"));
+        assert!(glob_to_regex("*").unwrap().is_match(""));
+        assert!(!glob_to_regex("a?c").unwrap().is_match("ac"));
+    }
+}
@@ -0,0 +1,61 @@
+//! `imi repair --remap OLD=NEW`: rewrite registered paths after the
+//! directories holding them moved, e.g. a backup restored under another home
+
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathRemap {
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+impl FromStr for PathRemap {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (from, to) = s
+            .split_once('=')
+            .ok_or_else(|| format!("expected OLD=NEW, got '{}'", s))?;
+        if from.is_empty() || to.is_empty() {
+            return Err(format!("expected OLD=NEW with both paths, got '{}'", s));
+        }
+        Ok(Self {
+            from: PathBuf::from(from.trim_end_matches('/')),
+            to: PathBuf::from(to.trim_end_matches('/')),
+        })
+    }
+}
+
+impl PathRemap {
+    /// `path` moved under `to`, or `None` when it isn't under `from`.
+    /// Components are compared whole, so `/home/al` doesn't match `/home/alice`.
+    pub fn apply(&self, path: &Path) -> Option<PathBuf> {
+        let rest = path.strip_prefix(&self.from).ok()?;
+        Some(match rest.as_os_str().is_empty() {
+            true => self.to.clone(),
+            false => self.to.join(rest),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remaps_paths_under_the_old_root() {
+        let remap: PathRemap = "/home/old/=/Users/new".parse().unwrap();
+        assert_eq!(
+            remap.apply(Path::new("/home/old/code/api/trunk-main")),
+            Some(PathBuf::from("/Users/new/code/api/trunk-main"))
+        );
+        assert_eq!(
+            remap.apply(Path::new("/home/old")),
+            Some(PathBuf::from("/Users/new"))
+        );
+        assert_eq!(remap.apply(Path::new("/home/older/code")), None);
+        assert!("/home/old".parse::<PathRemap>().is_err());
+        assert!("=/Users/new".parse::<PathRemap>().is_err());
+    }
+}
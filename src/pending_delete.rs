@@ -0,0 +1,170 @@
+//! Deleting a worktree with a multi-GB `target/` takes minutes, so removal
+//! renames the directory into `<repo>/.iMi/pending-delete` and returns. The
+//! contents are deleted by a detached process, or by the next `imi prune` or
+//! daemon cycle when that one was interrupted.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::Serialize;
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+const TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%S";
+
+/// A directory waiting to be deleted
+#[derive(Debug, Clone, Serialize)]
+pub struct PendingDelete {
+    /// `<timestamp>-<original directory name>`
+    pub id: String,
+    /// The directory name it had before removal
+    pub name: String,
+    pub path: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub staged_at: Option<DateTime<Utc>>,
+}
+
+impl PendingDelete {
+    fn from_id(id: &str, path: PathBuf) -> Self {
+        let (staged_at, name) = match id.split_once('-') {
+            Some((stamp, name)) => (
+                NaiveDateTime::parse_from_str(stamp, TIMESTAMP_FORMAT)
+                    .ok()
+                    .map(|t| t.and_utc()),
+                name,
+            ),
+            None => (None, id),
+        };
+        Self {
+            id: id.to_string(),
+            name: name.to_string(),
+            path,
+            staged_at,
+        }
+    }
+}
+
+/// The pending-delete directory of one repository
+#[derive(Debug, Clone)]
+pub struct PendingDeletes {
+    dir: PathBuf,
+}
+
+impl PendingDeletes {
+    /// Pending deletes for the repository whose worktrees live in `repo_container`
+    pub fn new(repo_container: &Path) -> Self {
+        Self {
+            dir: repo_container.join(".iMi").join("pending-delete"),
+        }
+    }
+
+    /// Rename `path` aside so its original location is free right away
+    pub fn stage(&self, path: &Path) -> Result<PendingDelete> {
+        std::fs::create_dir_all(&self.dir).context(format!(
+            "Failed to create pending-delete directory {}",
+            self.dir.display()
+        ))?;
+
+        let name = path
+            .file_name()
+            .context("Cannot delete a path without a name")?
+            .to_string_lossy();
+        let base_id = format!("{}-{}", Utc::now().format(TIMESTAMP_FORMAT), name);
+        let mut id = base_id.clone();
+        let mut suffix = 1;
+        while self.dir.join(&id).exists() {
+            suffix += 1;
+            id = format!("{}-{}", base_id, suffix);
+        }
+
+        let staged = self.dir.join(&id);
+        std::fs::rename(path, &staged)
+            .context(format!("Failed to move {} aside", path.display()))?;
+        Ok(PendingDelete::from_id(&id, staged))
+    }
+
+    /// Delete a staged directory in a detached `rm -rf` that outlives this
+    /// process and ignores Ctrl+C in the terminal
+    pub fn spawn_delete(entry: &PendingDelete) -> Result<()> {
+        let mut child = Command::new("rm")
+            .arg("-rf")
+            .arg(&entry.path)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .process_group(0)
+            .spawn()
+            .context("Failed to start background deletion")?;
+        // Reap it when a long-running process (daemon, server) did the removal
+        std::thread::spawn(move || child.wait());
+        Ok(())
+    }
+
+    /// Directories still waiting, oldest first
+    pub fn list(&self) -> Result<Vec<PendingDelete>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut entries = Vec::new();
+        for entry in std::fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            let id = entry.file_name().to_string_lossy().to_string();
+            entries.push(PendingDelete::from_id(&id, entry.path()));
+        }
+        entries.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(entries)
+    }
+
+    /// Delete everything still waiting, blocking until done; returns what
+    /// was deleted. Entries a background deletion finishes first are skipped.
+    pub fn drain(&self) -> Result<Vec<PendingDelete>> {
+        let mut deleted = Vec::new();
+        for entry in self.list()? {
+            // A background `rm` racing us can make the first attempt trip
+            // over files it just deleted
+            let result =
+                std::fs::remove_dir_all(&entry.path).or_else(|_| match entry.path.exists() {
+                    true => std::fs::remove_dir_all(&entry.path),
+                    false => Ok(()),
+                });
+            if let Err(e) = result {
+                if entry.path.exists() {
+                    return Err(e).context(format!("Failed to delete {}", entry.path.display()));
+                }
+            }
+            deleted.push(entry);
+        }
+        Ok(deleted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn staged_directories_free_their_path_until_drained() {
+        let container = tempfile::tempdir().unwrap();
+        let worktree = container.path().join("feat-big");
+        std::fs::create_dir_all(worktree.join("target/debug")).unwrap();
+        std::fs::write(worktree.join("target/debug/app"), "binary").unwrap();
+
+        let pending = PendingDeletes::new(container.path());
+        let entry = pending.stage(&worktree).unwrap();
+        assert!(!worktree.exists());
+        assert!(entry.path.join("target/debug/app").exists());
+        assert_eq!(entry.name, "feat-big");
+        assert!(entry.staged_at.is_some());
+
+        // The same name can be removed again before the first is gone
+        std::fs::create_dir(&worktree).unwrap();
+        let again = pending.stage(&worktree).unwrap();
+        assert_ne!(again.id, entry.id);
+
+        assert_eq!(pending.list().unwrap().len(), 2);
+        assert_eq!(pending.drain().unwrap().len(), 2);
+        assert!(pending.list().unwrap().is_empty());
+        assert!(!entry.path.exists());
+    }
+}
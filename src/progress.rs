@@ -0,0 +1,86 @@
+//! `--json-stream`: long operations write NDJSON progress events to stdout
+//! as they go, and the final result object ends the stream, so supervisors
+//! can show progress and enforce timeouts instead of waiting in silence.
+
+use serde_json::Value;
+use std::io::Write;
+
+/// Whether `--json-stream` (or IMI_JSON_STREAM=1) is on
+pub fn enabled() -> bool {
+    std::env::var("IMI_JSON_STREAM").is_ok_and(|value| !matches!(value.trim(), "" | "0" | "false"))
+}
+
+/// One progress line: the phase the operation is in, how far through it
+/// (when known) and what it is doing
+pub fn event(phase: &str, percent: Option<u8>, message: &str) -> Value {
+    serde_json::json!({
+        "event": "progress",
+        "phase": phase,
+        "percent": percent.map(|p| p.min(100)),
+        "message": message,
+    })
+}
+
+/// Write a progress event when streaming; a no-op otherwise
+pub fn emit(phase: &str, percent: Option<u8>, message: &str) {
+    if !enabled() {
+        return;
+    }
+    let line = event(phase, percent, message).to_string();
+    let mut stdout = std::io::stdout().lock();
+    let _ = writeln!(stdout, "{}", line);
+    let _ = stdout.flush();
+}
+
+/// `done` of `total` as a percentage
+pub fn percent(done: usize, total: usize) -> u8 {
+    match total {
+        0 => 100,
+        _ => (done.min(total) * 100 / total) as u8,
+    }
+}
+
+/// The stage and percentage in a line of `git clone --progress` output,
+/// e.g. "Receiving objects:  45% (450/1000), 1.2 MiB | 3 MiB/s"
+pub fn parse_git_progress(line: &str) -> Option<(&str, u8)> {
+    let line = line.trim();
+    let line = line.strip_prefix("remote:").map_or(line, str::trim);
+    let (stage, rest) = line.split_once(':')?;
+    let percent = rest.trim_start().split_once('%')?.0.trim().parse().ok()?;
+    Some((stage.trim(), percent))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_events_and_reads_git_progress() {
+        assert_eq!(
+            event("clone", Some(140), "acme/api"),
+            serde_json::json!({
+                "event": "progress",
+                "phase": "clone",
+                "percent": 100,
+                "message": "acme/api",
+            })
+        );
+        assert_eq!(event("merge", None, "x")["percent"], Value::Null);
+
+        assert_eq!(
+            parse_git_progress("Receiving objects:  45% (450/1000), 1.20 MiB | 3.00 MiB/s"),
+            Some(("Receiving objects", 45))
+        );
+        assert_eq!(
+            parse_git_progress("Resolving deltas: 100% (20/20), done."),
+            Some(("Resolving deltas", 100))
+        );
+        assert_eq!(
+            parse_git_progress("remote: Counting objects:   7% (7/100)"),
+            Some(("Counting objects", 7))
+        );
+        assert_eq!(parse_git_progress("Cloning into 'api'..."), None);
+        assert_eq!(percent(1, 3), 33);
+        assert_eq!(percent(0, 0), 100);
+    }
+}
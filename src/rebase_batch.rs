@@ -0,0 +1,194 @@
+//! `imi sync --rebase-worktrees` rebases every worktree of a repository onto
+//! the updated trunk. Worktrees that conflict are left mid-rebase for the
+//! user to resolve, and the batch is kept in `<repo>/.iMi/rebase-batch.json`
+//! so `imi sync --continue` picks up where it stopped.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Where one worktree is in the batch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RebaseState {
+    /// Not attempted yet
+    Pending,
+    /// Being rebased; still set if iMi was interrupted
+    InProgress,
+    /// Stopped mid-rebase, waiting for the user
+    Conflicted,
+    Rebased,
+    UpToDate,
+    /// Left alone (uncommitted changes, detached HEAD, aborted)
+    Skipped,
+    /// The rebase couldn't start and was rolled back
+    Failed,
+}
+
+impl RebaseState {
+    /// Still needs `imi sync --continue`
+    pub fn is_open(self) -> bool {
+        matches!(self, Self::Pending | Self::InProgress | Self::Conflicted)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::InProgress => "in progress",
+            Self::Conflicted => "conflicted",
+            Self::Rebased => "rebased",
+            Self::UpToDate => "up to date",
+            Self::Skipped => "skipped",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RebaseItem {
+    pub worktree_name: String,
+    pub branch_name: String,
+    pub path: PathBuf,
+    pub state: RebaseState,
+    /// Why it was skipped or failed, or what it stopped on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl RebaseItem {
+    pub fn new(worktree_name: &str, branch_name: &str, path: &Path) -> Self {
+        Self {
+            worktree_name: worktree_name.to_string(),
+            branch_name: branch_name.to_string(),
+            path: path.to_path_buf(),
+            state: RebaseState::Pending,
+            detail: None,
+        }
+    }
+
+    pub fn set(&mut self, state: RebaseState, detail: Option<String>) {
+        self.state = state;
+        self.detail = detail;
+    }
+}
+
+/// The worktrees of one repository being rebased onto `onto`, in order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RebaseBatch {
+    pub repo_name: String,
+    /// What the worktrees are rebased onto, e.g. `origin/main`
+    pub onto: String,
+    pub started_at: DateTime<Utc>,
+    pub items: Vec<RebaseItem>,
+}
+
+impl RebaseBatch {
+    pub fn new(repo_name: &str, onto: &str, items: Vec<RebaseItem>) -> Self {
+        Self {
+            repo_name: repo_name.to_string(),
+            onto: onto.to_string(),
+            started_at: Utc::now(),
+            items,
+        }
+    }
+
+    /// Nothing is left for `imi sync --continue`
+    pub fn is_finished(&self) -> bool {
+        !self.items.iter().any(|item| item.state.is_open())
+    }
+
+    pub fn count(&self, state: RebaseState) -> usize {
+        self.items.iter().filter(|item| item.state == state).count()
+    }
+}
+
+/// A repository's unfinished batch on disk
+#[derive(Debug, Clone)]
+pub struct RebaseBatchFile {
+    path: PathBuf,
+}
+
+impl RebaseBatchFile {
+    /// The batch for the repository whose worktrees live in `repo_container`
+    pub fn new(repo_container: &Path) -> Self {
+        Self {
+            path: repo_container.join(".iMi").join("rebase-batch.json"),
+        }
+    }
+
+    pub fn load(&self) -> Result<Option<RebaseBatch>> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(&self.path)
+            .context(format!("Failed to read {}", self.path.display()))?;
+        let batch = serde_json::from_str(&content)
+            .context(format!("Failed to parse {}", self.path.display()))?;
+        Ok(Some(batch))
+    }
+
+    /// Write the batch, replacing the previous state in one rename
+    pub fn save(&self, batch: &RebaseBatch) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(batch)?)?;
+        std::fs::rename(&tmp, &self.path)
+            .context(format!("Failed to write {}", self.path.display()))
+    }
+
+    pub fn clear(&self) -> Result<()> {
+        match std::fs::remove_file(&self.path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batch_survives_a_round_trip_until_nothing_is_open() {
+        let container = tempfile::tempdir().unwrap();
+        let file = RebaseBatchFile::new(container.path());
+        assert!(file.load().unwrap().is_none());
+
+        let mut batch = RebaseBatch::new(
+            "api",
+            "origin/main",
+            vec![
+                RebaseItem::new(
+                    "feat-auth",
+                    "feat/auth",
+                    &container.path().join("feat-auth"),
+                ),
+                RebaseItem::new(
+                    "fix-login",
+                    "fix/login",
+                    &container.path().join("fix-login"),
+                ),
+            ],
+        );
+        batch.items[0].set(RebaseState::Rebased, None);
+        batch.items[1].set(RebaseState::Conflicted, Some("src/login.rs".to_string()));
+        file.save(&batch).unwrap();
+
+        let loaded = file.load().unwrap().unwrap();
+        assert_eq!(loaded.onto, "origin/main");
+        assert_eq!(loaded.items[1].state, RebaseState::Conflicted);
+        assert_eq!(loaded.items[1].detail.as_deref(), Some("src/login.rs"));
+        assert!(!loaded.is_finished());
+
+        batch.items[1].set(RebaseState::Rebased, None);
+        assert!(batch.is_finished());
+        assert_eq!(batch.count(RebaseState::Rebased), 2);
+
+        file.clear().unwrap();
+        file.clear().unwrap();
+        assert!(file.load().unwrap().is_none());
+    }
+}
@@ -0,0 +1,447 @@
+//! Code activity of a repository's trunk for `imi repo stats --git`: commits
+//! per week, authors, the directories that change most, and how long PRs
+//! take to merge. Walking the history and asking GitHub is slow, so results
+//! are cached until trunk moves (git metrics) or for a few hours (PR data).
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::git::GitManager;
+use crate::json_cache;
+
+/// Directory levels kept when grouping changed files
+const DIRECTORY_DEPTH: usize = 2;
+
+/// Merged PRs fetched per repository for merge times
+const PR_LIMIT: usize = 200;
+
+/// How long fetched PR data is reused
+const PR_TTL_SECS: i64 = 6 * 60 * 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitActivity {
+    pub branch: String,
+    /// Trunk commit the metrics were computed at
+    pub head: String,
+    /// First day counted (a Monday)
+    pub since: NaiveDate,
+    /// Oldest week first
+    pub weeks: Vec<WeekActivity>,
+    pub commits: usize,
+    pub unique_authors: usize,
+    /// Most active first
+    pub authors: Vec<AuthorActivity>,
+    /// Directories touched by the most commits first
+    pub hot_directories: Vec<DirectoryActivity>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pr_merge_time: Option<MergeTime>,
+    /// Why PR merge times are missing (e.g. gh isn't authenticated)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pr_merge_time_error: Option<String>,
+    pub computed_at: DateTime<Utc>,
+    /// Served from the cache rather than computed now
+    #[serde(default)]
+    pub cached: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WeekActivity {
+    pub week_start: NaiveDate,
+    pub commits: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthorActivity {
+    pub name: String,
+    pub email: String,
+    pub commits: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DirectoryActivity {
+    pub path: String,
+    pub commits: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MergeTime {
+    /// PRs merged since the first counted day
+    pub pull_requests: usize,
+    pub average_hours: f64,
+    pub median_hours: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MergedPullRequest {
+    pub number: u64,
+    pub created_at: DateTime<Utc>,
+    pub merged_at: DateTime<Utc>,
+}
+
+/// One commit as read from `git log`
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Commit {
+    at: DateTime<Utc>,
+    name: String,
+    email: String,
+    files: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CachedStats {
+    activity: Option<GitActivity>,
+    #[serde(default)]
+    merged_prs: Vec<MergedPullRequest>,
+    prs_fetched_at: Option<DateTime<Utc>>,
+}
+
+/// Activity per trunk path, persisted between commands
+#[derive(Debug)]
+pub struct GitStatsCache {
+    path: Option<PathBuf>,
+    entries: HashMap<PathBuf, CachedStats>,
+}
+
+impl GitStatsCache {
+    pub fn load() -> Self {
+        Self::load_from(json_cache::cache_file("git-stats.json"))
+    }
+
+    pub fn load_from(path: Option<PathBuf>) -> Self {
+        let entries = json_cache::read(path.as_deref());
+        Self { path, entries }
+    }
+
+    /// Write the cache back, dropping trunks that no longer exist
+    pub fn save(&mut self) -> Result<()> {
+        self.entries.retain(|trunk, _| trunk.exists());
+        json_cache::write(self.path.as_deref(), &self.entries)
+    }
+}
+
+impl GitActivity {
+    /// Compute the activity of `branch` in the trunk at `trunk` over the last
+    /// `weeks` weeks (the current one included), reusing cached results
+    /// unless `refresh` is set
+    pub fn collect(
+        git: &GitManager,
+        cache: &mut GitStatsCache,
+        trunk: &Path,
+        branch: &str,
+        weeks: u32,
+        refresh: bool,
+    ) -> Result<Self> {
+        let (rev, head) = resolve_branch(git, trunk, branch)?;
+        let today = Utc::now().date_naive();
+        let since = first_day(today, weeks);
+        let entry = cache.entries.entry(trunk.to_path_buf()).or_default();
+
+        let reusable = entry.activity.as_ref().filter(|activity| {
+            !refresh
+                && activity.head == head
+                && activity.branch == branch
+                && activity.since == since
+                && activity.computed_at.date_naive() == today
+        });
+        if let Some(activity) = reusable {
+            let mut activity = activity.clone();
+            activity.cached = true;
+            return Ok(activity);
+        }
+
+        let since_arg = format!("--since={}T00:00:00Z", since);
+        let log = git
+            .execute_git_command(
+                trunk,
+                &[
+                    "log",
+                    &rev,
+                    &since_arg,
+                    "--no-merges",
+                    "--name-only",
+                    "--format=%x1e%at%x1f%aN%x1f%aE",
+                ],
+            )
+            .context("Failed to read the trunk's history")?;
+        let mut activity = summarize(&parse_log(&log), since, weeks);
+        activity.branch = branch.to_string();
+        activity.head = head;
+
+        let prs_fresh = entry
+            .prs_fetched_at
+            .is_some_and(|at| Utc::now() - at < Duration::seconds(PR_TTL_SECS));
+        if refresh || !prs_fresh {
+            match fetch_merged_prs(git, trunk, branch) {
+                Ok(prs) => {
+                    entry.merged_prs = prs;
+                    entry.prs_fetched_at = Some(Utc::now());
+                }
+                Err(e) => activity.pr_merge_time_error = Some(format!("{:#}", e)),
+            }
+        }
+        // Don't cache a failed PR lookup; the next run tries again
+        if activity.pr_merge_time_error.is_none() {
+            activity.pr_merge_time = merge_time(&entry.merged_prs, since);
+            entry.activity = Some(activity.clone());
+        }
+        Ok(activity)
+    }
+
+    /// Keep only the `top` most active authors and directories
+    pub fn truncate(&mut self, top: usize) {
+        self.authors.truncate(top);
+        self.hot_directories.truncate(top);
+    }
+}
+
+/// The revision to read `branch` from and the commit it points at; falls back
+/// to origin's copy when the trunk has no local branch of that name
+fn resolve_branch(git: &GitManager, trunk: &Path, branch: &str) -> Result<(String, String)> {
+    [branch.to_string(), format!("origin/{}", branch)]
+        .into_iter()
+        .find_map(|rev| {
+            let spec = format!("{}^{{commit}}", rev);
+            git.execute_git_command(trunk, &["rev-parse", "--verify", "--quiet", &spec])
+                .ok()
+                .map(|sha| (rev, sha.trim().to_string()))
+                .filter(|(_, sha)| !sha.is_empty())
+        })
+        .with_context(|| format!("Branch '{}' not found in {}", branch, trunk.display()))
+}
+
+/// Monday of the oldest of the last `weeks` weeks
+fn first_day(today: NaiveDate, weeks: u32) -> NaiveDate {
+    let this_week = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+    this_week - Duration::weeks(weeks.saturating_sub(1) as i64)
+}
+
+/// Parse `git log --name-only --format=%x1e%at%x1f%aN%x1f%aE`
+fn parse_log(log: &str) -> Vec<Commit> {
+    log.split('\x1e')
+        .filter_map(|record| {
+            let mut lines = record.lines();
+            let mut header = lines.next()?.split('\x1f');
+            let at = header
+                .next()?
+                .parse()
+                .ok()
+                .and_then(|secs| DateTime::from_timestamp(secs, 0))?;
+            let name = header.next()?.to_string();
+            let email = header.next()?.to_lowercase();
+            let files = lines
+                .filter(|line| !line.is_empty())
+                .map(String::from)
+                .collect();
+            Some(Commit {
+                at,
+                name,
+                email,
+                files,
+            })
+        })
+        .collect()
+}
+
+fn summarize(commits: &[Commit], since: NaiveDate, weeks: u32) -> GitActivity {
+    let mut per_week: Vec<WeekActivity> = (0..weeks.max(1) as i64)
+        .map(|week| WeekActivity {
+            week_start: since + Duration::weeks(week),
+            commits: 0,
+        })
+        .collect();
+    let mut authors: HashMap<&str, AuthorActivity> = HashMap::new();
+    let mut directories: BTreeMap<String, usize> = BTreeMap::new();
+
+    for commit in commits {
+        let week = (commit.at.date_naive() - since).num_days().div_euclid(7);
+        if let Some(bucket) = usize::try_from(week).ok().and_then(|w| per_week.get_mut(w)) {
+            bucket.commits += 1;
+        }
+
+        authors
+            .entry(&commit.email)
+            .or_insert_with(|| AuthorActivity {
+                name: commit.name.clone(),
+                email: commit.email.clone(),
+                commits: 0,
+            })
+            .commits += 1;
+
+        let touched: HashSet<String> = commit.files.iter().map(|f| directory_of(f)).collect();
+        for directory in touched {
+            *directories.entry(directory).or_default() += 1;
+        }
+    }
+
+    let mut authors: Vec<AuthorActivity> = authors.into_values().collect();
+    authors.sort_by(|a, b| b.commits.cmp(&a.commits).then(a.name.cmp(&b.name)));
+    let mut hot_directories: Vec<DirectoryActivity> = directories
+        .into_iter()
+        .map(|(path, commits)| DirectoryActivity { path, commits })
+        .collect();
+    hot_directories.sort_by(|a, b| b.commits.cmp(&a.commits).then(a.path.cmp(&b.path)));
+
+    GitActivity {
+        branch: String::new(),
+        head: String::new(),
+        since,
+        weeks: per_week,
+        commits: commits.len(),
+        unique_authors: authors.len(),
+        authors,
+        hot_directories,
+        pr_merge_time: None,
+        pr_merge_time_error: None,
+        computed_at: Utc::now(),
+        cached: false,
+    }
+}
+
+/// The directory a changed file is grouped under; files at the root go
+/// under "."
+fn directory_of(file: &str) -> String {
+    let parts: Vec<&str> = file.split('/').collect();
+    if parts.len() < 2 {
+        return ".".to_string();
+    }
+    parts[..parts.len() - 1]
+        .iter()
+        .take(DIRECTORY_DEPTH)
+        .copied()
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn fetch_merged_prs(git: &GitManager, trunk: &Path, base: &str) -> Result<Vec<MergedPullRequest>> {
+    let list = git.merged_prs(trunk, base, PR_LIMIT, &["number", "createdAt", "mergedAt"])?;
+    Ok(parse_merged_prs(&list))
+}
+
+/// Read `gh pr list --json number,createdAt,mergedAt` output
+fn parse_merged_prs(list: &Value) -> Vec<MergedPullRequest> {
+    let time = |value: &Value| {
+        value
+            .as_str()
+            .and_then(|text| DateTime::parse_from_rfc3339(text).ok())
+            .map(|at| at.with_timezone(&Utc))
+    };
+    list.as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|pr| {
+            Some(MergedPullRequest {
+                number: pr["number"].as_u64()?,
+                created_at: time(&pr["createdAt"])?,
+                merged_at: time(&pr["mergedAt"])?,
+            })
+        })
+        .collect()
+}
+
+/// Open-to-merge time of the PRs merged since `since`
+fn merge_time(prs: &[MergedPullRequest], since: NaiveDate) -> Option<MergeTime> {
+    let mut hours: Vec<f64> = prs
+        .iter()
+        .filter(|pr| pr.merged_at.date_naive() >= since)
+        .map(|pr| (pr.merged_at - pr.created_at).num_minutes() as f64 / 60.0)
+        .collect();
+    if hours.is_empty() {
+        return None;
+    }
+    hours.sort_by(f64::total_cmp);
+    let middle = hours.len() / 2;
+    let median_hours = if hours.len().is_multiple_of(2) {
+        (hours[middle - 1] + hours[middle]) / 2.0
+    } else {
+        hours[middle]
+    };
+    Some(MergeTime {
+        pull_requests: hours.len(),
+        average_hours: hours.iter().sum::<f64>() / hours.len() as f64,
+        median_hours,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn day(text: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn summarizes_commits_per_week_author_and_directory() {
+        let since = first_day(day("2026-10-15"), 2);
+        assert_eq!(since, day("2026-10-05"));
+
+        let at = |text: &str| {
+            DateTime::parse_from_rfc3339(text)
+                .unwrap()
+                .timestamp()
+                .to_string()
+        };
+        let log = format!(
+            "\x1e{}\x1fAda\x1fADA@example.com\n\nsrc/git/log.rs\nsrc/git/diff.rs\nREADME.md\n\
+             \x1e{}\x1fBob\x1fbob@example.com\n\nsrc/main.rs\n\
+             \x1e{}\x1fAda\x1fada@example.com\n\nsrc/git/log.rs\n",
+            at("2026-10-14T09:00:00Z"),
+            at("2026-10-13T09:00:00Z"),
+            at("2026-10-06T09:00:00Z"),
+        );
+        let activity = summarize(&parse_log(&log), since, 2);
+
+        assert_eq!(activity.commits, 3);
+        assert_eq!(
+            activity.weeks,
+            [
+                WeekActivity {
+                    week_start: day("2026-10-05"),
+                    commits: 1
+                },
+                WeekActivity {
+                    week_start: day("2026-10-12"),
+                    commits: 2
+                },
+            ]
+        );
+        assert_eq!(activity.unique_authors, 2);
+        assert_eq!(
+            (
+                activity.authors[0].name.as_str(),
+                activity.authors[0].commits
+            ),
+            ("Ada", 2)
+        );
+        let directories: Vec<(&str, usize)> = activity
+            .hot_directories
+            .iter()
+            .map(|d| (d.path.as_str(), d.commits))
+            .collect();
+        assert_eq!(directories, [("src/git", 2), (".", 1), ("src", 1)]);
+    }
+
+    #[test]
+    fn merge_time_of_prs_merged_in_the_window() {
+        let list = json!([
+            { "number": 1, "createdAt": "2026-10-06T00:00:00Z", "mergedAt": "2026-10-06T10:00:00Z" },
+            { "number": 2, "createdAt": "2026-10-07T00:00:00Z", "mergedAt": "2026-10-09T00:00:00Z" },
+            { "number": 3, "createdAt": "2026-10-08T00:00:00Z", "mergedAt": "2026-10-08T02:00:00Z" },
+            { "number": 4, "createdAt": "2026-09-01T00:00:00Z", "mergedAt": "2026-09-02T00:00:00Z" },
+            { "number": 5, "createdAt": "2026-10-08T00:00:00Z", "mergedAt": null },
+        ]);
+        let prs = parse_merged_prs(&list);
+        assert_eq!(prs.len(), 4);
+
+        let time = merge_time(&prs, day("2026-10-05")).unwrap();
+        assert_eq!(time.pull_requests, 3);
+        assert_eq!(time.average_hours, 20.0);
+        assert_eq!(time.median_hours, 10.0);
+        assert!(merge_time(&prs, day("2026-11-01")).is_none());
+    }
+}
@@ -0,0 +1,223 @@
+//! Retry with exponential backoff for flaky network operations: git
+//! fetch/push, `gh` invocations and GitHub API requests
+
+use anyhow::Result;
+use colored::*;
+use std::future::Future;
+use std::time::Duration;
+use tracing::Instrument;
+
+use crate::config::RetrySettings;
+use crate::error::ImiError;
+
+/// Run a blocking operation, retrying transient failures per `settings`
+pub fn retry<T>(
+    settings: &RetrySettings,
+    what: &str,
+    mut op: impl FnMut() -> Result<T>,
+) -> Result<T> {
+    let _span = tracing::info_span!("network", detail = what).entered();
+    let mut attempt = 1;
+    loop {
+        match op() {
+            Err(e) if attempt < settings.max_attempts && is_transient(&e) => {
+                let delay = settings.delay(attempt);
+                log_retry(settings, what, attempt, &e, delay);
+                sleep_blocking(delay);
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Wait between blocking attempts. On a multi-threaded tokio runtime the
+/// worker hands its other tasks off first, so async work keeps running.
+fn sleep_blocking(delay: Duration) {
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == tokio::runtime::RuntimeFlavor::MultiThread => {
+            tokio::task::block_in_place(|| std::thread::sleep(delay))
+        }
+        _ => std::thread::sleep(delay),
+    }
+}
+
+/// Async counterpart of [`retry`]
+pub async fn retry_async<T, F, Fut>(settings: &RetrySettings, what: &str, mut op: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut attempt = 1;
+    async {
+        loop {
+            match op().await {
+                Err(e) if attempt < settings.max_attempts && is_transient(&e) => {
+                    let delay = settings.delay(attempt);
+                    log_retry(settings, what, attempt, &e, delay);
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+    .instrument(tracing::info_span!("network", detail = what))
+    .await
+}
+
+/// Failures that may succeed on another attempt. Authentication errors and
+/// "not found" style answers never qualify.
+pub fn is_transient(err: &anyhow::Error) -> bool {
+    for cause in err.chain() {
+        if let Some(imi_error) = cause.downcast_ref::<ImiError>() {
+            match imi_error {
+                ImiError::RemoteUnreachable { .. } => return true,
+                ImiError::RemoteAuthFailed { .. } => return false,
+                _ => {}
+            }
+        }
+        if let Some(e) = cause.downcast_ref::<reqwest::Error>() {
+            if let Some(status) = e.status() {
+                return status.is_server_error()
+                    || status == reqwest::StatusCode::TOO_MANY_REQUESTS;
+            }
+            return e.is_timeout() || e.is_connect();
+        }
+        if let Some(e) = cause.downcast_ref::<git2::Error>() {
+            if e.code() == git2::ErrorCode::Auth {
+                return false;
+            }
+            if matches!(e.class(), git2::ErrorClass::Net | git2::ErrorClass::Http) {
+                return true;
+            }
+        }
+    }
+
+    is_transient_message(&format!("{:#}", err))
+}
+
+/// Recognize transient failures from command output (e.g. `gh` stderr)
+pub fn is_transient_message(message: &str) -> bool {
+    const MARKERS: &[&str] = &[
+        "timed out",
+        "timeout",
+        "connection reset",
+        "connection refused",
+        "could not resolve host",
+        "temporary failure in name resolution",
+        "network is unreachable",
+        "early eof",
+        "unexpected eof",
+        "the remote end hung up unexpectedly",
+        "bad gateway",
+        "service unavailable",
+        "gateway time",
+        "rate limit",
+    ];
+
+    let message = message.to_lowercase();
+    MARKERS.iter().any(|marker| message.contains(marker))
+}
+
+/// Whether retries and similar diagnostics are logged (IMI_VERBOSE=1)
+pub fn verbose() -> bool {
+    std::env::var("IMI_VERBOSE")
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+fn log_retry(
+    settings: &RetrySettings,
+    what: &str,
+    attempt: u32,
+    err: &anyhow::Error,
+    delay: Duration,
+) {
+    if verbose() {
+        eprintln!(
+            "{} {} failed (attempt {}/{}): {:#}; retrying in {}ms",
+            "↻".bright_yellow(),
+            what,
+            attempt,
+            settings.max_attempts,
+            err,
+            delay.as_millis()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn fast() -> RetrySettings {
+        RetrySettings {
+            initial_delay_ms: 1,
+            max_delay_ms: 1,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn retries_transient_failures_until_success() {
+        let calls = Cell::new(0);
+        let result = retry(&fast(), "fetch", || {
+            calls.set(calls.get() + 1);
+            if calls.get() < 3 {
+                Err(anyhow::anyhow!("Connection reset by peer"))
+            } else {
+                Ok(calls.get())
+            }
+        });
+        assert_eq!(result.unwrap(), 3);
+    }
+
+    #[test]
+    fn gives_up_after_max_attempts_and_skips_permanent_errors() {
+        let calls = Cell::new(0);
+        let result: Result<()> = retry(&fast(), "fetch", || {
+            calls.set(calls.get() + 1);
+            Err(ImiError::RemoteUnreachable {
+                remote: "origin".to_string(),
+                message: "no route".to_string(),
+            }
+            .into())
+        });
+        assert!(result.is_err());
+        assert_eq!(calls.get(), 3);
+
+        calls.set(0);
+        let result: Result<()> = retry(&fast(), "push", || {
+            calls.set(calls.get() + 1);
+            Err(anyhow::anyhow!("PR #42 does not exist"))
+        });
+        assert!(result.is_err());
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn blocking_retries_inside_the_runtime_leave_other_tasks_running() {
+        let settings = RetrySettings {
+            initial_delay_ms: 300,
+            max_delay_ms: 300,
+            max_attempts: 2,
+            ..Default::default()
+        };
+        let started = std::time::Instant::now();
+        // Both tasks share the only worker
+        let retrying = tokio::spawn(async move {
+            let result: Result<()> = retry(&settings, "fetch", || {
+                Err(anyhow::anyhow!("Connection reset by peer"))
+            });
+            result.is_err()
+        });
+        let ticker = tokio::spawn(async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            std::time::Instant::now()
+        });
+        assert!(ticker.await.unwrap() - started < Duration::from_millis(250));
+        assert!(retrying.await.unwrap());
+    }
+}
@@ -0,0 +1,363 @@
+//! REVIEW_CONTEXT.json: what a reviewer (often an LLM agent) needs about a
+//! pull request up front, written into its review worktree so nothing has to
+//! be queried from GitHub again: the PR's metadata, the base and head SHAs,
+//! and every changed file with its diffstat.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::git::GitManager;
+
+pub const FILE_NAME: &str = "REVIEW_CONTEXT.json";
+
+/// Bumped when fields change meaning
+const FORMAT_VERSION: u32 = 1;
+
+/// `gh pr view --json` fields read into [`PullRequestInfo`]
+const PR_FIELDS: &[&str] = &[
+    "number",
+    "title",
+    "body",
+    "url",
+    "author",
+    "state",
+    "isDraft",
+    "labels",
+    "baseRefName",
+    "headRefName",
+    "baseRefOid",
+    "headRefOid",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewContext {
+    pub version: u32,
+    pub generated_at: DateTime<Utc>,
+    pub repo: String,
+    pub pull_request: PullRequestInfo,
+    /// Tip of the base branch according to GitHub
+    pub base_sha: String,
+    /// Commit checked out in the worktree
+    pub head_sha: String,
+    /// Where the PR branched off; the diffstats are against it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub merge_base: Option<String>,
+    pub files: Vec<ChangedFile>,
+    pub totals: DiffTotals,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PullRequestInfo {
+    pub number: u32,
+    pub title: String,
+    pub url: String,
+    pub author: String,
+    pub state: String,
+    pub draft: bool,
+    pub labels: Vec<String>,
+    pub base_ref: String,
+    pub head_ref: String,
+    pub body: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangedFile {
+    pub path: String,
+    /// added, modified, deleted, renamed, copied or type_changed
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_path: Option<String>,
+    /// None for binary files
+    pub additions: Option<u64>,
+    pub deletions: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiffTotals {
+    pub files: usize,
+    pub additions: u64,
+    pub deletions: u64,
+}
+
+impl PullRequestInfo {
+    /// Read `gh pr view --json` output
+    fn from_gh(view: &Value) -> Result<(Self, String, String)> {
+        let text = |field: &str| view[field].as_str().unwrap_or_default().to_string();
+        let info = Self {
+            number: view["number"]
+                .as_u64()
+                .and_then(|n| u32::try_from(n).ok())
+                .context("gh pr view returned no PR number")?,
+            title: text("title"),
+            url: text("url"),
+            author: view["author"]["login"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            state: text("state").to_lowercase(),
+            draft: view["isDraft"].as_bool().unwrap_or(false),
+            labels: view["labels"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|label| label["name"].as_str().map(String::from))
+                .collect(),
+            base_ref: text("baseRefName"),
+            head_ref: text("headRefName"),
+            body: text("body"),
+        };
+        Ok((info, text("baseRefOid"), text("headRefOid")))
+    }
+}
+
+impl ReviewContext {
+    /// Gather the context for PR `pr_number`, checked out at `worktree_path`
+    pub fn collect(
+        git: &GitManager,
+        worktree_path: &Path,
+        repo_name: &str,
+        pr_number: u32,
+    ) -> Result<Self> {
+        let view = git.pr_view(worktree_path, pr_number, PR_FIELDS)?;
+        let (pull_request, base_sha, github_head) = PullRequestInfo::from_gh(&view)?;
+        let head_sha = git
+            .execute_git_command(worktree_path, &["rev-parse", "HEAD"])
+            .map(|sha| sha.trim().to_string())
+            .unwrap_or(github_head);
+
+        let merge_base = find_merge_base(git, worktree_path, &base_sha, &pull_request.base_ref);
+        let files = match &merge_base {
+            Some(base) => changed_files(git, worktree_path, base)?,
+            None => Vec::new(),
+        };
+        let totals = DiffTotals {
+            files: files.len(),
+            additions: files.iter().filter_map(|f| f.additions).sum(),
+            deletions: files.iter().filter_map(|f| f.deletions).sum(),
+        };
+
+        Ok(Self {
+            version: FORMAT_VERSION,
+            generated_at: Utc::now(),
+            repo: repo_name.to_string(),
+            pull_request,
+            base_sha,
+            head_sha,
+            merge_base,
+            files,
+            totals,
+        })
+    }
+
+    /// Write the file into the worktree, keeping it out of `git status`
+    pub fn write(&self, git: &GitManager, worktree_path: &Path) -> Result<PathBuf> {
+        let path = worktree_path.join(FILE_NAME);
+        let contents =
+            serde_json::to_string_pretty(self).context("Failed to serialize review context")?;
+        std::fs::write(&path, contents + "\n")
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        git.exclude_untracked(worktree_path, &format!("/{}", FILE_NAME))?;
+        Ok(path)
+    }
+}
+
+/// Merge base of HEAD and the PR's base, fetching the base when the trunk
+/// hasn't seen it yet
+fn find_merge_base(
+    git: &GitManager,
+    worktree_path: &Path,
+    base_sha: &str,
+    base_ref: &str,
+) -> Option<String> {
+    let merge_base = |base: &str| {
+        git.execute_git_command(worktree_path, &["merge-base", "HEAD", base])
+            .ok()
+            .map(|sha| sha.trim().to_string())
+            .filter(|sha| !sha.is_empty())
+    };
+    let remote_base = format!("origin/{}", base_ref);
+
+    let candidates = [base_sha, remote_base.as_str()];
+    if let Some(found) = candidates
+        .iter()
+        .filter(|base| !base.is_empty())
+        .find_map(|base| merge_base(base))
+    {
+        return Some(found);
+    }
+    let _ = git.run_git(worktree_path, &["fetch", "origin", base_ref]);
+    candidates
+        .iter()
+        .filter(|base| !base.is_empty())
+        .find_map(|base| merge_base(base))
+}
+
+/// Files changed between `base` and HEAD, with their diffstats
+pub fn changed_files(
+    git: &GitManager,
+    worktree_path: &Path,
+    base: &str,
+) -> Result<Vec<ChangedFile>> {
+    let range = format!("{}..HEAD", base);
+    let name_status = git
+        .execute_git_command(
+            worktree_path,
+            &["diff", "-z", "-M", "--name-status", &range],
+        )
+        .context("Failed to list the PR's changed files")?;
+    let numstat = git
+        .execute_git_command(worktree_path, &["diff", "-z", "-M", "--numstat", &range])
+        .context("Failed to compute the PR's diffstats")?;
+    Ok(parse_diff(&name_status, &numstat))
+}
+
+/// Combine `git diff -z --name-status` and `git diff -z --numstat` output
+pub fn parse_diff(name_status: &str, numstat: &str) -> Vec<ChangedFile> {
+    let mut stats: HashMap<&str, (Option<u64>, Option<u64>)> = HashMap::new();
+    let mut fields = numstat.split('\0');
+    while let Some(entry) = fields.next() {
+        let mut parts = entry.splitn(3, '\t');
+        let (Some(added), Some(deleted), Some(path)) = (parts.next(), parts.next(), parts.next())
+        else {
+            continue;
+        };
+        // Renames leave the path empty and give old and new path next
+        let path = if path.is_empty() {
+            fields.next();
+            fields.next().unwrap_or_default()
+        } else {
+            path
+        };
+        stats.insert(path, (added.parse().ok(), deleted.parse().ok()));
+    }
+
+    let mut files = Vec::new();
+    let mut fields = name_status.split('\0').filter(|field| !field.is_empty());
+    while let Some(code) = fields.next() {
+        let status = match code.chars().next() {
+            Some('A') => "added",
+            Some('D') => "deleted",
+            Some('R') => "renamed",
+            Some('C') => "copied",
+            Some('T') => "type_changed",
+            _ => "modified",
+        };
+        let previous_path = match status {
+            "renamed" | "copied" => fields.next().map(String::from),
+            _ => None,
+        };
+        let Some(path) = fields.next() else {
+            break;
+        };
+        let (additions, deletions) = stats.get(path).copied().unwrap_or((None, None));
+        files.push(ChangedFile {
+            path: path.to_string(),
+            status: status.to_string(),
+            previous_path,
+            additions,
+            deletions,
+        });
+    }
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::process::Command;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let output = Command::new("git")
+            .current_dir(dir)
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?}: {:?}", args, output);
+    }
+
+    #[test]
+    fn reads_gh_pr_view_output() {
+        let view = json!({
+            "number": 42,
+            "title": "Add login",
+            "body": "Closes #7",
+            "url": "https://github.com/acme/api/pull/42",
+            "author": { "login": "octocat" },
+            "state": "OPEN",
+            "isDraft": true,
+            "labels": [{ "name": "auth" }, { "name": "needs-review" }],
+            "baseRefName": "main",
+            "headRefName": "feat/login",
+            "baseRefOid": "aaa",
+            "headRefOid": "bbb",
+        });
+        let (info, base, head) = PullRequestInfo::from_gh(&view).unwrap();
+        assert_eq!(info.number, 42);
+        assert_eq!(info.author, "octocat");
+        assert_eq!(info.state, "open");
+        assert!(info.draft);
+        assert_eq!(info.labels, ["auth", "needs-review"]);
+        assert_eq!((base.as_str(), head.as_str()), ("aaa", "bbb"));
+        assert!(PullRequestInfo::from_gh(&json!({})).is_err());
+    }
+
+    #[test]
+    fn diffstats_per_file_including_renames_and_binaries() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path();
+        git(repo, &["init", "-q", "-b", "main"]);
+        git(repo, &["config", "user.email", "t@example.com"]);
+        git(repo, &["config", "user.name", "T"]);
+        let long: String = (1..=20).map(|i| format!("line {}\n", i)).collect();
+        std::fs::write(repo.join("old.txt"), &long).unwrap();
+        std::fs::write(repo.join("edit.txt"), "a\nb\n").unwrap();
+        std::fs::write(repo.join("gone.txt"), "x\n").unwrap();
+        git(repo, &["add", "."]);
+        git(repo, &["commit", "-q", "-m", "base"]);
+        let base = String::from_utf8(
+            Command::new("git")
+                .current_dir(repo)
+                .args(["rev-parse", "HEAD"])
+                .output()
+                .unwrap()
+                .stdout,
+        )
+        .unwrap();
+
+        git(repo, &["mv", "old.txt", "new name.txt"]);
+        std::fs::write(repo.join("edit.txt"), "a\nc\nd\n").unwrap();
+        std::fs::remove_file(repo.join("gone.txt")).unwrap();
+        std::fs::write(repo.join("logo.bin"), [0u8, 159, 146, 150]).unwrap();
+        git(repo, &["add", "-A"]);
+        git(repo, &["commit", "-q", "-m", "change"]);
+
+        let git_manager = GitManager::new();
+        let files = changed_files(&git_manager, repo, base.trim()).unwrap();
+        let by_path = |path: &str| files.iter().find(|f| f.path == path).unwrap().clone();
+
+        assert_eq!(files.len(), 4);
+        assert_eq!(
+            by_path("edit.txt"),
+            ChangedFile {
+                path: "edit.txt".to_string(),
+                status: "modified".to_string(),
+                previous_path: None,
+                additions: Some(2),
+                deletions: Some(1),
+            }
+        );
+        let renamed = by_path("new name.txt");
+        assert_eq!(renamed.status, "renamed");
+        assert_eq!(renamed.previous_path.as_deref(), Some("old.txt"));
+        assert_eq!((renamed.additions, renamed.deletions), (Some(0), Some(0)));
+        assert_eq!(by_path("gone.txt").status, "deleted");
+        let binary = by_path("logo.bin");
+        assert_eq!(binary.status, "added");
+        assert_eq!(binary.additions, None);
+    }
+}
@@ -0,0 +1,166 @@
+//! Read-only HTTP status API (`imi serve --http`, behind the `http` feature)
+//! so dashboards and browser extensions can follow worktrees without a shell
+
+use anyhow::{Context, Result};
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use chrono::Utc;
+use futures_util::stream::{self, Stream, StreamExt};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config::WorktreeSort;
+use crate::metrics;
+use crate::ops::{self, Imi, ListOptions};
+
+/// How often `/events` polls the registry for new activity
+const EVENT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Serve the API on `addr` until the process is stopped
+pub async fn serve(imi: Imi, addr: SocketAddr) -> Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .context(format!("Failed to listen on {}", addr))?;
+    axum::serve(listener, router(imi))
+        .await
+        .context("HTTP server stopped")
+}
+
+/// Serve `GET /metrics` for Prometheus on `addr` until Ctrl+C
+pub async fn serve_metrics(imi: Imi, addr: SocketAddr) -> Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .context(format!("Failed to listen on {}", addr))?;
+    let router = Router::new()
+        .route("/metrics", get(prometheus))
+        .with_state(Arc::new(imi));
+    axum::serve(listener, router)
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await
+        .context("Metrics server stopped")
+}
+
+/// `GET /repos`, `/worktrees?repo=&tag=`, `/status/<repo>` and `/events` (SSE)
+pub fn router(imi: Imi) -> Router {
+    Router::new()
+        .route("/repos", get(repos))
+        .route("/worktrees", get(worktrees))
+        .route("/status/:repo", get(status))
+        .route("/events", get(events))
+        .with_state(Arc::new(imi))
+}
+
+type ApiResult = std::result::Result<Json<Value>, ApiError>;
+
+struct ApiError(StatusCode, String);
+
+impl From<anyhow::Error> for ApiError {
+    fn from(error: anyhow::Error) -> Self {
+        Self(StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", error))
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "error": self.1 }))).into_response()
+    }
+}
+
+async fn repos(State(imi): State<Arc<Imi>>) -> ApiResult {
+    Ok(Json(json!(ops::list_repositories(&imi).await?)))
+}
+
+#[derive(Debug, Deserialize)]
+struct WorktreeQuery {
+    repo: Option<String>,
+    #[serde(default)]
+    include_inactive: bool,
+    tag: Option<String>,
+    sort: Option<WorktreeSort>,
+}
+
+async fn worktrees(State(imi): State<Arc<Imi>>, Query(query): Query<WorktreeQuery>) -> ApiResult {
+    let options = ListOptions {
+        repo: query.repo,
+        include_inactive: query.include_inactive,
+        tag: query.tag,
+        sort: query.sort,
+    };
+    Ok(Json(json!(ops::list_worktrees(&imi, options).await?)))
+}
+
+async fn status(State(imi): State<Arc<Imi>>, Path(repo): Path<String>) -> ApiResult {
+    let Some(repository) = imi.database().get_repository(&repo).await? else {
+        return Err(ApiError(
+            StatusCode::NOT_FOUND,
+            format!("Repository '{}' is not registered", repo),
+        ));
+    };
+    let options = ListOptions {
+        repo: Some(repo.clone()),
+        include_inactive: false,
+        tag: None,
+        sort: None,
+    };
+
+    Ok(Json(json!({
+        "repository": repository,
+        "worktrees": ops::list_worktrees(&imi, options).await?,
+        "alerts": ops::worktree_alerts(&imi, Some(&repo)).await?,
+    })))
+}
+
+async fn prometheus(State(imi): State<Arc<Imi>>) -> std::result::Result<Response, ApiError> {
+    let snapshot = metrics::collect(&imi).await?;
+    Ok((
+        [(
+            axum::http::header::CONTENT_TYPE,
+            "text/plain; version=0.0.4; charset=utf-8",
+        )],
+        metrics::render(&snapshot),
+    )
+        .into_response())
+}
+
+/// Agent activity logged after the client connected, one SSE event per
+/// entry named after its activity type
+async fn events(
+    State(imi): State<Arc<Imi>>,
+) -> Sse<impl Stream<Item = std::result::Result<Event, Infallible>>> {
+    let batches = stream::unfold((imi, Utc::now()), |(imi, since)| async move {
+        tokio::time::sleep(EVENT_POLL_INTERVAL).await;
+        let (events, since) = match imi.database().activities_since(since).await {
+            Ok(activities) => {
+                let since = activities.last().map_or(since, |a| a.created_at);
+                let events = activities
+                    .iter()
+                    .map(|activity| {
+                        Event::default()
+                            .event(&activity.activity_type)
+                            .json_data(activity)
+                            .unwrap_or_default()
+                    })
+                    .collect();
+                (events, since)
+            }
+            Err(e) => (
+                vec![Event::default().event("error").data(format!("{:#}", e))],
+                since,
+            ),
+        };
+        Some((events, (imi, since)))
+    });
+
+    Sse::new(batches.flat_map(|events| stream::iter(events.into_iter().map(Ok))))
+        .keep_alive(KeepAlive::default())
+}
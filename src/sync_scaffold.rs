@@ -0,0 +1,177 @@
+//! Files a repository's sync directories start with. A scaffold directory
+//! holds `user/` and `local/` trees copied into `sync/user` and `sync/local`
+//! with `{{repo_name}}`, `{{language}}` and `{{date}}` filled in; a
+//! per-repository scaffold overrides files of the same name.
+
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::worktree::render_copy_template;
+
+/// Written to `sync/user` when no scaffold provides any files
+const DEFAULT_USER_FILES: [(&str, &str); 2] = [
+    (
+        "coding-rules.md",
+        "# Coding Rules\n\n## Style Guidelines\n\n## Best Practices\n",
+    ),
+    (
+        "stack-specific.md",
+        "# Stack-Specific Guidelines\n\n## Frontend\n\n## Backend\n\n## Database\n",
+    ),
+];
+
+/// Marker files and the language they indicate, first match wins
+const LANGUAGE_MARKERS: [(&str, &str); 10] = [
+    ("Cargo.toml", "rust"),
+    ("go.mod", "go"),
+    ("tsconfig.json", "typescript"),
+    ("package.json", "javascript"),
+    ("pyproject.toml", "python"),
+    ("requirements.txt", "python"),
+    ("Gemfile", "ruby"),
+    ("pom.xml", "java"),
+    ("build.gradle", "java"),
+    ("mix.exs", "elixir"),
+];
+
+/// The main language of the checkout at `dir`, by its build files
+pub fn detect_language(dir: &Path) -> Option<&'static str> {
+    LANGUAGE_MARKERS
+        .iter()
+        .find(|(marker, _)| dir.join(marker).is_file())
+        .map(|(_, language)| *language)
+}
+
+/// Files under `subdir` of each scaffold, keyed by relative path; later
+/// scaffolds win
+fn scaffold_files(scaffolds: &[PathBuf], subdir: &str) -> BTreeMap<PathBuf, PathBuf> {
+    let mut files = BTreeMap::new();
+    for scaffold in scaffolds {
+        let root = scaffold.join(subdir);
+        for entry in walkdir::WalkDir::new(&root)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+        {
+            if let Ok(relative) = entry.path().strip_prefix(&root) {
+                files.insert(relative.to_path_buf(), entry.path().to_path_buf());
+            }
+        }
+    }
+    files
+}
+
+/// Fill `sync_dir` from the `subdir` tree of the scaffolds, skipping files
+/// that already exist; returns the paths written, relative to `sync_dir`
+pub fn apply(
+    scaffolds: &[PathBuf],
+    subdir: &str,
+    sync_dir: &Path,
+    vars: &[(&str, &str)],
+) -> Result<Vec<PathBuf>> {
+    let mut contents: BTreeMap<PathBuf, Vec<u8>> = BTreeMap::new();
+    for (relative, source) in scaffold_files(scaffolds, subdir) {
+        let bytes =
+            std::fs::read(&source).context(format!("Failed to read {}", source.display()))?;
+        contents.insert(relative, bytes);
+    }
+    if contents.is_empty() && subdir == "user" {
+        for (name, text) in DEFAULT_USER_FILES {
+            contents.insert(PathBuf::from(name), text.as_bytes().to_vec());
+        }
+    }
+
+    let mut written = Vec::new();
+    for (relative, bytes) in contents {
+        let target = sync_dir.join(&relative);
+        if target.exists() {
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let bytes = match String::from_utf8(bytes) {
+            Ok(text) => render_copy_template(&text, vars).into_bytes(),
+            Err(e) => e.into_bytes(),
+        };
+        std::fs::write(&target, bytes).context(format!("Failed to write {}", target.display()))?;
+        written.push(relative);
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn copies_scaffolds_with_repo_overrides_and_keeps_existing_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let user_scaffold = dir.path().join("scaffold");
+        let repo_scaffold = dir.path().join("api-scaffold");
+        std::fs::create_dir_all(user_scaffold.join("user/rules")).unwrap();
+        std::fs::write(
+            user_scaffold.join("user/coding-rules.md"),
+            "# {{repo_name}} ({{ language }})",
+        )
+        .unwrap();
+        std::fs::write(user_scaffold.join("user/rules/review.md"), "review").unwrap();
+        std::fs::create_dir_all(repo_scaffold.join("user/rules")).unwrap();
+        std::fs::write(repo_scaffold.join("user/rules/review.md"), "api review").unwrap();
+
+        let sync_dir = dir.path().join("sync/user");
+        std::fs::create_dir_all(&sync_dir).unwrap();
+        std::fs::write(sync_dir.join("notes.md"), "mine").unwrap();
+
+        let vars = [("repo_name", "api"), ("language", "rust")];
+        let scaffolds = vec![user_scaffold, repo_scaffold];
+        let written = apply(&scaffolds, "user", &sync_dir, &vars).unwrap();
+        assert_eq!(
+            written,
+            vec![
+                PathBuf::from("coding-rules.md"),
+                PathBuf::from("rules/review.md")
+            ]
+        );
+        assert_eq!(
+            std::fs::read_to_string(sync_dir.join("coding-rules.md")).unwrap(),
+            "# api (rust)"
+        );
+        assert_eq!(
+            std::fs::read_to_string(sync_dir.join("rules/review.md")).unwrap(),
+            "api review"
+        );
+
+        // Edited files are never overwritten
+        std::fs::write(sync_dir.join("coding-rules.md"), "edited").unwrap();
+        assert!(apply(&scaffolds, "user", &sync_dir, &vars)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            std::fs::read_to_string(sync_dir.join("coding-rules.md")).unwrap(),
+            "edited"
+        );
+    }
+
+    #[test]
+    fn falls_back_to_default_files_without_a_scaffold() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = vec![dir.path().join("no-scaffold")];
+        let written = apply(&missing, "user", dir.path(), &[]).unwrap();
+        assert_eq!(written.len(), 2);
+        assert!(apply(&missing, "local", &dir.path().join("local"), &[])
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn detects_language_from_build_files() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(detect_language(dir.path()), None);
+        std::fs::write(dir.path().join("package.json"), "{}").unwrap();
+        assert_eq!(detect_language(dir.path()), Some("javascript"));
+        std::fs::write(dir.path().join("tsconfig.json"), "{}").unwrap();
+        assert_eq!(detect_language(dir.path()), Some("typescript"));
+    }
+}
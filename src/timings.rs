@@ -0,0 +1,316 @@
+//! `--timings`: a report of where a command spent its time, printed to
+//! stderr when it finishes. DB, git, network and filesystem work runs in
+//! `db`/`git`/`network`/`fs` tracing spans (sqlx reports its queries as
+//! `sqlx::query` events); each phase is charged its own time, so a `git
+//! fetch` inside a `network` retry counts once.
+
+use colored::*;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::{Event, Metadata, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::registry::LookupSpan;
+
+/// Span names that make up the report, in display order
+pub const PHASES: &[&str] = &["db", "git", "network", "fs"];
+
+/// Operations listed under "Slowest"
+const SLOWEST: usize = 10;
+
+/// One finished operation
+#[derive(Debug, Clone)]
+pub struct Operation {
+    pub phase: &'static str,
+    pub detail: String,
+    /// Wall time from start to finish
+    pub elapsed: Duration,
+    /// `elapsed` minus time spent in nested operations
+    pub own: Duration,
+}
+
+#[derive(Debug, Default)]
+pub struct Recorder {
+    operations: Vec<Operation>,
+}
+
+impl Recorder {
+    /// Per-phase own time and operation count
+    pub fn totals(&self) -> BTreeMap<&'static str, (Duration, usize)> {
+        let mut totals = BTreeMap::new();
+        for op in &self.operations {
+            let (time, count) = totals.entry(op.phase).or_insert((Duration::ZERO, 0));
+            *time += op.own;
+            *count += 1;
+        }
+        totals
+    }
+
+    /// The report for a command that ran for `total`
+    pub fn render(&self, total: Duration) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "{} {}",
+            "⏱️".bright_cyan(),
+            format!("Timings ({})", format_duration(total)).bold()
+        );
+
+        let totals = self.totals();
+        let mut attributed = Duration::ZERO;
+        for phase in PHASES {
+            let (time, count) = totals.get(phase).copied().unwrap_or_default();
+            attributed += time;
+            let _ = writeln!(
+                out,
+                "   {:<8} {:>9}  {} {}",
+                phase,
+                format_duration(time),
+                count,
+                unit(phase, count)
+            );
+        }
+        let _ = writeln!(
+            out,
+            "   {:<8} {:>9}  {}",
+            "other",
+            format_duration(total.saturating_sub(attributed)),
+            "(CPU, output, waiting)".bright_black()
+        );
+        if attributed > total {
+            let _ = writeln!(
+                out,
+                "   {}",
+                "Phases overlap: some work ran concurrently".bright_black()
+            );
+        }
+
+        let mut slowest: Vec<&Operation> = self.operations.iter().collect();
+        slowest.sort_by_key(|op| std::cmp::Reverse(op.elapsed));
+        if !slowest.is_empty() {
+            let _ = writeln!(out, "   {}", "Slowest:".bold());
+        }
+        for op in slowest.into_iter().take(SLOWEST) {
+            let _ = writeln!(
+                out,
+                "   {:>9}  {:<8} {}",
+                format_duration(op.elapsed),
+                op.phase,
+                op.detail.bright_black()
+            );
+        }
+        out
+    }
+}
+
+fn unit(phase: &str, count: usize) -> &'static str {
+    match (phase, count == 1) {
+        ("db", true) => "query",
+        ("db", false) => "queries",
+        ("network", true) => "request",
+        ("network", false) => "requests",
+        ("fs", true) => "scan",
+        ("fs", false) => "scans",
+        (_, true) => "operation",
+        (_, false) => "operations",
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    if duration >= Duration::from_secs(1) {
+        format!("{:.2}s", duration.as_secs_f64())
+    } else {
+        format!("{:.1}ms", duration.as_secs_f64() * 1000.0)
+    }
+}
+
+/// Start time and nested time of an open span
+struct Timing {
+    phase: &'static str,
+    detail: String,
+    start: Instant,
+    nested: Duration,
+}
+
+/// Records `db`/`git`/`network`/`fs` spans and sqlx queries into a [`Recorder`]
+pub struct TimingLayer {
+    recorder: Arc<Mutex<Recorder>>,
+}
+
+impl TimingLayer {
+    pub fn new(recorder: Arc<Mutex<Recorder>>) -> Self {
+        Self { recorder }
+    }
+
+    fn record(&self, operation: Operation) {
+        if let Ok(mut recorder) = self.recorder.lock() {
+            recorder.operations.push(operation);
+        }
+    }
+}
+
+fn phase_of(name: &str) -> Option<&'static str> {
+    PHASES.iter().copied().find(|phase| *phase == name)
+}
+
+fn is_query(metadata: &Metadata<'_>) -> bool {
+    metadata.is_event() && metadata.target() == "sqlx::query"
+}
+
+/// Pulls `detail` out of our spans and `summary`/`elapsed_secs` out of sqlx
+/// query events
+#[derive(Default)]
+struct Fields {
+    detail: Option<String>,
+    elapsed_secs: Option<f64>,
+}
+
+impl Visit for Fields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if matches!(field.name(), "detail" | "summary") {
+            self.detail = Some(value.to_string());
+        }
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        if field.name() == "elapsed_secs" {
+            self.elapsed_secs = Some(value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if matches!(field.name(), "detail" | "summary") {
+            self.detail = Some(format!("{:?}", value));
+        }
+    }
+}
+
+impl<S> Layer<S> for TimingLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn enabled(&self, metadata: &Metadata<'_>, _ctx: Context<'_, S>) -> bool {
+        (metadata.is_span() && phase_of(metadata.name()).is_some()) || is_query(metadata)
+    }
+
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(phase) = phase_of(attrs.metadata().name()) else {
+            return;
+        };
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut fields = Fields::default();
+        attrs.record(&mut fields);
+        span.extensions_mut().insert(Timing {
+            phase,
+            detail: fields.detail.unwrap_or_default(),
+            start: Instant::now(),
+            nested: Duration::ZERO,
+        });
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        if !is_query(event.metadata()) {
+            return;
+        }
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        let elapsed = Duration::from_secs_f64(fields.elapsed_secs.unwrap_or_default());
+        if let Some(parent) = ctx.lookup_current() {
+            if let Some(timing) = parent.extensions_mut().get_mut::<Timing>() {
+                timing.nested += elapsed;
+            }
+        }
+        self.record(Operation {
+            phase: "db",
+            detail: fields.detail.unwrap_or_default(),
+            elapsed,
+            own: elapsed,
+        });
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(timing) = span.extensions_mut().remove::<Timing>() else {
+            return;
+        };
+        let elapsed = timing.start.elapsed();
+        if let Some(parent) = span.parent() {
+            if let Some(parent_timing) = parent.extensions_mut().get_mut::<Timing>() {
+                parent_timing.nested += elapsed;
+            }
+        }
+        self.record(Operation {
+            phase: timing.phase,
+            detail: timing.detail,
+            elapsed,
+            own: elapsed.saturating_sub(timing.nested),
+        });
+    }
+}
+
+static INSTALLED: OnceLock<(Instant, Arc<Mutex<Recorder>>)> = OnceLock::new();
+
+/// Start recording for this process
+pub fn install() {
+    let recorder = Arc::new(Mutex::new(Recorder::default()));
+    let subscriber = tracing_subscriber::registry().with(TimingLayer::new(recorder.clone()));
+    if tracing::subscriber::set_global_default(subscriber).is_ok() {
+        let _ = INSTALLED.set((Instant::now(), recorder));
+    }
+}
+
+/// Print the report to stderr if [`install`] was called
+pub fn report() {
+    let Some((started, recorder)) = INSTALLED.get() else {
+        return;
+    };
+    if let Ok(recorder) = recorder.lock() {
+        eprint!("{}", recorder.render(started.elapsed()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn charges_each_phase_its_own_time() {
+        let recorder = Arc::new(Mutex::new(Recorder::default()));
+        let subscriber = tracing_subscriber::registry().with(TimingLayer::new(recorder.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let network = tracing::info_span!("network", detail = "git fetch origin").entered();
+            {
+                let _git = tracing::info_span!("git", detail = "git fetch").entered();
+                std::thread::sleep(Duration::from_millis(20));
+            }
+            drop(network);
+            tracing::debug!(
+                target: "sqlx::query",
+                summary = "select * from worktrees",
+                elapsed_secs = 0.5
+            );
+            let _ignored = tracing::info_span!("render").entered();
+        });
+
+        let recorder = recorder.lock().unwrap();
+        let totals = recorder.totals();
+        assert_eq!(totals["git"].1, 1);
+        assert!(totals["git"].0 >= Duration::from_millis(20));
+        assert!(totals["network"].0 < Duration::from_millis(20));
+        assert_eq!(totals["db"], (Duration::from_millis(500), 1));
+        assert!(!totals.contains_key("render"));
+
+        let report = recorder.render(Duration::from_secs(1));
+        assert!(report.contains("select * from worktrees"));
+        assert!(report.contains("1 query"));
+    }
+}
@@ -0,0 +1,202 @@
+//! Removed and closed worktree directories are moved into
+//! `<repo>/.iMi/trash` instead of being deleted, so a removal (uncommitted
+//! files included) can be undone until the retention period runs out
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// A trashed worktree directory; its metadata lives next to it as `<id>.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashEntry {
+    /// `<timestamp>-<worktree name>`, unique within the repository's trash
+    pub id: String,
+    pub repo_name: String,
+    pub worktree_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub worktree_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch_name: Option<String>,
+    /// HEAD when trashed, so the branch can be recreated if it was deleted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub head_commit: Option<String>,
+    pub original_path: PathBuf,
+    pub trashed_at: DateTime<Utc>,
+    /// Where the directory is now
+    #[serde(skip_deserializing)]
+    pub path: PathBuf,
+}
+
+impl TrashEntry {
+    pub fn new(repo_name: &str, worktree_name: &str, original_path: &Path) -> Self {
+        let trashed_at = Utc::now();
+        Self {
+            id: format!("{}-{}", trashed_at.format("%Y%m%dT%H%M%S"), worktree_name),
+            repo_name: repo_name.to_string(),
+            worktree_name: worktree_name.to_string(),
+            worktree_type: None,
+            branch_name: None,
+            head_commit: None,
+            original_path: original_path.to_path_buf(),
+            trashed_at,
+            path: PathBuf::new(),
+        }
+    }
+
+    pub fn age_days(&self) -> i64 {
+        (Utc::now() - self.trashed_at).num_days()
+    }
+}
+
+/// The trash directory of one repository
+#[derive(Debug, Clone)]
+pub struct Trash {
+    dir: PathBuf,
+}
+
+impl Trash {
+    /// Trash for the repository whose worktrees live in `repo_container`
+    pub fn new(repo_container: &Path) -> Self {
+        Self {
+            dir: repo_container.join(".iMi").join("trash"),
+        }
+    }
+
+    /// Move `entry.original_path` into the trash and record its metadata
+    pub fn put(&self, mut entry: TrashEntry) -> Result<TrashEntry> {
+        std::fs::create_dir_all(&self.dir).context(format!(
+            "Failed to create trash directory {}",
+            self.dir.display()
+        ))?;
+
+        // Two removals of the same name within a second get distinct ids
+        let base_id = entry.id.clone();
+        let mut suffix = 1;
+        while self.dir.join(&entry.id).exists() {
+            suffix += 1;
+            entry.id = format!("{}-{}", base_id, suffix);
+        }
+
+        entry.path = self.dir.join(&entry.id);
+        std::fs::rename(&entry.original_path, &entry.path).context(format!(
+            "Failed to move {} to the trash",
+            entry.original_path.display()
+        ))?;
+        std::fs::write(
+            self.metadata_path(&entry.id),
+            serde_json::to_string_pretty(&entry)?,
+        )
+        .context("Failed to write trash metadata")?;
+        Ok(entry)
+    }
+
+    /// Entries in this trash, newest first. Directories without readable
+    /// metadata are left alone.
+    pub fn list(&self) -> Result<Vec<TrashEntry>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut entries = Vec::new();
+        for file in std::fs::read_dir(&self.dir)? {
+            let path = file?.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let Some(mut entry) = std::fs::read_to_string(&path)
+                .ok()
+                .and_then(|contents| serde_json::from_str::<TrashEntry>(&contents).ok())
+            else {
+                continue;
+            };
+            entry.path = self.dir.join(&entry.id);
+            if entry.path.exists() {
+                entries.push(entry);
+            }
+        }
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.trashed_at));
+        Ok(entries)
+    }
+
+    /// Entry by id, or the newest one trashed under that worktree name
+    pub fn find(&self, id_or_name: &str) -> Result<Option<TrashEntry>> {
+        let entries = self.list()?;
+        Ok(entries
+            .iter()
+            .find(|entry| entry.id == id_or_name)
+            .or_else(|| {
+                entries
+                    .iter()
+                    .find(|entry| entry.worktree_name == id_or_name)
+            })
+            .cloned())
+    }
+
+    /// Remove an entry's metadata once its directory was restored elsewhere
+    pub fn forget(&self, entry: &TrashEntry) -> Result<()> {
+        let metadata = self.metadata_path(&entry.id);
+        if metadata.exists() {
+            std::fs::remove_file(&metadata).context("Failed to remove trash metadata")?;
+        }
+        Ok(())
+    }
+
+    /// Delete an entry for good
+    pub fn purge(&self, entry: &TrashEntry) -> Result<()> {
+        if entry.path.exists() {
+            std::fs::remove_dir_all(&entry.path)
+                .context(format!("Failed to delete {}", entry.path.display()))?;
+        }
+        self.forget(entry)
+    }
+
+    /// Delete entries older than `retention_days`; returns what was deleted
+    pub fn purge_expired(&self, retention_days: u32) -> Result<Vec<TrashEntry>> {
+        let mut purged = Vec::new();
+        for entry in self.list()? {
+            if entry.age_days() >= i64::from(retention_days) {
+                self.purge(&entry)?;
+                purged.push(entry);
+            }
+        }
+        Ok(purged)
+    }
+
+    fn metadata_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trashed_directories_keep_their_files_until_purged() {
+        let container = tempfile::tempdir().unwrap();
+        let worktree = container.path().join("feat-auth");
+        std::fs::create_dir(&worktree).unwrap();
+        std::fs::write(worktree.join("notes.txt"), "uncommitted").unwrap();
+
+        let trash = Trash::new(container.path());
+        let mut entry = TrashEntry::new("iMi", "feat-auth", &worktree);
+        entry.branch_name = Some("feat/auth".to_string());
+        let entry = trash.put(entry).unwrap();
+
+        assert!(!worktree.exists());
+        assert_eq!(
+            std::fs::read_to_string(entry.path.join("notes.txt")).unwrap(),
+            "uncommitted"
+        );
+
+        let found = trash.find("feat-auth").unwrap().unwrap();
+        assert_eq!(found.id, entry.id);
+        assert_eq!(found.branch_name.as_deref(), Some("feat/auth"));
+
+        assert!(trash.purge_expired(1).unwrap().is_empty());
+        assert_eq!(trash.purge_expired(0).unwrap().len(), 1);
+        assert!(trash.list().unwrap().is_empty());
+        assert!(!entry.path.exists());
+    }
+}
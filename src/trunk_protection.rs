@@ -0,0 +1,220 @@
+//! Trunk protection (`[trunk_protection]`): the trunk checkout is for syncing
+//! and merging, and work done there directly gets merged over or pushed by
+//! accident. `imi status` and `imi monitor` report trunks with uncommitted
+//! changes, unpushed commits or another branch checked out; with `enforce`,
+//! `imi merge` refuses a dirty trunk and `imi merge` and `imi sync` leave
+//! clean trunks with read-only files.
+
+use anyhow::{Context, Result};
+use colored::*;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::git::GitManager;
+
+/// Trunk git config key recording that its files were made read-only
+const LOCK_KEY: &str = "imi.trunkLocked";
+
+/// What's wrong with one trunk
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TrunkViolation {
+    pub repo_name: String,
+    pub trunk_path: PathBuf,
+    /// Branch checked out instead of the default branch
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wrong_branch: Option<String>,
+    /// Modified, new and deleted files
+    pub dirty_files: Vec<String>,
+    /// Commits the trunk has and its upstream doesn't
+    pub local_commits: usize,
+}
+
+impl TrunkViolation {
+    pub fn is_dirty(&self) -> bool {
+        !self.dirty_files.is_empty()
+    }
+
+    pub fn describe(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if let Some(branch) = &self.wrong_branch {
+            problems.push(format!("on branch '{}'", branch));
+        }
+        if self.is_dirty() {
+            problems.push(format!(
+                "{} uncommitted file(s), e.g. {}",
+                self.dirty_files.len(),
+                self.dirty_files[0]
+            ));
+        }
+        if self.local_commits > 0 {
+            problems.push(format!("{} unpushed commit(s)", self.local_commits));
+        }
+        problems
+    }
+
+    pub fn print(&self) {
+        println!(
+            "{} {} {}",
+            "🚨".bright_red(),
+            format!("Work found in {}'s trunk:", self.repo_name)
+                .bright_red()
+                .bold(),
+            self.trunk_path.display().to_string().bright_black()
+        );
+        for problem in self.describe() {
+            println!("   {} {}", "•".bright_red(), problem);
+        }
+        println!(
+            "   {}",
+            "Move it into a worktree: git stash, imi add feat <name>, then git stash pop there"
+                .bright_black()
+        );
+    }
+}
+
+/// Check the trunk at `trunk`; `None` when it is clean, on `default_branch`
+/// and has nothing unpushed
+pub fn inspect(
+    git: &GitManager,
+    repo_name: &str,
+    trunk: &Path,
+    default_branch: &str,
+) -> Result<Option<TrunkViolation>> {
+    let status = git.get_worktree_status(trunk)?;
+    let branch = git.get_current_branch(trunk)?;
+    let violation = TrunkViolation {
+        repo_name: repo_name.to_string(),
+        trunk_path: trunk.to_path_buf(),
+        wrong_branch: (branch != default_branch).then_some(branch),
+        dirty_files: status
+            .modified_files
+            .into_iter()
+            .chain(status.new_files)
+            .chain(status.deleted_files)
+            .collect(),
+        local_commits: status.commits_ahead,
+    };
+
+    let clean =
+        violation.wrong_branch.is_none() && !violation.is_dirty() && violation.local_commits == 0;
+    Ok((!clean).then_some(violation))
+}
+
+/// Make the trunk's tracked files read-only; returns how many changed
+pub fn lock(git: &GitManager, trunk: &Path) -> Result<usize> {
+    let changed = set_writable(git, trunk, false)?;
+    git.execute_git_command(trunk, &["config", LOCK_KEY, "true"])?;
+    Ok(changed)
+}
+
+/// Undo [`lock`]
+pub fn unlock(git: &GitManager, trunk: &Path) -> Result<usize> {
+    let changed = set_writable(git, trunk, true)?;
+    git.execute_git_command(trunk, &["config", "--unset", LOCK_KEY])?;
+    Ok(changed)
+}
+
+pub fn is_locked(git: &GitManager, trunk: &Path) -> bool {
+    git.execute_git_command(trunk, &["config", "--get", LOCK_KEY])
+        .is_ok_and(|value| value.trim() == "true")
+}
+
+fn set_writable(git: &GitManager, trunk: &Path, writable: bool) -> Result<usize> {
+    let files = git
+        .execute_git_command(trunk, &["ls-files", "-z"])
+        .context("Failed to list the trunk's files")?;
+
+    let mut changed = 0;
+    for file in files.split('\0').filter(|file| !file.is_empty()) {
+        let path = trunk.join(file);
+        let Ok(metadata) = std::fs::symlink_metadata(&path) else {
+            continue;
+        };
+        if !metadata.is_file() {
+            continue;
+        }
+        let mut permissions = metadata.permissions();
+        if !set_permissions_writable(&mut permissions, writable) {
+            continue;
+        }
+        std::fs::set_permissions(&path, permissions)
+            .with_context(|| format!("Failed to change permissions of {}", path.display()))?;
+        changed += 1;
+    }
+    Ok(changed)
+}
+
+/// Owner write permission on or (for everyone) off; false when unchanged
+#[cfg(unix)]
+fn set_permissions_writable(permissions: &mut std::fs::Permissions, writable: bool) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = permissions.mode();
+    let new_mode = match writable {
+        true => mode | 0o200,
+        false => mode & !0o222,
+    };
+    permissions.set_mode(new_mode);
+    new_mode != mode
+}
+
+#[cfg(not(unix))]
+fn set_permissions_writable(permissions: &mut std::fs::Permissions, writable: bool) -> bool {
+    if permissions.readonly() != writable {
+        return false;
+    }
+    #[allow(clippy::permissions_set_readonly_false)]
+    permissions.set_readonly(!writable);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let output = Command::new("git")
+            .current_dir(dir)
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?}: {:?}", args, output);
+    }
+
+    #[test]
+    fn reports_work_in_trunk_and_locks_clean_trunks() {
+        let dir = tempfile::tempdir().unwrap();
+        let trunk = dir.path();
+        git(trunk, &["init", "-q", "-b", "main"]);
+        git(trunk, &["config", "user.email", "t@example.com"]);
+        git(trunk, &["config", "user.name", "T"]);
+        std::fs::write(trunk.join("lib.rs"), "fn main() {}\n").unwrap();
+        git(trunk, &["add", "."]);
+        git(trunk, &["commit", "-q", "-m", "init"]);
+
+        let manager = GitManager::new();
+        assert_eq!(inspect(&manager, "api", trunk, "main").unwrap(), None);
+
+        std::fs::write(trunk.join("lib.rs"), "fn main() { todo!() }\n").unwrap();
+        let violation = inspect(&manager, "api", trunk, "main").unwrap().unwrap();
+        assert_eq!(violation.dirty_files, ["lib.rs"]);
+        assert!(violation.wrong_branch.is_none());
+        git(trunk, &["checkout", "-q", "--", "lib.rs"]);
+
+        git(trunk, &["checkout", "-q", "-b", "hotfix"]);
+        let violation = inspect(&manager, "api", trunk, "main").unwrap().unwrap();
+        assert_eq!(violation.wrong_branch.as_deref(), Some("hotfix"));
+        git(trunk, &["checkout", "-q", "main"]);
+
+        assert_eq!(lock(&manager, trunk).unwrap(), 1);
+        assert!(is_locked(&manager, trunk));
+        let readonly = |path: &Path| std::fs::metadata(path).unwrap().permissions().readonly();
+        assert!(readonly(&trunk.join("lib.rs")));
+        assert_eq!(lock(&manager, trunk).unwrap(), 0);
+
+        assert_eq!(unlock(&manager, trunk).unwrap(), 1);
+        assert!(!is_locked(&manager, trunk));
+        assert!(!readonly(&trunk.join("lib.rs")));
+    }
+}
@@ -0,0 +1,205 @@
+//! Worktree types recognized from directory and branch names, driven by the
+//! `worktree_prefix` and `branch_prefix` of each row in `worktree_types`, so
+//! custom types are found wherever iMi meets worktrees it didn't create:
+//! `imi sync`, office migration and orphan pruning.
+
+use crate::config::TypeInferenceSettings;
+use crate::database::{Database, WorktreeType};
+
+/// Seeded types, for when the registry can't be read
+const BUILTIN_PREFIXES: [(&str, &str, &str); 6] = [
+    ("feat", "feat-", "feat/"),
+    ("fix", "fix-", "fix/"),
+    ("aiops", "aiops-", "aiops/"),
+    ("devops", "devops-", "devops/"),
+    ("review", "pr-", "pr-review/"),
+    ("trunk", "trunk-", ""),
+];
+
+/// Directory prefixes older releases created, still recognized
+const LEGACY_WORKTREE_PREFIXES: [(&str, &str); 1] = [("review", "review-")];
+
+#[derive(Debug, Clone)]
+struct TypePrefix {
+    name: String,
+    worktree_prefix: String,
+    branch_prefix: String,
+}
+
+/// Reverse mapping from names to worktree types
+#[derive(Debug, Clone)]
+pub struct TypePrefixes {
+    types: Vec<TypePrefix>,
+    /// Type for worktrees no prefix matches (`[type_inference]
+    /// unmatched_type`)
+    unmatched: Option<String>,
+}
+
+impl TypePrefixes {
+    pub fn new(types: &[WorktreeType], unmatched: Option<String>) -> Self {
+        let types = types
+            .iter()
+            .map(|wt_type| TypePrefix {
+                name: wt_type.name.clone(),
+                worktree_prefix: wt_type.worktree_prefix.clone(),
+                branch_prefix: wt_type.branch_prefix.clone(),
+            })
+            .chain(legacy_prefixes())
+            .collect();
+        Self { types, unmatched }
+    }
+
+    pub fn builtin(unmatched: Option<String>) -> Self {
+        let types = BUILTIN_PREFIXES
+            .iter()
+            .map(|(name, worktree_prefix, branch_prefix)| TypePrefix {
+                name: name.to_string(),
+                worktree_prefix: worktree_prefix.to_string(),
+                branch_prefix: branch_prefix.to_string(),
+            })
+            .chain(legacy_prefixes())
+            .collect();
+        Self { types, unmatched }
+    }
+
+    /// The registered types with the repository's `unmatched_type`, or the
+    /// built-in ones when the registry can't be read
+    pub async fn load(db: &Database, rules: &TypeInferenceSettings, repo_name: &str) -> Self {
+        let unmatched = rules.for_repo(Some(repo_name)).unmatched_type;
+        match db.list_worktree_types().await {
+            Ok(types) if !types.is_empty() => Self::new(&types, unmatched),
+            _ => Self::builtin(unmatched),
+        }
+    }
+
+    /// The type whose `worktree_prefix` starts the directory name, longest
+    /// prefix first
+    pub fn type_for_directory(&self, dir_name: &str) -> Option<&str> {
+        self.longest_match(dir_name, |t| &t.worktree_prefix)
+    }
+
+    /// The type whose `branch_prefix` starts the branch name
+    pub fn type_for_branch(&self, branch: &str) -> Option<&str> {
+        self.longest_match(branch, |t| &t.branch_prefix)
+    }
+
+    /// Type of a worktree iMi didn't create: by directory name, then branch,
+    /// then `unmatched_type`; None when nothing applies
+    pub fn infer(&self, dir_name: &str, branch: &str) -> Option<&str> {
+        self.type_for_directory(dir_name)
+            .or_else(|| self.type_for_branch(branch))
+            .or(self.unmatched.as_deref())
+    }
+
+    /// Name for a worktree of `worktree_type` iMi didn't create: the
+    /// directory name when it already has the type's prefix, otherwise the
+    /// prefix and the branch without its type prefix (`fix/login` under
+    /// `myrepo-login` becomes `fix-login`)
+    pub fn name_for(&self, worktree_type: &str, dir_name: &str, branch: &str) -> String {
+        let Some(wt_type) = self.types.iter().find(|t| t.name == worktree_type) else {
+            return dir_name.to_string();
+        };
+        if dir_name.starts_with(&wt_type.worktree_prefix) || branch.is_empty() {
+            return dir_name.to_string();
+        }
+        let short = match wt_type.branch_prefix.as_str() {
+            "" => branch,
+            prefix => branch.strip_prefix(prefix).unwrap_or(branch),
+        };
+        format!("{}{}", wt_type.worktree_prefix, short.replace('/', "-"))
+    }
+
+    /// Whether a directory is named like a worktree of some type
+    pub fn looks_like_worktree(&self, dir_name: &str) -> bool {
+        self.type_for_directory(dir_name).is_some()
+    }
+
+    fn longest_match(&self, name: &str, prefix: impl Fn(&TypePrefix) -> &String) -> Option<&str> {
+        self.types
+            .iter()
+            .filter(|t| !prefix(t).is_empty() && name.starts_with(prefix(t).as_str()))
+            .max_by_key(|t| prefix(t).len())
+            .map(|t| t.name.as_str())
+    }
+}
+
+fn legacy_prefixes() -> impl Iterator<Item = TypePrefix> {
+    LEGACY_WORKTREE_PREFIXES
+        .iter()
+        .map(|(name, worktree_prefix)| TypePrefix {
+            name: name.to_string(),
+            worktree_prefix: worktree_prefix.to_string(),
+            branch_prefix: String::new(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn worktree_type(name: &str, worktree_prefix: &str, branch_prefix: &str) -> WorktreeType {
+        WorktreeType {
+            id: 1,
+            name: name.to_string(),
+            branch_prefix: branch_prefix.to_string(),
+            worktree_prefix: worktree_prefix.to_string(),
+            description: None,
+            is_builtin: false,
+            color: None,
+            icon: None,
+            metadata: serde_json::json!({}),
+            created_at: chrono::Utc::now(),
+            default_ttl_days: None,
+            default_base_branch: None,
+            commit_template: None,
+        }
+    }
+
+    #[test]
+    fn recognizes_custom_types_by_directory_then_branch() {
+        let types = vec![
+            worktree_type("feat", "feat-", "feat/"),
+            worktree_type("experiment", "exp-", "exp/"),
+            worktree_type("spike", "exp-spike-", "spike/"),
+        ];
+        let prefixes = TypePrefixes::new(&types, None);
+
+        assert_eq!(prefixes.infer("exp-cache", "whatever"), Some("experiment"));
+        assert_eq!(prefixes.infer("exp-spike-db", "whatever"), Some("spike"));
+        assert_eq!(prefixes.infer("cache-work", "spike/cache"), Some("spike"));
+        assert_eq!(prefixes.infer("review-12", "x"), Some("review"));
+        assert_eq!(prefixes.infer("scratch", "scratch"), None);
+        assert!(prefixes.looks_like_worktree("exp-cache"));
+        assert!(!prefixes.looks_like_worktree("docs"));
+
+        let with_fallback = TypePrefixes::new(&types, Some("feat".to_string()));
+        assert_eq!(with_fallback.infer("scratch", "scratch"), Some("feat"));
+        assert!(!with_fallback.looks_like_worktree("scratch"));
+    }
+
+    #[test]
+    fn names_adopted_worktrees_after_their_type() {
+        let prefixes = TypePrefixes::builtin(None);
+        assert_eq!(
+            prefixes.name_for("fix", "fix-login", "fix/login"),
+            "fix-login"
+        );
+        assert_eq!(
+            prefixes.name_for("fix", "api-login", "fix/login"),
+            "fix-login"
+        );
+        assert_eq!(
+            prefixes.name_for("feat", "scratch", "alice/new-ui"),
+            "feat-alice-new-ui"
+        );
+        assert_eq!(prefixes.name_for("nope", "scratch", "x"), "scratch");
+    }
+
+    #[test]
+    fn builtin_prefixes_match_the_seeded_types() {
+        let prefixes = TypePrefixes::builtin(None);
+        assert_eq!(prefixes.type_for_directory("pr-42"), Some("review"));
+        assert_eq!(prefixes.type_for_branch("devops/ci"), Some("devops"));
+        assert_eq!(prefixes.type_for_branch("main"), None);
+    }
+}
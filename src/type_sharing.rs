@@ -0,0 +1,355 @@
+//! Worktree type definitions shared as a TOML or JSON file (`imi types
+//! export/import`), e.g. a `.imi/types.toml` committed to the repository
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::database::WorktreeType;
+
+/// Format version written by `imi types export`
+pub const TYPES_FILE_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TypeDefinition {
+    pub name: String,
+    pub branch_prefix: String,
+    pub worktree_prefix: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_ttl_days: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_base_branch: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit_template: Option<String>,
+}
+
+impl From<&WorktreeType> for TypeDefinition {
+    fn from(wt_type: &WorktreeType) -> Self {
+        Self {
+            name: wt_type.name.clone(),
+            branch_prefix: wt_type.branch_prefix.clone(),
+            worktree_prefix: wt_type.worktree_prefix.clone(),
+            description: wt_type.description.clone(),
+            default_ttl_days: wt_type.default_ttl_days,
+            default_base_branch: wt_type.default_base_branch.clone(),
+            commit_template: wt_type.commit_template.clone(),
+        }
+    }
+}
+
+impl TypeDefinition {
+    /// Copy these settings onto a stored type
+    pub fn apply_to(&self, wt_type: &mut WorktreeType) {
+        wt_type.branch_prefix = self.branch_prefix.clone();
+        wt_type.worktree_prefix = self.worktree_prefix.clone();
+        wt_type.description = self.description.clone();
+        wt_type.default_ttl_days = self.default_ttl_days;
+        wt_type.default_base_branch = self.default_base_branch.clone();
+        wt_type.commit_template = self.commit_template.clone();
+    }
+
+    fn validate(&self) -> Result<()> {
+        let valid_name = !self.name.is_empty()
+            && self
+                .name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+        if !valid_name {
+            return Err(anyhow::anyhow!(
+                "Invalid type name '{}' (lowercase, alphanumeric, hyphens)",
+                self.name
+            ));
+        }
+        if self.worktree_prefix.is_empty() {
+            return Err(anyhow::anyhow!(
+                "Type '{}' has an empty worktree prefix",
+                self.name
+            ));
+        }
+        if let Some(ttl) = self.default_ttl_days.filter(|ttl| *ttl <= 0) {
+            return Err(anyhow::anyhow!(
+                "Type '{}' has a non-positive TTL ({})",
+                self.name,
+                ttl
+            ));
+        }
+        Ok(())
+    }
+
+    /// Fields whose values differ from `other`
+    fn differences(&self, other: &Self) -> Vec<&'static str> {
+        let mut fields = Vec::new();
+        let mut check = |name, differs: bool| {
+            if differs {
+                fields.push(name);
+            }
+        };
+        check("branch_prefix", self.branch_prefix != other.branch_prefix);
+        check(
+            "worktree_prefix",
+            self.worktree_prefix != other.worktree_prefix,
+        );
+        check("description", self.description != other.description);
+        check(
+            "default_ttl_days",
+            self.default_ttl_days != other.default_ttl_days,
+        );
+        check(
+            "default_base_branch",
+            self.default_base_branch != other.default_base_branch,
+        );
+        check(
+            "commit_template",
+            self.commit_template != other.commit_template,
+        );
+        fields
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TypesFile {
+    #[serde(default = "default_version")]
+    pub version: u32,
+    #[serde(default)]
+    pub types: Vec<TypeDefinition>,
+}
+
+fn default_version() -> u32 {
+    TYPES_FILE_VERSION
+}
+
+impl TypesFile {
+    pub fn new(types: &[WorktreeType]) -> Self {
+        Self {
+            version: TYPES_FILE_VERSION,
+            types: types.iter().map(TypeDefinition::from).collect(),
+        }
+    }
+
+    /// Parse TOML, or JSON for `.json` paths
+    pub fn parse(contents: &str, path: &Path) -> Result<Self> {
+        let file: Self = if is_json(path) {
+            serde_json::from_str(contents).context("Failed to parse types file as JSON")?
+        } else {
+            toml::from_str(contents).context("Failed to parse types file as TOML")?
+        };
+
+        if file.version > TYPES_FILE_VERSION {
+            return Err(anyhow::anyhow!(
+                "{} uses types file version {}; this iMi understands up to {}",
+                path.display(),
+                file.version,
+                TYPES_FILE_VERSION
+            ));
+        }
+        for definition in &file.types {
+            definition.validate()?;
+        }
+        Ok(file)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .context(format!("Failed to read types file {}", path.display()))?;
+        Self::parse(&contents, path)
+    }
+
+    /// TOML, or JSON for `.json` paths
+    pub fn render(&self, path: Option<&Path>) -> Result<String> {
+        if path.is_some_and(is_json) {
+            Ok(serde_json::to_string_pretty(self)?)
+        } else {
+            toml::to_string_pretty(self).context("Failed to serialize types")
+        }
+    }
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "json")
+}
+
+/// What to do when an imported type already exists with different settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportMode {
+    /// Leave it as it is and report the conflict
+    Skip,
+    /// Fill in settings that are unset locally
+    Merge,
+    /// Replace its settings with the imported ones
+    Overwrite,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "action", rename_all = "lowercase")]
+pub enum ImportAction {
+    Add {
+        definition: TypeDefinition,
+    },
+    Update {
+        definition: TypeDefinition,
+        fields: Vec<&'static str>,
+    },
+    Unchanged {
+        name: String,
+    },
+    Conflict {
+        name: String,
+        fields: Vec<&'static str>,
+    },
+}
+
+/// Decide, type by type, how importing `incoming` changes `existing`.
+/// Built-in types never get their prefixes changed.
+pub fn plan_import(
+    existing: &[WorktreeType],
+    incoming: &[TypeDefinition],
+    mode: ImportMode,
+) -> Vec<ImportAction> {
+    incoming
+        .iter()
+        .map(|imported| {
+            let Some(current) = existing.iter().find(|t| t.name == imported.name) else {
+                return ImportAction::Add {
+                    definition: imported.clone(),
+                };
+            };
+            let local = TypeDefinition::from(current);
+
+            let mut target = match mode {
+                ImportMode::Skip => local.clone(),
+                ImportMode::Merge => merged(&local, imported),
+                ImportMode::Overwrite => imported.clone(),
+            };
+            if current.is_builtin {
+                target.branch_prefix = local.branch_prefix.clone();
+                target.worktree_prefix = local.worktree_prefix.clone();
+            }
+
+            let fields = local.differences(&target);
+            if !fields.is_empty() {
+                return ImportAction::Update {
+                    definition: target,
+                    fields,
+                };
+            }
+            let conflicts = local.differences(imported);
+            if conflicts.is_empty() {
+                ImportAction::Unchanged {
+                    name: imported.name.clone(),
+                }
+            } else {
+                ImportAction::Conflict {
+                    name: imported.name.clone(),
+                    fields: conflicts,
+                }
+            }
+        })
+        .collect()
+}
+
+fn merged(local: &TypeDefinition, imported: &TypeDefinition) -> TypeDefinition {
+    let mut result = local.clone();
+    result.description = local
+        .description
+        .clone()
+        .or_else(|| imported.description.clone());
+    result.default_ttl_days = local.default_ttl_days.or(imported.default_ttl_days);
+    result.default_base_branch = local
+        .default_base_branch
+        .clone()
+        .or_else(|| imported.default_base_branch.clone());
+    result.commit_template = local
+        .commit_template
+        .clone()
+        .or_else(|| imported.commit_template.clone());
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn existing(name: &str, builtin: bool) -> WorktreeType {
+        WorktreeType {
+            id: 1,
+            name: name.to_string(),
+            branch_prefix: format!("{}/", name),
+            worktree_prefix: format!("{}-", name),
+            description: Some("local".to_string()),
+            is_builtin: builtin,
+            color: None,
+            icon: None,
+            metadata: serde_json::json!({}),
+            created_at: chrono::Utc::now(),
+            default_ttl_days: None,
+            default_base_branch: None,
+            commit_template: None,
+        }
+    }
+
+    fn imported(name: &str) -> TypeDefinition {
+        TypeDefinition {
+            name: name.to_string(),
+            branch_prefix: format!("team-{}/", name),
+            worktree_prefix: format!("{}-", name),
+            description: Some("team".to_string()),
+            default_ttl_days: Some(14),
+            default_base_branch: None,
+            commit_template: None,
+        }
+    }
+
+    #[test]
+    fn round_trips_toml_and_json() {
+        let file = TypesFile::new(&[existing("spike", false)]);
+        for name in ["types.toml", "types.json"] {
+            let path = Path::new(name);
+            let parsed = TypesFile::parse(&file.render(Some(path)).unwrap(), path).unwrap();
+            assert_eq!(parsed.types, file.types);
+        }
+    }
+
+    #[test]
+    fn conflicts_are_skipped_merged_or_overwritten() {
+        let local = [existing("spike", false), existing("feat", true)];
+        let incoming = [imported("spike"), imported("feat"), imported("docs")];
+
+        let plan = plan_import(&local, &incoming, ImportMode::Skip);
+        assert!(
+            matches!(&plan[0], ImportAction::Conflict { fields, .. } if fields.contains(&"branch_prefix"))
+        );
+        assert!(matches!(&plan[2], ImportAction::Add { definition } if definition.name == "docs"));
+
+        let plan = plan_import(&local, &incoming, ImportMode::Merge);
+        let ImportAction::Update { definition, .. } = &plan[0] else {
+            panic!("expected an update");
+        };
+        assert_eq!(definition.branch_prefix, "spike/");
+        assert_eq!(definition.description.as_deref(), Some("local"));
+        assert_eq!(definition.default_ttl_days, Some(14));
+
+        let plan = plan_import(&local, &incoming, ImportMode::Overwrite);
+        let ImportAction::Update { definition, .. } = &plan[0] else {
+            panic!("expected an update");
+        };
+        assert_eq!(definition.branch_prefix, "team-spike/");
+        // Built-in prefixes stay put even when overwriting
+        let ImportAction::Update { definition, .. } = &plan[1] else {
+            panic!("expected an update");
+        };
+        assert_eq!(definition.branch_prefix, "feat/");
+        assert_eq!(definition.description.as_deref(), Some("team"));
+    }
+
+    #[test]
+    fn rejects_invalid_definitions() {
+        let path = Path::new("types.toml");
+        let bad =
+            "[[types]]\nname = \"Bad Name\"\nbranch_prefix = \"x/\"\nworktree_prefix = \"x-\"\n";
+        assert!(TypesFile::parse(bad, path).is_err());
+        assert!(TypesFile::parse("version = 99\n", path).is_err());
+    }
+}
@@ -1,17 +1,64 @@
 use anyhow::{Context, Result};
 use colored::*;
 use dialoguer::Confirm;
+use std::collections::{HashMap, HashSet};
 use std::env;
 use std::os::unix::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use tokio::fs as async_fs;
-
+use uuid::Uuid;
+
+use crate::agent_scaffold::{self, FileOutcome};
+use crate::alerts::AlertEvaluator;
+use crate::backend::{GitHubApi, GitHubBackend};
+use crate::cache_clone::{self, CacheFallback, ClonedCache};
+use crate::cancel;
+use crate::capabilities;
+use crate::commit_template;
+use crate::config::BudgetAction;
 use crate::config::Config;
-use crate::database::{Database, Repository};
+use crate::config::EventHook;
+use crate::config::MergeStrategy;
+use crate::config::MonitoringSettings;
+use crate::config::OrgSettings;
+use crate::config::Remotes;
+use crate::config::UpstreamMode;
+use crate::config::WorktreeSort;
+use crate::context;
+use crate::database::{
+    AgentActivity, CachedWorktreeStatus, Database, Repository, RepositoryEdit, Worktree,
+    WorktreeArtifact, WorktreeMessage, WorktreeNote,
+};
+use crate::deletion_safety::{self, Safety};
+use crate::disk_usage::{BudgetReport, DiskUsageCache, WorktreeUsage};
+use crate::editor::{self, WorkspaceContext};
 use crate::error::ImiError;
-use crate::fuzzy::FuzzyMatcher;
-use crate::git::{GitManager, WorktreeStatus};
+use crate::event_hooks::HookEvent;
+use crate::events::EventLog;
+use crate::fuzzy::{highlight_matches, FuzzyMatcher, SearchTarget};
+use crate::git::{
+    BranchBase, BranchOrigin, CommitSummary, Divergence, GitManager, PrSync, RebaseOutcome,
+    StatusCacheKey, WorktreeStatus,
+};
+use crate::git_hooks;
+use crate::github::pulls::PullRequestState;
+use crate::github::{CiCache, CiState, CiStatus, QueueState};
+use crate::graph::{BranchGraph, GraphInput};
+use crate::i18n::tr;
+use crate::journal::{steps, Journal, JournalEntry, OperationHandle, OperationKind};
 use crate::local::LocalContext;
+use crate::path_remap::PathRemap;
+use crate::pending_delete::{PendingDelete, PendingDeletes};
+use crate::progress;
+use crate::rebase_batch::{RebaseBatch, RebaseBatchFile, RebaseItem, RebaseState};
+use crate::remote_url::{normalize_remote_url, RemoteUrl};
+use crate::retry;
+use crate::review_context::{self, ReviewContext};
+use crate::sync_scaffold;
+use crate::trash::{Trash, TrashEntry};
+use crate::trunk_protection::{self, TrunkViolation};
+use crate::type_prefixes::TypePrefixes;
 
 #[derive(Debug, Clone)]
 pub struct WorktreeManager {
@@ -19,6 +66,31 @@ pub struct WorktreeManager {
     pub db: Database,
     pub config: Config,
     pub repo_path: Option<PathBuf>,
+    /// Suppress progress messages on stdout (JSON mode and library use)
+    quiet: bool,
+    /// Create worktrees even when the repository is over its size budget
+    ignore_budget: bool,
+    /// Take worktree names literally instead of trying type prefixes (`--exact`)
+    exact_names: bool,
+    /// Leave git hook setup out of new worktrees (`--no-hooks`)
+    skip_git_hooks: bool,
+    /// Push new worktree branches with an upstream regardless of `[upstream]`
+    publish: bool,
+    /// Check out a branch that already exists instead of refusing
+    /// (`--adopt-existing`)
+    adopt_existing: bool,
+    /// Check out only the directories a PR changes in review worktrees
+    /// (`--sparse-changed`)
+    sparse_changed: bool,
+    /// Order of listed worktrees (`--sort`), else `[list_settings] sort`
+    sort: Option<WorktreeSort>,
+    /// List archived repositories and their worktrees too (`--all`)
+    include_archived: bool,
+    /// GitHub to look up and merge pull requests on; `None` is the real API
+    github: Option<Arc<dyn GitHubBackend>>,
+    /// Walk every worktree for its git status instead of reusing cached
+    /// results (`--no-cache`)
+    no_status_cache: bool,
 }
 
 impl WorktreeManager {
@@ -28,6 +100,219 @@ impl WorktreeManager {
             db,
             config,
             repo_path,
+            quiet: false,
+            ignore_budget: false,
+            exact_names: false,
+            skip_git_hooks: false,
+            publish: false,
+            adopt_existing: false,
+            sparse_changed: false,
+            sort: None,
+            include_archived: false,
+            github: None,
+            no_status_cache: false,
+        }
+    }
+
+    /// Keep stdout clean for callers that produce their own output
+    pub fn quiet(mut self, quiet: bool) -> Self {
+        self.quiet = quiet;
+        self
+    }
+
+    /// Downgrade a `refuse` size budget to a warning (`--ignore-budget`)
+    pub fn ignore_budget(mut self, ignore_budget: bool) -> Self {
+        self.ignore_budget = ignore_budget;
+        self
+    }
+
+    /// Only accept full worktree names, never `auth` for `feat-auth`
+    pub fn exact_names(mut self, exact_names: bool) -> Self {
+        self.exact_names = exact_names;
+        self
+    }
+
+    /// Don't set up git hooks in worktrees created from here on (`--no-hooks`)
+    pub fn skip_git_hooks(mut self, skip_git_hooks: bool) -> Self {
+        self.skip_git_hooks = skip_git_hooks;
+        self
+    }
+
+    /// Push the branch of worktrees created from here on (`--publish`)
+    pub fn publish(mut self, publish: bool) -> Self {
+        self.publish = publish;
+        self
+    }
+
+    /// Check out existing branches for worktrees created from here on
+    /// (`--adopt-existing`)
+    pub fn adopt_existing(mut self, adopt_existing: bool) -> Self {
+        self.adopt_existing = adopt_existing;
+        self
+    }
+
+    /// Limit review worktrees created from here on to the directories
+    /// their PR changes (`--sparse-changed`)
+    pub fn sparse_changed(mut self, sparse_changed: bool) -> Self {
+        self.sparse_changed = sparse_changed;
+        self
+    }
+
+    /// Order worktrees in listings by `sort` (`--sort`)
+    pub fn sort_worktrees(mut self, sort: Option<WorktreeSort>) -> Self {
+        self.sort = sort;
+        self
+    }
+
+    /// Keep archived repositories in listings and fuzzy results (`--all`)
+    pub fn include_archived(mut self, include_archived: bool) -> Self {
+        self.include_archived = include_archived;
+        self
+    }
+
+    /// See every machine's repositories and worktrees (`--all-hosts`), not
+    /// just this one's
+    pub fn all_hosts(mut self, all_hosts: bool) -> Self {
+        if all_hosts {
+            self.db = self.db.all_hosts();
+        }
+        self
+    }
+
+    /// Recompute git statuses in status and list (`--no-cache`); fresh
+    /// results still replace the cached ones
+    pub fn no_status_cache(mut self, no_status_cache: bool) -> Self {
+        self.no_status_cache = no_status_cache;
+        self
+    }
+
+    /// Talk to `github` instead of the GitHub API
+    #[allow(dead_code)] // Library accessor
+    pub fn with_github(mut self, github: Arc<dyn GitHubBackend>) -> Self {
+        self.github = Some(github);
+        self
+    }
+
+    fn github(&self) -> Result<Arc<dyn GitHubBackend>> {
+        match &self.github {
+            Some(github) => Ok(github.clone()),
+            None => Ok(Arc::new(GitHubApi::new(
+                self.config.retry_settings.clone(),
+            )?)),
+        }
+    }
+
+    /// Registered repositories, or those tagged `tag`, leaving out archived
+    /// ones unless `--all` was given
+    pub async fn listed_repositories(&self, tag: Option<&str>) -> Result<Vec<Repository>> {
+        let mut repositories = match tag {
+            Some(tag) => self.db.list_repositories_with_tag(tag).await?,
+            None => self.db.list_repositories().await?,
+        };
+        if !self.include_archived {
+            let archived = self.archived_repository_ids().await;
+            repositories.retain(|repository| !archived.contains(&repository.id));
+        }
+        Ok(repositories)
+    }
+
+    /// Drop worktrees of archived repositories unless `--all` was given;
+    /// naming the repository shows its worktrees regardless
+    pub async fn without_archived(
+        &self,
+        mut worktrees: Vec<Worktree>,
+        repo: Option<&str>,
+    ) -> Vec<Worktree> {
+        if !self.include_archived && repo.is_none() {
+            let archived = self.archived_repository_ids().await;
+            worktrees.retain(|wt| !archived.contains(&wt.project_id));
+        }
+        worktrees
+    }
+
+    /// Archived repositories; none when the registry predates archiving
+    async fn archived_repository_ids(&self) -> HashSet<Uuid> {
+        self.db
+            .list_archived_repository_ids()
+            .await
+            .unwrap_or_default()
+    }
+
+    /// Active worktrees in the order asked for with `--sort` or configured,
+    /// without those of archived repositories unless `--all` was given
+    pub async fn active_worktrees(&self, repo: Option<&str>) -> Result<Vec<Worktree>> {
+        let worktrees = self.sorted_worktrees(repo).await?;
+        Ok(self.without_archived(worktrees, repo).await)
+    }
+
+    async fn sorted_worktrees(&self, repo: Option<&str>) -> Result<Vec<Worktree>> {
+        let Some(sort) = self.sort.or(self.config.list_settings.sort) else {
+            return self.db.list_worktrees(repo).await;
+        };
+        let mut worktrees = self.db.list_worktrees_sorted(repo, sort).await?;
+        if sort == WorktreeSort::Size {
+            let ttl = self.config.size_budget.cache_ttl_secs;
+            worktrees = tokio::task::spawn_blocking(move || {
+                let mut cache = DiskUsageCache::load(ttl);
+                let mut sized: Vec<(u64, Worktree)> = worktrees
+                    .into_iter()
+                    .map(|wt| (cache.size_of(Path::new(&wt.path)), wt))
+                    .collect();
+                let _ = cache.save();
+                sized.sort_by_key(|(size, _)| std::cmp::Reverse(*size));
+                sized.into_iter().map(|(_, wt)| wt).collect()
+            })
+            .await?;
+        }
+        Ok(worktrees)
+    }
+
+    /// When each worktree was last changed or visited, for aging cues
+    async fn last_activity(
+        &self,
+        worktrees: &[Worktree],
+    ) -> HashMap<Uuid, chrono::DateTime<chrono::Utc>> {
+        let visits: HashMap<Uuid, chrono::DateTime<chrono::Utc>> = self
+            .db
+            .list_navigation_stats()
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .map(|stat| (stat.target_id, stat.last_visited_at))
+            .collect();
+        worktrees
+            .iter()
+            .map(|wt| {
+                let visited = visits.get(&wt.id).copied();
+                (
+                    wt.id,
+                    visited.map_or(wt.updated_at, |v| v.max(wt.updated_at)),
+                )
+            })
+            .collect()
+    }
+
+    /// Days since a stale worktree was last active; None while it's fresh
+    fn stale_idle_days(&self, last_active: Option<&chrono::DateTime<chrono::Utc>>) -> Option<i64> {
+        last_active
+            .filter(|time| self.config.list_settings.is_stale(**time))
+            .map(|time| (chrono::Utc::now() - *time).num_days())
+    }
+
+    /// Print a progress message unless running quiet
+    fn report(&self, message: impl std::fmt::Display) {
+        if !self.quiet {
+            println!("{}", message);
+        }
+    }
+
+    /// A step of a long operation: a `--json-stream` progress event, else
+    /// the line `report` prints
+    fn step(&self, phase: &str, percent: u8, icon: ColoredString, message: impl std::fmt::Display) {
+        if progress::enabled() {
+            progress::emit(phase, Some(percent), &message.to_string());
+        } else {
+            self.report(format_args!("{} {}", icon, message));
         }
     }
 
@@ -35,13 +320,14 @@ impl WorktreeManager {
     pub async fn create_feature_worktree(&self, name: &str, repo: Option<&str>) -> Result<PathBuf> {
         let worktree_name = format!("feat-{}", name);
         let branch_name = format!("feat/{}", name);
+        let base_branch = self.base_branch_for_type("feat").await;
 
         self.create_worktree_internal(
             repo,
             &worktree_name,
             &branch_name,
             "feat",
-            Some(&self.config.git_settings.default_branch),
+            Some(&base_branch),
         )
         .await
     }
@@ -56,17 +342,83 @@ impl WorktreeManager {
         self.create_pr_worktree_with_gh(pr_number, repo).await
     }
 
+    /// The repository's `pr-*` review worktrees on this machine, or only the
+    /// one for `pr_number`
+    pub async fn review_worktrees(
+        &self,
+        repo: Option<&str>,
+        pr_number: Option<u32>,
+    ) -> Result<Vec<Worktree>> {
+        let repo_name = self.resolve_repo_name(repo).await?;
+        let mut worktrees = self.db.list_worktrees(Some(&repo_name)).await?;
+        self.db.populate_worktree_names(&mut worktrees).await?;
+        worktrees.retain(|wt| {
+            wt.is_local()
+                && wt
+                    .review_pr_number()
+                    .is_some_and(|number| pr_number.is_none_or(|wanted| wanted == number))
+        });
+        if let (Some(number), true) = (pr_number, worktrees.is_empty()) {
+            anyhow::bail!(tr!(
+                "No review worktree for PR #{} in {}; create one with `imi pr {}`",
+                number,
+                repo_name,
+                number
+            ));
+        }
+        Ok(worktrees)
+    }
+
+    /// Move a review worktree to its pull request's latest head, recording
+    /// `pr_updated` when the head changed
+    pub async fn sync_review_worktree(&self, worktree: &Worktree, force: bool) -> Result<PrSync> {
+        let number = worktree
+            .review_pr_number()
+            .with_context(|| tr!("{} is not a pr-<number> worktree", worktree.name))?;
+        let sync = self
+            .git
+            .sync_pr_head(Path::new(&worktree.path), number, force)?;
+        if sync.updated() {
+            self.record_event(HookEvent::for_pr_sync(worktree, &sync));
+            self.write_review_context(&worktree.repo_name, number, Path::new(&worktree.path));
+        }
+        Ok(sync)
+    }
+
+    /// REVIEW_CONTEXT.json for a review worktree, unless turned off in
+    /// `[review_settings]`; never fatal
+    pub fn write_review_context(&self, repo_name: &str, pr_number: u32, worktree_path: &Path) {
+        if !self.config.review_settings.context_file {
+            return;
+        }
+        let written = ReviewContext::collect(&self.git, worktree_path, repo_name, pr_number)
+            .and_then(|context| context.write(&self.git, worktree_path));
+        match written {
+            Ok(path) => self.report(tr!("{} Wrote {}", "🧾".bright_cyan(), path.display())),
+            Err(e) => eprintln!(
+                "{}",
+                tr!(
+                    "{} Failed to write {}: {}",
+                    "⚠️".bright_yellow(),
+                    review_context::FILE_NAME,
+                    format!("{:#}", e)
+                )
+            ),
+        }
+    }
+
     /// Create a fix worktree
     pub async fn create_fix_worktree(&self, name: &str, repo: Option<&str>) -> Result<PathBuf> {
         let worktree_name = format!("fix-{}", name);
         let branch_name = format!("fix/{}", name);
+        let base_branch = self.base_branch_for_type("fix").await;
 
         self.create_worktree_internal(
             repo,
             &worktree_name,
             &branch_name,
             "fix",
-            Some(&self.config.git_settings.default_branch),
+            Some(&base_branch),
         )
         .await
     }
@@ -75,13 +427,14 @@ impl WorktreeManager {
     pub async fn create_aiops_worktree(&self, name: &str, repo: Option<&str>) -> Result<PathBuf> {
         let worktree_name = format!("aiops-{}", name);
         let branch_name = format!("aiops/{}", name);
+        let base_branch = self.base_branch_for_type("aiops").await;
 
         self.create_worktree_internal(
             repo,
             &worktree_name,
             &branch_name,
             "aiops",
-            Some(&self.config.git_settings.default_branch),
+            Some(&base_branch),
         )
         .await
     }
@@ -90,13 +443,14 @@ impl WorktreeManager {
     pub async fn create_devops_worktree(&self, name: &str, repo: Option<&str>) -> Result<PathBuf> {
         let worktree_name = format!("devops-{}", name);
         let branch_name = format!("devops/{}", name);
+        let base_branch = self.base_branch_for_type("devops").await;
 
         self.create_worktree_internal(
             repo,
             &worktree_name,
             &branch_name,
             "devops",
-            Some(&self.config.git_settings.default_branch),
+            Some(&base_branch),
         )
         .await
     }
@@ -108,30 +462,187 @@ impl WorktreeManager {
         worktree_type: &str,
         repo: Option<&str>,
     ) -> Result<PathBuf> {
-        // Get the worktree type metadata from database
-        let wt_type = self
-            .db
-            .get_worktree_type(worktree_type)
+        self.create_typed_worktree(name, worktree_type, repo, None)
             .await
-            .context(format!(
-                "Unknown worktree type '{}'. Run 'imi types' to see available types.",
-                worktree_type
-            ))?;
+    }
+
+    /// Create a worktree of any type whose branch starts at `base` (a branch,
+    /// tag or commit), or at the type's default base branch
+    pub async fn create_typed_worktree(
+        &self,
+        name: &str,
+        worktree_type: &str,
+        repo: Option<&str>,
+        base: Option<&str>,
+    ) -> Result<PathBuf> {
+        // Get the worktree type metadata from database
+        let wt_type = self.db.get_worktree_type(worktree_type).await.context(tr!(
+            "Unknown worktree type '{}'. Run 'imi types' to see available types.",
+            worktree_type
+        ))?;
 
         // Build worktree and branch names using type metadata
         let worktree_name = format!("{}{}", wt_type.worktree_prefix, name);
         let branch_name = format!("{}{}", wt_type.branch_prefix, name);
 
+        let base_branch = base
+            .or(wt_type.default_base_branch.as_deref())
+            .unwrap_or(&self.config.git_settings.default_branch);
+
         self.create_worktree_internal(
             repo,
             &worktree_name,
             &branch_name,
             worktree_type,
-            Some(&self.config.git_settings.default_branch),
+            Some(base_branch),
         )
         .await
     }
 
+    /// Base branch for a new worktree: the type's default, else the configured default branch
+    async fn base_branch_for_type(&self, worktree_type: &str) -> String {
+        self.db
+            .get_worktree_type(worktree_type)
+            .await
+            .ok()
+            .and_then(|t| t.default_base_branch)
+            .unwrap_or_else(|| self.config.git_settings.default_branch.clone())
+    }
+
+    /// Fork a worktree: create `name` (with the source's type) on a new branch
+    /// starting at the source worktree's HEAD, optionally carrying over its
+    /// uncommitted changes, and record the parent link in the database
+    pub async fn fork_worktree(
+        &self,
+        source: &str,
+        name: &str,
+        repo: Option<&str>,
+        include_uncommitted: bool,
+        cache_fallback: Option<CacheFallback>,
+    ) -> Result<(PathBuf, Vec<ClonedCache>)> {
+        let repo_name = self.resolve_repo_name(repo).await?;
+        let source_name = self.find_actual_worktree_name(source, &repo_name).await?;
+        let parent = self
+            .db
+            .get_worktree(&repo_name, &source_name)
+            .await?
+            .ok_or_else(|| ImiError::WorktreeNotFound {
+                repo: repo_name.clone(),
+                name: source.to_string(),
+            })?;
+        let parent_path = PathBuf::from(&parent.path);
+
+        let wt_type = self
+            .db
+            .list_worktree_types()
+            .await?
+            .into_iter()
+            .find(|t| t.id == parent.type_id)
+            .ok_or_else(|| anyhow::anyhow!(tr!("Unknown type for worktree '{}'", parent.name)))?;
+
+        let head = self
+            .git
+            .execute_git_command(&parent_path, &["rev-parse", "HEAD"])?
+            .trim()
+            .to_string();
+
+        // `stash create` snapshots tracked changes as a commit without touching
+        // the source worktree; it prints nothing when the worktree is clean
+        let snapshot = if include_uncommitted {
+            let sha = self
+                .git
+                .execute_git_command(&parent_path, &["stash", "create"])?
+                .trim()
+                .to_string();
+            (!sha.is_empty()).then_some(sha)
+        } else {
+            None
+        };
+
+        let worktree_name = format!("{}{}", wt_type.worktree_prefix, name);
+        let branch_name = format!("{}{}", wt_type.branch_prefix, name);
+        let worktree_path = self
+            .create_worktree_internal(
+                Some(&repo_name),
+                &worktree_name,
+                &branch_name,
+                &wt_type.name,
+                Some(&head),
+            )
+            .await?;
+
+        if let Some(sha) = &snapshot {
+            self.git
+                .execute_git_command(&worktree_path, &["stash", "apply", sha])
+                .context(tr!(
+                    "Failed to apply uncommitted changes from the source worktree"
+                ))?;
+            self.report(tr!(
+                "{} Applied uncommitted changes from {}",
+                "📦".bright_cyan(),
+                parent.name
+            ));
+        }
+
+        if let Some(child) = self.db.get_worktree(&repo_name, &worktree_name).await? {
+            self.db
+                .set_worktree_parent(&child.id, &parent.id, &head)
+                .await?;
+        }
+
+        let caches = match cache_fallback {
+            Some(fallback) => {
+                self.clone_build_caches(&parent_path, &worktree_path, fallback)
+                    .await?
+            }
+            None => Vec::new(),
+        };
+        Ok((worktree_path, caches))
+    }
+
+    /// Clone the `[fork_caches]` directories of a forked worktree into the
+    /// fork, reporting what each one cost
+    async fn clone_build_caches(
+        &self,
+        source: &Path,
+        dest: &Path,
+        fallback: CacheFallback,
+    ) -> Result<Vec<ClonedCache>> {
+        let settings = self.config.fork_caches.clone();
+        let (source, dest) = (source.to_path_buf(), dest.to_path_buf());
+        let caches = tokio::task::spawn_blocking(move || {
+            cache_clone::clone_caches(
+                &source,
+                &dest,
+                &settings.dirs,
+                fallback,
+                settings.max_copy_mb * 1024 * 1024,
+            )
+        })
+        .await?;
+
+        for cache in &caches {
+            let size = self.format_size(cache.bytes);
+            match (&cache.method, &cache.skipped) {
+                (Some(method), _) => self.report(tr!(
+                    "{} Cloned {} ({}, {})",
+                    "🧊".bright_cyan(),
+                    cache.dir,
+                    size,
+                    format!("{:?}", method).to_lowercase()
+                )),
+                (None, reason) => self.report(tr!(
+                    "{} Skipped {} ({}): {}",
+                    "⏭️".bright_black(),
+                    cache.dir,
+                    size,
+                    reason.as_deref().unwrap_or_default()
+                )),
+            }
+        }
+        Ok(caches)
+    }
+
     /// Get the trunk worktree path
     pub async fn get_trunk_worktree(&self, repo: Option<&str>) -> Result<PathBuf> {
         let repo_name = self.resolve_repo_name(repo).await?;
@@ -156,16 +667,16 @@ impl WorktreeManager {
                 }
             } else {
                 // Fallback to config-based path construction for unregistered repos
-                self.config.get_worktree_path(&repo_name, &trunk_name)
+                self.config.get_trunk_path(&repo_name)
             };
 
         self.ensure_office_layout(&repo_name, &worktree_path)?;
 
         if !worktree_path.exists() {
-            return Err(anyhow::anyhow!(
+            return Err(anyhow::anyhow!(tr!(
                 "Trunk worktree not found at: {}. Please run 'imi trunk' from the repository root first.",
                 worktree_path.display()
-            ));
+            )));
         }
 
         Ok(worktree_path)
@@ -193,28 +704,30 @@ impl WorktreeManager {
                 let registered_path = PathBuf::from(&registered_repo.path);
                 self.ensure_office_layout(&repo_name, &registered_path)?;
                 let imi_path = self.detect_imi_path(&registered_path)?;
-                imi_path.join(worktree_name)
+                self.worktree_root(&repo_name, &imi_path)
+                    .join(worktree_name)
             } else {
                 // Fall back to current repository location with IMI_PATH detection
                 let current_dir = env::current_dir()?;
                 let repo = self.git.find_repository(Some(&current_dir))?;
                 let repo_root = repo
                     .workdir()
-                    .ok_or_else(|| anyhow::anyhow!("Repository has no working directory"))?;
+                    .ok_or_else(|| anyhow::anyhow!(tr!("Repository has no working directory")))?;
 
                 // Detect IMI_PATH - if we're in a trunk directory, use its parent
                 let imi_path = self.detect_imi_path(repo_root)?;
-                imi_path.join(worktree_name)
+                self.worktree_root(&repo_name, &imi_path)
+                    .join(worktree_name)
             };
 
         // Check if worktree already exists
         if let Some(_existing) = self.db.get_worktree(&repo_name, worktree_name).await? {
             if worktree_path.exists() {
-                println!(
+                self.report(tr!(
                     "{} Worktree already exists: {}",
                     "ℹ️".bright_blue(),
                     worktree_path.display()
-                );
+                ));
                 return Ok(worktree_path);
             } else {
                 // Clean up stale database entry
@@ -224,6 +737,8 @@ impl WorktreeManager {
             }
         }
 
+        self.check_size_budget(&repo_name).await?;
+
         // Find the repository - use registered path if available, otherwise current directory
         let repo = if let Some(mut registered_repo) = self.db.get_repository(&repo_name).await? {
             // Validate and repair path if needed (note: already validated above, but path may have changed)
@@ -235,7 +750,7 @@ impl WorktreeManager {
             self.ensure_office_layout(&repo_name, &registered_path)?;
             self.git
                 .find_repository(Some(&registered_path))
-                .context(format!(
+                .context(tr!(
                     "Git repository not found at registered path: {}",
                     registered_path.display()
                 ))?
@@ -245,7 +760,7 @@ impl WorktreeManager {
             let repo = self.git.find_repository(Some(&current_dir))?;
             let repo_root = repo
                 .workdir()
-                .ok_or_else(|| anyhow::anyhow!("Repository has no working directory"))?;
+                .ok_or_else(|| anyhow::anyhow!(tr!("Repository has no working directory")))?;
 
             // Auto-register repository if not in database
             self.ensure_repository_registered(&repo_name, repo_root)
@@ -254,25 +769,116 @@ impl WorktreeManager {
             repo
         };
 
+        // A fork's branches start from the upstream trunk, not whatever the
+        // local trunk has checked out
+        let remotes = self.config.remotes_for(&repo_name);
+        let fork_base = match (base_branch, remotes.is_fork()) {
+            (None, true) => self
+                .db
+                .get_repository(&repo_name)
+                .await?
+                .map(|repository| repository.default_branch),
+            _ => None,
+        };
+        let base_branch = base_branch.or(fork_base.as_deref());
+
+        // Journal the operation so an interruption can be resumed or rolled back
+        let mut journal_entry = JournalEntry::new(
+            OperationKind::Add,
+            &repo_name,
+            worktree_name,
+            branch_name,
+            worktree_type,
+            &worktree_path,
+        );
+        journal_entry.base_branch = base_branch.map(String::from);
+        let mut op = Journal::open_default()?.begin(journal_entry)?;
+
         // Create the Git worktree
-        self.git
-            .create_worktree(
-                &repo,
+        let branch_origin = match self.git.create_worktree(
+            &repo,
+            worktree_name,
+            &worktree_path,
+            branch_name,
+            BranchBase {
+                branch: base_branch,
+                remote: &remotes.upstream,
+            },
+            self.adopt_existing,
+        ) {
+            Ok(origin) => origin,
+            Err(e) => {
+                op.finish()?;
+                return Err(e.context(tr!("Failed to create Git worktree")));
+            }
+        };
+        op.step(steps::GIT_WORKTREE)?;
+        if cancel::requested() {
+            return Err(self.abandon_add(op).await);
+        }
+        match branch_origin {
+            BranchOrigin::AdoptedLocal => self.report(tr!(
+                "{} Checked out existing branch {}",
+                "🔗".bright_cyan(),
+                branch_name
+            )),
+            BranchOrigin::AdoptedRemote => self.report(tr!(
+                "{} Checked out {} from origin, tracking it",
+                "🔗".bright_cyan(),
+                branch_name
+            )),
+            BranchOrigin::Recreated => self.report(tr!(
+                "{} Recreated branch {}; it had no commits beyond the base",
+                "♻️".bright_cyan(),
+                branch_name
+            )),
+            BranchOrigin::Created => {}
+        }
+
+        // Create sync directories
+        self.create_sync_directories(&repo_name, &worktree_path)
+            .await?;
+
+        // Copy per-worktree config from trunk before symlinking shared dotfiles
+        if let Some(trunk_path) = repo.workdir() {
+            self.copy_from_trunk(
+                trunk_path,
+                &repo_name,
                 worktree_name,
-                &worktree_path,
+                worktree_type,
                 branch_name,
-                base_branch,
+                &worktree_path,
             )
-            .context("Failed to create Git worktree")?;
-
-        // Create sync directories
-        self.create_sync_directories(&repo_name).await?;
+            .await?;
+        }
+        self.generate_editor_workspace(
+            &repo_name,
+            worktree_name,
+            worktree_type,
+            branch_name,
+            &worktree_path,
+        );
 
         // Create symlinks for dotfiles
         self.create_symlinks(&repo_name, &worktree_path).await?;
+        self.install_commit_template(
+            &repo_name,
+            worktree_name,
+            worktree_type,
+            branch_name,
+            &worktree_path,
+        )
+        .await;
+        self.install_git_hooks(&repo_name, &worktree_path).await;
+        self.setup_fork_remotes(&worktree_path, &remotes);
+        self.setup_upstream(worktree_type, branch_name, &worktree_path, &remotes);
+        if cancel::requested() {
+            return Err(self.abandon_add(op).await);
+        }
 
         // Record the worktree in the database
-        self.db
+        let worktree = self
+            .db
             .create_worktree(
                 &repo_name,
                 worktree_name,
@@ -282,60 +888,63 @@ impl WorktreeManager {
                 None, // agent_id will be set later if needed
             )
             .await?;
-        // --- DUAL-WRITE: Update Local Context ---
-        // Get the IMI_PATH (sandbox directory) where .iMi/ cluster hub lives
-        // This is the parent directory containing all worktrees as siblings
-
-        let imi_path = if let Some(registered_repo) = self.db.get_repository(&repo_name).await? {
-            // Registered repo: trunk path is stored, get its parent
-            let trunk_path = PathBuf::from(&registered_repo.path);
-            self.detect_imi_path(&trunk_path)?
-        } else {
-            // Implicit repo: detect from worktree path
-            self.detect_imi_path(&worktree_path)?
-        };
-
-        let local_ctx = LocalContext::new(&imi_path);
-
-        // Transactional update with rollback
-        let update_result = (|| -> Result<()> {
-            // 1. Ensure .iMi structure exists (if this is the first worktree)
-            local_ctx
-                .init()
-                .context("Failed to initialize local .iMi context")?;
+        op.step(steps::DB_RECORD)?;
+        if let Some(base) = base_branch {
+            self.record_worktree_base(
+                &worktree,
+                base,
+                &worktree_path,
+                branch_origin,
+                &remotes.upstream,
+            )
+            .await?;
+        }
+        self.db
+            .set_worktree_metadata(
+                &worktree.id,
+                "branch_origin",
+                serde_json::json!(branch_origin.as_str()),
+            )
+            .await?;
 
-            // 2. Register metadata for Starship
-            local_ctx.register_worktree(worktree_name, worktree_type, None)?;
-            Ok(())
-        })();
+        // --- DUAL-WRITE: Update Local Context ---
+        let update_result = self
+            .register_local_context(&repo_name, worktree_name, worktree_type, &worktree_path)
+            .await;
 
         if let Err(e) = update_result {
             eprintln!(
-                "{} Failed to update local context: {}. Rolling back...",
-                "❌".bright_red(),
-                e
+                "{}",
+                tr!(
+                    "{} Failed to update local context: {}. Rolling back...",
+                    "❌".bright_red(),
+                    e
+                )
             );
 
             // Rollback: Deactivate DB entry
             if let Err(db_err) = self.db.deactivate_worktree(&repo_name, worktree_name).await {
                 eprintln!(
-                    "{} Failed to rollback database entry: {}",
-                    "⚠️".bright_yellow(),
-                    db_err
+                    "{}",
+                    tr!(
+                        "{} Failed to rollback database entry: {}",
+                        "⚠️".bright_yellow(),
+                        db_err
+                    )
                 );
             }
 
             // Rollback: Remove git worktree
-            let current_dir = env::current_dir()?;
-            if let Ok(git_repo) = self.git.find_repository(Some(&current_dir)) {
-                if self.git.worktree_exists(&git_repo, worktree_name) {
-                    if let Err(git_err) = self.git.remove_worktree(&git_repo, worktree_name) {
-                        eprintln!(
+            if self.git.worktree_exists(&repo, worktree_name) {
+                if let Err(git_err) = self.git.remove_worktree(&repo, worktree_name) {
+                    eprintln!(
+                        "{}",
+                        tr!(
                             "{} Failed to remove git worktree: {}",
                             "⚠️".bright_yellow(),
                             git_err
-                        );
-                    }
+                        )
+                    );
                 }
             }
 
@@ -343,66 +952,373 @@ impl WorktreeManager {
             if worktree_path.exists() {
                 if let Err(io_err) = async_fs::remove_dir_all(&worktree_path).await {
                     eprintln!(
-                        "{} Failed to remove worktree directory: {}",
-                        "⚠️".bright_yellow(),
-                        io_err
+                        "{}",
+                        tr!(
+                            "{} Failed to remove worktree directory: {}",
+                            "⚠️".bright_yellow(),
+                            io_err
+                        )
                     );
                 }
             }
 
+            op.finish()?;
             return Err(e);
         }
+        op.finish()?;
+        self.record_event(HookEvent {
+            worktree_name: Some(worktree_name.to_string()),
+            worktree_path: Some(worktree_path.clone()),
+            branch: Some(branch_name.to_string()),
+            ..HookEvent::for_repo(
+                "worktree_created",
+                &repo_name,
+                format!("Created {} worktree {}", worktree_type, worktree_name),
+            )
+        });
 
-        println!(
+        self.report(tr!(
             "{} Local context updated for Starship",
             "✨".bright_magenta()
-        );
+        ));
 
-        println!("{} Worktree created successfully", "✅".bright_green());
+        self.report(tr!("{} Worktree created successfully", "✅".bright_green()));
 
         Ok(worktree_path)
     }
 
-    /// Create PR worktree using gh CLI
-    async fn create_pr_worktree_with_gh(
+    /// Remember the base ref and the commit it resolved to, for ahead/behind
+    /// against the base rather than trunk
+    async fn record_worktree_base(
         &self,
-        pr_number: u32,
-        repo: Option<&str>,
-    ) -> Result<PathBuf> {
-        let repo_name = self.resolve_repo_name(repo).await?;
-        let worktree_name = format!("pr-{}", pr_number);
+        worktree: &Worktree,
+        base: &str,
+        worktree_path: &Path,
+        branch_origin: BranchOrigin,
+        upstream: &str,
+    ) -> Result<()> {
+        // An adopted branch may be ahead of its base; it forked at the merge base
+        let remote_base = format!("{}/{}", upstream, base);
+        let args: &[&str] = match branch_origin.is_adopted() {
+            true => &["merge-base", "HEAD", &remote_base],
+            false => &["rev-parse", "HEAD"],
+        };
+        let base_commit = self
+            .git
+            .execute_git_command(worktree_path, args)
+            .or_else(|_| {
+                self.git
+                    .execute_git_command(worktree_path, &["merge-base", "HEAD", base])
+            })
+            .or_else(|_| {
+                self.git
+                    .execute_git_command(worktree_path, &["rev-parse", "HEAD"])
+            })?
+            .trim()
+            .to_string();
+        self.db
+            .set_worktree_base(&worktree.id, base, &base_commit)
+            .await
+    }
 
-        // Get worktree path - apply IMI_PATH logic to both registered and unregistered repos
-        let (worktree_path, trunk_path) =
-            if let Some(registered_repo) = self.db.get_repository(&repo_name).await? {
-                // Use registered repository path with IMI_PATH detection
-                let registered_path = PathBuf::from(&registered_repo.path);
-                self.ensure_office_layout(&repo_name, &registered_path)?;
-                let imi_path = self.detect_imi_path(&registered_path)?;
-                let worktree_path = imi_path.join(&worktree_name);
-                let trunk_path = self.config.get_trunk_path(&repo_name);
-                (worktree_path, trunk_path)
-            } else {
-                // Fall back to current repository location with IMI_PATH detection
-                let current_dir = env::current_dir()?;
-                let repo = self.git.find_repository(Some(&current_dir))?;
-                let repo_root = repo
-                    .workdir()
-                    .ok_or_else(|| anyhow::anyhow!("Repository has no working directory"))?;
-                let imi_path = self.detect_imi_path(repo_root)?;
-                let worktree_path = imi_path.join(&worktree_name);
+    /// Register a worktree in the repo's local `.iMi/` context (used by Starship)
+    async fn register_local_context(
+        &self,
+        repo_name: &str,
+        worktree_name: &str,
+        worktree_type: &str,
+        worktree_path: &Path,
+    ) -> Result<()> {
+        // Get the IMI_PATH (sandbox directory) where .iMi/ cluster hub lives
+        // This is the parent directory containing all worktrees as siblings
+        let imi_path = if let Some(registered_repo) = self.db.get_repository(repo_name).await? {
+            // Registered repo: trunk path is stored, get its parent
+            let trunk_path = PathBuf::from(&registered_repo.path);
+            self.detect_imi_path(&trunk_path)?
+        } else {
+            // Implicit repo: detect from worktree path
+            self.detect_imi_path(worktree_path)?
+        };
+
+        let local_ctx = LocalContext::new(&imi_path);
+
+        // 1. Ensure .iMi structure exists (if this is the first worktree)
+        local_ctx
+            .init()
+            .context(tr!("Failed to initialize local .iMi context"))?;
+
+        // 2. Register metadata for Starship
+        local_ctx.register_worktree(worktree_name, worktree_type, None)?;
+        Ok(())
+    }
+
+    /// In a fork (`[remotes.repos.<name>]`), make a plain `git push` go to
+    /// the push remote and `gh pr create` target the upstream. The push
+    /// default is this worktree's own and is dropped when it is closed.
+    /// Best-effort.
+    fn setup_fork_remotes(&self, worktree_path: &Path, remotes: &Remotes) {
+        if !remotes.is_fork() {
+            return;
+        }
+        let gh_resolved = format!("remote.{}.gh-resolved", remotes.upstream);
+        let result = self
+            .git
+            .set_worktree_config(worktree_path, "remote.pushDefault", &remotes.push)
+            .and_then(|_| {
+                self.git
+                    .execute_git_command(worktree_path, &["config", &gh_resolved, "base"])
+            });
+        if let Err(e) = result {
+            eprintln!(
+                "{}",
+                tr!(
+                    "{} Failed to set up pushing to {} and pull requests against {}: {}",
+                    "⚠️".bright_yellow(),
+                    remotes.push,
+                    remotes.upstream,
+                    e
+                )
+            );
+        }
+    }
+
+    /// Give a new branch an upstream per `[upstream]` (or `--publish`) so a
+    /// plain `git push` works. A failed push falls back to
+    /// `push.autoSetupRemote`. Best-effort.
+    fn setup_upstream(
+        &self,
+        worktree_type: &str,
+        branch_name: &str,
+        worktree_path: &Path,
+        remotes: &Remotes,
+    ) {
+        let mode = match self.publish {
+            true => UpstreamMode::Push,
+            false => self.config.upstream.mode_for(worktree_type),
+        };
+        if mode == UpstreamMode::Push {
+            let remote = &remotes.push;
+            match self.git.publish_branch(worktree_path, remote, branch_name) {
+                Ok(()) => {
+                    self.report(tr!(
+                        "{} Pushed {} to {} and set it as upstream",
+                        "⬆️".bright_cyan(),
+                        branch_name,
+                        remote
+                    ));
+                    return;
+                }
+                Err(e) => eprintln!(
+                    "{}",
+                    tr!(
+                        "{} Failed to push {}: {}; the first `git push` will set the upstream",
+                        "⚠️".bright_yellow(),
+                        branch_name,
+                        e
+                    )
+                ),
+            }
+        } else if mode == UpstreamMode::None {
+            return;
+        }
+
+        if let Err(e) = self
+            .git
+            .set_worktree_config(worktree_path, "push.autoSetupRemote", "true")
+        {
+            eprintln!(
+                "{}",
+                tr!(
+                    "{} Failed to set push.autoSetupRemote: {}",
+                    "⚠️".bright_yellow(),
+                    e
+                )
+            );
+        }
+    }
+
+    /// Point the worktree's `core.hooksPath` where `[git_hooks]` says, else
+    /// run the install step of each hook manager it uses. Best-effort.
+    async fn install_git_hooks(&self, repo_name: &str, worktree_path: &Path) {
+        if self.skip_git_hooks {
+            return;
+        }
+        let settings = &self.config.git_hooks;
+        if let Some(hooks_path) = settings.hooks_path_for(repo_name) {
+            match self
+                .git
+                .set_worktree_config(worktree_path, "core.hooksPath", hooks_path)
+            {
+                Ok(()) => self.report(tr!("{} Git hooks from {}", "🪝".bright_cyan(), hooks_path)),
+                Err(e) => eprintln!(
+                    "{}",
+                    tr!(
+                        "{} Failed to set core.hooksPath: {}",
+                        "⚠️".bright_yellow(),
+                        e
+                    )
+                ),
+            }
+            return;
+        }
+        if !settings.auto_install {
+            return;
+        }
+
+        for install in git_hooks::detect(worktree_path) {
+            let output = tokio::process::Command::new(&install.program)
+                .args(&install.args)
+                .current_dir(worktree_path)
+                .stdin(std::process::Stdio::null())
+                .output()
+                .await;
+            match output {
+                Ok(output) if output.status.success() => self.report(tr!(
+                    "{} Installed {} hooks",
+                    "🪝".bright_cyan(),
+                    install.manager
+                )),
+                Ok(output) => eprintln!(
+                    "{}",
+                    tr!(
+                        "{} `{}` failed: {}",
+                        "⚠️".bright_yellow(),
+                        install.command_line(),
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    )
+                ),
+                Err(e) => eprintln!(
+                    "{}",
+                    tr!(
+                        "{} Could not run `{}` to install {} hooks: {}",
+                        "⚠️".bright_yellow(),
+                        install.command_line(),
+                        install.manager,
+                        e
+                    )
+                ),
+            }
+        }
+    }
+
+    /// Make the type's commit message template (from the registry, else the
+    /// user sync directory) this worktree's `commit.template`. Best-effort.
+    async fn install_commit_template(
+        &self,
+        repo_name: &str,
+        worktree_name: &str,
+        worktree_type: &str,
+        branch_name: &str,
+        worktree_path: &Path,
+    ) {
+        let stored = self
+            .db
+            .get_worktree_type(worktree_type)
+            .await
+            .ok()
+            .and_then(|t| t.commit_template);
+        let Some(template) = commit_template::resolve(
+            stored.as_deref(),
+            &self.config.get_sync_path(repo_name, true),
+            worktree_type,
+        ) else {
+            return;
+        };
+
+        let message = commit_template::render(
+            &template,
+            repo_name,
+            worktree_name,
+            worktree_type,
+            branch_name,
+        );
+        let installed = self
+            .git
+            .execute_git_command(worktree_path, &["rev-parse", "--absolute-git-dir"])
+            .and_then(|git_dir| {
+                let path = Path::new(git_dir.trim()).join("commit-template");
+                std::fs::write(&path, message)?;
+                self.git.set_worktree_config(
+                    worktree_path,
+                    "commit.template",
+                    &path.to_string_lossy(),
+                )
+            });
+        match installed {
+            Ok(()) => self.report(tr!(
+                "{} Commit template for {} installed",
+                "📝".bright_cyan(),
+                worktree_type
+            )),
+            Err(e) => eprintln!(
+                "{}",
+                tr!(
+                    "{} Failed to install commit template: {}",
+                    "⚠️".bright_yellow(),
+                    e
+                )
+            ),
+        }
+    }
+
+    /// Create PR worktree using gh CLI
+    async fn create_pr_worktree_with_gh(
+        &self,
+        pr_number: u32,
+        repo: Option<&str>,
+    ) -> Result<PathBuf> {
+        let repo_name = self.resolve_repo_name(repo).await?;
+        let worktree_name = format!("pr-{}", pr_number);
+        self.check_size_budget(&repo_name).await?;
+
+        // Get worktree path - apply IMI_PATH logic to both registered and unregistered repos
+        let (worktree_path, trunk_path) =
+            if let Some(registered_repo) = self.db.get_repository(&repo_name).await? {
+                // Use registered repository path with IMI_PATH detection
+                let registered_path = PathBuf::from(&registered_repo.path);
+                self.ensure_office_layout(&repo_name, &registered_path)?;
+                let imi_path = self.detect_imi_path(&registered_path)?;
+                let worktree_path = self
+                    .worktree_root(&repo_name, &imi_path)
+                    .join(&worktree_name);
+                let trunk_path = self.config.get_trunk_path(&repo_name);
+                (worktree_path, trunk_path)
+            } else {
+                // Fall back to current repository location with IMI_PATH detection
+                let current_dir = env::current_dir()?;
+                let repo = self.git.find_repository(Some(&current_dir))?;
+                let repo_root = repo
+                    .workdir()
+                    .ok_or_else(|| anyhow::anyhow!(tr!("Repository has no working directory")))?;
+                let imi_path = self.detect_imi_path(repo_root)?;
+                let worktree_path = self
+                    .worktree_root(&repo_name, &imi_path)
+                    .join(&worktree_name);
                 let trunk_path = self.config.get_trunk_path(&repo_name);
                 (worktree_path, trunk_path)
             };
 
         // Try to checkout PR using gh CLI
+        if capabilities::known_unsupported(|report| &report.gh) {
+            return Err(anyhow::anyhow!(tr!(
+                "Checking out a pull request needs the gh CLI, which `imi verify` didn't find. Install it and run `imi verify` again."
+            )));
+        }
         let _repo = self.git.find_repository(Some(&trunk_path))?;
-        self.git
-            .checkout_pr(&trunk_path, pr_number, &worktree_path)?;
-
-        // Create sync directories and symlinks
-        self.create_sync_directories(&repo_name).await?;
-        self.create_symlinks(&repo_name, &worktree_path).await?;
+        if self.sparse_changed {
+            let dirs = self.pr_changed_dirs(&trunk_path, pr_number).await?;
+            self.git
+                .checkout_pr_sparse(&trunk_path, pr_number, &worktree_path, &dirs)?;
+            self.report(tr!(
+                "{} Sparse checkout of {} changed director{}",
+                "🌱".bright_green(),
+                dirs.len(),
+                if dirs.len() == 1 { "y" } else { "ies" }
+            ));
+        } else {
+            self.git
+                .checkout_pr(&trunk_path, pr_number, &worktree_path)?;
+        }
 
         // Get the actual branch name from the checked out PR
         let branch_name = self
@@ -410,6 +1326,29 @@ impl WorktreeManager {
             .get_current_branch(&worktree_path)
             .unwrap_or_else(|_| format!("pr/{}", pr_number));
 
+        // Create sync directories, copied config and symlinks
+        self.create_sync_directories(&repo_name, &worktree_path)
+            .await?;
+        self.copy_from_trunk(
+            &trunk_path,
+            &repo_name,
+            &worktree_name,
+            "pr",
+            &branch_name,
+            &worktree_path,
+        )
+        .await?;
+        self.generate_editor_workspace(
+            &repo_name,
+            &worktree_name,
+            "pr",
+            &branch_name,
+            &worktree_path,
+        );
+        self.create_symlinks(&repo_name, &worktree_path).await?;
+        self.install_git_hooks(&repo_name, &worktree_path).await;
+        self.write_review_context(&repo_name, pr_number, &worktree_path);
+
         // Record in database
         self.db
             .create_worktree(
@@ -425,38 +1364,58 @@ impl WorktreeManager {
         Ok(worktree_path)
     }
 
-    /// Create sync directories as per PRD specifications
-    async fn create_sync_directories(&self, repo_name: &str) -> Result<()> {
+    /// The directories PR `pr_number` touches, from the GitHub API
+    async fn pr_changed_dirs(&self, trunk_path: &Path, pr_number: u32) -> Result<Vec<String>> {
+        if capabilities::known_unsupported(|report| &report.sparse_checkout_cone) {
+            return Err(anyhow::anyhow!(tr!(
+                "--sparse-changed needs `git sparse-checkout --cone` (git 2.25+), which `imi verify` didn't find"
+            )));
+        }
+        let remote_url = self
+            .git
+            .execute_git_command(trunk_path, &["remote", "get-url", "origin"])?;
+        let files = self
+            .github()?
+            .pull_request_files(remote_url.trim(), pr_number as i32)
+            .await
+            .context(tr!("Failed to list the files the PR changes"))?;
+        Ok(crate::git::sparse_dirs(&files))
+    }
+
+    /// Create sync directories as per PRD specifications, filling them
+    /// from the sync scaffolds; `checkout` is used to detect the language
+    async fn create_sync_directories(&self, repo_name: &str, checkout: &Path) -> Result<()> {
         let user_sync = self.config.get_sync_path(repo_name, true);
         let local_sync = self.config.get_sync_path(repo_name, false);
 
         // Create sync/user directory
         async_fs::create_dir_all(&user_sync)
             .await
-            .context("Failed to create user sync directory")?;
+            .context(tr!("Failed to create user sync directory"))?;
 
         // Create sync/local directory
         async_fs::create_dir_all(&local_sync)
             .await
-            .context("Failed to create local sync directory")?;
-
-        // Create default sync files if they don't exist
-        let coding_rules = user_sync.join("coding-rules.md");
-        if !coding_rules.exists() {
-            async_fs::write(
-                &coding_rules,
-                "# Coding Rules\n\n## Style Guidelines\n\n## Best Practices\n",
-            )
-            .await?;
-        }
+            .context(tr!("Failed to create local sync directory"))?;
+
+        let scaffolds = self.config.sync_settings.scaffold_dirs_for(repo_name)?;
+        let language = sync_scaffold::detect_language(checkout).unwrap_or("unknown");
+        let date = chrono::Local::now().format("%Y-%m-%d").to_string();
+        let vars = [
+            ("repo_name", repo_name),
+            ("language", language),
+            ("date", date.as_str()),
+        ];
 
-        let stack_specific = user_sync.join("stack-specific.md");
-        if !stack_specific.exists() {
-            async_fs::write(
-                &stack_specific,
-                "# Stack-Specific Guidelines\n\n## Frontend\n\n## Backend\n\n## Database\n",
-            )
-            .await?;
+        // Existing files are kept, so this only fills gaps
+        for (subdir, sync_dir) in [("user", &user_sync), ("local", &local_sync)] {
+            for relative in sync_scaffold::apply(&scaffolds, subdir, sync_dir, &vars)? {
+                self.report(tr!(
+                    "{} Created sync file: {}",
+                    "📄".bright_cyan(),
+                    sync_dir.join(relative).display()
+                ));
+            }
         }
 
         Ok(())
@@ -465,16 +1424,59 @@ impl WorktreeManager {
     /// Create symlinks for dotfiles and config files
     async fn create_symlinks(&self, repo_name: &str, worktree_path: &Path) -> Result<()> {
         let local_sync = self.config.get_sync_path(repo_name, false);
-
-        for file_name in &self.config.symlink_files {
-            let source = local_sync.join(file_name);
+        let user_sync = self.config.get_sync_path(repo_name, true);
+        // `imi verify` found no symlink support here; copies at least work
+        let copy_instead = capabilities::known_unsupported(|report| &report.symlinks);
+
+        // Agent instruction files from `imi agent scaffold` live in sync/user.
+        // Unlike .env and friends they aren't gitignored, so they're excluded
+        // to keep worktrees clean and the links out of `git add -A`
+        let links = self
+            .config
+            .symlink_files
+            .iter()
+            .map(|file_name| (local_sync.join(file_name), file_name, false))
+            .chain(
+                self.config
+                    .agent_scaffold
+                    .files
+                    .iter()
+                    .map(|file_name| (user_sync.join(file_name), file_name, true)),
+            );
+        for (source, file_name, exclude) in links {
             let target = worktree_path.join(file_name);
+            let exclude = || match exclude {
+                true => self
+                    .git
+                    .exclude_untracked(worktree_path, &format!("/{}", file_name)),
+                false => Ok(()),
+            };
+            // Linked before the exclude existed
+            if std::fs::read_link(&target).is_ok_and(|linked| linked == source) {
+                exclude()?;
+                continue;
+            }
 
             // Create parent directories if needed
             if let Some(parent) = target.parent() {
                 async_fs::create_dir_all(parent).await?;
             }
 
+            if copy_instead && source.is_file() && !target.exists() {
+                async_fs::copy(&source, &target).await.context(tr!(
+                    "Failed to copy {} to {}",
+                    source.display(),
+                    target.display()
+                ))?;
+                exclude()?;
+                self.report(tr!(
+                    "{} Copied (no symlink support): {}",
+                    "📄".bright_cyan(),
+                    target.display()
+                ));
+                continue;
+            }
+
             // Create symlink if source exists and target doesn't
             if source.exists() && !target.exists() {
                 fs::symlink(&source, &target).map_err(|e| ImiError::SymlinkCreationFailed {
@@ -482,204 +1484,334 @@ impl WorktreeManager {
                     target: target.display().to_string(),
                     io_error: e,
                 })?;
+                exclude()?;
 
-                println!(
+                self.report(tr!(
                     "{} Created symlink: {} -> {}",
                     "🔗".bright_cyan(),
                     target.display(),
                     source.display()
-                );
+                ));
             }
         }
 
         Ok(())
     }
 
-    /// Remove a worktree
-    pub async fn remove_worktree(
+    /// Copy untracked files listed in `copy_from_trunk` from trunk into a new
+    /// worktree, rendering template variables in text files
+    async fn copy_from_trunk(
         &self,
-        name: &str,
-        repo: Option<&str>,
-        keep_branch: bool,
-        keep_remote: bool,
+        trunk_path: &Path,
+        repo_name: &str,
+        worktree_name: &str,
+        worktree_type: &str,
+        branch_name: &str,
+        worktree_path: &Path,
     ) -> Result<()> {
-        let repo_name = self.resolve_repo_name(repo).await?;
-
-        // Find the actual worktree name - it might be prefixed (e.g., feat-iteractive-learning)
-        let actual_worktree_name = self.find_actual_worktree_name(name, &repo_name).await?;
-
-        // Find the repository - use registered path if available, otherwise current directory
-        let repo = if let Some(mut registered_repo) = self.db.get_repository(&repo_name).await? {
-            // Validate and repair path if needed (note: already validated above, but path may have changed)
-            self.validate_and_repair_repository_path(&mut registered_repo)
-                .await?;
-
-            // Use the registered repository path
-            let registered_path = PathBuf::from(&registered_repo.path);
-            self.git
-                .find_repository(Some(&registered_path))
-                .context(format!(
-                    "Git repository not found at registered path: {}",
-                    registered_path.display()
-                ))?
-        } else {
-            // Fall back to current directory
-            let current_dir = env::current_dir()?;
-            self.git.find_repository(Some(&current_dir))?
-        };
-
-        // Use IMI_PATH detection for consistent worktree removal
-        let repo_root = repo
-            .workdir()
-            .ok_or_else(|| anyhow::anyhow!("Repository has no working directory"))?;
-        let imi_path = self.detect_imi_path(repo_root)?;
-        let worktree_path = imi_path.join(&actual_worktree_name);
-
-        // Get worktree info from database before removing
-        let worktree_info = self
-            .db
-            .get_worktree(&repo_name, &actual_worktree_name)
-            .await?;
-        let branch_name = worktree_info.as_ref().map(|w| w.branch_name.clone());
-
-        // Remove directory first
-        if worktree_path.exists() {
-            async_fs::remove_dir_all(&worktree_path)
-                .await
-                .context("Failed to remove worktree directory")?;
-        }
-
-        // Remove from Git (this will now be able to prune since directory is gone)
-        if self.git.worktree_exists(&repo, &actual_worktree_name) {
-            self.git.remove_worktree(&repo, &actual_worktree_name)?;
+        let settings = &self.config.copy_from_trunk;
+        let files = settings.files_for(repo_name, worktree_type);
+        if files.is_empty() {
+            return Ok(());
         }
 
-        // Handle branch deletion (default is to delete unless explicitly kept)
-        if !keep_branch {
-            if let Some(branch) = &branch_name {
-                // Delete local branch
-                self.git.delete_local_branch(&repo, branch)?;
+        let port = settings.port_for(repo_name, worktree_name).to_string();
+        let worktree_path_str = worktree_path.display().to_string();
+        let vars = [
+            ("worktree_name", worktree_name),
+            ("worktree_type", worktree_type),
+            ("repo_name", repo_name),
+            ("branch", branch_name),
+            ("worktree_path", worktree_path_str.as_str()),
+            ("port", port.as_str()),
+        ];
 
-                // Delete remote branch (default is to delete unless explicitly kept)
-                if !keep_remote {
-                    if let Err(e) = self.git.delete_remote_branch(&repo, branch).await {
-                        println!("⚠️ Could not delete remote branch '{}': {}", branch, e);
-                        println!(
-                            "   (This is normal if the branch was already deleted or never pushed)"
-                        );
-                    }
-                }
+        for file_name in &files {
+            let source = trunk_path.join(file_name);
+            if !source.exists() {
+                continue;
             }
-        }
 
-        // Deactivate in database
-        self.db
-            .deactivate_worktree(&repo_name, &actual_worktree_name)
-            .await?;
+            for entry in walkdir::WalkDir::new(&source)
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .filter(|e| e.file_type().is_file())
+            {
+                let relative = entry.path().strip_prefix(trunk_path)?;
+                let target = worktree_path.join(relative);
+
+                // Never clobber a file the branch already tracks or the user edited
+                if target.exists() {
+                    continue;
+                }
+                if let Some(parent) = target.parent() {
+                    async_fs::create_dir_all(parent).await?;
+                }
 
-        // Clean up Local Context (Data Plane)
-        // Determine project root from the worktree path
-        let project_root = self.detect_imi_path(&worktree_path)?;
-        let local_ctx = LocalContext::new(&project_root);
+                let bytes = async_fs::read(entry.path())
+                    .await
+                    .context(tr!("Failed to read {}", entry.path().display()))?;
+                let contents = match String::from_utf8(bytes) {
+                    Ok(text) if settings.template => {
+                        render_copy_template(&text, &vars).into_bytes()
+                    }
+                    Ok(text) => text.into_bytes(),
+                    Err(e) => e.into_bytes(),
+                };
+                async_fs::write(&target, contents)
+                    .await
+                    .context(tr!("Failed to write {}", target.display()))?;
 
-        // Remove the entry from registry.toml and delete any .lock files
-        if let Err(e) = local_ctx.unregister_worktree(&actual_worktree_name) {
-            eprintln!(
-                "{} Warning: Failed to update local context: {}",
-                "⚠️".bright_yellow(),
-                e
-            );
+                self.report(tr!(
+                    "{} Copied from trunk: {}",
+                    "📄".bright_cyan(),
+                    relative.display()
+                ));
+            }
         }
 
         Ok(())
     }
 
-    /// Close a worktree without deleting the branch
-    /// This removes the worktree directory and git reference but preserves the branch
-    pub async fn close_worktree(&self, name: &str, repo: Option<&str>) -> Result<()> {
-        // Try to find the worktree in the database first
-        // This allows the command to work from any directory when an explicit name is provided
-        let (repo_name, actual_worktree_name, worktree_path, git_repo) =
-            if let Some(worktree_info) = self.find_worktree_in_database(name, repo).await? {
-                // Found in database - use the stored information
-                let path = PathBuf::from(&worktree_info.path);
-
-                // Get the repository path from the database
-                let repo_record = self
-                    .db
-                    .get_repository(&worktree_info.repo_name)
-                    .await?
-                    .ok_or_else(|| {
-                        anyhow::anyhow!(
-                            "Repository not found in database: {}",
-                            worktree_info.repo_name
-                        )
-                    })?;
-
-                let repo_path_buf = PathBuf::from(repo_record.path);
+    fn workspace_context<'a>(
+        &self,
+        repo_name: &'a str,
+        worktree_name: &'a str,
+        worktree_type: &'a str,
+        branch_name: &'a str,
+        worktree_path: &'a Path,
+    ) -> WorkspaceContext<'a> {
+        let sync_dir = |is_user: bool| {
+            Some(self.config.get_sync_path(repo_name, is_user)).filter(|dir| dir.exists())
+        };
 
-                // Find the git repository - it should be at the registered path
-                let git_repo = self
-                    .git
-                    .find_repository(Some(&repo_path_buf))
-                    .context(format!(
-                        "Failed to find Git repository at: {}",
-                        repo_path_buf.display()
-                    ))?;
+        WorkspaceContext {
+            repo_name,
+            worktree_name,
+            worktree_type,
+            branch_name,
+            worktree_path,
+            port: self
+                .config
+                .copy_from_trunk
+                .port_for(repo_name, worktree_name),
+            user_sync: sync_dir(true),
+            local_sync: sync_dir(false),
+        }
+    }
 
-                (
-                    worktree_info.repo_name,
-                    worktree_info.worktree_name,
-                    path,
-                    git_repo,
-                )
-            } else {
-                // Fall back to current directory-based lookup
-                let repo_name = self.resolve_repo_name(repo).await?;
-                let actual_worktree_name = self.find_actual_worktree_name(name, &repo_name).await?;
+    /// Write the editor workspace file into a worktree unless it already
+    /// exists; returns its path
+    pub fn write_editor_workspace(
+        &self,
+        repo_name: &str,
+        worktree_name: &str,
+        worktree_type: &str,
+        branch_name: &str,
+        worktree_path: &Path,
+    ) -> Result<PathBuf> {
+        let settings = &self.config.editor;
+        let relative = editor::workspace_file(settings, worktree_name);
+        let workspace = worktree_path.join(&relative);
+        if workspace.exists() {
+            return Ok(workspace);
+        }
 
-                // Find the repository - use registered path if available, otherwise current directory
-                let repo = if let Some(registered_repo) = self.db.get_repository(&repo_name).await?
-                {
-                    // Use the registered repository path
-                    let registered_path = PathBuf::from(&registered_repo.path);
-                    self.git
-                        .find_repository(Some(&registered_path))
-                        .context(format!(
-                            "Git repository not found at registered path: {}",
-                            registered_path.display()
-                        ))?
-                } else {
-                    // Fall back to current directory
-                    let current_dir = env::current_dir()?;
-                    self.git.find_repository(Some(&current_dir))?
-                };
+        let ctx = self.workspace_context(
+            repo_name,
+            worktree_name,
+            worktree_type,
+            branch_name,
+            worktree_path,
+        );
+        if let Some(parent) = workspace.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&workspace, editor::render_workspace(settings, &ctx)?)
+            .context(tr!("Failed to write {}", workspace.display()))?;
+        self.git
+            .exclude_untracked(worktree_path, &format!("/{}", relative.to_string_lossy()))?;
 
-                let repo_root = repo
-                    .workdir()
-                    .ok_or_else(|| anyhow::anyhow!("Repository has no working directory"))?;
-                let imi_path = self.detect_imi_path(repo_root)?;
-                let worktree_path = imi_path.join(&actual_worktree_name);
+        Ok(workspace)
+    }
 
-                (repo_name, actual_worktree_name, worktree_path, repo)
-            };
+    /// Workspace generation during creation: opt-in, and never fatal
+    fn generate_editor_workspace(
+        &self,
+        repo_name: &str,
+        worktree_name: &str,
+        worktree_type: &str,
+        branch_name: &str,
+        worktree_path: &Path,
+    ) {
+        if !self.config.editor.generate_on_create {
+            return;
+        }
 
-        // Remove directory first
-        if worktree_path.exists() {
-            async_fs::remove_dir_all(&worktree_path)
-                .await
-                .context("Failed to remove worktree directory")?;
+        match self.write_editor_workspace(
+            repo_name,
+            worktree_name,
+            worktree_type,
+            branch_name,
+            worktree_path,
+        ) {
+            Ok(workspace) => self.report(tr!(
+                "{} Editor workspace: {}",
+                "🖥️".bright_cyan(),
+                workspace.display()
+            )),
+            Err(e) => eprintln!(
+                "{}",
+                tr!(
+                    "{} Failed to write editor workspace: {}",
+                    "⚠️".bright_yellow(),
+                    e
+                )
+            ),
         }
+    }
+
+    /// Open a worktree (the current one when `name` is None) in the
+    /// configured editor, generating its workspace first if needed
+    pub async fn open_in_editor(&self, name: Option<&str>, repo: Option<&str>) -> Result<PathBuf> {
+        let (repo_name, worktree_name) = match name {
+            Some(name) => {
+                let repo_name = self.resolve_worktree_repo(name, repo).await?;
+                let worktree_name = self.find_actual_worktree_name(name, &repo_name).await?;
+                (repo_name, worktree_name)
+            }
+            None => {
+                let current_dir = env::current_dir()?;
+                let git_repo = self.git.find_repository(Some(&current_dir))?;
+                let worktree_name = git_repo
+                    .workdir()
+                    .and_then(|dir| dir.file_name())
+                    .map(|n| n.to_string_lossy().to_string())
+                    .ok_or_else(|| anyhow::anyhow!(tr!("Not inside a worktree")))?;
+                (self.resolve_repo_name(repo).await?, worktree_name)
+            }
+        };
+
+        let worktree = self
+            .db
+            .get_worktree(&repo_name, &worktree_name)
+            .await?
+            .ok_or_else(|| {
+                anyhow::anyhow!(tr!(
+                    "Worktree '{}' not found in {}",
+                    worktree_name,
+                    repo_name
+                ))
+            })?;
+        let worktree_type = self
+            .db
+            .list_worktree_types()
+            .await?
+            .into_iter()
+            .find(|t| t.id == worktree.type_id)
+            .map(|t| t.name)
+            .unwrap_or_default();
+        let worktree_path = PathBuf::from(&worktree.path);
+
+        let workspace = self.write_editor_workspace(
+            &repo_name,
+            &worktree_name,
+            &worktree_type,
+            &worktree.branch_name,
+            &worktree_path,
+        )?;
+        let ctx = self.workspace_context(
+            &repo_name,
+            &worktree_name,
+            &worktree_type,
+            &worktree.branch_name,
+            &worktree_path,
+        );
+        editor::open_command(&self.config.editor, &ctx, &workspace)?
+            .spawn()
+            .context(tr!(
+                "Failed to launch editor '{}'",
+                self.config.editor.command()
+            ))?;
+        // Counts as a visit for `imi go` ranking and `imi recent`; best-effort
+        let _ = self.db.record_navigation(&worktree.id, "worktree").await;
+
+        Ok(workspace)
+    }
+
+    /// Remove a worktree
+    pub async fn remove_worktree(
+        &self,
+        name: &str,
+        repo: Option<&str>,
+        keep_branch: bool,
+        keep_remote: bool,
+    ) -> Result<()> {
+        let repo_name = self.resolve_worktree_repo(name, repo).await?;
+
+        // Find the actual worktree name - it might be prefixed (e.g., feat-iteractive-learning)
+        let actual_worktree_name = self.find_actual_worktree_name(name, &repo_name).await?;
+
+        let repo = self.open_git_repository(&repo_name).await?;
+
+        // Use IMI_PATH detection for consistent worktree removal
+        let repo_root = repo
+            .workdir()
+            .ok_or_else(|| anyhow::anyhow!(tr!("Repository has no working directory")))?;
+        let imi_path = self.detect_imi_path(repo_root)?;
+        let worktree_path = self
+            .worktree_root(&repo_name, &imi_path)
+            .join(&actual_worktree_name);
+
+        // Get worktree info from database before removing
+        let worktree_info = self
+            .db
+            .get_worktree(&repo_name, &actual_worktree_name)
+            .await?;
+        let branch_name = worktree_info.as_ref().map(|w| w.branch_name.clone());
+
+        // Move the directory out first (to the trash unless it's disabled)
+        self.discard_worktree_dir(
+            &repo_name,
+            &actual_worktree_name,
+            &worktree_path,
+            worktree_info.as_ref(),
+        )
+        .await?;
 
         // Remove from Git (this will now be able to prune since directory is gone)
-        if self.git.worktree_exists(&git_repo, &actual_worktree_name) {
-            self.git.remove_worktree(&git_repo, &actual_worktree_name)?;
+        if self.git.worktree_exists(&repo, &actual_worktree_name) {
+            self.git.remove_worktree(&repo, &actual_worktree_name)?;
+        }
+
+        // Handle branch deletion (default is to delete unless explicitly kept)
+        if !keep_branch {
+            if let Some(branch) = &branch_name {
+                // Delete local branch
+                self.git.delete_local_branch(&repo, branch)?;
+
+                // Delete remote branch (default is to delete unless explicitly kept)
+                if !keep_remote {
+                    if let Err(e) = self.git.delete_remote_branch(&repo, branch).await {
+                        self.report(tr!("⚠️ Could not delete remote branch '{}': {}", branch, e));
+                        self.report(tr!(
+                            "   (This is normal if the branch was already deleted or never pushed)"
+                        ));
+                    }
+                }
+            }
         }
 
         // Deactivate in database
         self.db
             .deactivate_worktree(&repo_name, &actual_worktree_name)
             .await?;
+        self.record_removal(
+            &repo_name,
+            &actual_worktree_name,
+            &worktree_path,
+            branch_name,
+        );
 
         // Clean up Local Context (Data Plane)
         // Determine project root from the worktree path
@@ -689,1686 +1821,6055 @@ impl WorktreeManager {
         // Remove the entry from registry.toml and delete any .lock files
         if let Err(e) = local_ctx.unregister_worktree(&actual_worktree_name) {
             eprintln!(
-                "{} Warning: Failed to update local context: {}",
-                "⚠️".bright_yellow(),
-                e
+                "{}",
+                tr!(
+                    "{} Warning: Failed to update local context: {}",
+                    "⚠️".bright_yellow(),
+                    e
+                )
             );
         }
 
         Ok(())
     }
 
-    /// Show status of worktrees
-    pub async fn show_status(&self, repo: Option<&str>) -> Result<()> {
-        let worktrees = self.db.list_worktrees(repo).await?;
+    /// Append an event to the `imi events` log. Commands are short-lived, so
+    /// their events are recorded without running `[event_hooks]`.
+    fn record_event(&self, event: HookEvent) {
+        if let Ok(Some(log)) = EventLog::from_settings(&self.config.event_hooks) {
+            log.record(&event);
+        }
+    }
 
-        if worktrees.is_empty() {
-            println!("{} No active worktrees found", "ℹ️".bright_blue());
+    fn record_removal(
+        &self,
+        repo_name: &str,
+        worktree_name: &str,
+        worktree_path: &Path,
+        branch: Option<String>,
+    ) {
+        self.record_event(HookEvent {
+            worktree_name: Some(worktree_name.to_string()),
+            worktree_path: Some(worktree_path.to_path_buf()),
+            branch,
+            ..HookEvent::for_repo(
+                "worktree_removed",
+                repo_name,
+                format!("Removed worktree {}", worktree_name),
+            )
+        });
+    }
+
+    /// Move a worktree directory into its repository's trash, or delete it
+    /// when the trash is disabled. Expired trash is purged along the way.
+    async fn discard_worktree_dir(
+        &self,
+        repo_name: &str,
+        worktree_name: &str,
+        worktree_path: &Path,
+        worktree_info: Option<&Worktree>,
+    ) -> Result<()> {
+        if !worktree_path.exists() {
             return Ok(());
         }
+        if !self.config.trash.enabled {
+            return self.delete_worktree_dir(worktree_path).await;
+        }
 
-        println!("\n{}", "Active Worktrees:".bright_cyan().bold());
-        println!("{}", "─".repeat(80).bright_black());
+        let mut entry = TrashEntry::new(repo_name, worktree_name, worktree_path);
+        entry.head_commit = self
+            .git
+            .get_head_commit(worktree_path)
+            .ok()
+            .map(|(commit, _)| commit);
+        if let Some(info) = worktree_info {
+            entry.branch_name = Some(info.branch_name.clone());
+            entry.worktree_type = self
+                .db
+                .list_worktree_types()
+                .await?
+                .into_iter()
+                .find(|t| t.id == info.type_id)
+                .map(|t| t.name);
+        }
 
-        for worktree in worktrees {
-            let status_icon = match worktree.worktree_type.as_str() {
-                "feat" => "🚀",
-                "pr" => "🔍",
-                "fix" => "🔧",
-                "aiops" => "🤖",
-                "devops" => "⚙️",
-                "trunk" => "🌳",
-                _ => "📁",
-            };
+        let trash = Trash::new(&self.detect_imi_path(worktree_path)?);
+        let entry = trash.put(entry)?;
+        self.report(tr!(
+            "{} Moved {} to the trash (restore with: imi trash restore {})",
+            "🗑️".bright_yellow(),
+            worktree_name,
+            entry.id
+        ));
 
-            println!(
-                "{} {} {} ({})",
-                status_icon,
-                worktree.worktree_name.bright_green(),
-                worktree.branch_name.bright_yellow(),
-                worktree.worktree_type.bright_blue()
+        if let Err(e) = trash.purge_expired(self.config.trash.retention_days) {
+            eprintln!(
+                "{}",
+                tr!(
+                    "{} Failed to purge expired trash: {}",
+                    "⚠️".bright_yellow(),
+                    e
+                )
             );
+        }
+        Ok(())
+    }
 
-            // Get Git status if worktree path exists
-            let worktree_path = PathBuf::from(&worktree.path);
-            if worktree_path.exists() {
-                if let Ok(git_status) = self.git.get_worktree_status(&worktree_path) {
-                    self.print_git_status(&git_status);
+    /// Delete a worktree directory. With `trash.background_delete` it is
+    /// renamed aside first and deleted by a detached process, so the path is
+    /// free as soon as this returns.
+    async fn delete_worktree_dir(&self, path: &Path) -> Result<()> {
+        if self.config.trash.background_delete {
+            let pending = PendingDeletes::new(&self.detect_imi_path(path)?);
+            match pending.stage(path) {
+                Ok(entry) => {
+                    if let Err(e) = PendingDeletes::spawn_delete(&entry) {
+                        eprintln!(
+                            "{}",
+                            tr!(
+                                "{} {}; `imi prune` will finish deleting {}",
+                                "⚠️".bright_yellow(),
+                                e,
+                                entry.path.display()
+                            )
+                        );
+                    }
+                    return Ok(());
                 }
-            } else {
-                println!(
-                    "   {} Path not found: {}",
-                    "⚠️".bright_yellow(),
-                    worktree.path
-                );
+                Err(e) => eprintln!(
+                    "{}",
+                    tr!("{} {}, deleting in place", "⚠️".bright_yellow(), e)
+                ),
             }
-
-            if let Some(agent_id) = &worktree.agent_id {
-                println!("   {} Agent: {}", "🤖".bright_magenta(), agent_id);
-            }
-
-            println!(
-                "   {} Created: {}",
-                "📅".bright_black(),
-                worktree.created_at.format("%Y-%m-%d %H:%M:%S")
-            );
-            println!();
         }
+        async_fs::remove_dir_all(path)
+            .await
+            .context(tr!("Failed to remove worktree directory"))
+    }
 
-        Ok(())
+    /// Directories of one repository, or of every registered repository,
+    /// still waiting for background deletion
+    pub async fn pending_deletes(&self, repo: Option<&str>) -> Result<Vec<PendingDelete>> {
+        let mut entries = Vec::new();
+        for container in self.repo_containers(repo).await? {
+            entries.extend(PendingDeletes::new(&container).list()?);
+        }
+        Ok(entries)
     }
 
-    fn print_git_status(&self, status: &WorktreeStatus) {
-        if status.clean {
-            println!("   {} Working tree clean", "✅".bright_green());
-        } else {
-            if !status.modified_files.is_empty() {
-                println!(
-                    "   {} Modified: {}",
-                    "📝".bright_yellow(),
-                    status.modified_files.len()
-                );
+    /// Trunks with work in them, when `[trunk_protection]` is on. Read-only:
+    /// locking is left to [`Self::refresh_trunk_lock`] on merge and sync
+    pub async fn trunk_violations(&self, repo: Option<&str>) -> Vec<TrunkViolation> {
+        if !self.config.trunk_protection.active() {
+            return Vec::new();
+        }
+        let repositories = match repo {
+            Some(repo) => match self.resolve_repo_name(Some(repo)).await {
+                Ok(name) => self.db.get_repository(&name).await.ok().flatten(),
+                Err(_) => None,
             }
-            if !status.new_files.is_empty() {
-                println!(
-                    "   {} New files: {}",
-                    "➕".bright_green(),
-                    status.new_files.len()
-                );
+            .into_iter()
+            .collect(),
+            None => self.db.list_repositories().await.unwrap_or_default(),
+        };
+
+        let mut violations = Vec::new();
+        for repository in repositories {
+            let trunk = PathBuf::from(&repository.path);
+            if !trunk.exists() {
+                continue;
             }
-            if !status.deleted_files.is_empty() {
-                println!(
-                    "   {} Deleted: {}",
-                    "➖".bright_red(),
-                    status.deleted_files.len()
-                );
+            if let Ok(Some(violation)) = trunk_protection::inspect(
+                &self.git,
+                &repository.name,
+                &trunk,
+                &repository.default_branch,
+            ) {
+                violations.push(violation);
             }
         }
+        violations
+    }
 
-        if status.commits_ahead > 0 {
-            println!(
-                "   {} {} commits ahead",
-                "⬆️".bright_green(),
-                status.commits_ahead
-            );
+    /// With `enforce`, make a clean trunk's files read-only; without it,
+    /// unlock a trunk locked earlier. Best-effort, after the merge and sync
+    /// paths have changed the trunk
+    fn refresh_trunk_lock(&self, repo_name: &str, trunk: &Path, default_branch: &str) {
+        if !self.config.trunk_protection.enforce {
+            if trunk_protection::is_locked(&self.git, trunk) {
+                let _ = trunk_protection::unlock(&self.git, trunk);
+            }
+            return;
         }
-        if status.commits_behind > 0 {
-            println!(
-                "   {} {} commits behind",
-                "⬇️".bright_red(),
-                status.commits_behind
-            );
+        if self.config.permissions.read_only {
+            return;
+        }
+        if let Ok(None) = trunk_protection::inspect(&self.git, repo_name, trunk, default_branch) {
+            let _ = trunk_protection::lock(&self.git, trunk);
         }
     }
 
-    fn print_git_status_indented(&self, status: &WorktreeStatus) {
-        if status.clean {
-            println!("     {} Working tree clean", "✅".bright_green());
-        } else {
-            if !status.modified_files.is_empty() {
-                println!(
-                    "     {} Modified: {}",
-                    "📝".bright_yellow(),
-                    status.modified_files.len()
-                );
-            }
-            if !status.new_files.is_empty() {
-                println!(
-                    "     {} New files: {}",
-                    "➕".bright_green(),
-                    status.new_files.len()
-                );
-            }
-            if !status.deleted_files.is_empty() {
-                println!(
-                    "     {} Deleted: {}",
-                    "➖".bright_red(),
-                    status.deleted_files.len()
-                );
-            }
+    /// The `imi status` footer for trunks with work in them
+    pub fn print_trunk_violations(&self, violations: &[TrunkViolation]) {
+        for violation in violations {
+            violation.print();
         }
+    }
 
-        if status.commits_ahead > 0 {
-            println!(
-                "     {} {} commits ahead",
-                "⬆️".bright_green(),
-                status.commits_ahead
-            );
-        }
-        if status.commits_behind > 0 {
-            println!(
-                "     {} {} commits behind",
-                "⬇️".bright_red(),
-                status.commits_behind
-            );
+    /// The trash of one repository, or of every registered repository
+    async fn trashes(&self, repo: Option<&str>) -> Result<Vec<Trash>> {
+        Ok(self
+            .repo_containers(repo)
+            .await?
+            .iter()
+            .map(|container| Trash::new(container))
+            .collect())
+    }
+
+    /// Directories holding the trunk and worktrees of one repository, or of
+    /// every registered repository
+    async fn repo_containers(&self, repo: Option<&str>) -> Result<Vec<PathBuf>> {
+        let repos =
+            match repo {
+                Some(repo) => {
+                    let repo_name = self.resolve_repo_name(Some(repo)).await?;
+                    vec![self.db.get_repository(&repo_name).await?.ok_or_else(|| {
+                        anyhow::anyhow!(tr!("Repository not found: {}", repo_name))
+                    })?]
+                }
+                None => self.db.list_repositories().await?,
+            };
+
+        repos
+            .iter()
+            .map(|repo| self.detect_imi_path(Path::new(&repo.path)))
+            .collect()
+    }
+
+    /// Trashed worktrees, newest first
+    pub async fn trash_entries(&self, repo: Option<&str>) -> Result<Vec<TrashEntry>> {
+        let mut entries = Vec::new();
+        for trash in self.trashes(repo).await? {
+            entries.extend(trash.list()?);
         }
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.trashed_at));
+        Ok(entries)
     }
 
-    /// Smart context-aware list command
-    /// Implements the decision tree based on context and flags
-    pub async fn list_smart(
+    /// Put a trashed worktree back where it was, uncommitted files included.
+    /// The branch is recreated at the trashed HEAD if it was deleted since.
+    pub async fn restore_from_trash(
         &self,
+        id_or_name: &str,
         repo: Option<&str>,
-        worktrees_flag: bool,
-        projects_flag: bool,
-    ) -> Result<()> {
-        let current_dir = env::current_dir()?;
-        let git_context = self.git.detect_context(Some(&current_dir));
-
-        // Extract repository information from context
-        let detected_repo_path = git_context.repo_path();
-        let detected_repo_name = if let Some(repo_path) = detected_repo_path {
-            self.git.get_repo_name(repo_path).ok()
+    ) -> Result<TrashEntry> {
+        let mut found = None;
+        for trash in self.trashes(repo).await? {
+            if let Some(entry) = trash.find(id_or_name)? {
+                if found
+                    .as_ref()
+                    .is_none_or(|(_, newest): &(Trash, TrashEntry)| {
+                        entry.id == id_or_name || entry.trashed_at > newest.trashed_at
+                    })
+                {
+                    found = Some((trash, entry));
+                }
+            }
+        }
+        let (trash, entry) = found
+            .ok_or_else(|| anyhow::anyhow!(tr!("Nothing named '{}' in the trash", id_or_name)))?;
+
+        let target = &entry.original_path;
+        if target.exists() {
+            return Err(anyhow::anyhow!(tr!(
+                "{} already exists; move it away before restoring",
+                target.display()
+            )));
+        }
+        let target_str = target
+            .to_str()
+            .context(tr!("Worktree path is not valid UTF-8"))?;
+        let branch = entry
+            .branch_name
+            .clone()
+            .unwrap_or_else(|| entry.worktree_name.clone());
+
+        // Check out a fresh worktree without touching files, then move the
+        // trashed files over it, keeping the new .git link
+        let trunk_path = self.get_trunk_worktree(Some(&entry.repo_name)).await?;
+        let branch_ref = format!("refs/heads/{}", branch);
+        if self
+            .git
+            .execute_git_command(
+                &trunk_path,
+                &["rev-parse", "--verify", "--quiet", &branch_ref],
+            )
+            .is_ok()
+        {
+            self.git.execute_git_command(
+                &trunk_path,
+                &["worktree", "add", "--no-checkout", target_str, &branch],
+            )?;
         } else {
-            None
-        };
+            let commit = entry.head_commit.as_deref().context(tr!(
+                "Branch '{}' no longer exists and no commit was recorded to recreate it",
+                branch
+            ))?;
+            self.git.execute_git_command(
+                &trunk_path,
+                &[
+                    "worktree",
+                    "add",
+                    "--no-checkout",
+                    "-b",
+                    &branch,
+                    target_str,
+                    commit,
+                ],
+            )?;
+        }
 
-        // Handle explicit --projects flag
-        if projects_flag {
-            return self.list_all_repositories().await;
-        }
-
-        // Handle explicit --worktrees flag
-        if worktrees_flag {
-            // If repo is specified, list that repo's worktrees
-            if let Some(repo_name) = repo {
-                return self.list_worktrees_detailed(Some(repo_name)).await;
-            }
-            // If in a repo, list that repo's worktrees
-            if let Some(repo_name) = detected_repo_name.as_ref() {
-                return self.list_worktrees_detailed(Some(repo_name)).await;
+        for child in std::fs::read_dir(&entry.path)? {
+            let child = child?;
+            if child.file_name() == ".git" {
+                continue;
             }
-            // Not in a repo, list all worktrees
-            return self.list_worktrees_detailed(None).await;
+            std::fs::rename(child.path(), target.join(child.file_name()))
+                .context(tr!("Failed to restore {}", child.path().display()))?;
         }
+        std::fs::remove_dir_all(&entry.path).context(tr!("Failed to clean up the trash entry"))?;
+        trash.forget(&entry)?;
 
-        // Handle explicit --repo flag
-        if let Some(repo_name) = repo {
-            // Check if repo is registered
-            if let Some(_registered) = self.db.get_repository(repo_name).await? {
-                // Registered: list its worktrees
-                return self.list_worktrees_detailed(Some(repo_name)).await;
-            } else {
-                // Not registered: show helpful error
-                println!(
-                    "{} Repository '{}' is not registered",
+        // Rebuild the index from HEAD; working files stay as they were
+        self.git
+            .execute_git_command(target, &["reset", "--quiet"])?;
+
+        let worktree_type = entry.worktree_type.as_deref().unwrap_or("feat");
+        self.db
+            .create_worktree(
+                &entry.repo_name,
+                &entry.worktree_name,
+                &branch,
+                worktree_type,
+                target_str,
+                None,
+            )
+            .await
+            .context(tr!(
+                "Restored the files but failed to register the worktree"
+            ))?;
+        if let Err(e) = self
+            .register_local_context(
+                &entry.repo_name,
+                &entry.worktree_name,
+                worktree_type,
+                target,
+            )
+            .await
+        {
+            eprintln!(
+                "{}",
+                tr!(
+                    "{} Warning: Failed to update local context: {}",
                     "⚠️".bright_yellow(),
-                    repo_name.bright_red()
-                );
-                println!(
-                    "\n{} To register this repository, run {} from its directory",
-                    "💡".bright_yellow(),
-                    "imi trunk".bright_green()
-                );
-                return Ok(());
-            }
+                    e
+                )
+            );
         }
 
-        // No explicit flags - use context detection
-        if git_context.is_in_repository() {
-            // We're in a git repository
-            if let Some(repo_name) = detected_repo_name {
-                // Check if this repo is registered
-                if let Some(_registered) = self.db.get_repository(&repo_name).await? {
-                    // Registered: list its worktrees
-                    return self.list_worktrees_detailed(Some(&repo_name)).await;
-                } else {
-                    // Unregistered: show helpful message
-                    println!(
-                        "\n{} {} {}",
-                        "📦".bright_cyan(),
-                        "Repository:".bright_white(),
-                        repo_name.bright_yellow()
-                    );
-                    println!(
-                        "{} {}",
-                        "⚠️".bright_yellow(),
-                        "This repository is not registered with iMi".bright_yellow()
-                    );
-                    println!(
-                        "\n{} To start using iMi with this repository:",
-                        "💡".bright_yellow()
-                    );
-                    println!(
-                        "   1. Run {} to create the trunk worktree",
-                        "imi trunk".bright_green()
-                    );
-                    println!(
-                        "   2. Then use {} to create feature worktrees",
-                        "imi feat <name>".bright_green()
-                    );
-                    println!(
-                        "\n{} Once registered, {} will show worktrees for this repo",
-                        "ℹ️".bright_blue(),
-                        "imi list".bright_cyan()
-                    );
-                    return Ok(());
-                }
+        Ok(entry)
+    }
+
+    /// Delete trashed worktrees for good, or only those past the retention
+    /// period; returns what was deleted
+    pub async fn empty_trash(
+        &self,
+        repo: Option<&str>,
+        expired_only: bool,
+    ) -> Result<Vec<TrashEntry>> {
+        let mut purged = Vec::new();
+        for trash in self.trashes(repo).await? {
+            if expired_only {
+                purged.extend(trash.purge_expired(self.config.trash.retention_days)?);
             } else {
-                // In a repo but couldn't detect name - unusual case
-                println!(
-                    "{} You are in a Git repository, but the repository name could not be determined",
-                    "⚠️".bright_yellow()
-                );
-                println!(
-                    "\n{} Run {} to register this repository",
-                    "💡".bright_yellow(),
-                    "imi trunk".bright_green()
-                );
-                return Ok(());
+                for entry in trash.list()? {
+                    trash.purge(&entry)?;
+                    purged.push(entry);
+                }
             }
-        } else {
-            // Not in a git repository - list all repositories
-            return self.list_all_repositories().await;
         }
+        Ok(purged)
     }
 
-    /// List all registered repositories with worktree counts
-    pub async fn list_all_repositories(&self) -> Result<()> {
-        let repositories = self.db.list_repositories().await?;
+    /// Close a worktree without deleting the branch
+    /// This removes the worktree directory and git reference but preserves the branch
+    pub async fn close_worktree(&self, name: &str, repo: Option<&str>) -> Result<()> {
+        let repo_name = self.resolve_worktree_repo(name, repo).await?;
+        let actual_worktree_name = self.find_actual_worktree_name(name, &repo_name).await?;
+        let git_repo = self.open_git_repository(&repo_name).await?;
 
-        if repositories.is_empty() {
-            println!("\n{}", "No Registered Repositories".bright_cyan().bold());
-            println!("{}", "─".repeat(80).bright_black());
-            println!("\n{} No repositories registered yet", "ℹ️".bright_blue());
-            println!(
-                "\n{} Run {} from a git repository to register it",
-                "💡".bright_yellow(),
-                "imi trunk".bright_green()
-            );
-            return Ok(());
+        // Prefer the recorded path; fall back to where the worktree would live
+        let worktree_info = self
+            .db
+            .get_worktree(&repo_name, &actual_worktree_name)
+            .await?;
+        let worktree_path = match &worktree_info {
+            Some(worktree_info) => PathBuf::from(&worktree_info.path),
+            None => {
+                let repo_root = git_repo
+                    .workdir()
+                    .ok_or_else(|| anyhow::anyhow!(tr!("Repository has no working directory")))?;
+                let imi_path = self.detect_imi_path(repo_root)?;
+                self.worktree_root(&repo_name, &imi_path)
+                    .join(&actual_worktree_name)
+            }
+        };
+
+        // Move the directory out first (to the trash unless it's disabled)
+        self.discard_worktree_dir(
+            &repo_name,
+            &actual_worktree_name,
+            &worktree_path,
+            worktree_info.as_ref(),
+        )
+        .await?;
+
+        // Remove from Git (this will now be able to prune since directory is gone)
+        if self.git.worktree_exists(&git_repo, &actual_worktree_name) {
+            self.git.remove_worktree(&git_repo, &actual_worktree_name)?;
         }
 
-        println!("\n{}", "Registered Repositories".bright_cyan().bold());
-        println!("{}", "═".repeat(80).bright_black());
+        // Deactivate in database
+        self.db
+            .deactivate_worktree(&repo_name, &actual_worktree_name)
+            .await?;
+        self.record_removal(
+            &repo_name,
+            &actual_worktree_name,
+            &worktree_path,
+            worktree_info.map(|info| info.branch_name),
+        );
 
-        for (i, repo) in repositories.iter().enumerate() {
-            // Get worktree count for this repo
-            let worktrees = self.db.list_worktrees(Some(&repo.name)).await?;
-            let worktree_count = worktrees.len();
+        // Clean up Local Context (Data Plane)
+        // Determine project root from the worktree path
+        let project_root = self.detect_imi_path(&worktree_path)?;
+        let local_ctx = LocalContext::new(&project_root);
 
-            println!(
-                "\n{} {} {}",
-                format!("{}.", i + 1).bright_black(),
-                "📦".bright_cyan(),
-                repo.name.bright_green().bold()
-            );
-            println!(
-                "   {} Path: {}",
-                "📂".bright_cyan(),
-                repo.path.bright_white()
-            );
-            println!(
-                "   {} Branch: {}",
-                "🌿".bright_cyan(),
-                repo.default_branch.bright_yellow()
+        // Remove the entry from registry.toml and delete any .lock files
+        if let Err(e) = local_ctx.unregister_worktree(&actual_worktree_name) {
+            eprintln!(
+                "{}",
+                tr!(
+                    "{} Warning: Failed to update local context: {}",
+                    "⚠️".bright_yellow(),
+                    e
+                )
             );
+        }
 
-            if !repo.remote_url.is_empty() {
-                println!(
-                    "   {} Remote: {}",
-                    "🔗".bright_cyan(),
-                    repo.remote_url.bright_white()
-                );
-            }
+        Ok(())
+    }
 
-            // Show worktree count with appropriate icon
-            let wt_icon = if worktree_count == 0 { "📭" } else { "📬" };
-            println!(
-                "   {} Worktrees: {}",
-                wt_icon,
-                if worktree_count == 0 {
-                    "None".bright_black().to_string()
-                } else {
-                    worktree_count.to_string().bright_white().to_string()
-                }
-            );
+    /// Branch relationships among a repository's active worktrees
+    pub async fn branch_graph(&self, repo: Option<&str>) -> Result<BranchGraph> {
+        let repo_name = self.resolve_repo_name(repo).await?;
+        let git_repo = self.open_git_repository(&repo_name).await?;
+        let worktrees = self.db.list_worktrees(Some(&repo_name)).await?;
+        let parents = self.db.list_worktree_parents().await?;
+        let bases = self.db.list_worktree_bases().await?;
 
-            println!(
-                "   {} Created: {}",
-                "📅".bright_black(),
-                repo.created_at
-                    .format("%Y-%m-%d %H:%M:%S")
-                    .to_string()
-                    .bright_green()
-            );
+        let inputs = worktrees
+            .iter()
+            .filter(|wt| !wt.name.starts_with("trunk-"))
+            .map(|wt| GraphInput {
+                worktree_name: wt.name.clone(),
+                branch_name: wt.branch_name.clone(),
+                recorded_parent: parents
+                    .iter()
+                    .find(|(id, _)| *id == wt.id)
+                    .and_then(|(_, parent_id)| worktrees.iter().find(|p| p.id == *parent_id))
+                    .map(|parent| parent.name.clone()),
+                recorded_base: bases
+                    .iter()
+                    .find(|(id, _, _)| *id == wt.id)
+                    .map(|(_, _, commit)| commit.clone()),
+            })
+            .collect();
 
-            if i < repositories.len() - 1 {
-                println!("{}", "─".repeat(80).bright_black());
+        BranchGraph::build(
+            &git_repo,
+            &repo_name,
+            &self.config.git_settings.default_branch,
+            inputs,
+        )
+    }
+
+    /// Disk usage of a repository's worktrees against its size budget.
+    /// Sizes come from the du cache unless `refresh` is set.
+    pub async fn size_budget_report(
+        &self,
+        repo: Option<&str>,
+        refresh: bool,
+    ) -> Result<BudgetReport> {
+        let repo_name = self.resolve_repo_name(repo).await?;
+        let worktrees = self.db.list_worktrees(Some(&repo_name)).await?;
+        let settings = self.config.size_budget.clone();
+        let git = self.git.clone();
+        let trunk_branch = self.config.git_settings.default_branch.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let mut cache = DiskUsageCache::load(settings.cache_ttl_secs);
+            let usage = worktrees
+                .iter()
+                .map(|wt| {
+                    let path = PathBuf::from(&wt.path);
+                    let bytes = match (path.exists(), refresh) {
+                        (false, _) => 0,
+                        (true, true) => cache.refresh(&path),
+                        (true, false) => cache.size_of(&path),
+                    };
+                    let merged = wt.merged_at.is_some()
+                        || wt.pr_state.as_deref() == Some("merged")
+                        || (path.exists()
+                            && git
+                                .is_branch_merged(&path, &wt.branch_name, &trunk_branch)
+                                .unwrap_or(false));
+
+                    WorktreeUsage {
+                        worktree_name: wt.name.clone(),
+                        bytes,
+                        merged,
+                        is_trunk: wt.name.starts_with("trunk-") || wt.branch_name == trunk_branch,
+                        last_activity: git
+                            .get_head_commit_time(&path)
+                            .unwrap_or(wt.created_at)
+                            .max(wt.created_at),
+                        path,
+                    }
+                })
+                .collect();
+            if let Err(e) = cache.save() {
+                if retry::verbose() {
+                    eprintln!("{} {:#}", "⚠️".bright_yellow(), e);
+                }
             }
+
+            BudgetReport::new(
+                &repo_name,
+                settings.budget_bytes(&repo_name),
+                usage,
+                settings.suggestions,
+            )
+        })
+        .await
+        .context(tr!("Disk usage scan failed"))
+    }
+
+    /// Warn, or refuse per `[size_budget]`, before adding a worktree to a
+    /// repository that is over its budget
+    async fn check_size_budget(&self, repo_name: &str) -> Result<()> {
+        if self.config.size_budget.budget_bytes(repo_name).is_none() {
+            return Ok(());
         }
+        let report = self.size_budget_report(Some(repo_name), false).await?;
+        let Some(budget) = report.budget_bytes.filter(|_| report.over_budget()) else {
+            return Ok(());
+        };
 
-        println!(
-            "\n{} Total: {} repositories",
-            "📊".bright_cyan(),
-            repositories.len().to_string().bright_white().bold()
+        let usage = tr!(
+            "{} worktrees use {} of the {} budget",
+            repo_name,
+            self.format_size(report.total_bytes),
+            self.format_size(budget)
         );
-        println!();
+        let candidates = report.candidates.join(", ");
+        if self.config.size_budget.action == BudgetAction::Refuse && !self.ignore_budget {
+            return Err(anyhow::anyhow!(tr!(
+                "{}. Close some worktrees first (candidates: {}) or pass --ignore-budget",
+                usage,
+                if candidates.is_empty() {
+                    "none"
+                } else {
+                    &candidates
+                }
+            )));
+        }
 
+        self.report(format_args!("{} {}", "⚠️".bright_yellow(), usage));
+        if !candidates.is_empty() {
+            self.report(tr!(
+                "   {} Cleanup candidates: {} (see {})",
+                "💡".bright_yellow(),
+                candidates,
+                "imi size-budget".bright_cyan()
+            ));
+        }
         Ok(())
     }
 
-    /// List all worktrees with detailed metadata
-    pub async fn list_worktrees_detailed(&self, repo: Option<&str>) -> Result<()> {
-        let worktrees = self.db.list_worktrees(repo).await?;
-
-        if worktrees.is_empty() {
-            println!("{} No active worktrees found", "ℹ️".bright_blue());
-            return Ok(());
-        }
-
+    /// Print `imi size-budget`
+    pub fn print_size_budget(&self, report: &BudgetReport) {
         println!(
-            "\n{}",
-            "Detailed Worktree Information:".bright_cyan().bold()
+            "\n{} {}",
+            "💾".bright_cyan(),
+            format!("Disk usage: {}", report.repo_name)
+                .bright_cyan()
+                .bold()
         );
-        println!("{}", "═".repeat(100).bright_black());
-
-        for (i, worktree) in worktrees.iter().enumerate() {
-            let status_icon = match worktree.worktree_type.as_str() {
-                "feat" => "🚀",
-                "pr" => "🔍",
-                "fix" => "🔧",
-                "aiops" => "🤖",
-                "devops" => "⚙️",
-                "trunk" => "🌳",
-                _ => "📁",
-            };
+        println!("{}", "─".repeat(80).bright_black());
 
+        let now = chrono::Utc::now();
+        for wt in &report.worktrees {
+            let mut notes = vec![format!("{}d idle", (now - wt.last_activity).num_days())];
+            if wt.merged {
+                notes.push("merged".to_string());
+            }
+            if wt.is_trunk {
+                notes = vec!["trunk".to_string()];
+            }
             println!(
-                "\n{} {} {} {} ({})",
-                format!("{}.", i + 1).bright_black(),
-                status_icon,
-                worktree.worktree_name.bright_green().bold(),
-                worktree.branch_name.bright_yellow(),
-                worktree.worktree_type.bright_blue()
+                "   {:>10}  {:<36} {}",
+                self.format_size(wt.bytes).bright_white(),
+                wt.worktree_name.bright_green(),
+                notes.join(", ").bright_black()
             );
+        }
 
-            // Repository and path info
-            println!(
-                "   {} Repo: {}",
-                "📦".bright_cyan(),
-                worktree.repo_name.bright_white()
-            );
-            println!(
-                "   {} Path: {}",
-                "📂".bright_cyan(),
-                worktree.path.bright_white()
-            );
+        let total = self.format_size(report.total_bytes);
+        match report.budget_bytes {
+            Some(budget) if report.over_budget() => println!(
+                "{}",
+                tr!(
+                    "\n{} Total {} is over the {} budget",
+                    "⚠️".bright_yellow(),
+                    total.bright_red(),
+                    self.format_size(budget)
+                )
+            ),
+            Some(budget) => println!(
+                "{}",
+                tr!(
+                    "\n{} Total {} of {} budget",
+                    "✅".bright_green(),
+                    total.bright_green(),
+                    self.format_size(budget)
+                )
+            ),
+            None => println!(
+                "{}",
+                tr!(
+                    "\n{} Total {} (no budget set; see [size_budget] in config)",
+                    "📊".bright_cyan(),
+                    total.bright_white()
+                )
+            ),
+        }
 
-            // Timestamps
+        if !report.candidates.is_empty() {
             println!(
-                "   {} Created: {} | Updated: {}",
-                "📅".bright_cyan(),
-                worktree
-                    .created_at
-                    .format("%Y-%m-%d %H:%M:%S")
-                    .to_string()
-                    .bright_green(),
-                worktree
-                    .updated_at
-                    .format("%Y-%m-%d %H:%M:%S")
-                    .to_string()
-                    .bright_yellow()
+                "{}",
+                tr!(
+                    "{} Cleanup candidates: {}",
+                    "💡".bright_yellow(),
+                    report.candidates.join(", ").bright_cyan()
+                )
             );
+        }
+    }
 
-            // Agent assignment
-            if let Some(agent_id) = &worktree.agent_id {
-                println!(
-                    "   {} Agent: {}",
-                    "🤖".bright_magenta(),
-                    agent_id.bright_white()
-                );
-            } else {
-                println!(
-                    "   {} Agent: {}",
-                    "🤖".bright_black(),
-                    "Unassigned".bright_black()
-                );
+    /// Look up the GitHub PR opened from each active worktree's branch and
+    /// record it; returns how many links changed. Failed lookups (offline,
+    /// no access) keep whatever was recorded before.
+    pub async fn refresh_pull_requests(&self, repo: Option<&str>) -> Result<usize> {
+        let repositories = self.db.list_repositories().await?;
+        let github = self.github()?;
+
+        let mut tasks = tokio::task::JoinSet::new();
+        for wt in self.db.list_worktrees(repo).await? {
+            let Some(repository) = repositories.iter().find(|r| r.id == wt.project_id) else {
+                continue;
+            };
+            // A merged PR is final, and trunk never has one
+            if wt.pr_state.as_deref() == Some("merged")
+                || wt.branch_name == repository.default_branch
+            {
+                continue;
             }
 
-            // Database ID for debugging
-            println!(
-                "   {} ID: {}",
-                "🔑".bright_black(),
-                worktree.id.to_string().bright_black()
-            );
+            let github = github.clone();
+            let remote_url = repository.remote_url.clone();
+            tasks.spawn(async move {
+                let found = github.find_pull_request(&remote_url, &wt.branch_name).await;
+                (wt, found)
+            });
+        }
 
-            // Git status if worktree exists
-            let worktree_path = PathBuf::from(&worktree.path);
-            if worktree_path.exists() {
-                if let Ok(git_status) = self.git.get_worktree_status(&worktree_path) {
-                    println!("   {} Git Status:", "📊".bright_cyan());
-                    self.print_git_status_indented(&git_status);
+        let mut changed = 0;
+        while let Some(joined) = tasks.join_next().await {
+            let (wt, found) = joined?;
+            let pr = match found {
+                Ok(pr) => pr,
+                Err(e) => {
+                    if retry::verbose() {
+                        eprintln!(
+                            "{}",
+                            tr!(
+                                "{} PR lookup for {} failed: {}",
+                                "⚠️".bright_yellow(),
+                                wt.name,
+                                format!("{:#}", e)
+                            )
+                        );
+                    }
+                    continue;
                 }
-            } else {
-                println!(
-                    "   {} Status: {}",
-                    "⚠️".bright_yellow(),
-                    "Path not found".bright_red()
-                );
-            }
+            };
 
-            if i < worktrees.len() - 1 {
-                println!("{}", "─".repeat(100).bright_black());
+            let number = pr.as_ref().map(|pr| pr.number);
+            let state = pr.as_ref().map(|pr| pr.state.as_str());
+            if wt.pr_number == number && wt.pr_state.as_deref() == state {
+                continue;
             }
+            self.db
+                .set_worktree_pull_request(
+                    &wt.id,
+                    number,
+                    pr.as_ref().map(|pr| pr.url.as_str()),
+                    state,
+                )
+                .await?;
+            changed += 1;
         }
 
-        println!(
-            "\n{} Total: {} active worktrees",
-            "📊".bright_cyan(),
-            worktrees.len().to_string().bright_white().bold()
-        );
-        Ok(())
-    }
-
-    /// Start real-time monitoring
-    pub async fn start_monitoring(&self, repo: Option<&str>) -> Result<()> {
-        use crate::monitor::MonitorManager;
-
-        let monitor = MonitorManager::new(self.clone(), self.config.clone());
-        monitor.start(repo).await
+        Ok(changed)
     }
 
-    /// Sync database with actual Git worktrees
-    pub async fn sync_with_git(&self, repo: Option<&str>) -> Result<()> {
-        let current_dir = std::env::current_dir()?;
-        let repo_name = if let Some(repo) = repo {
-            repo.to_string()
-        } else {
-            self.git.get_repo_name(&current_dir)?
-        };
-
-        println!(
-            "   {} Checking Git worktrees for repo: {}",
-            "🔍".bright_blue(),
-            repo_name
-        );
-
-        // Get actual Git worktrees
-        let git_worktrees = self.git.list_git_worktrees(&current_dir)?;
-        println!(
-            "   {} Found {} Git worktrees",
-            "📊".bright_green(),
-            git_worktrees.len()
-        );
-
-        // Get database worktrees
-        let db_worktrees = self.db.list_worktrees(Some(&repo_name)).await?;
-        println!(
-            "   {} Found {} database entries",
-            "💾".bright_blue(),
-            db_worktrees.len()
-        );
+    /// GitHub Actions status of each active worktree's branch, keyed by
+    /// worktree id. Branches without runs (or off GitHub) are left out, and
+    /// failed lookups fall back to whatever was cached.
+    pub async fn ci_statuses(&self, repo: Option<&str>) -> Result<HashMap<Uuid, CiStatus>> {
+        let repositories = self.db.list_repositories().await?;
+        let github = self.github()?;
+        let mut cache = CiCache::load();
+        let mut statuses = HashMap::new();
 
-        let mut synced = 0;
-        let mut deactivated = 0;
-        let mut added = 0;
+        let mut tasks = tokio::task::JoinSet::new();
+        for wt in self.db.list_worktrees(repo).await? {
+            let Some(repository) = repositories.iter().find(|r| r.id == wt.project_id) else {
+                continue;
+            };
+            let remote_url = repository.remote_url.clone();
+            let head = self
+                .git
+                .get_head_commit(Path::new(&wt.path))
+                .ok()
+                .map(|(sha, _)| sha);
+            if let Some(cached) = cache.get(&remote_url, &wt.branch_name, head.as_deref()) {
+                if let Some(status) = cached {
+                    statuses.insert(wt.id, status);
+                }
+                continue;
+            }
 
-        // Deactivate database entries that don't exist in Git
-        for db_worktree in &db_worktrees {
-            let exists_in_git = git_worktrees
-                .iter()
-                .any(|git_wt| PathBuf::from(&git_wt.path) == PathBuf::from(&db_worktree.path));
+            let github = github.clone();
+            tasks.spawn(async move {
+                let found = github
+                    .latest_workflow_run(&remote_url, &wt.branch_name)
+                    .await;
+                (wt, remote_url, found)
+            });
+        }
 
-            if !exists_in_git {
-                self.db
-                    .deactivate_worktree(&repo_name, &db_worktree.worktree_name)
-                    .await?;
-                println!(
-                    "   {} Deactivated: {}",
-                    "❌".bright_red(),
-                    db_worktree.worktree_name
-                );
-                deactivated += 1;
-            } else {
-                synced += 1;
+        while let Some(joined) = tasks.join_next().await {
+            let (wt, remote_url, found) = joined?;
+            match found {
+                Ok(status) => {
+                    cache.insert(&remote_url, &wt.branch_name, status.clone());
+                    if let Some(status) = status {
+                        statuses.insert(wt.id, status);
+                    }
+                }
+                Err(e) => {
+                    if retry::verbose() {
+                        eprintln!(
+                            "{}",
+                            tr!(
+                                "{} CI lookup for {} failed: {}",
+                                "⚠️".bright_yellow(),
+                                wt.name,
+                                format!("{:#}", e)
+                            )
+                        );
+                    }
+                }
             }
         }
 
-        // Add Git worktrees that aren't in database
-        for git_worktree in &git_worktrees {
-            let exists_in_db = db_worktrees
-                .iter()
-                .any(|db_wt| PathBuf::from(&db_wt.path) == PathBuf::from(&git_worktree.path));
+        cache.save()?;
+        Ok(statuses)
+    }
 
-            if !exists_in_db {
-                // Extract worktree info from Git data
-                let path = PathBuf::from(&git_worktree.path);
-                let worktree_name = path
-                    .file_name()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or("unknown")
-                    .to_string();
+    /// The newest note of every worktree that has one, by worktree id. Empty
+    /// when notes can't be read (e.g. a registry from before notes existed).
+    pub async fn latest_notes(&self) -> HashMap<Uuid, WorktreeNote> {
+        self.db
+            .latest_worktree_notes()
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .map(|note| (note.worktree_id, note))
+            .collect()
+    }
 
-                let worktree_type = if worktree_name.starts_with("feat-") {
-                    "feat"
-                } else if worktree_name.starts_with("pr-") {
-                    "pr"
-                } else if worktree_name.starts_with("fix-") {
-                    "fix"
-                } else if worktree_name.starts_with("aiops-") {
-                    "aiops"
-                } else if worktree_name.starts_with("devops-") {
-                    "devops"
-                } else if worktree_name.contains("main") || worktree_name.contains("trunk") {
-                    "trunk"
-                } else {
-                    "unknown"
-                };
+    /// Number of unread `imi msg` messages per worktree id. Empty when
+    /// messages can't be read (e.g. a registry from before messages existed).
+    pub async fn unread_messages(&self) -> HashMap<Uuid, i64> {
+        self.db
+            .unread_message_counts()
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .collect()
+    }
 
-                self.db
-                    .create_worktree(
-                        &repo_name,
-                        &worktree_name,
-                        &git_worktree.branch,
-                        worktree_type,
-                        &git_worktree.path,
-                        None,
-                    )
-                    .await?;
+    /// Git status of every worktree on disk, reusing the cached status of
+    /// worktrees whose HEAD, index and upstream haven't moved
+    async fn worktree_statuses(&self, worktrees: &[Worktree]) -> HashMap<Uuid, WorktreeStatus> {
+        let max_age_secs = self.config.list_settings.status_cache_secs;
+        let mut cached: HashMap<Uuid, CachedWorktreeStatus> = HashMap::new();
+        if max_age_secs > 0 && !self.no_status_cache {
+            let ids: Vec<Uuid> = worktrees.iter().map(|wt| wt.id).collect();
+            cached = self
+                .db
+                .get_cached_worktree_statuses(&ids)
+                .await
+                .unwrap_or_default()
+                .into_iter()
+                .map(|entry| (entry.worktree_id, entry))
+                .collect();
+        }
 
-                println!(
-                    "   {} Added: {} ({})",
-                    "➕".bright_green(),
-                    worktree_name,
-                    worktree_type
-                );
-                added += 1;
+        let mut statuses = HashMap::new();
+        for worktree in worktrees {
+            let path = Path::new(&worktree.path);
+            if !path.exists() {
+                continue;
+            }
+            let key = self.git.status_cache_key(path).ok();
+            let hit = key
+                .as_ref()
+                .zip(cached.get(&worktree.id))
+                .filter(|(key, entry)| cache_is_fresh(entry, key, max_age_secs))
+                .and_then(|(_, entry)| serde_json::from_value(entry.status.clone()).ok());
+            if let Some(status) = hit {
+                statuses.insert(worktree.id, status);
+                continue;
             }
-        }
 
-        println!("\n{} Sync complete:", "✅".bright_green());
-        println!("   {} {} entries synced", "🔄".bright_cyan(), synced);
-        println!(
-            "   {} {} entries deactivated",
-            "❌".bright_red(),
-            deactivated
-        );
-        println!("   {} {} entries added", "➕".bright_green(), added);
+            let Ok(status) = self.git.get_worktree_status(path) else {
+                continue;
+            };
+            if let (true, Some(key), Ok(value)) =
+                (max_age_secs > 0, key, serde_json::to_value(&status))
+            {
+                // Best-effort: a failed write only costs the next run a walk
+                let _ = self
+                    .db
+                    .put_cached_worktree_status(&CachedWorktreeStatus {
+                        worktree_id: worktree.id,
+                        head_sha: key.head_sha,
+                        index_mtime_ns: key.index_mtime_ns,
+                        upstream_sha: key.upstream_sha,
+                        status: value,
+                        computed_at: chrono::Utc::now(),
+                    })
+                    .await;
+            }
+            statuses.insert(worktree.id, status);
+        }
+        statuses
+    }
 
-        Ok(())
+    /// Show status of worktrees
+    #[allow(dead_code)] // Library API; the CLI goes through show_status_with_ci
+    pub async fn show_status(&self, repo: Option<&str>) -> Result<()> {
+        self.show_status_with_ci(repo, &HashMap::new()).await
     }
 
-    /// Find a worktree in the database by name, searching across all repos if needed
-    async fn find_worktree_in_database(
+    /// Show status of worktrees, with the CI result of each branch found in
+    /// `ci` (see [`Self::ci_statuses`])
+    pub async fn show_status_with_ci(
         &self,
-        name: &str,
         repo: Option<&str>,
-    ) -> Result<Option<crate::database::Worktree>> {
-        // If repo is specified, search within that repo
-        if let Some(repo_name) = repo {
-            // Try to find with the exact name first
-            if let Some(worktree) = self.db.get_worktree(repo_name, name).await? {
-                return Ok(Some(worktree));
-            }
+        ci: &HashMap<Uuid, CiStatus>,
+    ) -> Result<()> {
+        let worktrees = self.active_worktrees(repo).await?;
 
-            // Try different prefixed versions within the specified repo
-            let possible_names = vec![
-                format!("feat-{}", name),
-                format!("fix-{}", name),
-                format!("aiops-{}", name),
-                format!("devops-{}", name),
-                format!("pr-{}", name),
-            ];
+        if worktrees.is_empty() {
+            println!(
+                "{}",
+                tr!("{} No active worktrees found", "ℹ️".bright_blue())
+            );
+            return Ok(());
+        }
+        let notes = self.latest_notes().await;
+        let unread = self.unread_messages().await;
+        let activity = self.last_activity(&worktrees).await;
+        let git_statuses = self.worktree_statuses(&worktrees).await;
 
-            for possible_name in possible_names {
-                if let Some(worktree) = self.db.get_worktree(repo_name, &possible_name).await? {
-                    return Ok(Some(worktree));
-                }
-            }
+        println!("\n{}", tr!("Active Worktrees:").bright_cyan().bold());
+        println!("{}", "─".repeat(80).bright_black());
 
-            return Ok(None);
-        }
+        let alert_evaluator = AlertEvaluator::new(
+            &self.git,
+            &self.config.alert_settings,
+            &self.config.git_settings.default_branch,
+        );
 
-        // No repo specified - search across all repos
-        // First try exact name match
-        if let Some(worktree) = self.db.find_worktree_by_name(name).await? {
-            return Ok(Some(worktree));
-        }
+        for worktree in worktrees {
+            let status_icon = match worktree.worktree_type.as_str() {
+                "feat" => "🚀",
+                "pr" => "🔍",
+                "fix" => "🔧",
+                "aiops" => "🤖",
+                "devops" => "⚙️",
+                "trunk" => "🌳",
+                _ => "📁",
+            };
 
-        // Try different prefixed versions across all repos
-        let possible_names = vec![
-            format!("feat-{}", name),
-            format!("fix-{}", name),
-            format!("aiops-{}", name),
-            format!("devops-{}", name),
-            format!("pr-{}", name),
-        ];
+            let idle = self.stale_idle_days(activity.get(&worktree.id));
+            println!(
+                "{} {} {} ({}){}{}",
+                status_icon,
+                match idle {
+                    Some(_) => worktree.worktree_name.dimmed(),
+                    None => worktree.worktree_name.bright_green(),
+                },
+                worktree.branch_name.bright_yellow(),
+                worktree.worktree_type.bright_blue(),
+                stale_marker(idle),
+                unread_marker(unread.get(&worktree.id).copied())
+            );
+
+            // Get Git status if worktree path exists
+            let worktree_path = PathBuf::from(&worktree.path);
+            if worktree_path.exists() {
+                if let Some(git_status) = git_statuses.get(&worktree.id) {
+                    self.print_git_status(git_status);
+                }
+            } else {
+                println!(
+                    "{}",
+                    tr!(
+                        "   {} Path not found: {}",
+                        "⚠️".bright_yellow(),
+                        worktree.path
+                    )
+                );
+            }
+
+            if let Some(agent_id) = &worktree.agent_id {
+                println!(
+                    "{}",
+                    tr!("   {} Agent: {}", "🤖".bright_magenta(), agent_id)
+                );
+            }
+            print_pull_request(&worktree);
+            if let Some(status) = ci.get(&worktree.id) {
+                print_ci_status(status);
+            }
+            if let Some(note) = notes.get(&worktree.id) {
+                print_latest_note(note);
+            }
 
-        for possible_name in possible_names {
-            if let Some(worktree) = self.db.find_worktree_by_name(&possible_name).await? {
-                return Ok(Some(worktree));
+            for alert in alert_evaluator.evaluate(&worktree) {
+                alert.print();
             }
+
+            println!(
+                "{}",
+                tr!(
+                    "   {} Created: {}",
+                    "📅".bright_black(),
+                    worktree.created_at.format("%Y-%m-%d %H:%M:%S")
+                )
+            );
+            println!();
         }
 
-        Ok(None)
+        Ok(())
     }
 
-    /// Find the actual worktree name by trying different prefixed versions
-    async fn find_actual_worktree_name(&self, name: &str, repo_name: &str) -> Result<String> {
-        // If the name is already prefixed, use it as-is
-        if name.contains('-')
-            && (name.starts_with("feat-")
-                || name.starts_with("fix-")
-                || name.starts_with("aiops-")
-                || name.starts_with("devops-")
-                || name.starts_with("pr-"))
-        {
-            return Ok(name.to_string());
+    /// The `imi status` footer for directories still being deleted
+    pub fn print_pending_deletes(&self, pending: &[PendingDelete]) {
+        if pending.is_empty() {
+            return;
         }
+        println!(
+            "{}",
+            tr!(
+                "{} {} removed worktree(s) still being deleted:",
+                "🧹".bright_yellow(),
+                pending.len()
+            )
+        );
+        for entry in pending {
+            let since = entry
+                .staged_at
+                .map(|t| format!(" (since {})", t.format("%Y-%m-%d %H:%M:%S")))
+                .unwrap_or_default();
+            println!("   {}{}", entry.name.bright_white(), since.bright_black());
+        }
+    }
 
-        // Try different prefixed versions
-        let possible_names = vec![
-            name.to_string(),           // Original name
-            format!("feat-{}", name),   // Feature worktree
-            format!("fix-{}", name),    // Fix worktree
-            format!("aiops-{}", name),  // AI ops worktree
-            format!("devops-{}", name), // DevOps worktree
-            format!("pr-{}", name),     // PR worktree
-        ];
-
-        for possible_name in possible_names {
-            if let Some(_worktree) = self.db.get_worktree(repo_name, &possible_name).await? {
-                return Ok(possible_name);
+    fn print_git_status(&self, status: &WorktreeStatus) {
+        if status.clean {
+            println!("{}", tr!("   {} Working tree clean", "✅".bright_green()));
+        } else {
+            if !status.modified_files.is_empty() {
+                println!(
+                    "{}",
+                    tr!(
+                        "   {} Modified: {}",
+                        "📝".bright_yellow(),
+                        status.modified_files.len()
+                    )
+                );
+            }
+            if !status.new_files.is_empty() {
+                println!(
+                    "{}",
+                    tr!(
+                        "   {} New files: {}",
+                        "➕".bright_green(),
+                        status.new_files.len()
+                    )
+                );
+            }
+            if !status.deleted_files.is_empty() {
+                println!(
+                    "{}",
+                    tr!(
+                        "   {} Deleted: {}",
+                        "➖".bright_red(),
+                        status.deleted_files.len()
+                    )
+                );
             }
         }
 
-        // If none found, return the original name (will likely fail but with better error)
-        Ok(name.to_string())
+        if status.commits_ahead > 0 {
+            println!(
+                "{}",
+                tr!(
+                    "   {} {} commits ahead",
+                    "⬆️".bright_green(),
+                    status.commits_ahead
+                )
+            );
+        }
+        if status.commits_behind > 0 {
+            println!(
+                "{}",
+                tr!(
+                    "   {} {} commits behind",
+                    "⬇️".bright_red(),
+                    status.commits_behind
+                )
+            );
+        }
     }
 
-    /// Parse repository argument to extract org/repo pattern
-    /// Supports: "name", "org/repo", or defaults org to "delorenj" if omitted
-    fn parse_repo_argument(&self, repo_arg: &str) -> (Option<String>, String) {
-        if repo_arg.contains('/') {
-            // Format: org/repo
-            let parts: Vec<&str> = repo_arg.splitn(2, '/').collect();
-            (Some(parts[0].to_string()), parts[1].to_string())
+    fn print_git_status_indented(&self, status: &WorktreeStatus) {
+        if status.clean {
+            println!("{}", tr!("     {} Working tree clean", "✅".bright_green()));
         } else {
-            // Just repo name - default org to delorenj
-            (Some("delorenj".to_string()), repo_arg.to_string())
+            if !status.modified_files.is_empty() {
+                println!(
+                    "{}",
+                    tr!(
+                        "     {} Modified: {}",
+                        "📝".bright_yellow(),
+                        status.modified_files.len()
+                    )
+                );
+            }
+            if !status.new_files.is_empty() {
+                println!(
+                    "{}",
+                    tr!(
+                        "     {} New files: {}",
+                        "➕".bright_green(),
+                        status.new_files.len()
+                    )
+                );
+            }
+            if !status.deleted_files.is_empty() {
+                println!(
+                    "{}",
+                    tr!(
+                        "     {} Deleted: {}",
+                        "➖".bright_red(),
+                        status.deleted_files.len()
+                    )
+                );
+            }
         }
-    }
 
-    /// Resolve repository name from current directory or provided name
-    /// Handles GitHub org/repo format: searches database by remote_url pattern
-    /// Also supports running from sandbox directory (parent of trunk-main containing .iMi/)
-    async fn resolve_repo_name(&self, repo: Option<&str>) -> Result<String> {
-        if let Some(repo_arg) = repo {
-            // Parse the argument to check if it's org/repo format
-            let (org, repo_name) = self.parse_repo_argument(repo_arg);
+        if status.commits_ahead > 0 {
+            println!(
+                "{}",
+                tr!(
+                    "     {} {} commits ahead",
+                    "⬆️".bright_green(),
+                    status.commits_ahead
+                )
+            );
+        }
+        if status.commits_behind > 0 {
+            println!(
+                "{}",
+                tr!(
+                    "     {} {} commits behind",
+                    "⬇️".bright_red(),
+                    status.commits_behind
+                )
+            );
+        }
+    }
+
+    /// Smart context-aware list command
+    /// Implements the decision tree based on context and flags
+    pub async fn list_smart(
+        &self,
+        repo: Option<&str>,
+        worktrees_flag: bool,
+        projects_flag: bool,
+    ) -> Result<()> {
+        let current_dir = env::current_dir()?;
+        let git_context = self.git.detect_context(Some(&current_dir));
+
+        // Extract repository information from context
+        let detected_repo_path = git_context.repo_path();
+        let detected_repo_name = if let Some(repo_path) = detected_repo_path {
+            self.git.get_repo_name(repo_path).ok()
+        } else {
+            None
+        };
+
+        // Handle explicit --projects flag
+        if projects_flag {
+            return self.list_all_repositories().await;
+        }
+
+        // Handle explicit --worktrees flag
+        if worktrees_flag {
+            // If repo is specified, list that repo's worktrees
+            if let Some(repo_name) = repo {
+                return self.list_worktrees_detailed(Some(repo_name)).await;
+            }
+            // If in a repo, list that repo's worktrees
+            if let Some(repo_name) = detected_repo_name.as_ref() {
+                return self.list_worktrees_detailed(Some(repo_name)).await;
+            }
+            // Not in a repo, list all worktrees
+            return self.list_worktrees_detailed(None).await;
+        }
+
+        // Handle explicit --repo flag
+        if let Some(repo_name) = repo {
+            // Check if repo is registered
+            if let Some(_registered) = self.db.get_repository(repo_name).await? {
+                // Registered: list its worktrees
+                return self.list_worktrees_detailed(Some(repo_name)).await;
+            } else {
+                // Not registered: show helpful error
+                println!(
+                    "{}",
+                    tr!(
+                        "{} Repository '{}' is not registered",
+                        "⚠️".bright_yellow(),
+                        repo_name.bright_red()
+                    )
+                );
+                println!(
+                    "{}",
+                    tr!(
+                        "\n{} To register this repository, run {} from its directory",
+                        "💡".bright_yellow(),
+                        "imi trunk".bright_green()
+                    )
+                );
+                return Ok(());
+            }
+        }
+
+        // No explicit flags - use context detection
+        if git_context.is_in_repository() {
+            // We're in a git repository
+            if let Some(repo_name) = detected_repo_name {
+                // Check if this repo is registered
+                if let Some(_registered) = self.db.get_repository(&repo_name).await? {
+                    // Registered: list its worktrees
+                    return self.list_worktrees_detailed(Some(&repo_name)).await;
+                } else {
+                    // Unregistered: show helpful message
+                    println!(
+                        "\n{} {} {}",
+                        "📦".bright_cyan(),
+                        tr!("Repository:").bright_white(),
+                        repo_name.bright_yellow()
+                    );
+                    println!(
+                        "{} {}",
+                        "⚠️".bright_yellow(),
+                        tr!("This repository is not registered with iMi").bright_yellow()
+                    );
+                    println!(
+                        "{}",
+                        tr!(
+                            "\n{} To start using iMi with this repository:",
+                            "💡".bright_yellow()
+                        )
+                    );
+                    println!(
+                        "{}",
+                        tr!(
+                            "   1. Run {} to create the trunk worktree",
+                            "imi trunk".bright_green()
+                        )
+                    );
+                    println!(
+                        "{}",
+                        tr!(
+                            "   2. Then use {} to create feature worktrees",
+                            "imi feat <name>".bright_green()
+                        )
+                    );
+                    println!(
+                        "{}",
+                        tr!(
+                            "\n{} Once registered, {} will show worktrees for this repo",
+                            "ℹ️".bright_blue(),
+                            "imi list".bright_cyan()
+                        )
+                    );
+                    return Ok(());
+                }
+            } else {
+                // In a repo but couldn't detect name - unusual case
+                println!(
+                    "{}",
+                    tr!(
+                        "{} You are in a Git repository, but the repository name could not be determined",
+                        "⚠️".bright_yellow()
+                    )
+                );
+                println!(
+                    "{}",
+                    tr!(
+                        "\n{} Run {} to register this repository",
+                        "💡".bright_yellow(),
+                        "imi trunk".bright_green()
+                    )
+                );
+                return Ok(());
+            }
+        } else {
+            // Not in a git repository - list all repositories
+            return self.list_all_repositories().await;
+        }
+    }
+
+    /// List all registered repositories with worktree counts
+    pub async fn list_all_repositories(&self) -> Result<()> {
+        self.print_repositories(false, None).await
+    }
+
+    /// List all registered repositories with health indicators (costs a git scan per repo)
+    pub async fn list_repositories_with_health(&self) -> Result<()> {
+        self.print_repositories(true, None).await
+    }
+
+    /// Compute health for every registered repository concurrently
+    pub async fn collect_repository_health(&self) -> Result<Vec<(Repository, RepoHealth)>> {
+        let repositories = self.listed_repositories(None).await?;
+        let fetch_states = self.db.list_fetch_states().await.unwrap_or_default();
+
+        let mut tasks = tokio::task::JoinSet::new();
+        for (index, repo) in repositories.iter().enumerate() {
+            let worktree_paths: Vec<PathBuf> = self
+                .db
+                .list_worktrees(Some(&repo.name))
+                .await?
+                .iter()
+                .map(|wt| PathBuf::from(&wt.path))
+                .collect();
+            let daemon_fetch = fetch_states
+                .iter()
+                .find(|f| f.project_id == repo.id)
+                .and_then(|f| f.last_success_at);
+            let git = self.git.clone();
+            let trunk_path = PathBuf::from(&repo.path);
+            let default_branch = repo.default_branch.clone();
+
+            tasks.spawn_blocking(move || {
+                let health =
+                    RepoHealth::compute(&git, &trunk_path, &default_branch, &worktree_paths);
+                (
+                    index,
+                    RepoHealth {
+                        last_fetch: health.last_fetch.max(daemon_fetch),
+                        ..health
+                    },
+                )
+            });
+        }
+
+        let mut health_by_index = std::collections::HashMap::new();
+        while let Some(joined) = tasks.join_next().await {
+            let (index, health) = joined?;
+            health_by_index.insert(index, health);
+        }
+
+        Ok(repositories
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, repo)| health_by_index.remove(&i).map(|h| (repo, h)))
+            .collect())
+    }
+
+    /// List the worktrees of the repositories tagged `tag`, grouped by
+    /// repository, or with `projects_flag` just the repositories
+    pub async fn list_tagged(&self, tag: &str, projects_flag: bool) -> Result<()> {
+        if projects_flag {
+            return self.print_repositories(false, Some(tag)).await;
+        }
+        for repository in self.listed_repositories(Some(tag)).await? {
+            println!(
+                "\n{} {}",
+                "📦".bright_cyan(),
+                repository.name.bright_green().bold()
+            );
+            self.list_worktrees_detailed(Some(&repository.name)).await?;
+        }
+        Ok(())
+    }
+
+    async fn print_repositories(&self, with_health: bool, tag: Option<&str>) -> Result<()> {
+        let repositories = self.listed_repositories(tag).await?;
+        let tags = self.db.list_repository_tags().await.unwrap_or_default();
+        let mut health_by_repo = std::collections::HashMap::new();
+        if with_health {
+            for (repo, health) in self.collect_repository_health().await? {
+                health_by_repo.insert(repo.id, health);
+            }
+        }
+
+        if let (true, Some(tag)) = (repositories.is_empty(), tag) {
+            println!(
+                "{}",
+                tr!(
+                    "{} No repositories tagged {}",
+                    "ℹ️".bright_blue(),
+                    tag.bright_yellow()
+                )
+            );
+            return Ok(());
+        }
+        if repositories.is_empty() {
+            println!(
+                "\n{}",
+                tr!("No Registered Repositories").bright_cyan().bold()
+            );
+            println!("{}", "─".repeat(80).bright_black());
+            println!(
+                "{}",
+                tr!("\n{} No repositories registered yet", "ℹ️".bright_blue())
+            );
+            println!(
+                "{}",
+                tr!(
+                    "\n{} Run {} from a git repository to register it",
+                    "💡".bright_yellow(),
+                    "imi trunk".bright_green()
+                )
+            );
+            return Ok(());
+        }
+
+        println!("\n{}", tr!("Registered Repositories").bright_cyan().bold());
+        println!("{}", "═".repeat(80).bright_black());
+
+        for (i, repo) in repositories.iter().enumerate() {
+            // Get worktree count for this repo
+            let worktrees = self.db.list_worktrees(Some(&repo.name)).await?;
+            let worktree_count = worktrees.len();
+
+            println!(
+                "\n{} {} {}",
+                format!("{}.", i + 1).bright_black(),
+                "📦".bright_cyan(),
+                repo.name.bright_green().bold()
+            );
+            println!(
+                "{}",
+                tr!(
+                    "   {} Path: {}",
+                    "📂".bright_cyan(),
+                    repo.path.bright_white()
+                )
+            );
+            if let Some(host) = repo.host.as_ref().filter(|_| !repo.is_local()) {
+                println!(
+                    "{}",
+                    tr!("   {} Host: {}", "🖥️".bright_cyan(), host.bright_magenta())
+                );
+            }
+            println!(
+                "{}",
+                tr!(
+                    "   {} Branch: {}",
+                    "🌿".bright_cyan(),
+                    repo.default_branch.bright_yellow()
+                )
+            );
+
+            if !repo.remote_url.is_empty() {
+                println!(
+                    "{}",
+                    tr!(
+                        "   {} Remote: {}",
+                        "🔗".bright_cyan(),
+                        repo.remote_url.bright_white()
+                    )
+                );
+            }
+
+            // Show worktree count with appropriate icon
+            let wt_icon = if worktree_count == 0 { "📭" } else { "📬" };
+            println!(
+                "{}",
+                tr!(
+                    "   {} Worktrees: {}",
+                    wt_icon,
+                    if worktree_count == 0 {
+                        "None".bright_black().to_string()
+                    } else {
+                        worktree_count.to_string().bright_white().to_string()
+                    }
+                )
+            );
+
+            println!(
+                "{}",
+                tr!(
+                    "   {} Created: {}",
+                    "📅".bright_black(),
+                    repo.created_at
+                        .format("%Y-%m-%d %H:%M:%S")
+                        .to_string()
+                        .bright_green()
+                )
+            );
+
+            let repo_tags: Vec<&str> = tags
+                .iter()
+                .filter(|(project_id, _)| *project_id == repo.id)
+                .map(|(_, tag)| tag.as_str())
+                .collect();
+            if !repo_tags.is_empty() {
+                println!(
+                    "{}",
+                    tr!(
+                        "   {} Tags: {}",
+                        "🏷️".bright_cyan(),
+                        repo_tags.join(", ").bright_magenta()
+                    )
+                );
+            }
+
+            if let Some(health) = health_by_repo.get(&repo.id) {
+                health.print();
+            }
+
+            if i < repositories.len() - 1 {
+                println!("{}", "─".repeat(80).bright_black());
+            }
+        }
+
+        println!(
+            "{}",
+            tr!(
+                "\n{} Total: {} repositories",
+                "📊".bright_cyan(),
+                repositories.len().to_string().bright_white().bold()
+            )
+        );
+        println!();
+
+        Ok(())
+    }
+
+    /// List all worktrees with detailed metadata
+    pub async fn list_worktrees_detailed(&self, repo: Option<&str>) -> Result<()> {
+        let worktrees = self.active_worktrees(repo).await?;
+
+        if worktrees.is_empty() {
+            println!(
+                "{}",
+                tr!("{} No active worktrees found", "ℹ️".bright_blue())
+            );
+            return Ok(());
+        }
+        let notes = self.latest_notes().await;
+        let activity = self.last_activity(&worktrees).await;
+        let git_statuses = self.worktree_statuses(&worktrees).await;
+
+        println!(
+            "\n{}",
+            tr!("Detailed Worktree Information:").bright_cyan().bold()
+        );
+        println!("{}", "═".repeat(100).bright_black());
+
+        for (i, worktree) in worktrees.iter().enumerate() {
+            let status_icon = match worktree.worktree_type.as_str() {
+                "feat" => "🚀",
+                "pr" => "🔍",
+                "fix" => "🔧",
+                "aiops" => "🤖",
+                "devops" => "⚙️",
+                "trunk" => "🌳",
+                _ => "📁",
+            };
+
+            let idle = self.stale_idle_days(activity.get(&worktree.id));
+            println!(
+                "\n{} {} {} {} ({}){}",
+                format!("{}.", i + 1).bright_black(),
+                status_icon,
+                match idle {
+                    Some(_) => worktree.worktree_name.dimmed(),
+                    None => worktree.worktree_name.bright_green().bold(),
+                },
+                worktree.branch_name.bright_yellow(),
+                worktree.worktree_type.bright_blue(),
+                stale_marker(idle)
+            );
+
+            // Repository and path info
+            println!(
+                "{}",
+                tr!(
+                    "   {} Repo: {}",
+                    "📦".bright_cyan(),
+                    worktree.repo_name.bright_white()
+                )
+            );
+            println!(
+                "{}",
+                tr!(
+                    "   {} Path: {}",
+                    "📂".bright_cyan(),
+                    worktree.path.bright_white()
+                )
+            );
+            if let Some(host) = worktree.host.as_ref().filter(|_| !worktree.is_local()) {
+                println!(
+                    "{}",
+                    tr!("   {} Host: {}", "🖥️".bright_cyan(), host.bright_magenta())
+                );
+            }
+
+            // Timestamps
+            println!(
+                "{}",
+                tr!(
+                    "   {} Created: {} | Updated: {}",
+                    "📅".bright_cyan(),
+                    worktree
+                        .created_at
+                        .format("%Y-%m-%d %H:%M:%S")
+                        .to_string()
+                        .bright_green(),
+                    worktree
+                        .updated_at
+                        .format("%Y-%m-%d %H:%M:%S")
+                        .to_string()
+                        .bright_yellow()
+                )
+            );
+
+            // Agent assignment
+            if let Some(agent_id) = &worktree.agent_id {
+                println!(
+                    "{}",
+                    tr!(
+                        "   {} Agent: {}",
+                        "🤖".bright_magenta(),
+                        agent_id.bright_white()
+                    )
+                );
+            } else {
+                println!(
+                    "{}",
+                    tr!(
+                        "   {} Agent: {}",
+                        "🤖".bright_black(),
+                        tr!("Unassigned").bright_black()
+                    )
+                );
+            }
+            print_pull_request(worktree);
+            if let Some(note) = notes.get(&worktree.id) {
+                print_latest_note(note);
+            }
+
+            // Database ID for debugging
+            println!(
+                "{}",
+                tr!(
+                    "   {} ID: {}",
+                    "🔑".bright_black(),
+                    worktree.id.to_string().bright_black()
+                )
+            );
+
+            // Git status if worktree exists
+            let worktree_path = PathBuf::from(&worktree.path);
+            if !worktree.is_local() {
+                // Its path means nothing on this machine
+            } else if worktree_path.exists() {
+                if let Some(git_status) = git_statuses.get(&worktree.id) {
+                    println!("{}", tr!("   {} Git Status:", "📊".bright_cyan()));
+                    self.print_git_status_indented(git_status);
+                }
+            } else {
+                println!(
+                    "{}",
+                    tr!(
+                        "   {} Status: {}",
+                        "⚠️".bright_yellow(),
+                        tr!("Path not found").bright_red()
+                    )
+                );
+            }
+
+            if i < worktrees.len() - 1 {
+                println!("{}", "─".repeat(100).bright_black());
+            }
+        }
+
+        println!(
+            "{}",
+            tr!(
+                "\n{} Total: {} active worktrees",
+                "📊".bright_cyan(),
+                worktrees.len().to_string().bright_white().bold()
+            )
+        );
+        Ok(())
+    }
+
+    /// Everything known about one worktree, for `imi show`
+    pub async fn worktree_detail(&self, name: &str, repo: Option<&str>) -> Result<WorktreeDetail> {
+        let found = self
+            .get_worktree_by_name(name, repo)
+            .await?
+            .with_context(|| tr!("Worktree '{}' not found", name))?;
+        let repository = self
+            .db
+            .get_repository_by_id(&found.project_id)
+            .await?
+            .context(tr!("The worktree's repository is no longer registered"))?;
+        // The listing fills in repository, type and display names
+        let worktree = self
+            .db
+            .list_worktrees(Some(&repository.name))
+            .await?
+            .into_iter()
+            .find(|wt| wt.id == found.id)
+            .unwrap_or(found);
+
+        let base_ref = self
+            .db
+            .list_worktree_bases()
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .find(|(id, _, _)| *id == worktree.id)
+            .map(|(_, base_ref, _)| base_ref)
+            .unwrap_or_else(|| repository.default_branch.clone());
+        let notes = self
+            .db
+            .list_worktree_notes(&worktree.id)
+            .await
+            .unwrap_or_default();
+        let hooks = self
+            .config
+            .event_hooks
+            .hooks
+            .iter()
+            .filter(|hook| {
+                hook.repo
+                    .as_ref()
+                    .is_none_or(|repo| repo.eq_ignore_ascii_case(&repository.name))
+            })
+            .cloned()
+            .collect();
+
+        let path = PathBuf::from(&worktree.path);
+        let symlinks = self.symlink_health(&repository.name, &path);
+        let git = self.git.clone();
+        let ttl = self.config.size_budget.cache_ttl_secs;
+        let (status, base, upstream, recent_commits, disk_bytes) =
+            tokio::task::spawn_blocking(move || {
+                if !path.exists() {
+                    return (None, None, None, Vec::new(), None);
+                }
+                let mut cache = DiskUsageCache::load(ttl);
+                let bytes = cache.size_of(&path);
+                let _ = cache.save();
+                (
+                    git.get_worktree_status(&path).ok(),
+                    git.get_divergence(&path, &base_ref).ok(),
+                    git.get_upstream_divergence(&path).ok().flatten(),
+                    git.recent_commits(&path, RECENT_COMMITS)
+                        .unwrap_or_default(),
+                    Some(bytes),
+                )
+            })
+            .await?;
+
+        Ok(WorktreeDetail {
+            worktree,
+            status,
+            base,
+            upstream,
+            disk_bytes,
+            recent_commits,
+            notes,
+            hooks,
+            symlinks,
+        })
+    }
+
+    /// State of each configured `symlink_files` entry in a worktree; files
+    /// with nothing to link to are left out
+    fn symlink_health(&self, repo_name: &str, worktree_path: &Path) -> Vec<SymlinkHealth> {
+        let local_sync = self.config.get_sync_path(repo_name, false);
+        self.config
+            .symlink_files
+            .iter()
+            .filter_map(|file| {
+                let target = worktree_path.join(file);
+                let state = match std::fs::symlink_metadata(&target) {
+                    Ok(meta) if meta.file_type().is_symlink() => match target.exists() {
+                        true => SymlinkState::Linked,
+                        false => SymlinkState::Broken,
+                    },
+                    Ok(_) => SymlinkState::NotALink,
+                    Err(_) if local_sync.join(file).exists() => SymlinkState::Missing,
+                    Err(_) => return None,
+                };
+                Some(SymlinkHealth {
+                    file: file.clone(),
+                    state,
+                })
+            })
+            .collect()
+    }
+
+    /// Human-readable `imi show`
+    pub fn print_worktree_detail(&self, detail: &WorktreeDetail) {
+        let worktree = &detail.worktree;
+        println!(
+            "\n{} {} ({})",
+            worktree.worktree_name.bright_green().bold(),
+            worktree.branch_name.bright_yellow(),
+            worktree.worktree_type.bright_blue()
+        );
+        println!("{}", "─".repeat(80).bright_black());
+        println!(
+            "{}",
+            tr!(
+                "   {} Repo: {}",
+                "📦".bright_cyan(),
+                worktree.repo_name.bright_white()
+            )
+        );
+        println!(
+            "{}",
+            tr!(
+                "   {} Path: {}",
+                "📂".bright_cyan(),
+                worktree.path.bright_white()
+            )
+        );
+        if let Some(host) = &worktree.host {
+            println!("{}", tr!("   {} Host: {}", "🖥️".bright_cyan(), host));
+        }
+        println!(
+            "{}",
+            tr!(
+                "   {} Created: {} | Updated: {}",
+                "📅".bright_cyan(),
+                worktree.created_at.format("%Y-%m-%d %H:%M:%S"),
+                worktree.updated_at.format("%Y-%m-%d %H:%M:%S")
+            )
+        );
+        if let Some(agent_id) = &worktree.agent_id {
+            println!(
+                "{}",
+                tr!("   {} Agent: {}", "🤖".bright_magenta(), agent_id)
+            );
+        }
+        print_pull_request(worktree);
+        if let Some(bytes) = detail.disk_bytes {
+            println!(
+                "{}",
+                tr!(
+                    "   {} Disk: {}",
+                    "💾".bright_cyan(),
+                    self.format_size(bytes)
+                )
+            );
+        }
+
+        println!("\n{}", tr!("Git").bright_cyan().bold());
+        match &detail.status {
+            Some(status) => self.print_git_status(status),
+            None => println!(
+                "{}",
+                tr!(
+                    "   {} Path not found: {}",
+                    "⚠️".bright_yellow(),
+                    worktree.path
+                )
+            ),
+        }
+        if let Some(base) = &detail.base {
+            println!(
+                "{}",
+                tr!(
+                    "   {} Base {}: {} ahead, {} behind",
+                    "🌿".bright_green(),
+                    base.reference.bright_white(),
+                    base.ahead,
+                    base.behind
+                )
+            );
+        }
+        match &detail.upstream {
+            Some(upstream) => println!(
+                "{}",
+                tr!(
+                    "   {} Upstream {}: {} ahead, {} behind",
+                    "🔗".bright_cyan(),
+                    upstream.reference.bright_white(),
+                    upstream.ahead,
+                    upstream.behind
+                )
+            ),
+            None if detail.status.is_some() => {
+                println!("{}", tr!("   {} No upstream branch", "🔗".bright_black()))
+            }
+            None => {}
+        }
+
+        if !detail.recent_commits.is_empty() {
+            println!("\n{}", tr!("Recent commits").bright_cyan().bold());
+            for commit in &detail.recent_commits {
+                println!(
+                    "   {} {} {}",
+                    commit.id[..7.min(commit.id.len())].bright_yellow(),
+                    commit.summary,
+                    format!("({}, {})", commit.author, commit.time.format("%Y-%m-%d"))
+                        .bright_black()
+                );
+            }
+        }
+
+        if !detail.notes.is_empty() {
+            println!("\n{}", tr!("Notes").bright_cyan().bold());
+            print_notes(&detail.notes);
+        }
+
+        if let Some(metadata) = worktree.metadata.as_object().filter(|m| !m.is_empty()) {
+            println!("\n{}", tr!("Metadata").bright_cyan().bold());
+            for (key, value) in metadata {
+                println!("   {}: {}", key.bright_yellow(), value);
+            }
+        }
+
+        if !detail.hooks.is_empty() {
+            println!("\n{}", tr!("Hooks").bright_cyan().bold());
+            for hook in &detail.hooks {
+                println!("   {} {}", hook.event.bright_yellow(), hook.command);
+            }
+        }
+
+        if !detail.symlinks.is_empty() {
+            println!("\n{}", tr!("Symlinks").bright_cyan().bold());
+            for link in &detail.symlinks {
+                let (icon, state) = match link.state {
+                    SymlinkState::Linked => ("✅", "linked".bright_green()),
+                    SymlinkState::Missing => ("⚠️", "missing".bright_yellow()),
+                    SymlinkState::Broken => ("❌", "broken".bright_red()),
+                    SymlinkState::NotALink => ("⚠️", "not a symlink".bright_yellow()),
+                };
+                println!("   {} {} {}", icon, link.file, state);
+            }
+        }
+        println!();
+    }
+
+    /// Run `git bisect run` in a throwaway detached worktree so no checkout
+    /// is disturbed. `bad` and `good` resolve in the checkout the command runs
+    /// from when that belongs to the repository, else in trunk. The worktree
+    /// is removed afterwards unless `keep` is set.
+    pub async fn bisect(
+        &self,
+        repo: Option<&str>,
+        bad: &str,
+        good: &[String],
+        command: &[String],
+        keep: bool,
+    ) -> Result<BisectOutcome> {
+        let repo_name = self.resolve_repo_name(repo).await?;
+        let trunk_path = self.get_trunk_worktree(Some(&repo_name)).await?;
+        let rev_dir = self.rev_dir(&repo_name, &trunk_path);
+        let resolve = |rev: &str| self.resolve_commit(&rev_dir, rev);
+        let bad_commit = resolve(bad)?;
+        let good_commits = good
+            .iter()
+            .map(|rev| resolve(rev))
+            .collect::<Result<Vec<_>>>()?;
+
+        let bisect_dir = self
+            .detect_imi_path(&trunk_path)?
+            .join(".iMi")
+            .join("bisect");
+        async_fs::create_dir_all(&bisect_dir).await?;
+        let path = bisect_dir.join(chrono::Utc::now().format("%Y%m%dT%H%M%S").to_string());
+        let path_str = path
+            .to_str()
+            .context(tr!("Bisect path is not valid UTF-8"))?;
+        self.git.execute_git_command(
+            &trunk_path,
+            &["worktree", "add", "--detach", path_str, &bad_commit],
+        )?;
+        self.report(tr!(
+            "{} Bisecting {} in {}",
+            "🔎".bright_cyan(),
+            repo_name.bright_green(),
+            path.display()
+        ));
+
+        let culprit = self
+            .run_bisect(&path, &bad_commit, &good_commits, command)
+            .await;
+
+        let kept_path = if keep {
+            Some(path)
+        } else {
+            if let Err(e) = self
+                .git
+                .execute_git_command(&trunk_path, &["worktree", "remove", "--force", path_str])
+            {
+                eprintln!(
+                    "{}",
+                    tr!(
+                        "{} Failed to remove the bisect worktree {}: {}",
+                        "⚠️".bright_yellow(),
+                        path.display(),
+                        e
+                    )
+                );
+            }
+            None
+        };
+
+        Ok(BisectOutcome {
+            repo_name,
+            culprit: culprit?,
+            kept_path,
+        })
+    }
+
+    /// Where revisions given on the command line resolve: the checkout the
+    /// command runs from when it belongs to `repo_name`, else trunk
+    fn rev_dir(&self, repo_name: &str, trunk_path: &Path) -> PathBuf {
+        env::current_dir()
+            .ok()
+            .filter(|dir| {
+                self.git
+                    .find_repository(Some(dir))
+                    .and_then(|found| self.git.get_repository_name(&found))
+                    .is_ok_and(|name| name == repo_name)
+            })
+            .unwrap_or_else(|| trunk_path.to_path_buf())
+    }
+
+    fn resolve_commit(&self, dir: &Path, rev: &str) -> Result<String> {
+        self.git
+            .execute_git_command(
+                dir,
+                &[
+                    "rev-parse",
+                    "--verify",
+                    "--quiet",
+                    &format!("{}^{{commit}}", rev),
+                ],
+            )
+            .map(|sha| sha.trim().to_string())
+            .with_context(|| tr!("Cannot resolve '{}' to a commit", rev))
+    }
+
+    /// `imi temp`: run `command` in a throwaway detached worktree of `base`
+    /// (trunk's default branch when not given) and remove the worktree
+    /// afterwards, whether the command passed, failed or was interrupted.
+    /// The worktree is never registered, so it doesn't show up in the layout.
+    pub async fn run_in_temp_worktree(
+        &self,
+        repo: Option<&str>,
+        base: Option<&str>,
+        command: &[String],
+    ) -> Result<TempRun> {
+        let (program, args) = command
+            .split_first()
+            .context(tr!("No command given to run"))?;
+        let repo_name = self.resolve_repo_name(repo).await?;
+        let trunk_path = self.get_trunk_worktree(Some(&repo_name)).await?;
+        let commit = match base {
+            Some(base) => self.resolve_commit(&self.rev_dir(&repo_name, &trunk_path), base)?,
+            None => {
+                let repository = self.db.get_repository(&repo_name).await?;
+                let default_branch = repository
+                    .map(|r| r.default_branch)
+                    .unwrap_or_else(|| "main".to_string());
+                self.resolve_commit(&trunk_path, &default_branch)?
+            }
+        };
+
+        let temp_dir = self.detect_imi_path(&trunk_path)?.join(".iMi").join("temp");
+        async_fs::create_dir_all(&temp_dir).await?;
+        let name = format!(
+            "temp-{}-{}",
+            chrono::Utc::now().format("%Y%m%dT%H%M%S"),
+            &Uuid::new_v4().simple().to_string()[..8]
+        );
+        let path = temp_dir.join(&name);
+        let path_str = path.to_str().context(tr!("Temp path is not valid UTF-8"))?;
+        self.git.execute_git_command(
+            &trunk_path,
+            &["worktree", "add", "--detach", path_str, &commit],
+        )?;
+        eprintln!(
+            "{}",
+            tr!(
+                "{} Running in temporary worktree {} ({})",
+                "🧪".bright_cyan(),
+                path.display(),
+                &commit[..commit.len().min(8)]
+            )
+        );
+
+        let status = self.run_in_temp(&repo_name, &path, program, args).await;
+
+        let removed = match self
+            .git
+            .execute_git_command(&trunk_path, &["worktree", "remove", "--force", path_str])
+        {
+            Ok(_) => true,
+            Err(e) => {
+                eprintln!(
+                    "{}",
+                    tr!(
+                        "{} Failed to remove the temporary worktree {}: {}",
+                        "⚠️".bright_yellow(),
+                        path.display(),
+                        e
+                    )
+                );
+                false
+            }
+        };
+
+        Ok(TempRun {
+            repo_name,
+            path,
+            commit,
+            exit_code: status.map(|status| {
+                use std::os::unix::process::ExitStatusExt;
+                // A signal counts as 128 + its number, as shells report it
+                status
+                    .code()
+                    .or_else(|| status.signal().map(|signal| 128 + signal))
+                    .unwrap_or(1)
+            })?,
+            removed,
+        })
+    }
+
+    /// Run the command with the terminal's stdio. Ctrl-C reaches the command
+    /// directly; iMi waits for it to exit so the worktree still gets removed.
+    async fn run_in_temp(
+        &self,
+        repo_name: &str,
+        path: &Path,
+        program: &str,
+        args: &[String],
+    ) -> Result<std::process::ExitStatus> {
+        let mut child = tokio::process::Command::new(program)
+            .args(args)
+            .current_dir(path)
+            .env("IMI_REPO", repo_name)
+            .env("IMI_WORKTREE_PATH", path)
+            .env("IMI_TEMP_WORKTREE", "1")
+            .spawn()
+            .with_context(|| tr!("Failed to start {}", program))?;
+        loop {
+            tokio::select! {
+                status = child.wait() => return Ok(status?),
+                _ = tokio::signal::ctrl_c() => {}
+            }
+        }
+    }
+
+    /// Drive `git bisect` in `path` and return the first bad commit. The
+    /// test command's output goes to stderr so stdout stays machine-readable.
+    async fn run_bisect(
+        &self,
+        path: &Path,
+        bad: &str,
+        good: &[String],
+        command: &[String],
+    ) -> Result<CommitSummary> {
+        let mut start = vec!["bisect", "start", bad];
+        start.extend(good.iter().map(String::as_str));
+        self.git.execute_git_command(path, &start)?;
+
+        // Ctrl-C reaches `git bisect run` too, so it stops on its own
+        let git = self.git.clone();
+        let dir = path.to_path_buf();
+        let mut args = vec!["bisect".to_string(), "run".to_string()];
+        args.extend(command.iter().cloned());
+        let run = tokio::task::spawn_blocking(move || {
+            let args: Vec<&str> = args.iter().map(String::as_str).collect();
+            git.run_git_attached(&dir, &args)
+        });
+        let code = tokio::select! {
+            code = run => code?.context(tr!("Failed to run git bisect"))?,
+            _ = tokio::signal::ctrl_c() => {
+                return Err(anyhow::anyhow!(tr!("Bisect interrupted")));
+            }
+        };
+        if code != 0 {
+            return Err(anyhow::anyhow!(tr!(
+                "git bisect run could not find the first bad commit (exit {})",
+                code
+            )));
+        }
+
+        self.git.commit_summary(path, "refs/bisect/bad")
+    }
+
+    /// Start real-time monitoring with `settings` (config plus `imi monitor` flags)
+    pub async fn start_monitoring(
+        &self,
+        repo: Option<&str>,
+        settings: MonitoringSettings,
+    ) -> Result<()> {
+        use crate::monitor::MonitorManager;
+
+        let mut config = self.config.clone();
+        config.monitoring_settings = settings;
+        let monitor = MonitorManager::new(self.clone(), config);
+        monitor.start(repo).await
+    }
+
+    /// Keep redrawing the status table until interrupted (`imi status --watch`)
+    pub async fn watch_status(
+        &self,
+        repo: Option<&str>,
+        ci: bool,
+        interval: std::time::Duration,
+    ) -> Result<()> {
+        use crate::monitor::MonitorManager;
+
+        let monitor = MonitorManager::new(self.clone(), self.config.clone());
+        monitor.watch_status(repo, ci, interval).await
+    }
+
+    /// Sync database with actual Git worktrees
+    pub async fn sync_with_git(&self, repo: Option<&str>) -> Result<()> {
+        let repo_name = self.resolve_repo_name(repo).await?;
+        let git_repo = self.open_git_repository(&repo_name).await?;
+        let repo_root = git_repo
+            .workdir()
+            .ok_or_else(|| anyhow::anyhow!(tr!("Repository has no working directory")))?;
+
+        println!(
+            "{}",
+            tr!(
+                "   {} Checking Git worktrees for repo: {}",
+                "🔍".bright_blue(),
+                repo_name
+            )
+        );
+
+        // Get actual Git worktrees
+        let git_worktrees = self.git.list_git_worktrees(repo_root)?;
+        println!(
+            "{}",
+            tr!(
+                "   {} Found {} Git worktrees",
+                "📊".bright_green(),
+                git_worktrees.len()
+            )
+        );
+
+        // Get database worktrees
+        let db_worktrees = self.db.list_worktrees(Some(&repo_name)).await?;
+        println!(
+            "{}",
+            tr!(
+                "   {} Found {} database entries",
+                "💾".bright_blue(),
+                db_worktrees.len()
+            )
+        );
+
+        let type_prefixes =
+            TypePrefixes::load(&self.db, &self.config.type_inference, &repo_name).await;
+        let mut unmatched = Vec::new();
+        let mut synced = 0;
+        let mut deactivated = 0;
+        let mut added = 0;
+        let mut detached = Vec::new();
+
+        // Deactivate database entries that don't exist in Git, or that git
+        // reports as prunable. Locked ones are kept even when their directory
+        // is missing (it may be on an unmounted drive), and worktrees on
+        // other machines sharing the registry can't be checked from here.
+        for db_worktree in db_worktrees.iter().filter(|wt| wt.is_local()) {
+            let git_worktree = git_worktrees
+                .iter()
+                .find(|git_wt| git_wt.is_at(Path::new(&db_worktree.path)));
+            let exists_in_git = match git_worktree {
+                Some(git_wt) if git_wt.is_prunable() && git_wt.is_locked() => {
+                    println!(
+                        "{}",
+                        tr!(
+                            "   {} Kept locked worktree: {}",
+                            "🔒".bright_yellow(),
+                            db_worktree.worktree_name
+                        )
+                    );
+                    true
+                }
+                Some(git_wt) => !git_wt.is_prunable(),
+                None => false,
+            };
+
+            if !exists_in_git {
+                self.db
+                    .deactivate_worktree(&repo_name, &db_worktree.worktree_name)
+                    .await?;
+                println!(
+                    "{}",
+                    tr!(
+                        "   {} Deactivated: {}",
+                        "❌".bright_red(),
+                        db_worktree.worktree_name
+                    )
+                );
+                deactivated += 1;
+            } else {
+                synced += 1;
+            }
+        }
+
+        // Add Git worktrees that aren't in database
+        for git_worktree in git_worktrees
+            .iter()
+            .filter(|wt| !wt.bare && !wt.is_prunable())
+        {
+            let exists_in_db = db_worktrees
+                .iter()
+                .any(|db_wt| git_worktree.is_at(Path::new(&db_wt.path)));
+
+            if !exists_in_db {
+                // Extract worktree info from Git data
+                let path = PathBuf::from(&git_worktree.path);
+                let worktree_name = path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or("unknown")
+                    .to_string();
+
+                // A detached HEAD has no branch to register
+                let Some(branch) = git_worktree.branch.as_deref() else {
+                    detached.push(worktree_name);
+                    continue;
+                };
+                let Some(worktree_type) = type_prefixes.infer(&worktree_name, branch) else {
+                    unmatched.push(worktree_name);
+                    continue;
+                };
+
+                self.db
+                    .create_worktree(
+                        &repo_name,
+                        &worktree_name,
+                        branch,
+                        worktree_type,
+                        &git_worktree.path,
+                        None,
+                    )
+                    .await?;
+
+                println!(
+                    "{}",
+                    tr!(
+                        "   {} Added: {} ({})",
+                        "➕".bright_green(),
+                        worktree_name,
+                        worktree_type
+                    )
+                );
+                added += 1;
+            }
+        }
+
+        println!("{}", tr!("\n{} Sync complete:", "✅".bright_green()));
+        println!(
+            "{}",
+            tr!("   {} {} entries synced", "🔄".bright_cyan(), synced)
+        );
+        println!(
+            "{}",
+            tr!(
+                "   {} {} entries deactivated",
+                "❌".bright_red(),
+                deactivated
+            )
+        );
+        println!(
+            "{}",
+            tr!("   {} {} entries added", "➕".bright_green(), added)
+        );
+        if !unmatched.is_empty() {
+            println!(
+                "{}",
+                tr!(
+                    "   {} {} worktree(s) match no type's prefix and were skipped: {}",
+                    "⚠️".bright_yellow(),
+                    unmatched.len(),
+                    unmatched.join(", ")
+                )
+            );
+            println!(
+                "{}",
+                tr!(
+                    "   {} Add a type with 'imi types add' or set [type_inference] unmatched_type",
+                    "💡".bright_yellow()
+                )
+            );
+        }
+        if !detached.is_empty() {
+            println!(
+                "{}",
+                tr!(
+                    "   {} {} worktree(s) on a detached HEAD were skipped: {}",
+                    "⚠️".bright_yellow(),
+                    detached.len(),
+                    detached.join(", ")
+                )
+            );
+        }
+
+        if let Some(repository) = self.db.get_repository(&repo_name).await? {
+            self.refresh_trunk_lock(
+                &repo_name,
+                Path::new(&repository.path),
+                &repository.default_branch,
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Rebase every worktree of the repository onto its freshly fetched
+    /// trunk (`imi sync --rebase-worktrees`). Worktrees with uncommitted
+    /// changes are skipped; conflicted ones stay mid-rebase and the batch is
+    /// saved for `imi sync --continue`.
+    pub async fn rebase_worktrees(&self, repo: Option<&str>) -> Result<RebaseBatch> {
+        let repo_name = self.resolve_repo_name(repo).await?;
+        let trunk_path = self.get_trunk_worktree(Some(&repo_name)).await?;
+        let batch_file = RebaseBatchFile::new(&self.detect_imi_path(&trunk_path)?);
+        if let Some(batch) = batch_file.load()? {
+            if !batch.is_finished() {
+                return Err(anyhow::anyhow!(tr!(
+                    "A rebase of {}'s worktrees onto {} is unfinished; resolve the conflicts and run `imi sync --continue`, or `imi sync --abort`",
+                    repo_name,
+                    batch.onto
+                )));
+            }
+        }
+
+        let trunk_repo = self.git.find_repository(Some(&trunk_path))?;
+        let upstream = self.config.remotes_for(&repo_name).upstream;
+        if let Err(e) = self.git.fetch_remote(&trunk_repo, &upstream) {
+            eprintln!(
+                "{}",
+                tr!(
+                    "{} Failed to fetch, rebasing onto the local trunk: {}",
+                    "⚠️".bright_yellow(),
+                    e
+                )
+            );
+        }
+        let default_branch = match self.db.get_repository(&repo_name).await? {
+            Some(repository) => repository.default_branch,
+            None => self.config.git_settings.default_branch.clone(),
+        };
+        let remote = format!("{}/{}", upstream, default_branch);
+        let onto = match trunk_repo.find_branch(&remote, git2::BranchType::Remote) {
+            Ok(_) => remote,
+            Err(_) => default_branch.clone(),
+        };
+
+        let items = self
+            .db
+            .list_worktrees(Some(&repo_name))
+            .await?
+            .into_iter()
+            .filter(|wt| {
+                wt.is_local()
+                    && wt.branch_name != default_branch
+                    && Path::new(&wt.path) != trunk_path
+                    && Path::new(&wt.path).exists()
+            })
+            .map(|wt| RebaseItem::new(&wt.name, &wt.branch_name, Path::new(&wt.path)))
+            .collect();
+        let batch = RebaseBatch::new(&repo_name, &onto, items);
+        self.run_rebase_batch(&batch_file, batch)
+    }
+
+    /// Resume the repository's unfinished rebase batch: stopped worktrees
+    /// whose conflicts are resolved are continued, and those not reached yet
+    /// are rebased (`imi sync --continue`)
+    pub async fn continue_rebase_worktrees(&self, repo: Option<&str>) -> Result<RebaseBatch> {
+        let (batch_file, batch) = self.unfinished_rebase_batch(repo).await?;
+        self.run_rebase_batch(&batch_file, batch)
+    }
+
+    /// Abort the stopped rebases of the repository's batch and forget it
+    /// (`imi sync --abort`)
+    pub async fn abort_rebase_worktrees(&self, repo: Option<&str>) -> Result<RebaseBatch> {
+        let (batch_file, mut batch) = self.unfinished_rebase_batch(repo).await?;
+        for item in batch.items.iter_mut().filter(|item| item.state.is_open()) {
+            if self.git.rebase_in_progress(&item.path).unwrap_or(false) {
+                self.git
+                    .execute_git_command(&item.path, &["rebase", "--abort"])
+                    .with_context(|| tr!("Failed to abort the rebase in {}", item.worktree_name))?;
+            }
+            item.set(RebaseState::Skipped, Some("aborted".to_string()));
+        }
+        batch_file.clear()?;
+        Ok(batch)
+    }
+
+    async fn unfinished_rebase_batch(
+        &self,
+        repo: Option<&str>,
+    ) -> Result<(RebaseBatchFile, RebaseBatch)> {
+        let repo_name = self.resolve_repo_name(repo).await?;
+        let trunk_path = self.get_trunk_worktree(Some(&repo_name)).await?;
+        let batch_file = RebaseBatchFile::new(&self.detect_imi_path(&trunk_path)?);
+        match batch_file.load()? {
+            Some(batch) if !batch.is_finished() => Ok((batch_file, batch)),
+            _ => Err(anyhow::anyhow!(tr!(
+                "No unfinished rebase for {}; start one with `imi sync --rebase-worktrees`",
+                repo_name
+            ))),
+        }
+    }
+
+    /// Work through the open worktrees of `batch` in order, saving it after
+    /// each one so an interruption loses nothing; forgotten once finished
+    fn run_rebase_batch(
+        &self,
+        batch_file: &RebaseBatchFile,
+        mut batch: RebaseBatch,
+    ) -> Result<RebaseBatch> {
+        for i in 0..batch.items.len() {
+            let path = batch.items[i].path.clone();
+            let outcome = match batch.items[i].state {
+                RebaseState::Pending => {
+                    if let Some(reason) = self.rebase_blocker(&path)? {
+                        batch.items[i].set(RebaseState::Skipped, Some(reason));
+                        batch_file.save(&batch)?;
+                        continue;
+                    }
+                    batch.items[i].set(RebaseState::InProgress, None);
+                    batch_file.save(&batch)?;
+                    self.git.rebase_onto(&path, &batch.onto)?
+                }
+                RebaseState::InProgress | RebaseState::Conflicted => {
+                    if self.git.rebase_in_progress(&path)? {
+                        self.git.continue_rebase(&path)?
+                    } else if self
+                        .git
+                        .run_git(&path, &["merge-base", "--is-ancestor", &batch.onto, "HEAD"])?
+                        .success
+                    {
+                        // Finished by hand with `git rebase --continue`
+                        RebaseOutcome::Rebased
+                    } else {
+                        batch.items[i].set(
+                            RebaseState::Skipped,
+                            Some("rebase was aborted by hand".to_string()),
+                        );
+                        batch_file.save(&batch)?;
+                        continue;
+                    }
+                }
+                _ => continue,
+            };
+
+            let (state, detail) = match outcome {
+                RebaseOutcome::Rebased => (RebaseState::Rebased, None),
+                RebaseOutcome::UpToDate => (RebaseState::UpToDate, None),
+                RebaseOutcome::Conflicted(files) => {
+                    (RebaseState::Conflicted, Some(files.join(", ")))
+                }
+                RebaseOutcome::Stopped(message) => (RebaseState::Conflicted, Some(message)),
+                RebaseOutcome::Failed(message) => (RebaseState::Failed, Some(message)),
+            };
+            batch.items[i].set(state, detail);
+            batch_file.save(&batch)?;
+        }
+
+        if batch.is_finished() {
+            batch_file.clear()?;
+        }
+        Ok(batch)
+    }
+
+    /// Why a worktree can't be rebased as it is
+    fn rebase_blocker(&self, path: &Path) -> Result<Option<String>> {
+        if !path.exists() {
+            return Ok(Some("worktree directory is missing".to_string()));
+        }
+        if self.git.is_head_detached(path)? {
+            return Ok(Some("detached HEAD".to_string()));
+        }
+        if self.git.rebase_in_progress(path)? {
+            return Ok(Some("another rebase is in progress".to_string()));
+        }
+        let changes = self
+            .git
+            .execute_git_command(path, &["status", "--porcelain", "--untracked-files=no"])?;
+        if !changes.trim().is_empty() {
+            return Ok(Some("uncommitted changes".to_string()));
+        }
+        Ok(None)
+    }
+
+    /// Find a worktree in the database by name, searching across all repos
+    /// if needed: the exact name first, else the single type-prefixed match.
+    /// Several prefixed matches are an [`ImiError::AmbiguousWorktreeName`].
+    async fn find_worktree_in_database(
+        &self,
+        name: &str,
+        repo: Option<&str>,
+    ) -> Result<Option<crate::database::Worktree>> {
+        let mut matches = Vec::new();
+        for (i, candidate) in self
+            .worktree_name_candidates(name)
+            .await
+            .into_iter()
+            .enumerate()
+        {
+            let found = match repo {
+                Some(repo_name) => self.db.get_worktree(repo_name, &candidate).await?,
+                None => self.db.find_worktree_by_name(&candidate).await?,
+            };
+            match found {
+                Some(worktree) if i == 0 => return Ok(Some(worktree)),
+                Some(worktree) => matches.push(worktree),
+                None => {}
+            }
+        }
+
+        match matches.len() {
+            0 | 1 => Ok(matches.pop()),
+            _ => Err(ImiError::AmbiguousWorktreeName {
+                name: name.to_string(),
+                matches: matches.into_iter().map(|wt| wt.name).collect(),
+            }
+            .into()),
+        }
+    }
+
+    /// The worktree `name` refers to: the name itself when such a worktree
+    /// exists, else the single type-prefixed match (`auth` -> `feat-auth`).
+    /// Several prefixed matches are an [`ImiError::AmbiguousWorktreeName`].
+    async fn find_actual_worktree_name(&self, name: &str, repo_name: &str) -> Result<String> {
+        if self.exact_names || self.db.get_worktree(repo_name, name).await?.is_some() {
+            return Ok(name.to_string());
+        }
+
+        let mut matches = Vec::new();
+        for candidate in self
+            .worktree_name_candidates(name)
+            .await
+            .into_iter()
+            .skip(1)
+        {
+            if self.db.get_worktree(repo_name, &candidate).await?.is_some() {
+                matches.push(candidate);
+            }
+        }
+
+        match matches.len() {
+            // None found: keep the original name so the error names it
+            0 => Ok(name.to_string()),
+            1 => Ok(matches.remove(0)),
+            _ => Err(ImiError::AmbiguousWorktreeName {
+                name: name.to_string(),
+                matches,
+            }
+            .into()),
+        }
+    }
+
+    /// `name` followed by `name` under every worktree type prefix
+    async fn worktree_name_candidates(&self, name: &str) -> Vec<String> {
+        if self.exact_names {
+            return vec![name.to_string()];
+        }
+
+        let mut prefixes: Vec<String> = ["feat-", "fix-", "aiops-", "devops-", "pr-"]
+            .iter()
+            .map(|prefix| prefix.to_string())
+            .collect();
+        if let Ok(types) = self.db.list_worktree_types().await {
+            for wt_type in types {
+                if !wt_type.worktree_prefix.is_empty()
+                    && !prefixes.contains(&wt_type.worktree_prefix)
+                {
+                    prefixes.push(wt_type.worktree_prefix);
+                }
+            }
+        }
+
+        std::iter::once(name.to_string())
+            .chain(
+                prefixes
+                    .iter()
+                    .filter(|prefix| !name.starts_with(prefix.as_str()))
+                    .map(|prefix| format!("{}{}", prefix, name)),
+            )
+            .collect()
+    }
+
+    /// Resolve the repository that owns worktree `name`.
+    ///
+    /// An explicit `--repo` or a current directory inside a repository wins.
+    /// Otherwise (e.g. running from $HOME, or detection from the current
+    /// directory fails) the registry is searched, and the repository is used
+    /// if it is the only one with an active worktree of that name.
+    async fn resolve_worktree_repo(&self, name: &str, repo: Option<&str>) -> Result<String> {
+        if repo.is_some() {
+            return self.resolve_repo_name(repo).await;
+        }
+        let in_repository = env::current_dir()
+            .map(|dir| self.git.is_in_repository(&dir))
+            .unwrap_or(false);
+        let detected = match in_repository {
+            true => Some(self.resolve_repo_name(None).await),
+            false => None,
+        };
+        if let Some(Ok(repo_name)) = detected {
+            return Ok(repo_name);
+        }
+
+        let candidates = self.worktree_name_candidates(name).await;
+        let repositories = self.db.list_repositories().await?;
+        let mut owners: Vec<String> = self
+            .db
+            .list_worktrees(None)
+            .await?
+            .into_iter()
+            .filter(|wt| candidates.contains(&wt.name))
+            .filter_map(|wt| {
+                repositories
+                    .iter()
+                    .find(|r| r.id == wt.project_id)
+                    .map(|r| r.name.clone())
+            })
+            .collect();
+        owners.sort();
+        owners.dedup();
+
+        match owners.as_slice() {
+            [] => match detected {
+                Some(result) => result,
+                None => self.resolve_repo_name(None).await,
+            },
+            [owner] => Ok(owner.clone()),
+            _ => Err(anyhow::anyhow!(tr!(
+                "Worktree '{}' exists in several repositories: {}. Please specify one with --repo.",
+                name,
+                owners.join(", ")
+            ))),
+        }
+    }
+
+    /// Open the Git repository for `repo_name` from its registered path, so
+    /// commands don't depend on the current directory. Unregistered
+    /// repositories are only found from inside them.
+    async fn open_git_repository(&self, repo_name: &str) -> Result<git2::Repository> {
+        if let Some(mut registered_repo) = self.db.get_repository(repo_name).await? {
+            self.validate_and_repair_repository_path(&mut registered_repo)
+                .await?;
+
+            let registered_path = PathBuf::from(&registered_repo.path);
+            return self
+                .git
+                .find_repository(Some(&registered_path))
+                .context(tr!(
+                    "Git repository not found at registered path: {}",
+                    registered_path.display()
+                ));
+        }
+
+        let current_dir = env::current_dir()?;
+        self.git
+            .find_repository(Some(&current_dir))
+            .context(tr!(
+                "Repository '{}' is not registered. Run 'imi init' inside it, or pass --repo with a registered repository.",
+                repo_name
+            ))
+    }
+
+    /// Split a repository argument into owner and name: `org/repo` with
+    /// aliases expanded, or a bare name under the default org
+    async fn parse_repo_argument(&self, repo_arg: &str) -> Result<(Option<String>, String)> {
+        // The name is the last segment; GitLab subgroups nest the owner
+        if let Some((org, name)) = repo_arg.rsplit_once('/') {
+            let org = self.config.org_settings.expand_alias(org);
+            return Ok((Some(org.to_string()), name.to_string()));
+        }
+        Ok((self.default_org().await?, repo_arg.to_string()))
+    }
+
+    /// The owner a bare repository name belongs to: the enclosing root's
+    /// or the global `default_org`, else the owner most registered remotes share
+    pub(crate) async fn default_org(&self) -> Result<Option<String>> {
+        let orgs = &self.config.org_settings;
+        let current_dir = env::current_dir().unwrap_or_default();
+        if let Some(org) = orgs.default_org_for(&current_dir) {
+            return Ok(Some(org.to_string()));
+        }
+        if !orgs.detect_from_remotes {
+            return Ok(None);
+        }
+
+        let repos = self.db.list_repositories().await?;
+        Ok(OrgSettings::detect_org(repos.iter().filter_map(|repo| {
+            RemoteUrl::parse(&repo.remote_url)
+                .map(|remote| remote.namespace)
+                .filter(|owner| !owner.is_empty())
+        })))
+    }
+
+    /// Resolve repository name from current directory or provided name
+    /// Handles GitHub org/repo format: searches database by remote_url pattern
+    /// Also supports running from sandbox directory (parent of trunk-main containing .iMi/)
+    pub(crate) async fn resolve_repo_name(&self, repo: Option<&str>) -> Result<String> {
+        if let Some(repo_arg) = repo {
+            // Parse the argument to check if it's org/repo format
+            let (org, repo_name) = self.parse_repo_argument(repo_arg).await?;
+
+            if let Some(org) = org {
+                // Query database for repo matching github pattern
+                let repos = self.db.list_repositories().await?;
+
+                // Match the remote's whole path: acme/api is not acme/api-gateway
+                let path = format!("{}/{}", org, repo_name);
+                for db_repo in repos {
+                    if RemoteUrl::parse(&db_repo.remote_url)
+                        .is_some_and(|remote| remote.matches_path(&path))
+                    {
+                        return Ok(db_repo.name);
+                    }
+                }
+
+                // If not found in database, return the repo_name part as fallback
+                // This allows plain name lookup to work for unregistered repos
+                Ok(repo_name)
+            } else {
+                // Plain name lookup
+                Ok(repo_arg.to_string())
+            }
+        } else {
+            // Try to get repo name from current directory
+            let current_dir = env::current_dir()?;
+
+            // PRIORITY FIX: Check if we're in a Git repository FIRST
+            // This is more reliable than repo_path or sandbox detection because:
+            // 1. repo_path may point to trunk directory instead of repo root
+            // 2. Worktrees have their own .iMi directories causing sandbox detection issues
+            // Using Git remote URL extraction ensures we get the correct repository name
+            if let Ok(repo) = self.git.find_repository(Some(&current_dir)) {
+                if let Ok(name) = self.git.get_repository_name(&repo) {
+                    return Ok(name);
+                }
+            }
+
+            // Fallback to repo_path if Git detection failed
+            if let Some(repo_path) = &self.repo_path {
+                if let Some(repo_name) = repo_path.file_name().and_then(|n| n.to_str()) {
+                    return Ok(repo_name.to_string());
+                }
+            }
+
+            // Check if we're in a sandbox directory (contains .iMi/)
+            // This allows commands like 'imi prune' and 'imi add' to work from the sandbox root
+            // Note: This must come AFTER Git detection because worktrees also contain .iMi directories
+            if let Some(sandbox_root) = self.find_sandbox_root(&current_dir)? {
+                // We're in or at a sandbox directory - look for registered repos
+                let repos = self.db.list_repositories().await?;
+
+                // If there's exactly one registered repo, use it
+                if repos.len() == 1 {
+                    return Ok(repos[0].name.clone());
+                }
+
+                // If multiple repos, try to find one whose path matches the sandbox
+                for db_repo in &repos {
+                    let repo_path = PathBuf::from(&db_repo.path);
+                    if let Some(repo_parent) = repo_path.parent() {
+                        if repo_parent == sandbox_root {
+                            return Ok(db_repo.name.clone());
+                        }
+                    }
+                }
+
+                // If we have multiple repos and can't determine which one, ask user to specify
+                if repos.len() > 1 {
+                    return Err(anyhow::anyhow!(tr!(
+                        "Multiple repositories found. Please specify with --repo or run from within a specific worktree.\nAvailable repositories: {}",
+                        repos.iter().map(|r| r.name.as_str()).collect::<Vec<_>>().join(", ")
+                    )));
+                }
+            }
+
+            // Try to infer from directory structure - look up the directory tree
+            let mut path = current_dir.as_path();
+            while let Some(parent) = path.parent() {
+                // Try to find a git repository in parent directories
+                if let Ok(repo) = self.git.find_repository(Some(parent)) {
+                    if let Ok(name) = self.git.get_repository_name(&repo) {
+                        return Ok(name);
+                    }
+                }
+                path = parent;
+            }
+
+            // Try to infer from directory name
+            if let Some(dir_name) = current_dir.file_name() {
+                if let Some(name) = dir_name.to_str() {
+                    // Handle worktree directory names (feat-name, pr-123, etc.)
+                    if let Some(_captures) =
+                        regex::Regex::new(r"^(feat|pr|fix|aiops|devops|trunk)-.*$")
+                            .unwrap()
+                            .captures(name)
+                    {
+                        // Look for parent directory that might be the repo
+                        if let Some(parent) = current_dir.parent() {
+                            if let Some(parent_name) = parent.file_name() {
+                                if let Some(repo_name) = parent_name.to_str() {
+                                    return Ok(repo_name.to_string());
+                                }
+                            }
+                        }
+                    }
+                    return Ok(name.to_string());
+                }
+            }
+
+            Err(anyhow::anyhow!(tr!(
+                "Could not determine repository name. Please specify with --repo or run from within a registered Git repository."
+            )))
+        }
+    }
+
+    /// Find the sandbox root directory by looking for .iMi/ directory
+    /// Searches current directory and walks up the tree
+    /// Returns Some(PathBuf) if found, None if not in a sandbox
+    fn find_sandbox_root(&self, start_path: &Path) -> Result<Option<PathBuf>> {
+        let mut current = start_path;
+
+        loop {
+            // Check if current directory contains .iMi/
+            if current.join(".iMi").is_dir() {
+                return Ok(Some(current.to_path_buf()));
+            }
+
+            // Move up to parent directory
+            match current.parent() {
+                Some(parent) => current = parent,
+                None => return Ok(None), // Reached filesystem root without finding .iMi/
+            }
+        }
+    }
+
+    /// Ensure repository is registered in the database
+    async fn ensure_repository_registered(&self, repo_name: &str, repo_path: &Path) -> Result<()> {
+        // Check if already registered
+        if self.db.get_repository(repo_name).await?.is_some() {
+            return Ok(());
+        }
+
+        // Get repository information
+        let remote_url = self
+            .git
+            .get_remote_url(repo_path)
+            .await
+            .unwrap_or_else(|_| "".to_string());
+        let default_branch = self
+            .git
+            .get_default_branch(repo_path)
+            .await
+            .unwrap_or_else(|_| "main".to_string());
+
+        // Register the repository
+        self.db
+            .create_repository(
+                repo_name,
+                repo_path.to_str().unwrap(),
+                &normalize_remote_url(&remote_url),
+                &default_branch,
+            )
+            .await?;
+
+        println!(
+            "{}",
+            tr!(
+                "📝 Registered repository: {} at {}",
+                repo_name.bright_green(),
+                repo_path.display()
+            )
+        );
+        Ok(())
+    }
+
+    /// Prune stale worktree references and orphaned directories
+    ///
+    /// This method addresses the issue where manually deleted worktree directories
+    /// leave behind stale Git references and database entries. The fix works in three phases:
+    ///
+    /// Phase 1: Git Cleanup - Force-prune Git worktree references for missing directories
+    /// Phase 2: Database Sync - Deactivate database entries where filesystem paths don't exist
+    /// Phase 3: Orphan Detection - Remove unregistered directories matching worktree patterns,
+    /// leaving risky ones (unpushed commits, stashes, important untracked files) unless
+    /// `include_risky` is set
+    ///
+    /// The critical fix is in Phase 1 (GitManager::prune_worktrees), which:
+    /// - Compares Git's worktree list with actual filesystem state using Path::exists()
+    /// - Identifies orphaned Git references (exist in Git but not on disk)
+    /// - Force-removes the .git/worktrees/<name> admin directory to clean up the reference
+    /// - Falls back to standard pruning for normally-prunable worktrees
+    pub async fn prune_stale_worktrees(
+        &self,
+        repo: Option<&str>,
+        dry_run: bool,
+        force: bool,
+        include_risky: bool,
+    ) -> Result<()> {
+        use colored::Colorize;
+
+        let repo_name = self.resolve_repo_name(repo).await?;
+        println!(
+            "{}",
+            tr!(
+                "{} Starting prune operation for: {}",
+                "🧹".bright_cyan(),
+                repo_name.bright_yellow()
+            )
+        );
+
+        // FIX: Use the configured trunk path to find the repository
+        // This allows 'imi prune' to run from the parent sandbox directory
+        let trunk_path = self.get_trunk_worktree(Some(&repo_name)).await
+            .context(tr!("Failed to find trunk worktree. Has the repository been initialized with 'imi init' or 'imi trunk'?"))?;
+        let git_repo = self.git.find_repository(Some(&trunk_path))
+            .context(tr!("Failed to find Git repository in trunk path: {}. Ensure your configuration is correct.", trunk_path.display()))?;
+
+        // PHASE 1: Git State Cleanup
+        // This is the CRITICAL FIX for the TASK.md issue:
+        // - Lists Git's worktrees from `git worktree list --porcelain`
+        // - A worktree git reports as prunable, or whose directory is missing
+        //   (orphaned reference), has its .git/worktrees/<name> admin
+        //   directory force-removed so Git forgets the deleted worktree
+        // - Locked worktrees are kept, as `git worktree prune` does
+        //
+        // Error Handling: Gracefully handles permission errors and concurrent access
+        println!(
+            "{}",
+            tr!(
+                "{} Phase 1: Cleaning up Git worktree references...",
+                "🔍".bright_blue()
+            )
+        );
+        self.git
+            .prune_worktrees(&git_repo)
+            .context(tr!("Failed to prune Git worktree references"))?;
+
+        // PHASE 2: Database State Cleanup
+        // Synchronize database with filesystem reality:
+        // - Query all database entries for this repository
+        // - Verify each entry's path actually exists on disk using Path::exists()
+        // - If path doesn't exist, deactivate the database entry
+        // - Maintains database consistency with actual worktree state
+        //
+        // Transaction Safety: Each deactivation is atomic within the database layer
+        cancel::check()?;
+        println!(
+            "{}",
+            tr!(
+                "{} Phase 2: Synchronizing database with filesystem...",
+                "💾".bright_blue()
+            )
+        );
+        let db_worktrees = self
+            .db
+            .list_worktrees(Some(&repo_name))
+            .await
+            .context(tr!("Failed to list database worktrees"))?;
+
+        let mut cleaned_count = 0;
+
+        // Git's worktrees after Phase 1, for cross-reference by path
+        let git_worktrees = self
+            .git
+            .list_git_worktrees(&trunk_path)
+            .context(tr!("Failed to list Git worktrees"))?;
+
+        // Paths of worktrees on other machines sharing the registry can't be
+        // checked from here
+        for worktree in db_worktrees.into_iter().filter(|wt| wt.is_local()) {
+            let worktree_path = PathBuf::from(&worktree.path);
+            // By path, or by directory name when the path was recorded
+            // differently, as git names its admin directories
+            let git_worktree = git_worktrees
+                .iter()
+                .find(|git_wt| git_wt.is_at(&worktree_path))
+                .or_else(|| {
+                    git_worktrees.iter().find(|git_wt| {
+                        Path::new(&git_wt.path).file_name()
+                            == Some(std::ffi::OsStr::new(&worktree.worktree_name))
+                    })
+                });
+
+            // A locked worktree's directory may be on an unmounted drive
+            if git_worktree.is_some_and(|git_wt| git_wt.is_locked()) {
+                continue;
+            }
+
+            // Check both filesystem existence AND Git registration
+            // A worktree should be deactivated if:
+            // 1. The directory doesn't exist on disk, OR
+            // 2. It's not registered in Git's worktree list, or Git reports
+            //    it as prunable
+            let path_exists = worktree_path.exists();
+            let git_registered = git_worktree.is_some_and(|git_wt| !git_wt.is_prunable());
+
+            if !path_exists || !git_registered {
+                let reason = if !path_exists && !git_registered {
+                    "path missing and not in Git"
+                } else if !path_exists {
+                    "path missing"
+                } else {
+                    "not in Git"
+                };
+
+                // Deactivate the database entry to maintain consistency
+                self.db
+                    .deactivate_worktree(&repo_name, &worktree.worktree_name)
+                    .await
+                    .context(tr!(
+                        "Failed to deactivate worktree: {}",
+                        worktree.worktree_name
+                    ))?;
+
+                println!(
+                    "{}",
+                    tr!(
+                        "   {} Deactivated database entry: {} ({})",
+                        "🗑️".bright_red(),
+                        worktree.worktree_name.bright_yellow(),
+                        reason.bright_black()
+                    )
+                );
+                cleaned_count += 1;
+            }
+        }
+
+        if cleaned_count > 0 {
+            println!(
+                "{}",
+                tr!(
+                    "{} Cleaned {} stale database entries",
+                    "✅".bright_green(),
+                    cleaned_count
+                )
+            );
+        } else {
+            println!(
+                "{}",
+                tr!("{} No stale database entries found", "ℹ️".bright_blue())
+            );
+        }
+
+        // PHASE 3: Orphaned Directory Cleanup
+        // Detect and remove directories that:
+        // - Match a worktree type's directory prefix (feat-, fix-, custom types, ...)
+        // - Are NOT registered in Git as worktrees
+        // - Are NOT valid Git repositories themselves
+        //
+        // These are "orphaned directories" - leftover filesystem cruft from failed operations
+        // or manual deletions where the Git reference was already cleaned but directory remains
+        //
+        // Safety: Requires confirmation unless --force flag is used
+        //         Respects --dry-run to preview without deleting
+        //         Directories holding unpushed commits, stashes or important
+        //         untracked files are only removed with --include-risky
+        cancel::check()?;
+        println!(
+            "{}",
+            tr!(
+                "{} Phase 3: Detecting orphaned worktree directories...",
+                "📦".bright_blue()
+            )
+        );
+        let type_prefixes =
+            TypePrefixes::load(&self.db, &self.config.type_inference, &repo_name).await;
+        let worktree_root = self.worktree_root(&repo_name, &self.detect_imi_path(&trunk_path)?);
+        self.prune_orphaned_directories(
+            &git_repo,
+            &worktree_root,
+            &type_prefixes,
+            dry_run,
+            force,
+            include_risky,
+        )
+        .await
+        .context(tr!("Failed to prune orphaned directories"))?;
+
+        // Trashed worktrees past the retention period
+        let trash = Trash::new(&self.detect_imi_path(&trunk_path)?);
+        let retention_days = self.config.trash.retention_days;
+        let expired = if dry_run {
+            trash
+                .list()?
+                .into_iter()
+                .filter(|entry| entry.age_days() >= i64::from(retention_days))
+                .collect()
+        } else {
+            trash.purge_expired(retention_days)?
+        };
+        for entry in &expired {
+            println!(
+                "{}",
+                tr!(
+                    "   {} {} trashed worktree: {} ({} days old)",
+                    "🗑️".bright_red(),
+                    if dry_run { "Would purge" } else { "Purged" },
+                    entry.id.bright_yellow(),
+                    entry.age_days()
+                )
+            );
+        }
+
+        // Background deletions that were interrupted
+        let pending = PendingDeletes::new(&self.detect_imi_path(&trunk_path)?);
+        let leftovers = if dry_run {
+            pending.list()?
+        } else {
+            tokio::task::spawn_blocking(move || pending.drain()).await??
+        };
+        for entry in &leftovers {
+            println!(
+                "{}",
+                tr!(
+                    "   {} {} pending deletion: {}",
+                    "🧹".bright_red(),
+                    if dry_run { "Would finish" } else { "Finished" },
+                    entry.name.bright_yellow()
+                )
+            );
+        }
 
-            if let Some(org) = org {
-                // Query database for repo matching github pattern
-                let repos = self.db.list_repositories().await?;
+        println!(
+            "{}",
+            tr!(
+                "{} Prune operation completed successfully",
+                "✅".bright_green().bold()
+            )
+        );
+        Ok(())
+    }
 
-                // Match against remote_url pattern: github.com/{org}/{repo}
-                for db_repo in repos {
-                    if db_repo
-                        .remote_url
-                        .contains(&format!("{}/{}", org, repo_name))
-                    {
-                        return Ok(db_repo.name);
-                    }
+    /// Detect and remove orphaned worktree directories
+    async fn prune_orphaned_directories(
+        &self,
+        git_repo: &git2::Repository,
+        worktree_root: &Path,
+        type_prefixes: &TypePrefixes,
+        dry_run: bool,
+        force: bool,
+        include_risky: bool,
+    ) -> Result<()> {
+        // Nothing was ever created under a configured worktree_dir
+        if !worktree_root.is_dir() {
+            return Ok(());
+        }
+
+        // Get list of currently registered worktrees from git
+        let registered_worktrees: Vec<String> = git_repo
+            .worktrees()?
+            .iter()
+            .flatten()
+            .map(|s| s.to_string())
+            .collect();
+
+        // Scan parent directory for potential orphaned directories
+        let mut orphaned_dirs = Vec::new();
+        let mut entries = async_fs::read_dir(worktree_root).await?;
+
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+
+            // Skip if not a directory
+            if !path.is_dir() {
+                continue;
+            }
+
+            let dir_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+
+            // Skip hidden directories and trunk
+            if dir_name.starts_with('.') || dir_name.starts_with("trunk-") {
+                continue;
+            }
+
+            // Check if named like a worktree of some type
+            if !type_prefixes.looks_like_worktree(dir_name) {
+                continue;
+            }
+
+            // Check if registered as a worktree
+            let is_registered = registered_worktrees.iter().any(|wt| wt == dir_name);
+
+            if is_registered {
+                continue;
+            }
+
+            // Check if it's a valid git repository
+            let is_valid_repo = self.git.open_repository(&path).is_ok();
+
+            if is_valid_repo {
+                // Valid repo but not registered - could be manually created
+                continue;
+            }
+
+            // Vendored checkouts inside would be deleted along with it, and
+            // iMi can't tell whether anything else has them
+            let nested = context::find_nested_repositories(&path);
+            if !nested.is_empty() {
+                let listing: Vec<String> = nested.iter().map(|p| p.display().to_string()).collect();
+                println!(
+                    "{}",
+                    tr!(
+                        "{} Skipping {}: it contains nested repositories ({}); remove it by hand",
+                        "⚠️".bright_yellow(),
+                        dir_name.bright_white(),
+                        listing.join(", ")
+                    )
+                );
+                continue;
+            }
+
+            // This is an orphaned directory - collect info
+            let size = self.get_directory_size(&path).await?;
+            let check = deletion_safety::inspect(&path);
+            orphaned_dirs.push((path.clone(), dir_name.to_string(), size, check));
+        }
+
+        if orphaned_dirs.is_empty() {
+            return Ok(());
+        }
+
+        // Display orphaned directories
+        println!(
+            "{}",
+            tr!(
+                "\n{} Found {} orphaned worktree directories:",
+                "📦".bright_yellow(),
+                orphaned_dirs.len()
+            )
+        );
+
+        let mut total_size = 0u64;
+        for (_, name, size, check) in &orphaned_dirs {
+            match check.safety() {
+                Safety::Safe => println!(
+                    "  {} {} ({})",
+                    "•".bright_yellow(),
+                    name.bright_white(),
+                    self.format_size(*size).bright_cyan()
+                ),
+                Safety::Risky => println!(
+                    "  {} {} ({}) {}",
+                    "⚠️".bright_red(),
+                    name.bright_white(),
+                    self.format_size(*size).bright_cyan(),
+                    format!("risky: {}", check.summary()).bright_red()
+                ),
+            }
+            total_size += size;
+        }
+
+        let risky = orphaned_dirs
+            .iter()
+            .filter(|(_, _, _, check)| !check.is_safe())
+            .count();
+        if risky > 0 && !include_risky {
+            println!(
+                "{}",
+                tr!(
+                    "\n{} Keeping {} risky director{}; pass --include-risky to remove {} too",
+                    "🛡️".bright_yellow(),
+                    risky,
+                    if risky == 1 { "y" } else { "ies" },
+                    if risky == 1 { "it" } else { "them" }
+                )
+            );
+            orphaned_dirs.retain(|(_, _, _, check)| check.is_safe());
+        }
+
+        println!(
+            "{}",
+            tr!(
+                "\n{} Total size: {}",
+                "💾".bright_cyan(),
+                self.format_size(total_size).bright_yellow()
+            )
+        );
+
+        if dry_run {
+            println!(
+                "{}",
+                tr!("\n{} Dry run - no directories removed", "ℹ️".bright_blue())
+            );
+            return Ok(());
+        }
+        if orphaned_dirs.is_empty() {
+            return Ok(());
+        }
+
+        // Ask for confirmation unless force flag is set
+        let should_remove = if force {
+            true
+        } else {
+            Confirm::new()
+                .with_prompt(tr!("Remove these orphaned directories?"))
+                .default(false)
+                .interact()?
+        };
+
+        if !should_remove {
+            println!("{}", tr!("{} Skipping removal", "⏭️".bright_yellow()));
+            return Ok(());
+        }
+
+        // Remove orphaned directories; each removal is whole, so Ctrl-C
+        // stops between them
+        let mut removed_count = 0;
+        let total = orphaned_dirs.len();
+        for (path, name, _, _) in orphaned_dirs {
+            if cancel::requested() {
+                println!(
+                    "{}",
+                    tr!(
+                        "{} Stopped after removing {} of {} directories; rerun 'imi prune' for the rest",
+                        "⏹️".bright_yellow(),
+                        removed_count,
+                        total
+                    )
+                );
+                return Err(ImiError::Cancelled.into());
+            }
+            match self.delete_worktree_dir(&path).await {
+                Ok(_) => {
+                    println!("{}", tr!("🗑️ Removed: {}", name.bright_green()));
+                    removed_count += 1;
+                }
+                Err(e) => {
+                    println!(
+                        "{}",
+                        tr!("❌ Failed to remove {}: {}", name.bright_red(), e)
+                    );
+                }
+            }
+        }
+
+        if removed_count > 0 {
+            println!(
+                "{}",
+                tr!(
+                    "\n{} Removed {} orphaned directories",
+                    "✅".bright_green(),
+                    removed_count
+                )
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Calculate directory size recursively
+    fn get_directory_size<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<u64>> + 'a>> {
+        Box::pin(async move {
+            let mut total_size = 0u64;
+            let mut entries = async_fs::read_dir(path).await?;
+
+            while let Some(entry) = entries.next_entry().await? {
+                let entry_path = entry.path();
+                let metadata = async_fs::metadata(&entry_path).await?;
+
+                if metadata.is_file() {
+                    total_size += metadata.len();
+                } else if metadata.is_dir() {
+                    total_size += self.get_directory_size(&entry_path).await?;
                 }
+            }
+
+            Ok(total_size)
+        })
+    }
+
+    /// Format byte size to human-readable string
+    fn format_size(&self, bytes: u64) -> String {
+        const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
+        let mut size = bytes as f64;
+        let mut unit_idx = 0;
+
+        while size >= 1024.0 && unit_idx < UNITS.len() - 1 {
+            size /= 1024.0;
+            unit_idx += 1;
+        }
+
+        format!("{:.2} {}", size, UNITS[unit_idx])
+    }
+
+    /// Fuzzy navigate to a worktree or repository
+    #[allow(dead_code)] // Library API; the CLI goes through fuzzy_select
+    pub async fn fuzzy_navigate(
+        &self,
+        query: Option<&str>,
+        repo: Option<&str>,
+        worktrees_only: bool,
+        include_inactive: bool,
+    ) -> Result<PathBuf> {
+        Ok(self
+            .fuzzy_select(query, repo, worktrees_only, include_inactive)
+            .await?
+            .path())
+    }
+
+    /// The worktree or repository `fuzzy_navigate` goes to
+    pub async fn fuzzy_select(
+        &self,
+        query: Option<&str>,
+        repo: Option<&str>,
+        worktrees_only: bool,
+        include_inactive: bool,
+    ) -> Result<SearchTarget> {
+        let matcher = FuzzyMatcher::new(self.db.clone()).include_archived(self.include_archived);
+
+        let selected = if let Some(query_str) = query {
+            // Perform fuzzy search
+            let results = matcher
+                .search(query_str, repo, worktrees_only, include_inactive)
+                .await?;
+
+            if results.is_empty() {
+                return Err(anyhow::anyhow!(tr!(
+                    "No worktrees or repositories found matching '{}'",
+                    query_str
+                )));
+            }
+
+            // Auto-select top result if score is very high (exact/near match)
+            if results[0].score() >= 0.8 {
+                eprintln!(
+                    "{}",
+                    tr!(
+                        "{} Selected: {}",
+                        "✅".bright_green(),
+                        results[0].display_name()
+                    )
+                );
+                results[0].clone()
+            } else if results.len() == 1 {
+                // Only one result, auto-select it
+                eprintln!(
+                    "{}",
+                    tr!(
+                        "{} Selected: {}",
+                        "✅".bright_green(),
+                        results[0].display_name()
+                    )
+                );
+                results[0].clone()
+            } else {
+                // Multiple ambiguous results - show picker
+                eprintln!(
+                    "{}",
+                    tr!(
+                        "{} Multiple matches found for '{}':",
+                        "🔍".bright_yellow(),
+                        query_str
+                    )
+                );
+
+                use dialoguer::{theme::ColorfulTheme, Select};
+
+                let display_items: Vec<String> = results
+                    .iter()
+                    .map(|target| {
+                        let icon = match target.worktree_type() {
+                            Some("feat") => "🚀",
+                            Some("pr") => "🔍",
+                            Some("fix") => "🔧",
+                            Some("aiops") => "🤖",
+                            Some("devops") => "⚙️",
+                            Some("trunk") => "🌳",
+                            _ => "📁",
+                        };
+                        format!(
+                            "{} {} ({}) - score: {:.2}",
+                            icon,
+                            highlight_matches(&target.display_name(), query_str),
+                            target.repo_name(),
+                            target.score()
+                        )
+                    })
+                    .collect();
 
-                // If not found in database, return the repo_name part as fallback
-                // This allows plain name lookup to work for unregistered repos
-                Ok(repo_name)
-            } else {
-                // Plain name lookup
-                Ok(repo_arg.to_string())
+                let selection = Select::with_theme(&ColorfulTheme::default())
+                    .with_prompt(tr!("Select a target"))
+                    .items(&display_items)
+                    .default(0)
+                    .interact()?;
+
+                results[selection].clone()
             }
         } else {
-            // Try to get repo name from current directory
-            let current_dir = env::current_dir()?;
+            // No query - show interactive picker
+            if let Some(target) = matcher.interactive_select(repo, worktrees_only).await? {
+                target
+            } else {
+                return Err(anyhow::anyhow!(tr!(
+                    "No worktrees or repositories available"
+                )));
+            }
+        };
 
-            // PRIORITY FIX: Check if we're in a Git repository FIRST
-            // This is more reliable than repo_path or sandbox detection because:
-            // 1. repo_path may point to trunk directory instead of repo root
-            // 2. Worktrees have their own .iMi directories causing sandbox detection issues
-            // Using Git remote URL extraction ensures we get the correct repository name
-            if let Ok(repo) = self.git.find_repository(Some(&current_dir)) {
-                if let Ok(name) = self.git.get_repository_name(&repo) {
-                    return Ok(name);
+        // Frecency is best-effort; never fail navigation over it
+        let _ = matcher.record_selection(&selected).await;
+
+        Ok(selected)
+    }
+
+    /// Resolve a query to its best-scoring targets without prompting.
+    ///
+    /// Uses the same scoring as `fuzzy_navigate`, but instead of showing a
+    /// picker it returns every target tied for the top score. An empty result
+    /// means nothing matched; more than one means the query is ambiguous.
+    pub async fn resolve_targets(
+        &self,
+        query: &str,
+        repo: Option<&str>,
+        worktrees_only: bool,
+        include_inactive: bool,
+    ) -> Result<Vec<SearchTarget>> {
+        // Usage history would make the result depend on past navigation
+        let matcher = FuzzyMatcher::new(self.db.clone())
+            .without_frecency()
+            .include_archived(self.include_archived);
+        let results = matcher
+            .search(query, repo, worktrees_only, include_inactive)
+            .await?;
+
+        let top_score = match results.first() {
+            Some(top) => top.score(),
+            None => return Ok(Vec::new()),
+        };
+
+        Ok(results
+            .into_iter()
+            .take_while(|target| target.score() >= top_score)
+            .collect())
+    }
+
+    /// Directory a repository's worktrees are created in: IMI_PATH itself,
+    /// or its `[worktree_layout]` subdirectory
+    pub fn worktree_root(&self, repo_name: &str, imi_path: &Path) -> PathBuf {
+        match self.config.worktree_layout.worktree_dir_for(repo_name) {
+            Some(dir) => imi_path.join(dir),
+            None => imi_path.to_path_buf(),
+        }
+    }
+
+    /// Detect IMI_PATH based on repository structure
+    /// If we're in a trunk directory (trunk-*), return its parent
+    /// If we're in a worktree under a `[worktree_layout]` directory, return
+    /// the directory that one is in
+    /// Otherwise, return the repository root's parent
+    pub fn detect_imi_path(&self, repo_root: &Path) -> Result<PathBuf> {
+        // Check if the current repo_root is a trunk directory
+        if let Some(dir_name) = repo_root.file_name() {
+            if let Some(name) = dir_name.to_str() {
+                // Check if this is a trunk directory (pattern: trunk-*)
+                if name.starts_with("trunk-") {
+                    // This is a trunk directory, so its parent is the IMI_PATH
+                    if let Some(parent) = repo_root.parent() {
+                        return Ok(parent.to_path_buf());
+                    }
                 }
             }
+        }
 
-            // Fallback to repo_path if Git detection failed
-            if let Some(repo_path) = &self.repo_path {
-                if let Some(repo_name) = repo_path.file_name().and_then(|n| n.to_str()) {
-                    return Ok(repo_name.to_string());
+        if let Some(parent) = repo_root.parent() {
+            for dir in self.config.worktree_layout.worktree_dirs() {
+                if parent.ends_with(dir) {
+                    if let Some(imi_path) = parent.ancestors().nth(dir.components().count()) {
+                        return Ok(imi_path.to_path_buf());
+                    }
                 }
             }
+        }
 
-            // Check if we're in a sandbox directory (contains .iMi/)
-            // This allows commands like 'imi prune' and 'imi add' to work from the sandbox root
-            // Note: This must come AFTER Git detection because worktrees also contain .iMi directories
-            if let Some(sandbox_root) = self.find_sandbox_root(&current_dir)? {
-                // We're in or at a sandbox directory - look for registered repos
-                let repos = self.db.list_repositories().await?;
+        // Fall back to repository root's parent (original behavior)
+        let imi_path = repo_root.parent().unwrap_or(repo_root);
+        Ok(imi_path.to_path_buf())
+    }
 
-                // If there's exactly one registered repo, use it
-                if repos.len() == 1 {
-                    return Ok(repos[0].name.clone());
-                }
+    /// Ensure all work is happening inside the current entity's office path.
+    fn ensure_office_layout(&self, repo_name: &str, trunk_path: &Path) -> Result<()> {
+        let expected_container = self.config.get_repo_path(repo_name);
+        let actual_parent = trunk_path
+            .parent()
+            .ok_or_else(|| anyhow::anyhow!(tr!("Invalid trunk path: {}", trunk_path.display())))?;
 
-                // If multiple repos, try to find one whose path matches the sandbox
-                for db_repo in &repos {
-                    let repo_path = PathBuf::from(&db_repo.path);
-                    if let Some(repo_parent) = repo_path.parent() {
-                        if repo_parent == sandbox_root {
-                            return Ok(db_repo.name.clone());
-                        }
-                    }
-                }
+        if actual_parent != expected_container.as_path() {
+            return Err(anyhow::anyhow!(tr!(
+                "Repository '{}' is outside the current entity office.\nExpected trunk under: {}\nActual trunk path: {}\nMigrate with 'imi init' from the repository to enforce office layout.",
+                repo_name,
+                expected_container.display(),
+                trunk_path.display()
+            )));
+        }
 
-                // If we have multiple repos and can't determine which one, ask user to specify
-                if repos.len() > 1 {
-                    return Err(anyhow::anyhow!(
-                        "Multiple repositories found. Please specify with --repo or run from within a specific worktree.\nAvailable repositories: {}",
-                        repos.iter().map(|r| r.name.as_str()).collect::<Vec<_>>().join(", ")
-                    ));
+        let dir_name = trunk_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default();
+
+        if !dir_name.starts_with("trunk-") {
+            return Err(anyhow::anyhow!(tr!(
+                "Invalid trunk path '{}'. Expected directory name to start with 'trunk-'.",
+                trunk_path.display()
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Share another machine's registrations with this one: copy `host`'s
+    /// repositories and worktrees, with paths under the remap's OLD moved
+    /// to NEW, as this machine's own. Ones already registered here are
+    /// skipped. Returns how many repositories and worktrees were adopted.
+    pub async fn adopt_host_registrations(
+        &self,
+        host: &str,
+        remap: &PathRemap,
+        repo: Option<&str>,
+    ) -> Result<(usize, usize)> {
+        if host == crate::database::local_host() {
+            return Err(anyhow::anyhow!(tr!(
+                "'{}' is this machine; --from-host takes another machine's name",
+                host
+            )));
+        }
+        let moved = |path: &str| {
+            remap
+                .apply(Path::new(path))
+                .map_or_else(|| path.to_string(), |p| p.to_string_lossy().to_string())
+        };
+
+        let repositories: Vec<Repository> = self
+            .db
+            .list_host_repositories(host)
+            .await?
+            .into_iter()
+            .filter(|r| repo.is_none_or(|name| r.name == name))
+            .collect();
+        if repositories.is_empty() {
+            return Err(anyhow::anyhow!(tr!(
+                "No repositories registered on host '{}'",
+                host
+            )));
+        }
+        let worktrees = self.db.list_host_worktrees(host).await?;
+
+        let (mut adopted_repos, mut adopted_worktrees) = (0, 0);
+        for theirs in &repositories {
+            // A legacy row without a host is shared, so it doesn't count as ours
+            let ours = match self.db.get_repository(&theirs.name).await? {
+                Some(ours) if ours.host.is_some() => ours,
+                _ => {
+                    adopted_repos += 1;
+                    self.db
+                        .create_repository(
+                            &theirs.name,
+                            &moved(&theirs.path),
+                            &theirs.remote_url,
+                            &theirs.default_branch,
+                        )
+                        .await?
                 }
-            }
+            };
 
-            // Try to infer from directory structure - look up the directory tree
-            let mut path = current_dir.as_path();
-            while let Some(parent) = path.parent() {
-                // Try to find a git repository in parent directories
-                if let Ok(repo) = self.git.find_repository(Some(parent)) {
-                    if let Ok(name) = self.git.get_repository_name(&repo) {
-                        return Ok(name);
-                    }
+            for wt in worktrees.iter().filter(|wt| wt.project_id == theirs.id) {
+                if self.db.get_worktree(&ours.name, &wt.name).await?.is_some() {
+                    continue;
                 }
-                path = parent;
+                self.db
+                    .create_worktree(
+                        &ours.name,
+                        &wt.name,
+                        &wt.branch_name,
+                        &wt.worktree_type,
+                        &moved(&wt.path),
+                        None,
+                    )
+                    .await?;
+                adopted_worktrees += 1;
             }
+        }
+        Ok((adopted_repos, adopted_worktrees))
+    }
 
-            // Try to infer from directory name
-            if let Some(dir_name) = current_dir.file_name() {
-                if let Some(name) = dir_name.to_str() {
-                    // Handle worktree directory names (feat-name, pr-123, etc.)
-                    if let Some(_captures) = regex::Regex::new(r"^(feat|pr|fix|aiops|devops|trunk)-.*$")
-                        .unwrap()
-                        .captures(name)
-                    {
-                        // Look for parent directory that might be the repo
-                        if let Some(parent) = current_dir.parent() {
-                            if let Some(parent_name) = parent.file_name() {
-                                if let Some(repo_name) = parent_name.to_str() {
-                                    return Ok(repo_name.to_string());
-                                }
-                            }
-                        }
+    /// `imi hooks install`/`uninstall`: add or remove the activity hooks in
+    /// every hooks directory the repository's worktrees run (one shared
+    /// directory unless worktrees set their own `core.hooksPath`)
+    pub async fn activity_hooks(
+        &self,
+        repo: Option<&str>,
+        install: bool,
+    ) -> Result<Vec<(String, PathBuf, Vec<(&'static str, git_hooks::HookChange)>)>> {
+        let repositories =
+            match repo {
+                Some(name) => vec![self.db.get_repository(name).await?.ok_or_else(|| {
+                    anyhow::anyhow!(tr!("Repository '{}' is not registered", name))
+                })?],
+                None => self.db.list_repositories().await?,
+            };
+
+        let mut results = Vec::new();
+        for repository in repositories.iter().filter(|r| r.is_local()) {
+            let trunk = PathBuf::from(&repository.path);
+            let mut paths = vec![trunk];
+            paths.extend(
+                self.db
+                    .list_worktrees(Some(&repository.name))
+                    .await?
+                    .into_iter()
+                    .map(|wt| PathBuf::from(wt.path)),
+            );
+
+            let mut hooks_dirs: Vec<PathBuf> = Vec::new();
+            for path in paths.iter().filter(|path| path.exists()) {
+                if let Ok(dir) = self.git.hooks_dir(path) {
+                    let dir = dir.canonicalize().unwrap_or(dir);
+                    if !hooks_dirs.contains(&dir) {
+                        hooks_dirs.push(dir);
                     }
-                    return Ok(name.to_string());
                 }
             }
 
-            Err(anyhow::anyhow!("Could not determine repository name. Please specify with --repo or run from within a registered Git repository."))
+            for dir in hooks_dirs {
+                let changes = if install {
+                    git_hooks::install_activity_hooks(&dir)?
+                } else {
+                    git_hooks::uninstall_activity_hooks(&dir)?
+                };
+                results.push((repository.name.clone(), dir, changes));
+            }
         }
+        Ok(results)
     }
 
-    /// Find the sandbox root directory by looking for .iMi/ directory
-    /// Searches current directory and walks up the tree
-    /// Returns Some(PathBuf) if found, None if not in a sandbox
-    fn find_sandbox_root(&self, start_path: &Path) -> Result<Option<PathBuf>> {
-        let mut current = start_path;
+    /// `imi agent scaffold`: render the agent instruction files into the
+    /// repository's `sync/user` and, with `link`, symlink them into its
+    /// trunk and every worktree that doesn't have its own
+    pub async fn scaffold_agent_files(
+        &self,
+        repo: Option<&str>,
+        force: bool,
+        link: bool,
+    ) -> Result<AgentScaffold> {
+        let repo_name = self.resolve_repo_name(repo).await?;
+        let repository =
+            self.db.get_repository(&repo_name).await?.ok_or_else(|| {
+                anyhow::anyhow!(tr!("Repository '{}' is not registered", repo_name))
+            })?;
+        let trunk = PathBuf::from(&repository.path);
+
+        let types = self.db.list_worktree_types().await?;
+        let merge_strategy =
+            format!("{:?}", self.config.merge_policy.strategy_for(&repo_name)).to_lowercase();
+        let facts = agent_scaffold::RepoFacts {
+            repo_name: &repo_name,
+            language: sync_scaffold::detect_language(&trunk).unwrap_or("unknown"),
+            default_branch: &repository.default_branch,
+            merge_strategy: &merge_strategy,
+            types: &types,
+        };
 
-        loop {
-            // Check if current directory contains .iMi/
-            if current.join(".iMi").is_dir() {
-                return Ok(Some(current.to_path_buf()));
-            }
+        let settings = &self.config.agent_scaffold;
+        let template_dir = settings.resolve_template_dir()?;
+        let user_sync = self.config.get_sync_path(&repo_name, true);
+        let mut files = Vec::new();
+        for file in &settings.files {
+            let template = agent_scaffold::template_for(&template_dir, &repo_name, file)?;
+            let contents = agent_scaffold::render(&template, file, &facts);
+            let (path, outcome) = agent_scaffold::write(&user_sync, file, &contents, force)?;
+            files.push(ScaffoldedFile { path, outcome });
+        }
 
-            // Move up to parent directory
-            match current.parent() {
-                Some(parent) => current = parent,
-                None => return Ok(None), // Reached filesystem root without finding .iMi/
+        let mut worktrees = Vec::new();
+        if link {
+            let mut paths = vec![trunk];
+            for wt in self.db.list_worktrees(Some(&repo_name)).await? {
+                let path = PathBuf::from(wt.path);
+                if !paths.contains(&path) {
+                    paths.push(path);
+                }
+            }
+            for path in paths.into_iter().filter(|path| path.exists()) {
+                self.create_symlinks(&repo_name, &path).await?;
+                worktrees.push(path);
             }
         }
+
+        Ok(AgentScaffold {
+            repo_name,
+            files,
+            worktrees,
+        })
     }
 
-    /// Ensure repository is registered in the database
-    async fn ensure_repository_registered(&self, repo_name: &str, repo_path: &Path) -> Result<()> {
-        // Check if already registered
-        if self.db.get_repository(repo_name).await?.is_some() {
-            return Ok(());
-        }
+    /// `imi _record-activity`, run by the activity hooks: log the commit or
+    /// branch switch git just made in the current worktree. Returns None
+    /// for events not worth logging (file checkouts, worktree creation) or
+    /// directories imi doesn't manage.
+    pub async fn record_git_activity(
+        &self,
+        hook: &str,
+        args: &[String],
+    ) -> Result<Option<AgentActivity>> {
+        let current_dir = env::current_dir()?;
+        let current_dir = current_dir.canonicalize().unwrap_or(current_dir);
+        let worktree = self
+            .db
+            .list_worktrees(None)
+            .await?
+            .into_iter()
+            .filter(|wt| wt.is_local())
+            .filter(|wt| {
+                let path = PathBuf::from(&wt.path);
+                current_dir.starts_with(path.canonicalize().unwrap_or(path))
+            })
+            .max_by_key(|wt| wt.path.len());
+        let Some(worktree) = worktree else {
+            return Ok(None);
+        };
+        let worktree_path = PathBuf::from(&worktree.path);
 
-        // Get repository information
-        let remote_url = self
-            .git
-            .get_remote_url(repo_path)
-            .await
-            .unwrap_or_else(|_| "".to_string());
-        let default_branch = self
-            .git
-            .get_default_branch(repo_path)
-            .await
-            .unwrap_or_else(|_| "main".to_string());
+        let (activity_type, description) = match hook {
+            "post-commit" => {
+                let (sha, summary) = self.git.get_head_commit(&worktree_path)?;
+                let short = &sha[..sha.len().min(8)];
+                ("commit", format!("{} {}", short, summary))
+            }
+            "post-checkout" => {
+                // git passes the previous HEAD, the new HEAD, and 1 for a
+                // branch checkout; a zero previous HEAD is `git worktree add`
+                let previous = args.first().map(String::as_str).unwrap_or_default();
+                let branch_checkout = args.get(2).is_some_and(|flag| flag == "1");
+                if !branch_checkout || previous.chars().all(|c| c == '0') {
+                    return Ok(None);
+                }
+                let branch = self
+                    .git
+                    .get_current_branch(&worktree_path)
+                    .unwrap_or_else(|_| "detached HEAD".to_string());
+                ("checkout", format!("Switched to {}", branch))
+            }
+            other => return Err(anyhow::anyhow!(tr!("Unknown git hook '{}'", other))),
+        };
 
-        // Register the repository
-        self.db
-            .create_repository(
-                repo_name,
-                repo_path.to_str().unwrap(),
-                &remote_url,
-                &default_branch,
-            )
+        let agent_id = env::var("IMI_AGENT_ID")
+            .ok()
+            .filter(|id| !id.is_empty())
+            .or_else(|| worktree.agent_id.clone())
+            .unwrap_or_else(|| "git".to_string());
+        let activity = self
+            .db
+            .log_agent_activity(&agent_id, &worktree.id, activity_type, None, &description)
             .await?;
-
-        println!(
-            "📝 Registered repository: {} at {}",
-            repo_name.bright_green(),
-            repo_path.display()
-        );
-        Ok(())
+        self.db.record_activity(&worktree.id).await?;
+        Ok(Some(activity))
     }
 
-    /// Prune stale worktree references and orphaned directories
-    ///
-    /// This method addresses the issue where manually deleted worktree directories
-    /// leave behind stale Git references and database entries. The fix works in three phases:
-    ///
-    /// Phase 1: Git Cleanup - Force-prune Git worktree references for missing directories
-    /// Phase 2: Database Sync - Deactivate database entries where filesystem paths don't exist
-    /// Phase 3: Orphan Detection - Remove unregistered directories matching worktree patterns
-    ///
-    /// The critical fix is in Phase 1 (GitManager::prune_worktrees), which:
-    /// - Compares Git's worktree list with actual filesystem state using Path::exists()
-    /// - Identifies orphaned Git references (exist in Git but not on disk)
-    /// - Force-removes the .git/worktrees/<name> admin directory to clean up the reference
-    /// - Falls back to standard pruning for normally-prunable worktrees
-    pub async fn prune_stale_worktrees(
+    /// `imi repair`: rewrite paths under `remap`, find moved trunks and
+    /// worktrees, run `git worktree repair` so git's gitdir links match, and
+    /// recreate broken `symlink_files` links
+    pub async fn repair_repositories(
         &self,
-        repo: Option<&str>,
-        dry_run: bool,
-        force: bool,
+        repo: Option<&str>,
+        remap: Option<&PathRemap>,
     ) -> Result<()> {
-        use colored::Colorize;
+        let repositories =
+            match repo {
+                Some(name) => vec![self.db.get_repository(name).await?.ok_or_else(|| {
+                    anyhow::anyhow!(tr!("Repository '{}' is not registered", name))
+                })?],
+                None => self.db.list_repositories().await?,
+            };
 
-        let repo_name = self.resolve_repo_name(repo).await?;
+        if repositories.is_empty() {
+            println!("{}", tr!("No repositories registered."));
+            return Ok(());
+        }
+
+        let total_count = repositories.len();
         println!(
-            "{} Starting prune operation for: {}",
-            "🧹".bright_cyan(),
-            repo_name.bright_yellow()
+            "{}",
+            tr!("Checking {} registered repositories...", total_count)
         );
+        println!();
 
-        // FIX: Use the configured trunk path to find the repository
-        // This allows 'imi prune' to run from the parent sandbox directory
-        let trunk_path = self.get_trunk_worktree(Some(&repo_name)).await
-            .context("Failed to find trunk worktree. Has the repository been initialized with 'imi init' or 'imi trunk'?")?;
-        let git_repo = self.git.find_repository(Some(&trunk_path))
-            .context(format!("Failed to find Git repository in trunk path: {}. Ensure your configuration is correct.", trunk_path.display()))?;
+        let mut repaired_count = 0;
+        let mut error_count = 0;
 
-        // PHASE 1: Git State Cleanup
-        // This is the CRITICAL FIX for the TASK.md issue:
-        // - Iterates through Git's registered worktrees
-        // - Uses Path::exists() to verify each worktree directory actually exists
-        // - If directory is missing but Git reference exists (orphaned reference):
-        //   * Force-removes the .git/worktrees/<name> admin directory
-        //   * This allows Git to forget about the manually deleted worktree
-        // - If directory exists and worktree is prunable, uses standard Git prune
-        //
-        // Error Handling: Gracefully handles permission errors and concurrent access
-        println!(
-            "{} Phase 1: Cleaning up Git worktree references...",
-            "🔍".bright_blue()
-        );
-        self.git
-            .prune_worktrees(&git_repo)
-            .context("Failed to prune Git worktree references")?;
+        for mut repo in repositories {
+            print!("{}", tr!("Checking {}: ", repo.name.bright_yellow()));
 
-        // PHASE 2: Database State Cleanup
-        // Synchronize database with filesystem reality:
-        // - Query all database entries for this repository
-        // - Verify each entry's path actually exists on disk using Path::exists()
-        // - If path doesn't exist, deactivate the database entry
-        // - Maintains database consistency with actual worktree state
-        //
-        // Transaction Safety: Each deactivation is atomic within the database layer
+            match self.repair_repository(&mut repo, remap).await {
+                Ok(repairs) if repairs.is_empty() => {
+                    println!("{}", tr!("✓ OK").bright_green());
+                }
+                Ok(repairs) => {
+                    println!(
+                        "{}",
+                        format!("✓ Repaired ({})", repairs.join(", ")).bright_green()
+                    );
+                    repaired_count += 1;
+                }
+                Err(e) => {
+                    println!("{}", format!("✗ Error: {}", e).bright_red());
+                    error_count += 1;
+                }
+            }
+        }
+
+        println!();
         println!(
-            "{} Phase 2: Synchronizing database with filesystem...",
-            "💾".bright_blue()
+            "{}",
+            tr!(
+                "Summary: {} repaired, {} errors, {} total",
+                repaired_count,
+                error_count,
+                total_count
+            )
         );
-        let db_worktrees = self
-            .db
-            .list_worktrees(Some(&repo_name))
-            .await
-            .context("Failed to list database worktrees")?;
 
-        let mut cleaned_count = 0;
-        let mut git_worktrees_set = std::collections::HashSet::new();
+        if error_count > 0 {
+            Err(anyhow::anyhow!(tr!(
+                "{} repositories could not be repaired",
+                error_count
+            )))
+        } else {
+            Ok(())
+        }
+    }
 
-        // Build a set of currently valid Git worktrees for cross-reference
-        if let Ok(git_worktree_names) = git_repo.worktrees() {
-            for wt_name in git_worktree_names.iter().flatten() {
-                git_worktrees_set.insert(wt_name.to_string());
+    /// Every repair step for one repository; returns what was fixed
+    async fn repair_repository(
+        &self,
+        repo: &mut Repository,
+        remap: Option<&PathRemap>,
+    ) -> Result<Vec<String>> {
+        let mut repairs = Vec::new();
+
+        if let Some(remap) = remap {
+            let remapped = self.remap_paths(repo, remap).await?;
+            if remapped > 0 {
+                repairs.push(format!("{} path(s) remapped", remapped));
             }
         }
+        if self.validate_and_repair_repository_path(repo).await? {
+            repairs.push("moved paths found".to_string());
+        }
 
-        for worktree in db_worktrees {
-            let worktree_path = PathBuf::from(&worktree.path);
+        let worktrees: Vec<Worktree> = self
+            .db
+            .list_worktrees(Some(&repo.name))
+            .await?
+            .into_iter()
+            .filter(|wt| wt.is_local() && wt.path != repo.path && Path::new(&wt.path).exists())
+            .collect();
 
-            // Check both filesystem existence AND Git registration
-            // A worktree should be deactivated if:
-            // 1. The directory doesn't exist on disk, OR
-            // 2. It's not registered in Git's worktree list
-            let path_exists = worktree_path.exists();
-            let git_registered = git_worktrees_set.contains(&worktree.worktree_name);
+        let gitdirs = self.repair_gitdir_links(Path::new(&repo.path), &worktrees)?;
+        if gitdirs > 0 {
+            repairs.push(format!("{} git link(s)", gitdirs));
+        }
 
-            if !path_exists || !git_registered {
-                let reason = if !path_exists && !git_registered {
-                    "path missing and not in Git"
-                } else if !path_exists {
-                    "path missing"
-                } else {
-                    "not in Git"
-                };
+        let mut symlinks = 0;
+        for wt in &worktrees {
+            symlinks += self
+                .repair_symlinks(&repo.name, Path::new(&wt.path))
+                .await?;
+        }
+        if symlinks > 0 {
+            repairs.push(format!("{} symlink(s)", symlinks));
+        }
 
-                // Deactivate the database entry to maintain consistency
+        Ok(repairs)
+    }
+
+    /// Rewrite the trunk and worktree paths under `remap.from`; returns how
+    /// many changed
+    async fn remap_paths(&self, repo: &mut Repository, remap: &PathRemap) -> Result<usize> {
+        let mut remapped = 0;
+        if let Some(new_path) = remap.apply(Path::new(&repo.path)) {
+            let new_path = new_path.to_string_lossy().to_string();
+            self.db
+                .update_repository_path(&repo.name, &new_path)
+                .await?;
+            repo.path = new_path;
+            remapped += 1;
+        }
+
+        for wt in self.db.list_worktrees(Some(&repo.name)).await? {
+            if !wt.is_local() {
+                continue;
+            }
+            if let Some(new_path) = remap.apply(Path::new(&wt.path)) {
                 self.db
-                    .deactivate_worktree(&repo_name, &worktree.worktree_name)
-                    .await
-                    .context(format!(
-                        "Failed to deactivate worktree: {}",
-                        worktree.worktree_name
-                    ))?;
+                    .update_worktree_path(
+                        &repo.name,
+                        &wt.worktree_name,
+                        &new_path.to_string_lossy(),
+                    )
+                    .await?;
+                remapped += 1;
+            }
+        }
+        Ok(remapped)
+    }
 
-                println!(
-                    "   {} Deactivated database entry: {} ({})",
-                    "🗑️".bright_red(),
-                    worktree.worktree_name.bright_yellow(),
-                    reason.bright_black()
-                );
-                cleaned_count += 1;
+    /// `git worktree repair` from trunk with every worktree's path, fixing
+    /// the links in both directions; returns how many git fixed
+    fn repair_gitdir_links(&self, trunk: &Path, worktrees: &[Worktree]) -> Result<usize> {
+        if !trunk.exists() || capabilities::known_unsupported(|report| &report.worktree_repair) {
+            return Ok(0);
+        }
+
+        let mut args = vec!["worktree", "repair"];
+        args.extend(worktrees.iter().map(|wt| wt.path.as_str()));
+        let output = self.git.run_git(trunk, &args)?;
+        if !output.success {
+            return Err(anyhow::anyhow!(tr!(
+                "git worktree repair failed: {}",
+                output.stderr.trim()
+            )));
+        }
+        Ok(output
+            .stderr
+            .lines()
+            .chain(output.stdout.lines())
+            .filter(|line| line.starts_with("repair:"))
+            .count())
+    }
+
+    /// Replace `symlink_files` links left pointing at an old location and
+    /// create missing ones; returns how many were fixed
+    async fn repair_symlinks(&self, repo_name: &str, worktree_path: &Path) -> Result<usize> {
+        let health = self.symlink_health(repo_name, worktree_path);
+        let mut fixed = 0;
+        for link in &health {
+            match link.state {
+                SymlinkState::Broken => {
+                    async_fs::remove_file(worktree_path.join(&link.file)).await?;
+                    fixed += 1;
+                }
+                SymlinkState::Missing => fixed += 1,
+                SymlinkState::Linked | SymlinkState::NotALink => {}
             }
         }
+        if fixed > 0 {
+            self.create_symlinks(repo_name, worktree_path).await?;
+        }
+        Ok(fixed)
+    }
 
-        if cleaned_count > 0 {
-            println!(
-                "{} Cleaned {} stale database entries",
-                "✅".bright_green(),
-                cleaned_count
-            );
-        } else {
-            println!("{} No stale database entries found", "ℹ️".bright_blue());
+    /// Validate and repair repository path if it's stale
+    /// Searches through IMI_SYSTEM_PATHS to find the actual location
+    /// Also repairs all worktree paths when the repository is moved
+    async fn validate_and_repair_repository_path(&self, repo: &mut Repository) -> Result<bool> {
+        use std::path::PathBuf;
+
+        let stored_path = PathBuf::from(&repo.path);
+
+        // If path exists, no repair needed for repository
+        // But we still need to check if worktrees need repair
+        if stored_path.exists() {
+            // Check and repair worktree paths even if repository path is ok
+            let worktrees_repaired = self.repair_worktree_paths(&repo.name).await?;
+            return Ok(worktrees_repaired > 0);
         }
 
-        // PHASE 3: Orphaned Directory Cleanup
-        // Detect and remove directories that:
-        // - Match worktree naming patterns (feat-, fix-, aiops-, devops-, review-)
-        // - Are NOT registered in Git as worktrees
-        // - Are NOT valid Git repositories themselves
-        //
-        // These are "orphaned directories" - leftover filesystem cruft from failed operations
-        // or manual deletions where the Git reference was already cleaned but directory remains
-        //
-        // Safety: Requires confirmation unless --force flag is used
-        //         Respects --dry-run to preview without deleting
-        println!(
-            "{} Phase 3: Detecting orphaned worktree directories...",
-            "📦".bright_blue()
+        // Path doesn't exist - search for actual location
+        eprintln!(
+            "{}",
+            tr!(
+                "⚠️  Repository '{}' path is stale: {}",
+                repo.name,
+                stored_path.display()
+            )
         );
-        self.prune_orphaned_directories(&git_repo, dry_run, force)
-            .await
-            .context("Failed to prune orphaned directories")?;
+        eprintln!("{}", tr!("   Searching for actual location..."));
 
-        println!(
-            "{} Prune operation completed successfully",
-            "✅".bright_green().bold()
-        );
-        Ok(())
+        // Extract the repository name from the stored path
+        // Path format: /path/to/system-root/repo-name/trunk-main
+        let repo_name_from_path = if stored_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .map(|s| s.starts_with("trunk-"))
+            .unwrap_or(false)
+        {
+            // Path ends with trunk-* - repo name is parent
+            stored_path
+                .parent()
+                .and_then(|p| p.file_name())
+                .and_then(|n| n.to_str())
+        } else {
+            // Use the registered name
+            Some(repo.name.as_str())
+        };
+
+        if let Some(repo_name) = repo_name_from_path {
+            // Search through all system roots
+            for system_root in &self.config.system_roots {
+                // Try: system_root/repo_name/trunk-main
+                let candidate = system_root
+                    .join(repo_name)
+                    .join(format!("trunk-{}", repo.default_branch));
+
+                if candidate.exists() {
+                    eprintln!(
+                        "{}",
+                        tr!("   ✓ Found repository at: {}", candidate.display())
+                    );
+
+                    // Update database with correct path
+                    let new_path = candidate.to_string_lossy().to_string();
+                    self.db
+                        .update_repository_path(&repo.name, &new_path)
+                        .await?;
+
+                    // Update the in-memory struct
+                    repo.path = new_path;
+
+                    eprintln!("{}", tr!("   ✓ Database updated with corrected path"));
+
+                    // Repair all worktree paths for this repository
+                    let worktrees_repaired = self.repair_worktree_paths(&repo.name).await?;
+                    if worktrees_repaired > 0 {
+                        eprintln!(
+                            "{}",
+                            tr!("   ✓ Repaired {} worktree path(s)", worktrees_repaired)
+                        );
+                    }
+
+                    return Ok(true);
+                }
+            }
+        }
+
+        // Could not find the repository anywhere
+        Err(anyhow::anyhow!(tr!(
+            "Repository '{}' not found at stored path '{}' or any IMI_SYSTEM_PATHS location.\n\
+             Please verify the repository exists or run 'imi init' to re-register it.",
+            repo.name,
+            stored_path.display()
+        )))
     }
 
-    /// Detect and remove orphaned worktree directories
-    async fn prune_orphaned_directories(
-        &self,
-        git_repo: &git2::Repository,
-        dry_run: bool,
-        force: bool,
-    ) -> Result<()> {
-        // Get the parent directory where worktrees live
-        // git_repo.path() returns path to .git directory
-        // We want the parent of the trunk directory (where worktrees are siblings to trunk)
-        let worktree_root = git_repo
-            .path()
-            .parent() // trunk-main/
-            .and_then(|p| p.parent()) // parent containing trunk-main and worktrees
-            .context("Failed to determine worktree root directory")?;
+    /// Repair all worktree paths for a repository
+    /// Returns the number of worktrees that were repaired
+    async fn repair_worktree_paths(&self, repo_name: &str) -> Result<usize> {
+        use std::path::PathBuf;
 
-        // Get list of currently registered worktrees from git
-        let registered_worktrees: Vec<String> = git_repo
-            .worktrees()?
-            .iter()
-            .flatten()
-            .map(|s| s.to_string())
-            .collect();
+        let worktrees = self.db.list_worktrees(Some(repo_name)).await?;
+        let mut repaired_count = 0;
+
+        for worktree in worktrees {
+            let stored_path = PathBuf::from(&worktree.path);
+
+            // If worktree path exists, skip
+            if stored_path.exists() {
+                continue;
+            }
+
+            // Worktree path doesn't exist - try to find it
+            // First, get the current (repaired) repository path
+            let repo = self.db.get_repository(repo_name).await?;
+            if let Some(repo) = repo {
+                let repo_path = PathBuf::from(&repo.path);
+                // Get the parent directory (IMI_PATH) which contains all worktrees
+                if let Some(imi_path) = repo_path.parent() {
+                    // Try to find the worktree at the expected location
+                    let candidate = self
+                        .worktree_root(repo_name, imi_path)
+                        .join(&worktree.worktree_name);
+
+                    if candidate.exists() {
+                        // Found the worktree, update its path
+                        let new_path = candidate.to_string_lossy().to_string();
+                        self.db
+                            .update_worktree_path(repo_name, &worktree.worktree_name, &new_path)
+                            .await?;
+                        // Point git's own admin files at the new location too
+                        // where this git has `worktree repair`
+                        if !capabilities::known_unsupported(|report| &report.worktree_repair) {
+                            let _ = self.git.execute_git_command(
+                                &repo_path,
+                                &["worktree", "repair", &new_path],
+                            );
+                        }
+                        repaired_count += 1;
+                        eprintln!(
+                            "{}",
+                            tr!(
+                                "   ✓ Repaired worktree '{}' path: {}",
+                                worktree.worktree_name,
+                                new_path
+                            )
+                        );
+                    }
+                }
+            }
+        }
 
-        // Scan parent directory for potential orphaned directories
-        let mut orphaned_dirs = Vec::new();
-        let mut entries = async_fs::read_dir(worktree_root).await?;
+        Ok(repaired_count)
+    }
 
-        while let Some(entry) = entries.next_entry().await? {
-            let path = entry.path();
+    /// Move worktrees to where `[worktree_layout]` puts them now, with
+    /// `git worktree move` so git's records follow (`imi mv --relayout`).
+    /// Nothing moves when any destination is taken.
+    pub async fn relayout_worktrees(
+        &self,
+        repo: Option<&str>,
+        dry_run: bool,
+    ) -> Result<Vec<Relocation>> {
+        let repositories =
+            match repo {
+                Some(name) => vec![self.db.get_repository(name).await?.ok_or_else(|| {
+                    anyhow::anyhow!(tr!("Repository '{}' is not registered", name))
+                })?],
+                None => self.db.list_repositories().await?,
+            };
 
-            // Skip if not a directory
-            if !path.is_dir() {
-                continue;
+        let mut moves = Vec::new();
+        for repository in &repositories {
+            let trunk_path = PathBuf::from(&repository.path);
+            let root = self.worktree_root(&repository.name, &self.detect_imi_path(&trunk_path)?);
+            for wt in self.db.list_worktrees(Some(&repository.name)).await? {
+                let from = PathBuf::from(&wt.path);
+                let to = root.join(&wt.name);
+                if from == to || from == trunk_path || !wt.is_local() || !from.exists() {
+                    continue;
+                }
+                if to.exists() {
+                    return Err(anyhow::anyhow!(tr!(
+                        "Can't move {} to {}: the destination already exists",
+                        from.display(),
+                        to.display()
+                    )));
+                }
+                moves.push(Relocation {
+                    repo_name: repository.name.clone(),
+                    worktree_name: wt.name,
+                    trunk_path: trunk_path.clone(),
+                    from,
+                    to,
+                });
             }
+        }
+        if dry_run {
+            return Ok(moves);
+        }
 
-            let dir_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        for relocation in &moves {
+            if let Some(parent) = relocation.to.parent() {
+                async_fs::create_dir_all(parent).await?;
+            }
+            let from = relocation.from.to_string_lossy();
+            let to = relocation.to.to_string_lossy();
+            self.git
+                .execute_git_command(&relocation.trunk_path, &["worktree", "move", &from, &to])
+                .with_context(|| tr!("Failed to move {}", relocation.worktree_name))?;
+            self.db
+                .update_worktree_path(&relocation.repo_name, &relocation.worktree_name, &to)
+                .await?;
+        }
 
-            // Skip hidden directories and trunk
-            if dir_name.starts_with('.') || dir_name.starts_with("trunk-") {
-                continue;
+        // Worktree directories left empty by moving back beside trunk
+        for relocation in &moves {
+            if let Some(parent) = relocation.from.parent() {
+                if Some(parent) != relocation.trunk_path.parent() {
+                    let _ = std::fs::remove_dir(parent);
+                }
             }
+        }
+        Ok(moves)
+    }
 
-            // Check if matches worktree naming pattern
-            let matches_pattern = dir_name.starts_with("feat-")
-                || dir_name.starts_with("fix-")
-                || dir_name.starts_with("aiops-")
-                || dir_name.starts_with("devops-")
-                || dir_name.starts_with("review-");
+    /// A registered repository with its tags, clone mode, worktree count and
+    /// whatever no longer matches the disk (`imi repo show`). Tags, clone
+    /// mode and archive state read as empty on databases without their
+    /// migrations.
+    pub async fn repository_detail(&self, name: &str) -> Result<RepositoryDetail> {
+        let repository = self
+            .db
+            .get_repository(name)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!(tr!("Repository '{}' is not registered", name)))?;
+        let tags = self
+            .db
+            .list_repository_tags()
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(project_id, _)| *project_id == repository.id)
+            .map(|(_, tag)| tag)
+            .collect();
+        let clone_mode = self
+            .db
+            .get_repository_clone_mode(&repository.id)
+            .await
+            .unwrap_or_default();
+        let archived_at = self
+            .db
+            .get_repository_archived_at(&repository.id)
+            .await
+            .unwrap_or_default();
+        let worktrees = self.db.list_worktrees(Some(&repository.name)).await?.len();
+        let problems = self.repository_problems(&repository);
+
+        Ok(RepositoryDetail {
+            repository,
+            tags,
+            clone_mode,
+            archived_at,
+            worktrees,
+            problems,
+        })
+    }
 
-            if !matches_pattern {
-                continue;
-            }
+    /// Recorded metadata the trunk on disk disagrees with
+    fn repository_problems(&self, repository: &Repository) -> Vec<String> {
+        let git_repo = match self.git.open_repository(Path::new(&repository.path)) {
+            Ok(git_repo) => git_repo,
+            Err(_) => return vec![format!("path {} is not a git repository", repository.path)],
+        };
 
-            // Check if registered as a worktree
-            let is_registered = registered_worktrees.iter().any(|wt| wt == dir_name);
+        let mut problems = Vec::new();
+        if !self
+            .git
+            .branch_exists(&git_repo, &repository.default_branch)
+        {
+            problems.push(tr!(
+                "default branch '{}' doesn't exist",
+                repository.default_branch
+            ));
+        }
+        let remote_name = &self.config.git_settings.remote_name;
+        let actual_url = git_repo
+            .find_remote(remote_name)
+            .ok()
+            .and_then(|remote| remote.url().map(str::to_string));
+        match actual_url {
+            Some(url) if url != repository.remote_url => problems.push(tr!(
+                "remote '{}' of the trunk is {}, not {}",
+                remote_name,
+                url,
+                repository.remote_url
+            )),
+            None if !repository.remote_url.is_empty() => {
+                problems.push(tr!("the trunk has no remote '{}'", remote_name))
+            }
+            _ => {}
+        }
+        problems
+    }
 
-            if is_registered {
-                continue;
+    /// Change a repository's recorded remote, default branch or trunk path
+    /// (`imi repo set`). The path must be a git repository and the branch
+    /// must exist there. A new default branch moves the path to a
+    /// `trunk-<branch>` sibling when there is one, a new remote URL is set
+    /// on the trunk's remote too, and worktree paths are repaired after a
+    /// move.
+    pub async fn edit_repository(
+        &self,
+        name: &str,
+        edit: RepositoryEdit,
+    ) -> Result<RepositoryEditOutcome> {
+        if edit.remote_url.is_none() && edit.default_branch.is_none() && edit.path.is_none() {
+            anyhow::bail!(tr!(
+                "Nothing to change; pass --remote-url, --default-branch or --path"
+            ));
+        }
+        let mut repository = self
+            .db
+            .get_repository(name)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!(tr!("Repository '{}' is not registered", name)))?;
+        let mut changes = Vec::new();
+
+        // The path first: the branch is looked up in the repository there
+        if let Some(path) = &edit.path {
+            let path = path
+                .canonicalize()
+                .context(tr!("Path {} does not exist", path.display()))?;
+            self.git
+                .open_repository(&path)
+                .context(tr!("{} is not a git repository", path.display()))?;
+            let path = path.to_string_lossy().to_string();
+            if path != repository.path {
+                changes.push(format!("path: {} -> {}", repository.path, path));
+                repository.path = path;
             }
+        }
 
-            // Check if it's a valid git repository
-            let is_valid_repo = git2::Repository::open(&path).is_ok();
+        if let Some(branch) = edit
+            .default_branch
+            .filter(|branch| *branch != repository.default_branch)
+        {
+            let git_repo = self
+                .git
+                .open_repository(Path::new(&repository.path))
+                .context(tr!(
+                    "{} is not a git repository; fix it with --path",
+                    repository.path
+                ))?;
+            if !self.git.branch_exists(&git_repo, &branch) {
+                anyhow::bail!(tr!(
+                    "Branch '{}' doesn't exist in {}, locally or on origin",
+                    branch,
+                    repository.path
+                ));
+            }
 
-            if is_valid_repo {
-                // Valid repo but not registered - could be manually created
-                continue;
+            // Trunk detection: trunk-<old> becomes trunk-<new> when it exists
+            let current = PathBuf::from(&repository.path);
+            let old_trunk = format!("trunk-{}", repository.default_branch);
+            let new_trunk = current.with_file_name(format!("trunk-{}", branch));
+            if edit.path.is_none()
+                && current.file_name().is_some_and(|n| *n == *old_trunk)
+                && new_trunk.is_dir()
+            {
+                let path = new_trunk.to_string_lossy().to_string();
+                changes.push(format!("path: {} -> {}", repository.path, path));
+                repository.path = path;
             }
 
-            // This is an orphaned directory - collect info
-            let size = self.get_directory_size(&path).await?;
-            orphaned_dirs.push((path.clone(), dir_name.to_string(), size));
+            changes.push(format!(
+                "default branch: {} -> {}",
+                repository.default_branch, branch
+            ));
+            repository.default_branch = branch;
         }
 
-        if orphaned_dirs.is_empty() {
-            return Ok(());
+        if let Some(url) = edit
+            .remote_url
+            .map(|url| url.trim().to_string())
+            .filter(|url| *url != repository.remote_url)
+        {
+            if url.is_empty() || url.contains(char::is_whitespace) {
+                anyhow::bail!(tr!("Invalid remote URL '{}'", url));
+            }
+            changes.push(format!("remote URL: {} -> {}", repository.remote_url, url));
+            let remote_name = &self.config.git_settings.remote_name;
+            if let Ok(git_repo) = self.git.open_repository(Path::new(&repository.path)) {
+                let current = git_repo
+                    .find_remote(remote_name)
+                    .ok()
+                    .and_then(|remote| remote.url().map(str::to_string));
+                if current.is_some_and(|current| current != url) {
+                    git_repo
+                        .remote_set_url(remote_name, &url)
+                        .context(tr!("Failed to set the URL of remote '{}'", remote_name))?;
+                    changes.push(format!(
+                        "trunk remote '{}' now points to {}",
+                        remote_name, url
+                    ));
+                }
+            }
+            repository.remote_url = url;
         }
 
-        // Display orphaned directories
-        println!(
-            "\n{} Found {} orphaned worktree directories:",
-            "📦".bright_yellow(),
-            orphaned_dirs.len()
+        if changes.is_empty() {
+            return Ok(RepositoryEditOutcome {
+                repository,
+                changes,
+                worktrees_repaired: 0,
+            });
+        }
+
+        self.db.update_repository(&repository).await?;
+        let worktrees_repaired = self.repair_worktree_paths(&repository.name).await?;
+        Ok(RepositoryEditOutcome {
+            repository,
+            changes,
+            worktrees_repaired,
+        })
+    }
+
+    /// Merge a worktree into trunk-main and close it
+    /// Merge a worktree's branch and close the worktree. `strategy` overrides
+    /// the repository's merge policy.
+    /// When `resolve_conflicts` is set, a conflicted merge opens the
+    /// mergetool or editor on the conflicting files and carries on once
+    /// they are resolved.
+    pub async fn merge_worktree(
+        &self,
+        name: &str,
+        repo: Option<&str>,
+        strategy: Option<MergeStrategy>,
+        resolve_conflicts: bool,
+    ) -> Result<()> {
+        let repo_name = self.resolve_worktree_repo(name, repo).await?;
+        let actual_worktree_name = self.find_actual_worktree_name(name, &repo_name).await?;
+
+        self.step(
+            "start",
+            0,
+            "🔀".bright_cyan(),
+            tr!("Merging worktree: {}", actual_worktree_name.bright_yellow()),
         );
 
-        let mut total_size = 0u64;
-        for (_, name, size) in &orphaned_dirs {
-            println!(
-                "  {} {} ({})",
-                "•".bright_yellow(),
-                name.bright_white(),
-                self.format_size(*size).bright_cyan()
-            );
-            total_size += size;
+        let strategy =
+            strategy.unwrap_or_else(|| self.config.merge_policy.strategy_for(&repo_name));
+        if strategy != MergeStrategy::Direct {
+            return self
+                .merge_on_github(name, repo, &repo_name, &actual_worktree_name, strategy)
+                .await;
         }
 
-        println!(
-            "\n{} Total size: {}",
-            "💾".bright_cyan(),
-            self.format_size(total_size).bright_yellow()
+        let worktree_info = self
+            .db
+            .get_worktree(&repo_name, &actual_worktree_name)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!(tr!("Worktree '{}' not found", actual_worktree_name)))?;
+
+        let branch_name = worktree_info.branch_name.clone();
+        let trunk_path = self.get_trunk_worktree(Some(&repo_name)).await?;
+
+        self.step(
+            "prepare",
+            5,
+            "🌳".bright_green(),
+            tr!("Switching to trunk: {}", trunk_path.display()),
         );
 
-        if dry_run {
-            println!("\n{} Dry run - no directories removed", "ℹ️".bright_blue());
-            return Ok(());
-        }
+        let trunk_repo = self.git.find_repository(Some(&trunk_path))?;
+        self.step(
+            "fetch",
+            10,
+            "⬇️".bright_blue(),
+            tr!("Fetching latest changes"),
+        );
+        self.git.fetch_all(&trunk_repo)?;
 
-        // Ask for confirmation unless force flag is set
-        let should_remove = if force {
-            true
-        } else {
-            Confirm::new()
-                .with_prompt("Remove these orphaned directories?")
-                .default(false)
-                .interact()?
-        };
+        let default_branch = self.config.git_settings.default_branch.clone();
+        let current_branch = self.git.get_current_branch(&trunk_path)?;
 
-        if !should_remove {
-            println!("{} Skipping removal", "⏭️".bright_yellow());
-            return Ok(());
+        if current_branch != default_branch {
+            return Err(anyhow::anyhow!(tr!(
+                "Trunk is on branch '{}' instead of '{}'. Please checkout '{}' first.",
+                current_branch,
+                default_branch,
+                default_branch
+            )));
         }
-
-        // Remove orphaned directories
-        let mut removed_count = 0;
-        for (path, name, _) in orphaned_dirs {
-            match async_fs::remove_dir_all(&path).await {
-                Ok(_) => {
-                    println!("🗑️ Removed: {}", name.bright_green());
-                    removed_count += 1;
-                }
-                Err(e) => {
-                    println!("❌ Failed to remove {}: {}", name.bright_red(), e);
-                }
+        if self.config.trunk_protection.enforce {
+            let violation =
+                trunk_protection::inspect(&self.git, &repo_name, &trunk_path, &default_branch)?;
+            if let Some(violation) = violation.filter(TrunkViolation::is_dirty) {
+                violation.print();
+                return Err(anyhow::anyhow!(tr!(
+                    "Refusing to merge into a dirty trunk ([trunk_protection] enforce = true)"
+                )));
             }
         }
 
-        if removed_count > 0 {
-            println!(
-                "\n{} Removed {} orphaned directories",
-                "✅".bright_green(),
-                removed_count
+        let worktree_path = PathBuf::from(&worktree_info.path);
+        if worktree_path.exists() {
+            self.step(
+                "prepare",
+                30,
+                "🧹".bright_yellow(),
+                tr!("Discarding any unstaged changes in worktree"),
             );
-        }
 
-        Ok(())
-    }
+            // Reset worktree to HEAD, discarding all unstaged changes
+            self.git
+                .run_git(&worktree_path, &["reset", "--hard", "HEAD"])
+                .context(tr!("Failed to reset worktree to HEAD"))?;
+        }
 
-    /// Calculate directory size recursively
-    fn get_directory_size<'a>(
-        &'a self,
-        path: &'a Path,
-    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<u64>> + 'a>> {
-        Box::pin(async move {
-            let mut total_size = 0u64;
-            let mut entries = async_fs::read_dir(path).await?;
+        self.step(
+            "merge",
+            40,
+            "🔀".bright_magenta(),
+            tr!(
+                "Merging branch '{}' into '{}'",
+                branch_name.bright_yellow(),
+                default_branch.bright_green()
+            ),
+        );
 
-            while let Some(entry) = entries.next_entry().await? {
-                let entry_path = entry.path();
-                let metadata = async_fs::metadata(&entry_path).await?;
+        let mut journal_entry = JournalEntry::new(
+            OperationKind::Merge,
+            &repo_name,
+            &actual_worktree_name,
+            &branch_name,
+            &worktree_info.worktree_type,
+            &worktree_path,
+        );
+        journal_entry.trunk_head_before =
+            Some(trunk_repo.head()?.peel_to_commit()?.id().to_string());
+        journal_entry.trunk_branch = Some(default_branch.clone());
+        let mut op = Journal::open_default()?.begin(journal_entry)?;
 
-                if metadata.is_file() {
-                    total_size += metadata.len();
-                } else if metadata.is_dir() {
-                    total_size += self.get_directory_size(&entry_path).await?;
+        if let Err(e) = self
+            .git
+            .merge_branch(&trunk_repo, &branch_name, &default_branch)
+        {
+            let conflicted = matches!(
+                e.downcast_ref::<ImiError>(),
+                Some(ImiError::MergeConflicts { .. })
+            );
+            // Close the journal entry on every way out, aborts included, so
+            // 'imi resume' never pushes a merge that didn't happen
+            let resolved = if conflicted && resolve_conflicts {
+                self.resolve_merge_conflicts(&trunk_path)
+            } else {
+                Ok(false)
+            };
+            let resolved = match resolved {
+                Ok(resolved) => resolved,
+                Err(resolve_error) => {
+                    op.finish()?;
+                    return Err(resolve_error);
                 }
+            };
+            if !resolved {
+                op.finish()?;
+                return Err(e.context(tr!("Failed to merge branch into trunk")));
             }
-
-            Ok(total_size)
-        })
-    }
-
-    /// Format byte size to human-readable string
-    fn format_size(&self, bytes: u64) -> String {
-        const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
-        let mut size = bytes as f64;
-        let mut unit_idx = 0;
-
-        while size >= 1024.0 && unit_idx < UNITS.len() - 1 {
-            size /= 1024.0;
-            unit_idx += 1;
+        }
+        op.step(steps::MERGED)?;
+        if cancel::requested() {
+            // Journaled: the process is gone by the time anyone resumes
+            eprintln!(
+                "{}",
+                tr!(
+                    "{} Stopped after merging into '{}'; 'imi resume' pushes and cleans up, 'imi rollback' undoes the merge",
+                    "⏹️".bright_yellow(),
+                    default_branch
+                )
+            );
+            return Err(ImiError::Cancelled.into());
         }
 
-        format!("{:.2} {}", size, UNITS[unit_idx])
-    }
-
-    /// Fuzzy navigate to a worktree or repository
-    pub async fn fuzzy_navigate(
-        &self,
-        query: Option<&str>,
-        repo: Option<&str>,
-        worktrees_only: bool,
-        include_inactive: bool,
-    ) -> Result<PathBuf> {
-        let matcher = FuzzyMatcher::new(self.db.clone());
-
-        let selected = if let Some(query_str) = query {
-            // Perform fuzzy search
-            let results = matcher
-                .search(query_str, repo, worktrees_only, include_inactive)
-                .await?;
+        self.step(
+            "push",
+            60,
+            "⬆️".bright_cyan(),
+            tr!("Pushing merged changes to remote"),
+        );
 
-            if results.is_empty() {
-                return Err(anyhow::anyhow!(
-                    "No worktrees or repositories found matching '{}'",
-                    query_str
-                ));
+        match self.git.push_to_remote(&trunk_repo, &default_branch) {
+            Ok(_) => {
+                op.step(steps::PUSHED)?;
+                self.report(tr!("{} Changes pushed to remote", "✅".bright_green()))
             }
-
-            // Auto-select top result if score is very high (exact/near match)
-            if results[0].score() >= 0.8 {
-                eprintln!(
-                    "{} Selected: {}",
-                    "✅".bright_green(),
-                    results[0].display_name()
-                );
-                results[0].clone()
-            } else if results.len() == 1 {
-                // Only one result, auto-select it
+            Err(e) => {
                 eprintln!(
-                    "{} Selected: {}",
-                    "✅".bright_green(),
-                    results[0].display_name()
+                    "{}",
+                    tr!(
+                        "{} Warning: Failed to push to remote: {}",
+                        "⚠️".bright_yellow(),
+                        e
+                    )
                 );
-                results[0].clone()
-            } else {
-                // Multiple ambiguous results - show picker
                 eprintln!(
-                    "{} Multiple matches found for '{}':",
-                    "🔍".bright_yellow(),
-                    query_str
+                    "{}",
+                    tr!(
+                        "   You may need to push manually: cd {} && git push",
+                        trunk_path.display()
+                    )
                 );
+            }
+        }
 
-                use dialoguer::{theme::ColorfulTheme, Select};
+        self.step(
+            "close",
+            80,
+            "🧹".bright_cyan(),
+            tr!("Closing worktree: {}", actual_worktree_name),
+        );
+        self.close_worktree(name, repo).await?;
+        op.step(steps::CLOSED)?;
+
+        self.delete_merged_branch(&trunk_repo, &branch_name).await?;
+        op.finish()?;
+        self.record_merge(&repo_name, &actual_worktree_name, &branch_name, None);
+        self.refresh_trunk_lock(&repo_name, &trunk_path, &default_branch);
+
+        self.report("");
+        self.step(
+            "done",
+            100,
+            "✅".bright_green().bold(),
+            tr!("Merge completed successfully!"),
+        );
+        self.report(tr!(
+            "{} Branch '{}' has been merged into '{}' and cleaned up",
+            "📝".bright_blue(),
+            branch_name.bright_yellow(),
+            default_branch.bright_green()
+        ));
+
+        Ok(())
+    }
+
+    /// Merge worktrees one after another, rebasing each onto the trunk the
+    /// previous ones updated. Stops at the first failure with the command
+    /// that resumes the train; returns the worktrees merged.
+    pub async fn merge_train(
+        &self,
+        names: &[String],
+        repo: Option<&str>,
+        resolve_conflicts: bool,
+    ) -> Result<Vec<String>> {
+        let train = LocalTrain {
+            manager: self,
+            repo,
+            resolve_conflicts,
+            total: names.len(),
+        };
+        run_train(&train, names).await
+    }
 
-                let display_items: Vec<String> = results
-                    .iter()
-                    .map(|target| {
-                        let icon = match target.worktree_type() {
-                            Some("feat") => "🚀",
-                            Some("pr") => "🔍",
-                            Some("fix") => "🔧",
-                            Some("aiops") => "🤖",
-                            Some("devops") => "⚙️",
-                            Some("trunk") => "🌳",
-                            _ => "📁",
-                        };
-                        format!(
-                            "{} {} ({}) - score: {:.2}",
-                            icon,
-                            target.display_name(),
-                            target.repo_name(),
-                            target.score()
-                        )
-                    })
-                    .collect();
+    /// Walk the user through a conflicted merge in trunk: open the
+    /// mergetool or editor until no conflicts remain, then commit the merge.
+    /// False when they leave it for later; aborting rolls trunk back.
+    fn resolve_merge_conflicts(&self, trunk_path: &Path) -> Result<bool> {
+        use dialoguer::{theme::ColorfulTheme, Select};
 
-                let selection = Select::with_theme(&ColorfulTheme::default())
-                    .with_prompt("Select a target")
-                    .items(&display_items)
-                    .default(0)
-                    .interact()?;
+        loop {
+            let files = self.git.conflicted_files(trunk_path)?;
+            if files.is_empty() {
+                self.git.conclude_merge(trunk_path)?;
+                println!(
+                    "{}",
+                    tr!(
+                        "{} Conflicts resolved; merge committed",
+                        "✅".bright_green()
+                    )
+                );
+                return Ok(true);
+            }
 
-                results[selection].clone()
+            println!(
+                "{}",
+                tr!(
+                    "{} Merge conflicts in {} file(s):",
+                    "⚔️".bright_red(),
+                    files.len()
+                )
+            );
+            for file in &files {
+                println!("   {}", file.bright_yellow());
             }
-        } else {
-            // No query - show interactive picker
-            if let Some(target) = matcher.interactive_select(repo, worktrees_only).await? {
-                target
-            } else {
-                return Err(anyhow::anyhow!("No worktrees or repositories available"));
+
+            let tool = self.git.merge_tool(trunk_path);
+            let open = match &tool {
+                Some(tool) => tr!("Open mergetool ({})", tool),
+                None => tr!(
+                    "Open {} on the conflicting files",
+                    conflict_editor(&self.config)
+                ),
+            };
+            let choice = Select::with_theme(&ColorfulTheme::default())
+                .with_prompt(tr!("How do you want to continue?"))
+                .items(&[
+                    open,
+                    tr!("Continue (I resolved and staged them myself)"),
+                    tr!("Abort the merge"),
+                    tr!("Leave the merge in progress"),
+                ])
+                .default(0)
+                .interact()?;
+
+            match choice {
+                0 => {
+                    let code = match tool {
+                        Some(_) => self
+                            .git
+                            .run_git_attached(trunk_path, &["mergetool", "--no-prompt"]),
+                        None => std::process::Command::new("sh")
+                            .arg("-c")
+                            .arg(format!("{} \"$@\"", conflict_editor(&self.config)))
+                            .arg("sh")
+                            .args(&files)
+                            .current_dir(trunk_path)
+                            .status()
+                            .map(|status| status.code().unwrap_or(-1))
+                            .map_err(anyhow::Error::from),
+                    }
+                    .context(tr!("Failed to start the conflict resolution tool"))?;
+                    if code != 0 {
+                        println!(
+                            "{}",
+                            tr!("{} The tool exited with {}", "⚠️".bright_yellow(), code)
+                        );
+                    }
+                    // Editors don't stage; files without markers count as resolved
+                    self.git.stage_resolved(trunk_path, &files)?;
+                }
+                1 => {}
+                2 => {
+                    self.git.abort_merge(trunk_path)?;
+                    return Err(anyhow::anyhow!(tr!(
+                        "Merge aborted; trunk is back where it started"
+                    )));
+                }
+                _ => return Ok(false),
             }
-        };
+        }
+    }
 
-        Ok(selected.path())
+    /// Roll back the merge a conflicted `imi merge` left in the trunk
+    /// worktree of `name`'s repository (or `repo`, or the current one)
+    pub async fn abort_merge(&self, name: Option<&str>, repo: Option<&str>) -> Result<PathBuf> {
+        let repo_name = match name {
+            Some(name) => Some(self.resolve_worktree_repo(name, repo).await?),
+            None => repo.map(str::to_string),
+        };
+        let trunk_path = self.get_trunk_worktree(repo_name.as_deref()).await?;
+        if !self.git.merge_in_progress(&trunk_path)? {
+            return Err(anyhow::anyhow!(tr!(
+                "No merge in progress in {}",
+                trunk_path.display()
+            )));
+        }
+        self.git.abort_merge(&trunk_path)?;
+
+        // The merge's journal entry, left open when it stopped on conflicts
+        let repo_name = self.resolve_repo_name(repo_name.as_deref()).await?;
+        let journal = Journal::open_default()?;
+        for entry in journal.list()? {
+            if entry.kind == OperationKind::Merge
+                && entry.repo_name == repo_name
+                && !entry.has_step(steps::MERGED)
+            {
+                journal.discard(&entry.id)?;
+            }
+        }
+        Ok(trunk_path)
     }
 
-    /// Detect IMI_PATH based on repository structure
-    /// If we're in a trunk directory (trunk-*), return its parent
-    /// Otherwise, return the repository root's parent
-    fn detect_imi_path(&self, repo_root: &Path) -> Result<PathBuf> {
-        // Check if the current repo_root is a trunk directory
-        if let Some(dir_name) = repo_root.file_name() {
-            if let Some(name) = dir_name.to_str() {
-                // Check if this is a trunk directory (pattern: trunk-*)
-                if name.starts_with("trunk-") {
-                    // This is a trunk directory, so its parent is the IMI_PATH
-                    if let Some(parent) = repo_root.parent() {
-                        return Ok(parent.to_path_buf());
-                    }
+    /// Merge the branch's pull request on GitHub, directly or through the
+    /// merge queue, then close the worktree. The worktree is kept when the
+    /// PR doesn't land.
+    async fn merge_on_github(
+        &self,
+        name: &str,
+        repo: Option<&str>,
+        repo_name: &str,
+        worktree_name: &str,
+        strategy: MergeStrategy,
+    ) -> Result<()> {
+        let worktree = self
+            .db
+            .get_worktree(repo_name, worktree_name)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!(tr!("Worktree '{}' not found", worktree_name)))?;
+        let repository = self
+            .db
+            .get_repository(repo_name)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!(tr!("Repository '{}' not found", repo_name)))?;
+        let branch_name = worktree.branch_name.clone();
+
+        // GitHub merges what it has, so local commits go up first
+        let worktree_path = PathBuf::from(&worktree.path);
+        if worktree_path.exists() {
+            self.step(
+                "push",
+                10,
+                "⬆️".bright_cyan(),
+                tr!("Pushing {}", branch_name),
+            );
+            let worktree_repo = self.git.find_repository(Some(&worktree_path))?;
+            self.git.push_to_remote(&worktree_repo, &branch_name)?;
+        }
+
+        let github = self.github()?;
+        let pr = github
+            .find_pull_request(&repository.remote_url, &branch_name)
+            .await?
+            .filter(|pr| pr.state != PullRequestState::Closed)
+            .ok_or_else(|| {
+                anyhow::anyhow!(tr!(
+                    "Branch '{}' has no open pull request; open one first (e.g. `gh pr create`)",
+                    branch_name
+                ))
+            })?;
+
+        if pr.state == PullRequestState::Open {
+            let remote_url = &repository.remote_url;
+            match strategy {
+                MergeStrategy::Queue => {
+                    self.wait_for_merge_queue(github.as_ref(), remote_url, pr.number)
+                        .await?
+                }
+                _ => {
+                    self.step(
+                        "merge",
+                        30,
+                        "🔀".bright_magenta(),
+                        tr!("Merging PR #{} on GitHub", pr.number),
+                    );
+                    github.merge_pull_request(remote_url, pr.number).await?;
                 }
             }
         }
+        self.db
+            .set_worktree_pull_request(
+                &worktree.id,
+                Some(pr.number),
+                Some(&pr.url),
+                Some(PullRequestState::Merged.as_str()),
+            )
+            .await?;
+        self.step(
+            "merge",
+            60,
+            "✅".bright_green(),
+            tr!("PR #{} merged", pr.number),
+        );
 
-        // Fall back to repository root's parent (original behavior)
-        let imi_path = repo_root.parent().unwrap_or(repo_root);
-        Ok(imi_path.to_path_buf())
+        self.step(
+            "close",
+            80,
+            "🧹".bright_cyan(),
+            tr!("Closing worktree: {}", worktree_name),
+        );
+        self.close_worktree(name, repo).await?;
+
+        let trunk_repo = self.open_git_repository(repo_name).await?;
+        if let Err(e) = self.git.fetch_all(&trunk_repo) {
+            eprintln!(
+                "{}",
+                tr!("{} Warning: Failed to fetch: {}", "⚠️".bright_yellow(), e)
+            );
+        }
+        self.delete_merged_branch(&trunk_repo, &branch_name).await?;
+        self.record_merge(repo_name, worktree_name, &branch_name, Some(pr.number));
+
+        self.report("");
+        self.step(
+            "done",
+            100,
+            "✅".bright_green().bold(),
+            tr!("Merge completed successfully!"),
+        );
+        Ok(())
     }
 
-    /// Ensure all work is happening inside the current entity's office path.
-    fn ensure_office_layout(&self, repo_name: &str, trunk_path: &Path) -> Result<()> {
-        let expected_container = self.config.get_repo_path(repo_name);
-        let actual_parent = trunk_path
-            .parent()
-            .ok_or_else(|| anyhow::anyhow!("Invalid trunk path: {}", trunk_path.display()))?;
+    fn record_merge(
+        &self,
+        repo_name: &str,
+        worktree_name: &str,
+        branch: &str,
+        pr_number: Option<i32>,
+    ) {
+        let mut event = HookEvent {
+            worktree_name: Some(worktree_name.to_string()),
+            branch: Some(branch.to_string()),
+            ..HookEvent::for_repo("merge_completed", repo_name, format!("Merged {}", branch))
+        };
+        if let Some(number) = pr_number {
+            event = event.var("IMI_PR_NUMBER", number.to_string());
+        }
+        self.record_event(event);
+    }
 
-        if actual_parent != expected_container.as_path() {
-            return Err(anyhow::anyhow!(
-                "Repository '{}' is outside the current entity office.\nExpected trunk under: {}\nActual trunk path: {}\nMigrate with 'imi init' from the repository to enforce office layout.",
-                repo_name,
-                expected_container.display(),
-                trunk_path.display()
-            ));
+    /// Put the PR in the merge queue (unless it already is) and poll until it
+    /// merges, drops out or `queue_timeout_secs` passes
+    async fn wait_for_merge_queue(
+        &self,
+        github: &dyn GitHubBackend,
+        remote_url: &str,
+        number: i32,
+    ) -> Result<()> {
+        let policy = &self.config.merge_policy;
+
+        if github.merge_queue_state(remote_url, number).await? == QueueState::Removed {
+            github.enqueue_pull_request(remote_url, number).await?;
+            self.step(
+                "queue",
+                30,
+                "📥".bright_cyan(),
+                tr!("Added PR #{} to the merge queue", number),
+            );
         }
 
-        let dir_name = trunk_path
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or_default();
+        let started = std::time::Instant::now();
+        let timeout = std::time::Duration::from_secs(policy.queue_timeout_secs);
+        loop {
+            match github.merge_queue_state(remote_url, number).await? {
+                QueueState::Merged => return Ok(()),
+                QueueState::Removed => {
+                    return Err(anyhow::anyhow!(tr!(
+                        "PR #{} left the merge queue without merging (failed checks or conflicts?); the worktree was kept",
+                        number
+                    )));
+                }
+                QueueState::Queued { .. } if started.elapsed() >= timeout => {
+                    return Err(anyhow::anyhow!(tr!(
+                        "PR #{} is still queued after {}s; it stays in the queue, run `imi merge` again to finish",
+                        number,
+                        policy.queue_timeout_secs
+                    )));
+                }
+                QueueState::Queued { position, state } => {
+                    let position = position
+                        .map(|p| format!(", position {}", p))
+                        .unwrap_or_default();
+                    let status = format!("{}{}", state.to_lowercase().replace('_', " "), position);
+                    match progress::enabled() {
+                        true => progress::emit("queue", None, &status),
+                        false => {
+                            self.report(format_args!("   {} {}", "⏳".bright_yellow(), status))
+                        }
+                    }
+                    tokio::time::sleep(std::time::Duration::from_secs(policy.queue_poll_secs))
+                        .await;
+                }
+            }
+        }
+    }
 
-        if !dir_name.starts_with("trunk-") {
-            return Err(anyhow::anyhow!(
-                "Invalid trunk path '{}'. Expected directory name to start with 'trunk-'.",
-                trunk_path.display()
-            ));
+    /// Delete a merged branch locally and (best effort) on the remote
+    async fn delete_merged_branch(
+        &self,
+        trunk_repo: &git2::Repository,
+        branch_name: &str,
+    ) -> Result<()> {
+        self.step(
+            "cleanup",
+            90,
+            "🗑️".bright_red(),
+            tr!("Deleting merged branch: {}", branch_name),
+        );
+        self.git.delete_local_branch(trunk_repo, branch_name)?;
+
+        match self.git.delete_remote_branch(trunk_repo, branch_name).await {
+            Ok(_) => self.report(tr!("{} Remote branch deleted", "✅".bright_green())),
+            Err(e) => {
+                eprintln!(
+                    "{}",
+                    tr!(
+                        "{} Warning: Could not delete remote branch '{}': {}",
+                        "⚠️".bright_yellow(),
+                        branch_name,
+                        e
+                    )
+                );
+                eprintln!(
+                    "{}",
+                    tr!("   (This is normal if the branch was already deleted or never pushed)")
+                );
+            }
         }
 
         Ok(())
     }
 
-    /// Repair all repository paths in the database
-    /// Scans all registered repositories and fixes stale paths
-    pub async fn repair_all_repository_paths(&self) -> Result<()> {
-        let repositories = self.db.list_repositories().await?;
+    /// Fetch the full history of a repository cloned shallow or partial and
+    /// mark it as a full clone; returns the repository name and what was
+    /// converted
+    pub async fn unshallow_repository(
+        &self,
+        repo: Option<&str>,
+    ) -> Result<(String, Vec<&'static str>)> {
+        let repo_name = self.resolve_repo_name(repo).await?;
+        let mut repository =
+            self.db.get_repository(&repo_name).await?.ok_or_else(|| {
+                anyhow::anyhow!(tr!("Repository '{}' is not registered", repo_name))
+            })?;
+        self.validate_and_repair_repository_path(&mut repository)
+            .await?;
 
-        if repositories.is_empty() {
-            println!("No repositories registered.");
-            return Ok(());
+        self.report(tr!(
+            "{} Fetching the full history of {}",
+            "⬇️".bright_blue(),
+            repo_name.bright_cyan()
+        ));
+        let converted = self.git.unshallow(Path::new(&repository.path))?;
+        if self
+            .db
+            .get_repository_clone_mode(&repository.id)
+            .await?
+            .is_some()
+        {
+            self.db
+                .set_repository_clone_mode(&repository.id, None)
+                .await?;
         }
 
-        let total_count = repositories.len();
-        println!("Checking {} registered repositories...", total_count);
-        println!();
+        Ok((repo_name, converted))
+    }
 
-        let mut repaired_count = 0;
-        let mut error_count = 0;
+    /// Finish the remaining steps of an interrupted add or merge
+    pub async fn resume_operation(&self, entry: JournalEntry) -> Result<()> {
+        let journal = Journal::open_default()?;
+
+        match entry.kind {
+            OperationKind::Add => {
+                if !entry.has_step(steps::GIT_WORKTREE) {
+                    // Nothing happened yet: simply run the whole add again
+                    journal.discard(&entry.id)?;
+                    self.create_worktree_internal(
+                        Some(&entry.repo_name),
+                        &entry.worktree_name,
+                        &entry.branch_name,
+                        &entry.worktree_type,
+                        entry.base_branch.as_deref(),
+                    )
+                    .await?;
+                    return Ok(());
+                }
 
-        for mut repo in repositories {
-            print!("Checking {}: ", repo.name.bright_yellow());
+                let mut op = journal.reopen(entry.clone());
+                if !entry.has_step(steps::DB_RECORD) {
+                    self.create_sync_directories(&entry.repo_name, &entry.worktree_path)
+                        .await?;
+                    let trunk_path = match self.db.get_repository(&entry.repo_name).await? {
+                        Some(repo) => PathBuf::from(repo.path),
+                        None => self.config.get_trunk_path(&entry.repo_name),
+                    };
+                    self.copy_from_trunk(
+                        &trunk_path,
+                        &entry.repo_name,
+                        &entry.worktree_name,
+                        &entry.worktree_type,
+                        &entry.branch_name,
+                        &entry.worktree_path,
+                    )
+                    .await?;
+                    self.generate_editor_workspace(
+                        &entry.repo_name,
+                        &entry.worktree_name,
+                        &entry.worktree_type,
+                        &entry.branch_name,
+                        &entry.worktree_path,
+                    );
+                    self.create_symlinks(&entry.repo_name, &entry.worktree_path)
+                        .await?;
+                    self.db
+                        .create_worktree(
+                            &entry.repo_name,
+                            &entry.worktree_name,
+                            &entry.branch_name,
+                            &entry.worktree_type,
+                            &entry.worktree_path.to_string_lossy(),
+                            None,
+                        )
+                        .await?;
+                    op.step(steps::DB_RECORD)?;
+                }
 
-            match self.validate_and_repair_repository_path(&mut repo).await {
-                Ok(true) => {
-                    println!("{}", "✓ Repaired".bright_green());
-                    repaired_count += 1;
+                self.register_local_context(
+                    &entry.repo_name,
+                    &entry.worktree_name,
+                    &entry.worktree_type,
+                    &entry.worktree_path,
+                )
+                .await?;
+                op.finish()
+            }
+            OperationKind::Merge => {
+                // Pushing, closing and deleting the branch would lose work
+                // that never made it into trunk
+                if !entry.has_step(steps::MERGED) {
+                    return Err(anyhow::anyhow!(tr!(
+                        "The merge of '{}' stopped before the merge commit; run 'imi rollback' and merge again",
+                        entry.worktree_name
+                    )));
                 }
-                Ok(false) => {
-                    println!("{}", "✓ OK".bright_green());
+                let mut op = journal.reopen(entry.clone());
+                let trunk_path = self.get_trunk_worktree(Some(&entry.repo_name)).await?;
+                let trunk_repo = self.git.find_repository(Some(&trunk_path))?;
+
+                if !entry.has_step(steps::PUSHED) {
+                    // Entries from before trunk_branch was journaled fall
+                    // back to the repository's registered default branch
+                    let trunk_branch = match &entry.trunk_branch {
+                        Some(branch) => branch.clone(),
+                        None => match self.db.get_repository(&entry.repo_name).await? {
+                            Some(repo) => repo.default_branch,
+                            None => self.config.git_settings.default_branch.clone(),
+                        },
+                    };
+                    match self.git.push_to_remote(&trunk_repo, &trunk_branch) {
+                        Ok(_) => op.step(steps::PUSHED)?,
+                        Err(e) => eprintln!(
+                            "{}",
+                            tr!(
+                                "{} Warning: Failed to push to remote: {}",
+                                "⚠️".bright_yellow(),
+                                e
+                            )
+                        ),
+                    }
                 }
-                Err(e) => {
-                    println!("{}", format!("✗ Error: {}", e).bright_red());
-                    error_count += 1;
+
+                if !entry.has_step(steps::CLOSED) {
+                    self.close_worktree(&entry.worktree_name, Some(&entry.repo_name))
+                        .await?;
+                    op.step(steps::CLOSED)?;
                 }
+
+                self.delete_merged_branch(&trunk_repo, &entry.branch_name)
+                    .await?;
+                op.finish()
             }
         }
+    }
 
-        println!();
-        println!(
-            "Summary: {} repaired, {} errors, {} total",
-            repaired_count, error_count, total_count
-        );
-
-        if error_count > 0 {
-            Err(anyhow::anyhow!(
-                "{} repositories could not be repaired",
-                error_count
-            ))
-        } else {
-            Ok(())
+    /// Ctrl-C arrived mid-add: remove the half-created worktree rather than
+    /// leave it for `imi resume`
+    async fn abandon_add(&self, op: OperationHandle) -> anyhow::Error {
+        let entry = op.into_entry();
+        self.report(tr!(
+            "{} Removing the half-created worktree {}",
+            "↩️".bright_yellow(),
+            entry.worktree_name
+        ));
+        match self.rollback_operation(entry).await {
+            Ok(()) => ImiError::Cancelled.into(),
+            Err(e) => e.context(tr!(
+                "Cancelled, but removing the worktree failed; run 'imi rollback'"
+            )),
         }
     }
 
-    /// Validate and repair repository path if it's stale
-    /// Searches through IMI_SYSTEM_PATHS to find the actual location
-    /// Also repairs all worktree paths when the repository is moved
-    async fn validate_and_repair_repository_path(&self, repo: &mut Repository) -> Result<bool> {
-        use std::path::PathBuf;
+    /// Undo the completed steps of an interrupted add or (unpushed) merge
+    pub async fn rollback_operation(&self, entry: JournalEntry) -> Result<()> {
+        let journal = Journal::open_default()?;
+
+        match entry.kind {
+            OperationKind::Add => {
+                if entry.has_step(steps::GIT_WORKTREE) {
+                    if entry.worktree_path.exists() {
+                        async_fs::remove_dir_all(&entry.worktree_path)
+                            .await
+                            .context(tr!("Failed to remove worktree directory"))?;
+                    }
 
-        let stored_path = PathBuf::from(&repo.path);
+                    let trunk_path = self.get_trunk_worktree(Some(&entry.repo_name)).await?;
+                    let git_repo = self.git.find_repository(Some(&trunk_path))?;
+                    if self.git.worktree_exists(&git_repo, &entry.worktree_name) {
+                        self.git.remove_worktree(&git_repo, &entry.worktree_name)?;
+                    }
+                }
 
-        // If path exists, no repair needed for repository
-        // But we still need to check if worktrees need repair
-        if stored_path.exists() {
-            // Check and repair worktree paths even if repository path is ok
-            let worktrees_repaired = self.repair_worktree_paths(&repo.name).await?;
-            return Ok(worktrees_repaired > 0);
+                if entry.has_step(steps::DB_RECORD) {
+                    self.db
+                        .deactivate_worktree(&entry.repo_name, &entry.worktree_name)
+                        .await?;
+                }
+
+                // The entry may never have been written; a missing entry is fine
+                let project_root = self.detect_imi_path(&entry.worktree_path)?;
+                let _ = LocalContext::new(&project_root).unregister_worktree(&entry.worktree_name);
+            }
+            OperationKind::Merge => {
+                if entry.has_step(steps::PUSHED) {
+                    return Err(anyhow::anyhow!(tr!(
+                        "The merge of '{}' was already pushed; run 'imi resume' to finish cleanup instead",
+                        entry.worktree_name
+                    )));
+                }
+                if entry.has_step(steps::CLOSED) {
+                    return Err(anyhow::anyhow!(tr!(
+                        "Worktree '{}' was already closed; run 'imi resume' to finish the merge instead",
+                        entry.worktree_name
+                    )));
+                }
+
+                let trunk_head = entry.trunk_head_before.as_deref().ok_or_else(|| {
+                    anyhow::anyhow!(tr!("Journal entry has no recorded trunk HEAD to restore"))
+                })?;
+                let trunk_path = self.get_trunk_worktree(Some(&entry.repo_name)).await?;
+                self.git
+                    .execute_git_command(&trunk_path, &["reset", "--hard", trunk_head])
+                    .context(tr!("Failed to restore trunk to its pre-merge commit"))?;
+            }
         }
 
-        // Path doesn't exist - search for actual location
-        eprintln!(
-            "⚠️  Repository '{}' path is stale: {}",
-            repo.name,
-            stored_path.display()
-        );
-        eprintln!("   Searching for actual location...");
+        journal.discard(&entry.id)
+    }
 
-        // Extract the repository name from the stored path
-        // Path format: /path/to/system-root/repo-name/trunk-main
-        let repo_name_from_path = if stored_path
-            .file_name()
-            .and_then(|n| n.to_str())
-            .map(|s| s.starts_with("trunk-"))
-            .unwrap_or(false)
-        {
-            // Path ends with trunk-* - repo name is parent
-            stored_path
-                .parent()
-                .and_then(|p| p.file_name())
-                .and_then(|n| n.to_str())
-        } else {
-            // Use the registered name
-            Some(repo.name.as_str())
-        };
+    /// Get a worktree by name (public wrapper around find_worktree_in_database)
+    pub async fn get_worktree_by_name(
+        &self,
+        name: &str,
+        repo: Option<&str>,
+    ) -> Result<Option<crate::database::Worktree>> {
+        self.find_worktree_in_database(name, repo).await
+    }
 
-        if let Some(repo_name) = repo_name_from_path {
-            // Search through all system roots
-            for system_root in &self.config.system_roots {
-                // Try: system_root/repo_name/trunk-main
-                let candidate = system_root
-                    .join(repo_name)
-                    .join(format!("trunk-{}", repo.default_branch));
+    /// Claim `worktree` for `agent_id`: recorded in the database, in the
+    /// presence lock file `lock_name.lock` and in the agent's activity log
+    pub async fn claim_worktree(
+        &self,
+        worktree: &crate::database::Worktree,
+        lock_name: &str,
+        agent_id: &str,
+    ) -> Result<()> {
+        self.db.claim_worktree(&worktree.id, agent_id).await?;
 
-                if candidate.exists() {
-                    eprintln!("   ✓ Found repository at: {}", candidate.display());
+        let repo_root = self.detect_imi_path(Path::new(&worktree.path))?;
+        LocalContext::new(&repo_root)
+            .create_lock_file(&repo_root.join(".iMi"), lock_name, agent_id)
+            .await?;
 
-                    // Update database with correct path
-                    let new_path = candidate.to_string_lossy().to_string();
-                    self.db
-                        .update_repository_path(&repo.name, &new_path)
-                        .await?;
+        self.db
+            .log_agent_activity(
+                agent_id,
+                &worktree.id,
+                "claimed",
+                None,
+                "Agent claimed worktree",
+            )
+            .await?;
+        Ok(())
+    }
+}
 
-                    // Update the in-memory struct
-                    repo.path = new_path;
+/// A cached status still applies when nothing it was computed from moved and
+/// it isn't older than `max_age_secs`; unstaged edits don't touch the index,
+/// so the age bounds how long they can go unnoticed without the daemon
+fn cache_is_fresh(entry: &CachedWorktreeStatus, key: &StatusCacheKey, max_age_secs: u64) -> bool {
+    let age = chrono::Utc::now().signed_duration_since(entry.computed_at);
+    entry.head_sha == key.head_sha
+        && entry.index_mtime_ns == key.index_mtime_ns
+        && entry.upstream_sha == key.upstream_sha
+        && age < chrono::Duration::seconds(max_age_secs as i64)
+}
 
-                    eprintln!("   ✓ Database updated with corrected path");
+/// Merging holds libgit2 handles across awaits, so train steps aren't `Send`
+type StepFuture<'a, T> = std::pin::Pin<Box<dyn std::future::Future<Output = Result<T>> + 'a>>;
 
-                    // Repair all worktree paths for this repository
-                    let worktrees_repaired = self.repair_worktree_paths(&repo.name).await?;
-                    if worktrees_repaired > 0 {
-                        eprintln!("   ✓ Repaired {} worktree path(s)", worktrees_repaired);
-                    }
+/// What a merge train does to each worktree, split from the driver so its
+/// ordering and stop rules can be exercised without a registry
+trait TrainSteps {
+    /// Announce the `index`th worktree and check it may be merged locally
+    fn start<'a>(&'a self, index: usize, name: &'a str) -> StepFuture<'a, ()>;
 
-                    return Ok(true);
-                }
+    /// Rebase onto the trunk the earlier merges updated; `Some` says why
+    /// the rebase stopped
+    fn rebase<'a>(&'a self, index: usize, name: &'a str) -> StepFuture<'a, Option<String>>;
+
+    fn merge<'a>(&'a self, name: &'a str) -> StepFuture<'a, ()>;
+}
+
+/// Merge `names` in order, rebasing every one after the first; returns the
+/// worktrees merged
+async fn run_train(steps: &dyn TrainSteps, names: &[String]) -> Result<Vec<String>> {
+    let mut merged = Vec::new();
+    for (i, name) in names.iter().enumerate() {
+        let remaining = &names[i..];
+        steps.start(i, name).await?;
+
+        if i > 0 {
+            if let Some(reason) = steps.rebase(i, name).await? {
+                return Err(train_stopped(&merged, remaining, &reason));
             }
         }
 
-        // Could not find the repository anywhere
-        Err(anyhow::anyhow!(
-            "Repository '{}' not found at stored path '{}' or any IMI_SYSTEM_PATHS location.\n\
-             Please verify the repository exists or run 'imi init' to re-register it.",
-            repo.name,
-            stored_path.display()
-        ))
+        if let Err(e) = steps.merge(name).await {
+            return Err(train_stopped(&merged, remaining, &format!("{:#}", e)));
+        }
+        merged.push(name.clone());
     }
 
-    /// Repair all worktree paths for a repository
-    /// Returns the number of worktrees that were repaired
-    async fn repair_worktree_paths(&self, repo_name: &str) -> Result<usize> {
-        use std::path::PathBuf;
+    Ok(merged)
+}
 
-        let worktrees = self.db.list_worktrees(Some(repo_name)).await?;
-        let mut repaired_count = 0;
+/// A merge train through this machine's trunk worktrees
+struct LocalTrain<'a> {
+    manager: &'a WorktreeManager,
+    repo: Option<&'a str>,
+    resolve_conflicts: bool,
+    total: usize,
+}
 
-        for worktree in worktrees {
-            let stored_path = PathBuf::from(&worktree.path);
+impl TrainSteps for LocalTrain<'_> {
+    fn start<'a>(&'a self, index: usize, name: &'a str) -> StepFuture<'a, ()> {
+        Box::pin(async move {
+            let manager = self.manager;
+            manager.report("");
+            manager.step(
+                "train",
+                progress::percent(index, self.total),
+                "🚂".bright_cyan(),
+                format_args!("[{}/{}] {}", index + 1, self.total, name.bright_yellow()),
+            );
 
-            // If worktree path exists, skip
-            if stored_path.exists() {
-                continue;
+            let repo_name = manager.resolve_worktree_repo(name, self.repo).await?;
+            let strategy = manager.config.merge_policy.strategy_for(&repo_name);
+            if strategy != MergeStrategy::Direct {
+                return Err(anyhow::anyhow!(tr!(
+                    "Merge trains merge locally, but {}'s merge policy is '{}'",
+                    repo_name,
+                    format!("{:?}", strategy).to_lowercase()
+                )));
             }
+            Ok(())
+        })
+    }
 
-            // Worktree path doesn't exist - try to find it
-            // First, get the current (repaired) repository path
-            let repo = self.db.get_repository(repo_name).await?;
-            if let Some(repo) = repo {
-                let repo_path = PathBuf::from(&repo.path);
-                // Get the parent directory (IMI_PATH) which contains all worktrees
-                if let Some(imi_path) = repo_path.parent() {
-                    // Try to find the worktree at the expected location
-                    let candidate = imi_path.join(&worktree.worktree_name);
-
-                    if candidate.exists() {
-                        // Found the worktree, update its path
-                        let new_path = candidate.to_string_lossy().to_string();
-                        self.db
-                            .update_worktree_path(repo_name, &worktree.worktree_name, &new_path)
-                            .await?;
-                        repaired_count += 1;
-                        eprintln!(
-                            "   ✓ Repaired worktree '{}' path: {}",
-                            worktree.worktree_name, new_path
-                        );
-                    }
+    fn rebase<'a>(&'a self, index: usize, name: &'a str) -> StepFuture<'a, Option<String>> {
+        Box::pin(async move {
+            let manager = self.manager;
+            let default_branch = &manager.config.git_settings.default_branch;
+            let repo_name = manager.resolve_worktree_repo(name, self.repo).await?;
+            let actual_name = manager.find_actual_worktree_name(name, &repo_name).await?;
+            let worktree = manager
+                .db
+                .get_worktree(&repo_name, &actual_name)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!(tr!("Worktree '{}' not found", actual_name)))?;
+            let path = PathBuf::from(&worktree.path);
+            manager.step(
+                "rebase",
+                progress::percent(index, self.total),
+                "🔁".bright_blue(),
+                tr!("Rebasing onto the updated '{}'", default_branch),
+            );
+            Ok(match manager.git.rebase_onto(&path, default_branch)? {
+                RebaseOutcome::Rebased | RebaseOutcome::UpToDate => None,
+                RebaseOutcome::Conflicted(files) => Some(format!(
+                    "rebasing {} stopped on conflicts in {}; resolve them in {} and run 'git rebase --continue'",
+                    name,
+                    files.join(", "),
+                    path.display()
+                )),
+                RebaseOutcome::Stopped(message) | RebaseOutcome::Failed(message) => {
+                    Some(format!("rebasing {} failed: {}", name, message))
                 }
-            }
-        }
+            })
+        })
+    }
 
-        Ok(repaired_count)
+    fn merge<'a>(&'a self, name: &'a str) -> StepFuture<'a, ()> {
+        Box::pin(self.manager.merge_worktree(
+            name,
+            self.repo,
+            Some(MergeStrategy::Direct),
+            self.resolve_conflicts,
+        ))
     }
+}
 
-    /// Merge a worktree into trunk-main and close it
-    pub async fn merge_worktree(&self, name: &str, repo: Option<&str>) -> Result<()> {
-        let repo_name = self.resolve_repo_name(repo).await?;
-        let actual_worktree_name = self.find_actual_worktree_name(name, &repo_name).await?;
+/// Why a merge train stopped, what already landed and how to carry on
+fn train_stopped(merged: &[String], remaining: &[String], reason: &str) -> anyhow::Error {
+    let landed = match merged.is_empty() {
+        true => "nothing merged yet".to_string(),
+        false => format!("merged {}", merged.join(", ")),
+    };
+    anyhow::anyhow!(tr!(
+        "Merge train stopped ({}): {}. Once fixed, resume with: imi merge --train {}",
+        landed,
+        reason,
+        remaining.join(" ")
+    ))
+}
 
-        println!(
-            "{} Merging worktree: {}",
-            "🔀".bright_cyan(),
-            actual_worktree_name.bright_yellow()
-        );
+/// Editor for resolving merge conflicts: `$VISUAL`, `$EDITOR`, or the
+/// configured editor told to block until its windows are closed
+fn conflict_editor(config: &Config) -> String {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| env::var(var).ok())
+        .find(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| format!("{} --wait", config.editor.command()))
+}
 
-        let worktree_info = self
-            .db
-            .get_worktree(&repo_name, &actual_worktree_name)
-            .await?
-            .ok_or_else(|| anyhow::anyhow!("Worktree '{}' not found", actual_worktree_name))?;
+/// ` ⏳ idle 21d` after the name of a stale worktree
+fn stale_marker(idle_days: Option<i64>) -> String {
+    idle_days
+        .map(|days| {
+            format!(
+                " {} {}",
+                "⏳".bright_yellow(),
+                format!("idle {}d", days).yellow()
+            )
+        })
+        .unwrap_or_default()
+}
 
-        let branch_name = worktree_info.branch_name.clone();
-        let trunk_path = self.get_trunk_worktree(repo).await?;
+fn unread_marker(unread: Option<i64>) -> String {
+    unread
+        .map(|count| {
+            format!(
+                " {} {}",
+                "✉️".bright_magenta(),
+                format!("{} unread", count).magenta()
+            )
+        })
+        .unwrap_or_default()
+}
 
-        println!(
-            "{} Switching to trunk: {}",
-            "🌳".bright_green(),
-            trunk_path.display()
-        );
+/// Replace `{{name}}` / `{{ name }}` placeholders; unknown placeholders are left as-is
+pub(crate) fn render_copy_template(contents: &str, vars: &[(&str, &str)]) -> String {
+    vars.iter()
+        .fold(contents.to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{{{}}}}}", name), value)
+                .replace(&format!("{{{{ {} }}}}", name), value)
+        })
+}
 
-        let trunk_repo = self.git.find_repository(Some(&trunk_path))?;
-        println!("{} Fetching latest changes", "⬇️".bright_blue());
-        self.git.fetch_all(&trunk_repo)?;
+/// Worktrees whose last commit is older than this count as stale
+const STALE_WORKTREE_DAYS: i64 = 30;
 
-        let default_branch = self.config.git_settings.default_branch.clone();
-        let current_branch = self.git.get_current_branch(&trunk_path)?;
+/// Commits listed by `imi show`
+const RECENT_COMMITS: usize = 5;
 
-        if current_branch != default_branch {
-            return Err(anyhow::anyhow!(
-                "Trunk is on branch '{}' instead of '{}'. Please checkout '{}' first.",
-                current_branch,
-                default_branch,
-                default_branch
-            ));
-        }
+/// Result of `imi agent scaffold`
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct AgentScaffold {
+    pub repo_name: String,
+    pub files: Vec<ScaffoldedFile>,
+    /// Trunk and worktrees the files were linked into
+    pub worktrees: Vec<PathBuf>,
+}
 
-        let worktree_path = PathBuf::from(&worktree_info.path);
-        if worktree_path.exists() {
-            println!(
-                "{} Discarding any unstaged changes in worktree",
-                "🧹".bright_yellow()
-            );
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ScaffoldedFile {
+    pub path: PathBuf,
+    pub outcome: FileOutcome,
+}
 
-            // Reset worktree to HEAD, discarding all unstaged changes
-            std::process::Command::new("git")
-                .arg("reset")
-                .arg("--hard")
-                .arg("HEAD")
-                .current_dir(&worktree_path)
-                .output()
-                .context("Failed to reset worktree to HEAD")?;
-        }
+/// Result of `imi bisect`
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct BisectOutcome {
+    pub repo_name: String,
+    /// The first commit where the command fails
+    pub culprit: CommitSummary,
+    /// The temporary worktree, when kept with `--keep`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kept_path: Option<PathBuf>,
+}
 
-        println!(
-            "{} Merging branch '{}' into '{}'",
-            "🔀".bright_magenta(),
-            branch_name.bright_yellow(),
-            default_branch.bright_green()
-        );
+/// Result of `imi temp`
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TempRun {
+    pub repo_name: String,
+    pub path: PathBuf,
+    /// Commit the worktree was checked out at
+    pub commit: String,
+    /// The command's exit status; 128 + the signal when one killed it
+    pub exit_code: i32,
+    /// False when removing the worktree failed and it was left behind
+    pub removed: bool,
+}
 
-        self.git
-            .merge_branch(&trunk_repo, &branch_name, &default_branch)
-            .context("Failed to merge branch into trunk")?;
+/// A worktree `imi mv --relayout` moved, or would move with `--dry-run`
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Relocation {
+    pub repo_name: String,
+    pub worktree_name: String,
+    #[serde(skip)]
+    trunk_path: PathBuf,
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
 
-        println!("{} Pushing merged changes to remote", "⬆️".bright_cyan());
+/// Everything `imi repo show` reports about one repository
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RepositoryDetail {
+    pub repository: Repository,
+    pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clone_mode: Option<String>,
+    /// Set while the repository is archived (`imi repo archive`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archived_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Active worktrees
+    pub worktrees: usize,
+    /// Where the recorded metadata and the trunk on disk disagree
+    pub problems: Vec<String>,
+}
 
-        match self.git.push_to_remote(&trunk_repo, &default_branch) {
-            Ok(_) => println!("{} Changes pushed to remote", "✅".bright_green()),
-            Err(e) => {
-                println!(
-                    "{} Warning: Failed to push to remote: {}",
-                    "⚠️".bright_yellow(),
-                    e
-                );
-                println!(
-                    "   You may need to push manually: cd {} && git push",
-                    trunk_path.display()
-                );
-            }
+/// Result of `imi repo set`
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RepositoryEditOutcome {
+    pub repository: Repository,
+    /// What changed, for display; empty when the values were already set
+    pub changes: Vec<String>,
+    pub worktrees_repaired: usize,
+}
+
+/// Everything `imi show` reports about one worktree
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct WorktreeDetail {
+    pub worktree: Worktree,
+    /// None when the worktree directory is missing
+    pub status: Option<WorktreeStatus>,
+    /// Divergence from the ref the branch was created from (or trunk)
+    pub base: Option<Divergence>,
+    pub upstream: Option<Divergence>,
+    pub disk_bytes: Option<u64>,
+    pub recent_commits: Vec<CommitSummary>,
+    pub notes: Vec<WorktreeNote>,
+    /// Event hooks that fire for this worktree's repository
+    pub hooks: Vec<EventHook>,
+    pub symlinks: Vec<SymlinkHealth>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SymlinkState {
+    Linked,
+    /// The shared file exists but the worktree has no link to it
+    Missing,
+    /// A link whose target is gone
+    Broken,
+    /// A regular file where the link should be
+    NotALink,
+}
+
+/// One `symlink_files` entry in a worktree
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SymlinkHealth {
+    pub file: String,
+    pub state: SymlinkState,
+}
+
+/// Health indicators for a registered repository (`imi list --health`)
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RepoHealth {
+    pub trunk_exists: bool,
+    /// None when trunk or its remote-tracking branch can't be read
+    pub behind_origin: Option<usize>,
+    pub dirty_worktrees: usize,
+    pub stale_worktrees: usize,
+    pub last_fetch: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl RepoHealth {
+    /// Inspect trunk and worktrees on disk; blocking, so run it off the async runtime
+    fn compute(
+        git: &GitManager,
+        trunk_path: &Path,
+        default_branch: &str,
+        worktree_paths: &[PathBuf],
+    ) -> Self {
+        let trunk_exists = trunk_path.exists();
+        let stale_cutoff = chrono::Utc::now() - chrono::Duration::days(STALE_WORKTREE_DAYS);
+
+        let existing: Vec<&PathBuf> = worktree_paths.iter().filter(|p| p.exists()).collect();
+        let dirty_worktrees = existing
+            .iter()
+            .filter(|p| {
+                git.get_worktree_status(p)
+                    .map(|status| !status.clean)
+                    .unwrap_or(false)
+            })
+            .count();
+        let stale_worktrees = existing
+            .iter()
+            .filter(|p| {
+                git.get_head_commit_time(p)
+                    .map(|time| time < stale_cutoff)
+                    .unwrap_or(false)
+            })
+            .count();
+
+        Self {
+            trunk_exists,
+            behind_origin: trunk_exists
+                .then(|| git.get_behind_origin(trunk_path, default_branch).ok())
+                .flatten(),
+            dirty_worktrees,
+            stale_worktrees,
+            last_fetch: trunk_exists
+                .then(|| git.get_last_fetch_time(trunk_path))
+                .flatten(),
         }
+    }
 
+    fn print(&self) {
+        let trunk = if self.trunk_exists {
+            "present".bright_green()
+        } else {
+            "missing".bright_red()
+        };
+        let behind = match self.behind_origin {
+            Some(0) => "up to date".bright_green(),
+            Some(n) => format!("{} behind origin", n).bright_yellow(),
+            None => "unknown".bright_black(),
+        };
         println!(
-            "{} Closing worktree: {}",
-            "🧹".bright_cyan(),
-            actual_worktree_name
+            "{}",
+            tr!("   {} Trunk: {} ({})", "🩺".bright_cyan(), trunk, behind)
         );
-        self.close_worktree(name, repo).await?;
 
+        let count = |n: usize| {
+            if n == 0 {
+                n.to_string().bright_green()
+            } else {
+                n.to_string().bright_yellow()
+            }
+        };
         println!(
-            "{} Deleting merged branch: {}",
-            "🗑️".bright_red(),
-            branch_name
+            "{}",
+            tr!(
+                "   {} Dirty worktrees: {}  Stale (>{}d): {}",
+                "🧹".bright_cyan(),
+                count(self.dirty_worktrees),
+                STALE_WORKTREE_DAYS,
+                count(self.stale_worktrees)
+            )
         );
-        self.git.delete_local_branch(&trunk_repo, &branch_name)?;
 
-        match self
-            .git
-            .delete_remote_branch(&trunk_repo, &branch_name)
-            .await
-        {
-            Ok(_) => println!("{} Remote branch deleted", "✅".bright_green()),
-            Err(e) => {
-                println!(
-                    "{} Warning: Could not delete remote branch '{}': {}",
-                    "⚠️".bright_yellow(),
-                    branch_name,
-                    e
-                );
-                println!("   (This is normal if the branch was already deleted or never pushed)");
+        let last_fetch = match self.last_fetch {
+            Some(at) => {
+                let hours = (chrono::Utc::now() - at).num_hours();
+                let text = at
+                    .with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string();
+                if hours > 24 {
+                    text.bright_yellow()
+                } else {
+                    text.bright_green()
+                }
             }
-        }
+            None => "never".bright_red(),
+        };
+        println!(
+            "{}",
+            tr!("   {} Last fetch: {}", "⬇️".bright_cyan(), last_fetch)
+        );
+    }
+}
+
+/// The CI result line shown by `imi status --ci`
+fn print_ci_status(status: &CiStatus) {
+    let (icon, state) = match status.state {
+        CiState::Success => ("✅", "passing".bright_green()),
+        CiState::Failure => ("❌", "failing".bright_red()),
+        CiState::InProgress => ("⏳", "running".bright_yellow()),
+        CiState::Cancelled => ("⏹️", "cancelled".bright_black()),
+    };
+    let job = status
+        .failing_job
+        .as_ref()
+        .map(|job| format!(" in {}", job.bright_red()))
+        .unwrap_or_default();
+    println!(
+        "{}",
+        tr!(
+            "   {} CI {} ({}{}) {}",
+            icon,
+            state,
+            status.workflow,
+            job,
+            status.url.bright_white()
+        )
+    );
+}
 
+/// Notes newest first, each with when and by whom
+pub fn print_notes(notes: &[WorktreeNote]) {
+    for note in notes {
+        let by = match (&note.author, &note.agent_id) {
+            (Some(author), Some(agent)) => format!("{} via {}", author, agent),
+            (None, Some(agent)) => agent.clone(),
+            (Some(author), None) => author.clone(),
+            (None, None) => String::new(),
+        };
         println!(
-            "\n{} Merge completed successfully!",
-            "✅".bright_green().bold()
+            "   {} {} {}",
+            note.created_at
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+                .bright_black(),
+            by.bright_magenta(),
+            note.body
         );
+    }
+}
+
+/// One line per message, unread ones highlighted, for `imi msg read`
+pub fn print_messages(messages: &[WorktreeMessage]) {
+    for message in messages {
+        let from = match (&message.sender, &message.agent_id) {
+            (Some(sender), Some(agent)) => format!("{} via {}", sender, agent),
+            (None, Some(agent)) => agent.clone(),
+            (Some(sender), None) => sender.clone(),
+            (None, None) => String::new(),
+        };
+        let marker = match message.read_at {
+            Some(_) => " ",
+            None => "●",
+        };
         println!(
-            "{} Branch '{}' has been merged into '{}' and cleaned up",
-            "📝".bright_blue(),
-            branch_name.bright_yellow(),
-            default_branch.bright_green()
+            "   {} {} {} {}",
+            marker.bright_magenta(),
+            message
+                .created_at
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+                .bright_black(),
+            from.bright_magenta(),
+            message.body
         );
+    }
+}
 
-        Ok(())
+/// One line per `imi check` or `imi exec` run, for `imi artifacts list`
+pub fn print_artifacts(artifacts: &[WorktreeArtifact]) {
+    for artifact in artifacts {
+        let result = match (artifact.success, artifact.exit_code) {
+            (true, _) => "✅ passed".bright_green(),
+            (false, Some(code)) => format!("❌ exit {}", code).bright_red(),
+            (false, None) => "❌ no exit code".bright_red(),
+        };
+        let short_id = artifact.id.to_string();
+        println!(
+            "   {} {} {} {} {}",
+            short_id[..8].bright_white(),
+            artifact
+                .created_at
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+                .bright_black(),
+            result,
+            artifact.command,
+            format!(
+                "({}, {:.1}s)",
+                artifact.kind,
+                artifact.duration_ms as f64 / 1000.0
+            )
+            .bright_black()
+        );
     }
+}
 
-    /// Get a worktree by name (public wrapper around find_worktree_in_database)
-    pub async fn get_worktree_by_name(
-        &self,
-        name: &str,
-        repo: Option<&str>,
-    ) -> Result<Option<crate::database::Worktree>> {
-        self.find_worktree_in_database(name, repo).await
+/// The newest `imi note` line shown by `imi status` and `imi list`
+fn print_latest_note(note: &WorktreeNote) {
+    let by = note
+        .agent_id
+        .as_deref()
+        .or(note.author.as_deref())
+        .map(|by| format!(" ({})", by))
+        .unwrap_or_default();
+    println!(
+        "{}",
+        tr!(
+            "   {} Note: {} {}{}",
+            "🗒️".bright_cyan(),
+            note.body.bright_white(),
+            note.created_at
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+                .bright_black(),
+            by.bright_black()
+        )
+    );
+}
+
+/// The recorded pull request line shown by `imi status` and `imi list`
+fn print_pull_request(worktree: &Worktree) {
+    let (Some(number), Some(url)) = (worktree.pr_number, &worktree.pr_url) else {
+        return;
+    };
+    let state = match worktree.pr_state.as_deref() {
+        Some("merged") => "merged".bright_magenta(),
+        Some("closed") => "closed".bright_red(),
+        _ => "open".bright_green(),
+    };
+    println!(
+        "{}",
+        tr!(
+            "   {} PR #{} ({}) {}",
+            "🔀".bright_cyan(),
+            number,
+            state,
+            url.bright_white()
+        )
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Records each step; rebasing or merging a listed worktree fails
+    #[derive(Default)]
+    struct ScriptedTrain {
+        calls: Mutex<Vec<String>>,
+        rebase_conflicts: Vec<&'static str>,
+        merge_failures: Vec<&'static str>,
+    }
+
+    impl ScriptedTrain {
+        fn record(&self, step: &str, name: &str) {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("{} {}", step, name));
+        }
+    }
+
+    impl TrainSteps for ScriptedTrain {
+        fn start<'a>(&'a self, _index: usize, name: &'a str) -> StepFuture<'a, ()> {
+            self.record("start", name);
+            Box::pin(async { Ok(()) })
+        }
+
+        fn rebase<'a>(&'a self, _index: usize, name: &'a str) -> StepFuture<'a, Option<String>> {
+            self.record("rebase", name);
+            let stopped = self
+                .rebase_conflicts
+                .contains(&name)
+                .then(|| format!("rebasing {} stopped on conflicts in src/lib.rs", name));
+            Box::pin(async move { Ok(stopped) })
+        }
+
+        fn merge<'a>(&'a self, name: &'a str) -> StepFuture<'a, ()> {
+            self.record("merge", name);
+            let failed = self.merge_failures.contains(&name);
+            Box::pin(async move {
+                match failed {
+                    true => Err(anyhow::anyhow!("trunk has uncommitted changes")),
+                    false => Ok(()),
+                }
+            })
+        }
+    }
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn merge_train_rebases_each_worktree_after_the_first_in_order() {
+        let train = ScriptedTrain::default();
+        let merged = run_train(&train, &names(&["feat-a", "feat-b", "feat-c"]))
+            .await
+            .unwrap();
+
+        assert_eq!(merged, names(&["feat-a", "feat-b", "feat-c"]));
+        assert_eq!(
+            *train.calls.lock().unwrap(),
+            [
+                "start feat-a",
+                "merge feat-a",
+                "start feat-b",
+                "rebase feat-b",
+                "merge feat-b",
+                "start feat-c",
+                "rebase feat-c",
+                "merge feat-c",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn merge_train_stops_with_the_command_that_resumes_it() {
+        let train = ScriptedTrain {
+            rebase_conflicts: vec!["feat-b"],
+            ..Default::default()
+        };
+        let err = run_train(&train, &names(&["feat-a", "feat-b", "feat-c"]))
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("merged feat-a"), "{}", err);
+        assert!(err.contains("conflicts in src/lib.rs"), "{}", err);
+        assert!(err.ends_with("imi merge --train feat-b feat-c"), "{}", err);
+        assert_eq!(train.calls.lock().unwrap().last().unwrap(), "rebase feat-b");
+
+        let train = ScriptedTrain {
+            merge_failures: vec!["feat-a"],
+            ..Default::default()
+        };
+        let err = run_train(&train, &names(&["feat-a", "feat-b"]))
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("nothing merged yet"), "{}", err);
+        assert!(err.contains("trunk has uncommitted changes"), "{}", err);
+        assert!(err.ends_with("imi merge --train feat-a feat-b"), "{}", err);
     }
 }
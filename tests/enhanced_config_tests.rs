@@ -391,6 +391,8 @@ database_path = "/tmp/test.db"
             enabled: true,
             user_sync_path: PathBuf::from("sync/global"),
             local_sync_path: PathBuf::from("sync/repo"),
+            scaffold_dir: None,
+            repos: Default::default(),
         };
 
         assert!(sync_settings.enabled);
@@ -406,6 +408,8 @@ database_path = "/tmp/test.db"
             remote_name: "origin".to_string(),
             auto_fetch: true,
             prune_on_fetch: true,
+            ssh_key_paths: Vec::new(),
+            github_auth: Default::default(),
         };
 
         assert_eq!(git_settings.default_branch, "main");
@@ -422,6 +426,7 @@ database_path = "/tmp/test.db"
             refresh_interval_ms: 1000,
             watch_file_changes: true,
             track_agent_activity: true,
+            ..Default::default()
         };
 
         assert!(monitoring_settings.enabled);
@@ -18,7 +18,7 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use tempfile::TempDir;
 
-use imi::git::{GitManager, WorktreeStatus};
+use imi::git::{BranchBase, GitManager, WorktreeStatus};
 
 /// Test helper for git operations
 struct GitTestHelper {
@@ -393,7 +393,11 @@ mod worktree_operations_tests {
             "test-worktree",
             &worktree_path,
             "feature-branch",
-            Some("main"),
+            BranchBase {
+                branch: Some("main"),
+                remote: "origin",
+            },
+            false,
         );
 
         // May succeed or fail depending on fetch, but shouldn't panic
@@ -431,7 +435,11 @@ mod worktree_operations_tests {
             "existing-worktree",
             &worktree_path,
             "existing-branch",
-            None,
+            BranchBase {
+                branch: None,
+                remote: "origin",
+            },
+            true,
         );
 
         // Should work with existing branch
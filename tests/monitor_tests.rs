@@ -76,6 +76,10 @@ async fn create_test_worktree(db: &Database, name: &str, wt_type: &str, path: &P
         merged_at: None,
         merged_by: None,
         merge_commit_hash: None,
+        pr_number: None,
+        pr_url: None,
+        pr_state: None,
+        host: None,
 
         metadata: serde_json::Value::Object(serde_json::Map::new()),
         created_at: chrono::Utc::now(),
@@ -677,6 +681,10 @@ mod status_reporting_tests {
             merged_at: None,
             merged_by: None,
             merge_commit_hash: None,
+            pr_number: None,
+            pr_url: None,
+            pr_state: None,
+            host: None,
 
             metadata: serde_json::Value::Object(serde_json::Map::new()),
             created_at: chrono::Utc::now(),
@@ -784,6 +792,10 @@ mod error_handling_tests {
             merged_at: None,
             merged_by: None,
             merge_commit_hash: None,
+            pr_number: None,
+            pr_url: None,
+            pr_state: None,
+            host: None,
 
             metadata: serde_json::Value::Object(serde_json::Map::new()),
             created_at: chrono::Utc::now(),
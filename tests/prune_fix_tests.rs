@@ -198,7 +198,7 @@ impl PruneTestFixture {
     async fn run_prune(&self, dry_run: bool, force: bool) -> Result<()> {
         std::env::set_current_dir(&self.trunk_path)?;
         self.manager
-            .prune_stale_worktrees(None, dry_run, force)
+            .prune_stale_worktrees(None, dry_run, force, false)
             .await
     }
 }
@@ -292,6 +292,7 @@ impl<'a> RepositoryBuilder<'a> {
             created_at: now,
             updated_at: now,
             active: self.active,
+            host: None,
         };
 
         self.builder
@@ -377,6 +378,10 @@ impl<'a> WorktreeBuilder<'a> {
             merged_at: None,
             merged_by: None,
             merge_commit_hash: None,
+            pr_number: None,
+            pr_url: None,
+            pr_state: None,
+            host: None,
 
             metadata: serde_json::Value::Object(serde_json::Map::new()),
             created_at: now,
@@ -22,7 +22,7 @@ use std::path::PathBuf;
 use tempfile::TempDir;
 use std::fs;
 
-use imi::config::Config;
+use imi::config::{Config, MonitoringSettings};
 use imi::database::Database;
 use imi::git::GitManager;
 use imi::worktree::WorktreeManager;
@@ -1064,7 +1064,7 @@ mod monitoring_integration_tests {
         tokio::spawn(async move {
             let result = helper
                 .manager
-                .start_monitoring(Some("monitor-test-repo"))
+                .start_monitoring(Some("monitor-test-repo"), MonitoringSettings::default())
                 .await;
             match result {
                 Ok(_) => println!("Monitoring started successfully"),